@@ -1,4 +1,5 @@
 use crate::fs::{FileSystem, FileWatcher};
+use crate::fs::watcher::{ChangeDebouncer, FileChange, FileChangeKind};
 use crate::system::SystemManager;
 use crate::config::Config;
 use std::path::PathBuf;
@@ -27,12 +28,26 @@ pub enum Pane {
 pub enum DialogMode {
     None,
     Rename { current_name: String, new_name: String },
-    Delete { path_name: String },
+    BatchRename { paths: Vec<PathBuf>, find: String, replace: String, editing_replace: bool }, // 여러 항목 이름 일괄 변경 (정규식 찾기/바꾸기)
+    Delete { path_name: String, permanent: bool }, // permanent = skip the trash
     NewFile { name: String },
     NewFolder { name: String },
-    Search { query: String, results: Vec<(PathBuf, usize)> }, // (파일 경로, 디렉토리 내 인덱스)
+    Search { query: String, results: Vec<(PathBuf, i64)>, selected: usize }, // (파일 경로, 퍼지 점수)
+    Grep { query: String, hits: Vec<crate::fs::grep::GrepHit>, selected: usize }, // 파일 내용 검색
     Command { input: String }, // 명령어 모드 (:game, :help 등)
+    SftpConnect { target: String }, // SFTP 원격 접속 주소 입력 (user@host[:port])
     QuitConfirm, // 종료 확인 다이얼로그 (ESC)
+    TaskPicker { tasks: Vec<crate::tasks::Task>, selected: usize }, // 프로젝트 작업 선택 (npm/make/cargo/just)
+    TemplatePicker { templates: Vec<crate::fs::template::Template>, selected: usize }, // 템플릿 선택 (~/.config/senterm/templates)
+    TemplateName { template: crate::fs::template::Template, name: String }, // 템플릿으로 만들 파일/폴더 이름 입력
+    OpenWith { path: PathBuf, command: String }, // 외부 프로그램으로 열기
+    GoTo { input: String, candidates: Vec<String>, cycle: Option<usize>, error: Option<String> }, // 경로로 이동 (Tab 자동완성)
+    AddBookmark { path: PathBuf, name: String }, // 북마크 이름 입력 (비우면 디렉토리 이름)
+    TrustPlugin { name: String }, // 플러그인 신뢰 승격 확인 (샌드박스 해제)
+    ReloadFile, // 저장하지 않은 편집 내용을 버리고 디스크에서 다시 읽기 확인
+    Properties { path: PathBuf, rows: Vec<(&'static str, String)> }, // 파일 속성 (크기, 권한, 소유자, 시간 등)
+    Chmod { path: PathBuf, mode: u32, cursor: usize, octal: String, typed: bool, confirm: bool }, // 권한 편집 (rwx 토글 또는 8진수 입력, Unix 전용; typed: 8진수 칸을 직접 입력하기 시작함)
+    TextStats { name: String, stats: crate::viewer::stats::TextStats }, // 뷰어 텍스트 통계 (줄/단어/문자/바이트 수)
 }
 
 pub struct App {
@@ -48,11 +63,35 @@ pub struct App {
     pub system: SystemManager,
     pub config: Config,
     pub show_help: bool,
-    pub show_bookmarks: bool,
+    pub bookmark_list: Option<BookmarkList>, // Bookmarks popup (B)
+    pub jump_list: Option<JumpList>, // Frecency-ranked directory history popup (J)
+    pub command_palette: Option<crate::palette::CommandPalette>, // Built-in and plugin commands (Ctrl+P)
+    pub disk_usage: Option<crate::fs::du::DiskUsageView>, // Recursive size overlay (U)
+    pub du_cache: crate::fs::du::DuTree, // Sizes from finished scans, kept until rescanned or over DU_CACHE_MAX_DIRS
     pub viewer_content: Option<crate::viewer::ViewerContent>,
+    pub viewer_path: Option<PathBuf>, // File shown in the viewer (for re-highlighting/reload)
+    pub viewer_mtime: Option<std::time::SystemTime>, // Modification time of the viewed file when it was read
+    pub viewer_changed_on_disk: bool, // The watcher saw the viewed file change since it was read
+    pub viewer_follow: Option<crate::viewer::follow::FollowState>, // 'F' follow mode (tail -f) in the read-only viewer
+    pub viewer_index_job: Option<crate::viewer::large::IndexJob>, // Line index of a windowed large file being built
+    pub viewer_pending_jump: Option<crate::viewer::LineJump>, // 'G' / ':N' waiting for the index to reach its line
+    pub viewer_hex_highlight: Option<(usize, usize)>, // (offset, length) highlighted in the hex view: goto target or current match
+    pub viewer_hex_search: Option<crate::viewer::hex::HexSearch>, // '/' byte pattern search in the hex view
+    pub viewer_encoding: Option<crate::viewer::TextEncoding>, // Encoding the viewed text was decoded from, when not plain UTF-8
+    pub viewer_encoding_override: Option<&'static encoding_rs::Encoding>, // Set with ':enc NAME' for the viewed file
     pub viewer_scroll: usize,
+    pub viewer_hscroll: usize, // First table column shown (Left/Right in CSV/TSV previews)
+    pub viewer_drawn_lines: std::cell::Cell<(usize, usize)>, // Line range drawn last frame (extends lazy highlighting)
+    pub viewer_area: std::cell::Cell<ratatui::layout::Rect>, // Content area drawn last frame, for scroll math and pixel images
+    pub process_list_height: std::cell::Cell<usize>, // Process list rows drawn last frame, for scrolling and paging
+    pub inline_image_shown: Option<(crate::viewer::image::GraphicsProtocol, crate::viewer::image::InlinePlacement)>, // Pixel image currently on screen
+    pub viewer_search: Option<crate::viewer::search::ViewerSearch>, // '/' search in the read-only viewer
+    pub viewer_command: Option<String>, // ':' line jump prompt in the read-only viewer
+    pub viewer_markdown_raw: bool, // Show markdown source instead of the rendered view (kept for the session)
     pub viewer_editing: bool, // True when in vim edit mode
+    pub viewer_read_only: bool, // Showing a temporary copy of a remote file, so edits are refused
     pub text_editor: Option<crate::viewer::TextEditor>,
+    pub hex_editor: Option<crate::viewer::hex::HexEditor>, // 'i' on a hex view: overwrite bytes in place
     pub dialog: DialogMode,
     pub status_message: Option<String>,
     pub temp_message: Option<(String, Instant)>, // Temporary message with timer (auto-dismiss after 0.5s)
@@ -62,20 +101,247 @@ pub struct App {
     // Console panel state (right side panel - Shell)
     pub show_console: bool,
     pub console_focus: bool,
-    pub console: ShellState,
-    // File watcher for real-time updates (reserved for future use)
-    #[allow(dead_code)]
+    pub consoles: Vec<ShellState>, // Console panel tabs (never empty; one stopped shell while closed)
+    pub active_console: usize,
+    // File watcher for real-time updates (None when disabled in config)
     pub file_watcher: Option<FileWatcher>,
+    watch_debounce: ChangeDebouncer, // Changed directories waiting for a burst to settle
+    pub keybindings: crate::keybindings::KeyBindings,
+    pub git_overlay: Option<crate::fs::git::GitOverlay>,
     // Process viewer popup state
     pub show_process_viewer: bool,
     pub process_viewer: crate::process::ProcessViewer,
     // External game launcher flag
     pub launch_external_game: bool,
+    pub pending_open: Option<crate::fs::open_with::OpenCommand>, // Terminal program to run outside the TUI
     // Settings state
     pub settings_theme_index: usize,
+    pub themes: Vec<crate::config::Theme>, // Built-in and custom themes listed on the Theme tab
+    pub theme_editor: Option<ThemeEditor>,
     pub settings_tab: SettingsTab,
+    pub settings_plugin_index: usize, // Highlighted plugin on the Plugins tab
     // Viewer state
     pub viewer_wrap_mode: bool,
+    // Mouse double-click detection
+    pub click_tracker: ClickTracker,
+    // Incremental find in the active column ('f', then type)
+    pub type_ahead: Option<TypeAhead>,
+    // Per-item results of the last batch file operation (shown as popup)
+    pub operation_summary: Option<crate::fs::OperationSummary>,
+    pub summary_scroll: usize,
+    // Background paste and the pane it was started from
+    pub paste_job: Option<(Pane, crate::fs::PasteJob)>,
+    pub extract_job: Option<(Pane, crate::fs::ExtractJob)>,
+    pub search_job: Option<crate::fs::SearchJob>,
+    pub grep_job: Option<crate::fs::GrepJob>,
+    pub url_fetch: Option<crate::viewer::url::UrlFetch>, // `:open URL` download, viewed when done
+    pub download_dir: Option<tempfile::TempDir>, // Directory of the last viewed URL or remote download, removed when replaced or on exit
+    // Lua plugins and the file last reported to their file_selected hooks
+    pub plugins: crate::plugin::PluginManager,
+    pub last_selected_file: Option<PathBuf>,
+    // Preview column of the active pane (show_preview) and its text area drawn last frame
+    pub file_preview: Option<crate::viewer::preview::FilePreview>,
+    pub preview_area: std::cell::Cell<ratatui::layout::Rect>,
+    // Clock and disk space shown at the right of the footer
+    pub footer_info: FooterInfo,
+    // Selection-driven work (preview, plugin hook, git status, frecency) waits for the selection to rest
+    pub selection_debounce: SelectionDebounce,
+    // Batch rename in the editor: the name list file (deleted when dropped) and the entries it renames (line N -> paths[N])
+    pub batch_rename: Option<(tempfile::NamedTempFile, Vec<PathBuf>)>,
+    // Reversible file operations for 'u'
+    pub history: crate::fs::history::OperationHistory,
+    // SFTP connection being made for a pane, and the local panes remote ones replaced (:local restores them)
+    #[cfg(feature = "sftp")]
+    pub sftp_connect: Option<(Pane, crate::fs::remote::ConnectJob)>,
+    pub local_panes: Vec<(Pane, FileSystem)>,
+    pub remote_download: Option<RemoteDownload>, // Remote file being fetched for the viewer
+}
+
+/// Right-hand footer segments, refreshed at most once per second
+#[derive(Debug, Default)]
+pub struct FooterInfo {
+    pub clock: Option<String>,
+    pub disk: Option<(u64, u64)>, // (free, total) bytes
+    updated: Option<Instant>,
+}
+
+impl FooterInfo {
+    /// Refresh on the next tick (after a setting changed)
+    pub fn invalidate(&mut self) {
+        self.updated = None;
+    }
+}
+
+/// Tracks left clicks to detect double-clicks on the same cell
+#[derive(Debug, Default)]
+pub struct ClickTracker {
+    last_click: Option<(Instant, u16, u16)>, // (time, column, row)
+}
+
+impl ClickTracker {
+    /// Register a click; returns true if it completes a double-click
+    /// A zero window disables double-click detection
+    pub fn register(&mut self, at: Instant, column: u16, row: u16, window: std::time::Duration) -> bool {
+        let is_double = !window.is_zero() && matches!(
+            self.last_click,
+            Some((t, c, r)) if c == column && r == row && at.saturating_duration_since(t) <= window
+        );
+        // A completed double-click resets so a third click starts a new sequence
+        self.last_click = if is_double { None } else { Some((at, column, row)) };
+        is_double
+    }
+}
+
+/// Status shown for keys that would change files in a remote pane
+pub const REMOTE_READ_ONLY: &str = "Remote panes are read-only (:local goes back to the disk)";
+
+/// Status shown when trying to edit the viewer's copy of a remote file
+pub const REMOTE_COPY_READ_ONLY: &str = "This is a local copy of a remote file; edits can't be saved to the host";
+
+/// How long the selection must rest before selection-driven work runs
+pub const SELECTION_SETTLE: std::time::Duration = std::time::Duration::from_millis(120);
+
+/// Holds back work that follows the selection while it keeps moving
+/// Holding an arrow key passes many entries; only the one landed on is worth
+/// loading. Work opts in by checking `is_settled` before it runs.
+#[derive(Debug, Default)]
+pub struct SelectionDebounce {
+    selection: Option<(Pane, PathBuf)>,
+    changed: Option<Instant>,
+}
+
+impl SelectionDebounce {
+    /// Note the current selection; a different one restarts the wait
+    pub fn observe(&mut self, selection: Option<(Pane, PathBuf)>, now: Instant) {
+        if selection != self.selection {
+            self.selection = selection;
+            self.changed = Some(now);
+        }
+    }
+
+    /// Whether the selection has been still for `SELECTION_SETTLE`
+    pub fn is_settled(&self, now: Instant) -> bool {
+        self.changed.is_none_or(|changed| now.saturating_duration_since(changed) >= SELECTION_SETTLE)
+    }
+}
+
+/// How long the type-ahead buffer survives without a keystroke
+pub const TYPE_AHEAD_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(1500);
+
+/// Bookmarks popup state: the typed filter and the highlighted match
+#[derive(Debug, Default)]
+pub struct BookmarkList {
+    pub filter: String,
+    pub selected: usize, // Index into `matches`
+}
+
+impl BookmarkList {
+    /// Indices of the bookmarks whose label or path contains the filter (case-insensitive)
+    pub fn matches(&self, bookmarks: &[crate::config::Bookmark]) -> Vec<usize> {
+        let filter = self.filter.to_lowercase();
+        bookmarks.iter()
+            .enumerate()
+            .filter(|(_, b)| {
+                b.name.to_lowercase().contains(&filter)
+                    || b.path.to_string_lossy().to_lowercase().contains(&filter)
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Index of the highlighted bookmark
+    pub fn selected_bookmark(&self, bookmarks: &[crate::config::Bookmark]) -> Option<usize> {
+        self.matches(bookmarks).get(self.selected).copied()
+    }
+}
+
+/// Jump list popup state: the typed filter and the highlighted match
+#[derive(Debug, Default)]
+pub struct JumpList {
+    pub filter: String,
+    pub selected: usize, // Index into `matches`
+}
+
+impl JumpList {
+    /// Indices of the history entries whose path contains the filter (case-insensitive),
+    /// highest frecency first
+    pub fn matches(&self, history: &[crate::config::DirVisit], now: u64) -> Vec<usize> {
+        let filter = self.filter.to_lowercase();
+        let mut matches: Vec<usize> = history.iter()
+            .enumerate()
+            .filter(|(_, v)| v.path.to_string_lossy().to_lowercase().contains(&filter))
+            .map(|(i, _)| i)
+            .collect();
+        matches.sort_by(|&a, &b| history[b].frecency(now).total_cmp(&history[a].frecency(now)));
+        matches
+    }
+
+    /// Index of the highlighted history entry
+    pub fn selected_entry(&self, history: &[crate::config::DirVisit], now: u64) -> Option<usize> {
+        self.matches(history, now).get(self.selected).copied()
+    }
+}
+
+/// Octal mode as typed in the permission editor: "0755" style only when special bits are set
+pub fn format_octal_mode(mode: u32) -> String {
+    if mode > 0o777 { format!("{:04o}", mode) } else { format!("{:03o}", mode) }
+}
+
+/// Modification time of `path`, if it can be read
+fn file_mtime(path: &std::path::Path) -> Option<std::time::SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Current time in Unix seconds (jump list timestamps)
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Type-ahead find state: the typed letters and when the last one arrived
+#[derive(Debug)]
+pub struct TypeAhead {
+    pub buffer: String,
+    pub last_keystroke: Instant,
+}
+
+impl TypeAhead {
+    pub fn new(at: Instant) -> Self {
+        Self { buffer: String::new(), last_keystroke: at }
+    }
+
+    pub fn is_expired(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.last_keystroke) > TYPE_AHEAD_TIMEOUT
+    }
+
+    /// Add a typed character; returns true if it repeats the buffer's only letter,
+    /// in which case the buffer is kept and the caller cycles to the next match
+    pub fn push(&mut self, c: char, at: Instant) -> bool {
+        self.last_keystroke = at;
+        let lower = c.to_lowercase().to_string();
+        let repeated = !self.buffer.is_empty() && self.buffer.chars().all(|b| b.to_lowercase().to_string() == lower);
+        if repeated {
+            self.buffer = lower;
+        } else {
+            self.buffer.push(c);
+        }
+        repeated
+    }
+
+    /// Index of the first name matching the buffer at or after `start` (wrapping)
+    /// Prefix matches win over substring matches; case is ignored.
+    pub fn find(&self, names: &[String], start: usize) -> Option<usize> {
+        if self.buffer.is_empty() || names.is_empty() {
+            return None;
+        }
+        let query = self.buffer.to_lowercase();
+        let order: Vec<usize> = (0..names.len()).map(|i| (start + i) % names.len()).collect();
+        let lowered: Vec<String> = names.iter().map(|n| n.to_lowercase()).collect();
+        order.iter().copied().find(|&i| lowered[i].starts_with(&query))
+            .or_else(|| order.iter().copied().find(|&i| lowered[i].contains(&query)))
+    }
 }
 
 /// Settings tab
@@ -84,8 +350,31 @@ pub enum SettingsTab {
     #[default]
     Theme,
     Interface,
+    Plugins,
+}
+
+/// A file of a remote pane being downloaded to view
+pub struct RemoteDownload {
+    pub path: PathBuf,      // Path on the host
+    dir: tempfile::TempDir, // Private directory for the local copy; removed with the download unless viewed
+    len: u64,               // Size on the host, to tell when only the start was read
+    receiver: std::sync::mpsc::Receiver<std::io::Result<Vec<u8>>>,
+}
+
+/// Editing the active theme's colors on the Theme tab ('c')
+#[derive(Debug, Clone)]
+pub struct ThemeEditor {
+    pub role: usize,                     // Selected entry of `Theme::COLOR_ROLES`
+    pub input: Option<String>,           // Color being typed for the selected role
+    pub original: crate::config::Theme,  // Restored when the editor is left without saving
 }
 
+/// Directories whose sizes the disk usage overlay keeps between scans
+pub const DU_CACHE_MAX_DIRS: usize = 100_000;
+
+/// Lines of shell output kept for scrolling back (Shift+PageUp)
+pub const SHELL_SCROLLBACK_LINES: usize = 10_000;
+
 /// PTY-based shell state for full terminal emulation
 pub struct ShellState {
     pub master: Option<Box<dyn portable_pty::MasterPty + Send>>,
@@ -93,6 +382,9 @@ pub struct ShellState {
     pub child: Option<Box<dyn portable_pty::Child + Send + Sync>>,
     pub parser: Arc<Mutex<vt100::Parser>>,
     pub working_dir: PathBuf,
+    pub tracked_cwd: Option<PathBuf>, // Last directory the shell reported via OSC 7
+    osc7: crate::osc::Osc7Scanner,
+    pub copy_mode: Option<crate::copy_mode::CopyMode>, // Frozen screen being selected from (F7)
     pub size: (u16, u16),  // (cols, rows)
     pub is_running: bool,
     // Background thread for non-blocking PTY reading
@@ -104,6 +396,7 @@ impl std::fmt::Debug for ShellState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ShellState")
             .field("working_dir", &self.working_dir)
+            .field("tracked_cwd", &self.tracked_cwd)
             .field("size", &self.size)
             .field("is_running", &self.is_running)
             .finish()
@@ -116,8 +409,11 @@ impl ShellState {
             master: None,
             writer: None,
             child: None,
-            parser: Arc::new(Mutex::new(vt100::Parser::new(24, 80, 0))),
+            parser: Arc::new(Mutex::new(vt100::Parser::new(24, 80, SHELL_SCROLLBACK_LINES))),
             working_dir,
+            tracked_cwd: None,
+            osc7: crate::osc::Osc7Scanner::default(),
+            copy_mode: None,
             size: (80, 24),
             is_running: false,
             output_receiver: None,
@@ -183,9 +479,12 @@ impl ShellState {
         self.output_receiver = Some(rx);
         self.reader_thread = Some(reader_thread);
         self.is_running = true;
+        self.tracked_cwd = None;
+        self.osc7 = crate::osc::Osc7Scanner::default();
+        self.copy_mode = None;
         
         // Reset parser with correct size
-        *self.parser.lock().unwrap() = vt100::Parser::new(self.size.1, self.size.0, 0);
+        *self.parser.lock().unwrap() = vt100::Parser::new(self.size.1, self.size.0, SHELL_SCROLLBACK_LINES);
         
         Ok(())
     }
@@ -205,8 +504,80 @@ impl ShellState {
         self.is_running = false;
     }
     
-    /// Write data to PTY
+    /// Process id of the running shell
+    fn pid(&self) -> Option<sysinfo::Pid> {
+        self.child.as_ref()?.process_id().map(sysinfo::Pid::from_u32)
+    }
+
+    /// Directory shown in the panel title: the OSC 7 report, else the start directory
+    pub fn display_dir(&self) -> &std::path::Path {
+        self.tracked_cwd.as_deref().unwrap_or(&self.working_dir)
+    }
+
+    /// Current directory of the shell (follows the user's `cd`s)
+    /// Uses the OSC 7 report if the shell sends them, otherwise asks the OS.
+    pub fn cwd(&self) -> Option<PathBuf> {
+        if let Some(dir) = &self.tracked_cwd {
+            return Some(dir.clone());
+        }
+        let pid = self.pid()?;
+        let mut sys = sysinfo::System::new();
+        sys.refresh_processes_specifics(
+            sysinfo::ProcessesToUpdate::Some(&[pid]),
+            false,
+            sysinfo::ProcessRefreshKind::nothing().with_cwd(sysinfo::UpdateKind::Always),
+        );
+        sys.process(pid)?.cwd().map(|dir| dir.to_path_buf())
+    }
+
+    /// Whether the shell is running a command, so typed input would go to that program
+    /// (another process group owns the terminal's foreground)
+    pub fn is_busy(&self) -> bool {
+        #[cfg(unix)]
+        {
+            let leader = self.master.as_ref().and_then(|m| m.process_group_leader());
+            match (leader, self.pid()) {
+                (Some(leader), Some(pid)) => leader as u32 != pid.as_u32(),
+                _ => false,
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            false
+        }
+    }
+
+    /// Type a `cd` line into the shell
+    pub fn cd(&mut self, dir: &std::path::Path) -> std::io::Result<()> {
+        self.write(format!("cd {}\r", shell_quote(dir)).as_bytes())?;
+        self.working_dir = dir.to_path_buf();
+        Ok(())
+    }
+
+    /// Freeze the shown screen for selecting and copying text
+    pub fn enter_copy_mode(&mut self) {
+        if let Ok(parser) = self.parser.lock() {
+            self.copy_mode = Some(crate::copy_mode::CopyMode::new(parser.screen().clone()));
+        }
+    }
+
+    /// Scroll the view `rows` further back (negative: towards the live screen)
+    ///
+    /// vt100 0.15 only clamps the offset to the stored history and underflows
+    /// when it exceeds the screen height, so it is kept within one screen.
+    pub fn scroll_back(&mut self, rows: isize) {
+        if let Ok(mut parser) = self.parser.lock() {
+            let screen_rows = parser.screen().size().0 as usize;
+            let offset = parser.screen().scrollback().saturating_add_signed(rows).min(screen_rows);
+            parser.set_scrollback(offset);
+        }
+    }
+
+    /// Write data to PTY (returns the view to the live screen)
     pub fn write(&mut self, data: &[u8]) -> std::io::Result<()> {
+        if let Ok(mut parser) = self.parser.lock() {
+            parser.set_scrollback(0);
+        }
         if let Some(writer) = &self.writer {
             let mut writer = writer.lock().unwrap();
             writer.write_all(data)?;
@@ -220,6 +591,9 @@ impl ShellState {
         if let Some(receiver) = &self.output_receiver {
             // Non-blocking: process all available data from channel
             while let Ok(data) = receiver.try_recv() {
+                if let Some(dir) = self.osc7.feed(&data) {
+                    self.tracked_cwd = Some(dir);
+                }
                 let mut parser = self.parser.lock().unwrap();
                 parser.process(&data);
             }
@@ -241,7 +615,9 @@ impl ShellState {
         }
         
         let mut parser = self.parser.lock().unwrap();
+        let offset = parser.screen().scrollback().min(rows as usize);
         parser.set_size(rows, cols);
+        parser.set_scrollback(offset);
     }
     
     /// Check if child process is still running
@@ -272,15 +648,39 @@ impl App {
 
         let mut fs_left = FileSystem::new();
         fs_left.sort_option = config.sort_option;
+        fs_left.sort_reverse = config.sort_reverse;
+        fs_left.dirs_first = config.dirs_first;
 
         let mut fs_center = FileSystem::new();
         fs_center.sort_option = config.sort_option;
+        fs_center.sort_reverse = config.sort_reverse;
+        fs_center.dirs_first = config.dirs_first;
 
         let mut fs_right = FileSystem::new();
         fs_right.sort_option = config.sort_option;
+        fs_right.sort_reverse = config.sort_reverse;
+        fs_right.dirs_first = config.dirs_first;
 
         let current_dir = fs_left.current_dir.clone();
-        App {
+        let file_watcher = if config.enable_watcher { FileWatcher::new().ok() } else { None };
+        let git_overlay = config.show_git_status.then(crate::fs::git::GitOverlay::new);
+        let (keybindings, keybinding_warnings) = crate::keybindings::KeyBindings::from_config(&config.keybindings);
+        let mut plugins = crate::plugin::PluginManager::default();
+        plugins.set_trusted_plugins(&config.trusted_plugins);
+        plugins.set_disabled_plugins(&config.disabled_plugins);
+        if let Err(e) = plugins.load_all() {
+            tracing::warn!("Failed to load plugins: {}", e);
+        }
+        if config.plugin_hot_reload {
+            if let Err(e) = plugins.enable_hot_reload() {
+                tracing::warn!("Failed to watch the plugin directory: {}", e);
+            }
+        }
+        plugins.set_current_path(&fs_left.current_dir);
+        for warning in &keybinding_warnings {
+            tracing::warn!("{}", warning);
+        }
+        let mut app = App {
             mode: AppMode::FileManager,
             fs_left,
             fs_center,
@@ -291,29 +691,626 @@ impl App {
             should_quit: false,
             config,
             show_help: false,
-            show_bookmarks: false,
+            bookmark_list: None,
+            disk_usage: None,
+            du_cache: crate::fs::du::DuTree::new(),
+            jump_list: None,
+            command_palette: None,
             viewer_content: None,
+            viewer_path: None,
+            viewer_mtime: None,
+            viewer_changed_on_disk: false,
+            viewer_follow: None,
+            viewer_index_job: None,
+            viewer_pending_jump: None,
+            viewer_hex_highlight: None,
+            viewer_hex_search: None,
+            viewer_encoding: None,
+            viewer_encoding_override: None,
             viewer_scroll: 0,
+            viewer_hscroll: 0,
+            viewer_drawn_lines: std::cell::Cell::new((0, 0)),
+            viewer_area: std::cell::Cell::new(ratatui::layout::Rect::default()),
+            process_list_height: std::cell::Cell::new(0),
+            inline_image_shown: None,
+            viewer_search: None,
+            viewer_command: None,
+            viewer_markdown_raw: false,
             viewer_editing: false,
+            viewer_read_only: false,
             text_editor: None,
+            hex_editor: None,
             dialog: DialogMode::None,
-            status_message: None,
+            // Surface config problems without refusing to start
+            status_message: keybinding_warnings.first().map(|w| format!("Keybindings: {}", w)),
             temp_message: None,
             show_shell: false,
             shell: ShellState::new(current_dir.clone()),
             show_console: false,
             console_focus: false,
-            console: ShellState::new(current_dir.clone()),
-            file_watcher: FileWatcher::new().ok(),
+            consoles: vec![ShellState::new(current_dir.clone())],
+            active_console: 0,
+            file_watcher,
+            watch_debounce: ChangeDebouncer::default(),
+            keybindings,
+            git_overlay,
             show_process_viewer: false,
             process_viewer: crate::process::ProcessViewer::new(),
             launch_external_game: false,
+            pending_open: None,
             settings_theme_index: 0,
+            themes: crate::config::Theme::available(),
+            theme_editor: None,
             settings_tab: SettingsTab::default(),
+            settings_plugin_index: 0,
             viewer_wrap_mode: true,
+            click_tracker: ClickTracker::default(),
+            type_ahead: None,
+            operation_summary: None,
+            summary_scroll: 0,
+            paste_job: None,
+            extract_job: None,
+            search_job: None,
+            grep_job: None,
+            url_fetch: None,
+            download_dir: None,
+            plugins,
+            last_selected_file: None,
+            file_preview: None,
+            preview_area: std::cell::Cell::new(ratatui::layout::Rect::default()),
+            footer_info: FooterInfo::default(),
+            selection_debounce: SelectionDebounce::default(),
+            batch_rename: None,
+            history: crate::fs::history::OperationHistory::default(),
+            #[cfg(feature = "sftp")]
+            sftp_connect: None,
+            local_panes: Vec::new(),
+            remote_download: None,
+        };
+
+        if !crate::viewer::highlight::is_syntax_theme(&app.config.syntax_theme) {
+            tracing::warn!("Unknown syntax theme '{}', using default", app.config.syntax_theme);
+        }
+        if app.config.restore_session {
+            app.restore_session();
+        }
+        app
+    }
+
+    /// Whether `path` can be opened in the viewer (built-in formats or a plugin viewer handler)
+    pub fn can_preview(&self, path: &std::path::Path) -> bool {
+        self.plugins.has_viewer_handler(path) || crate::viewer::is_supported_file_type(path)
+    }
+
+    /// Load `path` into the viewer, letting a plugin viewer handler take precedence
+    pub fn open_in_viewer(&mut self, path: &std::path::Path) {
+        self.viewer_encoding_override = None;
+        let (content, encoding) = match self.plugins.view_file(path) {
+            Some(content) => (content, None),
+            None => crate::viewer::load_file_with_encoding(path, &self.config.syntax_overrides, &self.config.syntax_theme, self.config.preview_limits(), None),
+        };
+        self.viewer_content = Some(content);
+        self.viewer_path = Some(path.to_path_buf());
+        self.viewer_read_only = false;
+        self.viewer_scroll = 0;
+        self.viewer_hscroll = 0;
+        self.viewer_search = None;
+        self.viewer_command = None;
+        self.viewer_follow = None;
+        self.viewer_hex_highlight = None;
+        self.viewer_hex_search = None;
+        self.viewer_index_job = None;
+        self.viewer_pending_jump = None;
+        self.update_viewer_encoding(encoding);
+        self.mark_viewer_fresh();
+        self.mode = AppMode::Viewer;
+        self.plugins.dispatch_hook(&crate::plugin::HookEvent::FileOpened { path: path.to_path_buf() });
+    }
+
+    /// Re-read the file shown in the read-only viewer, keeping the scroll position
+    pub fn reload_viewer(&mut self) {
+        let Some(path) = self.viewer_path.clone() else { return };
+        if !path.exists() {
+            self.status_message = Some(format!("{} no longer exists", path.display()));
+            return;
+        }
+        let (content, encoding) = match self.plugins.view_file(&path) {
+            Some(content) => (content, None),
+            None => crate::viewer::load_file_with_encoding(&path, &self.config.syntax_overrides, &self.config.syntax_theme, self.config.preview_limits(), self.viewer_encoding_override),
+        };
+        self.viewer_content = Some(content);
+        self.viewer_search = None;
+        self.viewer_index_job = None;
+        self.viewer_pending_jump = None;
+        self.update_viewer_encoding(encoding);
+        self.mark_viewer_fresh();
+        self.sync_follow_offset();
+        self.status_message = Some("File reloaded".to_string());
+    }
+
+    /// Note the encoding the viewed text was read in for the header, when it isn't plain UTF-8
+    fn update_viewer_encoding(&mut self, encoding: Option<crate::viewer::TextEncoding>) {
+        use crate::viewer::ViewerContent;
+
+        let is_text = matches!(
+            self.viewer_content,
+            Some(ViewerContent::PlainText(_) | ViewerContent::HighlightedCode { .. } | ViewerContent::Markdown(_))
+        );
+        self.viewer_encoding = encoding.filter(|encoding| is_text && !encoding.is_plain_utf8());
+    }
+
+    /// Re-decode the viewed file as the encoding labelled `label` ("auto" to detect it again)
+    pub fn set_viewer_encoding(&mut self, label: &str) {
+        if label.eq_ignore_ascii_case("auto") {
+            self.viewer_encoding_override = None;
+        } else {
+            match encoding_rs::Encoding::for_label(label.as_bytes()) {
+                Some(encoding) => self.viewer_encoding_override = Some(encoding),
+                None => {
+                    self.status_message = Some(format!("Unknown encoding: {}", label));
+                    return;
+                },
+            }
+        }
+        self.reload_viewer();
+        self.status_message = Some(match self.viewer_encoding {
+            Some(encoding) => format!("Decoded as {}", encoding.label()),
+            None => "Decoded as UTF-8".to_string(),
+        });
+    }
+
+    /// Start editing the binary file shown in the hex view
+    pub fn open_hex_editor(&mut self) {
+        if self.viewer_read_only {
+            self.status_message = Some(REMOTE_COPY_READ_ONLY.to_string());
+            return;
+        }
+        let Some(path) = self.viewer_path.clone() else {
+            self.status_message = Some("Cannot edit binary data without a file".to_string());
+            return;
+        };
+        match crate::viewer::hex::HexEditor::open(&path, self.config.preview_limits().binary_bytes) {
+            Ok(mut editor) => {
+                // Start at the highlighted byte, or the top of what was shown
+                let rows = self.viewer_area.get().height.saturating_sub(1) as usize;
+                editor.scroll = self.viewer_scroll;
+                let start = self.viewer_hex_highlight.map(|(offset, _)| offset);
+                editor.move_to(start.unwrap_or(editor.scroll * crate::viewer::hex::BYTES_PER_ROW));
+                editor.keep_cursor_visible(rows);
+                self.viewer_follow = None;
+                self.hex_editor = Some(editor);
+                self.status_message = Some("Hex edit: type hex digits to overwrite, Ctrl+S to save, Esc to leave".to_string());
+            },
+            Err(e) => self.status_message = Some(format!("Cannot edit: {}", e)),
+        }
+    }
+
+    /// Write the hex editor's changes to disk
+    pub fn save_hex_editor(&mut self) {
+        let Some(editor) = &mut self.hex_editor else { return };
+        match editor.save() {
+            Ok(0) => self.status_message = Some("No changes to save".to_string()),
+            Ok(count) => {
+                let data = editor.data.clone();
+                self.viewer_content = Some(crate::viewer::ViewerContent::HexView(data, false));
+                // Our own write isn't an outside change
+                self.mark_viewer_fresh();
+                self.status_message = Some(format!("Saved {} changed bytes", count));
+            },
+            Err(e) => self.status_message = Some(format!("Save failed: {}", e)),
+        }
+    }
+
+    /// Leave the hex editor; unsaved changes need a second Esc to discard
+    pub fn close_hex_editor(&mut self) {
+        let Some(editor) = &mut self.hex_editor else { return };
+        if editor.is_dirty() && !editor.confirm_discard {
+            editor.confirm_discard = true;
+            self.status_message = Some(format!(
+                "{} unsaved bytes: Ctrl+S to save, Esc again to discard", editor.dirty_count()
+            ));
+            return;
+        }
+        let discarded = editor.is_dirty();
+        let row = editor.cursor / crate::viewer::hex::BYTES_PER_ROW;
+        self.hex_editor = None;
+        self.viewer_scroll = row;
+        self.status_message = Some(if discarded { "Changes discarded" } else { "Left hex edit" }.to_string());
+    }
+
+    /// After a full re-read, continue following from the current end of the file
+    fn sync_follow_offset(&mut self) {
+        let len = self.viewer_path.as_deref().and_then(|p| std::fs::metadata(p).ok()).map(|m| m.len());
+        if let (Some(follow), Some(len)) = (&mut self.viewer_follow, len) {
+            follow.offset = len;
+        }
+    }
+
+    /// 'F' in the read-only viewer: start following the file, resume a paused
+    /// follow, or stop following
+    pub fn toggle_viewer_follow(&mut self) {
+        match &mut self.viewer_follow {
+            Some(follow) if follow.paused => {
+                follow.paused = false;
+                crate::events::scroll_viewer_to_end(self);
+                self.status_message = Some("Following resumed".to_string());
+            },
+            Some(_) => {
+                self.viewer_follow = None;
+                self.status_message = Some("Stopped following".to_string());
+            },
+            None => {
+                if self.viewer_path.is_none() || !self.viewer_content.as_ref().is_some_and(crate::viewer::follow::can_follow) {
+                    self.status_message = Some("Follow mode works on text files".to_string());
+                    return;
+                }
+                // Start from a fresh read so the offset matches what is shown
+                self.viewer_follow = Some(crate::viewer::follow::FollowState { offset: 0, paused: false });
+                self.reload_viewer();
+                crate::events::scroll_viewer_to_end(self);
+                self.status_message = Some("Following (F to stop, scroll up to pause)".to_string());
+            },
+        }
+    }
+
+    /// Append what was written to the followed file since the last tick
+    fn poll_viewer_follow(&mut self) {
+        use crate::viewer::follow::{self, Growth};
+
+        if self.mode != AppMode::Viewer || self.viewer_editing {
+            return;
+        }
+        let (Some(state), Some(path)) = (&mut self.viewer_follow, &self.viewer_path) else { return };
+        match follow::read_appended(path, &mut state.offset) {
+            Ok(Growth::Unchanged) => return,
+            Ok(Growth::Appended(text)) => {
+                if !self.viewer_content.as_mut().is_some_and(|content| follow::append(content, &text)) {
+                    self.viewer_follow = None;
+                    return;
+                }
+                self.viewer_search = None;
+            },
+            Ok(Growth::Truncated) => {
+                self.reload_viewer();
+                self.status_message = Some("File was truncated; reloaded".to_string());
+            },
+            Err(e) => {
+                self.viewer_follow = None;
+                self.status_message = Some(format!("Stopped following: {}", e));
+                return;
+            },
+        }
+        // Our own read is up to date; don't flag the file as changed on disk
+        self.mark_viewer_fresh();
+        if self.viewer_follow.as_ref().is_some_and(|f| !f.paused) {
+            crate::events::scroll_viewer_to_end(self);
+        }
+    }
+
+    /// Remember the viewed file's modification time and clear the changed-on-disk flag
+    pub fn mark_viewer_fresh(&mut self) {
+        self.viewer_mtime = self.viewer_path.as_deref().and_then(file_mtime);
+        self.viewer_changed_on_disk = false;
+    }
+
+    /// Re-read the custom themes in ~/.config/senterm/themes
+    pub fn reload_themes(&mut self) {
+        self.themes = crate::config::Theme::available();
+        self.settings_theme_index = self.settings_theme_index.min(self.themes.len() - 1);
+    }
+
+    /// Write the active theme to the themes directory for editing or sharing
+    pub fn export_theme(&mut self) {
+        self.status_message = Some(match self.write_theme_file() {
+            Ok(path) => format!("Exported theme to {}", path.display()),
+            Err(e) => format!("Failed to export theme: {}", e),
+        });
+    }
+
+    /// Write the active theme to the themes directory
+    fn write_theme_file(&self) -> Result<PathBuf, String> {
+        let dir = crate::config::Theme::themes_dir().ok_or("Could not determine config directory")?;
+        self.config.theme.export(&dir).map_err(|e| e.to_string())
+    }
+
+    /// Save the edited theme as a custom theme and make it the configured one
+    /// An edited built-in is saved as "<name> (custom)" so the preset stays as it was.
+    pub fn save_edited_theme(&mut self) {
+        let name = &self.config.theme.name;
+        if crate::config::Theme::all_themes().iter().any(|t| t.name == *name) {
+            self.config.theme.name = format!("{} (custom)", name);
+        }
+        let saved = self.write_theme_file()
+            .and_then(|path| self.config.save().map(|_| path).map_err(|e| e.to_string()));
+        self.status_message = Some(match saved {
+            Ok(path) => format!("Saved theme '{}' to {}", self.config.theme.name, path.display()),
+            Err(e) => format!("Failed to save theme: {}", e),
+        });
+        self.reload_themes();
+        if let Some(index) = self.themes.iter().position(|t| t.name == self.config.theme.name) {
+            self.settings_theme_index = index;
+        }
+        if let Some(editor) = &mut self.theme_editor {
+            editor.original = self.config.theme.clone();
+        }
+    }
+
+    /// Switch the syntax theme and re-highlight the open viewer content
+    pub fn set_syntax_theme(&mut self, name: &str) -> bool {
+        if !crate::viewer::highlight::is_syntax_theme(name) {
+            return false;
+        }
+        self.config.syntax_theme = name.to_string();
+        let _ = self.config.save();
+
+        if let (Some(crate::viewer::ViewerContent::HighlightedCode { .. }), Some(path)) = (&self.viewer_content, &self.viewer_path) {
+            if !self.viewer_editing {
+                let scroll = self.viewer_scroll;
+                self.viewer_content = Some(crate::viewer::load_file_with_overrides(path, &self.config.syntax_overrides, &self.config.syntax_theme, self.config.preview_limits(), self.viewer_encoding_override));
+                self.viewer_scroll = scroll;
+                self.sync_follow_offset();
+            }
+        }
+        true
+    }
+
+    /// Where the viewer's pixel image (Kitty/iTerm2) belongs on screen, if one should be shown
+    pub fn inline_image_placement(&self) -> Option<(crate::viewer::image::GraphicsProtocol, crate::viewer::image::InlinePlacement)> {
+        if self.mode != AppMode::Viewer || self.viewer_editing || !matches!(self.dialog, DialogMode::None) {
+            return None;
+        }
+        // Popups drawn over the viewer would be covered by the image
+        if self.show_help || self.show_shell || self.show_process_viewer {
+            return None;
+        }
+        let Some(crate::viewer::ViewerContent::ImagePreviewContent(preview)) = &self.viewer_content else { return None };
+        let inline = preview.inline.as_ref()?;
+        let placement = inline.placement(self.viewer_area.get(), self.viewer_scroll, crate::viewer::image::cell_pixel_size())?;
+        Some((inline.protocol, placement))
+    }
+
+    /// Escape sequence drawing the viewer's pixel image at `placement`
+    pub fn inline_image_sequence(&self, placement: &crate::viewer::image::InlinePlacement) -> Option<String> {
+        match &self.viewer_content {
+            Some(crate::viewer::ViewerContent::ImagePreviewContent(preview)) => preview.inline.as_ref()?.escape_sequence(placement),
+            _ => None,
+        }
+    }
+
+    /// Highlight the lines around the viewer window that aren't highlighted yet
+    fn extend_viewer_highlight(&mut self) {
+        use crate::viewer::highlight::HIGHLIGHT_BUFFER_LINES;
+
+        if let Some(crate::viewer::ViewerContent::HighlightedCode { highlighted, .. }) = &mut self.viewer_content {
+            let (start, end) = self.viewer_drawn_lines.get();
+            highlighted.ensure_range(start.saturating_sub(HIGHLIGHT_BUFFER_LINES)..end + HIGHLIGHT_BUFFER_LINES);
+        }
+    }
+
+    /// Index a windowed large file on a worker, taking its progress and doing a jump waiting for it
+    fn index_large_viewer_file(&mut self) {
+        use crate::viewer::{LineJump, ViewerContent};
+
+        let Some(ViewerContent::LargeFile(file)) = &mut self.viewer_content else {
+            self.viewer_index_job = None;
+            self.viewer_pending_jump = None;
+            return;
+        };
+        if file.is_indexed() {
+            self.viewer_index_job = None;
+        } else if let Some(job) = &self.viewer_index_job {
+            match job.poll() {
+                Some(Ok(progress)) if progress.path == file.path => *file = progress,
+                Some(Ok(_)) => self.viewer_index_job = None,
+                Some(Err(e)) => {
+                    self.viewer_content = Some(ViewerContent::Error(format!("Failed to read file: {}", e)));
+                    self.viewer_index_job = None;
+                    self.viewer_pending_jump = None;
+                    return;
+                },
+                None => {},
+            }
+        } else {
+            self.viewer_index_job = Some(crate::viewer::large::IndexJob::start(file.clone()));
+        }
+
+        let last_line = file.line_count().saturating_sub(1);
+        self.viewer_scroll = match self.viewer_pending_jump {
+            Some(LineJump::Line(line)) if line <= last_line || file.is_indexed() => line.min(last_line),
+            Some(LineJump::End) if file.is_indexed() => last_line,
+            _ => return,
+        };
+        self.viewer_pending_jump = None;
+    }
+
+    /// Reopen the panes saved by `save_session`
+    fn restore_session(&mut self) {
+        let Some(session) = self.config.session.clone() else { return };
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("/"));
+        let dirs = session.validated_dirs(&home);
+
+        for (fs, dir) in [&mut self.fs_left, &mut self.fs_center, &mut self.fs_right].into_iter().zip(dirs) {
+            fs.jump_to_dir(dir);
+            // Restoring isn't a visit; `-` shouldn't jump back to the launch directory
+            fs.previous_dir = None;
         }
+
+        let max_panes = self.config.max_ui_trees.clamp(1, 3);
+        self.pane_count = session.pane_count.clamp(1, max_panes);
+        self.active_pane = match session.active_pane {
+            1 if self.pane_count >= 2 => Pane::Center,
+            2 if self.pane_count >= 3 => Pane::Right,
+            _ => Pane::Left,
+        };
+        tracing::info!(pane_count = self.pane_count, "Restored previous session");
+    }
+
+    /// Record the open panes in the config (saved on exit)
+    pub fn save_session(&mut self) {
+        let active_pane = match self.active_pane {
+            Pane::Left => 0,
+            Pane::Center => 1,
+            Pane::Right => 2,
+        };
+        self.config.session = Some(crate::config::Session {
+            pane_count: self.pane_count,
+            active_pane,
+            dirs: self.session_dirs(),
+        });
+    }
+
+    /// Local directory of each open pane, for the session (the one behind a remote pane)
+    fn session_dirs(&self) -> Vec<PathBuf> {
+        [(Pane::Left, &self.fs_left), (Pane::Center, &self.fs_center), (Pane::Right, &self.fs_right)]
+            .into_iter()
+            .take(self.pane_count)
+            .map(|(pane, fs)| {
+                self.local_panes.iter()
+                    .find(|(local_pane, _)| *local_pane == pane)
+                    .map_or(&fs.current_dir, |(_, local)| &local.current_dir)
+                    .clone()
+            })
+            .collect()
     }
     
+    /// Move directories entered in any pane into the jump list history
+    pub fn record_dir_visits(&mut self) {
+        let now = unix_now();
+        let entered: Vec<PathBuf> = [&mut self.fs_left, &mut self.fs_center, &mut self.fs_right]
+            .into_iter()
+            .flat_map(|fs| {
+                let entered = std::mem::take(&mut fs.entered_dirs);
+                // Remote directories don't belong in the local history
+                if fs.is_remote() { Vec::new() } else { entered }
+            })
+            .collect();
+        for dir in entered {
+            self.plugins.dispatch_hook(&crate::plugin::HookEvent::DirectoryEntered { path: dir.clone() });
+            self.config.record_dir_visit(dir, now);
+        }
+    }
+
+    /// Open the command palette with the built-in and plugin commands
+    pub fn open_command_palette(&mut self) {
+        let mut entries = crate::palette::builtin_entries(|action| self.keybindings.keys_for(action));
+        entries.extend(self.plugins.command_list().into_iter().map(|(plugin, command)| crate::palette::PaletteEntry {
+            name: command.name.clone(),
+            description: command.description.clone(),
+            keybinding: command.keybinding.clone(),
+            command: crate::palette::PaletteCommand::Plugin {
+                plugin: plugin.to_string(),
+                function: command.function.clone(),
+            },
+        }));
+        self.command_palette = Some(crate::palette::CommandPalette::new(entries));
+    }
+
+    /// Give a plugin full io/os access and remember the choice
+    pub fn trust_plugin(&mut self, name: &str) {
+        match self.plugins.promote(name) {
+            Ok(message) => {
+                if !self.config.trusted_plugins.iter().any(|n| n == name) {
+                    self.config.trusted_plugins.push(name.to_string());
+                    let _ = self.config.save();
+                }
+                self.status_message = Some(message);
+            }
+            Err(e) => self.status_message = Some(e),
+        }
+    }
+
+    /// Enable or disable a plugin and remember the choice
+    pub fn toggle_plugin(&mut self, name: &str) {
+        match self.plugins.toggle_enabled(name) {
+            Ok(enabled) => {
+                self.config.disabled_plugins.retain(|n| n != name);
+                if !enabled {
+                    self.config.disabled_plugins.push(name.to_string());
+                }
+                let _ = self.config.save();
+                self.status_message = Some(format!("Plugin '{}' {}", name, if enabled { "enabled" } else { "disabled" }));
+            }
+            Err(e) => self.status_message = Some(e),
+        }
+    }
+
+    /// Run a command picked in the palette
+    pub fn run_palette_command(&mut self, command: crate::palette::PaletteCommand) {
+        use crate::keybindings::Action;
+        use crate::palette::PaletteCommand;
+        match command {
+            PaletteCommand::Key(code) => crate::events::handle_file_manager_keys(self, code),
+            PaletteCommand::Action(action) => match action {
+                Action::ToggleConsole => self.toggle_console(),
+                Action::ToggleShell => self.toggle_shell(),
+                Action::AddPane => self.add_pane(),
+                Action::RemovePane => self.remove_pane(),
+                Action::GrowSplit => self.resize_focused_split(true),
+                Action::ShrinkSplit => self.resize_focused_split(false),
+                Action::ToggleProcessViewer => self.toggle_process_viewer(),
+                Action::OpenSettings | Action::ToggleSettings => self.mode = AppMode::Settings,
+                Action::ToggleHelp => self.toggle_help(),
+                Action::Quit => self.should_quit = true,
+                Action::GrepSearch => self.open_grep(),
+                _ => {}
+            },
+            PaletteCommand::Plugin { plugin, function } => {
+                if let Err(e) = self.plugins.run_command(&plugin, &function) {
+                    self.status_message = Some(format!("Plugin command failed: {}", e));
+                }
+            }
+        }
+    }
+
+    /// Show the notifications and status text plugins queued through the `senterm` table
+    fn apply_plugin_effects(&mut self) {
+        for effect in self.plugins.drain_effects() {
+            match effect {
+                crate::plugin::PluginEffect::Notify { plugin, message } => {
+                    self.set_temp_message(format!("[{}] {}", plugin, message));
+                }
+                crate::plugin::PluginEffect::SetStatus { plugin, message } => {
+                    self.status_message = Some(format!("[{}] {}", plugin, message));
+                }
+            }
+        }
+    }
+
+    /// Tell plugins when the selection moves onto a different file
+    fn notify_file_selected(&mut self) {
+        if self.mode != AppMode::FileManager || !self.plugins.has_hook("file_selected") || self.active_fs().is_remote() {
+            return;
+        }
+        let selected = self.active_fs().selected_paths().into_iter().next().filter(|p| p.is_file());
+        if selected.is_some() && selected != self.last_selected_file {
+            if let Some(path) = selected.clone() {
+                self.plugins.dispatch_hook(&crate::plugin::HookEvent::FileSelected { path });
+            }
+        }
+        self.last_selected_file = selected;
+    }
+
+    /// Load the preview column's file when the selection, the file or the column size changed
+    fn update_file_preview(&mut self) {
+        if self.mode != AppMode::FileManager {
+            return;
+        }
+        let selected = (self.config.show_preview && !self.active_fs().is_remote())
+            .then(|| self.active_fs().selected_entry())
+            .flatten()
+            .filter(|path| path.is_file());
+        let Some(path) = selected else {
+            self.file_preview = None;
+            return;
+        };
+        // Not drawn yet: the size is known after the next frame
+        let area = self.preview_area.get();
+        if area.height == 0 || self.file_preview.as_ref().is_some_and(|p| p.is_current(&path, (area.width, area.height))) {
+            return;
+        }
+        self.file_preview = Some(crate::viewer::preview::load(&path, &self.config.syntax_overrides, area.width, area.height));
+    }
+
     /// Get reference to the active file system
     pub fn active_fs(&self) -> &FileSystem {
         match self.active_pane {
@@ -332,6 +1329,116 @@ impl App {
         }
     }
     
+    fn fs_for_pane_mut(&mut self, pane: Pane) -> &mut FileSystem {
+        match pane {
+            Pane::Left => &mut self.fs_left,
+            Pane::Center => &mut self.fs_center,
+            Pane::Right => &mut self.fs_right,
+        }
+    }
+
+    /// Connect the active pane to `target` ("user@host[:port]") over SFTP
+    #[cfg(feature = "sftp")]
+    pub fn connect_sftp(&mut self, target: &str) {
+        let Some(target) = crate::fs::remote::SftpTarget::parse(target) else {
+            self.status_message = Some(format!("Not a user@host[:port] address: {}", target));
+            return;
+        };
+        self.status_message = Some(format!("Connecting to {}...", target.label()));
+        self.sftp_connect = Some((self.active_pane, crate::fs::remote::ConnectJob::start(target)));
+    }
+
+    #[cfg(not(feature = "sftp"))]
+    pub fn connect_sftp(&mut self, _target: &str) {
+        self.status_message = Some("SFTP isn't built in (cargo build --features sftp)".to_string());
+    }
+
+    /// Mount a finished connection in the pane it was made for
+    #[cfg(feature = "sftp")]
+    fn poll_sftp_connect(&mut self) {
+        let Some((pane, job)) = &self.sftp_connect else { return };
+        let Some(result) = job.poll() else { return };
+        let (pane, label) = (*pane, job.target.label());
+        self.sftp_connect = None;
+        match result {
+            Ok(remote) => {
+                let home = remote.home.clone();
+                let fs = self.fs_for_pane_mut(pane);
+                let remote_fs = fs.remount(std::sync::Arc::new(remote), home);
+                let replaced = std::mem::replace(fs, remote_fs);
+                // Connecting again from a remote pane keeps the local one from before
+                if !replaced.is_remote() {
+                    self.local_panes.retain(|(local_pane, _)| *local_pane != pane);
+                    self.local_panes.push((pane, replaced));
+                }
+                self.status_message = Some(format!("Connected to {} (read-only, :local to go back)", label));
+            },
+            Err(e) => self.status_message = Some(format!("Couldn't connect to {}: {}", label, e)),
+        }
+    }
+
+    /// Put back the local directory a remote pane replaced (`:local`)
+    pub fn unmount_remote(&mut self) {
+        let pane = self.active_pane;
+        match self.local_panes.iter().position(|(local_pane, _)| *local_pane == pane) {
+            Some(index) => {
+                let (_, local) = self.local_panes.remove(index);
+                *self.fs_for_pane_mut(pane) = local;
+                self.status_message = Some("Back on the local disk".to_string());
+            },
+            None => self.status_message = Some("This pane isn't remote".to_string()),
+        }
+    }
+
+    /// View a file of a remote pane read-only: its first `text_bytes` are downloaded
+    /// in the background to a copy in a private temporary directory
+    pub fn open_remote_file(&mut self, path: &std::path::Path) {
+        if !self.can_preview(path) {
+            self.set_temp_message("미리보기가 지원되지 않는 파일 형식입니다".to_string());
+            return;
+        }
+        let dir = match tempfile::Builder::new().prefix("senterm-sftp-").tempdir() {
+            Ok(dir) => dir,
+            Err(e) => {
+                self.status_message = Some(format!("Couldn't create a temporary directory: {}", e));
+                return;
+            }
+        };
+        let fs = self.active_fs();
+        let len = fs.entry_meta(path).map_or(0, |meta| meta.len);
+        let receiver = fs.backend.read_file(path, self.config.preview_limits().text_bytes);
+        self.status_message = Some(format!("Downloading {}...", path.file_name().unwrap_or_default().to_string_lossy()));
+        self.remote_download = Some(RemoteDownload { path: path.to_path_buf(), dir, len, receiver });
+    }
+
+    /// Open a finished remote download in the viewer
+    fn poll_remote_download(&mut self) {
+        let Some(download) = &self.remote_download else { return };
+        let bytes = match download.receiver.try_recv() {
+            Ok(result) => result,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => Err(std::io::Error::other("the download stopped")),
+        };
+        let Some(RemoteDownload { path, dir, len, .. }) = self.remote_download.take() else { return };
+        let copy = dir.path().join(path.file_name().unwrap_or(std::ffi::OsStr::new("remote")));
+        let written = bytes.and_then(|bytes| {
+            std::fs::File::create_new(&copy)?.write_all(&bytes)?;
+            Ok(bytes.len() as u64)
+        });
+        match written {
+            Ok(read) => {
+                self.text_editor = None;
+                self.viewer_editing = false;
+                self.open_in_viewer(&copy);
+                self.viewer_read_only = true;
+                // Replacing the previous download deletes its copy
+                self.download_dir = Some(dir);
+                self.status_message = (read < len).then(|| format!("Showing the first {} of {} bytes", read, len));
+            },
+            Err(e) => self.status_message = Some(format!("Couldn't download {}: {}", path.display(), e)),
+        }
+    }
+
     /// Refresh all file systems (after file operations)
     pub fn refresh_both_panes(&mut self) {
         self.fs_left.refresh_current_dir();
@@ -401,6 +1508,42 @@ impl App {
         }
     }
     
+    /// Grow (or shrink) the focused pane or console by one step (Ctrl+Shift+Right/Left)
+    /// With a single pane next to the console, the pane and the console trade width.
+    pub fn resize_focused_split(&mut self, grow: bool) {
+        const STEP: i16 = 5;
+        let delta = if grow { STEP } else { -STEP };
+        let ratios = &mut self.config.split_ratios;
+        let resized = if self.show_console && self.console_focus {
+            ratios.resize_console(delta)
+        } else if self.pane_count > 1 {
+            let index = match self.active_pane {
+                Pane::Left => 0,
+                Pane::Center => 1,
+                Pane::Right => 2,
+            };
+            ratios.resize_pane(self.pane_count, index, delta)
+        } else if self.show_console {
+            ratios.resize_console(-delta)
+        } else {
+            self.status_message = Some("Nothing to resize: open another pane or the console".to_string());
+            return;
+        };
+
+        if resized {
+            let _ = self.config.save();
+            let ratios = &self.config.split_ratios;
+            let panes = ratios.pane_widths(self.pane_count).iter().map(|w| format!("{}%", w)).collect::<Vec<_>>().join(" | ");
+            self.status_message = Some(if self.show_console {
+                format!("Split: {} | console {}%", panes, ratios.console_percent())
+            } else {
+                format!("Split: {}", panes)
+            });
+        } else {
+            self.status_message = Some("Can't resize further".to_string());
+        }
+    }
+
     /// Switch to left pane (Ctrl+Left)
     pub fn switch_pane_left(&mut self) {
         if self.pane_count > 1 {
@@ -456,21 +1599,30 @@ impl App {
         }
     }
     
+    /// Shell of the console tab being shown
+    pub fn console(&self) -> &ShellState {
+        &self.consoles[self.active_console]
+    }
+
+    pub fn console_mut(&mut self) -> &mut ShellState {
+        &mut self.consoles[self.active_console]
+    }
+
     /// Toggle console panel (right side panel with PTY passthrough)
     pub fn toggle_console(&mut self) {
         if self.show_console {
-            // Closing console - stop PTY session
-            self.console.stop();
-            self.show_console = false;
-            self.console_focus = false;
+            // Closing console - stop every tab's PTY session
+            self.close_console();
         } else {
             // Opening console - start PTY session with current tree's path
-            self.console.working_dir = self.active_fs().current_dir.clone();
-            match self.console.start() {
+            let mut console = ShellState::new(self.active_fs().current_dir.clone());
+            match console.start() {
                 Ok(_) => {
+                    tracing::info!("Console panel started at {:?}", console.working_dir);
+                    self.consoles = vec![console];
+                    self.active_console = 0;
                     self.show_console = true;
                     self.console_focus = true;  // Auto-focus console when opened
-                    tracing::info!("Console panel started at {:?}", self.console.working_dir);
                 }
                 Err(e) => {
                     self.set_temp_message(format!("Failed to start console: {}", e));
@@ -479,6 +1631,142 @@ impl App {
             }
         }
     }
+
+    fn close_console(&mut self) {
+        for console in &mut self.consoles {
+            console.stop();
+        }
+        self.consoles.truncate(1);
+        self.active_console = 0;
+        self.show_console = false;
+        self.console_focus = false;
+    }
+
+    /// Open another shell tab in the console panel, in the browsed directory (F6)
+    pub fn new_console_tab(&mut self) {
+        let mut console = ShellState::new(self.active_fs().current_dir.clone());
+        // Same size as the panel's other tabs
+        console.size = self.console().size;
+        match console.start() {
+            Ok(_) => {
+                self.consoles.push(console);
+                self.active_console = self.consoles.len() - 1;
+                self.status_message = Some(format!("Console tab {} opened", self.consoles.len()));
+            },
+            Err(e) => self.set_temp_message(format!("Failed to start console: {}", e)),
+        }
+    }
+
+    /// Close the shown console tab, closing the panel with the last one (Shift+F6)
+    pub fn close_console_tab(&mut self) {
+        if self.consoles.len() <= 1 {
+            self.close_console();
+            return;
+        }
+        let mut console = self.consoles.remove(self.active_console);
+        console.stop();
+        self.active_console = self.active_console.min(self.consoles.len() - 1);
+    }
+
+    /// Show the next (or previous) console tab (Ctrl+PageDown/PageUp)
+    pub fn switch_console_tab(&mut self, forward: bool) {
+        let count = self.consoles.len();
+        self.active_console = if forward {
+            (self.active_console + 1) % count
+        } else {
+            (self.active_console + count - 1) % count
+        };
+    }
+
+    /// Drop console tabs whose shell exited; returns true if that closed the panel
+    pub fn close_exited_consoles(&mut self) -> bool {
+        if !self.show_console {
+            return false;
+        }
+        let before = self.consoles.len();
+        let active = self.active_console;
+        let mut index = 0;
+        self.consoles.retain_mut(|console| {
+            let keep = console.check_running();
+            if !keep && index < active {
+                // Keep showing the same tab
+                self.active_console -= 1;
+            }
+            index += 1;
+            keep
+        });
+        if self.consoles.is_empty() {
+            self.consoles.push(ShellState::new(self.active_fs().current_dir.clone()));
+            self.active_console = 0;
+            self.show_console = false;
+            self.console_focus = false;
+            return true;
+        }
+        self.active_console = self.active_console.min(self.consoles.len() - 1);
+        if self.consoles.len() < before {
+            self.set_temp_message("Console tab exited".to_string());
+        }
+        false
+    }
+    
+    /// Open the task picker for the active directory
+    pub fn open_task_picker(&mut self) {
+        let tasks = crate::tasks::detect_tasks(&self.active_fs().current_dir);
+        if tasks.is_empty() {
+            self.set_temp_message("No package.json, Makefile, Cargo.toml or justfile found".to_string());
+        } else {
+            self.dialog = DialogMode::TaskPicker { tasks, selected: 0 };
+        }
+    }
+    
+    /// List the templates to create a file or folder from (N)
+    pub fn open_template_picker(&mut self) {
+        let Some(dir) = crate::fs::template::template_dir() else {
+            self.set_temp_message("Could not find the config directory".to_string());
+            return;
+        };
+        let templates = crate::fs::template::list_templates(&dir);
+        if templates.is_empty() {
+            self.set_temp_message(format!("No templates yet: add files or folders to {}", dir.display()));
+        } else {
+            self.dialog = DialogMode::TemplatePicker { templates, selected: 0 };
+        }
+    }
+
+    /// Copy `template` into the active directory as `name`; files open in the editor
+    pub fn create_from_template(&mut self, template: &crate::fs::template::Template, name: &str) {
+        let target_dir = crate::navigation::get_active_directory(self.active_fs())
+            .unwrap_or_else(|| self.active_fs().current_dir.clone());
+        match crate::fs::template::instantiate(template, &target_dir, name) {
+            Ok(created) => {
+                self.history.push(crate::fs::history::FileOperation::created(created.clone()));
+                self.refresh_both_panes();
+                self.status_message = Some(format!("Created '{}' from template '{}'", name, template.name));
+                if created.is_file() {
+                    self.open_in_viewer(&created);
+                    crate::events::enter_edit_mode(self);
+                }
+            },
+            Err(e) => self.status_message = Some(format!("Create from template failed: {}", e)),
+        }
+    }
+
+    /// Run a command in the console panel, opening it if needed
+    pub fn run_in_console(&mut self, dir: &std::path::Path, command: &str) {
+        if !self.show_console {
+            self.toggle_console();
+            if !self.show_console {
+                return; // Failed to start (message already shown)
+            }
+        }
+        self.console_focus = true;
+        
+        let line = format!("cd {} && {}\r", shell_quote(dir), command);
+        match self.console_mut().write(line.as_bytes()) {
+            Ok(_) => self.status_message = Some(format!("Running: {}", command)),
+            Err(e) => self.status_message = Some(format!("Failed to run task: {}", e)),
+        }
+    }
     
     /// Switch focus between file manager and console panel
     #[allow(dead_code)]
@@ -489,52 +1777,159 @@ impl App {
     }
     
     /// Process file watcher events and refresh UI (call from event loop)
-    #[allow(dead_code)]
+    /// Listings are refreshed once a burst of changes settles, and only in the
+    /// panes showing an affected directory.
     pub fn process_file_watcher(&mut self) {
-        if let Some(watcher) = &self.file_watcher {
-            let changes = watcher.poll_changes();
-            
-            if !changes.is_empty() {
-                // Refresh file panels if any changes were detected
-                self.refresh_both_panes();
+        let Some(watcher) = &self.file_watcher else { return };
+        let changes: Vec<FileChange> = watcher.poll_changes()
+            .into_iter()
+            .filter(|c| c.kind != FileChangeKind::Other)
+            .collect();
+        let now = Instant::now();
+
+        if !changes.is_empty() {
+            // Flag the open file if it changed since it was read (our own saves update the mtime)
+            let viewed = self.viewer_path.as_ref()
+                .filter(|path| changes.iter().any(|c| &c.path == *path));
+            if let Some(path) = viewed {
+                if file_mtime(path) != self.viewer_mtime {
+                    self.viewer_changed_on_disk = true;
+                }
+            }
+            self.watch_debounce.add(changes.iter().flat_map(|c| c.affected_dirs()), now);
+        }
+
+        let Some(dirs) = self.watch_debounce.take_ready(now) else { return };
+        let mut refreshed = false;
+        for fs in [&mut self.fs_left, &mut self.fs_center, &mut self.fs_right] {
+            refreshed |= fs.refresh_dirs(&dirs);
+        }
+        if refreshed {
+            if let Some(overlay) = &mut self.git_overlay {
+                overlay.mark_dirty();
             }
         }
     }
     
+    /// Directories currently shown in the open local panes (not watched or git-scanned when remote)
+    fn visible_pane_dirs(&self) -> Vec<PathBuf> {
+        [&self.fs_left, &self.fs_center, &self.fs_right]
+            .into_iter()
+            .take(self.pane_count)
+            .filter(|fs| !fs.is_remote())
+            .map(|fs| fs.current_dir.clone())
+            .collect()
+    }
+    
     /// Start watching the current directories
-    #[allow(dead_code)]
     pub fn start_watching_dirs(&mut self) {
-        if let Some(watcher) = &mut self.file_watcher {
-            // Watch all active pane directories
-            let _ = watcher.watch(&self.fs_left.current_dir);
-            if self.pane_count >= 2 {
-                let _ = watcher.watch(&self.fs_center.current_dir);
-            }
-            if self.pane_count >= 3 {
-                let _ = watcher.watch(&self.fs_right.current_dir);
-            }
-        }
+        self.update_watched_dirs();
     }
     
     /// Update watched directories when navigation changes
-    #[allow(dead_code)]
     pub fn update_watched_dirs(&mut self) {
-        if let Some(watcher) = &mut self.file_watcher {
-            // Unwatch all and rewatch current directories
-            watcher.unwatch_all();
-            let _ = watcher.watch(&self.fs_left.current_dir);
-            if self.pane_count >= 2 {
-                let _ = watcher.watch(&self.fs_center.current_dir);
+        let mut dirs = self.visible_pane_dirs();
+        // The viewed file's directory, so changes to it can be flagged
+        if self.mode == AppMode::Viewer {
+            if let Some(parent) = self.viewer_path.as_ref().and_then(|p| p.parent()) {
+                if !dirs.iter().any(|d| d == parent) {
+                    dirs.push(parent.to_path_buf());
+                }
             }
-            if self.pane_count >= 3 {
-                let _ = watcher.watch(&self.fs_right.current_dir);
+        }
+        if let Some(watcher) = &mut self.file_watcher {
+            // Only (un)watch the directories that actually changed
+            watcher.sync_paths(&dirs);
+        }
+    }
+    
+    /// Number of directories being watched (0 when the watcher is disabled)
+    pub fn watch_count(&self) -> usize {
+        self.file_watcher.as_ref().map(|w| w.watch_count()).unwrap_or(0)
+    }
+    
+    /// Globally enable or disable the file watcher
+    pub fn set_watcher_enabled(&mut self, enabled: bool) {
+        self.config.enable_watcher = enabled;
+        let _ = self.config.save();
+        
+        if enabled {
+            if self.file_watcher.is_none() {
+                match FileWatcher::new() {
+                    Ok(watcher) => self.file_watcher = Some(watcher),
+                    Err(e) => {
+                        tracing::warn!("Failed to start file watcher: {}", e);
+                        self.status_message = Some(format!("File watcher unavailable: {}", e));
+                        return;
+                    }
+                }
             }
+            self.start_watching_dirs();
+        } else {
+            // Dropping the watcher unwatches everything
+            self.file_watcher = None;
         }
     }
     
+    /// Send `cd <active directory>` to the console shell ('z')
+    /// `quiet` skips the status message when nothing needed to change.
+    pub fn send_dir_to_console(&mut self, quiet: bool) {
+        if !self.show_console || !self.console().is_running {
+            if !quiet {
+                self.set_temp_message("Console is not open (F5)".to_string());
+            }
+            return;
+        }
+        let dir = self.active_fs().current_dir.clone();
+        if self.console().cwd().as_deref() == Some(dir.as_path()) {
+            if !quiet {
+                self.set_temp_message(format!("Console is already in {}", dir.display()));
+            }
+            return;
+        }
+        if self.console().is_busy() {
+            self.set_temp_message("Console is running a command; not sending cd".to_string());
+            return;
+        }
+        match self.console_mut().cd(&dir) {
+            Ok(_) => self.status_message = Some(format!("Console: cd {}", dir.display())),
+            Err(e) => self.set_temp_message(format!("Failed to write to console: {}", e)),
+        }
+    }
+
+    /// Move the active pane to the console shell's current directory ('Z')
+    pub fn follow_console_dir(&mut self) {
+        if !self.show_console || !self.console().is_running {
+            self.set_temp_message("Console is not open (F5)".to_string());
+            return;
+        }
+        match self.console().cwd() {
+            Some(dir) if dir.is_dir() => {
+                self.active_fs_mut().jump_to_dir(dir.clone());
+                self.status_message = Some(format!("Jumped to console directory {}", dir.display()));
+            },
+            _ => self.set_temp_message("Could not read the console's directory".to_string()),
+        }
+    }
+
+    /// Focus moved into the console: follow the browsed directory if `sync_shell_cwd` is set
+    fn on_console_focused(&mut self) {
+        if self.config.sync_shell_cwd {
+            self.send_dir_to_console(true);
+        }
+    }
+
     /// Cycle focus forward through panes and console (Tab)
     /// Order: Left → Center → Right → Console → Left ...
     pub fn cycle_focus_forward(&mut self) {
+        let was_console = self.console_focus;
+        self.cycle_focus_forward_inner();
+        if self.console_focus && !was_console {
+            self.on_console_focused();
+        }
+    }
+
+    fn cycle_focus_forward_inner(&mut self) {
         if self.show_console {
             if self.console_focus {
                 // Console focused → go to first pane
@@ -574,6 +1969,14 @@ impl App {
     /// Cycle focus backward through panes and console (Shift+Tab)
     /// Order: Console → Right → Center → Left → Console ...
     pub fn cycle_focus_backward(&mut self) {
+        let was_console = self.console_focus;
+        self.cycle_focus_backward_inner();
+        if self.console_focus && !was_console {
+            self.on_console_focused();
+        }
+    }
+
+    fn cycle_focus_backward_inner(&mut self) {
         if self.show_console {
             if self.console_focus {
                 // Console focused → go to last pane
@@ -623,11 +2026,79 @@ impl App {
         }
     }
 
+    /// Refresh the footer clock and disk space, at most once per second
+    fn update_footer_info(&mut self) {
+        let now = Instant::now();
+        if self.footer_info.updated.is_some_and(|t| now.duration_since(t) < std::time::Duration::from_secs(1)) {
+            return;
+        }
+        self.footer_info.updated = Some(now);
+        self.footer_info.clock = self.config.show_clock
+            .then(|| chrono::Local::now().format("%H:%M").to_string());
+        self.footer_info.disk = if self.config.show_disk_space {
+            let dir = self.active_fs().current_dir.clone();
+            self.system.disk_space(&dir)
+        } else {
+            None
+        };
+    }
+
     pub fn on_tick(&mut self, _dt: std::time::Duration) {
         if let AppMode::SystemMonitor = self.mode {
             self.system.refresh();
         }
         
+        self.poll_paste_job();
+        self.poll_extract_job();
+        if self.type_ahead.as_ref().is_some_and(|t| t.is_expired(Instant::now())) {
+            self.type_ahead = None;
+        }
+        self.poll_search_job();
+        self.poll_grep_job();
+        self.poll_du_job();
+        self.poll_url_fetch();
+        #[cfg(feature = "sftp")]
+        self.poll_sftp_connect();
+        self.poll_remote_download();
+        self.poll_viewer_follow();
+        self.update_footer_info();
+        self.extend_viewer_highlight();
+        self.index_large_viewer_file();
+        let browsed_dir = self.active_fs().current_dir.clone();
+        self.plugins.set_current_path(&browsed_dir);
+        let now = Instant::now();
+        let selection = (self.mode == AppMode::FileManager)
+            .then(|| self.active_fs().selected_entry())
+            .flatten()
+            .map(|path| (self.active_pane, path));
+        self.selection_debounce.observe(selection, now);
+        let settled = self.selection_debounce.is_settled(now);
+        if settled {
+            self.record_dir_visits();
+            self.notify_file_selected();
+            self.update_file_preview();
+        }
+        self.apply_plugin_effects();
+        for reload in self.plugins.poll_reloads() {
+            self.status_message = Some(reload.unwrap_or_else(|e| e));
+        }
+
+        // Keep the watcher in sync with the visible directories
+        if self.file_watcher.is_some() {
+            self.update_watched_dirs();
+            self.process_file_watcher();
+        }
+
+        if self.git_overlay.is_some() {
+            let dirs = settled.then(|| self.visible_pane_dirs());
+            if let Some(overlay) = &mut self.git_overlay {
+                if let Some(dirs) = dirs {
+                    overlay.sync_dirs(&dirs);
+                }
+                overlay.tick();
+            }
+        }
+        
         // Handle shell PTY reading (popup mode)
         if self.show_shell && self.shell.is_running {
             let _ = self.shell.read_and_parse();
@@ -638,12 +2109,12 @@ impl App {
             }
         }
         
-        // Handle console PTY reading (panel mode)
-        if self.show_console && self.console.is_running {
-            let _ = self.console.read_and_parse();
-            
-            // Check if console process is still running
-            if !self.console.check_running() {
+        // Handle console PTY reading (panel mode); background tabs keep reading too
+        if self.show_console {
+            for console in self.consoles.iter_mut().filter(|c| c.is_running) {
+                let _ = console.read_and_parse();
+            }
+            if self.close_exited_consoles() {
                 self.set_temp_message("Console process exited".to_string());
             }
         }
@@ -653,6 +2124,7 @@ impl App {
             if self.process_viewer.last_refresh.elapsed() >= std::time::Duration::from_secs(1) {
                 self.process_viewer.refresh();
             }
+            self.process_viewer.update_resources();
         }
         
         // Auto-clear temporary messages after 0.5 seconds
@@ -686,4 +2158,696 @@ impl App {
     pub fn set_temp_message(&mut self, message: String) {
         self.temp_message = Some((message, Instant::now()));
     }
+
+    /// Paste the active pane's clipboard in the background (progress shows in the status bar)
+    pub fn start_paste(&mut self) {
+        if self.paste_job.is_some() {
+            self.status_message = Some("A paste is already in progress (Esc to cancel)".to_string());
+            return;
+        }
+        let pane = self.active_pane;
+        match self.active_fs_mut().paste() {
+            Some(job) => {
+                self.status_message = None;
+                self.paste_job = Some((pane, job));
+            },
+            None => self.status_message = Some("Paste: nothing to do".to_string()),
+        }
+    }
+
+    /// Cancel the running paste; returns false if there is none
+    pub fn cancel_paste(&mut self) -> bool {
+        match &self.paste_job {
+            Some((_, job)) => {
+                job.cancel();
+                self.status_message = Some("Cancelling paste...".to_string());
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Show the diff of two files in the viewer: the two marked files, or the marked one and the selection
+    pub fn open_diff(&mut self) {
+        let mut marked: Vec<PathBuf> = self.active_fs().marked.iter().filter(|p| p.is_file()).cloned().collect();
+        marked.sort();
+        let selected = crate::navigation::get_active_directory(self.active_fs()).and_then(|dir| {
+            let entries = self.active_fs().entries_for_dir(&dir);
+            entries.get(self.active_fs().get_selection(&dir)).filter(|p| p.is_file()).cloned()
+        });
+
+        // Two marked files, or one marked file against the selection
+        let pair = match (marked.as_slice(), selected) {
+            ([old, new], _) => Some((old.clone(), new.clone())),
+            ([old], Some(new)) if *old != new => Some((old.clone(), new)),
+            _ => None,
+        };
+        let Some((old, new)) = pair else {
+            self.status_message = Some("Compare: mark a file (Space), select another and press =".to_string());
+            return;
+        };
+
+        match crate::viewer::diff::diff_files(&old, &new) {
+            Ok(diff) => {
+                self.viewer_content = Some(crate::viewer::ViewerContent::Diff(diff));
+                self.viewer_path = None;
+                self.viewer_follow = None;
+                self.viewer_scroll = 0;
+                self.viewer_search = None;
+                self.mode = AppMode::Viewer;
+            },
+            Err(e) => self.status_message = Some(format!("Compare failed: {}", e)),
+        }
+    }
+
+    /// Extract the selected archive into a folder named after it (runs in the background)
+    pub fn start_extract(&mut self) {
+        if self.extract_job.is_some() {
+            self.status_message = Some("An extraction is already in progress (Esc to cancel)".to_string());
+            return;
+        }
+        let Some(active_dir) = crate::navigation::get_active_directory(self.active_fs()) else { return };
+        let entries = self.active_fs().entries_for_dir(&active_dir);
+        let selected_index = self.active_fs().get_selection(&active_dir);
+        let Some(path) = entries.get(selected_index).filter(|p| p.is_file()).cloned() else { return };
+
+        match crate::fs::ExtractJob::start(path, &active_dir) {
+            Some(job) => {
+                self.status_message = None;
+                self.extract_job = Some((self.active_pane, job));
+            },
+            None => self.status_message = Some("Extract: not a supported archive (.zip, .tar, .tar.gz, .tgz)".to_string()),
+        }
+    }
+
+    /// Cancel the running extraction; returns false if there is none
+    pub fn cancel_extract(&mut self) -> bool {
+        match &self.extract_job {
+            Some((_, job)) => {
+                job.cancel();
+                self.status_message = Some("Cancelling extraction...".to_string());
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Download `url` in the background and open it in the viewer when done (`:open URL`)
+    pub fn open_url(&mut self, url: &str) {
+        if let Err(e) = crate::viewer::url::check_scheme(url) {
+            self.status_message = Some(e);
+            return;
+        }
+        // A new download replaces the running one, which is told to stop
+        if let Some(previous) = self.url_fetch.take() {
+            previous.cancel();
+        }
+        self.url_fetch = Some(crate::viewer::url::UrlFetch::start(url.to_string(), self.config.url_max_bytes));
+    }
+
+    /// Cancel the running URL download; returns false if there is none
+    pub fn cancel_url_fetch(&mut self) -> bool {
+        match &self.url_fetch {
+            Some(job) => {
+                job.cancel();
+                self.status_message = Some("Cancelling download...".to_string());
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Restart the '/' search for `query`, cancelling any walk still running
+    pub fn restart_search(&mut self, query: &str) {
+        // Dropping the old job cancels its walk
+        self.search_job = None;
+        if let DialogMode::Search { results, selected, .. } = &mut self.dialog {
+            results.clear();
+            *selected = 0;
+        }
+        if query.is_empty() {
+            return;
+        }
+
+        let fs = self.active_fs();
+        self.search_job = Some(crate::fs::SearchJob::start(
+            fs.current_dir.clone(),
+            query.to_string(),
+            self.config.search_depth,
+            fs.show_hidden,
+        ));
+    }
+
+    /// Stream new search results into the open search dialog
+    fn poll_search_job(&mut self) {
+        let Some(job) = &mut self.search_job else { return };
+        let DialogMode::Search { results, selected, .. } = &mut self.dialog else {
+            // Dialog was closed; stop walking
+            self.search_job = None;
+            return;
+        };
+
+        if let Some(latest) = job.poll() {
+            // Keep the highlighted result under the cursor as the list reorders
+            let current = results.get(*selected).map(|(path, _)| path.clone());
+            *results = latest;
+            *selected = current
+                .and_then(|path| results.iter().position(|(p, _)| *p == path))
+                .unwrap_or(0);
+        }
+        if job.is_done() {
+            self.search_job = None;
+        }
+    }
+
+    /// Open the content search prompt (Ctrl+/ by default)
+    pub fn open_grep(&mut self) {
+        if self.active_fs().is_remote() {
+            self.status_message = Some("Content search only works in local panes".to_string());
+            return;
+        }
+        self.grep_job = None;
+        self.dialog = DialogMode::Grep { query: String::new(), hits: Vec::new(), selected: 0 };
+    }
+
+    /// Restart the content search for `query`, cancelling any walk still running
+    pub fn restart_grep(&mut self, query: &str) {
+        // Dropping the old job cancels its walk
+        self.grep_job = None;
+        if let DialogMode::Grep { hits, selected, .. } = &mut self.dialog {
+            hits.clear();
+            *selected = 0;
+        }
+        if query.is_empty() {
+            return;
+        }
+
+        let fs = self.active_fs();
+        self.grep_job = Some(crate::fs::GrepJob::start(
+            fs.current_dir.clone(),
+            query.to_string(),
+            self.config.search_depth,
+            fs.show_hidden,
+        ));
+    }
+
+    /// Rename the marked entries together (F2 with marks)
+    pub fn open_batch_rename(&mut self) {
+        let paths = self.active_fs().selected_paths();
+        self.dialog = DialogMode::BatchRename { paths, find: String::new(), replace: String::new(), editing_replace: false };
+    }
+
+    /// Rename everything in `plan` at once and report the outcome
+    pub fn apply_batch_rename(&mut self, plan: &crate::fs::rename::RenamePlan) {
+        if plan.is_empty() {
+            self.status_message = Some("Batch rename: no names changed".to_string());
+            return;
+        }
+        self.status_message = Some(match crate::fs::rename::apply_plan(plan) {
+            Ok(count) => {
+                self.history.push(crate::fs::history::FileOperation::Renamed(plan.clone()));
+                self.active_fs_mut().marked.clear();
+                self.refresh_both_panes();
+                format!("Renamed {} items", count)
+            },
+            Err(e) => format!("Batch rename aborted, nothing renamed: {}", e),
+        });
+    }
+
+    /// Edit the names of `paths` as lines in the built-in editor; they are renamed when it closes
+    pub fn edit_batch_rename(&mut self, paths: Vec<PathBuf>) {
+        // Created exclusively and private to this user, so nobody else can plant or swap the list
+        let created = tempfile::Builder::new().prefix("senterm-rename-").suffix(".txt").tempfile()
+            .and_then(|mut list| list.write_all(crate::fs::rename::name_list(&paths).as_bytes()).map(|_| list));
+        let list = match created {
+            Ok(list) => list,
+            Err(e) => {
+                self.status_message = Some(format!("Batch rename failed: {}", e));
+                return;
+            }
+        };
+        self.open_in_viewer(list.path());
+        crate::events::enter_edit_mode(self);
+        if let Some(editor) = &mut self.text_editor {
+            editor.status_message = "Edit the names (one per line), then :wq to rename or :q! to cancel".to_string();
+        }
+        self.batch_rename = Some((list, paths));
+    }
+
+    /// Apply the saved name list once the editor closes
+    pub fn finish_batch_rename(&mut self) {
+        let Some((list, paths)) = self.batch_rename.take() else { return };
+        let text = std::fs::read_to_string(list.path()).unwrap_or_default();
+        drop(list);
+        match crate::fs::rename::edited_plan(&paths, &text) {
+            Ok(plan) => self.apply_batch_rename(&plan),
+            Err(e) => self.status_message = Some(format!("Batch rename aborted, nothing renamed: {}", e)),
+        }
+    }
+
+    /// Reverse the most recent file operation (u)
+    /// With nothing recorded, falls back to restoring the latest trash deletion (possibly from an earlier session).
+    pub fn undo_last_operation(&mut self) {
+        let trash_root = crate::fs::trash::trash_dir();
+        let Some(operation) = self.history.pop() else {
+            match trash_root.and_then(|root| crate::fs::trash::restore_latest(&root)) {
+                Some(summary) => {
+                    self.report_operation(summary);
+                    self.refresh_both_panes();
+                },
+                None => self.status_message = Some("Nothing to undo".to_string()),
+            }
+            return;
+        };
+
+        match operation.undo(trash_root.as_deref()) {
+            Ok(message) => self.status_message = Some(message),
+            Err(e) => {
+                // Older entries may depend on the state this one expected
+                self.history.clear();
+                self.status_message = Some(format!("Can't undo: {} (undo history cleared)", e));
+            },
+        }
+        self.refresh_both_panes();
+    }
+
+    /// Show the metadata of the selected entry (I)
+    pub fn open_properties(&mut self) {
+        let Some(path) = self.active_fs().selected_paths().into_iter().next() else { return };
+        match crate::fs::properties::read_properties(&path) {
+            Ok(rows) => self.dialog = DialogMode::Properties { path, rows },
+            Err(e) => self.status_message = Some(format!("Can't read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Open the permission editor for `path` (Unix only)
+    pub fn open_chmod(&mut self, path: PathBuf) {
+        if path.is_symlink() {
+            self.status_message = Some(format!(
+                "'{}' is a symlink; change the permissions of its target instead",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            ));
+            return;
+        }
+        match crate::fs::properties::permission_mode(&path) {
+            Some(mode) => {
+                let octal = format_octal_mode(mode);
+                self.dialog = DialogMode::Chmod { path, mode, cursor: 0, octal, typed: false, confirm: false };
+            },
+            None => self.status_message = Some("Changing permissions is only supported on Unix".to_string()),
+        }
+    }
+
+    /// Apply the confirmed permission change and refresh the panes (executable colors may change)
+    pub fn apply_chmod(&mut self, path: &std::path::Path, mode: u32) {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        self.status_message = Some(match crate::fs::properties::set_permission_mode(path, mode) {
+            Ok(()) => {
+                self.refresh_both_panes();
+                format!("Permissions of '{}' set to {} ({})", name, format_octal_mode(mode), crate::fs::properties::symbolic_mode(mode))
+            },
+            Err(e) => format!("chmod '{}' failed: {}", name, e),
+        });
+    }
+
+    /// Open the disk usage overlay for the selected directory (or the active one)
+    pub fn open_disk_usage(&mut self) {
+        let Some(active_dir) = crate::navigation::get_active_directory(self.active_fs()) else { return };
+        let entries = self.active_fs().entries_for_dir(&active_dir);
+        let selected = self.active_fs().get_selection(&active_dir);
+        let dir = entries.get(selected)
+            .filter(|p| p.is_dir() && active_dir.parent() != Some(p.as_path()))
+            .cloned()
+            .unwrap_or(active_dir);
+        self.disk_usage = Some(crate::fs::du::DiskUsageView::default());
+        self.show_disk_usage(dir, None);
+    }
+
+    /// Show `dir` in the disk usage overlay, scanning it unless its sizes are cached
+    /// `highlight` selects that child (the directory we came back up from).
+    pub fn show_disk_usage(&mut self, dir: PathBuf, highlight: Option<&std::path::Path>) {
+        let Some(view) = &mut self.disk_usage else { return };
+        view.selected = highlight
+            .and_then(|path| self.du_cache.get(&dir)?.iter().position(|e| e.path == path))
+            .unwrap_or(0);
+        view.job = (!self.du_cache.contains_key(&dir)).then(|| crate::fs::DuJob::start(dir.clone()));
+        view.dir = dir;
+    }
+
+    /// Forget the cached sizes below the shown directory and scan it again
+    pub fn rescan_disk_usage(&mut self) {
+        let Some(view) = &self.disk_usage else { return };
+        let dir = view.dir.clone();
+        self.du_cache.retain(|path, _| !path.starts_with(&dir));
+        self.show_disk_usage(dir, None);
+    }
+
+    /// Pick up the finished disk usage scan
+    fn poll_du_job(&mut self) {
+        let Some(job) = self.disk_usage.as_mut().and_then(|view| view.job.as_mut()) else { return };
+        if let Some(tree) = job.poll() {
+            // Keep the cache bounded: past the cap, only the directory just scanned stays cached
+            if self.du_cache.len() + tree.len() > DU_CACHE_MAX_DIRS {
+                self.du_cache.clear();
+            }
+            self.du_cache.extend(tree);
+        }
+        if job.is_done() {
+            if let Some(view) = &mut self.disk_usage {
+                view.job = None;
+            }
+        }
+    }
+
+    /// Stream new content search hits into the open grep dialog
+    fn poll_grep_job(&mut self) {
+        let Some(job) = &mut self.grep_job else { return };
+        let DialogMode::Grep { hits, .. } = &mut self.dialog else {
+            // Dialog was closed; stop walking
+            self.grep_job = None;
+            return;
+        };
+
+        // Hits only ever grow, so the highlighted index stays valid
+        if let Some(latest) = job.poll() {
+            *hits = latest;
+        }
+        if job.is_done() {
+            self.grep_job = None;
+        }
+    }
+
+    /// View a finished URL download
+    fn poll_url_fetch(&mut self) {
+        let Some(job) = &mut self.url_fetch else { return };
+        let Some(result) = job.poll() else { return };
+        let url = std::mem::take(&mut job.url);
+        self.url_fetch = None;
+        match result {
+            Ok(fetched) => {
+                self.text_editor = None;
+                self.viewer_editing = false;
+                self.open_in_viewer(&fetched.path);
+                // Replacing the previous download deletes its copy
+                self.download_dir = Some(fetched.dir);
+                self.status_message = Some(if fetched.truncated {
+                    format!("{} (cut off at url_max_bytes = {})", url, self.config.url_max_bytes)
+                } else {
+                    url
+                });
+            },
+            Err(e) => self.status_message = Some(format!("Couldn't open {}: {}", url, e)),
+        }
+    }
+
+    /// Pick up progress and completion of the background paste
+    fn poll_paste_job(&mut self) {
+        let Some((pane, job)) = &mut self.paste_job else { return };
+        let Some(summary) = job.poll() else { return };
+        let pane = *pane;
+        let op = job.operation;
+        self.paste_job = None;
+
+        if !summary.destinations.is_empty() {
+            use crate::fs::history::FileOperation;
+            self.history.push(match op {
+                crate::fs::ClipboardOperation::Cut => FileOperation::Moved(summary.destinations.clone()),
+                crate::fs::ClipboardOperation::Copy => FileOperation::copied(summary.destinations.iter().map(|(_, to)| to.clone()).collect()),
+            });
+        }
+
+        // Keep failed moves in the clipboard so they can be retried
+        if op == crate::fs::ClipboardOperation::Cut {
+            let failed: Vec<std::path::PathBuf> = summary.items.iter()
+                .filter(|item| matches!(item.outcome, crate::fs::ItemOutcome::Failed(_)))
+                .map(|item| item.path.clone())
+                .collect();
+            if !failed.is_empty() {
+                self.fs_for_pane_mut(pane).clipboard = Some((failed, op));
+            }
+        }
+
+        self.report_operation(summary);
+        self.refresh_both_panes();
+    }
+
+    /// Pick up progress and completion of the background extraction
+    fn poll_extract_job(&mut self) {
+        let Some((pane, job)) = &mut self.extract_job else { return };
+        let Some(outcome) = job.poll() else { return };
+        let pane = *pane;
+        self.extract_job = None;
+
+        let folder = outcome.target.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let problems = outcome.problems.items.len();
+        let mut message = format!("Extracted {} files into '{}/'", outcome.extracted, folder);
+        if problems > 0 {
+            message.push_str(&format!(", {} skipped or failed", problems));
+            self.operation_summary = Some(outcome.problems);
+            self.summary_scroll = 0;
+        }
+        self.status_message = Some(message);
+
+        self.refresh_both_panes();
+        // Select the new folder so it can be entered right away
+        let fs = self.fs_for_pane_mut(pane);
+        if let Some(dir) = outcome.target.parent().map(|p| p.to_path_buf()) {
+            if let Some(index) = fs.entries_for_dir(&dir).iter().position(|p| *p == outcome.target) {
+                fs.set_selection(dir, index);
+            }
+        }
+    }
+
+    /// Copy the marked paths (or the selected one) to the system clipboard, one per line
+    /// `names_only` copies just the file names ('Y') instead of absolute paths ('y').
+    pub fn copy_paths_to_clipboard(&mut self, names_only: bool) {
+        let paths = self.active_fs().selected_paths();
+        if paths.is_empty() {
+            return;
+        }
+        let text = clipboard_path_text(&paths, names_only);
+        let what = match (names_only, paths.len()) {
+            (true, 1) => "name".to_string(),
+            (false, 1) => "path".to_string(),
+            (true, n) => format!("{} names", n),
+            (false, n) => format!("{} paths", n),
+        };
+        let message = match crate::events::utils::copy_to_system_clipboard(&text) {
+            Ok(_) if paths.len() == 1 => format!("Copied {}: {}", what, text),
+            Ok(_) => format!("Copied {}", what),
+            Err(e) => format!("Copy failed: {}", e),
+        };
+        self.set_temp_message(message);
+    }
+
+    /// Status message after copying/cutting to the clipboard
+    pub fn clipboard_status(&self, verb: &str) -> String {
+        match &self.active_fs().clipboard {
+            Some((paths, _)) if paths.len() > 1 => format!("{} {} items to clipboard", verb, paths.len()),
+            _ => format!("{} to clipboard", verb),
+        }
+    }
+
+    /// Report the result of a file operation
+    /// Single items only update the status line; batches also open the summary popup
+    pub fn report_operation(&mut self, summary: crate::fs::OperationSummary) {
+        if summary.is_empty() {
+            self.status_message = Some(format!("{}: nothing to do", summary.operation));
+            return;
+        }
+        self.status_message = Some(summary.status_line());
+        if summary.is_batch() {
+            self.operation_summary = Some(summary);
+            self.summary_scroll = 0;
+        }
+    }
+}
+
+/// Quote a path for a POSIX shell ('...' with embedded quotes escaped)
+pub(crate) fn shell_quote(path: &std::path::Path) -> String {
+    format!("'{}'", path.display().to_string().replace('\'', "'\\''"))
+}
+
+/// Newline-joined absolute paths, or file names when `names_only`
+fn clipboard_path_text(paths: &[PathBuf], names_only: bool) -> String {
+    paths.iter()
+        .map(|path| {
+            if names_only {
+                path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+            } else {
+                std::path::absolute(path).unwrap_or_else(|_| path.clone()).display().to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_shell_scrollback_stays_within_one_screen() {
+        let mut shell = ShellState::new(PathBuf::from("/"));
+        {
+            let mut parser = shell.parser.lock().unwrap();
+            for line in 0..200 {
+                parser.process(format!("line {}\r\n", line).as_bytes());
+            }
+        }
+        shell.scroll_back(1000);
+        {
+            let parser = shell.parser.lock().unwrap();
+            assert_eq!(parser.screen().scrollback(), 24);
+            assert!(parser.screen().contents().contains("line 160"));
+        }
+
+        // Shrinking the screen pulls the offset in as well
+        shell.resize(80, 10);
+        let parser = shell.parser.lock().unwrap();
+        assert_eq!(parser.screen().scrollback(), 10);
+        let _ = parser.screen().contents();
+    }
+
+    #[test]
+    fn test_bookmark_list_filter() {
+        use crate::config::Bookmark;
+        let bookmarks = vec![
+            Bookmark::new(PathBuf::from("/home/me/Documents"), ""),
+            Bookmark::new(PathBuf::from("/srv/www"), "Web"),
+            Bookmark::new(PathBuf::from("/home/me/src/web-app"), ""),
+        ];
+        let mut list = BookmarkList::default();
+        assert_eq!(list.matches(&bookmarks), vec![0, 1, 2]);
+
+        // Label or path, ignoring case
+        list.filter = "web".to_string();
+        assert_eq!(list.matches(&bookmarks), vec![1, 2]);
+        list.selected = 1;
+        assert_eq!(list.selected_bookmark(&bookmarks), Some(2));
+
+        list.filter = "home".to_string();
+        assert_eq!(list.matches(&bookmarks), vec![0, 2]);
+        list.filter = "nothing".to_string();
+        assert_eq!(list.selected_bookmark(&bookmarks), None);
+    }
+
+    #[test]
+    fn test_jump_list_ranks_by_frecency() {
+        use crate::config::DirVisit;
+        let visit = |path: &str, visits, last_visit| DirVisit { path: PathBuf::from(path), visits, last_visit };
+        let now = 1_000_000;
+        let history = vec![
+            visit("/home/me/old-project", 10, 0),
+            visit("/home/me/project", 3, now - 60),
+            visit("/srv/www", 1, now),
+        ];
+        let mut list = JumpList::default();
+        // 3 recent visits beat 10 old ones
+        assert_eq!(list.matches(&history, now), vec![1, 2, 0]);
+
+        // Filtering keeps the ranking
+        list.filter = "PROJECT".to_string();
+        assert_eq!(list.matches(&history, now), vec![1, 0]);
+        list.selected = 1;
+        assert_eq!(list.selected_entry(&history, now), Some(0));
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote(std::path::Path::new("/tmp/my dir")), "'/tmp/my dir'");
+        assert_eq!(shell_quote(std::path::Path::new("/tmp/it's")), "'/tmp/it'\\''s'");
+    }
+
+    #[test]
+    fn test_clipboard_path_text() {
+        let paths = vec![PathBuf::from("/tmp/a.txt"), PathBuf::from("/home/user/src")];
+        assert_eq!(clipboard_path_text(&paths, false), "/tmp/a.txt\n/home/user/src");
+        assert_eq!(clipboard_path_text(&paths, true), "a.txt\nsrc");
+        assert_eq!(clipboard_path_text(&paths[..1], true), "a.txt");
+    }
+
+    #[test]
+    fn test_selection_debounce() {
+        let t0 = Instant::now();
+        let mut debounce = SelectionDebounce::default();
+        assert!(debounce.is_settled(t0));
+
+        debounce.observe(Some((Pane::Left, PathBuf::from("a"))), t0);
+        assert!(!debounce.is_settled(t0 + SELECTION_SETTLE / 2));
+        // Moving on restarts the wait; staying put doesn't
+        let t1 = t0 + SELECTION_SETTLE / 2;
+        debounce.observe(Some((Pane::Left, PathBuf::from("b"))), t1);
+        debounce.observe(Some((Pane::Left, PathBuf::from("b"))), t1 + SELECTION_SETTLE / 2);
+        assert!(!debounce.is_settled(t0 + SELECTION_SETTLE));
+        assert!(debounce.is_settled(t1 + SELECTION_SETTLE));
+    }
+
+    #[test]
+    fn test_type_ahead_prefix_contains_and_cycle() {
+        let names: Vec<String> = ["Cargo.toml", "README.md", "src", "scripts", "tests"]
+            .iter().map(|s| s.to_string()).collect();
+        let t0 = Instant::now();
+        let mut type_ahead = TypeAhead::new(t0);
+
+        assert!(!type_ahead.push('s', t0));
+        assert_eq!(type_ahead.find(&names, 0), Some(2));
+        // Same letter again cycles from after the current match
+        assert!(type_ahead.push('s', t0));
+        assert_eq!(type_ahead.buffer, "s");
+        assert_eq!(type_ahead.find(&names, 3), Some(3));
+
+        let mut type_ahead = TypeAhead::new(t0);
+        type_ahead.push('m', t0);
+        type_ahead.push('D', t0);
+        // No name starts with "md", so fall back to substring
+        assert_eq!(type_ahead.find(&names, 0), Some(1));
+
+        assert!(!type_ahead.is_expired(t0 + Duration::from_millis(500)));
+        assert!(type_ahead.is_expired(t0 + TYPE_AHEAD_TIMEOUT + Duration::from_millis(1)));
+    }
+
+    #[test]
+    fn test_double_click_same_position_within_window() {
+        let mut tracker = ClickTracker::default();
+        let t0 = Instant::now();
+        let window = Duration::from_millis(400);
+
+        assert!(!tracker.register(t0, 10, 5, window));
+        assert!(tracker.register(t0 + Duration::from_millis(200), 10, 5, window));
+        // Third click starts a new sequence
+        assert!(!tracker.register(t0 + Duration::from_millis(300), 10, 5, window));
+    }
+
+    #[test]
+    fn test_double_click_too_slow() {
+        let mut tracker = ClickTracker::default();
+        let t0 = Instant::now();
+        let window = Duration::from_millis(400);
+
+        assert!(!tracker.register(t0, 10, 5, window));
+        assert!(!tracker.register(t0 + Duration::from_millis(500), 10, 5, window));
+    }
+
+    #[test]
+    fn test_double_click_different_position() {
+        let mut tracker = ClickTracker::default();
+        let t0 = Instant::now();
+        let window = Duration::from_millis(400);
+
+        assert!(!tracker.register(t0, 10, 5, window));
+        assert!(!tracker.register(t0 + Duration::from_millis(100), 10, 6, window));
+        // Second click on the new position completes a double-click there
+        assert!(tracker.register(t0 + Duration::from_millis(200), 10, 6, window));
+    }
+
+    #[test]
+    fn test_double_click_disabled() {
+        let mut tracker = ClickTracker::default();
+        let t0 = Instant::now();
+
+        assert!(!tracker.register(t0, 1, 1, Duration::ZERO));
+        assert!(!tracker.register(t0, 1, 1, Duration::ZERO));
+    }
 }
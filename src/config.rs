@@ -1,14 +1,154 @@
 use serde::{Deserialize, Serialize};
 use ratatui::style::Color;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SortOption {
     Name,
+    Natural, // Name with digit runs compared by value ("file2" before "file10")
     Size,
     Modified,
 }
 
+impl SortOption {
+    /// Next option in the sort cycle (Name -> Natural -> Size -> Modified)
+    pub fn next(self) -> Self {
+        match self {
+            SortOption::Name => SortOption::Natural,
+            SortOption::Natural => SortOption::Size,
+            SortOption::Size => SortOption::Modified,
+            SortOption::Modified => SortOption::Name,
+        }
+    }
+
+    /// Lowercase name as stored in `.senterm-sort` files
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOption::Name => "name",
+            SortOption::Natural => "natural",
+            SortOption::Size => "size",
+            SortOption::Modified => "modified",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "name" => Some(SortOption::Name),
+            "natural" => Some(SortOption::Natural),
+            "size" => Some(SortOption::Size),
+            "modified" | "date" => Some(SortOption::Modified),
+            _ => None,
+        }
+    }
+}
+
+/// Narrowest a file pane may get, in percent of the file manager width
+pub const MIN_PANE_PERCENT: u16 = 10;
+
+/// Console panel width limits, in percent of the screen
+pub const CONSOLE_PERCENT_RANGE: (u16, u16) = (15, 80);
+
+/// Allowed max_text_preview_bytes, in bytes (t / T in Settings > Interface)
+pub const TEXT_PREVIEW_RANGE: (u64, u64) = (64 * 1024, 256 * 1024 * 1024);
+
+/// Allowed max_binary_preview_bytes, in bytes (b / B in Settings > Interface)
+pub const BINARY_PREVIEW_RANGE: (u64, u64) = (1024 * 1024, 1024 * 1024 * 1024);
+
+/// Split sizes in percent, adjusted at runtime (Ctrl+Shift+Left/Right by default)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SplitRatios {
+    pub console: u16, // Console panel width; the file manager gets the rest
+    pub two_panes: [u16; 2],
+    pub three_panes: [u16; 3],
+}
+
+impl Default for SplitRatios {
+    fn default() -> Self {
+        Self { console: 40, two_panes: [50, 50], three_panes: [33, 34, 33] }
+    }
+}
+
+impl SplitRatios {
+    /// Console width, falling back to the default if the config holds something out of range
+    pub fn console_percent(&self) -> u16 {
+        let (min, max) = CONSOLE_PERCENT_RANGE;
+        if (min..=max).contains(&self.console) { self.console } else { Self::default().console }
+    }
+
+    /// Pane widths for `count` panes, falling back to the defaults if the config holds invalid ones
+    pub fn pane_widths(&self, count: usize) -> Vec<u16> {
+        let valid = |widths: &[u16]| widths.iter().all(|&w| w >= MIN_PANE_PERCENT) && widths.iter().sum::<u16>() == 100;
+        let defaults = Self::default();
+        match count {
+            0 | 1 => vec![100],
+            2 if valid(&self.two_panes) => self.two_panes.to_vec(),
+            2 => defaults.two_panes.to_vec(),
+            _ if valid(&self.three_panes) => self.three_panes.to_vec(),
+            _ => defaults.three_panes.to_vec(),
+        }
+    }
+
+    /// Grow (positive `delta`) or shrink pane `index` of `count`, trading width with its right
+    /// neighbour (the left one for the last pane). Returns false if a pane would get too narrow.
+    pub fn resize_pane(&mut self, count: usize, index: usize, delta: i16) -> bool {
+        if count < 2 || index >= count {
+            return false;
+        }
+        let mut widths = self.pane_widths(count);
+        let neighbour = if index + 1 < count { index + 1 } else { index - 1 };
+        let grown = widths[index] as i16 + delta;
+        let shrunk = widths[neighbour] as i16 - delta;
+        if grown < MIN_PANE_PERCENT as i16 || shrunk < MIN_PANE_PERCENT as i16 {
+            return false;
+        }
+        widths[index] = grown as u16;
+        widths[neighbour] = shrunk as u16;
+        match count {
+            2 => self.two_panes = [widths[0], widths[1]],
+            _ => self.three_panes = [widths[0], widths[1], widths[2]],
+        }
+        true
+    }
+
+    /// Grow or shrink the console panel; returns false at the limits
+    pub fn resize_console(&mut self, delta: i16) -> bool {
+        let (min, max) = CONSOLE_PERCENT_RANGE;
+        let width = self.console_percent() as i16 + delta;
+        if width < min as i16 || width > max as i16 {
+            return false;
+        }
+        self.console = width as u16;
+        true
+    }
+}
+
+/// Open panes saved on exit and restored on launch (see `restore_session`)
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Session {
+    pub pane_count: usize,
+    pub active_pane: usize, // 0 = left, 1 = center, 2 = right
+    pub dirs: Vec<PathBuf>, // current_dir of each pane, left to right
+}
+
+impl Session {
+    /// Saved directories that still exist; missing ones fall back to `fallback`
+    pub fn validated_dirs(&self, fallback: &std::path::Path) -> Vec<PathBuf> {
+        self.dirs
+            .iter()
+            .map(|dir| {
+                if dir.is_dir() {
+                    dir.clone()
+                } else {
+                    tracing::warn!(dir = ?dir, "Saved session directory is gone, using fallback");
+                    fallback.to_path_buf()
+                }
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Theme {
     pub name: String,
@@ -59,6 +199,127 @@ impl Theme {
         Self::all_themes().into_iter().find(|t| t.name == name)
     }
 
+    /// Color fields in the order the theme editor lists them
+    pub const COLOR_ROLES: [&'static str; 14] = [
+        "bg", "fg", "selection_bg", "selection_fg", "border", "header_bg", "header_fg",
+        "footer_bg", "footer_fg", "directory_fg", "file_fg", "symlink_fg", "executable_fg", "accent_color",
+    ];
+
+    /// The color field named `role` (see `COLOR_ROLES`)
+    pub fn color(&self, role: &str) -> Option<Color> {
+        Some(match role {
+            "bg" => self.bg,
+            "fg" => self.fg,
+            "selection_bg" => self.selection_bg,
+            "selection_fg" => self.selection_fg,
+            "border" => self.border,
+            "header_bg" => self.header_bg,
+            "header_fg" => self.header_fg,
+            "footer_bg" => self.footer_bg,
+            "footer_fg" => self.footer_fg,
+            "directory_fg" => self.directory_fg,
+            "file_fg" => self.file_fg,
+            "symlink_fg" => self.symlink_fg,
+            "executable_fg" => self.executable_fg,
+            "accent_color" => self.accent_color,
+            _ => return None,
+        })
+    }
+
+    /// Mutable access to the color field named `role`
+    pub fn color_mut(&mut self, role: &str) -> Option<&mut Color> {
+        Some(match role {
+            "bg" => &mut self.bg,
+            "fg" => &mut self.fg,
+            "selection_bg" => &mut self.selection_bg,
+            "selection_fg" => &mut self.selection_fg,
+            "border" => &mut self.border,
+            "header_bg" => &mut self.header_bg,
+            "header_fg" => &mut self.header_fg,
+            "footer_bg" => &mut self.footer_bg,
+            "footer_fg" => &mut self.footer_fg,
+            "directory_fg" => &mut self.directory_fg,
+            "file_fg" => &mut self.file_fg,
+            "symlink_fg" => &mut self.symlink_fg,
+            "executable_fg" => &mut self.executable_fg,
+            "accent_color" => &mut self.accent_color,
+            _ => return None,
+        })
+    }
+
+    /// Directory of custom theme files (~/.config/senterm/themes)
+    pub fn themes_dir() -> Option<PathBuf> {
+        Some(dirs::config_dir()?.join("senterm").join("themes"))
+    }
+
+    /// Built-in themes followed by the custom ones in `themes_dir`
+    pub fn available() -> Vec<Theme> {
+        let custom = Self::themes_dir().map(|dir| Self::load_custom(&dir)).unwrap_or_default();
+        Self::with_custom(custom)
+    }
+
+    /// Built-in themes merged with `custom`: one with a built-in's name replaces it
+    fn with_custom(custom: Vec<Theme>) -> Vec<Theme> {
+        let mut themes = Self::all_themes();
+        for theme in custom {
+            match themes.iter_mut().find(|t| t.name == theme.name) {
+                Some(existing) => *existing = theme,
+                None => themes.push(theme),
+            }
+        }
+        themes
+    }
+
+    /// Themes from the *.toml files in `dir`, by file name; unreadable or invalid files are skipped
+    pub fn load_custom(dir: &std::path::Path) -> Vec<Theme> {
+        let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|e| e.eq_ignore_ascii_case("toml")))
+            .collect();
+        paths.sort();
+        paths.iter()
+            .filter_map(|path| match Self::from_file(path) {
+                Ok(theme) => Some(theme),
+                Err(e) => {
+                    tracing::warn!(path = ?path, error = %e, "Skipping invalid theme file");
+                    None
+                },
+            })
+            .collect()
+    }
+
+    /// Read a theme file; `name` defaults to the file name without extension
+    fn from_file(path: &std::path::Path) -> Result<Theme, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let mut table: toml::Table = toml::from_str(&contents).map_err(|e| e.to_string())?;
+        if !table.contains_key("name") {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            table.insert("name".to_string(), toml::Value::String(stem));
+        }
+        // Check each color here so the error names the field
+        for (field, value) in table.iter_mut().filter(|(field, _)| field.as_str() != "name") {
+            let toml::Value::String(text) = value else {
+                return Err(format!("{}: expected a color string", field));
+            };
+            *text = parse_color(text).map_err(|e| format!("{}: {}", field, e))?.to_string();
+        }
+        table.try_into().map_err(|e: toml::de::Error| e.to_string())
+    }
+
+    /// Write this theme to `dir` as <name>.toml; returns the file written
+    pub fn export(&self, dir: &std::path::Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let stem: String = self.name.to_lowercase()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '-' })
+            .collect();
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}.toml", stem));
+        std::fs::write(&path, toml::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+
     pub fn elegant_dark() -> Self {
         Self {
             name: "Elegant Dark".to_string(),
@@ -260,21 +521,313 @@ impl Theme {
     }
 }
 
+/// A bookmarked directory and its label
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "BookmarkEntry")]
+pub struct Bookmark {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Bookmarks as stored: older configs list bare paths
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BookmarkEntry {
+    Path(PathBuf),
+    Named { name: String, path: PathBuf },
+}
+
+impl From<BookmarkEntry> for Bookmark {
+    fn from(entry: BookmarkEntry) -> Self {
+        match entry {
+            BookmarkEntry::Path(path) => Bookmark::new(path, ""),
+            BookmarkEntry::Named { name, path } => Bookmark::new(path, &name),
+        }
+    }
+}
+
+impl Bookmark {
+    /// An empty `name` labels the bookmark with the directory name
+    pub fn new(path: PathBuf, name: &str) -> Self {
+        let name = match name.trim() {
+            "" => Self::default_label(&path),
+            name => name.to_string(),
+        };
+        Self { name, path }
+    }
+
+    pub fn default_label(path: &std::path::Path) -> String {
+        path.file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string())
+    }
+}
+
+/// Most directories kept in the jump list history
+pub const DIR_HISTORY_LIMIT: usize = 500;
+
+/// A directory in the jump list history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DirVisit {
+    pub path: PathBuf,
+    pub visits: u32,
+    pub last_visit: u64, // Unix seconds
+}
+
+impl DirVisit {
+    /// Visit count weighted by how recently the directory was entered
+    pub fn frecency(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last_visit);
+        let weight = match age {
+            0..=3_599 => 4.0,           // Last hour
+            3_600..=86_399 => 2.0,      // Last day
+            86_400..=604_799 => 0.5,    // Last week
+            _ => 0.25,
+        };
+        self.visits as f64 * weight
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub theme: Theme,
     pub show_parent_dirs: usize, // How many parent levels to show
     pub first_run: bool,
-    pub bookmarks: Vec<PathBuf>, // Bookmarked directories
+    pub bookmarks: Vec<Bookmark>, // Bookmarked directories
     pub sort_option: SortOption, // File sorting option
+    #[serde(default)]
+    pub sort_reverse: bool, // Reverse the sort order (Shift+S)
     #[serde(default = "default_max_ui_trees")]
     pub max_ui_trees: usize, // Maximum number of UI trees (default 3, max 10)
+    #[serde(default = "default_enable_watcher")]
+    pub enable_watcher: bool, // Watch visible directories for changes (disable on network mounts)
+    #[serde(default = "default_double_click_ms")]
+    pub double_click_ms: u64, // Max interval between clicks for a double-click (0 = disabled)
+    #[serde(default)]
+    pub syntax_overrides: HashMap<String, String>, // Extension -> highlighter language (e.g. conf = "toml")
+    #[serde(default)]
+    pub use_trash: bool, // Move deleted items to ~/.local/share/senterm/trash instead of removing them
+    #[serde(default)]
+    pub persist_dir_sort: bool, // Pressing 's' writes the sort order to the directory's .senterm-sort
+    #[serde(default = "default_dirs_first")]
+    pub dirs_first: bool, // Directories listed before files; off mixes them by the sort key
+    #[serde(default = "default_search_depth")]
+    pub search_depth: usize, // How many directory levels '/' and grep search descend
+    #[serde(default = "default_show_git_status")]
+    pub show_git_status: bool, // Mark modified/staged/untracked files inside git repositories
+    #[serde(default)]
+    pub open_with: HashMap<String, String>, // Extension -> last command used with 'o' ("" = no extension)
+    #[serde(default)]
+    pub keybindings: HashMap<String, Vec<String>>, // Action name -> key specs (e.g. quit = ["ctrl+q"])
+    #[serde(default)]
+    pub restore_session: bool, // Reopen the last session's panes and directories on launch
+    #[serde(default)]
+    pub session: Option<Session>, // Written on exit
+    #[serde(default = "default_syntax_theme")]
+    pub syntax_theme: String, // syntect theme for code highlighting (e.g. "InspiredGitHub")
+    #[serde(default = "default_csv_preview_rows")]
+    pub csv_preview_rows: usize, // Data rows shown when previewing CSV/TSV files
+    #[serde(default)]
+    pub sync_shell_cwd: bool, // Focusing the console sends `cd <browsed dir>` to its shell
+    #[serde(default)]
+    pub dir_history: Vec<DirVisit>, // Entered directories ranked by the jump list (J)
+    #[serde(default)]
+    pub plugin_hot_reload: bool, // Reload plugins when their files change (plugin development)
+    #[serde(default)]
+    pub trusted_plugins: Vec<String>, // Plugins promoted to full io/os access (Settings > Plugins)
+    #[serde(default)]
+    pub disabled_plugins: Vec<String>, // Plugins listed but not loaded (Settings > Plugins)
+    #[serde(default)]
+    pub editor_regex_substitute: bool, // ':s' patterns are regular expressions ($1 in the replacement)
+    #[serde(default = "default_editor_auto_indent")]
+    pub editor_auto_indent: bool, // New lines keep the indentation (':set noai' turns it off per file)
+    #[serde(default = "default_tab_width")]
+    pub tab_width: usize, // Columns per tab in the viewer and editor, and spaces per indent level
+    #[serde(default = "default_editor_expand_tab")]
+    pub editor_expand_tab: bool, // Tab and '>' insert spaces; false inserts real tabs (':set et/noet')
+    #[serde(default)]
+    pub relative_line_numbers: bool, // Editor gutter counts lines from the cursor (':set rnu/nornu')
+    #[serde(default)]
+    pub editor_auto_pair: bool, // Typing ( [ { " ' in the editor also inserts the closing character
+    #[serde(default)]
+    pub split_ratios: SplitRatios, // Console and pane widths (Ctrl+Shift+Left/Right)
+    #[serde(default)]
+    pub use_nerd_icons: bool, // Devicon glyphs per file type in the panes (needs a Nerd Font)
+    #[serde(default)]
+    pub show_clock: bool, // Current time at the right of the file manager footer
+    #[serde(default)]
+    pub show_disk_space: bool, // Free/total space of the active directory's filesystem in the footer
+    #[serde(default)]
+    pub show_preview: bool, // Preview column for the selected file in the active pane ('w')
+    #[serde(default)]
+    pub ext_colors: HashMap<String, Color>, // Extension (no dot) -> file name color in the panes, e.g. rs = "#FF8700"
+    #[serde(default = "default_ext_colors_enabled")]
+    pub ext_colors_enabled: bool, // Off shows every file in the theme's file color, keeping ext_colors
+    #[serde(default = "default_max_text_preview_bytes")]
+    pub max_text_preview_bytes: u64, // Larger text files of unknown type are read a window at a time (no highlighting or editing)
+    #[serde(default = "default_max_binary_preview_bytes")]
+    pub max_binary_preview_bytes: u64, // Larger files aren't previewed (or hex edited) at all
+    #[serde(default = "default_url_max_bytes")]
+    pub url_max_bytes: u64, // `:open URL` downloads stop after this many bytes
 }
 
 fn default_max_ui_trees() -> usize {
     3
 }
 
+fn default_enable_watcher() -> bool {
+    true
+}
+
+fn default_dirs_first() -> bool {
+    true
+}
+
+fn default_ext_colors_enabled() -> bool {
+    true
+}
+
+fn default_double_click_ms() -> u64 {
+    400
+}
+
+fn default_search_depth() -> usize {
+    5
+}
+
+fn default_show_git_status() -> bool {
+    true
+}
+
+fn default_editor_auto_indent() -> bool {
+    true
+}
+
+fn default_tab_width() -> usize {
+    crate::viewer::editor::DEFAULT_TAB_WIDTH
+}
+
+fn default_editor_expand_tab() -> bool {
+    true
+}
+
+/// Extension colors applied from Settings > Interface (e)
+pub fn default_ext_colors() -> HashMap<String, Color> {
+    [
+        ("rs", Color::Rgb(255, 135, 0)),
+        ("go", Color::Rgb(0, 173, 216)),
+        ("py", Color::Rgb(255, 212, 59)),
+        ("js", Color::Rgb(240, 219, 79)),
+        ("ts", Color::Rgb(49, 120, 198)),
+        ("md", Color::Rgb(100, 150, 250)),
+        ("json", Color::Rgb(203, 203, 65)),
+        ("toml", Color::Rgb(156, 66, 33)),
+        ("yaml", Color::Rgb(203, 23, 30)),
+        ("sh", Color::Rgb(137, 224, 81)),
+        ("zip", Color::Rgb(230, 80, 80)),
+        ("png", Color::Rgb(200, 120, 220)),
+        ("jpg", Color::Rgb(200, 120, 220)),
+    ]
+    .into_iter()
+    .map(|(ext, color)| (ext.to_string(), color))
+    .collect()
+}
+
+/// Parse "#RRGGBB" (or "#RGB") into an RGB color, or a color name such as
+/// "red", "darkgray" or "brightblue" ("bright" and "light" are the same)
+pub fn parse_color(text: &str) -> Result<Color, String> {
+    let text = text.trim();
+    if let Some(hex) = text.strip_prefix('#') {
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(format!("\"{}\" has a non-hex digit", text));
+        }
+        let digits: Vec<u8> = match hex.len() {
+            6 => hex.as_bytes().to_vec(),
+            3 => hex.bytes().flat_map(|b| [b, b]).collect(),
+            _ => return Err(format!("\"{}\" isn't #RRGGBB", text)),
+        };
+        let channel = |i: usize| {
+            let pair = std::str::from_utf8(&digits[i..i + 2]).unwrap_or_default();
+            u8::from_str_radix(pair, 16).unwrap_or_default()
+        };
+        return Ok(Color::Rgb(channel(0), channel(2), channel(4)));
+    }
+
+    let name: String = text.to_lowercase().chars().filter(|c| !matches!(c, ' ' | '_' | '-')).collect();
+    let (light, base) = match name.strip_prefix("bright").or_else(|| name.strip_prefix("light")) {
+        Some(base) => (true, base),
+        None => (false, name.as_str()),
+    };
+    Ok(match (light, base) {
+        (false, "reset") => Color::Reset,
+        (false, "black") => Color::Black,
+        (false, "red") => Color::Red,
+        (false, "green") => Color::Green,
+        (false, "yellow") => Color::Yellow,
+        (false, "blue") => Color::Blue,
+        (false, "magenta") => Color::Magenta,
+        (false, "cyan") => Color::Cyan,
+        (false, "gray" | "grey") => Color::Gray,
+        (false, "darkgray" | "darkgrey") | (true, "black") => Color::DarkGray,
+        (false, "white") | (true, "white") => Color::White,
+        (true, "red") => Color::LightRed,
+        (true, "green") => Color::LightGreen,
+        (true, "yellow") => Color::LightYellow,
+        (true, "blue") => Color::LightBlue,
+        (true, "magenta") => Color::LightMagenta,
+        (true, "cyan") => Color::LightCyan,
+        (true, "gray" | "grey") => Color::White,
+        _ => return Err(format!("unknown color \"{}\" (use #RRGGBB or a name like \"brightblue\")", text)),
+    })
+}
+
+/// `color` made lighter (positive `delta`) or darker, as RGB; named colors
+/// start from their usual xterm values
+pub fn shift_color(color: Color, delta: i16) -> Color {
+    let (r, g, b) = match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Black | Color::Reset => (0, 0, 0),
+        Color::Red => (205, 0, 0),
+        Color::Green => (0, 205, 0),
+        Color::Yellow => (205, 205, 0),
+        Color::Blue => (0, 0, 238),
+        Color::Magenta => (205, 0, 205),
+        Color::Cyan => (0, 205, 205),
+        Color::Gray => (229, 229, 229),
+        Color::DarkGray => (127, 127, 127),
+        Color::LightRed => (255, 0, 0),
+        Color::LightGreen => (0, 255, 0),
+        Color::LightYellow => (255, 255, 0),
+        Color::LightBlue => (92, 92, 255),
+        Color::LightMagenta => (255, 0, 255),
+        Color::LightCyan => (0, 255, 255),
+        Color::White => (255, 255, 255),
+        Color::Indexed(_) => (127, 127, 127),
+    };
+    let shift = |c: u8| (c as i16 + delta).clamp(0, 255) as u8;
+    Color::Rgb(shift(r), shift(g), shift(b))
+}
+
+fn default_syntax_theme() -> String {
+    crate::viewer::highlight::DEFAULT_SYNTAX_THEME.to_string()
+}
+
+fn default_csv_preview_rows() -> usize {
+    crate::viewer::csv::DEFAULT_MAX_ROWS
+}
+
+fn default_max_text_preview_bytes() -> u64 {
+    crate::viewer::DEFAULT_MAX_TEXT_PREVIEW
+}
+
+fn default_max_binary_preview_bytes() -> u64 {
+    crate::viewer::DEFAULT_MAX_BINARY_PREVIEW
+}
+
+fn default_url_max_bytes() -> u64 {
+    16 * 1024 * 1024
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -283,12 +836,69 @@ impl Default for Config {
             first_run: false,
             bookmarks: Vec::new(),
             sort_option: SortOption::Name,
+            sort_reverse: false,
             max_ui_trees: default_max_ui_trees(),
+            enable_watcher: default_enable_watcher(),
+            double_click_ms: default_double_click_ms(),
+            syntax_overrides: HashMap::new(),
+            use_trash: false,
+            persist_dir_sort: false,
+            dirs_first: default_dirs_first(),
+            search_depth: default_search_depth(),
+            show_git_status: default_show_git_status(),
+            open_with: HashMap::new(),
+            keybindings: HashMap::new(),
+            restore_session: false,
+            session: None,
+            syntax_theme: default_syntax_theme(),
+            csv_preview_rows: default_csv_preview_rows(),
+            sync_shell_cwd: false,
+            dir_history: Vec::new(),
+            plugin_hot_reload: false,
+            trusted_plugins: Vec::new(),
+            disabled_plugins: Vec::new(),
+            editor_regex_substitute: false,
+            editor_auto_indent: default_editor_auto_indent(),
+            tab_width: default_tab_width(),
+            editor_expand_tab: default_editor_expand_tab(),
+            relative_line_numbers: false,
+            editor_auto_pair: false,
+            split_ratios: SplitRatios::default(),
+            use_nerd_icons: false,
+            show_clock: false,
+            show_disk_space: false,
+            show_preview: false,
+            ext_colors: HashMap::new(),
+            ext_colors_enabled: default_ext_colors_enabled(),
+            max_text_preview_bytes: default_max_text_preview_bytes(),
+            max_binary_preview_bytes: default_max_binary_preview_bytes(),
+            url_max_bytes: default_url_max_bytes(),
         }
     }
 }
 
 impl Config {
+    /// Preview limits, clamped to the allowed ranges
+    pub fn preview_limits(&self) -> crate::viewer::PreviewLimits {
+        let clamp = |value: u64, (min, max): (u64, u64)| value.clamp(min, max);
+        crate::viewer::PreviewLimits {
+            text_bytes: clamp(self.max_text_preview_bytes, TEXT_PREVIEW_RANGE),
+            binary_bytes: clamp(self.max_binary_preview_bytes, BINARY_PREVIEW_RANGE),
+            csv_rows: self.csv_preview_rows.max(1),
+        }
+    }
+
+    /// Color configured for the extension of `path` (case-insensitive)
+    pub fn ext_color(&self, path: &std::path::Path) -> Option<Color> {
+        if !self.ext_colors_enabled || self.ext_colors.is_empty() {
+            return None;
+        }
+        let ext = path.extension()?.to_string_lossy().to_lowercase();
+        self.ext_colors.get(&ext)
+            .or_else(|| self.ext_colors.iter().find(|(k, _)| k.to_lowercase() == ext).map(|(_, c)| c))
+            .copied()
+    }
+
     /// Get the config file path (~/.config/senterm/config.toml)
     fn config_path() -> Option<std::path::PathBuf> {
         if let Some(config_dir) = dirs::config_dir() {
@@ -304,8 +914,9 @@ impl Config {
         if let Some(config_path) = Self::config_path() {
             if config_path.exists() {
                 if let Ok(contents) = std::fs::read_to_string(&config_path) {
-                    if let Ok(config) = toml::from_str::<Config>(&contents) {
+                    if let Ok(mut config) = toml::from_str::<Config>(&contents) {
                         tracing::info!("Loaded config from {:?}", config_path);
+                        config.prune_dir_history();
                         return config;
                     } else {
                         tracing::warn!("Failed to parse config file, using defaults");
@@ -320,6 +931,31 @@ impl Config {
         Self::default()
     }
 
+    /// Count a visit to `dir`, evicting the lowest-ranked entry when the history is full
+    pub fn record_dir_visit(&mut self, dir: PathBuf, now: u64) {
+        if let Some(entry) = self.dir_history.iter_mut().find(|v| v.path == dir) {
+            entry.visits = entry.visits.saturating_add(1);
+            entry.last_visit = now;
+            return;
+        }
+
+        if self.dir_history.len() >= DIR_HISTORY_LIMIT {
+            let lowest = self.dir_history.iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.frecency(now).total_cmp(&b.frecency(now)))
+                .map(|(i, _)| i);
+            if let Some(idx) = lowest {
+                self.dir_history.remove(idx);
+            }
+        }
+        self.dir_history.push(DirVisit { path: dir, visits: 1, last_visit: now });
+    }
+
+    /// Forget directories that no longer exist
+    pub fn prune_dir_history(&mut self) {
+        self.dir_history.retain(|v| v.path.is_dir());
+    }
+
     /// Save configuration to file
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(config_path) = Self::config_path() {
@@ -354,6 +990,18 @@ mod tests {
         assert_ne!(name, size);
     }
 
+    #[test]
+    fn test_sort_option_cycle_and_parse() {
+        assert_eq!(SortOption::Name.next(), SortOption::Natural);
+        assert_eq!(SortOption::Natural.next(), SortOption::Size);
+        assert_eq!(SortOption::Modified.next(), SortOption::Name);
+        for option in [SortOption::Name, SortOption::Natural, SortOption::Size, SortOption::Modified] {
+            assert_eq!(SortOption::parse(option.as_str()), Some(option));
+        }
+        assert_eq!(SortOption::parse(" Modified\n"), Some(SortOption::Modified));
+        assert_eq!(SortOption::parse("random"), None);
+    }
+
     #[test]
     fn test_theme_default() {
         let theme = Theme::default();
@@ -412,6 +1060,65 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_custom_theme_export_and_import() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut theme = Theme::dracula();
+        theme.accent_color = Color::Rgb(1, 2, 3);
+        let path = theme.export(temp.path()).unwrap();
+        assert_eq!(path.file_name().unwrap(), "dracula.toml");
+        assert!(std::fs::read_to_string(&path).unwrap().contains("accent_color = \"#010203\""));
+
+        let mut mine = toml::to_string(&Theme::nord()).unwrap().replace("name = \"Nord\"\n", "");
+        mine = mine.replace(&format!("bg = \"{}\"", Theme::nord().bg), "bg = \"#102030\"");
+        std::fs::write(temp.path().join("My Theme.toml"), mine).unwrap();
+        std::fs::write(temp.path().join("broken.toml"), "bg = \"not a color\"").unwrap();
+        std::fs::write(temp.path().join("notes.txt"), "ignored").unwrap();
+
+        let custom = Theme::load_custom(temp.path());
+        assert_eq!(custom.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(), vec!["My Theme", "Dracula"]);
+        assert_eq!(custom[0].bg, Color::Rgb(0x10, 0x20, 0x30));
+
+        let themes = Theme::with_custom(custom);
+        assert_eq!(themes.len(), 11);
+        assert_eq!(themes.iter().find(|t| t.name == "Dracula").unwrap().accent_color, Color::Rgb(1, 2, 3));
+        assert_eq!(themes[10].name, "My Theme");
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#FF8700"), Ok(Color::Rgb(255, 135, 0)));
+        assert_eq!(parse_color("#0af"), Ok(Color::Rgb(0, 0xAA, 0xFF)));
+        assert_eq!(parse_color("red"), Ok(Color::Red));
+        assert_eq!(parse_color("BrightBlue"), Ok(Color::LightBlue));
+        assert_eq!(parse_color("light_cyan"), Ok(Color::LightCyan));
+        assert_eq!(parse_color("dark gray"), Ok(Color::DarkGray));
+        assert!(parse_color("#12345").is_err());
+        assert!(parse_color("#GG0000").is_err());
+        assert!(parse_color("brightpurple").is_err());
+
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("bad.toml");
+        std::fs::write(&path, "bg = \"#102030\"\nfg = \"#12\"\n").unwrap();
+        assert!(Theme::from_file(&path).unwrap_err().starts_with("fg: "));
+    }
+
+    #[test]
+    fn test_theme_color_roles() {
+        let mut theme = Theme::nord();
+        for role in Theme::COLOR_ROLES {
+            assert!(theme.color_mut(role).is_some(), "{} should be a color field", role);
+        }
+        *theme.color_mut("directory_fg").unwrap() = Color::Rgb(1, 2, 3);
+        assert_eq!(theme.directory_fg, Color::Rgb(1, 2, 3));
+        assert_eq!(theme.color("directory_fg"), Some(Color::Rgb(1, 2, 3)));
+        assert!(theme.color_mut("name").is_none());
+        assert!(theme.color("name").is_none());
+
+        assert_eq!(shift_color(Color::Rgb(250, 10, 100), 16), Color::Rgb(255, 26, 116));
+        assert_eq!(shift_color(Color::White, -55), Color::Rgb(200, 200, 200));
+    }
+
     #[test]
     fn test_theme_elegant_dark() {
         let theme = Theme::elegant_dark();
@@ -434,7 +1141,40 @@ mod tests {
         assert!(!config.first_run);
         assert!(config.bookmarks.is_empty());
         assert_eq!(config.sort_option, SortOption::Name);
+        assert!(!config.sort_reverse);
         assert_eq!(config.max_ui_trees, 3);
+        assert!(config.enable_watcher);
+        assert_eq!(config.double_click_ms, 400);
+        assert!(!config.use_trash);
+        assert_eq!(config.search_depth, 5);
+        assert!(config.show_git_status);
+        assert_eq!(config.syntax_theme, "base16-ocean.dark");
+        assert_eq!(config.csv_preview_rows, 1000);
+        assert!(!config.sync_shell_cwd);
+        assert!(config.dir_history.is_empty());
+        assert!(!config.plugin_hot_reload);
+        assert!(config.trusted_plugins.is_empty());
+        assert!(config.disabled_plugins.is_empty());
+        assert!(!config.editor_regex_substitute);
+        assert!(config.editor_auto_indent);
+        assert_eq!(config.tab_width, 4);
+        assert!(config.ext_colors.is_empty());
+        assert!(!config.use_nerd_icons);
+        assert!(!config.show_clock);
+        assert!(!config.show_disk_space);
+        assert!(!config.show_preview);
+        assert_eq!(config.preview_limits(), crate::viewer::PreviewLimits::default());
+        assert_eq!(config.split_ratios, SplitRatios::default());
+        assert!(config.editor_expand_tab);
+        assert!(!config.relative_line_numbers);
+        assert!(!config.editor_auto_pair);
+        assert_eq!(config.url_max_bytes, 16 * 1024 * 1024);
+        assert!(config.open_with.is_empty());
+        assert!(config.keybindings.is_empty());
+        assert!(!config.restore_session);
+        assert!(config.session.is_none());
+        assert!(!config.persist_dir_sort);
+        assert!(config.dirs_first);
     }
 
     #[test]
@@ -488,6 +1228,52 @@ mod tests {
         assert!(config.first_run);
         assert_eq!(config.sort_option, SortOption::Size);
         assert_eq!(config.max_ui_trees, 5);
+        // Missing enable_watcher falls back to the default
+        assert!(config.enable_watcher);
+    }
+
+    #[test]
+    fn test_split_ratios() {
+        let mut ratios = SplitRatios::default();
+        assert!(ratios.resize_pane(3, 0, 5));
+        assert_eq!(ratios.pane_widths(3), vec![38, 29, 33]);
+        // The last pane trades with its left neighbour
+        assert!(ratios.resize_pane(2, 1, -10));
+        assert_eq!(ratios.pane_widths(2), vec![60, 40]);
+        // Panes can't get narrower than the minimum
+        assert!(!ratios.resize_pane(2, 1, -35));
+        assert_eq!(ratios.pane_widths(2), vec![60, 40]);
+
+        assert!(ratios.resize_console(5));
+        assert_eq!(ratios.console_percent(), 45);
+        assert!(!ratios.resize_console(40));
+
+        // Invalid values from the config fall back to the defaults
+        let broken = SplitRatios { console: 0, two_panes: [100, 0], three_panes: [50, 50, 50] };
+        assert_eq!(broken.pane_widths(2), vec![50, 50]);
+        assert_eq!(broken.pane_widths(3), vec![33, 34, 33]);
+        assert_eq!(broken.console_percent(), 40);
+    }
+
+    #[test]
+    fn test_ext_colors() {
+        let config = Config {
+            ext_colors: toml::from_str("rs = \"#FF8700\"\nMD = \"blue\"\n").unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(config.ext_color(std::path::Path::new("src/main.rs")), Some(Color::Rgb(255, 135, 0)));
+        assert_eq!(config.ext_color(std::path::Path::new("README.md")), Some(Color::Blue));
+        assert_eq!(config.ext_color(std::path::Path::new("notes.txt")), None);
+        assert_eq!(config.ext_color(std::path::Path::new("Makefile")), None);
+
+        // Turned off, the colors are kept for turning back on
+        let disabled = Config { ext_colors_enabled: false, ..config.clone() };
+        assert_eq!(disabled.ext_color(std::path::Path::new("src/main.rs")), None);
+        assert_eq!(disabled.ext_colors, config.ext_colors);
+
+        // Saved and read back unchanged
+        let reloaded: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(reloaded.ext_colors, config.ext_colors);
     }
 
     #[test]
@@ -506,10 +1292,79 @@ mod tests {
         let mut config = Config::default();
         assert!(config.bookmarks.is_empty());
 
-        config.bookmarks.push(PathBuf::from("/home/user/Documents"));
+        config.bookmarks.push(Bookmark::new(PathBuf::from("/home/user/Documents"), ""));
         assert_eq!(config.bookmarks.len(), 1);
+        assert_eq!(config.bookmarks[0].name, "Documents");
 
-        config.bookmarks.push(PathBuf::from("/home/user/Projects"));
+        config.bookmarks.push(Bookmark::new(PathBuf::from("/home/user/Projects"), " work "));
         assert_eq!(config.bookmarks.len(), 2);
+        assert_eq!(config.bookmarks[1].name, "work");
+    }
+
+    #[test]
+    fn test_bookmarks_old_format_migrates() {
+        let mut config = Config::default();
+        let mut value = toml::Value::try_from(&config).unwrap();
+        value["bookmarks"] = toml::Value::Array(vec![
+            "/home/user/Documents".into(),
+            toml::Value::try_from(Bookmark::new(PathBuf::from("/srv/www"), "web")).unwrap(),
+        ]);
+        config = value.try_into().unwrap();
+        assert_eq!(config.bookmarks, vec![
+            Bookmark::new(PathBuf::from("/home/user/Documents"), "Documents"),
+            Bookmark::new(PathBuf::from("/srv/www"), "web"),
+        ]);
+
+        // Saved in the named format and read back unchanged
+        let saved = toml::to_string(&config).unwrap();
+        let reloaded: Config = toml::from_str(&saved).unwrap();
+        assert_eq!(reloaded.bookmarks, config.bookmarks);
+    }
+
+    #[test]
+    fn test_session_roundtrip_and_validation() {
+        let temp = tempfile::tempdir().unwrap();
+        let existing = temp.path().to_path_buf();
+        let missing = temp.path().join("gone");
+
+        let config = Config {
+            restore_session: true,
+            session: Some(Session {
+                pane_count: 2,
+                active_pane: 1,
+                dirs: vec![existing.clone(), missing],
+            }),
+            ..Default::default()
+        };
+
+        let toml_string = toml::to_string_pretty(&config).unwrap();
+        let loaded: Config = toml::from_str(&toml_string).unwrap();
+        assert!(loaded.restore_session);
+        let session = loaded.session.unwrap();
+        assert_eq!(session, config.session.unwrap());
+
+        let fallback = PathBuf::from("/");
+        assert_eq!(session.validated_dirs(&fallback), vec![existing, fallback]);
+    }
+
+    #[test]
+    fn test_dir_history_visits_and_pruning() {
+        let temp = tempfile::tempdir().unwrap();
+        let existing = temp.path().to_path_buf();
+        let missing = temp.path().join("gone");
+
+        let mut config = Config::default();
+        config.record_dir_visit(existing.clone(), 100);
+        config.record_dir_visit(existing.clone(), 200);
+        config.record_dir_visit(missing, 200);
+        assert_eq!(config.dir_history[0], DirVisit { path: existing.clone(), visits: 2, last_visit: 200 });
+
+        // Recent visits outrank older ones with the same count
+        let old = DirVisit { path: existing.clone(), visits: 2, last_visit: 0 };
+        assert!(config.dir_history[0].frecency(300) > old.frecency(1_000_000));
+
+        config.prune_dir_history();
+        assert_eq!(config.dir_history.len(), 1);
+        assert_eq!(config.dir_history[0].path, existing);
     }
 }
@@ -0,0 +1,117 @@
+//! Copy-mode for the embedded shells
+//!
+//! Freezes the shell screen so a region can be selected with the keyboard and
+//! copied. The selection runs like a terminal's: from the anchor to the cursor
+//! in reading order, joining soft-wrapped rows without a newline.
+
+/// Frozen screen with a cursor and an optional selection anchor (row, col)
+#[derive(Clone)]
+pub struct CopyMode {
+    pub screen: vt100::Screen,
+    pub cursor: (u16, u16),
+    pub anchor: Option<(u16, u16)>,
+}
+
+impl std::fmt::Debug for CopyMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CopyMode")
+            .field("cursor", &self.cursor)
+            .field("anchor", &self.anchor)
+            .finish()
+    }
+}
+
+impl CopyMode {
+    /// Freeze `screen`, starting at its cursor (or the bottom row when scrolled back)
+    pub fn new(screen: vt100::Screen) -> Self {
+        let (rows, _) = screen.size();
+        let cursor = if screen.scrollback() > 0 {
+            (rows.saturating_sub(1), 0)
+        } else {
+            screen.cursor_position()
+        };
+        Self { screen, cursor, anchor: None }
+    }
+
+    /// Move the cursor, staying on the screen
+    pub fn move_cursor(&mut self, rows: i32, cols: i32) {
+        let (max_row, max_col) = self.screen.size();
+        let clamp = |value: u16, delta: i32, max: u16| (value as i32 + delta).clamp(0, max as i32 - 1) as u16;
+        self.cursor = (clamp(self.cursor.0, rows, max_row), clamp(self.cursor.1, cols, max_col));
+    }
+
+    /// Put the cursor at `col` of its row (u16::MAX: the last column)
+    pub fn set_column(&mut self, col: u16) {
+        self.cursor.1 = col.min(self.screen.size().1.saturating_sub(1));
+    }
+
+    /// Start a selection at the cursor, or drop the current one
+    pub fn toggle_anchor(&mut self) {
+        self.anchor = match self.anchor {
+            Some(_) => None,
+            None => Some(self.cursor),
+        };
+    }
+
+    /// Selected range as (start, end) in reading order, end inclusive
+    pub fn selection(&self) -> Option<((u16, u16), (u16, u16))> {
+        let anchor = self.anchor?;
+        Some(if anchor <= self.cursor { (anchor, self.cursor) } else { (self.cursor, anchor) })
+    }
+
+    pub fn is_selected(&self, row: u16, col: u16) -> bool {
+        self.selection().is_some_and(|(start, end)| (row, col) >= start && (row, col) <= end)
+    }
+
+    /// Text of the selection (or of the cursor's row), trailing whitespace trimmed per line
+    pub fn selected_text(&self) -> String {
+        let (start, end) = self.selection().unwrap_or(((self.cursor.0, 0), (self.cursor.0, u16::MAX - 1)));
+        let end_col = end.1.min(self.screen.size().1.saturating_sub(1)) + 1;
+        self.screen
+            .contents_between(start.0, start.1, end.0, end_col)
+            .lines()
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(rows: u16, cols: u16, output: &[u8]) -> vt100::Screen {
+        let mut parser = vt100::Parser::new(rows, cols, 0);
+        parser.process(output);
+        parser.screen().clone()
+    }
+
+    #[test]
+    fn test_copy_mode_selection_trims_and_orders() {
+        let mut copy = CopyMode::new(screen(4, 20, b"$ ls   \r\nfoo.txt  bar   \r\n$ "));
+        assert_eq!(copy.cursor, (2, 2));
+        // Without a selection the cursor's row is copied
+        copy.move_cursor(-1, 0);
+        assert_eq!(copy.selected_text(), "foo.txt  bar");
+
+        // Select backwards from (1, 2) to (0, 2)
+        copy.set_column(2);
+        copy.toggle_anchor();
+        copy.move_cursor(-1, 0);
+        assert!(copy.is_selected(0, 5) && !copy.is_selected(1, 3));
+        assert_eq!(copy.selected_text(), "ls\nfoo");
+
+        // The cursor stays on screen
+        copy.move_cursor(-10, 100);
+        assert_eq!(copy.cursor, (0, 19));
+    }
+
+    #[test]
+    fn test_copy_mode_joins_wrapped_rows() {
+        let mut copy = CopyMode::new(screen(3, 5, b"abcdefgh"));
+        copy.cursor = (0, 0);
+        copy.toggle_anchor();
+        copy.cursor = (1, 4);
+        assert_eq!(copy.selected_text(), "abcdefgh");
+    }
+}
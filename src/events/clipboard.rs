@@ -19,21 +19,24 @@ pub fn handle_clipboard_operations(app: &mut App, key_code: KeyCode, modifiers:
     match app.mode {
         AppMode::FileManager => {
             // Check for both lowercase and uppercase (macOS may report uppercase with Cmd)
+            let is_file_key = matches!(key_code, KeyCode::Char('c' | 'C' | 'x' | 'X' | 'v' | 'V'));
+            if is_file_key && app.active_fs().is_remote() {
+                app.status_message = Some(crate::app::REMOTE_READ_ONLY.to_string());
+                return true;
+            }
             match key_code {
                 KeyCode::Char('c') | KeyCode::Char('C') => {
                     app.active_fs_mut().copy_selected();
-                    app.status_message = Some("Copied to clipboard".to_string());
+                    app.status_message = Some(app.clipboard_status("Copied"));
                     true
                 },
                 KeyCode::Char('x') | KeyCode::Char('X') => {
                     app.active_fs_mut().cut_selected();
-                    app.status_message = Some("Cut to clipboard".to_string());
+                    app.status_message = Some(app.clipboard_status("Cut"));
                     true
                 },
                 KeyCode::Char('v') | KeyCode::Char('V') => {
-                    app.active_fs_mut().paste();
-                    app.status_message = Some("Pasted from clipboard".to_string());
-                    app.refresh_both_panes();
+                    app.start_paste();
                     true
                 },
                 _ => false
@@ -22,8 +22,12 @@ pub fn handle_dialog_keys(app: &mut App, key_code: KeyCode) -> bool {
             app.dialog = DialogMode::Rename { current_name, new_name };
             handle_rename_dialog(app, key_code)
         },
-        DialogMode::Delete { path_name } => {
-            app.dialog = DialogMode::Delete { path_name };
+        DialogMode::BatchRename { paths, find, replace, editing_replace } => {
+            app.dialog = DialogMode::BatchRename { paths, find, replace, editing_replace };
+            handle_batch_rename_dialog(app, key_code)
+        },
+        DialogMode::Delete { path_name, permanent } => {
+            app.dialog = DialogMode::Delete { path_name, permanent };
             handle_delete_dialog(app, key_code)
         },
         DialogMode::NewFile { name } => {
@@ -34,18 +38,72 @@ pub fn handle_dialog_keys(app: &mut App, key_code: KeyCode) -> bool {
             app.dialog = DialogMode::NewFolder { name };
             handle_new_folder_dialog(app, key_code)
         },
-        DialogMode::Search { query, results } => {
-            app.dialog = DialogMode::Search { query, results };
-            handle_search_dialog(app, key_code, &search_dir)
+        DialogMode::Search { query, results, selected } => {
+            app.dialog = DialogMode::Search { query, results, selected };
+            handle_search_dialog(app, key_code)
+        },
+        DialogMode::Grep { query, hits, selected } => {
+            app.dialog = DialogMode::Grep { query, hits, selected };
+            handle_grep_dialog(app, key_code)
         },
         DialogMode::Command { input } => {
             app.dialog = DialogMode::Command { input };
             handle_command_dialog(app, key_code)
         },
+        DialogMode::SftpConnect { target } => {
+            app.dialog = DialogMode::SftpConnect { target };
+            handle_sftp_connect_dialog(app, key_code)
+        },
         DialogMode::QuitConfirm => {
             app.dialog = DialogMode::QuitConfirm;
             handle_quit_confirm_dialog(app, key_code)
-        }
+        },
+        DialogMode::TaskPicker { tasks, selected } => {
+            app.dialog = DialogMode::TaskPicker { tasks, selected };
+            handle_task_picker_dialog(app, key_code, &search_dir)
+        },
+        DialogMode::TemplatePicker { templates, selected } => {
+            app.dialog = DialogMode::TemplatePicker { templates, selected };
+            handle_template_picker_dialog(app, key_code)
+        },
+        DialogMode::TemplateName { template, name } => {
+            app.dialog = DialogMode::TemplateName { template, name };
+            handle_template_name_dialog(app, key_code)
+        },
+        DialogMode::OpenWith { path, command } => {
+            app.dialog = DialogMode::OpenWith { path, command };
+            handle_open_with_dialog(app, key_code)
+        },
+        DialogMode::GoTo { input, candidates, cycle, error } => {
+            app.dialog = DialogMode::GoTo { input, candidates, cycle, error };
+            handle_goto_dialog(app, key_code, &search_dir)
+        },
+        DialogMode::AddBookmark { path, name } => {
+            app.dialog = DialogMode::AddBookmark { path, name };
+            handle_add_bookmark_dialog(app, key_code)
+        },
+        DialogMode::TrustPlugin { name } => handle_trust_plugin_dialog(app, key_code, name),
+        DialogMode::ReloadFile => handle_reload_file_dialog(app, key_code),
+        // Read-only apart from 'p', which edits the permissions; Esc, Enter or q closes it
+        DialogMode::Properties { path, rows } => {
+            match key_code {
+                KeyCode::Char('p') => app.open_chmod(path),
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') | KeyCode::Char('I') => {},
+                _ => app.dialog = DialogMode::Properties { path, rows },
+            }
+            true
+        },
+        DialogMode::Chmod { path, mode, cursor, octal, typed, confirm } => {
+            app.dialog = DialogMode::Chmod { path, mode, cursor, octal, typed, confirm };
+            handle_chmod_dialog(app, key_code)
+        },
+        DialogMode::TextStats { name, stats } => {
+            match key_code {
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') | KeyCode::Char('I') => {},
+                _ => app.dialog = DialogMode::TextStats { name, stats },
+            }
+            true
+        },
     };
     
     result
@@ -72,7 +130,8 @@ fn handle_rename_dialog(app: &mut App, key_code: KeyCode) -> bool {
             app.dialog = DialogMode::None;
 
             match app.active_fs_mut().rename_selected(&name_to_use) {
-                Ok(_) => {
+                Ok(renamed) => {
+                    app.history.push(crate::fs::history::FileOperation::Renamed(vec![renamed]));
                     app.status_message = Some(format!("Renamed to '{}'", name_to_use));
                     app.refresh_both_panes();
                 },
@@ -92,22 +151,33 @@ fn handle_rename_dialog(app: &mut App, key_code: KeyCode) -> bool {
 fn handle_delete_dialog(app: &mut App, key_code: KeyCode) -> bool {
     match key_code {
         KeyCode::Char('y') | KeyCode::Char('Y') => {
-            let path_name = if let DialogMode::Delete { ref path_name } = app.dialog {
-                path_name.clone()
+            let (path_name, permanent) = if let DialogMode::Delete { ref path_name, permanent } = app.dialog {
+                (path_name.clone(), permanent)
             } else {
                 return true;
             };
             app.dialog = DialogMode::None;
 
-            match app.active_fs_mut().delete_selected() {
-                Ok(_) => {
-                    app.status_message = Some(format!("Deleted '{}'", path_name));
-                    app.refresh_both_panes();
-                },
-                Err(e) => {
-                    app.status_message = Some(format!("Delete failed: {}", e));
-                }
+            let trash_root = if permanent { None } else { crate::fs::trash::trash_dir() };
+            if !permanent && trash_root.is_none() {
+                // Never fall back to a permanent delete behind the user's back
+                app.status_message = Some("Couldn't find the trash directory; nothing was deleted".to_string());
+                return true;
+            }
+            let summary = match &trash_root {
+                Some(root) => app.active_fs_mut().trash_selected(root),
+                None => app.active_fs_mut().delete_selected(),
+            };
+            if trash_root.is_some() && summary.succeeded() > 0 {
+                app.history.push(crate::fs::history::FileOperation::Trashed);
+            }
+            if summary.failed() == 0 && !summary.is_batch() {
+                let verb = if trash_root.is_some() { "Moved to trash" } else { "Deleted" };
+                app.status_message = Some(format!("{} '{}'", verb, path_name));
+            } else {
+                app.report_operation(summary);
             }
+            app.refresh_both_panes();
         },
         KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
             app.dialog = DialogMode::None;
@@ -139,7 +209,8 @@ fn handle_new_file_dialog(app: &mut App, key_code: KeyCode) -> bool {
 
             if !file_name.is_empty() {
                 match app.active_fs_mut().create_file(&file_name) {
-                    Ok(_) => {
+                    Ok(created) => {
+                        app.history.push(crate::fs::history::FileOperation::created(created));
                         app.status_message = Some(format!("Created file '{}'", file_name));
                         // Clear viewer content to avoid showing old file content
                         app.viewer_content = None;
@@ -161,6 +232,34 @@ fn handle_new_file_dialog(app: &mut App, key_code: KeyCode) -> bool {
     true // Always consume key events when dialog is active
 }
 
+fn handle_add_bookmark_dialog(app: &mut App, key_code: KeyCode) -> bool {
+    match key_code {
+        KeyCode::Char(c) => {
+            if let DialogMode::AddBookmark { ref mut name, .. } = app.dialog {
+                name.push(c);
+            }
+        },
+        KeyCode::Backspace => {
+            if let DialogMode::AddBookmark { ref mut name, .. } = app.dialog {
+                name.pop();
+            }
+        },
+        KeyCode::Enter => {
+            if let DialogMode::AddBookmark { path, name } = std::mem::replace(&mut app.dialog, DialogMode::None) {
+                let bookmark = crate::config::Bookmark::new(path, &name);
+                app.status_message = Some(format!("Bookmarked '{}'", bookmark.name));
+                app.config.bookmarks.push(bookmark);
+                let _ = app.config.save();
+            }
+        },
+        KeyCode::Esc => {
+            app.dialog = DialogMode::None;
+        },
+        _ => {} // Ignore all other keys
+    }
+    true // Always consume key events when dialog is active
+}
+
 fn handle_new_folder_dialog(app: &mut App, key_code: KeyCode) -> bool {
     match key_code {
         KeyCode::Char(c) => {
@@ -183,7 +282,8 @@ fn handle_new_folder_dialog(app: &mut App, key_code: KeyCode) -> bool {
 
             if !folder_name.is_empty() {
                 match app.active_fs_mut().create_folder(&folder_name) {
-                    Ok(_) => {
+                    Ok(created) => {
+                        app.history.push(crate::fs::history::FileOperation::created(created));
                         app.status_message = Some(format!("Created folder '{}'", folder_name));
                         app.refresh_both_panes();
                     },
@@ -201,46 +301,234 @@ fn handle_new_folder_dialog(app: &mut App, key_code: KeyCode) -> bool {
     true // Always consume key events when dialog is active
 }
 
-fn handle_search_dialog(app: &mut App, key_code: KeyCode, search_dir: &PathBuf) -> bool {
+fn handle_search_dialog(app: &mut App, key_code: KeyCode) -> bool {
     match key_code {
         KeyCode::Char(c) => {
-            if let DialogMode::Search { ref mut query, ref mut results } = app.dialog {
+            if let DialogMode::Search { ref mut query, .. } = app.dialog {
                 query.push(c);
-                // Update search results
-                *results = perform_search(search_dir, query);
+                let query = query.clone();
+                app.restart_search(&query);
             }
         },
         KeyCode::Backspace => {
-            if let DialogMode::Search { ref mut query, ref mut results } = app.dialog {
+            if let DialogMode::Search { ref mut query, .. } = app.dialog {
                 query.pop();
-                // Update search results
-                *results = perform_search(search_dir, query);
+                let query = query.clone();
+                app.restart_search(&query);
+            }
+        },
+        KeyCode::Up => {
+            if let DialogMode::Search { ref mut selected, .. } = app.dialog {
+                *selected = selected.saturating_sub(1);
+            }
+        },
+        KeyCode::Down => {
+            if let DialogMode::Search { ref results, ref mut selected, .. } = app.dialog {
+                if *selected + 1 < results.len() {
+                    *selected += 1;
+                }
             }
         },
         KeyCode::Enter => {
-            let (first_result, result_count) = if let DialogMode::Search { ref results, .. } = app.dialog {
-                (results.first().cloned(), results.len())
+            let chosen = if let DialogMode::Search { ref results, selected, .. } = app.dialog {
+                results.get(selected).map(|(path, _)| path.clone())
             } else {
                 return true;
             };
-            
-            if let Some((path, _)) = first_result {
+
+            if let Some(path) = chosen {
                 if let Some(parent) = path.parent() {
+                    let parent = parent.to_path_buf();
                     let fs = app.active_fs_mut();
-                    fs.current_dir = parent.to_path_buf();
-                    let current_dir = fs.current_dir.clone();
-                    // Find and select the file in the current directory
-                    let entries = crate::fs::FileSystem::get_entries_for_dir(&current_dir);
+                    fs.jump_to_dir(parent.clone());
+                    // Find and select the file in its directory
+                    let entries = fs.entries_for_dir(&parent);
                     if let Some(idx) = entries.iter().position(|p| p == &path) {
-                        fs.set_selection(current_dir, idx);
+                        fs.set_selection(parent, idx);
                     }
                 }
-                app.status_message = Some(format!("Found {} result(s)", result_count));
+                app.status_message = Some(format!("Jumped to {}", path.display()));
             } else {
                 app.status_message = Some("No results found".to_string());
             }
+            app.search_job = None;
+            app.dialog = DialogMode::None;
+        },
+        KeyCode::Esc => {
+            app.search_job = None;
+            app.dialog = DialogMode::None;
+        },
+        _ => {} // Ignore all other keys
+    }
+    true // Always consume key events when dialog is active
+}
+
+fn handle_grep_dialog(app: &mut App, key_code: KeyCode) -> bool {
+    match key_code {
+        KeyCode::Char(c) => {
+            if let DialogMode::Grep { ref mut query, .. } = app.dialog {
+                query.push(c);
+                let query = query.clone();
+                app.restart_grep(&query);
+            }
+        },
+        KeyCode::Backspace => {
+            if let DialogMode::Grep { ref mut query, .. } = app.dialog {
+                query.pop();
+                let query = query.clone();
+                app.restart_grep(&query);
+            }
+        },
+        KeyCode::Up => {
+            if let DialogMode::Grep { ref mut selected, .. } = app.dialog {
+                *selected = selected.saturating_sub(1);
+            }
+        },
+        KeyCode::Down => {
+            if let DialogMode::Grep { ref hits, ref mut selected, .. } = app.dialog {
+                if *selected + 1 < hits.len() {
+                    *selected += 1;
+                }
+            }
+        },
+        KeyCode::Enter => {
+            let DialogMode::Grep { ref query, ref hits, selected } = app.dialog else { return true };
+            let Some(hit) = hits.get(selected).cloned() else {
+                app.status_message = Some("No results found".to_string());
+                return true;
+            };
+            let query = query.clone();
+            app.grep_job = None;
+            app.dialog = DialogMode::None;
+            app.open_in_viewer(&hit.path);
+            crate::events::show_viewer_line(app, hit.line - 1, &query);
+        },
+        KeyCode::Esc => {
+            app.grep_job = None;
+            app.dialog = DialogMode::None;
+        },
+        _ => {} // Ignore all other keys
+    }
+    true // Always consume key events when dialog is active
+}
+
+fn handle_open_with_dialog(app: &mut App, key_code: KeyCode) -> bool {
+    use crate::fs::open_with::{extension_key, OpenCommand};
+
+    match key_code {
+        KeyCode::Char(c) => {
+            if let DialogMode::OpenWith { ref mut command, .. } = app.dialog {
+                command.push(c);
+            }
+        },
+        KeyCode::Backspace => {
+            if let DialogMode::OpenWith { ref mut command, .. } = app.dialog {
+                command.pop();
+            }
+        },
+        KeyCode::Enter => {
+            let (path, command) = match std::mem::replace(&mut app.dialog, DialogMode::None) {
+                DialogMode::OpenWith { path, command } => (path, command),
+                _ => return true,
+            };
+            let Some(open) = OpenCommand::parse(&command, &path) else {
+                app.status_message = Some("Open with: no command given".to_string());
+                return true;
+            };
+
+            // Remember the command for this extension
+            app.config.open_with.insert(extension_key(&path), command.trim().to_string());
+            let _ = app.config.save();
+
+            if open.is_interactive() {
+                // The main loop releases the terminal before running it
+                app.pending_open = Some(open);
+            } else {
+                match open.spawn_detached() {
+                    Ok(()) => app.status_message = Some(format!("Opened with {}", open.program)),
+                    Err(e) => app.status_message = Some(format!("Failed to run {}: {}", open.program, e)),
+                }
+            }
+        },
+        KeyCode::Esc => {
             app.dialog = DialogMode::None;
         },
+        _ => {} // Ignore all other keys
+    }
+    true // Always consume key events when dialog is active
+}
+
+fn handle_goto_dialog(app: &mut App, key_code: KeyCode, base_dir: &std::path::Path) -> bool {
+    use crate::fs::path_complete::{complete, longest_common_prefix, resolve_input};
+
+    let DialogMode::GoTo { ref mut input, ref mut candidates, ref mut cycle, ref mut error } = app.dialog else {
+        return true;
+    };
+
+    match key_code {
+        KeyCode::Char(c) => {
+            input.push(c);
+            candidates.clear();
+            *cycle = None;
+            *error = None;
+        },
+        KeyCode::Backspace => {
+            input.pop();
+            candidates.clear();
+            *cycle = None;
+            *error = None;
+        },
+        KeyCode::Tab => {
+            if let Some(idx) = cycle {
+                // Repeated Tab cycles through the candidates
+                *idx = (*idx + 1) % candidates.len();
+                *input = candidates[*idx].clone();
+                return true;
+            }
+
+            *candidates = complete(input, base_dir);
+            match candidates.len() {
+                0 => *error = Some("No matches".to_string()),
+                1 => {
+                    *input = candidates[0].clone();
+                    candidates.clear();
+                },
+                _ => {
+                    let prefix = longest_common_prefix(candidates);
+                    if prefix.len() > input.len() {
+                        *input = prefix;
+                    } else {
+                        // Nothing more in common: start cycling
+                        *cycle = Some(0);
+                        *input = candidates[0].clone();
+                    }
+                }
+            }
+        },
+        KeyCode::Enter => {
+            let target = resolve_input(input, base_dir);
+            if target.is_dir() {
+                let fs = app.active_fs_mut();
+                fs.jump_to_dir(target.clone());
+                app.status_message = Some(format!("Jumped to {}", target.display()));
+                app.dialog = DialogMode::None;
+            } else if target.is_file() {
+                // Go to the file's directory and select it
+                if let Some(parent) = target.parent().map(|p| p.to_path_buf()) {
+                    let fs = app.active_fs_mut();
+                    fs.jump_to_dir(parent.clone());
+                    let entries = fs.entries_for_dir(&parent);
+                    if let Some(idx) = entries.iter().position(|p| p == &target) {
+                        fs.set_selection(parent, idx);
+                    }
+                }
+                app.status_message = Some(format!("Jumped to {}", target.display()));
+                app.dialog = DialogMode::None;
+            } else {
+                // Keep the dialog open so the path can be fixed
+                *error = Some(format!("No such directory: {}", target.display()));
+            }
+        },
         KeyCode::Esc => {
             app.dialog = DialogMode::None;
         },
@@ -262,11 +550,13 @@ fn handle_command_dialog(app: &mut App, key_code: KeyCode) -> bool {
             }
         },
         KeyCode::Enter => {
-            let command = if let DialogMode::Command { ref input } = app.dialog {
-                input.trim().to_lowercase()
+            // The lowercased command picks the action; arguments keep their case
+            let original = if let DialogMode::Command { ref input } = app.dialog {
+                input.trim().to_string()
             } else {
                 return true;
             };
+            let command = original.to_lowercase();
             app.dialog = DialogMode::None;
 
             // Parse and execute command
@@ -283,8 +573,21 @@ fn handle_command_dialog(app: &mut App, key_code: KeyCode) -> bool {
                 "quit" | "q" => {
                     app.should_quit = true;
                 },
-                _ => {
-                    app.status_message = Some(format!("Unknown command: {}", command));
+                "tasks" => {
+                    app.open_task_picker();
+                },
+                "sftp" => {
+                    app.dialog = DialogMode::SftpConnect { target: String::new() };
+                },
+                cmd if cmd.starts_with("sftp ") => {
+                    app.connect_sftp(original.get(5..).unwrap_or_default());
+                },
+                "local" => {
+                    app.unmount_remote();
+                },
+                _ => match crate::viewer::url::parse_command(&original) {
+                    Some(url) => app.open_url(url),
+                    None => app.status_message = Some(format!("Unknown command: {}", command)),
                 }
             }
         },
@@ -296,6 +599,31 @@ fn handle_command_dialog(app: &mut App, key_code: KeyCode) -> bool {
     true // Always consume key events when dialog is active
 }
 
+fn handle_sftp_connect_dialog(app: &mut App, key_code: KeyCode) -> bool {
+    match key_code {
+        KeyCode::Char(c) => {
+            if let DialogMode::SftpConnect { ref mut target } = app.dialog {
+                target.push(c);
+            }
+        },
+        KeyCode::Backspace => {
+            if let DialogMode::SftpConnect { ref mut target } = app.dialog {
+                target.pop();
+            }
+        },
+        KeyCode::Enter => {
+            if let DialogMode::SftpConnect { target } = std::mem::replace(&mut app.dialog, DialogMode::None) {
+                app.connect_sftp(&target);
+            }
+        },
+        KeyCode::Esc => {
+            app.dialog = DialogMode::None;
+        },
+        _ => {} // Ignore all other keys
+    }
+    true // Always consume key events when dialog is active
+}
+
 fn handle_quit_confirm_dialog(app: &mut App, key_code: KeyCode) -> bool {
     match key_code {
         KeyCode::Char('y') | KeyCode::Char('Y') => {
@@ -310,47 +638,200 @@ fn handle_quit_confirm_dialog(app: &mut App, key_code: KeyCode) -> bool {
     true // Always consume key events when dialog is active
 }
 
-/// Perform recursive file search in directory
-fn perform_search(dir: &PathBuf, query: &str) -> Vec<(PathBuf, usize)> {
-    use std::fs;
-
-    if query.is_empty() {
-        return Vec::new();
+fn handle_trust_plugin_dialog(app: &mut App, key_code: KeyCode, name: String) -> bool {
+    match key_code {
+        KeyCode::Char('y') | KeyCode::Char('Y') => app.trust_plugin(&name),
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {},
+        _ => app.dialog = DialogMode::TrustPlugin { name }, // Keep asking
     }
+    true // Always consume key events when dialog is active
+}
+
+fn handle_chmod_dialog(app: &mut App, key_code: KeyCode) -> bool {
+    use crate::fs::properties::{parse_octal_mode, toggle_permission_bit};
+    let DialogMode::Chmod { ref path, ref mut mode, ref mut cursor, ref mut octal, ref mut typed, ref mut confirm } = app.dialog else { return true };
 
-    let mut results = Vec::new();
-    let query_lower = query.to_lowercase();
-
-    // Search recursively
-    fn search_recursive(
-        dir: &PathBuf,
-        query: &str,
-        results: &mut Vec<(PathBuf, usize)>,
-        depth: usize,
-    ) {
-        // Limit recursion depth to avoid performance issues
-        if depth > 5 {
-            return;
+    if *confirm {
+        match key_code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                let (path, mode) = (path.clone(), *mode);
+                app.dialog = DialogMode::None;
+                app.apply_chmod(&path, mode);
+            },
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => *confirm = false,
+            _ => {} // Keep asking
         }
+        return true;
+    }
 
-        if let Ok(entries) = fs::read_dir(dir) {
-            for (idx, entry) in entries.flatten().enumerate() {
-                let path = entry.path();
-                if let Some(name) = path.file_name() {
-                    if name.to_string_lossy().to_lowercase().contains(query) {
-                        results.push((path.clone(), idx));
-                    }
-                }
+    match key_code {
+        KeyCode::Left | KeyCode::Char('h') => *cursor = cursor.saturating_sub(1),
+        KeyCode::Right | KeyCode::Char('l') => *cursor = (*cursor + 1).min(8),
+        KeyCode::Char(' ') | KeyCode::Char('x') => {
+            *mode = toggle_permission_bit(*mode, *cursor);
+            *octal = crate::app::format_octal_mode(*mode);
+            *typed = false;
+        },
+        // Typing digits edits the octal field (the first one replaces the shown mode);
+        // the checkboxes follow once it parses
+        KeyCode::Char(c @ '0'..='7') if !*typed || octal.len() < 4 => {
+            if !*typed {
+                octal.clear();
+                *typed = true;
+            }
+            octal.push(c);
+            if let Some(parsed) = parse_octal_mode(octal) {
+                *mode = parsed;
+            }
+        },
+        KeyCode::Backspace => {
+            octal.pop();
+            *typed = true;
+            if let Some(parsed) = parse_octal_mode(octal) {
+                *mode = parsed;
+            }
+        },
+        KeyCode::Enter => {
+            if parse_octal_mode(octal) == Some(*mode) {
+                *confirm = true;
+            } else {
+                app.status_message = Some(format!("'{}' is not an octal mode (e.g. 644 or 0755)", octal));
+            }
+        },
+        KeyCode::Esc => app.dialog = DialogMode::None,
+        _ => {}
+    }
+    true // Always consume key events when dialog is active
+}
+
+fn handle_reload_file_dialog(app: &mut App, key_code: KeyCode) -> bool {
+    match key_code {
+        KeyCode::Char('y') | KeyCode::Char('Y') => super::editor::reload_editor(app),
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {},
+        _ => app.dialog = DialogMode::ReloadFile, // Keep asking
+    }
+    true // Always consume key events when dialog is active
+}
+
+fn handle_batch_rename_dialog(app: &mut App, key_code: KeyCode) -> bool {
+    let DialogMode::BatchRename { ref paths, ref mut find, ref mut replace, ref mut editing_replace } = app.dialog else { return true };
+    match key_code {
+        KeyCode::Char(c) => if *editing_replace { replace.push(c) } else { find.push(c) },
+        KeyCode::Backspace => {
+            if *editing_replace { replace.pop() } else { find.pop() };
+        },
+        KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => *editing_replace = !*editing_replace,
+        KeyCode::Enter => {
+            match crate::fs::rename::pattern_plan(paths, find, replace) {
+                Ok(plan) => {
+                    app.dialog = DialogMode::None;
+                    app.apply_batch_rename(&plan);
+                },
+                // Keep the dialog open so the pattern can be fixed
+                Err(e) => app.status_message = Some(format!("Batch rename: {}", e)),
+            }
+        },
+        // Switch to editing the names as lines in the editor
+        KeyCode::F(2) => {
+            let paths = paths.clone();
+            app.dialog = DialogMode::None;
+            app.edit_batch_rename(paths);
+        },
+        KeyCode::Esc => app.dialog = DialogMode::None,
+        _ => {} // Ignore all other keys
+    }
+    true // Always consume key events when dialog is active
+}
 
-                // Recurse into subdirectories
-                if path.is_dir() && results.len() < 50 {
-                    search_recursive(&path, query, results, depth + 1);
+fn handle_template_picker_dialog(app: &mut App, key_code: KeyCode) -> bool {
+    match key_code {
+        KeyCode::Up | KeyCode::Char('k') => {
+            if let DialogMode::TemplatePicker { ref mut selected, .. } = app.dialog {
+                *selected = selected.saturating_sub(1);
+            }
+        },
+        KeyCode::Down | KeyCode::Char('j') => {
+            if let DialogMode::TemplatePicker { ref templates, ref mut selected } = app.dialog {
+                if *selected + 1 < templates.len() {
+                    *selected += 1;
                 }
             }
-        }
+        },
+        KeyCode::Enter => {
+            let DialogMode::TemplatePicker { ref templates, selected } = app.dialog else { return true };
+            // The template's own name is the suggested name
+            app.dialog = match templates.get(selected).cloned() {
+                Some(template) => DialogMode::TemplateName { name: template.name.clone(), template },
+                None => DialogMode::None,
+            };
+        },
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.dialog = DialogMode::None;
+        },
+        _ => {} // Ignore all other keys
+    }
+    true // Always consume key events when dialog is active
+}
+
+fn handle_template_name_dialog(app: &mut App, key_code: KeyCode) -> bool {
+    match key_code {
+        KeyCode::Char(c) => {
+            if let DialogMode::TemplateName { ref mut name, .. } = app.dialog {
+                name.push(c);
+            }
+        },
+        KeyCode::Backspace => {
+            if let DialogMode::TemplateName { ref mut name, .. } = app.dialog {
+                name.pop();
+            }
+        },
+        KeyCode::Enter => {
+            let DialogMode::TemplateName { template, name } = std::mem::replace(&mut app.dialog, DialogMode::None) else { return true };
+            if name.is_empty() {
+                app.dialog = DialogMode::TemplateName { template, name };
+                return true;
+            }
+            app.create_from_template(&template, &name);
+        },
+        KeyCode::Esc => {
+            app.dialog = DialogMode::None;
+        },
+        _ => {} // Ignore all other keys
     }
+    true // Always consume key events when dialog is active
+}
 
-    search_recursive(dir, &query_lower, &mut results, 0);
-    results.truncate(50); // Limit to 50 results
-    results
+fn handle_task_picker_dialog(app: &mut App, key_code: KeyCode, task_dir: &PathBuf) -> bool {
+    match key_code {
+        KeyCode::Up | KeyCode::Char('k') => {
+            if let DialogMode::TaskPicker { ref mut selected, .. } = app.dialog {
+                *selected = selected.saturating_sub(1);
+            }
+        },
+        KeyCode::Down | KeyCode::Char('j') => {
+            if let DialogMode::TaskPicker { ref tasks, ref mut selected } = app.dialog {
+                if *selected + 1 < tasks.len() {
+                    *selected += 1;
+                }
+            }
+        },
+        KeyCode::Enter => {
+            let task = if let DialogMode::TaskPicker { ref tasks, selected } = app.dialog {
+                tasks.get(selected).cloned()
+            } else {
+                return true;
+            };
+            app.dialog = DialogMode::None;
+            
+            if let Some(task) = task {
+                tracing::info!("Running task {:?} in {:?}", task.command, task_dir);
+                app.run_in_console(task_dir, &task.command);
+            }
+        },
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.dialog = DialogMode::None;
+        },
+        _ => {} // Ignore all other keys
+    }
+    true // Always consume key events when dialog is active
 }
@@ -3,7 +3,7 @@
 use crossterm::event::{KeyCode, KeyModifiers};
 use crate::app::App;
 use crate::viewer::VimMode;
-use crate::viewer::editor::PendingOperator;
+use crate::viewer::editor::{InsertEntry, NanoPrompt, PendingOperator, RepeatableChange, TextEditor, MAX_MACRO_DEPTH, MAX_MACRO_KEYS};
 use super::viewer::{exit_editor, save_file};
 
 /// Handle vim editor key events
@@ -13,17 +13,89 @@ pub fn handle_editor_keys(app: &mut App, key_code: KeyCode) {
         None => return,
     };
 
+    // Record keys for q<reg>; a plain 'q' in Normal mode ends the recording
+    if editor.recording_macro.is_some() && editor.macro_depth == 0 {
+        let stops = key_code == KeyCode::Char('q')
+            && editor.mode == VimMode::Normal
+            && editor.pending_op == PendingOperator::None
+            && editor.count_buffer.is_empty();
+        if stops {
+            editor.stop_macro_recording();
+            return;
+        }
+        editor.macro_buffer.push(key_code);
+    }
+
+    let register_selected = editor.pending_register.is_some();
     match editor.mode {
         VimMode::Normal => handle_normal_mode_keys(app, key_code),
         VimMode::Insert => handle_insert_mode_keys(app, key_code),
         VimMode::Command => handle_command_mode_keys(app, key_code),
-        VimMode::Visual | VimMode::VisualLine => handle_visual_mode_keys(app, key_code),
+        VimMode::Visual | VimMode::VisualLine | VimMode::VisualBlock => handle_visual_mode_keys(app, key_code),
+    }
+
+    // A selected register applies to the next complete command
+    if let Some(editor) = app.text_editor.as_mut() {
+        let in_visual = matches!(editor.mode, VimMode::Visual | VimMode::VisualLine | VimMode::VisualBlock);
+        if register_selected && !in_visual && editor.pending_op == PendingOperator::None && editor.count_buffer.is_empty() {
+            editor.finish_register();
+        }
+    }
+}
+
+/// Feed the keys of a macro back through the editor key handler `count` times
+fn play_macro(app: &mut App, keys: Vec<KeyCode>, count: usize) {
+    let Some(editor) = app.text_editor.as_mut() else { return };
+    if editor.macro_depth >= MAX_MACRO_DEPTH {
+        editor.status_message = "Macro nesting too deep".to_string();
+        return;
+    }
+    if editor.macro_depth == 0 {
+        editor.macro_keys_played = 0;
+    }
+    editor.macro_depth += 1;
+
+    'play: for _ in 0..count {
+        for &key in &keys {
+            let Some(editor) = app.text_editor.as_mut() else { return };
+            if editor.macro_keys_played >= MAX_MACRO_KEYS {
+                editor.status_message = "Macro stopped after too many keys".to_string();
+                break 'play;
+            }
+            editor.macro_keys_played += 1;
+            handle_editor_keys(app, key);
+        }
+    }
+
+    // The macro may have closed the editor (e.g. :q)
+    if let Some(editor) = app.text_editor.as_mut() {
+        editor.macro_depth -= 1;
     }
 }
 
 /// Handle Visual mode keys
 fn handle_visual_mode_keys(app: &mut App, key_code: KeyCode) {
     let editor = app.text_editor.as_mut().unwrap();
+
+    // "{reg}: register for the following y/d/c
+    if editor.pending_op == PendingOperator::Register {
+        editor.pending_op = PendingOperator::None;
+        if let KeyCode::Char(c) = key_code {
+            editor.select_register(c);
+        }
+        return;
+    }
+
+    // g-prefix: gg (first line) or gc (toggle comment on selection)
+    if editor.pending_op == PendingOperator::GPrefix {
+        editor.pending_op = PendingOperator::None;
+        match key_code {
+            KeyCode::Char('g') => editor.move_to_first_line(),
+            KeyCode::Char('c') => editor.toggle_comment_visual(),
+            _ => {}
+        }
+        return;
+    }
     
     match key_code {
         // Exit visual mode
@@ -43,7 +115,8 @@ fn handle_visual_mode_keys(app: &mut App, key_code: KeyCode) {
         KeyCode::Char('$') | KeyCode::End => editor.move_to_line_end(),
         KeyCode::Char('^') => editor.move_to_first_nonblank(),
         KeyCode::Char('G') => editor.move_to_last_line(),
-        KeyCode::Char('g') => editor.move_to_first_line(),
+        KeyCode::Char('g') => editor.pending_op = PendingOperator::GPrefix,
+        KeyCode::Char('"') => editor.pending_op = PendingOperator::Register,
         
         // Operations on selection
         KeyCode::Char('d') | KeyCode::Char('x') => {
@@ -60,6 +133,13 @@ fn handle_visual_mode_keys(app: &mut App, key_code: KeyCode) {
                 }
             }
         },
+        // Block insert/append/change on every row of the block
+        KeyCode::Char('I') | KeyCode::Char('A') if editor.mode == VimMode::VisualBlock => {
+            editor.block_insert(key_code == KeyCode::Char('A'));
+        },
+        KeyCode::Char('c') | KeyCode::Char('s') if editor.mode == VimMode::VisualBlock => {
+            editor.change_block();
+        },
         KeyCode::Char('c') | KeyCode::Char('s') => {
             editor.delete_visual_selection();
             let ed = app.text_editor.as_mut().unwrap();
@@ -70,8 +150,9 @@ fn handle_visual_mode_keys(app: &mut App, key_code: KeyCode) {
         KeyCode::Char('>') => {
             let (sr, _, er, _) = editor.get_visual_selection();
             editor.save_undo();
+            let unit = editor.indent_unit();
             for row in sr..=er {
-                editor.lines[row].insert_str(0, "    ");
+                editor.lines[row].insert_str(0, &unit);
             }
             editor.modified = true;
             editor.enter_normal_mode();
@@ -80,12 +161,7 @@ fn handle_visual_mode_keys(app: &mut App, key_code: KeyCode) {
             let (sr, _, er, _) = editor.get_visual_selection();
             editor.save_undo();
             for row in sr..=er {
-                let line = &mut editor.lines[row];
-                let mut removed = 0;
-                while removed < 4 && line.starts_with(' ') {
-                    line.remove(0);
-                    removed += 1;
-                }
+                editor.remove_indent_level(row);
             }
             editor.modified = true;
             editor.enter_normal_mode();
@@ -93,14 +169,13 @@ fn handle_visual_mode_keys(app: &mut App, key_code: KeyCode) {
         
         // Case toggle
         KeyCode::Char('~') | KeyCode::Char('u') | KeyCode::Char('U') => {
-            let (sr, sc, er, ec) = editor.get_visual_selection();
+            let (sr, _, er, _) = editor.get_visual_selection();
             editor.save_undo();
             
             for row in sr..=er {
+                let (start, end) = editor.visual_columns(row).unwrap_or_default();
                 let line = &editor.lines[row];
                 let chars: Vec<char> = line.chars().collect();
-                let start = if row == sr { sc } else { 0 };
-                let end = if row == er { ec + 1 } else { chars.len() };
                 
                 let new_line: String = chars.iter().enumerate().map(|(i, &c)| {
                     if i >= start && i < end {
@@ -158,7 +233,7 @@ fn handle_normal_mode_keys(app: &mut App, key_code: KeyCode) {
         match (editor.pending_op, key_code) {
             // dd - delete line(s)
             (PendingOperator::Delete, KeyCode::Char('d')) => {
-                editor.delete_lines(count);
+                editor.perform_change(RepeatableChange::DeleteLines(count));
                 editor.pending_op = PendingOperator::None;
                 return;
             },
@@ -170,29 +245,25 @@ fn handle_normal_mode_keys(app: &mut App, key_code: KeyCode) {
             },
             // cc - change line(s)
             (PendingOperator::Change, KeyCode::Char('c')) => {
-                editor.substitute_line();
+                editor.begin_insert(InsertEntry::SubstituteLine);
                 editor.pending_op = PendingOperator::None;
                 return;
             },
             // >> - indent
             (PendingOperator::Indent, KeyCode::Char('>')) => {
-                for _ in 0..count {
-                    editor.indent_line();
-                }
+                editor.perform_change(RepeatableChange::Indent(count));
                 editor.pending_op = PendingOperator::None;
                 return;
             },
             // << - outdent
             (PendingOperator::Outdent, KeyCode::Char('<')) => {
-                for _ in 0..count {
-                    editor.outdent_line();
-                }
+                editor.perform_change(RepeatableChange::Outdent(count));
                 editor.pending_op = PendingOperator::None;
                 return;
             },
             // d$ or D - delete to end
             (PendingOperator::Delete, KeyCode::Char('$')) => {
-                editor.delete_to_end();
+                editor.perform_change(RepeatableChange::DeleteToEnd);
                 editor.pending_op = PendingOperator::None;
                 return;
             },
@@ -204,15 +275,13 @@ fn handle_normal_mode_keys(app: &mut App, key_code: KeyCode) {
             },
             // c$ - change to end
             (PendingOperator::Change, KeyCode::Char('$')) => {
-                editor.change_to_end();
+                editor.begin_insert(InsertEntry::ChangeToEnd);
                 editor.pending_op = PendingOperator::None;
                 return;
             },
             // dw - delete word
             (PendingOperator::Delete, KeyCode::Char('w')) => {
-                for _ in 0..count {
-                    editor.delete_word();
-                }
+                editor.perform_change(RepeatableChange::DeleteWord(count));
                 editor.pending_op = PendingOperator::None;
                 return;
             },
@@ -244,13 +313,49 @@ fn handle_normal_mode_keys(app: &mut App, key_code: KeyCode) {
                 editor.pending_op = PendingOperator::None;
                 return;
             },
-            // gg - go to first line (when pending is not set, but we handle 'g' followed by 'g')
+            // q{reg} - start recording a macro
+            (PendingOperator::Record, KeyCode::Char(c)) => {
+                editor.pending_op = PendingOperator::None;
+                editor.start_macro_recording(c);
+                return;
+            },
+            // "{reg} - register for the next yank, delete or paste
+            (PendingOperator::Register, KeyCode::Char(c)) => {
+                editor.pending_op = PendingOperator::None;
+                if count > 1 {
+                    editor.count_buffer = count.to_string();
+                }
+                editor.select_register(c);
+                return;
+            },
+            // @{reg} / @@ - play a macro
+            (PendingOperator::Play, KeyCode::Char(c)) => {
+                editor.pending_op = PendingOperator::None;
+                if let Some(keys) = editor.macro_keys(c) {
+                    play_macro(app, keys, count);
+                }
+                return;
+            },
+            // gg - go to first line
+            (PendingOperator::GPrefix, KeyCode::Char('g')) => {
+                editor.move_to_first_line();
+                editor.pending_op = PendingOperator::None;
+                return;
+            },
+            // gc - toggle comment on current line(s)
+            (PendingOperator::GPrefix, KeyCode::Char('c')) => {
+                let start = editor.cursor_row;
+                editor.toggle_comment_lines(start, start + count - 1);
+                editor.pending_op = PendingOperator::None;
+                return;
+            },
             _ => {
                 editor.pending_op = PendingOperator::None;
             }
         }
     }
     
+    let explicit_count = !editor.count_buffer.is_empty();
     let count = editor.get_count();
     
     match key_code {
@@ -303,11 +408,9 @@ fn handle_normal_mode_keys(app: &mut App, key_code: KeyCode) {
             if count > 1 {
                 editor.move_to_line(count);
             } else {
-                // Wait for second 'g'
-                editor.pending_op = PendingOperator::Delete; // Reuse for 'gg' detection
+                // Wait for second key ('gg' or 'gc')
+                editor.pending_op = PendingOperator::GPrefix;
                 editor.status_message = "g".to_string();
-                // Actually, let's handle gg specially - just go to first line for single g
-                editor.move_to_first_line();
             }
         },
         KeyCode::Char('G') => {
@@ -322,23 +425,13 @@ fn handle_normal_mode_keys(app: &mut App, key_code: KeyCode) {
         KeyCode::Char('%') => editor.move_to_matching_bracket(),
         
         // Edit commands
-        KeyCode::Char('x') => {
-            for _ in 0..count {
-                editor.delete_char();
-            }
-        },
-        KeyCode::Char('X') => {
-            for _ in 0..count {
-                editor.delete_char_before();
-            }
-        },
+        KeyCode::Char('x') => editor.perform_change(RepeatableChange::DeleteChar(count)),
+        KeyCode::Char('X') => editor.perform_change(RepeatableChange::DeleteCharBefore(count)),
         KeyCode::Char('d') => {
             editor.pending_op = PendingOperator::Delete;
             editor.status_message = "d".to_string();
         },
-        KeyCode::Char('D') => {
-            editor.delete_to_end();
-        },
+        KeyCode::Char('D') => editor.perform_change(RepeatableChange::DeleteToEnd),
         KeyCode::Char('y') => {
             editor.pending_op = PendingOperator::Yank;
             editor.status_message = "y".to_string();
@@ -350,9 +443,7 @@ fn handle_normal_mode_keys(app: &mut App, key_code: KeyCode) {
             editor.pending_op = PendingOperator::Change;
             editor.status_message = "c".to_string();
         },
-        KeyCode::Char('C') => {
-            editor.change_to_end();
-        },
+        KeyCode::Char('C') => editor.begin_insert(InsertEntry::ChangeToEnd),
         KeyCode::Char('p') => editor.paste_after(),
         KeyCode::Char('P') => editor.paste_before(),
         
@@ -363,18 +454,17 @@ fn handle_normal_mode_keys(app: &mut App, key_code: KeyCode) {
             editor.mode = VimMode::Command;
             editor.command_buffer = "r".to_string();
         },
-        KeyCode::Char('s') => editor.substitute_char(),
-        KeyCode::Char('S') => editor.substitute_line(),
+        KeyCode::Char('s') => editor.begin_insert(InsertEntry::SubstituteChar),
+        KeyCode::Char('S') => editor.begin_insert(InsertEntry::SubstituteLine),
         
         // Undo/Redo
         KeyCode::Char('u') => editor.undo(),
         
+        // Repeat the last change
+        KeyCode::Char('.') => editor.repeat_last_change(explicit_count.then_some(count)),
+        
         // Join lines
-        KeyCode::Char('J') => {
-            for _ in 0..count {
-                editor.join_lines();
-            }
-        },
+        KeyCode::Char('J') => editor.perform_change(RepeatableChange::JoinLines(count)),
         
         // Indentation
         KeyCode::Char('>') => {
@@ -387,11 +477,7 @@ fn handle_normal_mode_keys(app: &mut App, key_code: KeyCode) {
         },
         
         // Case toggle
-        KeyCode::Char('~') => {
-            for _ in 0..count {
-                editor.toggle_case();
-            }
-        },
+        KeyCode::Char('~') => editor.perform_change(RepeatableChange::ToggleCase(count)),
         
         // Search
         KeyCode::Char('/') => editor.start_search_forward(),
@@ -413,35 +499,36 @@ fn handle_normal_mode_keys(app: &mut App, key_code: KeyCode) {
         KeyCode::Char('V') => editor.enter_visual_line_mode(),
         
         // Mode changes
-        KeyCode::Char('i') => editor.enter_insert_mode(),
-        KeyCode::Char('I') => {
-            editor.move_to_first_nonblank();
-            editor.enter_insert_mode();
-        },
-        KeyCode::Char('a') => {
-            editor.move_cursor_right();
-            editor.enter_insert_mode();
-        },
-        KeyCode::Char('A') => {
-            editor.move_to_line_end();
-            let line_len = crate::viewer::editor::char_count_pub(editor.get_current_line());
-            editor.cursor_col = line_len; // Allow cursor past last char in insert mode
-            editor.enter_insert_mode();
-        },
-        KeyCode::Char('o') => {
-            editor.save_undo();
-            editor.move_to_line_end();
-            editor.insert_newline();
-            editor.enter_insert_mode();
+        KeyCode::Char('i') => editor.begin_insert(InsertEntry::Insert),
+        KeyCode::Char('I') => editor.begin_insert(InsertEntry::LineStart),
+        KeyCode::Char('a') => editor.begin_insert(InsertEntry::Append),
+        KeyCode::Char('A') => editor.begin_insert(InsertEntry::LineEnd),
+        KeyCode::Char('o') => editor.begin_insert(InsertEntry::OpenBelow),
+        KeyCode::Char('O') => editor.begin_insert(InsertEntry::OpenAbove),
+        KeyCode::Char(':') => editor.enter_command_mode(),
+        
+        // Registers
+        KeyCode::Char('"') => {
+            if explicit_count {
+                editor.count_buffer = count.to_string();
+            }
+            editor.pending_op = PendingOperator::Register;
+            editor.status_message = "\"".to_string();
         },
-        KeyCode::Char('O') => {
-            editor.save_undo();
-            editor.lines.insert(editor.cursor_row, String::new());
-            editor.cursor_col = 0;
-            editor.modified = true;
-            editor.enter_insert_mode();
+        
+        // Macros
+        KeyCode::Char('q') => {
+            editor.pending_op = PendingOperator::Record;
+            editor.status_message = "q".to_string();
+        },
+        KeyCode::Char('@') => {
+            // Keep the count for the register key
+            if explicit_count {
+                editor.count_buffer = count.to_string();
+            }
+            editor.pending_op = PendingOperator::Play;
+            editor.status_message = "@".to_string();
         },
-        KeyCode::Char(':') => editor.enter_command_mode(),
         
         KeyCode::Esc => {
             editor.pending_op = PendingOperator::None;
@@ -459,34 +546,43 @@ fn handle_insert_mode_keys(app: &mut App, key_code: KeyCode) {
     
     match key_code {
         KeyCode::Esc => {
+            editor.finish_block_insert();
+            editor.finish_insert_record();
             editor.enter_normal_mode();
         },
         KeyCode::Char(c) => {
             editor.insert_char(c);
+            editor.record_insert(c);
         },
         KeyCode::Enter => {
             editor.insert_newline();
+            editor.record_insert('\n');
         },
         KeyCode::Backspace => {
-            editor.backspace();
+            let removed = editor.backspace();
+            editor.record_backspace(&removed);
         },
         KeyCode::Delete => {
             editor.delete_char();
+            editor.restart_insert_record();
         },
         KeyCode::Tab => {
-            // Insert 4 spaces for tab
-            for _ in 0..4 {
-                editor.insert_char(' ');
+            // One indent level: spaces, or a real tab with noexpandtab
+            for c in editor.indent_unit().chars() {
+                editor.insert_char(c);
+                editor.record_insert(c);
             }
         },
-        KeyCode::Left => editor.move_cursor_left(),
-        KeyCode::Right => editor.move_cursor_right(),
-        KeyCode::Up => editor.move_cursor_up(),
-        KeyCode::Down => editor.move_cursor_down(),
-        KeyCode::Home => editor.move_to_line_start(),
-        KeyCode::End => {
-            let line_len = crate::viewer::editor::char_count_pub(editor.get_current_line());
-            editor.cursor_col = line_len;
+        KeyCode::Left | KeyCode::Right | KeyCode::Up | KeyCode::Down | KeyCode::Home | KeyCode::End => {
+            match key_code {
+                KeyCode::Left => editor.move_cursor_left(),
+                KeyCode::Right => editor.move_cursor_right(),
+                KeyCode::Up => editor.move_cursor_up(),
+                KeyCode::Down => editor.move_cursor_down(),
+                KeyCode::Home => editor.move_to_line_start(),
+                _ => editor.cursor_col = crate::viewer::editor::char_count_pub(editor.get_current_line()),
+            }
+            editor.restart_insert_record();
         },
         _ => {}
     }
@@ -499,28 +595,17 @@ pub fn handle_nano_keys(app: &mut App, key_code: KeyCode, modifiers: KeyModifier
         None => return,
     };
     
-    // Check if in search mode
-    if editor.nano_search_mode {
+    // Answer the bottom-line prompt, if one is open
+    if editor.nano_prompt.is_some() {
+        handle_nano_prompt_keys(editor, key_code, modifiers);
+        return;
+    }
+
+    // Alt+G: Go to line, Alt+W: Repeat the last search
+    if modifiers.contains(KeyModifiers::ALT) {
         match key_code {
-            KeyCode::Esc => {
-                editor.nano_search_mode = false;
-                editor.command_buffer.clear();
-                editor.status_message = "Search cancelled".to_string();
-            },
-            KeyCode::Enter => {
-                editor.search_pattern = editor.command_buffer.clone();
-                editor.nano_search_mode = false;
-                editor.command_buffer.clear();
-                editor.search_next();
-            },
-            KeyCode::Char(c) => {
-                editor.command_buffer.push(c);
-                editor.status_message = format!("Search: {}", editor.command_buffer);
-            },
-            KeyCode::Backspace => {
-                editor.command_buffer.pop();
-                editor.status_message = format!("Search: {}", editor.command_buffer);
-            },
+            KeyCode::Char('g') | KeyCode::Char('G') => open_nano_prompt(editor, NanoPrompt::GoToLine),
+            KeyCode::Char('w') | KeyCode::Char('W') => editor.nano_search_next(),
             _ => {}
         }
         return;
@@ -539,13 +624,12 @@ pub fn handle_nano_keys(app: &mut App, key_code: KeyCode, modifiers: KeyModifier
             },
             // Ctrl+O: Save (WriteOut)
             KeyCode::Char('o') | KeyCode::Char('O') => {
-                let save_success = save_file(app);
+                let saved = save_file(app);
                 let editor = app.text_editor.as_mut().unwrap();
-                if save_success {
-                    editor.status_message = "File saved".to_string();
-                } else {
-                    editor.status_message = "Error saving file".to_string();
-                }
+                editor.status_message = match saved {
+                    Ok(()) => "File saved".to_string(),
+                    Err(e) => format!("Error saving file: {}", e),
+                };
             },
             // Ctrl+K: Cut line
             KeyCode::Char('k') | KeyCode::Char('K') => {
@@ -561,17 +645,16 @@ pub fn handle_nano_keys(app: &mut App, key_code: KeyCode, modifiers: KeyModifier
             },
             // Ctrl+W: Search (Where is)
             KeyCode::Char('w') | KeyCode::Char('W') => {
-                editor.nano_search_mode = true;
-                editor.command_buffer.clear();
-                editor.status_message = "Search: ".to_string();
+                open_nano_prompt(editor, NanoPrompt::Search);
             },
             // Ctrl+G: Help (display shortcuts)
             KeyCode::Char('g') | KeyCode::Char('G') => {
-                editor.status_message = "^X:Exit ^O:Save ^K:Cut ^U:Paste ^W:Search ^\\:Replace ^T:Vim".to_string();
+                editor.status_message = "^X:Exit ^O:Save ^K:Cut ^U:Paste ^W:Search ^\\:Replace ^_:Go to line ^T:Vim".to_string();
             },
             // Ctrl+\: Replace
-            KeyCode::Char('\\') => {
-                editor.status_message = "Replace: Use :s/old/new/g in Vim mode (^T to switch)".to_string();
+            // (terminals send Ctrl+\ as Ctrl+4)
+            KeyCode::Char('\\') | KeyCode::Char('4') => {
+                open_nano_prompt(editor, NanoPrompt::ReplaceSearch);
             },
             // Ctrl+A: Go to beginning of line
             KeyCode::Char('a') | KeyCode::Char('A') => {
@@ -590,9 +673,9 @@ pub fn handle_nano_keys(app: &mut App, key_code: KeyCode, modifiers: KeyModifier
             KeyCode::Char('v') | KeyCode::Char('V') => {
                 editor.move_page_down();
             },
-            // Ctrl+_: Go to line
-            KeyCode::Char('_') => {
-                editor.status_message = "Go to line: (not implemented in nano mode, use :n in Vim mode)".to_string();
+            // Ctrl+_: Go to line (sent as Ctrl+/ or Ctrl+7 by many terminals)
+            KeyCode::Char('_') | KeyCode::Char('/') | KeyCode::Char('7') => {
+                open_nano_prompt(editor, NanoPrompt::GoToLine);
             },
             // Ctrl+C: Show cursor position
             KeyCode::Char('c') | KeyCode::Char('C') => {
@@ -639,8 +722,8 @@ pub fn handle_nano_keys(app: &mut App, key_code: KeyCode, modifiers: KeyModifier
         },
         KeyCode::Tab => {
             editor.save_undo();
-            for _ in 0..4 {
-                editor.insert_char(' ');
+            for c in editor.indent_unit().chars() {
+                editor.insert_char(c);
             }
         },
         KeyCode::Left => editor.move_cursor_left(),
@@ -658,6 +741,78 @@ pub fn handle_nano_keys(app: &mut App, key_code: KeyCode, modifiers: KeyModifier
     }
 }
 
+/// Bottom-line label of a nano prompt; the last search pattern is offered as the default
+fn nano_prompt_label(editor: &TextEditor) -> String {
+    let default = if editor.search_pattern.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", editor.search_pattern)
+    };
+    match &editor.nano_prompt {
+        Some(NanoPrompt::Search) => format!("Search{}: {}", default, editor.command_buffer),
+        Some(NanoPrompt::ReplaceSearch) => format!("Search (to replace){}: {}", default, editor.command_buffer),
+        Some(NanoPrompt::ReplaceWith { .. }) => format!("Replace with: {}", editor.command_buffer),
+        Some(NanoPrompt::GoToLine) => format!("Enter line number, column number: {}", editor.command_buffer),
+        Some(NanoPrompt::ReplaceConfirm { .. }) | None => editor.status_message.clone(),
+    }
+}
+
+fn open_nano_prompt(editor: &mut TextEditor, prompt: NanoPrompt) {
+    editor.nano_prompt = Some(prompt);
+    editor.command_buffer.clear();
+    editor.status_message = nano_prompt_label(editor);
+}
+
+/// Keys typed while a nano prompt (search, go to line, replace) is open
+fn handle_nano_prompt_keys(editor: &mut TextEditor, key_code: KeyCode, modifiers: KeyModifiers) {
+    let cancel = key_code == KeyCode::Esc
+        || (modifiers.contains(KeyModifiers::CONTROL) && matches!(key_code, KeyCode::Char('c') | KeyCode::Char('C')));
+
+    if matches!(editor.nano_prompt, Some(NanoPrompt::ReplaceConfirm { .. })) {
+        match key_code {
+            _ if cancel => editor.nano_finish_replace(),
+            KeyCode::Char(c) => editor.nano_answer_replace(c),
+            _ => {}
+        }
+        return;
+    }
+
+    match key_code {
+        _ if cancel => {
+            editor.nano_prompt = None;
+            editor.command_buffer.clear();
+            editor.status_message = "Cancelled".to_string();
+        },
+        KeyCode::Enter => {
+            let input = std::mem::take(&mut editor.command_buffer);
+            // An empty search answer reuses the previous pattern
+            let pattern = if input.is_empty() { editor.search_pattern.clone() } else { input.clone() };
+            match editor.nano_prompt.take() {
+                Some(NanoPrompt::Search) => {
+                    editor.search_pattern = pattern;
+                    editor.nano_search_next();
+                },
+                Some(NanoPrompt::ReplaceSearch) if pattern.is_empty() => {
+                    editor.status_message = "No search pattern".to_string();
+                },
+                Some(NanoPrompt::ReplaceSearch) => open_nano_prompt(editor, NanoPrompt::ReplaceWith { pattern }),
+                Some(NanoPrompt::ReplaceWith { pattern }) => editor.nano_start_replace(pattern, input),
+                Some(NanoPrompt::GoToLine) => editor.nano_go_to_line(&input),
+                _ => {}
+            }
+        },
+        KeyCode::Char(c) => {
+            editor.command_buffer.push(c);
+            editor.status_message = nano_prompt_label(editor);
+        },
+        KeyCode::Backspace => {
+            editor.command_buffer.pop();
+            editor.status_message = nano_prompt_label(editor);
+        },
+        _ => {}
+    }
+}
+
 /// Handle Command mode keys
 fn handle_command_mode_keys(app: &mut App, key_code: KeyCode) {
     let editor = app.text_editor.as_mut().unwrap();
@@ -666,7 +821,7 @@ fn handle_command_mode_keys(app: &mut App, key_code: KeyCode) {
     if editor.command_buffer == "r" {
         if let KeyCode::Char(c) = key_code {
             editor.command_buffer.clear();
-            editor.replace_char(c);
+            editor.perform_change(RepeatableChange::ReplaceChar(c));
             editor.enter_normal_mode();
             return;
         } else if key_code == KeyCode::Esc {
@@ -742,9 +897,12 @@ fn execute_command(app: &mut App) {
         },
         "w" => {
             // Save file
-            let save_success = save_file(app);
+            let saved = save_file(app);
             let editor = app.text_editor.as_mut().unwrap();
-            if save_success {
+            if let Err(e) = saved {
+                editor.status_message = format!("Error: Could not save file: {}", e);
+                editor.enter_normal_mode();
+            } else {
                 editor.status_message = format!("File saved{}", 
                     if let Some(path) = &editor.file_path {
                         format!(": {:?}", path.file_name().unwrap_or_default())
@@ -753,47 +911,52 @@ fn execute_command(app: &mut App) {
                     }
                 );
                 editor.enter_normal_mode();
-            } else {
-                editor.status_message = "Error: Could not save file".to_string();
-                editor.enter_normal_mode();
             }
         },
         "wq" | "x" | "wq!" | "x!" => {
             // Save and quit
-            if save_file(app) {
-                exit_editor(app, true);
+            match save_file(app) {
+                Ok(()) => exit_editor(app, true),
+                Err(e) => {
+                    let editor = app.text_editor.as_mut().unwrap();
+                    editor.status_message = format!("Error: Could not save file: {}", e);
+                    editor.enter_normal_mode();
+                },
+            }
+        },
+        "e" => {
+            // Reload file, asking first if there are unsaved changes
+            let editor = app.text_editor.as_mut().unwrap();
+            editor.enter_normal_mode();
+            if editor.modified {
+                app.dialog = crate::app::DialogMode::ReloadFile;
             } else {
-                let editor = app.text_editor.as_mut().unwrap();
-                editor.status_message = "Error: Could not save file".to_string();
-                editor.enter_normal_mode();
+                reload_editor(app);
             }
         },
         "e!" => {
             // Reload file (discard changes)
-            let editor = app.text_editor.as_mut().unwrap();
-            if let Some(path) = editor.file_path.clone() {
-                if let Ok(content) = std::fs::read_to_string(&path) {
-                    editor.lines = content.lines().map(|s| s.to_string()).collect();
-                    if editor.lines.is_empty() {
-                        editor.lines.push(String::new());
-                    }
-                    editor.cursor_row = 0;
-                    editor.cursor_col = 0;
-                    editor.modified = false;
-                    editor.undo_stack.clear();
-                    editor.redo_stack.clear();
-                    editor.status_message = "File reloaded".to_string();
-                } else {
-                    editor.status_message = "Error reloading file".to_string();
-                }
-            }
-            editor.enter_normal_mode();
+            app.text_editor.as_mut().unwrap().enter_normal_mode();
+            reload_editor(app);
         },
         "set nu" | "set number" => {
             let editor = app.text_editor.as_mut().unwrap();
             editor.status_message = "Line numbers are always shown".to_string();
             editor.enter_normal_mode();
         },
+        cmd if ["w ", "w! ", "saveas ", "saveas! ", "sav ", "sav! "].iter().any(|prefix| cmd.starts_with(prefix)) => {
+            // Save as (:w <path>, :saveas <path>); '!' overwrites an existing file
+            let (command, target) = cmd.split_once(' ').unwrap_or_default();
+            save_as(app, target.trim(), command.ends_with('!'));
+        },
+        cmd if cmd.starts_with("set ") => {
+            // File format options (ff=unix|dos, eol/noeol)
+            let editor = app.text_editor.as_mut().unwrap();
+            editor.status_message = match editor.set_option(cmd["set ".len()..].trim()) {
+                Ok(message) | Err(message) => message,
+            };
+            editor.enter_normal_mode();
+        },
         "noh" | "nohlsearch" => {
             let editor = app.text_editor.as_mut().unwrap();
             editor.search_pattern.clear();
@@ -820,63 +983,84 @@ fn execute_command(app: &mut App) {
     }
 }
 
-/// Execute substitute command (:s/old/new/g or :%s/old/new/g)
-fn execute_substitute(app: &mut App, command: &str) {
-    let editor = app.text_editor.as_mut().unwrap();
-    
-    let global = command.starts_with("%s/");
-    let parts: Vec<&str> = if global {
-        command[3..].splitn(3, '/').collect()
-    } else {
-        command[2..].splitn(3, '/').collect()
-    };
-    
-    if parts.len() < 2 {
-        editor.status_message = "Invalid substitute command".to_string();
+/// Write the buffer to `target` and keep editing that file
+/// Relative paths resolve against the edited file's directory (the active pane's for a new buffer).
+/// Another existing file is only overwritten with `force` (`:w!`).
+fn save_as(app: &mut App, target: &str, force: bool) {
+    if target.is_empty() {
+        let editor = app.text_editor.as_mut().unwrap();
+        editor.status_message = "Argument required: file name".to_string();
         editor.enter_normal_mode();
         return;
     }
-    
-    let pattern = parts[0];
-    let replacement = parts[1];
-    let flags = if parts.len() > 2 { parts[2] } else { "" };
-    let replace_all = flags.contains('g');
-    
-    editor.save_undo();
-    
-    let mut total_replacements = 0;
-    
-    if global {
-        // Replace in all lines
-        for line in &mut editor.lines {
-            if replace_all {
-                let count = line.matches(pattern).count();
-                total_replacements += count;
-                *line = line.replace(pattern, replacement);
-            } else if line.contains(pattern) {
-                *line = line.replacen(pattern, replacement, 1);
-                total_replacements += 1;
-            }
-        }
-    } else {
-        // Replace in current line only
-        let line = &mut editor.lines[editor.cursor_row];
-        if replace_all {
-            let count = line.matches(pattern).count();
-            total_replacements += count;
-            *line = line.replace(pattern, replacement);
-        } else if line.contains(pattern) {
-            *line = line.replacen(pattern, replacement, 1);
-            total_replacements += 1;
-        }
+    let base = app.text_editor.as_ref()
+        .and_then(|editor| editor.file_path.as_ref())
+        .and_then(|path| path.parent())
+        .map(|dir| dir.to_path_buf())
+        .unwrap_or_else(|| app.active_fs().current_dir.clone());
+    let path = crate::fs::path_complete::resolve_input(target, &base);
+
+    let editor = app.text_editor.as_mut().unwrap();
+    editor.enter_normal_mode();
+    if !force && editor.file_path.as_ref() != Some(&path) && std::fs::symlink_metadata(&path).is_ok() {
+        editor.status_message = format!("Error: {} exists (add ! to overwrite)", path.display());
+        return;
     }
-    
-    if total_replacements > 0 {
-        editor.modified = true;
-        editor.status_message = format!("{} substitution(s) made", total_replacements);
+    let content = match editor.encoded_content() {
+        Ok(content) => content,
+        Err(e) => {
+            editor.status_message = format!("Error: could not write {}: {}", path.display(), e);
+            return;
+        },
+    };
+    let written = match path.parent() {
+        Some(parent) => std::fs::create_dir_all(parent),
+        None => Ok(()),
+    }
+    .and_then(|_| std::fs::write(&path, &content));
+
+    match written {
+        Ok(()) => {
+            editor.file_path = Some(path.clone());
+            editor.modified = false;
+            editor.status_message = format!("\"{}\" {} bytes written", path.display(), content.len());
+            app.viewer_path = Some(path);
+            app.mark_viewer_fresh();
+            // Show the new file in the panes
+            app.refresh_both_panes();
+        },
+        Err(e) => {
+            editor.status_message = format!("Error: could not write {}: {}", path.display(), e);
+        },
+    }
+}
+
+/// Re-read the edited file from disk, discarding unsaved changes
+pub(super) fn reload_editor(app: &mut App) {
+    let Some(editor) = app.text_editor.as_mut() else { return };
+    let Some(path) = editor.file_path.clone() else { return };
+    if let Ok(content) = crate::viewer::read_text(&path, editor.encoding.map(|encoding| encoding.encoding)) {
+        editor.load_content(&content);
+        editor.undo_stack.clear();
+        editor.redo_stack.clear();
+        editor.status_message = "File reloaded".to_string();
+        app.mark_viewer_fresh();
     } else {
-        editor.status_message = format!("Pattern not found: {}", pattern);
+        editor.status_message = "Error reloading file".to_string();
     }
+}
+
+/// Execute substitute command (:s/old/new/g or :%s/old/new/g)
+fn execute_substitute(app: &mut App, command: &str) {
+    let regex = app.config.editor_regex_substitute;
+    let editor = app.text_editor.as_mut().unwrap();
+    editor.status_message = match crate::viewer::editor::parse_substitute(command) {
+        Some(substitute) => match editor.substitute(&substitute, regex) {
+            Ok(count) => format!("{} substitution(s) made", count),
+            Err(message) => message,
+        },
+        None => "Invalid substitute command".to_string(),
+    };
     editor.enter_normal_mode();
 }
 
@@ -1,16 +1,85 @@
 //! File manager event handling
 
 use crossterm::event::KeyCode;
-use crate::app::{App, AppMode};
+use crate::app::App;
+
+/// Feed a key to an active type-ahead find; returns true if it was consumed
+/// Once the buffer times out, keys go back to their normal hotkeys.
+pub fn handle_type_ahead_keys(app: &mut App, key_code: KeyCode) -> bool {
+    let now = std::time::Instant::now();
+    let Some(type_ahead) = &mut app.type_ahead else { return false };
+    if type_ahead.is_expired(now) {
+        app.type_ahead = None;
+        return false;
+    }
+
+    let cycle = match key_code {
+        KeyCode::Char(c) => type_ahead.push(c, now),
+        KeyCode::Backspace => {
+            type_ahead.buffer.pop();
+            type_ahead.last_keystroke = now;
+            false
+        },
+        KeyCode::Esc => {
+            app.type_ahead = None;
+            app.status_message = None;
+            return true;
+        },
+        // Any other key ends the find and keeps its usual meaning
+        _ => {
+            app.type_ahead = None;
+            return false;
+        }
+    };
+
+    let Some(active_dir) = crate::navigation::get_active_directory(app.active_fs()) else { return true };
+    let entries = app.active_fs().entries_for_dir(&active_dir);
+    let selected = app.active_fs().get_selection(&active_dir);
+    // The parent entry has no name to match
+    let names: Vec<String> = entries.iter()
+        .map(|p| if active_dir.parent() == Some(p.as_path()) {
+            String::new()
+        } else {
+            p.file_name().unwrap_or_default().to_string_lossy().to_string()
+        })
+        .collect();
+
+    let Some(type_ahead) = &app.type_ahead else { return true };
+    let start = if cycle { selected + 1 } else { selected };
+    let found = type_ahead.find(&names, start);
+    let buffer = type_ahead.buffer.clone();
+    match found {
+        Some(index) => {
+            app.active_fs_mut().set_selection(active_dir, index);
+            app.status_message = Some(format!("Find: {}", buffer));
+        },
+        None => app.status_message = Some(format!("Find: {} (no match)", buffer)),
+    }
+    true
+}
+
+/// Keys that work in a remote pane: moving around, viewing and the pane's display settings
+fn works_in_remote_pane(key_code: KeyCode) -> bool {
+    matches!(
+        key_code,
+        KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right | KeyCode::Enter | KeyCode::Backspace
+            | KeyCode::Char('-' | 'v' | 'V' | '.' | 'w' | 'f' | 's' | 'S' | ':' | 'y' | 'Y')
+    )
+}
 
 /// Handle file manager specific key events
 pub fn handle_file_manager_keys(app: &mut App, key_code: KeyCode) {
+    if app.active_fs().is_remote() && !works_in_remote_pane(key_code) {
+        app.status_message = Some(crate::app::REMOTE_READ_ONLY.to_string());
+        return;
+    }
     match key_code {
         // File operations
+        KeyCode::F(2) if !app.active_fs().marked.is_empty() => app.open_batch_rename(),
         KeyCode::F(2) => {
             // Rename file/folder (from active directory / PATH)
             if let Some(active_dir) = crate::navigation::get_active_directory(app.active_fs()) {
-                let entries = crate::fs::FileSystem::get_entries_for_dir(&active_dir);
+                let entries = app.active_fs().entries_for_dir(&active_dir);
                 let selected_index = app.active_fs_mut().get_selection(&active_dir);
                 if let Some(path) = entries.get(selected_index) {
                     let current_name = path.file_name()
@@ -25,19 +94,16 @@ pub fn handle_file_manager_keys(app: &mut App, key_code: KeyCode) {
             }
         },
         KeyCode::Delete => {
-            // Delete file/folder (with confirmation, from active directory / PATH)
-            if let Some(active_dir) = crate::navigation::get_active_directory(app.active_fs()) {
-                let entries = crate::fs::FileSystem::get_entries_for_dir(&active_dir);
-                let selected_index = app.active_fs_mut().get_selection(&active_dir);
-                if let Some(path) = entries.get(selected_index) {
-                    let path_name = path.file_name()
-                        .unwrap_or_default()
-                        .to_string_lossy()
-                        .to_string();
-                    app.dialog = crate::app::DialogMode::Delete { path_name };
-                }
-            }
+            // Delete (trash if enabled) with confirmation
+            let permanent = !app.config.use_trash;
+            open_delete_dialog(app, permanent);
+        },
+        KeyCode::Char('D') => {
+            // Permanent delete, bypassing the trash
+            open_delete_dialog(app, true);
         },
+        // Undo the most recent rename, move, paste, create or trash deletion
+        KeyCode::Char('u') => app.undo_last_operation(),
         KeyCode::F(7) => {
             // New folder
             app.dialog = crate::app::DialogMode::NewFolder { name: String::new() };
@@ -82,18 +148,31 @@ pub fn handle_file_manager_keys(app: &mut App, key_code: KeyCode) {
             handle_enter_key(app);
         },
         KeyCode::Backspace => app.active_fs_mut().go_back(),
+        KeyCode::Char('-') => {
+            // Toggle between current and previous directory (like `cd -`)
+            if app.active_fs_mut().toggle_previous_dir() {
+                let dir = app.active_fs().current_dir.display().to_string();
+                app.status_message = Some(format!("Switched to {}", dir));
+            } else {
+                app.status_message = Some("No previous directory".to_string());
+            }
+        },
         KeyCode::Char('v') | KeyCode::Char('V') => {
             // Open file in viewer (force)
             let current_dir = app.active_fs_mut().current_dir.clone();
-            let entries = crate::fs::FileSystem::get_entries_for_dir(&current_dir);
+            let entries = app.active_fs().entries_for_dir(&current_dir);
             let selected_index = app.active_fs_mut().get_selection(&current_dir);
             if let Some(path) = entries.get(selected_index) {
-                if path.is_file() {
+                if app.active_fs().is_remote() {
+                    if !app.active_fs().is_dir(path) {
+                        let path = path.clone();
+                        app.open_remote_file(&path);
+                    }
+                } else if path.is_file() {
                     // Check if file type is supported before opening viewer
-                    if crate::viewer::is_supported_file_type(path) {
-                        app.viewer_content = Some(crate::viewer::load_file(path));
-                        app.viewer_scroll = 0;
-                        app.mode = AppMode::Viewer;
+                    if app.can_preview(path) {
+                        let path = path.clone();
+                        app.open_in_viewer(&path);
                     } else {
                         // Show temporary message for unsupported file types
                         app.set_temp_message("미리보기가 지원되지 않는 파일 형식입니다".to_string());
@@ -101,27 +180,109 @@ pub fn handle_file_manager_keys(app: &mut App, key_code: KeyCode) {
                 }
             }
         },
+        // Show/hide dotfiles
+        KeyCode::Char('.') => {
+            let show = !app.active_fs().show_hidden;
+            app.active_fs_mut().set_show_hidden(show);
+            app.status_message = Some(format!("Hidden files: {}", if show { "shown" } else { "hidden" }));
+        },
+        // Preview column for the selected file (reads it on every selection move)
+        KeyCode::Char('w') => {
+            app.config.show_preview = !app.config.show_preview;
+            let _ = app.config.save();
+            app.status_message = Some(format!("Preview column: {}", if app.config.show_preview { "ON" } else { "OFF" }));
+        },
+        // Jump to a typed path (Tab completes)
+        KeyCode::Char('g') => {
+            app.dialog = crate::app::DialogMode::GoTo {
+                input: String::new(),
+                candidates: Vec::new(),
+                cycle: None,
+                error: None,
+            };
+        },
+        // Open with an external program
+        KeyCode::Char('o') => {
+            if let Some(active_dir) = crate::navigation::get_active_directory(app.active_fs()) {
+                let entries = app.active_fs().entries_for_dir(&active_dir);
+                let selected_index = app.active_fs().get_selection(&active_dir);
+                let is_parent = |p: &std::path::PathBuf| active_dir.parent() == Some(p.as_path());
+                if let Some(path) = entries.get(selected_index).filter(|p| !is_parent(p)) {
+                    // Pre-fill the command last used for this extension
+                    let command = app.config.open_with
+                        .get(&crate::fs::open_with::extension_key(path))
+                        .cloned()
+                        .unwrap_or_else(|| crate::fs::open_with::default_command().to_string());
+                    app.dialog = crate::app::DialogMode::OpenWith { path: path.clone(), command };
+                }
+            }
+        },
+        // Type-ahead find: following letters jump to matching entries
+        KeyCode::Char('f') => {
+            app.type_ahead = Some(crate::app::TypeAhead::new(std::time::Instant::now()));
+            app.status_message = Some("Find: ".to_string());
+        },
+        // Extract the selected archive into a folder named after it
+        KeyCode::Char('e') => app.start_extract(),
+        // Compare the marked file with the selection (or two marked files)
+        KeyCode::Char('=') => app.open_diff(),
+        // Toggle detailed view (size and modified time columns)
+        KeyCode::Char('i') => {
+            let fs = app.active_fs_mut();
+            fs.view_mode = fs.view_mode.toggle();
+            let label = match fs.view_mode {
+                crate::fs::ViewMode::Compact => "compact",
+                crate::fs::ViewMode::Detailed => "detailed",
+            };
+            app.status_message = Some(format!("View: {}", label));
+        },
+        // Toggle mark on the selected entry and move down
+        KeyCode::Char(' ') => {
+            if let Some(active_dir) = crate::navigation::get_active_directory(app.active_fs()) {
+                let entries = app.active_fs().entries_for_dir(&active_dir);
+                let selected_index = app.active_fs().get_selection(&active_dir);
+                if let Some(path) = entries.get(selected_index).cloned() {
+                    // The parent entry can't be marked
+                    if Some(path.as_path()) != active_dir.parent() {
+                        app.active_fs_mut().toggle_mark(path);
+                    }
+                    app.active_fs_mut().navigate_down(&active_dir);
+                    let count = app.active_fs().marked.len();
+                    app.status_message = Some(format!("{} selected", count));
+                }
+            }
+        },
         // Standalone clipboard keys (c/x/p)
         KeyCode::Char('c') | KeyCode::Char('C') => {
             app.active_fs_mut().copy_selected();
-            app.status_message = Some("Copied to clipboard".to_string());
+            app.status_message = Some(app.clipboard_status("Copied"));
         },
         KeyCode::Char('x') | KeyCode::Char('X') => {
             app.active_fs_mut().cut_selected();
-            app.status_message = Some("Cut to clipboard".to_string());
+            app.status_message = Some(app.clipboard_status("Cut"));
         },
         KeyCode::Char('p') | KeyCode::Char('P') => {
-            app.active_fs_mut().paste();
-            app.status_message = Some("Pasted from clipboard".to_string());
-            app.refresh_both_panes();
+            app.start_paste();
         },
+        // Copy the absolute path (y) or file name (Y) to the system clipboard
+        KeyCode::Char('y') => app.copy_paths_to_clipboard(false),
+        KeyCode::Char('Y') => app.copy_paths_to_clipboard(true),
+        // Console shell: cd to the browsed directory (z) / browse the shell's directory (Z)
+        KeyCode::Char('z') => app.send_dir_to_console(false),
+        KeyCode::Char('Z') => app.follow_console_dir(),
         // Search mode
         KeyCode::Char('/') => {
             app.dialog = crate::app::DialogMode::Search {
                 query: String::new(),
                 results: Vec::new(),
+                selected: 0,
             };
         },
+        // Task runner (npm scripts, make targets, cargo, just)
+        KeyCode::Char('t') => {
+            app.open_task_picker();
+        },
+        KeyCode::Char('N') => app.open_template_picker(),
         // Command mode (Vim-style)
         KeyCode::Char(':') => {
             app.dialog = crate::app::DialogMode::Command {
@@ -130,63 +291,113 @@ pub fn handle_file_manager_keys(app: &mut App, key_code: KeyCode) {
         },
         // Bookmark operations
         KeyCode::Char('b') => {
-            // Add current directory to bookmarks
+            // Bookmark the current directory, asking for a label
             let current_dir = app.active_fs_mut().current_dir.clone();
-            if !app.config.bookmarks.contains(&current_dir) {
-                app.config.bookmarks.push(current_dir.clone());
-                let _ = app.config.save();
-                app.status_message = Some("Bookmarked current directory".to_string());
-            } else {
+            if app.config.bookmarks.iter().any(|b| b.path == current_dir) {
                 app.status_message = Some("Already bookmarked".to_string());
+            } else {
+                let name = crate::config::Bookmark::default_label(&current_dir);
+                app.dialog = crate::app::DialogMode::AddBookmark { path: current_dir, name };
             }
         },
         KeyCode::Char('B') => {
             // Toggle bookmark list
-            app.show_bookmarks = !app.show_bookmarks;
-        },
-        // Sort option cycling
-        KeyCode::Char('s') | KeyCode::Char('S') => {
-            use crate::config::SortOption;
-            app.active_fs_mut().sort_option = match app.active_fs_mut().sort_option {
-                SortOption::Name => SortOption::Size,
-                SortOption::Size => SortOption::Modified,
-                SortOption::Modified => SortOption::Name,
+            app.bookmark_list = match app.bookmark_list {
+                Some(_) => None,
+                None => Some(crate::app::BookmarkList::default()),
             };
-            app.config.sort_option = app.active_fs_mut().sort_option;
+        },
+        KeyCode::Char('U') => app.open_disk_usage(),
+        KeyCode::Char('I') => app.open_properties(),
+        KeyCode::Char('J') => {
+            // Jump list of recently and frequently entered directories
+            app.record_dir_visits();
+            app.jump_list = Some(crate::app::JumpList::default());
+        },
+        // Sort option cycling (the active pane only; each pane keeps its own)
+        KeyCode::Char('s') => {
+            let active_dir = crate::navigation::get_active_directory(app.active_fs());
+            if app.config.persist_dir_sort && !app.active_fs().is_remote() {
+                // Remember the order for this directory only
+                if let Some(dir) = active_dir {
+                    let next = app.active_fs().effective_sort(&dir).next();
+                    app.status_message = Some(match crate::fs::FileSystem::write_dir_sort(&dir, next) {
+                        Ok(_) => format!("Sorting by: {} (saved for this directory)", sort_label(next)),
+                        Err(e) => format!("Failed to save sort order: {}", e),
+                    });
+                }
+            } else {
+                let next = app.active_fs().sort_option.next();
+                app.active_fs_mut().sort_option = next;
+                app.config.sort_option = next;
+                let _ = app.config.save();
+                let overridden = active_dir
+                    .filter(|_| !app.active_fs().is_remote())
+                    .and_then(|dir| crate::fs::FileSystem::dir_sort_override(&dir))
+                    .is_some();
+                app.status_message = Some(if overridden {
+                    format!("Sorting by: {} (this directory has a .senterm-sort override)", sort_label(next))
+                } else {
+                    format!("Sorting by: {}", sort_label(next))
+                });
+            }
+        },
+        KeyCode::Char('S') => {
+            let reverse = !app.active_fs().sort_reverse;
+            app.active_fs_mut().set_sort_reverse(reverse);
+            app.config.sort_reverse = reverse;
             let _ = app.config.save();
-            let sort_name = match app.active_fs_mut().sort_option {
-                SortOption::Name => "Name",
-                SortOption::Size => "Size",
-                SortOption::Modified => "Modified Date",
-            };
-            app.status_message = Some(format!("Sorting by: {}", sort_name));
+            app.status_message = Some(if reverse {
+                "Sort order: reversed (Z-A, smallest and oldest first)".to_string()
+            } else {
+                "Sort order: normal (A-Z, largest and newest first)".to_string()
+            });
         },
         _ => {}
     }
 }
 
+fn sort_label(option: crate::config::SortOption) -> &'static str {
+    use crate::config::SortOption;
+    match option {
+        SortOption::Name => "Name",
+        SortOption::Natural => "Natural Name",
+        SortOption::Size => "Size",
+        SortOption::Modified => "Modified Date",
+    }
+}
+
 /// Handle Enter key in file manager
 fn handle_enter_key(app: &mut App) {
     // Get the active directory (currently focused column)
     if let Some(active_dir) = crate::navigation::get_active_directory(app.active_fs()) {
-        let entries = crate::fs::FileSystem::get_entries_for_dir(&active_dir);
+        let entries = app.active_fs().entries_for_dir(&active_dir);
         let selected_index = app.active_fs_mut().get_selection(&active_dir);
         
         if let Some(path) = entries.get(selected_index) {
-            if path.is_file() {
+            let is_remote = app.active_fs().is_remote();
+            let is_dir = app.active_fs().is_dir(path);
+            if !is_remote && crate::fs::is_broken_symlink(path) {
+                let target = crate::fs::symlink_target(path).unwrap_or_default();
+                app.status_message = Some(format!("Broken symlink → {}", target.display()));
+            } else if is_dir && app.active_fs().is_symlink_loop(path) {
+                app.status_message = Some(format!("Symlink loop: {} is already open", path.display()));
+            } else if is_remote && !is_dir {
+                let path = path.clone();
+                app.open_remote_file(&path);
+            } else if path.is_file() {
                 // Check if file type is supported before opening viewer
-                if crate::viewer::is_supported_file_type(path) {
+                if app.can_preview(path) {
                     // Clear editor state and open file in viewer popup
                     app.text_editor = None;
                     app.viewer_editing = false;
-                    app.viewer_content = Some(crate::viewer::load_file(path));
-                    app.viewer_scroll = 0;
-                    app.mode = AppMode::Viewer;
+                    let path = path.clone();
+                    app.open_in_viewer(&path);
                 } else {
                     // Show temporary message for unsupported file types
                     app.set_temp_message("미리보기가 지원되지 않는 파일 형식입니다".to_string());
                 }
-            } else if path.is_dir() {
+            } else if is_dir {
                 // Check if this is the parent entry (..)
                 let is_parent = active_dir.parent()
                     .map(|p| p == path.as_path())
@@ -206,6 +417,29 @@ fn handle_enter_key(app: &mut App) {
     }
 }
 
+/// Open the delete confirmation for the marked entries or the selected one
+fn open_delete_dialog(app: &mut App, permanent: bool) {
+    let marked = app.active_fs().marked.len();
+    if marked > 0 {
+        let path_name = format!("{} selected items", marked);
+        app.dialog = crate::app::DialogMode::Delete { path_name, permanent };
+        return;
+    }
+
+    // From active directory / PATH
+    if let Some(active_dir) = crate::navigation::get_active_directory(app.active_fs()) {
+        let entries = app.active_fs().entries_for_dir(&active_dir);
+        let selected_index = app.active_fs_mut().get_selection(&active_dir);
+        if let Some(path) = entries.get(selected_index) {
+            let path_name = path.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            app.dialog = crate::app::DialogMode::Delete { path_name, permanent };
+        }
+    }
+}
+
 /// Handle navigation to parent directory
 fn handle_parent_navigation(app: &mut App, active_dir: &std::path::PathBuf) {
     // Going back to parent from active directory
@@ -215,6 +449,7 @@ fn handle_parent_navigation(app: &mut App, active_dir: &std::path::PathBuf) {
     } else {
         // Active dir is not current_dir: navigate to parent of active_dir
         if let Some(parent) = active_dir.parent() {
+            app.active_fs_mut().record_previous_dir();
             app.active_fs_mut().current_dir = parent.to_path_buf();
             
             // Find active_dir in navigation_path and remove it and everything after
@@ -241,6 +476,7 @@ fn handle_parent_navigation(app: &mut App, active_dir: &std::path::PathBuf) {
 /// Handle entering a subdirectory from a non-current column
 fn handle_subdirectory_entry(app: &mut App, active_dir: &std::path::PathBuf, path: &std::path::PathBuf) {
     // Update current_dir to the selected directory and rebuild navigation
+    app.active_fs_mut().record_previous_dir();
     app.active_fs_mut().current_dir = path.clone();
     
     // Find position in navigation_path and truncate
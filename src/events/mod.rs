@@ -12,9 +12,9 @@ mod clipboard;
 mod process;
 
 // Re-export all public handlers
-pub use file_manager::handle_file_manager_keys;
-pub use viewer::handle_viewer_keys;
-pub use settings::handle_settings_keys;
+pub use file_manager::{handle_file_manager_keys, handle_type_ahead_keys};
+pub use viewer::{handle_viewer_keys, handle_viewer_command_keys, handle_viewer_search_keys, show_viewer_line, enter_edit_mode, scroll_viewer_to_end};
+pub use settings::{handle_settings_keys, handle_theme_editor_keys};
 pub use dialog::handle_dialog_keys;
 pub use shell::handle_shell_keys;
 pub use shell::handle_console_keys;
@@ -72,6 +72,12 @@ pub(crate) mod utils {
             Some(crate::viewer::ViewerContent::HighlightedCode { raw, .. }) => Some(raw.clone()),
             Some(crate::viewer::ViewerContent::Markdown(s)) => Some(s.clone()),
             Some(crate::viewer::ViewerContent::HexView(_, _)) => None,
+            Some(crate::viewer::ViewerContent::LargeFile(_)) => None,
+            Some(crate::viewer::ViewerContent::ArchiveListing(entries, truncated)) => {
+                Some(crate::viewer::format_archive_listing(entries, *truncated))
+            },
+            Some(crate::viewer::ViewerContent::Diff(diff)) => Some(diff.text.clone()),
+            Some(crate::viewer::ViewerContent::Table(table)) => Some(table.raw.clone()),
             Some(crate::viewer::ViewerContent::Image(_)) => None,
             Some(crate::viewer::ViewerContent::ImagePreviewContent(_)) => None,
             Some(crate::viewer::ViewerContent::Error(e)) => Some(e.clone()),
@@ -7,6 +7,38 @@ use crate::app::App;
 /// Returns true if the key was handled
 pub fn handle_process_viewer_keys(app: &mut App, key_code: KeyCode) -> bool {
     let viewer = &mut app.process_viewer;
+
+    // Force kill confirmation: 'y' sends SIGKILL, anything else cancels
+    if let Some(pid) = viewer.confirm_kill.take() {
+        viewer.message = Some(match key_code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => match viewer.kill_pid(pid, true) {
+                Ok(message) => message,
+                Err(e) => format!("Kill failed: {}", e),
+            },
+            _ => "Force kill cancelled".to_string(),
+        });
+        return true;
+    }
+    
+    // Nice value prompt: digits and '-' edit, Enter applies, Esc cancels
+    if let Some((_, input)) = &mut viewer.renice_input {
+        match key_code {
+            KeyCode::Char(c) if c.is_ascii_digit() || c == '-' => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            },
+            KeyCode::Enter => {
+                let (pid, input) = viewer.renice_input.take().unwrap_or_default();
+                viewer.message = Some(match viewer.renice(pid, &input) {
+                    Ok(message) => message,
+                    Err(e) => e,
+                });
+            },
+            KeyCode::Esc => viewer.renice_input = None,
+            _ => {},
+        }
+        return true;
+    }
     
     // Handle search mode separately
     if viewer.search_mode {
@@ -76,28 +108,52 @@ pub fn handle_process_viewer_keys(app: &mut App, key_code: KeyCode) -> bool {
                 viewer.move_to_parent();
                 true
             },
+            KeyCode::Char('z') => {
+                viewer.set_all_expanded(false);
+                true
+            },
+            KeyCode::Char('Z') => {
+                viewer.set_all_expanded(true);
+                true
+            },
             
-            // Kill process (Shift+K for force kill)
+            // Terminate process (Shift+K force kills after confirmation)
             KeyCode::Char('K') => {
-                match viewer.kill_selected(true) {
-                    Ok(_) => {
-                        app.set_temp_message("Process killed (SIGKILL)".to_string());
-                    },
-                    Err(e) => {
-                        app.set_temp_message(format!("Kill failed: {}", e));
-                    }
-                }
+                viewer.request_force_kill();
                 true
             },
             KeyCode::Delete => {
-                match viewer.kill_selected(false) {
-                    Ok(_) => {
-                        app.set_temp_message("Process terminated (SIGTERM)".to_string());
-                    },
-                    Err(e) => {
-                        app.set_temp_message(format!("Kill failed: {}", e));
-                    }
-                }
+                viewer.message = Some(match viewer.kill_selected(false) {
+                    Ok(message) => message,
+                    Err(e) => format!("Kill failed: {}", e),
+                });
+                true
+            },
+            
+            // Open files / ports of the selected process
+            KeyCode::Char('o') => {
+                viewer.show_resources = !viewer.show_resources;
+                viewer.update_resources();
+                true
+            },
+            
+            // Change priority (nice value)
+            KeyCode::Char('n') => {
+                viewer.start_renice();
+                true
+            },
+            
+            // Export the visible list (J switches CSV/JSON)
+            KeyCode::Char('E') => {
+                viewer.message = Some(match viewer.export() {
+                    Ok(path) => format!("Exported {} processes to {}", viewer.tree_order.len(), path.display()),
+                    Err(e) => format!("Export failed: {}", e),
+                });
+                true
+            },
+            KeyCode::Char('J') => {
+                viewer.export_format = viewer.export_format.toggle();
+                viewer.message = Some(format!("Export format: {}", viewer.export_format.extension().to_uppercase()));
                 true
             },
             
@@ -106,6 +162,14 @@ pub fn handle_process_viewer_keys(app: &mut App, key_code: KeyCode) -> bool {
                 viewer.cycle_filter();
                 true
             },
+            KeyCode::Char('c') => {
+                viewer.cycle_cpu_threshold();
+                true
+            },
+            KeyCode::Char('m') => {
+                viewer.cycle_mem_threshold();
+                true
+            },
             KeyCode::Char('s') => {
                 viewer.cycle_sort();
                 true
@@ -1,12 +1,15 @@
 //! Settings mode event handling
 
 use crossterm::event::{KeyCode, KeyModifiers};
-use crate::app::{App, SettingsTab};
+use crate::app::{App, SettingsTab, ThemeEditor};
 use crate::config::Theme;
 
+/// How much +/- lighten or darken a color, per RGB channel
+const COLOR_STEP: i16 = 8;
+
 /// Handle settings mode key events
 pub fn handle_settings_keys(app: &mut App, key_code: KeyCode, _modifiers: KeyModifiers) {
-    // Tab switching with 1 and 2
+    // Tab switching with 1-3 (3 picks a theme on the Theme tab) and ←/→
     match key_code {
         KeyCode::Char('1') => {
             app.settings_tab = SettingsTab::Theme;
@@ -16,6 +19,17 @@ pub fn handle_settings_keys(app: &mut App, key_code: KeyCode, _modifiers: KeyMod
             app.settings_tab = SettingsTab::Interface;
             return;
         },
+        KeyCode::Char('3') if app.settings_tab != SettingsTab::Theme => {
+            app.settings_tab = SettingsTab::Plugins;
+            return;
+        },
+        KeyCode::Left | KeyCode::Right => {
+            let tabs = [SettingsTab::Theme, SettingsTab::Interface, SettingsTab::Plugins];
+            let index = tabs.iter().position(|t| *t == app.settings_tab).unwrap_or(0);
+            let next = if key_code == KeyCode::Right { index + 1 } else { index + tabs.len() - 1 };
+            app.settings_tab = tabs[next % tabs.len()];
+            return;
+        },
         _ => {}
     }
     
@@ -26,14 +40,16 @@ pub fn handle_settings_keys(app: &mut App, key_code: KeyCode, _modifiers: KeyMod
         },
         SettingsTab::Interface => {
             handle_settings_interface_keys(app, key_code);
+        },
+        SettingsTab::Plugins => {
+            handle_settings_plugins_keys(app, key_code);
         }
     }
 }
 
 /// Handle theme settings keys
 fn handle_settings_theme_keys(app: &mut App, key_code: KeyCode) {
-    let all_themes = Theme::all_themes();
-    let theme_count = all_themes.len();
+    let theme_count = app.themes.len();
 
     match key_code {
         KeyCode::Up | KeyCode::Char('k') => {
@@ -51,18 +67,40 @@ fn handle_settings_theme_keys(app: &mut App, key_code: KeyCode) {
             }
         },
         KeyCode::Enter => {
-            if let Some(theme) = all_themes.into_iter().nth(app.settings_theme_index) {
+            if let Some(theme) = app.themes.get(app.settings_theme_index).cloned() {
                 app.config.theme = theme;
                 let _ = app.config.save();
                 app.status_message = Some(format!("Theme changed to: {}", app.config.theme.name));
             }
         },
+        KeyCode::Char('c') | KeyCode::Char('C') => {
+            app.theme_editor = Some(ThemeEditor { role: 0, input: None, original: app.config.theme.clone() });
+        },
+        KeyCode::Char('e') | KeyCode::Char('E') => app.export_theme(),
+        KeyCode::Char('r') | KeyCode::Char('R') => {
+            app.reload_themes();
+            app.status_message = Some(format!("Loaded {} themes", app.themes.len()));
+        },
+        // Cycle the syntax highlighting theme (independent of the UI theme)
+        KeyCode::Char('s') | KeyCode::Char('S') => {
+            let names = crate::viewer::highlight::syntax_theme_names();
+            let current = &app.config.syntax_theme;
+            let index = names.iter().position(|n| n == current).unwrap_or(0);
+            let next = if key_code == KeyCode::Char('s') {
+                (index + 1) % names.len()
+            } else {
+                (index + names.len() - 1) % names.len()
+            };
+            if app.set_syntax_theme(&names[next]) {
+                app.status_message = Some(format!("Syntax theme changed to: {}", names[next]));
+            }
+        },
         KeyCode::Char(c) if c.is_ascii_digit() && c != '1' && c != '2' => {
             // Quick select themes 3-9, 0
             let index = if c == '0' { 9 } else { (c as usize) - ('1' as usize) };
             if index < theme_count && index >= 2 {
                 app.settings_theme_index = index;
-                if let Some(theme) = Theme::all_themes().into_iter().nth(index) {
+                if let Some(theme) = app.themes.get(index).cloned() {
                     app.config.theme = theme;
                     let _ = app.config.save();
                     app.status_message = Some(format!("Theme changed to: {}", app.config.theme.name));
@@ -73,6 +111,62 @@ fn handle_settings_theme_keys(app: &mut App, key_code: KeyCode) {
     }
 }
 
+/// Handle keys while the theme editor is open; returns false when it isn't
+pub fn handle_theme_editor_keys(app: &mut App, key_code: KeyCode) -> bool {
+    let Some(editor) = &mut app.theme_editor else { return false };
+    let role = Theme::COLOR_ROLES[editor.role];
+
+    // Typing a color for the selected role
+    if let Some(input) = &mut editor.input {
+        match key_code {
+            KeyCode::Enter => match crate::config::parse_color(input) {
+                Ok(color) => {
+                    if let Some(field) = app.config.theme.color_mut(role) {
+                        *field = color;
+                    }
+                    editor.input = None;
+                },
+                Err(e) => app.status_message = Some(format!("{}: {}", role, e)),
+            },
+            KeyCode::Esc => editor.input = None,
+            KeyCode::Backspace => {
+                input.pop();
+            },
+            KeyCode::Char(c) => input.push(c),
+            _ => {},
+        }
+        return true;
+    }
+
+    match key_code {
+        KeyCode::Up | KeyCode::Char('k') => {
+            editor.role = (editor.role + Theme::COLOR_ROLES.len() - 1) % Theme::COLOR_ROLES.len();
+        },
+        KeyCode::Down | KeyCode::Char('j') => {
+            editor.role = (editor.role + 1) % Theme::COLOR_ROLES.len();
+        },
+        KeyCode::Char('+') | KeyCode::Char('=') | KeyCode::Char('-') => {
+            let delta = if key_code == KeyCode::Char('-') { -COLOR_STEP } else { COLOR_STEP };
+            if let Some(field) = app.config.theme.color_mut(role) {
+                *field = crate::config::shift_color(*field, delta);
+            }
+        },
+        KeyCode::Enter | KeyCode::Char('#') => {
+            let current = app.config.theme.color(role).map(|c| c.to_string()).unwrap_or_default();
+            editor.input = Some(if key_code == KeyCode::Char('#') { "#".to_string() } else { current });
+        },
+        KeyCode::Char('w') | KeyCode::Char('W') => app.save_edited_theme(),
+        KeyCode::Esc => {
+            // Unsaved changes are dropped
+            if let Some(editor) = app.theme_editor.take() {
+                app.config.theme = editor.original;
+            }
+        },
+        _ => {},
+    }
+    true
+}
+
 /// Handle interface settings keys
 fn handle_settings_interface_keys(app: &mut App, key_code: KeyCode) {
     match key_code {
@@ -107,6 +201,106 @@ fn handle_settings_interface_keys(app: &mut App, key_code: KeyCode) {
                 app.status_message = Some("Minimum limit reached (1 tree)".to_string());
             }
         },
+        KeyCode::Char('w') | KeyCode::Char('W') => {
+            // Toggle file watcher globally (useful on network mounts)
+            let enabled = !app.config.enable_watcher;
+            app.set_watcher_enabled(enabled);
+            app.status_message = Some(format!("File watcher: {}", if enabled { "ON" } else { "OFF" }));
+        },
+        KeyCode::Char('p') | KeyCode::Char('P') => {
+            // Toggle writing .senterm-sort files when cycling sort order
+            app.config.persist_dir_sort = !app.config.persist_dir_sort;
+            let _ = app.config.save();
+            app.status_message = Some(format!(
+                "Remember sort per directory: {}",
+                if app.config.persist_dir_sort { "ON" } else { "OFF" }
+            ));
+        },
+        KeyCode::Char('f') | KeyCode::Char('F') => {
+            // Directories before files, or mixed with them, in every pane
+            let dirs_first = !app.config.dirs_first;
+            app.config.dirs_first = dirs_first;
+            let _ = app.config.save();
+            for fs in [&mut app.fs_left, &mut app.fs_center, &mut app.fs_right] {
+                fs.set_dirs_first(dirs_first);
+            }
+            app.status_message = Some(format!("Directories first: {}", if dirs_first { "ON" } else { "OFF" }));
+        },
+        KeyCode::Char('e') | KeyCode::Char('E') => {
+            // Switch extension colors on or off, keeping the user's [ext_colors];
+            // with none configured, switching on applies the default palette
+            let enabled = !app.config.ext_colors_enabled || app.config.ext_colors.is_empty();
+            if enabled && app.config.ext_colors.is_empty() {
+                app.config.ext_colors = crate::config::default_ext_colors();
+            }
+            app.config.ext_colors_enabled = enabled;
+            let _ = app.config.save();
+            app.status_message = Some(format!("Extension colors: {}", if enabled { "ON" } else { "OFF" }));
+        },
+        KeyCode::Char('n') | KeyCode::Char('N') => {
+            app.config.use_nerd_icons = !app.config.use_nerd_icons;
+            let _ = app.config.save();
+            app.status_message = Some(format!(
+                "Nerd Font icons: {}",
+                if app.config.use_nerd_icons { "ON" } else { "OFF" }
+            ));
+        },
+        KeyCode::Char('c') | KeyCode::Char('C') => {
+            app.config.show_clock = !app.config.show_clock;
+            let _ = app.config.save();
+            app.footer_info.invalidate();
+            app.status_message = Some(format!("Footer clock: {}", if app.config.show_clock { "ON" } else { "OFF" }));
+        },
+        KeyCode::Char('d') | KeyCode::Char('D') => {
+            app.config.show_disk_space = !app.config.show_disk_space;
+            let _ = app.config.save();
+            app.footer_info.invalidate();
+            app.status_message = Some(format!("Footer disk space: {}", if app.config.show_disk_space { "ON" } else { "OFF" }));
+        },
+        KeyCode::Char(c @ ('t' | 'T' | 'b' | 'B')) => {
+            // Double (lowercase) or halve (uppercase) a preview size limit
+            let (limit, (min, max), label) = if c.eq_ignore_ascii_case(&'t') {
+                (&mut app.config.max_text_preview_bytes, crate::config::TEXT_PREVIEW_RANGE, "Text preview limit")
+            } else {
+                (&mut app.config.max_binary_preview_bytes, crate::config::BINARY_PREVIEW_RANGE, "Hex view limit")
+            };
+            let stepped = if c.is_ascii_lowercase() { limit.saturating_mul(2) } else { *limit / 2 };
+            *limit = stepped.clamp(min, max);
+            let value = *limit;
+            let _ = app.config.save();
+            app.status_message = Some(format!("{}: {}", label, crate::viewer::format_limit(value)));
+        },
+        _ => {}
+    }
+}
+
+/// Handle plugin settings keys
+fn handle_settings_plugins_keys(app: &mut App, key_code: KeyCode) {
+    let plugin_count = app.plugins.list().len();
+
+    match key_code {
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.settings_plugin_index = app.settings_plugin_index.saturating_sub(1);
+        },
+        KeyCode::Down | KeyCode::Char('j') if app.settings_plugin_index + 1 < plugin_count => {
+            app.settings_plugin_index += 1;
+        },
+        KeyCode::Enter | KeyCode::Char(' ') => {
+            let selected = app.plugins.list().get(app.settings_plugin_index).map(|p| p.manifest.name.clone());
+            if let Some(name) = selected {
+                app.toggle_plugin(&name);
+            }
+        },
+        KeyCode::Char('t') | KeyCode::Char('T') => {
+            // Promote the highlighted plugin out of the sandbox (asks first)
+            let selected = app.plugins.list().get(app.settings_plugin_index)
+                .map(|p| (p.manifest.name.clone(), p.trusted));
+            match selected {
+                Some((name, true)) => app.status_message = Some(format!("Plugin '{}' is already trusted", name)),
+                Some((name, false)) => app.dialog = crate::app::DialogMode::TrustPlugin { name },
+                None => {},
+            }
+        },
         _ => {}
     }
 }
@@ -1,7 +1,7 @@
 //! Shell popup event handling
 
 use crossterm::event::{KeyCode, KeyModifiers};
-use crate::app::App;
+use crate::app::{App, ShellState};
 
 /// Handle shell popup key events (PTY-based)
 /// Returns true if the key was handled
@@ -15,6 +15,14 @@ pub fn handle_shell_keys(app: &mut App, key_code: KeyCode, modifiers: KeyModifie
         }
         return false;
     }
+
+    if handle_copy_mode_keys(app, key_code, false) {
+        return true;
+    }
+
+    if handle_scrollback_keys(&mut app.shell, key_code, modifiers) {
+        return true;
+    }
     
     // Convert key to bytes and send to PTY
     let bytes: Option<Vec<u8>> = match key_code {
@@ -74,7 +82,7 @@ pub fn handle_shell_keys(app: &mut App, key_code: KeyCode, modifiers: KeyModifie
         KeyCode::F(4) => Some(b"\x1bOS".to_vec()),
         KeyCode::F(5) => Some(b"\x1b[15~".to_vec()),
         KeyCode::F(6) => Some(b"\x1b[17~".to_vec()),
-        KeyCode::F(7) => Some(b"\x1b[18~".to_vec()),
+        // F7 enters copy-mode (handled above)
         KeyCode::F(8) => Some(b"\x1b[19~".to_vec()),
         KeyCode::F(9) => Some(b"\x1b[20~".to_vec()),
         KeyCode::F(10) => Some(b"\x1b[21~".to_vec()),
@@ -95,6 +103,67 @@ pub fn handle_shell_keys(app: &mut App, key_code: KeyCode, modifiers: KeyModifie
     }
 }
 
+/// F7 enters copy-mode; while in it every key moves the cursor or selection instead
+/// of reaching the PTY (F12 still closes the shell popup)
+fn handle_copy_mode_keys(app: &mut App, key_code: KeyCode, console: bool) -> bool {
+    let shell = if console { app.console_mut() } else { &mut app.shell };
+    let Some(copy) = &mut shell.copy_mode else {
+        if key_code == KeyCode::F(7) {
+            shell.enter_copy_mode();
+            return true;
+        }
+        return false;
+    };
+
+    let page = copy.screen.size().0 as i32;
+    let mut copied = None;
+    match key_code {
+        KeyCode::Left | KeyCode::Char('h') => copy.move_cursor(0, -1),
+        KeyCode::Right | KeyCode::Char('l') => copy.move_cursor(0, 1),
+        KeyCode::Up | KeyCode::Char('k') => copy.move_cursor(-1, 0),
+        KeyCode::Down | KeyCode::Char('j') => copy.move_cursor(1, 0),
+        KeyCode::PageUp | KeyCode::Char('g') => copy.move_cursor(-page, 0),
+        KeyCode::PageDown | KeyCode::Char('G') => copy.move_cursor(page, 0),
+        KeyCode::Home | KeyCode::Char('0') => copy.set_column(0),
+        KeyCode::End | KeyCode::Char('$') => copy.set_column(u16::MAX),
+        KeyCode::Char(' ') | KeyCode::Char('v') => copy.toggle_anchor(),
+        KeyCode::Char('y') | KeyCode::Enter => {
+            copied = Some(copy.selected_text());
+            shell.copy_mode = None;
+        },
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::F(7) => shell.copy_mode = None,
+        KeyCode::F(12) if !console => return false,
+        _ => {},
+    }
+
+    if let Some(text) = copied {
+        let message = match crate::events::utils::copy_to_system_clipboard(&text) {
+            Ok(_) => format!("Copied {} lines to clipboard", text.lines().count()),
+            Err(e) => format!("Copy failed: {}", e),
+        };
+        app.set_temp_message(message);
+    }
+    true
+}
+
+/// Shift+PageUp/PageDown (a page) and Shift+Up/Down (a line) scroll through the
+/// shell's scrollback instead of reaching the PTY; other keys return to the live screen.
+fn handle_scrollback_keys(shell: &mut ShellState, key_code: KeyCode, modifiers: KeyModifiers) -> bool {
+    if !modifiers.contains(KeyModifiers::SHIFT) {
+        return false;
+    }
+    let page = (shell.size.1 as isize - 1).max(1);
+    let rows = match key_code {
+        KeyCode::PageUp => page,
+        KeyCode::PageDown => -page,
+        KeyCode::Up => 1,
+        KeyCode::Down => -1,
+        _ => return false,
+    };
+    shell.scroll_back(rows);
+    true
+}
+
 /// Handle console panel key events when panel is focused
 /// Returns true if the key was handled
 /// 
@@ -111,7 +180,7 @@ pub fn handle_console_keys(app: &mut App, key_code: KeyCode, modifiers: KeyModif
 /// Handle shell mode console keys
 fn handle_console_shell_keys(app: &mut App, key_code: KeyCode, modifiers: KeyModifiers) -> bool {
     // Check if console is running
-    if !app.console.is_running {
+    if !app.console().is_running {
         // Console not running, allow focus cycling or Esc to unfocus
         match key_code {
             KeyCode::Tab => {
@@ -125,6 +194,31 @@ fn handle_console_shell_keys(app: &mut App, key_code: KeyCode, modifiers: KeyMod
             _ => return false,
         }
     }
+
+    if handle_copy_mode_keys(app, key_code, true) {
+        return true;
+    }
+
+    if handle_scrollback_keys(app.console_mut(), key_code, modifiers) {
+        return true;
+    }
+
+    // Console tabs: F6 opens, Shift+F6 closes, Ctrl+PageUp/PageDown switch
+    match key_code {
+        KeyCode::F(6) if modifiers.contains(KeyModifiers::SHIFT) => {
+            app.close_console_tab();
+            return true;
+        },
+        KeyCode::F(6) => {
+            app.new_console_tab();
+            return true;
+        },
+        KeyCode::PageUp | KeyCode::PageDown if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.switch_console_tab(key_code == KeyCode::PageDown);
+            return true;
+        },
+        _ => {},
+    }
     
     // Convert key to bytes and send to PTY
     let bytes: Option<Vec<u8>> = match key_code {
@@ -178,8 +272,8 @@ fn handle_console_shell_keys(app: &mut App, key_code: KeyCode, modifiers: KeyMod
         KeyCode::F(3) => Some(b"\x1bOR".to_vec()),
         KeyCode::F(4) => Some(b"\x1bOS".to_vec()),
         KeyCode::F(5) => Some(b"\x1b[15~".to_vec()),
-        KeyCode::F(6) => Some(b"\x1b[17~".to_vec()),
-        KeyCode::F(7) => Some(b"\x1b[18~".to_vec()),
+        // F6 manages console tabs (handled above)
+        // F7 enters copy-mode (handled above)
         KeyCode::F(8) => Some(b"\x1b[19~".to_vec()),
         KeyCode::F(9) => Some(b"\x1b[20~".to_vec()),
         KeyCode::F(10) => Some(b"\x1b[21~".to_vec()),
@@ -191,7 +285,7 @@ fn handle_console_shell_keys(app: &mut App, key_code: KeyCode, modifiers: KeyMod
     };
     
     if let Some(data) = bytes {
-        if let Err(e) = app.console.write(&data) {
+        if let Err(e) = app.console_mut().write(&data) {
             tracing::error!("Failed to write to console PTY: {}", e);
         }
         true
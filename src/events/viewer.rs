@@ -8,6 +8,12 @@ use super::editor::{handle_editor_keys, handle_nano_keys};
 /// Handle viewer mode key events
 pub fn handle_viewer_keys(app: &mut App, key_code: KeyCode, modifiers: KeyModifiers) {
     use crate::viewer::editor::EditorStyle;
+    use crate::viewer::VimMode;
+
+    if app.hex_editor.is_some() {
+        handle_hex_editor_keys(app, key_code, modifiers);
+        return;
+    }
     
     // Check if in editing mode
     if app.viewer_editing {
@@ -20,6 +26,11 @@ pub fn handle_viewer_keys(app: &mut App, key_code: KeyCode, modifiers: KeyModifi
                         return;
                     }
                 },
+                // Ctrl+C cancels an open nano prompt
+                KeyCode::Char('c') | KeyCode::Char('C') if app.text_editor.as_ref().is_some_and(|e| e.nano_prompt.is_some()) => {
+                    handle_nano_keys(app, key_code, modifiers);
+                    return;
+                },
                 // Ctrl+C: Copy selection to system clipboard (in editor mode)
                 KeyCode::Char('c') | KeyCode::Char('C') => {
                     if let Some(ref mut editor) = app.text_editor {
@@ -48,6 +59,18 @@ pub fn handle_viewer_keys(app: &mut App, key_code: KeyCode, modifiers: KeyModifi
                         return;
                     }
                 },
+                // Ctrl+V in Vim normal/visual modes: visual-block selection
+                KeyCode::Char('v') | KeyCode::Char('V') if modifiers.contains(KeyModifiers::CONTROL) && app.text_editor.as_ref()
+                    .is_some_and(|e| e.editor_style == EditorStyle::Vim && !matches!(e.mode, VimMode::Insert | VimMode::Command)) => {
+                    if let Some(ref mut editor) = app.text_editor {
+                        if editor.mode == VimMode::VisualBlock {
+                            editor.enter_normal_mode();
+                        } else {
+                            editor.enter_visual_block_mode();
+                        }
+                    }
+                    return;
+                },
                 // Ctrl+V: Paste from system clipboard
                 KeyCode::Char('v') | KeyCode::Char('V') => {
                     if let Some(ref mut editor) = app.text_editor {
@@ -118,6 +141,44 @@ pub fn handle_viewer_keys(app: &mut App, key_code: KeyCode, modifiers: KeyModifi
     }
 }
 
+/// Hex editor: arrows move, hex digits overwrite, Ctrl+S saves, Esc leaves
+fn handle_hex_editor_keys(app: &mut App, key_code: KeyCode, modifiers: KeyModifiers) {
+    use crate::viewer::hex::BYTES_PER_ROW;
+
+    // The column header takes the first row
+    let rows = (app.viewer_area.get().height as usize).saturating_sub(1).max(1);
+    let ctrl = modifiers.contains(KeyModifiers::CONTROL) || modifiers.contains(KeyModifiers::SUPER);
+    if ctrl && matches!(key_code, KeyCode::Char('s') | KeyCode::Char('S')) {
+        app.save_hex_editor();
+        return;
+    }
+    if matches!(key_code, KeyCode::Esc | KeyCode::Char('q')) {
+        app.close_hex_editor();
+        return;
+    }
+
+    let Some(editor) = &mut app.hex_editor else { return };
+    let page = (rows * BYTES_PER_ROW) as isize;
+    let row = BYTES_PER_ROW as isize;
+    match key_code {
+        KeyCode::Left | KeyCode::Char('h') => editor.move_by(-1),
+        KeyCode::Right | KeyCode::Char('l') => editor.move_by(1),
+        KeyCode::Up | KeyCode::Char('k') => editor.move_by(-row),
+        KeyCode::Down | KeyCode::Char('j') => editor.move_by(row),
+        KeyCode::PageUp => editor.move_by(-page),
+        KeyCode::PageDown => editor.move_by(page),
+        KeyCode::Home => editor.move_to(editor.cursor - editor.cursor % BYTES_PER_ROW),
+        KeyCode::End => editor.move_to(editor.cursor - editor.cursor % BYTES_PER_ROW + BYTES_PER_ROW - 1),
+        KeyCode::Char('g') => editor.move_to(0),
+        KeyCode::Char('G') => editor.move_to(usize::MAX),
+        KeyCode::Char(c) if !ctrl => {
+            editor.type_digit(c);
+        },
+        _ => {}
+    }
+    editor.keep_cursor_visible(rows);
+}
+
 /// Handle readonly viewer mode
 fn handle_readonly_viewer(app: &mut App, key_code: KeyCode, modifiers: KeyModifiers) {
     // Handle Ctrl+C: Copy all content to system clipboard
@@ -138,10 +199,14 @@ fn handle_readonly_viewer(app: &mut App, key_code: KeyCode, modifiers: KeyModifi
         return;
     }
     
+    // Another key drops a jump still waiting for the line index
+    app.viewer_pending_jump = None;
+
     // Calculate total lines for scroll bounds
-    let total_lines = get_viewer_total_lines(app);
-    let half_page = 15usize;
-    let full_page = 30usize;
+    let total_lines = get_viewer_scroll_rows(app);
+    let scroll_before = app.viewer_scroll;
+    let full_page = (app.viewer_area.get().height as usize).max(1);
+    let half_page = (full_page / 2).max(1);
     
     match key_code {
         KeyCode::Char('i') => {
@@ -167,8 +232,11 @@ fn handle_readonly_viewer(app: &mut App, key_code: KeyCode, modifiers: KeyModifi
             app.viewer_scroll = 0;
         },
         KeyCode::Char('G') | KeyCode::End => {
-            // Move to bottom (leave some visible lines)
-            app.viewer_scroll = total_lines.saturating_sub(1);
+            // Move to bottom (leave some visible lines), or once a large file's index gets there
+            let waiting = wait_for_large_file_index(app, crate::viewer::LineJump::End);
+            if !waiting {
+                app.viewer_scroll = get_viewer_scroll_rows(app).saturating_sub(1);
+            }
         },
         // Half page navigation (d/u style)
         KeyCode::Char('d') => {
@@ -184,6 +252,69 @@ fn handle_readonly_viewer(app: &mut App, key_code: KeyCode, modifiers: KeyModifi
         KeyCode::Char('b') => {
             app.viewer_scroll = app.viewer_scroll.saturating_sub(full_page);
         },
+        // Search: '/' prompts, n/N jump between matches
+        KeyCode::Char('/') => {
+            if let Some(crate::viewer::ViewerContent::HexView(..)) = &app.viewer_content {
+                app.viewer_hex_search = Some(crate::viewer::hex::HexSearch::prompt(app.viewer_hex_search.take()));
+            } else if searchable_text(app).is_some() {
+                app.viewer_search = Some(crate::viewer::search::ViewerSearch::prompt(app.viewer_search.take()));
+            } else if let Some(crate::viewer::ViewerContent::Markdown(_)) = &app.viewer_content {
+                app.status_message = Some("Search works on the raw markdown (press m)".to_string());
+            } else {
+                app.status_message = Some("Search is not available for this content".to_string());
+            }
+        },
+        // Re-read the file from disk
+        KeyCode::Char('r') => {
+            app.reload_viewer();
+        },
+        // Follow mode (tail -f): start, resume after scrolling up, or stop
+        KeyCode::Char('F') => {
+            app.toggle_viewer_follow();
+            return;
+        },
+        // Line jump: ':N' or ':$'
+        KeyCode::Char(':') => {
+            app.viewer_command = Some(String::new());
+            return;
+        },
+        KeyCode::Char('n') | KeyCode::Char('N') => {
+            if let Some(search) = &mut app.viewer_hex_search {
+                search.step(key_code == KeyCode::Char('n'));
+                show_hex_match(app);
+            } else if let Some(search) = &mut app.viewer_search {
+                search.step(key_code == KeyCode::Char('n'));
+                scroll_to_current_match(app);
+            }
+            return;
+        },
+        // Line, word, character and byte counts
+        KeyCode::Char('I') => {
+            show_text_stats(app);
+            return;
+        },
+        // Markdown: toggle between the rendered view and the raw source
+        KeyCode::Char('m') => {
+            if let Some(crate::viewer::ViewerContent::Markdown(_)) = &app.viewer_content {
+                app.viewer_markdown_raw = !app.viewer_markdown_raw;
+                app.viewer_scroll = 0;
+                app.viewer_search = None;
+                let view = if app.viewer_markdown_raw { "raw source" } else { "rendered" };
+                app.status_message = Some(format!("Markdown: {}", view));
+            }
+            return;
+        },
+        // Tables: scroll horizontally one column at a time
+        KeyCode::Left => {
+            app.viewer_hscroll = app.viewer_hscroll.saturating_sub(1);
+            return;
+        },
+        KeyCode::Right => {
+            if let Some(crate::viewer::ViewerContent::Table(table)) = &app.viewer_content {
+                app.viewer_hscroll = (app.viewer_hscroll + 1).min(table.widths.len().saturating_sub(1));
+            }
+            return;
+        },
         // Toggle wrap mode
         KeyCode::Char('w') => {
             app.viewer_wrap_mode = !app.viewer_wrap_mode;
@@ -198,6 +329,313 @@ fn handle_readonly_viewer(app: &mut App, key_code: KeyCode, modifiers: KeyModifi
     if total_lines > 0 {
         app.viewer_scroll = app.viewer_scroll.min(total_lines.saturating_sub(1));
     }
+
+    // Scrolling up pauses follow mode until 'F' is pressed again
+    if let Some(follow) = &mut app.viewer_follow {
+        if app.viewer_scroll < scroll_before && !follow.paused {
+            follow.paused = true;
+            app.status_message = Some("Following paused (F to resume)".to_string());
+        }
+    }
+}
+
+/// Handle the viewer's '/' search prompt; returns true if the key was consumed
+/// Esc clears the highlights of a finished search before it closes the viewer.
+pub fn handle_viewer_search_keys(app: &mut App, key_code: KeyCode) -> bool {
+    if app.mode != AppMode::Viewer || app.viewer_editing {
+        return false;
+    }
+    if app.viewer_hex_search.is_some() {
+        return handle_hex_search_keys(app, key_code);
+    }
+    let Some(search) = &mut app.viewer_search else { return false };
+
+    let Some(input) = &mut search.input else {
+        if key_code == KeyCode::Esc {
+            app.viewer_search = None;
+            return true;
+        }
+        return false;
+    };
+
+    match key_code {
+        KeyCode::Char(c) => input.push(c),
+        KeyCode::Backspace => {
+            input.pop();
+        },
+        KeyCode::Esc => {
+            // Back to the previous search, if there was one
+            if search.pattern.is_empty() {
+                app.viewer_search = None;
+            } else {
+                search.input = None;
+            }
+        },
+        KeyCode::Enter => {
+            let pattern = std::mem::take(input);
+            if pattern.is_empty() {
+                app.viewer_search = None;
+                return true;
+            }
+            let from_line = app.viewer_drawn_lines.get().0;
+            let Some(text) = searchable_text(app).map(str::to_string) else { return true };
+            let Some(search) = &mut app.viewer_search else { return true };
+            search.submit(pattern, &text, from_line);
+            if search.matches.is_empty() {
+                app.status_message = Some(format!("Pattern not found: {}", search.pattern));
+                app.viewer_search = None;
+            } else {
+                scroll_to_current_match(app);
+            }
+        },
+        _ => {}
+    }
+    true
+}
+
+/// The hex view's '/' prompt: hex bytes ("DE AD BE EF") or ASCII text
+fn handle_hex_search_keys(app: &mut App, key_code: KeyCode) -> bool {
+    let Some(search) = &mut app.viewer_hex_search else { return false };
+    let Some(input) = &mut search.input else {
+        // Esc clears the highlights of a finished search
+        if key_code == KeyCode::Esc {
+            app.viewer_hex_search = None;
+            app.viewer_hex_highlight = None;
+            return true;
+        }
+        return false;
+    };
+
+    match key_code {
+        KeyCode::Char(c) => input.push(c),
+        KeyCode::Backspace => {
+            input.pop();
+        },
+        KeyCode::Esc => {
+            if search.query.is_empty() {
+                app.viewer_hex_search = None;
+            } else {
+                search.input = None;
+            }
+        },
+        KeyCode::Enter => {
+            let query = std::mem::take(input);
+            if query.is_empty() {
+                app.viewer_hex_search = None;
+                return true;
+            }
+            let Some(crate::viewer::ViewerContent::HexView(data, _)) = &app.viewer_content else { return true };
+            search.submit(query, data, app.viewer_scroll * crate::viewer::hex::BYTES_PER_ROW);
+            if search.matches.is_empty() {
+                app.status_message = Some(format!("Not found: {}", search.query));
+                app.viewer_hex_search = None;
+                app.viewer_hex_highlight = None;
+            } else {
+                show_hex_match(app);
+            }
+        },
+        _ => {}
+    }
+    true
+}
+
+/// Scroll to the current hex search match and highlight it
+fn show_hex_match(app: &mut App) {
+    let Some(search) = &app.viewer_hex_search else { return };
+    let Some(offset) = search.current_match() else { return };
+    let (len, position) = (search.pattern.len(), search.position());
+
+    let row = offset / crate::viewer::hex::BYTES_PER_ROW;
+    let height = (app.viewer_area.get().height as usize).saturating_sub(1).max(1);
+    if row < app.viewer_scroll || row >= app.viewer_scroll + height {
+        // Leave some context above the match
+        app.viewer_scroll = row.saturating_sub(height / 3);
+    }
+    app.viewer_hex_highlight = Some((offset, len));
+    app.status_message = Some(format!("Match {} at offset 0x{:X} ({})", position, offset, offset));
+}
+
+/// Handle the viewer's ':' line jump prompt; returns true if the key was consumed
+pub fn handle_viewer_command_keys(app: &mut App, key_code: KeyCode) -> bool {
+    if app.mode != AppMode::Viewer || app.viewer_editing {
+        return false;
+    }
+    let Some(input) = &mut app.viewer_command else { return false };
+
+    match key_code {
+        KeyCode::Char(c) => input.push(c),
+        // Backspace on an empty prompt closes it
+        KeyCode::Backspace if input.is_empty() => app.viewer_command = None,
+        KeyCode::Backspace => {
+            input.pop();
+        },
+        KeyCode::Esc => app.viewer_command = None,
+        KeyCode::Enter => {
+            let command = std::mem::take(input);
+            app.viewer_command = None;
+            // ':enc NAME' also turns a misdetected hex view into text
+            if let Some(label) = crate::viewer::encoding::parse_command(&command) {
+                app.set_viewer_encoding(label);
+                return true;
+            }
+            if let Some(url) = crate::viewer::url::parse_command(&command) {
+                app.open_url(url);
+                return true;
+            }
+            if let Some(crate::viewer::ViewerContent::HexView(data, _)) = &app.viewer_content {
+                let len = data.len();
+                jump_to_hex_offset(app, &command, len);
+                return true;
+            }
+            match crate::viewer::parse_line_jump(&command) {
+                Some(jump) if wait_for_large_file_index(app, jump) => {},
+                Some(crate::viewer::LineJump::Line(line)) => scroll_to_line(app, line),
+                Some(crate::viewer::LineJump::End) => {
+                    let total_lines = get_viewer_scroll_rows(app);
+                    app.viewer_scroll = total_lines.saturating_sub(1);
+                },
+                None if command.trim().is_empty() => {},
+                None => app.status_message = Some(format!("Not a line number: {}", command.trim())),
+            }
+        },
+        _ => {}
+    }
+    true
+}
+
+/// ':' in the hex view: put the row of a decimal or 0x offset at the top and highlight the byte
+fn jump_to_hex_offset(app: &mut App, command: &str, len: usize) {
+    use crate::viewer::hex::{parse_offset, BYTES_PER_ROW};
+
+    let offset = match command.trim() {
+        "" => return,
+        "$" => len.saturating_sub(1),
+        input => match parse_offset(input) {
+            Some(offset) if offset < len => offset,
+            Some(_) => {
+                app.status_message = Some(format!("Offset beyond the end of the file ({} bytes)", len));
+                return;
+            },
+            None => {
+                app.status_message = Some(format!("Not an offset: {} (use decimal or 0x hex)", input));
+                return;
+            },
+        },
+    };
+    app.viewer_scroll = offset / BYTES_PER_ROW;
+    app.viewer_hex_highlight = Some((offset, 1));
+    app.status_message = Some(format!("Offset 0x{:X} ({})", offset, offset));
+}
+
+/// Text the viewer search runs against (the same text the viewer draws)
+fn searchable_text(app: &App) -> Option<&str> {
+    match &app.viewer_content {
+        Some(crate::viewer::ViewerContent::PlainText(s)) => Some(s),
+        Some(crate::viewer::ViewerContent::HighlightedCode { raw, .. }) => Some(raw),
+        // Rendered markdown lines don't map to the source; search the raw view
+        Some(crate::viewer::ViewerContent::Markdown(s)) if app.viewer_markdown_raw => Some(s),
+        Some(crate::viewer::ViewerContent::Diff(diff)) => Some(&diff.text),
+        _ => None,
+    }
+}
+
+/// Width lines are wrapped at, if the viewer is in wrap mode (mirrors the draw code)
+fn viewer_wrap_width(app: &App) -> Option<usize> {
+    let content_width = app.viewer_area.get().width.saturating_sub(7) as usize;
+    (app.viewer_wrap_mode && content_width > 10).then_some(content_width)
+}
+
+/// Scroll the viewer to `line` (0-based), highlighting `pattern` from there on
+pub fn show_viewer_line(app: &mut App, line: usize, pattern: &str) {
+    if let Some(text) = searchable_text(app).map(str::to_string) {
+        let mut search = crate::viewer::search::ViewerSearch::default();
+        search.submit(pattern.to_string(), &text, line);
+        if search.current_match().is_some_and(|m| m.line == line) {
+            app.viewer_search = Some(search);
+            scroll_to_current_match(app);
+            return;
+        }
+    }
+    scroll_to_line(app, line);
+}
+
+/// Scroll so source `line` (0-based, clamped to the content) is at the top
+fn scroll_to_line(app: &mut App, line: usize) {
+    app.viewer_scroll = match (viewer_wrap_width(app), searchable_text(app)) {
+        (width, Some(text)) => {
+            let line = line.min(text.lines().count().saturating_sub(1));
+            match width {
+                Some(width) => {
+                    let m = crate::viewer::search::SearchMatch { line, start: 0, end: 0 };
+                    crate::viewer::search::wrapped_row_of(text, &m, width)
+                },
+                None => line,
+            }
+        },
+        (_, None) => line.min(get_viewer_total_lines(app).saturating_sub(1)),
+    };
+}
+
+/// Scroll so the current search match is on screen
+fn scroll_to_current_match(app: &mut App) {
+    let Some(m) = app.viewer_search.as_ref().and_then(|s| s.current_match()).copied() else { return };
+    let target = match (viewer_wrap_width(app), searchable_text(app)) {
+        (Some(width), Some(text)) => crate::viewer::search::wrapped_row_of(text, &m, width),
+        _ => m.line,
+    };
+    let height = (app.viewer_area.get().height as usize).max(1);
+    if target < app.viewer_scroll || target >= app.viewer_scroll + height {
+        // Leave some context above the match
+        app.viewer_scroll = target.saturating_sub(height / 3);
+    }
+}
+
+/// Open the count dialog for the viewed text (markdown and code count their source)
+fn show_text_stats(app: &mut App) {
+    let text = match &app.viewer_content {
+        Some(crate::viewer::ViewerContent::PlainText(s)) | Some(crate::viewer::ViewerContent::Markdown(s)) => s,
+        Some(crate::viewer::ViewerContent::HighlightedCode { raw, .. }) => raw,
+        _ => {
+            app.status_message = Some("Counts are only available for text".to_string());
+            return;
+        },
+    };
+    let stats = crate::viewer::stats::TextStats::of(text);
+    let name = app.viewer_path.as_ref()
+        .and_then(|path| path.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    app.dialog = crate::app::DialogMode::TextStats { name, stats };
+}
+
+/// Leave `jump` for when the line index of a windowed large file reaches it; false if it's there already
+fn wait_for_large_file_index(app: &mut App, jump: crate::viewer::LineJump) -> bool {
+    let Some(crate::viewer::ViewerContent::LargeFile(file)) = &app.viewer_content else { return false };
+    let reached = match jump {
+        crate::viewer::LineJump::Line(line) => line < file.line_count(),
+        crate::viewer::LineJump::End => false,
+    };
+    if file.is_indexed() || reached {
+        app.viewer_pending_jump = None;
+        return false;
+    }
+    app.viewer_pending_jump = Some(jump);
+    app.status_message = Some("Indexing lines; jumping when done".to_string());
+    true
+}
+
+/// Scroll so the last page is shown (follow mode)
+pub fn scroll_viewer_to_end(app: &mut App) {
+    let height = (app.viewer_area.get().height as usize).max(1);
+    app.viewer_scroll = get_viewer_scroll_rows(app).saturating_sub(height);
+}
+
+/// Scrollable rows: source lines, or wrapped rows when text is wrapped
+fn get_viewer_scroll_rows(app: &App) -> usize {
+    match (viewer_wrap_width(app), searchable_text(app)) {
+        (Some(width), Some(text)) => crate::viewer::search::wrapped_row_count(text, width),
+        _ => get_viewer_total_lines(app),
+    }
 }
 
 /// Get total line count from viewer content
@@ -205,16 +643,29 @@ pub fn get_viewer_total_lines(app: &App) -> usize {
     match &app.viewer_content {
         Some(crate::viewer::ViewerContent::PlainText(s)) => s.lines().count(),
         Some(crate::viewer::ViewerContent::HighlightedCode { highlighted, .. }) => highlighted.len(),
-        Some(crate::viewer::ViewerContent::Markdown(s)) => s.lines().count(),
-        Some(crate::viewer::ViewerContent::HexView(data, truncated)) => {
-            // Hex view has header lines + data lines (16 bytes per line)
-            let header_lines = if *truncated { 7 } else { 6 };
-            header_lines + (data.len() + 15) / 16 + 1 // +1 for footer
+        Some(crate::viewer::ViewerContent::Markdown(s)) if app.viewer_markdown_raw => s.lines().count(),
+        Some(crate::viewer::ViewerContent::Markdown(s)) => {
+            let width = app.viewer_area.get().width.saturating_sub(1) as usize;
+            crate::ui::render_markdown_to_lines(s, width, 1, &app.config.syntax_theme).len()
+        },
+        // Rows of 16 bytes below the column header
+        Some(crate::viewer::ViewerContent::HexView(data, _)) => crate::viewer::hex::row_count(data.len()),
+        // Lines indexed so far
+        Some(crate::viewer::ViewerContent::LargeFile(file)) => file.line_count(),
+        Some(crate::viewer::ViewerContent::ArchiveListing(entries, truncated)) => {
+            crate::viewer::format_archive_listing(entries, *truncated).lines().count()
         },
+        Some(crate::viewer::ViewerContent::Diff(diff)) => diff.lines.len(),
+        // Header and summary are frozen; only data rows scroll
+        Some(crate::viewer::ViewerContent::Table(table)) => table.rows.len(),
         Some(crate::viewer::ViewerContent::Image(_)) => 10, // Image info display
         Some(crate::viewer::ViewerContent::ImagePreviewContent(preview)) => {
             // Count lines in rendered preview + metadata
-            preview.content.lines().count() + 5
+            let image_lines = match &preview.inline {
+                Some(inline) => inline.fit(app.viewer_area.get(), crate::viewer::image::cell_pixel_size()).1 as usize,
+                None => preview.content.lines().count(),
+            };
+            image_lines + crate::viewer::image::IMAGE_HEADER_LINES
         },
         Some(crate::viewer::ViewerContent::Error(_)) => 1,
         None => 0,
@@ -223,18 +674,35 @@ pub fn get_viewer_total_lines(app: &App) -> usize {
 
 /// Enter vim edit mode
 pub fn enter_edit_mode(app: &mut App) {
+    if app.viewer_read_only {
+        app.status_message = Some(crate::app::REMOTE_COPY_READ_ONLY.to_string());
+        return;
+    }
     if let Some(content) = &app.viewer_content {
         let text = match content {
             crate::viewer::ViewerContent::PlainText(s) => s.clone(),
             crate::viewer::ViewerContent::HighlightedCode { raw, .. } => raw.clone(),
             crate::viewer::ViewerContent::Markdown(s) => s.clone(),
+            crate::viewer::ViewerContent::Table(table) => table.raw.clone(),
             crate::viewer::ViewerContent::Image(_) | 
             crate::viewer::ViewerContent::ImagePreviewContent(_) => {
                 app.status_message = Some("Cannot edit image files".to_string());
                 return;
             },
             crate::viewer::ViewerContent::HexView(_, _) => {
-                app.status_message = Some("Cannot edit binary files".to_string());
+                app.open_hex_editor();
+                return;
+            },
+            crate::viewer::ViewerContent::LargeFile(_) => {
+                app.status_message = Some("File is too large to edit (above max_text_preview_bytes)".to_string());
+                return;
+            },
+            crate::viewer::ViewerContent::ArchiveListing(_, _) => {
+                app.status_message = Some("Cannot edit archive listings".to_string());
+                return;
+            },
+            crate::viewer::ViewerContent::Diff(_) => {
+                app.status_message = Some("Cannot edit a diff".to_string());
                 return;
             },
             crate::viewer::ViewerContent::Error(_) => {
@@ -243,16 +711,25 @@ pub fn enter_edit_mode(app: &mut App) {
             }
         };
 
-        // Get file path from current selection
-        let file_path = if let Some(active_dir) = crate::navigation::get_active_directory(app.active_fs()) {
-            let entries = crate::fs::FileSystem::get_entries_for_dir(&active_dir);
+        // Edit the viewed file, falling back to the current selection
+        let file_path = if let Some(path) = app.viewer_path.clone() {
+            Some(path)
+        } else if let Some(active_dir) = crate::navigation::get_active_directory(app.active_fs()) {
+            let entries = app.active_fs().entries_for_dir(&active_dir);
             let selected_index = app.active_fs_mut().get_selection(&active_dir);
             entries.get(selected_index).cloned()
         } else {
             None
         };
 
-        app.text_editor = Some(crate::viewer::TextEditor::new(text, file_path));
+        let mut editor = crate::viewer::TextEditor::new(text, file_path);
+        editor.auto_indent = app.config.editor_auto_indent;
+        editor.tab_width = app.config.tab_width.clamp(1, 16);
+        editor.expand_tab = app.config.editor_expand_tab;
+        editor.relative_numbers = app.config.relative_line_numbers;
+        editor.auto_pair = app.config.editor_auto_pair;
+        editor.encoding = app.viewer_encoding;
+        app.text_editor = Some(editor);
         app.viewer_editing = true;
         app.status_message = Some("Entered edit mode - ESC for normal, i for insert".to_string());
     }
@@ -263,32 +740,36 @@ pub fn exit_editor(app: &mut App, saved: bool) {
     app.viewer_editing = false;
     app.text_editor = None;
     app.viewer_content = None;
+    app.viewer_follow = None;
     app.viewer_scroll = 0;
     app.mode = AppMode::FileManager;
     app.status_message = Some(if saved { "Saved and exited".to_string() } else { "Exited".to_string() });
+    // Closing the name list of a batch rename applies it
+    app.finish_batch_rename();
 }
 
-/// Save file to disk
-pub fn save_file(app: &mut App) -> bool {
+/// Save file to disk, in the encoding it was read in
+pub fn save_file(app: &mut App) -> Result<(), String> {
     use std::fs;
     
     let editor = app.text_editor.as_mut().unwrap();
     
     if let Some(file_path) = &editor.file_path {
-        let content = editor.get_content();
+        let content = editor.encoded_content()?;
         match fs::write(file_path, content) {
             Ok(_) => {
                 editor.modified = false;
-                true
+                // Our own write isn't an outside change
+                app.mark_viewer_fresh();
+                Ok(())
             },
             Err(e) => {
                 tracing::error!("Failed to save file: {}", e);
-                false
+                Err(e.to_string())
             }
         }
     } else {
-        editor.status_message = "Error: No file path".to_string();
-        false
+        Err("no file path".to_string())
     }
 }
 
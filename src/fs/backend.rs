@@ -0,0 +1,105 @@
+//! Where a pane's entries come from
+//!
+//! A pane lists and reads files through a `Backend`: `LocalFs` for the disk,
+//! or a remote host mounted with `:sftp` (the `sftp` feature). Listings carry
+//! each entry's metadata, so sorting and drawing a remote column doesn't stat
+//! its entries one by one over the network.
+
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::SystemTime;
+
+/// What a pane needs to know about an entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EntryMeta {
+    pub is_dir: bool,     // A directory, or a symlink to one
+    pub is_symlink: bool, // The entry itself is a link
+    pub len: u64,
+    pub modified: Option<SystemTime>,
+}
+
+/// Listing and reading files for a pane
+pub trait Backend: Send + Sync {
+    /// Entries of `dir` with their metadata ("." and ".." left out)
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<(PathBuf, EntryMeta)>>;
+
+    /// Metadata of `path`, following symlinks
+    fn metadata(&self, path: &Path) -> io::Result<EntryMeta>;
+
+    /// Start reading the first `max_bytes` of a file; the bytes arrive on the
+    /// returned channel, so a slow source doesn't hold up the caller
+    fn read_file(&self, path: &Path, max_bytes: u64) -> Receiver<io::Result<Vec<u8>>>;
+
+    /// "user@host" of a remote backend; None for the local disk
+    fn remote_label(&self) -> Option<String> {
+        None
+    }
+
+    /// Drop cached listings so the next read goes to the source
+    fn invalidate(&self) {}
+}
+
+/// The local disk
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalFs;
+
+impl Backend for LocalFs {
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<(PathBuf, EntryMeta)>> {
+        Ok(std::fs::read_dir(dir)?
+            .flatten()
+            .map(|entry| {
+                let path = entry.path();
+                let is_symlink = entry.file_type().is_ok_and(|t| t.is_symlink());
+                // A broken link is still listed, as a link to nothing
+                let meta = self.metadata(&path).unwrap_or_default();
+                (path, EntryMeta { is_symlink, ..meta })
+            })
+            .collect())
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<EntryMeta> {
+        let metadata = std::fs::metadata(path)?;
+        Ok(EntryMeta {
+            is_dir: metadata.is_dir(),
+            is_symlink: path.is_symlink(),
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+
+    fn read_file(&self, path: &Path, max_bytes: u64) -> Receiver<io::Result<Vec<u8>>> {
+        let (reply, receiver) = mpsc::channel();
+        let path = path.to_path_buf();
+        std::thread::spawn(move || {
+            let mut bytes = Vec::new();
+            let read = std::fs::File::open(&path).and_then(|file| file.take(max_bytes).read_to_end(&mut bytes));
+            let _ = reply.send(read.map(|_| bytes));
+        });
+        receiver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_local_listing_metadata() {
+        let temp = tempdir().unwrap();
+        std::fs::write(temp.path().join("a.txt"), "hello").unwrap();
+        std::fs::create_dir(temp.path().join("sub")).unwrap();
+
+        let mut entries = LocalFs.read_dir(temp.path()).unwrap();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].1.is_dir, entries[0].1.len), (false, 5));
+        assert!(entries[1].1.is_dir);
+        assert!(entries[0].1.modified.is_some());
+
+        assert_eq!(LocalFs.read_file(&temp.path().join("a.txt"), 3).recv().unwrap().unwrap(), b"hel");
+        assert!(LocalFs.metadata(&temp.path().join("missing")).is_err());
+        assert_eq!(LocalFs.remote_label(), None);
+    }
+}
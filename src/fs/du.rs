@@ -0,0 +1,161 @@
+//! Disk usage (du) scan
+//!
+//! A background walker totals the size of everything below a directory and
+//! records the children of every directory it passes, largest first, so the
+//! overlay can drill into subdirectories without scanning again. Symlinks are
+//! counted as links and never followed, and like `du -x` the walk stays on the
+//! filesystem of the scanned directory (other mount points are listed as empty).
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::job::BackgroundJob;
+
+/// A child of a scanned directory and its recursive size
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuEntry {
+    pub path: PathBuf,
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+/// Children of every scanned directory, largest first
+pub type DuTree = HashMap<PathBuf, Vec<DuEntry>>;
+
+/// A disk usage scan running on a background thread; dropping it stops the walk
+pub struct DuJob {
+    job: BackgroundJob<u64, DuTree>,
+    files_scanned: u64,
+}
+
+impl DuJob {
+    /// Total up everything below `root`
+    pub fn start(root: PathBuf) -> Self {
+        let job = BackgroundJob::spawn(move |cancel, reporter| {
+            let mut tree = DuTree::new();
+            let mut files = 0u64;
+            let device = std::fs::metadata(&root).ok().and_then(|m| device_id(&m));
+            scan(&root, device, cancel, &mut tree, &mut files, &mut |files| reporter.report(|| files));
+            tree
+        });
+        Self { job, files_scanned: 0 }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.job.is_done()
+    }
+
+    /// Number of files counted so far
+    pub fn files_scanned(&self) -> u64 {
+        self.files_scanned
+    }
+
+    /// The finished tree, once the walk is complete
+    pub fn poll(&mut self) -> Option<DuTree> {
+        let (files, tree) = self.job.poll();
+        if let Some(files) = files {
+            self.files_scanned = files;
+        }
+        tree
+    }
+}
+
+/// Device the file lives on, used to keep the walk on one filesystem
+#[cfg(unix)]
+fn device_id(meta: &std::fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(meta.dev())
+}
+
+#[cfg(not(unix))]
+fn device_id(_meta: &std::fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// Recursive size of `dir`, recording its children (and those of every subdirectory) in `tree`
+/// Subdirectories on a device other than `device` are listed but not walked.
+fn scan(
+    dir: &Path,
+    device: Option<u64>,
+    cancel: &AtomicBool,
+    tree: &mut DuTree,
+    files: &mut u64,
+    on_progress: &mut dyn FnMut(u64),
+) -> u64 {
+    let Ok(read_dir) = std::fs::read_dir(dir) else { return 0 };
+    let mut children = Vec::new();
+
+    for entry in read_dir.flatten() {
+        if cancel.load(Ordering::Relaxed) {
+            return 0;
+        }
+        // file_type() doesn't follow symlinks, so linked directories aren't walked twice
+        let Ok(file_type) = entry.file_type() else { continue };
+        let path = entry.path();
+        let (size, is_dir) = if file_type.is_dir() {
+            let other_device = device.is_some() && entry.metadata().ok().and_then(|m| device_id(&m)) != device;
+            let size = if other_device { 0 } else { scan(&path, device, cancel, tree, files, on_progress) };
+            (size, true)
+        } else {
+            *files += 1;
+            on_progress(*files);
+            (entry.metadata().map(|m| m.len()).unwrap_or(0), false)
+        };
+        children.push(DuEntry { path, size, is_dir });
+    }
+
+    children.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    let total = children.iter().map(|c| c.size).sum();
+    tree.insert(dir.to_path_buf(), children);
+    total
+}
+
+/// Disk usage overlay state: the directory shown and the highlighted child
+#[derive(Default)]
+pub struct DiskUsageView {
+    pub dir: PathBuf,
+    pub selected: usize,
+    pub job: Option<DuJob>, // Scan of `dir` while its sizes aren't known yet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_scan_totals_and_sorts_children() {
+        let temp = tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        std::fs::create_dir_all(root.join("big/nested")).unwrap();
+        std::fs::write(root.join("big/a.bin"), vec![0u8; 300]).unwrap();
+        std::fs::write(root.join("big/nested/b.bin"), vec![0u8; 200]).unwrap();
+        std::fs::write(root.join("small.txt"), vec![0u8; 100]).unwrap();
+        std::fs::create_dir(root.join("empty")).unwrap();
+
+        let cancel = AtomicBool::new(false);
+        let mut tree = DuTree::new();
+        let mut files = 0;
+        assert_eq!(scan(&root, None, &cancel, &mut tree, &mut files, &mut |_| {}), 600);
+        assert_eq!(files, 3);
+
+        let sizes: Vec<(PathBuf, u64, bool)> = tree[&root].iter()
+            .map(|e| (e.path.strip_prefix(&root).unwrap().to_path_buf(), e.size, e.is_dir))
+            .collect();
+        assert_eq!(sizes, vec![
+            (PathBuf::from("big"), 500, true),
+            (PathBuf::from("small.txt"), 100, false),
+            (PathBuf::from("empty"), 0, true),
+        ]);
+        // Subdirectories are recorded too, so drilling in needs no new scan
+        assert_eq!(tree[&root.join("big")][0].size, 300);
+        assert_eq!(tree[&root.join("big/nested")][0].size, 200);
+
+        // Directories on another device are listed but not walked
+        let mut other = DuTree::new();
+        assert_eq!(scan(&root, Some(u64::MAX), &cancel, &mut other, &mut 0, &mut |_| {}), 100);
+        assert!(other[&root].iter().any(|e| e.path == root.join("big") && e.size == 0));
+        assert!(!other.contains_key(&root.join("big")));
+    }
+}
@@ -0,0 +1,361 @@
+//! Background archive extraction
+//!
+//! Extracts zip and tar archives into a new folder next to the archive,
+//! reporting progress through the same channel mechanism as `PasteJob`.
+//! Entries whose paths would escape the target folder are skipped.
+
+use std::cell::Cell;
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::summary::OperationSummary;
+use super::transfer::TransferProgress;
+use crate::viewer::archive::{archive_kind, ArchiveKind};
+
+/// Minimum interval between progress updates sent to the UI
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Result of a finished extraction
+#[derive(Debug, Clone)]
+pub struct ExtractOutcome {
+    /// Folder the archive was extracted into
+    pub target: PathBuf,
+    /// Number of files written
+    pub extracted: usize,
+    /// Entries that were skipped or failed (empty when everything went fine)
+    pub problems: OperationSummary,
+}
+
+enum ExtractEvent {
+    Progress(TransferProgress),
+    Finished(ExtractOutcome),
+}
+
+/// An extraction running on a background thread
+pub struct ExtractJob {
+    pub progress: TransferProgress,
+    cancel: Arc<AtomicBool>,
+    receiver: Receiver<ExtractEvent>,
+    target: PathBuf,
+}
+
+impl ExtractJob {
+    /// Spawn a worker extracting `archive` into a new folder inside `dest_dir`
+    /// Returns None if the file isn't a supported archive.
+    pub fn start(archive: PathBuf, dest_dir: &Path) -> Option<Self> {
+        let kind = archive_kind(&archive)?;
+        let target = unique_target(dest_dir, &archive_stem(&archive));
+        let (tx, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let worker_cancel = Arc::clone(&cancel);
+        let worker_target = target.clone();
+
+        std::thread::spawn(move || {
+            let mut last_sent = Instant::now();
+            let outcome = run_extract(&archive, kind, &worker_target, &worker_cancel, &mut |progress| {
+                if last_sent.elapsed() >= PROGRESS_INTERVAL {
+                    last_sent = Instant::now();
+                    let _ = tx.send(ExtractEvent::Progress(progress));
+                }
+            });
+            let _ = tx.send(ExtractEvent::Finished(outcome));
+        });
+
+        Some(Self {
+            progress: TransferProgress::default(),
+            cancel,
+            receiver,
+            target,
+        })
+    }
+
+    /// Ask the worker to stop after the current entry
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelling(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Drain pending events; returns the outcome once the worker is done
+    pub fn poll(&mut self) -> Option<ExtractOutcome> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(ExtractEvent::Progress(progress)) => self.progress = progress,
+                Ok(ExtractEvent::Finished(outcome)) => return Some(outcome),
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => {
+                    // Worker died without reporting (panic)
+                    let mut problems = OperationSummary::new("Extract");
+                    problems.record(&self.target, Err("extract worker stopped unexpectedly"));
+                    return Some(ExtractOutcome { target: self.target.clone(), extracted: 0, problems });
+                }
+            }
+        }
+    }
+}
+
+/// Archive file name without its archive extension ("logs.tar.gz" -> "logs")
+pub fn archive_stem(archive: &Path) -> String {
+    let name = archive.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let lower = name.to_lowercase();
+    for ext in [".tar.gz", ".tgz", ".tar", ".zip"] {
+        if lower.ends_with(ext) && lower.len() > ext.len() {
+            return name[..name.len() - ext.len()].to_string();
+        }
+    }
+    name
+}
+
+/// `dest_dir/stem`, or `stem_2`, `stem_3`, ... if that already exists
+fn unique_target(dest_dir: &Path, stem: &str) -> PathBuf {
+    let target = dest_dir.join(stem);
+    if !target.exists() {
+        return target;
+    }
+    (2..)
+        .map(|n| dest_dir.join(format!("{}_{}", stem, n)))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or(target)
+}
+
+/// Relative path of an archive entry, or None if it would escape the target
+/// (absolute paths, drive prefixes and `..` components are all rejected)
+fn safe_relative_path(name: &str) -> Option<PathBuf> {
+    let mut relative = PathBuf::new();
+    for component in Path::new(name).components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {},
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    (!relative.as_os_str().is_empty()).then_some(relative)
+}
+
+fn run_extract(
+    archive: &Path,
+    kind: ArchiveKind,
+    target: &Path,
+    cancel: &AtomicBool,
+    on_progress: &mut dyn FnMut(TransferProgress),
+) -> ExtractOutcome {
+    let mut outcome = ExtractOutcome {
+        target: target.to_path_buf(),
+        extracted: 0,
+        problems: OperationSummary::new("Extract"),
+    };
+
+    let result = fs::create_dir_all(target).and_then(|_| match kind {
+        ArchiveKind::Zip => extract_zip(archive, target, cancel, &mut outcome, on_progress),
+        ArchiveKind::Tar | ArchiveKind::TarGz => extract_tar(archive, kind, target, cancel, &mut outcome, on_progress),
+    });
+
+    match result {
+        Ok(true) => tracing::info!(from = ?archive, to = ?target, files = outcome.extracted, "Extract finished"),
+        Ok(false) => outcome.problems.skip(archive, "cancelled, partial extraction left in place"),
+        Err(e) => {
+            tracing::error!(?e, from = ?archive, "Extract failed");
+            outcome.problems.record(archive, Err(e));
+        }
+    }
+    outcome
+}
+
+/// Returns Ok(false) if cancelled before finishing
+fn extract_zip(
+    archive: &Path,
+    target: &Path,
+    cancel: &AtomicBool,
+    outcome: &mut ExtractOutcome,
+    on_progress: &mut dyn FnMut(TransferProgress),
+) -> std::io::Result<bool> {
+    let to_io = |e: zip::result::ZipError| std::io::Error::other(e.to_string());
+    let mut zip = zip::ZipArchive::new(BufReader::new(File::open(archive)?)).map_err(to_io)?;
+
+    let mut progress = TransferProgress::default();
+    for index in 0..zip.len() {
+        progress.total_bytes += zip.by_index_raw(index).map_err(to_io)?.size();
+    }
+
+    for index in 0..zip.len() {
+        if cancel.load(Ordering::Relaxed) {
+            return Ok(false);
+        }
+        let mut entry = zip.by_index(index).map_err(to_io)?;
+        let name = entry.name().to_string();
+        let Some(relative) = safe_relative_path(&name) else {
+            outcome.problems.skip(Path::new(&name), "path escapes target directory");
+            continue;
+        };
+        let dest = target.join(&relative);
+
+        if entry.is_dir() {
+            if let Err(e) = fs::create_dir_all(&dest) {
+                outcome.problems.record(&relative, Err(e));
+            }
+            continue;
+        }
+
+        progress.current = name;
+        let result = dest.parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| File::create(&dest))
+            .and_then(|mut file| std::io::copy(&mut entry, &mut file));
+        match result {
+            Ok(written) => {
+                outcome.extracted += 1;
+                progress.copied_bytes += written;
+            },
+            Err(e) => outcome.problems.record(&relative, Err(e)),
+        }
+        on_progress(progress.clone());
+    }
+    Ok(true)
+}
+
+/// Reader that counts the bytes pulled through it
+struct CountingReader<R> {
+    inner: R,
+    count: Rc<Cell<u64>>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count.set(self.count.get() + n as u64);
+        Ok(n)
+    }
+}
+
+/// Returns Ok(false) if cancelled before finishing
+fn extract_tar(
+    archive: &Path,
+    kind: ArchiveKind,
+    target: &Path,
+    cancel: &AtomicBool,
+    outcome: &mut ExtractOutcome,
+    on_progress: &mut dyn FnMut(TransferProgress),
+) -> std::io::Result<bool> {
+    let file = File::open(archive)?;
+    // Entry sizes aren't known up front, so progress follows the archive bytes read
+    let mut progress = TransferProgress { total_bytes: file.metadata()?.len(), ..Default::default() };
+    let count = Rc::new(Cell::new(0));
+    let reader = CountingReader { inner: BufReader::new(file), count: Rc::clone(&count) };
+    let reader: Box<dyn Read> = match kind {
+        ArchiveKind::TarGz => Box::new(flate2::read::GzDecoder::new(reader)),
+        _ => Box::new(reader),
+    };
+
+    let mut tar = tar::Archive::new(reader);
+    for entry in tar.entries()? {
+        if cancel.load(Ordering::Relaxed) {
+            return Ok(false);
+        }
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().to_string();
+        let Some(relative) = safe_relative_path(&name) else {
+            outcome.problems.skip(Path::new(&name), "path escapes target directory");
+            continue;
+        };
+
+        progress.current = name;
+        let is_dir = entry.header().entry_type().is_dir();
+        // unpack_in also refuses to write through symlinks pointing outside the target
+        match entry.unpack_in(target) {
+            Ok(true) if !is_dir => outcome.extracted += 1,
+            Ok(true) => {},
+            Ok(false) => outcome.problems.skip(&relative, "path escapes target directory"),
+            Err(e) => outcome.problems.record(&relative, Err(e)),
+        }
+        progress.copied_bytes = count.get();
+        on_progress(progress.clone());
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    fn extract(archive: &Path, target: &Path) -> ExtractOutcome {
+        let cancel = AtomicBool::new(false);
+        let kind = archive_kind(archive).unwrap();
+        run_extract(archive, kind, target, &cancel, &mut |_| {})
+    }
+
+    #[test]
+    fn test_archive_stem_and_safe_paths() {
+        assert_eq!(archive_stem(Path::new("/tmp/logs.tar.gz")), "logs");
+        assert_eq!(archive_stem(Path::new("Photos.ZIP")), "Photos");
+        assert_eq!(archive_stem(Path::new("site.v2.tgz")), "site.v2");
+
+        assert_eq!(safe_relative_path("a/./b.txt"), Some(PathBuf::from("a/b.txt")));
+        assert_eq!(safe_relative_path("../evil.sh"), None);
+        assert_eq!(safe_relative_path("a/../../evil.sh"), None);
+        assert_eq!(safe_relative_path("/etc/passwd"), None);
+        assert_eq!(safe_relative_path("./"), None);
+    }
+
+    #[test]
+    fn test_extract_zip_skips_traversal() {
+        let temp = tempdir().unwrap();
+        let archive = temp.path().join("bundle.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&archive).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        writer.add_directory("docs/", options).unwrap();
+        for (name, data) in [("docs/a.txt", "alpha"), ("b.txt", "beta"), ("../escape.txt", "gotcha")] {
+            writer.start_file(name, options).unwrap();
+            writer.write_all(data.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let target = unique_target(temp.path(), &archive_stem(&archive));
+        let outcome = extract(&archive, &target);
+
+        assert_eq!(outcome.extracted, 2);
+        assert_eq!(outcome.problems.skipped(), 1);
+        assert_eq!(fs::read_to_string(target.join("docs/a.txt")).unwrap(), "alpha");
+        assert_eq!(fs::read_to_string(temp.path().join("bundle/b.txt")).unwrap(), "beta");
+        assert!(!temp.path().join("escape.txt").exists());
+
+        // A second extraction goes into a fresh folder
+        assert_eq!(unique_target(temp.path(), "bundle"), temp.path().join("bundle_2"));
+    }
+
+    #[test]
+    fn test_extract_job_tar_gz() {
+        let temp = tempdir().unwrap();
+        let archive = temp.path().join("src.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(File::create(&archive).unwrap(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(5);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, "lib/mod.rs", &b"hello"[..]).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let mut job = ExtractJob::start(archive, temp.path()).unwrap();
+        let outcome = loop {
+            if let Some(outcome) = job.poll() {
+                break outcome;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        };
+
+        assert_eq!(outcome.extracted, 1);
+        assert!(outcome.problems.is_empty());
+        assert_eq!(outcome.target, temp.path().join("src"));
+        assert_eq!(fs::read_to_string(temp.path().join("src/lib/mod.rs")).unwrap(), "hello");
+    }
+}
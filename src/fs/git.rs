@@ -0,0 +1,190 @@
+//! Git status overlay
+//!
+//! When a visible directory is inside a git repository, `git status --porcelain`
+//! is run once per directory change and cached per repository. File watcher
+//! events mark the cache dirty and trigger a debounced refresh. If git isn't
+//! installed or the directory isn't a repo, the overlay is simply empty.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// Delay between a file change and the status refresh it triggers
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Git state of a path (directories take the most significant state of their contents)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GitState {
+    Untracked,
+    Staged,
+    Modified,
+}
+
+/// Repository containing `dir` (the nearest ancestor with a `.git` entry)
+pub fn repo_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|ancestor| ancestor.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Run `git status` for the repository at `root`
+/// Returns None if git is missing or fails
+pub fn status(root: &Path) -> Option<HashMap<PathBuf, GitState>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["status", "--porcelain", "-z", "--untracked-files=normal"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        tracing::debug!(root = ?root, "git status failed");
+        return None;
+    }
+    Some(parse_porcelain(&String::from_utf8_lossy(&output.stdout), root))
+}
+
+/// Parse `git status --porcelain -z` output; paths are relative to `root`
+/// Parent directories inside the repo inherit the most significant state below them.
+pub fn parse_porcelain(output: &str, root: &Path) -> HashMap<PathBuf, GitState> {
+    let mut states = HashMap::new();
+    let mut records = output.split('\0').filter(|r| !r.is_empty());
+
+    while let Some(record) = records.next() {
+        if record.len() < 4 {
+            continue;
+        }
+        let (code, path) = record.split_at(3);
+        let mut code = code.chars();
+        let (index, worktree) = (code.next().unwrap_or(' '), code.next().unwrap_or(' '));
+
+        // Renames and copies are followed by the original path
+        if matches!(index, 'R' | 'C') {
+            records.next();
+        }
+
+        let state = match (index, worktree) {
+            ('?', '?') => GitState::Untracked,
+            ('!', '!') => continue,
+            (_, 'M' | 'D' | 'T' | 'U') => GitState::Modified,
+            (' ', _) => continue,
+            _ => GitState::Staged,
+        };
+
+        let path = root.join(path.trim_end_matches('/'));
+        for ancestor in path.ancestors().take_while(|a| *a != root) {
+            let entry = states.entry(ancestor.to_path_buf()).or_insert(state);
+            *entry = (*entry).max(state);
+        }
+    }
+
+    states
+}
+
+/// Cached git status for the repositories of the visible directories
+#[derive(Default)]
+pub struct GitOverlay {
+    repos: HashMap<PathBuf, HashMap<PathBuf, GitState>>,
+    dirs: Vec<PathBuf>,
+    dirty_since: Option<Instant>,
+}
+
+impl GitOverlay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refresh immediately if the set of visible directories changed
+    pub fn sync_dirs(&mut self, dirs: &[PathBuf]) {
+        if self.dirs != dirs {
+            self.dirs = dirs.to_vec();
+            self.refresh();
+        }
+    }
+
+    /// Schedule a debounced refresh (called on file watcher events)
+    pub fn mark_dirty(&mut self) {
+        if self.dirty_since.is_none() {
+            self.dirty_since = Some(Instant::now());
+        }
+    }
+
+    /// Run a pending refresh once the debounce interval has passed
+    pub fn tick(&mut self) {
+        if self.dirty_since.is_some_and(|since| since.elapsed() >= REFRESH_DEBOUNCE) {
+            self.refresh();
+        }
+    }
+
+    fn refresh(&mut self) {
+        self.dirty_since = None;
+        let mut roots: Vec<PathBuf> = self.dirs.iter().filter_map(|dir| repo_root(dir)).collect();
+        roots.sort();
+        roots.dedup();
+
+        self.repos = roots
+            .into_iter()
+            .filter_map(|root| status(&root).map(|states| (root, states)))
+            .collect();
+    }
+
+    /// Whether `dir` is inside a repository with known status
+    pub fn is_tracked_dir(&self, dir: &Path) -> bool {
+        self.repos.keys().any(|root| dir.starts_with(root))
+    }
+
+    pub fn state(&self, path: &Path) -> Option<GitState> {
+        self.repos
+            .iter()
+            .filter(|(root, _)| path.starts_with(root))
+            .find_map(|(_, states)| states.get(path).copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_porcelain_states() {
+        let root = Path::new("/repo");
+        let output = " M src/main.rs\0A  src/new.rs\0MM both.rs\0?? notes/\0R  renamed.rs\0old.rs\0";
+        let states = parse_porcelain(output, root);
+
+        assert_eq!(states.get(Path::new("/repo/src/main.rs")), Some(&GitState::Modified));
+        assert_eq!(states.get(Path::new("/repo/src/new.rs")), Some(&GitState::Staged));
+        assert_eq!(states.get(Path::new("/repo/both.rs")), Some(&GitState::Modified));
+        assert_eq!(states.get(Path::new("/repo/notes")), Some(&GitState::Untracked));
+        assert_eq!(states.get(Path::new("/repo/renamed.rs")), Some(&GitState::Staged));
+        // The rename source isn't parsed as its own record
+        assert!(!states.contains_key(Path::new("/repo/old.rs")));
+        // Directories take the most significant state below them
+        assert_eq!(states.get(Path::new("/repo/src")), Some(&GitState::Modified));
+        assert!(!states.contains_key(root));
+    }
+
+    #[test]
+    fn test_repo_root_and_overlay() {
+        let temp = tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        assert_eq!(repo_root(&root.join("sub")), None);
+
+        let git_ok = Command::new("git").arg("init").arg("-q").arg(&root)
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false);
+        if !git_ok {
+            return; // git not installed
+        }
+        assert_eq!(repo_root(&root.join("sub")), Some(root.clone()));
+
+        std::fs::write(root.join("sub/file.txt"), "x").unwrap();
+        let mut overlay = GitOverlay::new();
+        overlay.sync_dirs(std::slice::from_ref(&root));
+
+        assert!(overlay.is_tracked_dir(&root.join("sub")));
+        assert_eq!(overlay.state(&root.join("sub")), Some(GitState::Untracked));
+    }
+}
@@ -0,0 +1,168 @@
+//! Content search (grep) below a directory
+//!
+//! A background walker reads every text file under the root and reports the
+//! lines containing the pattern. Binary files and files too large to preview
+//! are skipped, and the walk stops once `MAX_HITS` lines have matched.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::job::BackgroundJob;
+
+/// Maximum number of matching lines collected
+pub const MAX_HITS: usize = 500;
+
+/// Files larger than this aren't searched (same limit as the text viewer)
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// A matching line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrepHit {
+    pub path: PathBuf,
+    pub line: usize, // 1-based
+    pub text: String,
+}
+
+/// Whether `line` contains `pattern`
+/// Case-insensitive unless the pattern contains an uppercase letter (smart case).
+fn line_matches(line: &str, pattern: &str, ignore_case: bool) -> bool {
+    if ignore_case {
+        line.to_lowercase().contains(pattern)
+    } else {
+        line.contains(pattern)
+    }
+}
+
+/// Matching lines of one file, or nothing if it isn't a searchable text file
+fn grep_file(path: &Path, pattern: &str, ignore_case: bool, limit: usize) -> Vec<GrepHit> {
+    let too_large = std::fs::metadata(path).map(|m| m.len() > MAX_FILE_SIZE).unwrap_or(true);
+    if too_large {
+        return Vec::new();
+    }
+    let Ok(content) = std::fs::read_to_string(path) else { return Vec::new() };
+    if crate::viewer::is_likely_binary(&content) {
+        return Vec::new();
+    }
+    content.lines()
+        .enumerate()
+        .filter(|(_, line)| line_matches(line, pattern, ignore_case))
+        .take(limit)
+        .map(|(i, line)| GrepHit { path: path.to_path_buf(), line: i + 1, text: line.trim().to_string() })
+        .collect()
+}
+
+/// A content search running on a background thread; dropping it stops the walk
+pub struct GrepJob(BackgroundJob<Vec<GrepHit>, Vec<GrepHit>>);
+
+impl GrepJob {
+    /// Search the files under `root` (up to `max_depth` levels) for `pattern`
+    pub fn start(root: PathBuf, pattern: String, max_depth: usize, show_hidden: bool) -> Self {
+        Self(BackgroundJob::spawn(move |cancel, reporter| {
+            walk(&root, &pattern, max_depth, show_hidden, cancel, &mut |hits| reporter.report(|| hits.to_vec()))
+        }))
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.0.is_done()
+    }
+
+    /// All hits found so far, if there are new ones since the last poll
+    pub fn poll(&mut self) -> Option<Vec<GrepHit>> {
+        let (latest, hits) = self.0.poll();
+        hits.or(latest)
+    }
+}
+
+/// Breadth-first walk collecting up to `MAX_HITS` matching lines in walk order
+fn walk(
+    root: &Path,
+    pattern: &str,
+    max_depth: usize,
+    show_hidden: bool,
+    cancel: &AtomicBool,
+    on_update: &mut dyn FnMut(&[GrepHit]),
+) -> Vec<GrepHit> {
+    let ignore_case = !pattern.chars().any(char::is_uppercase);
+    let pattern = if ignore_case { pattern.to_lowercase() } else { pattern.to_string() };
+    let mut hits: Vec<GrepHit> = Vec::new();
+    if pattern.is_empty() {
+        return hits;
+    }
+    let mut queue = std::collections::VecDeque::from([(root.to_path_buf(), 0usize)]);
+
+    while let Some((dir, depth)) = queue.pop_front() {
+        let Ok(read_dir) = std::fs::read_dir(&dir) else { continue };
+        // Sorted so results come back in a stable order
+        let mut entries: Vec<_> = read_dir.flatten().collect();
+        entries.sort_by_key(|e| e.file_name());
+
+        for entry in entries {
+            if cancel.load(Ordering::Relaxed) {
+                return hits;
+            }
+            if !show_hidden && entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            // Don't follow symlinked directories (avoids cycles)
+            let Ok(file_type) = entry.file_type() else { continue };
+            if file_type.is_dir() {
+                if depth < max_depth {
+                    queue.push_back((entry.path(), depth + 1));
+                }
+                continue;
+            }
+
+            let found = grep_file(&entry.path(), &pattern, ignore_case, MAX_HITS - hits.len());
+            if !found.is_empty() {
+                hits.extend(found);
+                if hits.len() >= MAX_HITS {
+                    return hits;
+                }
+                on_update(&hits);
+            }
+        }
+    }
+
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_walk_finds_lines_and_skips_binary() {
+        let temp = tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {\n    // TODO: parse args\n}\n").unwrap();
+        std::fs::write(root.join("notes.txt"), "todo list\nnothing here\n").unwrap();
+        std::fs::write(root.join("blob.bin"), b"todo\0\x01\x02").unwrap();
+        std::fs::write(root.join(".hidden"), "todo").unwrap();
+
+        let cancel = AtomicBool::new(false);
+        let hits = walk(&root, "todo", 5, false, &cancel, &mut |_| {});
+        let found: Vec<(String, usize)> = hits.iter()
+            .map(|h| (h.path.strip_prefix(&root).unwrap().to_string_lossy().to_string(), h.line))
+            .collect();
+        assert_eq!(found, vec![("notes.txt".to_string(), 1), ("src/main.rs".to_string(), 2)]);
+        assert_eq!(hits[1].text, "// TODO: parse args");
+
+        // Smart case: an uppercase letter makes the search case-sensitive
+        assert_eq!(walk(&root, "TODO", 5, false, &cancel, &mut |_| {}).len(), 1);
+    }
+
+    #[test]
+    fn test_walk_caps_hits() {
+        let temp = tempdir().unwrap();
+        let many = "match\n".repeat(MAX_HITS + 10);
+        std::fs::write(temp.path().join("a.txt"), &many).unwrap();
+        std::fs::write(temp.path().join("b.txt"), &many).unwrap();
+
+        let cancel = AtomicBool::new(false);
+        let hits = walk(temp.path(), "match", 1, false, &cancel, &mut |_| {});
+        assert_eq!(hits.len(), MAX_HITS);
+        assert!(hits.iter().all(|h| h.path.ends_with("a.txt")));
+    }
+}
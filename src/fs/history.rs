@@ -0,0 +1,238 @@
+//! Undo history for file operations
+//!
+//! Renames, moves, pastes, creations and trash deletions are recorded with
+//! enough information to reverse them. Undo refuses (and the history is
+//! dropped) when reversing would touch something that changed since: a
+//! missing file, a name taken in the meantime or an edited copy. Copied and
+//! created folders are compared entry by entry, so a file edited or added
+//! anywhere inside keeps the folder from being removed.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Number of operations kept; older ones are forgotten
+pub const MAX_HISTORY: usize = 50;
+
+/// A reversible file operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileOperation {
+    Renamed(Vec<(PathBuf, PathBuf)>), // (old path, new path), including batch renames
+    Moved(Vec<(PathBuf, PathBuf)>),   // Cut-paste: (source, destination)
+    Copied(Vec<(PathBuf, Option<u64>)>), // Pasted copies and their `tree_stamp`
+    Created { path: PathBuf, stamp: Option<u64> }, // New file, folder or template
+    Trashed, // Deletion to the trash; undone by restoring the latest trash batch
+}
+
+/// Modification time, used to tell whether a copy or new file was edited since
+pub fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::symlink_metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Fingerprint of `path` and, for a folder, everything below it: names, types,
+/// sizes and modification times. None when `path` doesn't exist.
+pub fn tree_stamp(path: &Path) -> Option<u64> {
+    fn visit(path: &Path, relative: &Path, hasher: &mut DefaultHasher) -> std::io::Result<()> {
+        let metadata = std::fs::symlink_metadata(path)?;
+        relative.hash(hasher);
+        (metadata.file_type().is_dir(), metadata.is_symlink(), metadata.len(), metadata.modified().ok()).hash(hasher);
+        if metadata.is_dir() {
+            let mut children: Vec<_> = std::fs::read_dir(path)?.flatten().map(|e| e.file_name()).collect();
+            children.sort();
+            for child in children {
+                visit(&path.join(&child), &relative.join(&child), hasher)?;
+            }
+        }
+        Ok(())
+    }
+    let mut hasher = DefaultHasher::new();
+    visit(path, Path::new(""), &mut hasher).ok()?;
+    Some(hasher.finish())
+}
+
+fn name(path: &Path) -> String {
+    path.file_name().unwrap_or_default().to_string_lossy().to_string()
+}
+
+impl FileOperation {
+    /// Copies or new entries at `paths`, remembering what they contain now
+    pub fn copied(paths: Vec<PathBuf>) -> Self {
+        FileOperation::Copied(paths.into_iter().map(|p| { let s = tree_stamp(&p); (p, s) }).collect())
+    }
+
+    pub fn created(path: PathBuf) -> Self {
+        let stamp = tree_stamp(&path);
+        FileOperation::Created { path, stamp }
+    }
+
+    /// Why reversing this would be unsafe, if it would
+    fn check(&self) -> Result<(), String> {
+        match self {
+            FileOperation::Renamed(pairs) | FileOperation::Moved(pairs) => {
+                for (from, to) in pairs {
+                    if to.symlink_metadata().is_err() {
+                        return Err(format!("'{}' no longer exists", name(to)));
+                    }
+                    // Taken unless another entry of the same batch moves out of the way
+                    if from.symlink_metadata().is_ok() && !pairs.iter().any(|(_, moved)| moved == from) {
+                        return Err(format!("'{}' exists again", name(from)));
+                    }
+                }
+            },
+            FileOperation::Copied(copies) => {
+                for (path, stamp) in copies {
+                    if tree_stamp(path) != *stamp {
+                        return Err(format!("'{}' was changed or removed since it was pasted", name(path)));
+                    }
+                }
+            },
+            FileOperation::Created { path, stamp } => {
+                if tree_stamp(path) != *stamp {
+                    return Err(format!("'{}' was changed or removed since it was created", name(path)));
+                }
+            },
+            FileOperation::Trashed => {},
+        }
+        Ok(())
+    }
+
+    /// Reverse the operation; returns what was done for the status line
+    /// `trash_root` is needed to undo trash deletions.
+    pub fn undo(&self, trash_root: Option<&Path>) -> Result<String, String> {
+        self.check()?;
+        match self {
+            FileOperation::Renamed(pairs) => {
+                let plan: Vec<(PathBuf, PathBuf)> = pairs.iter().map(|(from, to)| (to.clone(), from.clone())).collect();
+                crate::fs::rename::apply_plan(&plan)?;
+                Ok(match pairs.as_slice() {
+                    [(from, to)] => format!("Undid rename: '{}' is '{}' again", name(to), name(from)),
+                    _ => format!("Undid rename of {} items", pairs.len()),
+                })
+            },
+            FileOperation::Moved(pairs) => {
+                for (from, to) in pairs {
+                    super::trash::move_path(to, from)
+                        .map_err(|e| format!("moving '{}' back failed: {}", name(to), e))?;
+                }
+                Ok(format!("Undid move: {} items back in place", pairs.len()))
+            },
+            FileOperation::Copied(copies) => {
+                for (path, _) in copies {
+                    remove(path).map_err(|e| format!("removing '{}' failed: {}", name(path), e))?;
+                }
+                Ok(format!("Undid paste: removed {} copies", copies.len()))
+            },
+            FileOperation::Created { path, .. } => {
+                remove(path).map_err(|e| format!("removing '{}' failed: {}", name(path), e))?;
+                Ok(format!("Undid create: removed '{}'", name(path)))
+            },
+            FileOperation::Trashed => {
+                let summary = trash_root
+                    .and_then(super::trash::restore_latest)
+                    .ok_or_else(|| "the trash is empty".to_string())?;
+                Ok(summary.status_line())
+            },
+        }
+    }
+}
+
+fn remove(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() && !path.is_symlink() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+/// Bounded stack of reversible operations, newest last
+#[derive(Debug, Default)]
+pub struct OperationHistory {
+    operations: Vec<FileOperation>,
+}
+
+impl OperationHistory {
+    pub fn push(&mut self, operation: FileOperation) {
+        if self.operations.len() == MAX_HISTORY {
+            self.operations.remove(0);
+        }
+        self.operations.push(operation);
+    }
+
+    pub fn pop(&mut self) -> Option<FileOperation> {
+        self.operations.pop()
+    }
+
+    /// Forget everything (after an undo that couldn't be done safely)
+    pub fn clear(&mut self) {
+        self.operations.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_undo_rename_move_and_copy() {
+        let temp = tempdir().unwrap();
+        let dir = temp.path();
+        std::fs::write(dir.join("a.txt"), "a").unwrap();
+        std::fs::create_dir(dir.join("sub")).unwrap();
+
+        // Rename
+        std::fs::rename(dir.join("a.txt"), dir.join("b.txt")).unwrap();
+        let rename = FileOperation::Renamed(vec![(dir.join("a.txt"), dir.join("b.txt"))]);
+        rename.undo(None).unwrap();
+        assert!(dir.join("a.txt").exists() && !dir.join("b.txt").exists());
+        // The renamed file is gone now, so undoing again is refused
+        assert!(rename.undo(None).unwrap_err().contains("no longer exists"));
+
+        // Move
+        std::fs::rename(dir.join("a.txt"), dir.join("sub/a.txt")).unwrap();
+        FileOperation::Moved(vec![(dir.join("a.txt"), dir.join("sub/a.txt"))]).undo(None).unwrap();
+        assert!(dir.join("a.txt").exists());
+
+        // Copy: an untouched copy is removed, an edited one is kept
+        std::fs::copy(dir.join("a.txt"), dir.join("sub/a.txt")).unwrap();
+        let copy = FileOperation::copied(vec![dir.join("sub/a.txt")]);
+        copy.undo(None).unwrap();
+        assert!(!dir.join("sub/a.txt").exists());
+
+        std::fs::copy(dir.join("a.txt"), dir.join("sub/a.txt")).unwrap();
+        let copy = FileOperation::Copied(vec![(dir.join("sub/a.txt"), Some(0))]);
+        assert!(copy.undo(None).is_err());
+        assert!(dir.join("sub/a.txt").exists());
+    }
+
+    #[test]
+    fn test_undo_keeps_folder_with_later_work_inside() {
+        let temp = tempdir().unwrap();
+        let folder = temp.path().join("copy");
+        std::fs::create_dir_all(folder.join("nested")).unwrap();
+        std::fs::write(folder.join("nested/notes.txt"), "pasted").unwrap();
+        let copy = FileOperation::copied(vec![folder.clone()]);
+        let created = FileOperation::created(folder.clone());
+
+        // Editing a file two levels down leaves the folder's own mtime alone
+        std::fs::write(folder.join("nested/notes.txt"), "edited later").unwrap();
+        assert!(copy.undo(None).unwrap_err().contains("changed"));
+        assert!(created.undo(None).unwrap_err().contains("changed"));
+        assert_eq!(std::fs::read_to_string(folder.join("nested/notes.txt")).unwrap(), "edited later");
+
+        // Untouched since it was recorded: removed
+        FileOperation::copied(vec![folder.clone()]).undo(None).unwrap();
+        assert!(!folder.exists());
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut history = OperationHistory::default();
+        for i in 0..MAX_HISTORY + 5 {
+            history.push(FileOperation::Created { path: PathBuf::from(i.to_string()), stamp: None });
+        }
+        assert_eq!(history.operations.len(), MAX_HISTORY);
+        assert_eq!(history.pop(), Some(FileOperation::Created { path: PathBuf::from((MAX_HISTORY + 4).to_string()), stamp: None }));
+    }
+}
@@ -0,0 +1,101 @@
+//! Background walker scaffold
+//!
+//! The search, grep and disk usage jobs all run a walk on a worker thread,
+//! stream throttled progress snapshots back over a channel and deliver a final
+//! result unless they were cancelled. This holds the shared channel, cancel
+//! flag and polling so each job only supplies its walk.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Minimum interval between progress updates sent to the UI
+const UPDATE_INTERVAL: Duration = Duration::from_millis(50);
+
+enum JobEvent<P, T> {
+    Progress(P),
+    Done(T),
+}
+
+/// Sends throttled progress from the worker thread
+pub struct Reporter<P, T> {
+    tx: Sender<JobEvent<P, T>>,
+    last_sent: Instant,
+}
+
+impl<P, T> Reporter<P, T> {
+    /// Send the snapshot built by `make`, unless one was sent within `UPDATE_INTERVAL`
+    pub fn report(&mut self, make: impl FnOnce() -> P) {
+        if self.last_sent.elapsed() >= UPDATE_INTERVAL {
+            self.last_sent = Instant::now();
+            let _ = self.tx.send(JobEvent::Progress(make()));
+        }
+    }
+}
+
+/// A walk running on a background thread, reporting progress `P` and finishing with `T`
+/// Dropping the job cancels the walk.
+pub struct BackgroundJob<P, T> {
+    cancel: Arc<AtomicBool>,
+    receiver: Receiver<JobEvent<P, T>>,
+    done: bool,
+}
+
+impl<P: Send + 'static, T: Send + 'static> BackgroundJob<P, T> {
+    /// Run `work` on a new thread; it should check the cancel flag as it goes
+    pub fn spawn<F>(work: F) -> Self
+    where
+        F: FnOnce(&AtomicBool, &mut Reporter<P, T>) -> T + Send + 'static,
+    {
+        let (tx, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let worker_cancel = Arc::clone(&cancel);
+
+        std::thread::spawn(move || {
+            let mut reporter = Reporter { tx, last_sent: Instant::now() };
+            let result = work(&worker_cancel, &mut reporter);
+            if !worker_cancel.load(Ordering::Relaxed) {
+                let _ = reporter.tx.send(JobEvent::Done(result));
+            }
+        });
+
+        Self { cancel, receiver, done: false }
+    }
+}
+
+impl<P, T> BackgroundJob<P, T> {
+    /// Stop the walk; no further updates will be delivered
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// The latest progress since the last poll and the result, once the walk has finished
+    pub fn poll(&mut self) -> (Option<P>, Option<T>) {
+        let mut latest = None;
+        loop {
+            match self.receiver.try_recv() {
+                Ok(JobEvent::Progress(progress)) => latest = Some(progress),
+                Ok(JobEvent::Done(result)) => {
+                    self.done = true;
+                    return (latest, Some(result));
+                },
+                Err(TryRecvError::Empty) => return (latest, None),
+                Err(TryRecvError::Disconnected) => {
+                    self.done = true;
+                    return (latest, None);
+                }
+            }
+        }
+    }
+}
+
+impl<P, T> Drop for BackgroundJob<P, T> {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
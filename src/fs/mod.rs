@@ -1,32 +1,153 @@
+pub mod backend;
+pub mod du;
+pub mod extract;
+pub mod git;
+pub mod grep;
+pub mod history;
+pub mod job;
+pub mod open_with;
+pub mod path_complete;
+pub mod properties;
+#[cfg(feature = "sftp")]
+pub mod remote;
+pub mod rename;
+pub mod search;
+pub mod summary;
+pub mod template;
+pub mod transfer;
+pub mod trash;
 pub mod watcher;
 
 use std::fs;
-use std::path::{PathBuf};
-use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use crate::config::SortOption;
 
+use backend::{Backend, EntryMeta, LocalFs};
+
+pub use du::DuJob;
+pub use extract::ExtractJob;
+pub use grep::GrepJob;
+pub use search::SearchJob;
+pub use summary::{ItemOutcome, OperationSummary};
+pub use transfer::PasteJob;
 pub use watcher::FileWatcher;
 
+/// Per-directory sort override file (contains "name", "size" or "modified")
+pub const SORT_FILE: &str = ".senterm-sort";
+
+/// Selection index in `new_entries` for the entry selected in `old_entries`:
+/// the same path if still listed, otherwise the nearest surviving neighbour
+fn stable_selection(old_entries: &[PathBuf], old_index: usize, new_entries: &[PathBuf]) -> usize {
+    let position = |path: &PathBuf| new_entries.iter().position(|p| p == path);
+    let after = old_entries.iter().skip(old_index);
+    let before = old_entries.iter().take(old_index).rev();
+
+    after.chain(before)
+        .find_map(position)
+        .unwrap_or(0)
+        .min(new_entries.len().saturating_sub(1))
+}
+
+/// Compare names the way people count: digit runs by value ("file2" before
+/// "file10"), letters regardless of case, ties broken by the exact names
+pub fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+    fn digit_run(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+        let mut run = String::new();
+        while let Some(c) = chars.next_if(char::is_ascii_digit) {
+            run.push(c);
+        }
+        run
+    }
+
+    let (mut a_chars, mut b_chars) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        let order = match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (run_a, run_b) = (digit_run(&mut a_chars), digit_run(&mut b_chars));
+                let (value_a, value_b) = (run_a.trim_start_matches('0'), run_b.trim_start_matches('0'));
+                value_a.len().cmp(&value_b.len()).then_with(|| value_a.cmp(value_b))
+            },
+            (Some(x), Some(y)) => {
+                a_chars.next();
+                b_chars.next();
+                x.to_lowercase().cmp(y.to_lowercase())
+            },
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+}
+
+/// Target of a symlink as stored in the link (None for regular entries)
+pub fn symlink_target(path: &Path) -> Option<PathBuf> {
+    if path.is_symlink() {
+        fs::read_link(path).ok()
+    } else {
+        None
+    }
+}
+
+/// Symlink whose target doesn't exist
+pub fn is_broken_symlink(path: &Path) -> bool {
+    path.is_symlink() && !path.exists()
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ClipboardOperation {
     Copy,
     Cut,
 }
 
+/// How entries are rendered in the file columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ViewMode {
+    #[default]
+    Compact, // Icon and name only
+    Detailed, // Name plus size (or item count) and modified time
+}
+
+impl ViewMode {
+    pub fn toggle(self) -> Self {
+        match self {
+            ViewMode::Compact => ViewMode::Detailed,
+            ViewMode::Detailed => ViewMode::Compact,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct FileSystem {
     pub current_dir: PathBuf,
     pub active_column_index: usize, // Index of currently focused column (0 = leftmost)
-    pub clipboard: Option<(PathBuf, ClipboardOperation)>,
+    pub clipboard: Option<(Vec<PathBuf>, ClipboardOperation)>,
     pub navigation_path: Vec<PathBuf>, // Track navigation history for Miller Columns
     pub column_selections: HashMap<PathBuf, usize>, // Selection index per directory
     pub sort_option: SortOption, // File sorting option
+    pub sort_reverse: bool, // Reverse the sort order (Z-A, smallest or oldest first)
+    pub dirs_first: bool, // Directories before files; off mixes them by the sort key
+    pub previous_dir: Option<PathBuf>, // Last visited directory (for `cd -` style toggle)
+    pub marked: HashSet<PathBuf>, // Marked entries for batch operations
+    pub show_hidden: bool, // Show dotfiles
+    pub view_mode: ViewMode, // Compact or detailed entry rendering
+    pub entered_dirs: Vec<PathBuf>, // Directories entered since the app last recorded them (jump list)
+    pub backend: Arc<dyn Backend>, // Where entries are read from: the disk or a remote host
 }
 
 impl FileSystem {
     pub fn new() -> Self {
         let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/"));
+        Self::with_backend(Arc::new(LocalFs), current_dir)
+    }
 
+    /// A pane showing `current_dir` as read through `backend`
+    pub fn with_backend(backend: Arc<dyn Backend>, current_dir: PathBuf) -> Self {
         // Build navigation path including parent directory for initial display
         let mut navigation_path = vec![];
         if let Some(parent) = current_dir.parent() {
@@ -41,6 +162,14 @@ impl FileSystem {
             navigation_path,
             column_selections: HashMap::new(),
             sort_option: SortOption::Name, // Default to name sorting
+            sort_reverse: false,
+            dirs_first: true,
+            previous_dir: None,
+            marked: HashSet::new(),
+            show_hidden: false,
+            view_mode: ViewMode::Compact,
+            entered_dirs: Vec::new(),
+            backend,
         };
 
         // Initialize selection for starting directory
@@ -50,7 +179,7 @@ impl FileSystem {
         if let Some(parent) = current_dir.parent() {
             let parent_buf = parent.to_path_buf();
             // Find current_dir in parent's entries and select it
-            let parent_entries = Self::get_entries_for_dir(&parent_buf);
+            let parent_entries = fs.entries_for_dir(&parent_buf);
             if let Some(idx) = parent_entries.iter().position(|p| p == &current_dir) {
                 fs.column_selections.insert(parent_buf, idx);
             }
@@ -59,17 +188,121 @@ impl FileSystem {
         fs
     }
 
+    /// A pane on `dir` read through `backend`, sorted and filtered like this one
+    #[cfg(feature = "sftp")]
+    pub fn remount(&self, backend: Arc<dyn Backend>, dir: PathBuf) -> Self {
+        let mut fs = Self::with_backend(backend, dir);
+        fs.keeping_selections(|fs| {
+            fs.sort_option = self.sort_option;
+            fs.sort_reverse = self.sort_reverse;
+            fs.dirs_first = self.dirs_first;
+            fs.show_hidden = self.show_hidden;
+        });
+        fs.view_mode = self.view_mode;
+        fs
+    }
+
     /// Refresh the current directory view (re-read entries from filesystem)
     pub fn refresh_current_dir(&mut self) {
         // Just clear cached selections that might be stale
-        // The entries are always read fresh from disk
+        // The entries are read fresh from disk (remote listings are re-fetched)
+        self.backend.invalidate();
         let current_selection = self.column_selections.get(&self.current_dir).cloned().unwrap_or(0);
-        let entries = Self::get_entries_for_dir(&self.current_dir);
+        let entries = self.entries_for_dir(&self.current_dir);
         // Clamp selection to valid range
         let new_selection = current_selection.min(entries.len().saturating_sub(1));
         self.column_selections.insert(self.current_dir.clone(), new_selection);
     }
 
+    /// Re-read the columns showing any of `dirs`, keeping their selection in range
+    /// Returns whether a visible column was affected.
+    pub fn refresh_dirs(&mut self, dirs: &[PathBuf]) -> bool {
+        let affected: Vec<PathBuf> = self.navigation_path.iter()
+            .filter(|dir| dirs.contains(dir))
+            .cloned()
+            .collect();
+        for dir in &affected {
+            let count = self.entries_for_dir(dir).len();
+            let selection = self.get_selection(dir).min(count.saturating_sub(1));
+            self.set_selection(dir.clone(), selection);
+        }
+        !affected.is_empty()
+    }
+
+    /// Remember the current directory before navigating away from it
+    /// Marks don't carry over to the new directory
+    pub fn record_previous_dir(&mut self) {
+        self.previous_dir = Some(self.current_dir.clone());
+        self.marked.clear();
+    }
+
+    /// Toggle the mark on an entry; returns true if it is now marked
+    pub fn toggle_mark(&mut self, path: PathBuf) -> bool {
+        if self.marked.remove(&path) {
+            false
+        } else {
+            self.marked.insert(path);
+            true
+        }
+    }
+
+    /// Paths an operation should act on: marked entries (sorted) if any,
+    /// otherwise the selected entry of the active directory
+    pub fn selected_paths(&self) -> Vec<PathBuf> {
+        if !self.marked.is_empty() {
+            let mut paths: Vec<PathBuf> = self.marked.iter().cloned().collect();
+            paths.sort();
+            return paths;
+        }
+
+        self.selected_entry().into_iter().collect()
+    }
+
+    /// Entry under the cursor in the active column
+    pub fn selected_entry(&self) -> Option<PathBuf> {
+        let active_dir = crate::navigation::get_active_directory(self)?;
+        self.entries_for_dir(&active_dir).get(self.get_selection(&active_dir)).cloned()
+    }
+
+    /// Jump directly to a directory, rebuilding the navigation path from scratch
+    pub fn jump_to_dir(&mut self, dir: PathBuf) {
+        if dir != self.current_dir {
+            self.record_previous_dir();
+            self.entered_dirs.push(dir.clone());
+        }
+
+        let mut navigation_path = vec![];
+        if let Some(parent) = dir.parent() {
+            navigation_path.push(parent.to_path_buf());
+
+            // Keep the parent column pointing at the jumped-to directory
+            let parent_buf = parent.to_path_buf();
+            let parent_entries = self.entries_for_dir(&parent_buf);
+            if let Some(idx) = parent_entries.iter().position(|p| p == &dir) {
+                self.column_selections.insert(parent_buf, idx);
+            }
+        }
+        navigation_path.push(dir.clone());
+
+        self.navigation_path = navigation_path;
+        self.current_dir = dir.clone();
+        self.column_selections.entry(dir).or_insert(0);
+        self.active_column_index = self.calculate_current_dir_column_index();
+    }
+
+    /// Swap between the current and the previously visited directory (like `cd -`)
+    /// Returns false if there is no previous directory to go back to
+    pub fn toggle_previous_dir(&mut self) -> bool {
+        match self.previous_dir.clone() {
+            Some(prev) if self.is_dir(&prev) => {
+                tracing::info!(to = ?prev, "Toggling to previous directory");
+                self.jump_to_dir(prev);
+                true
+            }
+            _ => false,
+        }
+    }
+
     pub fn get_selection(&self, dir: &PathBuf) -> usize {
         *self.column_selections.get(dir).unwrap_or(&0)
     }
@@ -78,87 +311,140 @@ impl FileSystem {
         self.column_selections.insert(dir, index);
     }
 
-    pub fn get_entries_for_dir(dir: &PathBuf) -> Vec<PathBuf> {
-        Self::get_entries_for_dir_sorted(dir, SortOption::Name)
+    /// Entries of `dir` as displayed in this pane (sort order and hidden-file filter applied)
+    pub fn entries_for_dir(&self, dir: &Path) -> Vec<PathBuf> {
+        let sort_option = self.effective_sort(dir);
+        Self::sorted_entries(self.backend.as_ref(), dir, sort_option, self.sort_reverse, self.dirs_first, self.show_hidden)
+    }
+
+    /// Whether this pane browses a remote host
+    pub fn is_remote(&self) -> bool {
+        self.backend.remote_label().is_some()
     }
 
-    pub fn get_entries_for_dir_sorted(dir: &PathBuf, sort_option: SortOption) -> Vec<PathBuf> {
-        let mut entries = Vec::new();
+    /// Metadata of an entry as the pane's backend sees it
+    pub fn entry_meta(&self, path: &Path) -> Option<EntryMeta> {
+        self.backend.metadata(path).ok()
+    }
 
-        // Add parent entry (..) at the top, except for root
-        if let Some(parent) = dir.parent() {
-            entries.push(parent.to_path_buf());
+    /// Whether `path` is a directory (or a link to one) in this pane
+    pub fn is_dir(&self, path: &Path) -> bool {
+        if self.is_remote() {
+            self.entry_meta(path).is_some_and(|meta| meta.is_dir)
+        } else {
+            path.is_dir()
         }
+    }
 
-        // Add all directory contents
-        if let Ok(read_dir) = fs::read_dir(dir) {
-            for entry in read_dir.flatten() {
-                entries.push(entry.path());
-            }
+    /// Show or hide dotfiles, keeping each visible column's selection on the same path
+    /// (or the nearest entry still visible)
+    pub fn set_show_hidden(&mut self, show_hidden: bool) {
+        self.keeping_selections(|fs| fs.show_hidden = show_hidden);
+    }
+
+    /// Put directories before files or mix them, keeping each column's selection on the same path
+    pub fn set_dirs_first(&mut self, dirs_first: bool) {
+        self.keeping_selections(|fs| fs.dirs_first = dirs_first);
+    }
+
+    /// Reverse the sort order or restore it, keeping each column's selection on the same path
+    pub fn set_sort_reverse(&mut self, sort_reverse: bool) {
+        self.keeping_selections(|fs| fs.sort_reverse = sort_reverse);
+    }
+
+    /// Apply a change to which entries are listed or their order, then move
+    /// each visible column's selection back to the path it was on
+    fn keeping_selections(&mut self, change: impl FnOnce(&mut Self)) {
+        let before: Vec<(PathBuf, Vec<PathBuf>, usize)> = self.navigation_path.iter()
+            .map(|dir| (dir.clone(), self.entries_for_dir(dir), self.get_selection(dir)))
+            .collect();
+
+        change(self);
+
+        for (dir, old_entries, old_index) in before {
+            let new_entries = self.entries_for_dir(&dir);
+            let index = stable_selection(&old_entries, old_index, &new_entries);
+            self.set_selection(dir, index);
         }
+    }
 
-        // Sort entries (keeping parent at top)
-        let parent_entry = entries.first().cloned();
-        let has_parent = parent_entry.as_ref().map(|p| dir.parent() == Some(p.as_path())).unwrap_or(false);
+    /// Sort order stored in the directory's `.senterm-sort` file, if any
+    pub fn dir_sort_override(dir: &std::path::Path) -> Option<SortOption> {
+        let content = fs::read_to_string(dir.join(SORT_FILE)).ok()?;
+        SortOption::parse(&content)
+    }
 
-        if has_parent {
-            // Sort everything except the first entry (parent)
-            let mut content_entries: Vec<PathBuf> = entries.into_iter().skip(1).collect();
+    /// Remember a sort order for `dir` in its `.senterm-sort` file
+    pub fn write_dir_sort(dir: &std::path::Path, sort_option: SortOption) -> Result<(), std::io::Error> {
+        fs::write(dir.join(SORT_FILE), format!("{}\n", sort_option.as_str()))
+    }
 
-            // Sort based on option
-            content_entries.sort_by(|a, b| {
-                // Always put directories first
-                match (a.is_dir(), b.is_dir()) {
-                    (true, false) => return std::cmp::Ordering::Less,
-                    (false, true) => return std::cmp::Ordering::Greater,
-                    _ => {}
-                }
+    /// Sort order actually used for `dir` (per-directory override or the pane default)
+    pub fn effective_sort(&self, dir: &std::path::Path) -> SortOption {
+        // Override files are only read from the local disk
+        if self.is_remote() {
+            return self.sort_option;
+        }
+        Self::dir_sort_override(dir).unwrap_or(self.sort_option)
+    }
+
+    /// Column title label of the sort used for `dir`, e.g. "name↓" (↑ when reversed)
+    pub fn sort_label(&self, dir: &std::path::Path) -> String {
+        format!("{}{}", self.effective_sort(dir).as_str(), if self.sort_reverse { "↑" } else { "↓" })
+    }
+
+    /// Entries of `dir` read through `backend`, sorted by `sort_option` after ".."
+    /// Names ascend, sizes and dates descend; `reverse` flips that. With
+    /// `dirs_first` directories come before files whatever the order.
+    fn sorted_entries(backend: &dyn Backend, dir: &Path, sort_option: SortOption, reverse: bool, dirs_first: bool, show_hidden: bool) -> Vec<PathBuf> {
+        let mut entries: Vec<(PathBuf, EntryMeta)> = Vec::new();
+
+        // Add parent entry (..) at the top, except for root
+        if let Some(parent) = dir.parent() {
+            entries.push((parent.to_path_buf(), EntryMeta { is_dir: true, ..EntryMeta::default() }));
+        }
 
-                // Then sort by the selected option
-                match sort_option {
-                    SortOption::Name => a.file_name().cmp(&b.file_name()),
-                    SortOption::Size => {
-                        let size_a = a.metadata().map(|m| m.len()).unwrap_or(0);
-                        let size_b = b.metadata().map(|m| m.len()).unwrap_or(0);
-                        size_b.cmp(&size_a) // Descending
-                    },
-                    SortOption::Modified => {
-                        let time_a = a.metadata().and_then(|m| m.modified()).ok();
-                        let time_b = b.metadata().and_then(|m| m.modified()).ok();
-                        time_b.cmp(&time_a) // Most recent first
-                    },
+        // Add all directory contents
+        if let Ok(listing) = backend.read_dir(dir) {
+            for (path, meta) in listing {
+                let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                // The sort override file is bookkeeping, not content
+                if name == SORT_FILE {
+                    continue;
+                }
+                if !show_hidden && name.starts_with('.') {
+                    continue;
                 }
-            });
+                entries.push((path, meta));
+            }
+        }
 
-            // Reconstruct with parent first
-            let mut result = vec![parent_entry.unwrap()];
-            result.extend(content_entries);
-            result
-        } else {
-            // No parent, just sort normally
-            entries.sort_by(|a, b| {
-                match (a.is_dir(), b.is_dir()) {
+        let compare = |(a, a_meta): &(PathBuf, EntryMeta), (b, b_meta): &(PathBuf, EntryMeta)| {
+            if dirs_first {
+                match (a_meta.is_dir, b_meta.is_dir) {
                     (true, false) => return std::cmp::Ordering::Less,
                     (false, true) => return std::cmp::Ordering::Greater,
                     _ => {}
                 }
+            }
 
-                match sort_option {
-                    SortOption::Name => a.file_name().cmp(&b.file_name()),
-                    SortOption::Size => {
-                        let size_a = a.metadata().map(|m| m.len()).unwrap_or(0);
-                        let size_b = b.metadata().map(|m| m.len()).unwrap_or(0);
-                        size_b.cmp(&size_a)
-                    },
-                    SortOption::Modified => {
-                        let time_a = a.metadata().and_then(|m| m.modified()).ok();
-                        let time_b = b.metadata().and_then(|m| m.modified()).ok();
-                        time_b.cmp(&time_a)
-                    },
-                }
-            });
-            entries
-        }
+            // Then sort by the selected option
+            let order = match sort_option {
+                SortOption::Name => a.file_name().cmp(&b.file_name()),
+                SortOption::Natural => natural_cmp(
+                    &a.file_name().unwrap_or_default().to_string_lossy(),
+                    &b.file_name().unwrap_or_default().to_string_lossy(),
+                ),
+                SortOption::Size => b_meta.len.cmp(&a_meta.len), // Descending
+                SortOption::Modified => b_meta.modified.cmp(&a_meta.modified), // Most recent first
+            };
+            if reverse { order.reverse() } else { order }
+        };
+
+        // Sort entries (keeping parent at top)
+        let first_content = usize::from(dir.parent().is_some());
+        entries[first_content..].sort_by(compare);
+        entries.into_iter().map(|(path, _)| path).collect()
     }
 
     #[tracing::instrument(skip(self))]
@@ -173,7 +459,7 @@ impl FileSystem {
 
     #[tracing::instrument(skip(self))]
     pub fn navigate_down(&mut self, dir: &PathBuf) {
-        let entries = Self::get_entries_for_dir_sorted(dir, self.sort_option);
+        let entries = self.entries_for_dir(dir);
         let current_selection = self.get_selection(dir);
         if current_selection + 1 < entries.len() {
             self.set_selection(dir.clone(), current_selection + 1);
@@ -185,21 +471,25 @@ impl FileSystem {
     #[tracing::instrument(skip(self))]
     pub fn enter_directory(&mut self) {
         let current_dir = self.current_dir.clone();
-        let entries = Self::get_entries_for_dir(&current_dir);
+        let entries = self.entries_for_dir(&current_dir);
         let selected_index = self.get_selection(&current_dir);
 
         if let Some(path) = entries.get(selected_index) {
-            if path.is_dir() {
+            if self.is_dir(path) {
                 // Check if we are entering the parent directory (Go Back)
                 if Some(path.as_path()) == current_dir.parent() {
                     self.go_back();
+                } else if self.is_symlink_loop(path) {
+                    tracing::warn!(path = ?path, "Refusing to follow symlink loop");
                 } else {
                     tracing::info!(path = ?path, "Entering directory");
+                    self.record_previous_dir();
                     self.current_dir = path.clone();
                     self.navigation_path.push(path.clone());
+                    self.entered_dirs.push(path.clone());
 
                     // Get entries to determine selection index
-                    let new_entries = Self::get_entries_for_dir_sorted(path, self.sort_option);
+                    let new_entries = self.entries_for_dir(path);
 
                     // Determine first real item index (skip parent entry if exists)
                     let first_real_idx = if !new_entries.is_empty() {
@@ -235,6 +525,23 @@ impl FileSystem {
         }
     }
 
+    /// Whether following the symlink `path` would lead back into a directory that is
+    /// already open (an ancestor or a column in navigation_path)
+    /// The link itself stays in navigation_path so its name is what gets displayed.
+    pub fn is_symlink_loop(&self, path: &Path) -> bool {
+        if self.is_remote() || !path.is_symlink() {
+            return false;
+        }
+        let Ok(target) = path.canonicalize() else { return false };
+
+        let is_ancestor = path.parent()
+            .and_then(|parent| parent.canonicalize().ok())
+            .is_some_and(|parent| parent.starts_with(&target));
+        is_ancestor || self.navigation_path.iter()
+            .filter_map(|p| p.canonicalize().ok())
+            .any(|p| p == target)
+    }
+
     /// Calculate the column index of current_dir in the visible columns
     pub fn calculate_current_dir_column_index(&self) -> usize {
         // Build visible_path similar to calculate_visible_columns
@@ -270,7 +577,7 @@ impl FileSystem {
 
     #[tracing::instrument(skip(self))]
     pub fn go_back(&mut self) {
-        if let Some(parent) = self.current_dir.parent() {
+        if let Some(parent) = self.current_dir.parent().map(Path::to_path_buf) {
             tracing::info!(to = ?parent, "Going back to parent");
             
             // Remove current directory from navigation path if it's the last element
@@ -278,8 +585,10 @@ impl FileSystem {
                 self.navigation_path.pop();
             }
             
-            // Move to parent directory
-            self.current_dir = parent.to_path_buf();
+            // Move to parent directory (marks don't follow)
+            self.record_previous_dir();
+            self.current_dir = parent;
+            self.entered_dirs.push(self.current_dir.clone());
             
             // Ensure parent has selection initialized
             if !self.column_selections.contains_key(&self.current_dir) {
@@ -300,59 +609,63 @@ impl FileSystem {
 
     #[tracing::instrument(skip(self))]
     pub fn copy_selected(&mut self) {
-        // Get the active directory (currently focused column)
-        if let Some(active_dir) = crate::navigation::get_active_directory(self) {
-            let entries = Self::get_entries_for_dir(&active_dir);
-            let selected_index = self.get_selection(&active_dir);
-
-            if let Some(path) = entries.get(selected_index) {
-                tracing::info!(path = ?path, active_dir = ?active_dir, "Copied to clipboard");
-                self.clipboard = Some((path.clone(), ClipboardOperation::Copy));
-            }
+        let paths = self.selected_paths();
+        if !paths.is_empty() {
+            tracing::info!(paths = ?paths, "Copied to clipboard");
+            self.clipboard = Some((paths, ClipboardOperation::Copy));
+            self.marked.clear();
         }
     }
 
     #[tracing::instrument(skip(self))]
     pub fn cut_selected(&mut self) {
-        // Get the active directory (currently focused column)
-        if let Some(active_dir) = crate::navigation::get_active_directory(self) {
-            let entries = Self::get_entries_for_dir(&active_dir);
-            let selected_index = self.get_selection(&active_dir);
-
-            if let Some(path) = entries.get(selected_index) {
-                tracing::info!(path = ?path, active_dir = ?active_dir, "Cut to clipboard");
-                self.clipboard = Some((path.clone(), ClipboardOperation::Cut));
-            }
+        let paths = self.selected_paths();
+        if !paths.is_empty() {
+            tracing::info!(paths = ?paths, "Cut to clipboard");
+            self.clipboard = Some((paths, ClipboardOperation::Cut));
+            self.marked.clear();
         }
     }
 
     #[tracing::instrument(skip(self))]
-    pub fn delete_selected(&mut self) -> Result<(), std::io::Error> {
-        // Use the active directory (current focused column / PATH)
-        let target_dir = crate::navigation::get_active_directory(self)
-            .unwrap_or_else(|| self.current_dir.clone());
-        let entries = Self::get_entries_for_dir(&target_dir);
-        let selected_index = self.get_selection(&target_dir);
+    pub fn delete_selected(&mut self) -> OperationSummary {
+        let paths = self.selected_paths();
+        self.marked.clear();
+        Self::delete_paths(&paths)
+    }
 
-        if let Some(path) = entries.get(selected_index) {
-            if path.is_dir() {
-                fs::remove_dir_all(path)?;
+    /// Move the marked entries (or the selected one) into the trash
+    #[tracing::instrument(skip(self))]
+    pub fn trash_selected(&mut self, trash_root: &std::path::Path) -> OperationSummary {
+        let paths = self.selected_paths();
+        self.marked.clear();
+        trash::move_to_trash(trash_root, &paths)
+    }
+
+    /// Delete every path, recording a per-item result
+    pub fn delete_paths(paths: &[PathBuf]) -> OperationSummary {
+        let mut summary = OperationSummary::new("Delete");
+        for path in paths {
+            let result = if path.is_dir() {
+                fs::remove_dir_all(path)
             } else {
-                fs::remove_file(path)?;
+                fs::remove_file(path)
+            };
+            match &result {
+                Ok(_) => tracing::info!(path = ?path, "Deleted"),
+                Err(e) => tracing::error!(path = ?path, ?e, "Delete failed"),
             }
-            tracing::info!(path = ?path, "Deleted from active directory");
-            Ok(())
-        } else {
-            Err(std::io::Error::new(std::io::ErrorKind::NotFound, "No file selected"))
+            summary.record(path, result);
         }
+        summary
     }
 
     #[tracing::instrument(skip(self))]
-    pub fn rename_selected(&mut self, new_name: &str) -> Result<(), std::io::Error> {
+    pub fn rename_selected(&mut self, new_name: &str) -> Result<(PathBuf, PathBuf), std::io::Error> {
         // Use the active directory (current focused column / PATH)
         let target_dir = crate::navigation::get_active_directory(self)
             .unwrap_or_else(|| self.current_dir.clone());
-        let entries = Self::get_entries_for_dir(&target_dir);
+        let entries = self.entries_for_dir(&target_dir);
         let selected_index = self.get_selection(&target_dir);
 
         if let Some(old_path) = entries.get(selected_index).cloned() {
@@ -373,19 +686,19 @@ impl FileSystem {
             }
 
             // Update selection index to point to the new item after re-sorting
-            let new_entries = Self::get_entries_for_dir_sorted(&target_dir, self.sort_option);
+            let new_entries = self.entries_for_dir(&target_dir);
             if let Some(new_index) = new_entries.iter().position(|p| p == &new_path) {
                 self.set_selection(target_dir, new_index);
             }
 
-            Ok(())
+            Ok((old_path, new_path))
         } else {
             Err(std::io::Error::new(std::io::ErrorKind::NotFound, "No file selected"))
         }
     }
 
     #[tracing::instrument(skip(self))]
-    pub fn create_file(&mut self, name: &str) -> Result<(), std::io::Error> {
+    pub fn create_file(&mut self, name: &str) -> Result<PathBuf, std::io::Error> {
         // Use the active directory (current focused column)
         let target_dir = crate::navigation::get_active_directory(self)
             .unwrap_or_else(|| self.current_dir.clone());
@@ -393,11 +706,11 @@ impl FileSystem {
         let file_path = target_dir.join(name);
         fs::File::create(&file_path)?;
         tracing::info!(path = ?file_path, target_dir = ?target_dir, "Created file in active directory");
-        Ok(())
+        Ok(file_path)
     }
 
     #[tracing::instrument(skip(self))]
-    pub fn create_folder(&mut self, name: &str) -> Result<(), std::io::Error> {
+    pub fn create_folder(&mut self, name: &str) -> Result<PathBuf, std::io::Error> {
         // Use the active directory (current focused column)
         let target_dir = crate::navigation::get_active_directory(self)
             .unwrap_or_else(|| self.current_dir.clone());
@@ -405,73 +718,24 @@ impl FileSystem {
         let folder_path = target_dir.join(name);
         fs::create_dir(&folder_path)?;
         tracing::info!(path = ?folder_path, target_dir = ?target_dir, "Created folder in active directory");
-        Ok(())
+        Ok(folder_path)
     }
 
+    /// Start pasting the clipboard into the active directory on a background thread
+    /// Returns None if there is nothing to paste
     #[tracing::instrument(skip(self))]
-    pub fn paste(&mut self) {
-        // Clone clipboard to avoid borrow checker issues
-        let clipboard_data = self.clipboard.clone();
-
-        if let Some((src_path, op)) = clipboard_data {
-            // Get the active directory (destination for paste)
-            if let Some(active_dir) = crate::navigation::get_active_directory(self) {
-                // Target is active directory + filename
-                if let Some(file_name) = src_path.file_name() {
-                    let mut dest_path = active_dir.join(file_name);
-
-                    // Handle collision (simple rename for now if exists, or error?)
-                    if dest_path.exists() {
-                        let stem = src_path.file_stem().unwrap_or_default().to_string_lossy();
-                        let ext = src_path.extension().unwrap_or_default().to_string_lossy();
-                        let new_name = if ext.is_empty() {
-                            format!("{}_copy", stem)
-                        } else {
-                            format!("{}_copy.{}", stem, ext)
-                        };
-                        dest_path = active_dir.join(new_name);
-                    }
-
-                    let result = match op {
-                        ClipboardOperation::Copy => {
-                            if src_path.is_dir() {
-                                // Use fs_extra for recursive directory copy
-                                let mut options = fs_extra::dir::CopyOptions::new();
-                                options.overwrite = false;  // Don't overwrite existing
-                                options.skip_exist = true;  // Skip if exists
-                                options.copy_inside = true; // Copy contents into destination
-
-                                match fs_extra::dir::copy(&src_path, &active_dir, &options) {
-                                    Ok(_) => Ok(()),
-                                    Err(e) => Err(std::io::Error::new(
-                                        std::io::ErrorKind::Other,
-                                        format!("Directory copy failed: {}", e)
-                                    ))
-                                }
-                            } else {
-                                fs::copy(&src_path, &dest_path).map(|_| ())
-                            }
-                        },
-                        ClipboardOperation::Cut => {
-                            fs::rename(&src_path, &dest_path)
-                        }
-                    };
-
-                    match result {
-                        Ok(_) => {
-                            tracing::info!(?op, from = ?src_path, to = ?dest_path, active_dir = ?active_dir, "Paste successful");
-                            // Entries are now queried on-demand, no need to refresh
-                            if let ClipboardOperation::Cut = op {
-                                self.clipboard = None;
-                            }
-                        },
-                        Err(e) => {
-                            tracing::error!(?e, "Paste failed");
-                        }
-                    }
-                }
-            }
+    pub fn paste(&mut self) -> Option<PasteJob> {
+        let (src_paths, op) = self.clipboard.clone()?;
+        // Get the active directory (destination for paste)
+        let active_dir = crate::navigation::get_active_directory(self)?;
+
+        // Moved items leave their source; failures are put back when the job finishes
+        if let ClipboardOperation::Cut = op {
+            self.clipboard = None;
         }
+
+        tracing::info!(?op, paths = ?src_paths, active_dir = ?active_dir, "Starting paste");
+        Some(PasteJob::start(src_paths, op, active_dir))
     }
 }
 
@@ -481,6 +745,12 @@ mod tests {
     use std::fs as stdfs;
     use tempfile::tempdir;
 
+    /// Entries of a local directory as a pane with these settings lists them
+    fn local_entries(dir: &Path, sort_option: SortOption, reverse: bool, dirs_first: bool, show_hidden: bool) -> Vec<PathBuf> {
+        let sort_option = FileSystem::dir_sort_override(dir).unwrap_or(sort_option);
+        FileSystem::sorted_entries(&LocalFs, dir, sort_option, reverse, dirs_first, show_hidden)
+    }
+
     #[test]
     fn test_filesystem_new() {
         let fs = FileSystem::new();
@@ -528,7 +798,7 @@ mod tests {
         stdfs::File::create(temp_path.join("file1.txt")).unwrap();
         stdfs::File::create(temp_path.join("file2.rs")).unwrap();
 
-        let entries = FileSystem::get_entries_for_dir(&temp_path);
+        let entries = local_entries(&temp_path, SortOption::Name, false, true, true);
         
         // Should contain parent + subdir + 2 files = 4 entries
         assert!(entries.len() >= 3); // At least our created items
@@ -564,7 +834,7 @@ mod tests {
         stdfs::File::create(temp_path.join("apple.txt")).unwrap();
         stdfs::File::create(temp_path.join("mango.txt")).unwrap();
 
-        let entries = FileSystem::get_entries_for_dir_sorted(&temp_path, SortOption::Name);
+        let entries = local_entries(&temp_path, SortOption::Name, false, true, true);
         
         // Find file positions (skip parent entry)
         let file_names: Vec<_> = entries.iter()
@@ -643,7 +913,7 @@ mod tests {
         fs.refresh_current_dir();
         
         // Selection should be clamped to valid range
-        let entries = FileSystem::get_entries_for_dir(&temp_path);
+        let entries = local_entries(&temp_path, SortOption::Name, false, true, true);
         let selection = fs.get_selection(&temp_path);
         assert!(selection < entries.len() || entries.is_empty());
     }
@@ -655,4 +925,278 @@ mod tests {
         // Index should be valid (non-negative, reasonable value)
         assert!(index < 10);
     }
+
+    #[test]
+    fn test_toggle_previous_dir() {
+        let temp = tempdir().unwrap();
+        let dir_a = temp.path().join("a");
+        let dir_b = temp.path().join("b");
+        stdfs::create_dir(&dir_a).unwrap();
+        stdfs::create_dir(&dir_b).unwrap();
+
+        let mut fs = FileSystem::new();
+        fs.jump_to_dir(dir_a.clone());
+        fs.jump_to_dir(dir_b.clone());
+        assert_eq!(fs.current_dir, dir_b);
+
+        // First toggle returns to A
+        assert!(fs.toggle_previous_dir());
+        assert_eq!(fs.current_dir, dir_a);
+        assert_eq!(fs.navigation_path.last(), Some(&dir_a));
+        assert_eq!(fs.navigation_path.first(), Some(&temp.path().to_path_buf()));
+
+        // Second toggle goes back to B
+        assert!(fs.toggle_previous_dir());
+        assert_eq!(fs.current_dir, dir_b);
+        assert_eq!(fs.navigation_path.last(), Some(&dir_b));
+    }
+
+    #[test]
+    fn test_toggle_previous_dir_after_enter_directory() {
+        let temp = tempdir().unwrap();
+        let temp_path = temp.path().to_path_buf();
+        let sub = temp_path.join("sub");
+        stdfs::create_dir(&sub).unwrap();
+
+        let mut fs = FileSystem::new();
+        fs.jump_to_dir(temp_path.clone());
+        // Select "sub" (index 0 is the parent entry)
+        fs.set_selection(temp_path.clone(), 1);
+        fs.enter_directory();
+        assert_eq!(fs.current_dir, sub);
+        assert_eq!(fs.previous_dir, Some(temp_path.clone()));
+
+        assert!(fs.toggle_previous_dir());
+        assert_eq!(fs.current_dir, temp_path);
+        assert!(fs.toggle_previous_dir());
+        assert_eq!(fs.current_dir, sub);
+    }
+
+    #[test]
+    fn test_toggle_previous_dir_without_history() {
+        let mut fs = FileSystem::new();
+        let before = fs.current_dir.clone();
+        assert!(!fs.toggle_previous_dir());
+        assert_eq!(fs.current_dir, before);
+    }
+
+    #[test]
+    fn test_delete_operates_on_marked_set() {
+        let temp = tempdir().unwrap();
+        let temp_path = temp.path().to_path_buf();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            stdfs::File::create(temp_path.join(name)).unwrap();
+        }
+
+        let mut fs = FileSystem::new();
+        fs.jump_to_dir(temp_path.clone());
+        assert!(fs.toggle_mark(temp_path.join("c.txt")));
+        assert!(fs.toggle_mark(temp_path.join("a.txt")));
+        assert_eq!(fs.selected_paths(), vec![temp_path.join("a.txt"), temp_path.join("c.txt")]);
+
+        let summary = fs.delete_selected();
+        assert_eq!(summary.succeeded(), 2);
+        assert!(fs.marked.is_empty());
+        assert!(!temp_path.join("a.txt").exists());
+        assert!(temp_path.join("b.txt").exists());
+        assert!(!temp_path.join("c.txt").exists());
+    }
+
+    #[test]
+    fn test_selected_paths_falls_back_to_selection() {
+        let temp = tempdir().unwrap();
+        let temp_path = temp.path().to_path_buf();
+        stdfs::File::create(temp_path.join("only.txt")).unwrap();
+
+        let mut fs = FileSystem::new();
+        fs.jump_to_dir(temp_path.clone());
+        // Index 0 is the parent entry
+        fs.set_selection(temp_path.clone(), 1);
+        assert_eq!(fs.selected_paths(), vec![temp_path.join("only.txt")]);
+
+        // Toggling twice unmarks
+        assert!(fs.toggle_mark(temp_path.join("only.txt")));
+        assert!(!fs.toggle_mark(temp_path.join("only.txt")));
+        assert!(fs.marked.is_empty());
+    }
+
+    #[test]
+    fn test_marks_clear_on_directory_change() {
+        let temp = tempdir().unwrap();
+        let temp_path = temp.path().to_path_buf();
+        let sub = temp_path.join("sub");
+        stdfs::create_dir(&sub).unwrap();
+
+        let mut fs = FileSystem::new();
+        fs.jump_to_dir(temp_path.clone());
+        fs.toggle_mark(sub.clone());
+        fs.jump_to_dir(sub.clone());
+        assert!(fs.marked.is_empty());
+
+        // Going back up drops them too
+        fs.toggle_mark(sub.join("file"));
+        fs.go_back();
+        assert!(fs.marked.is_empty());
+    }
+
+    #[test]
+    fn test_copy_and_paste_marked_set() {
+        let temp = tempdir().unwrap();
+        let src = temp.path().join("src");
+        let dest = temp.path().join("dest");
+        stdfs::create_dir(&src).unwrap();
+        stdfs::create_dir(&dest).unwrap();
+        stdfs::write(src.join("one.txt"), "1").unwrap();
+        stdfs::write(src.join("two.txt"), "2").unwrap();
+
+        let mut fs = FileSystem::new();
+        fs.jump_to_dir(src.clone());
+        fs.toggle_mark(src.join("one.txt"));
+        fs.toggle_mark(src.join("two.txt"));
+        fs.cut_selected();
+        assert!(fs.marked.is_empty());
+
+        fs.jump_to_dir(dest.clone());
+        let summary = fs.paste().unwrap().wait();
+        assert!(summary.is_batch());
+        assert_eq!(summary.succeeded(), 2);
+        assert!(dest.join("one.txt").exists());
+        assert!(dest.join("two.txt").exists());
+        assert!(!src.join("one.txt").exists());
+        assert!(fs.clipboard.is_none());
+        assert!(fs.paste().is_none());
+    }
+
+    #[test]
+    fn test_senterm_sort_override() {
+        let temp = tempdir().unwrap();
+        let temp_path = temp.path().to_path_buf();
+        stdfs::write(temp_path.join("a_small.txt"), "1").unwrap();
+        stdfs::write(temp_path.join("b_large.txt"), "1234567890").unwrap();
+
+        // Without override the requested sort is used
+        let entries = local_entries(&temp_path, SortOption::Name, false, true, true);
+        assert_eq!(entries[1], temp_path.join("a_small.txt"));
+
+        FileSystem::write_dir_sort(&temp_path, SortOption::Size).unwrap();
+        assert_eq!(FileSystem::dir_sort_override(&temp_path), Some(SortOption::Size));
+
+        let entries = local_entries(&temp_path, SortOption::Name, false, true, true);
+        assert_eq!(entries[1], temp_path.join("b_large.txt"));
+        // The override file itself is hidden
+        assert!(!entries.iter().any(|p| p.file_name().is_some_and(|n| n == SORT_FILE)));
+        assert_eq!(entries.len(), 3);
+    }
+
+    #[test]
+    fn test_reverse_and_mixed_sort() {
+        let temp = tempdir().unwrap();
+        let temp_path = temp.path().to_path_buf();
+        stdfs::create_dir(temp_path.join("dir")).unwrap();
+        stdfs::write(temp_path.join("a.txt"), "").unwrap();
+        stdfs::write(temp_path.join("b.txt"), "").unwrap();
+
+        let entries = local_entries(&temp_path, SortOption::Name, true, true, true);
+        assert_eq!(entries[1..], [temp_path.join("dir"), temp_path.join("b.txt"), temp_path.join("a.txt")]);
+        // Mixed, ".." still on top
+        let entries = local_entries(&temp_path, SortOption::Name, false, false, true);
+        assert_eq!(entries, [temp.path().parent().unwrap().to_path_buf(), temp_path.join("a.txt"), temp_path.join("b.txt"), temp_path.join("dir")]);
+
+        let mut fs = FileSystem::new();
+        assert_eq!(fs.sort_label(&temp_path), "name↓");
+        fs.sort_reverse = true;
+        assert_eq!(fs.sort_label(&temp_path), "name↑");
+    }
+
+    #[test]
+    fn test_natural_sort() {
+        let mut names = vec!["file10.txt", "File2.txt", "file1.txt", "file02b", "ep 9", "ep 10", "v1.10", "v1.9"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, ["ep 9", "ep 10", "file1.txt", "File2.txt", "file02b", "file10.txt", "v1.9", "v1.10"]);
+        // Case only breaks ties
+        assert_eq!(natural_cmp("a", "B"), std::cmp::Ordering::Less);
+        assert_eq!(natural_cmp("A", "a"), std::cmp::Ordering::Less);
+    }
+
+    #[test]
+    fn test_invalid_senterm_sort_is_ignored() {
+        let temp = tempdir().unwrap();
+        stdfs::write(temp.path().join(SORT_FILE), "sideways").unwrap();
+        assert_eq!(FileSystem::dir_sort_override(temp.path()), None);
+    }
+
+    #[test]
+    fn test_hidden_files_filtered_by_default() {
+        let temp = tempdir().unwrap();
+        let temp_path = temp.path().to_path_buf();
+        stdfs::write(temp_path.join(".hidden"), "").unwrap();
+        stdfs::write(temp_path.join("visible"), "").unwrap();
+
+        let hidden = local_entries(&temp_path, SortOption::Name, false, true, false);
+        assert_eq!(hidden, vec![temp.path().parent().unwrap().to_path_buf(), temp_path.join("visible")]);
+
+        let all = local_entries(&temp_path, SortOption::Name, false, true, true);
+        assert!(all.contains(&temp_path.join(".hidden")));
+    }
+
+    #[test]
+    fn test_toggle_hidden_keeps_selected_path() {
+        let temp = tempdir().unwrap();
+        let temp_path = temp.path().to_path_buf();
+        for name in [".a", "b", ".c", "d"] {
+            stdfs::write(temp_path.join(name), "").unwrap();
+        }
+
+        let mut fs = FileSystem::new();
+        fs.jump_to_dir(temp_path.clone());
+        // Hidden: [.., b, d] -> select "d"
+        fs.set_selection(temp_path.clone(), 2);
+        fs.set_show_hidden(true);
+        // Shown: [.., .a, .c, b, d]
+        assert_eq!(fs.entries_for_dir(&temp_path)[fs.get_selection(&temp_path)], temp_path.join("d"));
+
+        // Select ".c", then hide: nearest following visible entry is "b"
+        fs.set_selection(temp_path.clone(), 2);
+        fs.set_show_hidden(false);
+        assert_eq!(fs.entries_for_dir(&temp_path)[fs.get_selection(&temp_path)], temp_path.join("b"));
+
+        // Reversing the order keeps "b" selected
+        fs.set_sort_reverse(true);
+        assert_eq!(fs.entries_for_dir(&temp_path)[fs.get_selection(&temp_path)], temp_path.join("b"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_loop_detection() {
+        let temp = tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        stdfs::create_dir_all(root.join("a/b")).unwrap();
+        std::os::unix::fs::symlink(&root, root.join("a/b/up")).unwrap();
+        std::os::unix::fs::symlink(root.join("a/b"), root.join("side")).unwrap();
+        std::os::unix::fs::symlink(root.join("missing"), root.join("broken")).unwrap();
+
+        let mut fs = FileSystem::new();
+        fs.jump_to_dir(root.join("a/b"));
+        // Points at an ancestor
+        assert!(fs.is_symlink_loop(&root.join("a/b/up")));
+        // Points at a directory already open in navigation_path
+        assert!(fs.is_symlink_loop(&root.join("side")));
+        // Plain directories are never loops
+        assert!(!fs.is_symlink_loop(&root.join("a")));
+
+        assert_eq!(symlink_target(&root.join("broken")), Some(root.join("missing")));
+        assert!(is_broken_symlink(&root.join("broken")));
+        assert!(!is_broken_symlink(&root.join("side")));
+        assert_eq!(symlink_target(&root.join("a")), None);
+    }
+
+    #[test]
+    fn test_stable_selection_clamps_to_nearest() {
+        let p = |n: &str| PathBuf::from(n);
+        let old = vec![p("a"), p("b"), p(".x"), p(".y")];
+        // Nothing after the removed entry survives: fall back to the one before it
+        assert_eq!(stable_selection(&old, 3, &[p("a"), p("b")]), 1);
+        assert_eq!(stable_selection(&old, 1, &[p("a"), p("b")]), 1);
+        assert_eq!(stable_selection(&old, 0, &[]), 0);
+    }
 }
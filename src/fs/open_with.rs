@@ -0,0 +1,123 @@
+//! Open files with an external program
+//!
+//! GUI programs are spawned detached. Terminal programs (editors, pagers)
+//! need the terminal, so the main loop leaves the alternate screen and waits
+//! for them, like the senterm-games launch.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+
+/// Programs that take over the terminal and must run in the foreground
+const INTERACTIVE_PROGRAMS: &[&str] = &[
+    "vi", "vim", "nvim", "nano", "micro", "hx", "helix", "emacs", "kak",
+    "less", "more", "most", "bat", "man", "htop", "btop", "top",
+    "mc", "ranger", "lf", "nnn", "tig", "lazygit", "ssh", "python", "python3",
+];
+
+/// Command pre-filled when no command was used for an extension yet
+pub fn default_command() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(windows) {
+        "explorer"
+    } else {
+        "xdg-open"
+    }
+}
+
+/// Key used to remember the command for `path` (lowercase extension, "" if none)
+pub fn extension_key(path: &Path) -> String {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// A program invocation with the file path appended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    pub path: PathBuf,
+}
+
+impl OpenCommand {
+    /// Split `command` on whitespace; returns None if it is empty
+    pub fn parse(command: &str, path: &Path) -> Option<Self> {
+        let mut words = command.split_whitespace().map(str::to_string);
+        let program = words.next()?;
+        Some(Self {
+            program,
+            args: words.collect(),
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Whether the program needs the terminal
+    pub fn is_interactive(&self) -> bool {
+        let name = Path::new(&self.program)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        INTERACTIVE_PROGRAMS.contains(&name.as_str())
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args).arg(&self.path);
+        if let Some(dir) = self.path.parent() {
+            command.current_dir(dir);
+        }
+        command
+    }
+
+    /// Spawn without waiting; output is discarded so it can't garble the TUI
+    pub fn spawn_detached(&self) -> io::Result<()> {
+        let mut child = self.command()
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        tracing::info!(program = %self.program, path = ?self.path, "Spawned external program");
+
+        // Reap the child when it exits so it doesn't linger as a zombie
+        std::thread::spawn(move || {
+            let _ = child.wait();
+        });
+        Ok(())
+    }
+
+    /// Run in the foreground and wait (the caller must release the terminal first)
+    pub fn run_foreground(&self) -> io::Result<ExitStatus> {
+        tracing::info!(program = %self.program, path = ?self.path, "Running external program");
+        self.command().status()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_appends_path() {
+        let path = Path::new("/tmp/movie.mkv");
+        let cmd = OpenCommand::parse("  vlc --fullscreen ", path).unwrap();
+        assert_eq!(cmd.program, "vlc");
+        assert_eq!(cmd.args, vec!["--fullscreen".to_string()]);
+        assert_eq!(cmd.path, path);
+        assert!(!cmd.is_interactive());
+
+        assert!(OpenCommand::parse("   ", path).is_none());
+    }
+
+    #[test]
+    fn test_interactive_programs_and_extension_key() {
+        let path = Path::new("/tmp/Notes.MD");
+        assert!(OpenCommand::parse("nvim", path).unwrap().is_interactive());
+        assert!(OpenCommand::parse("/usr/bin/less -R", path).unwrap().is_interactive());
+        assert!(!OpenCommand::parse("code --wait", path).unwrap().is_interactive());
+
+        assert_eq!(extension_key(path), "md");
+        assert_eq!(extension_key(Path::new("/tmp/Makefile")), "");
+    }
+}
@@ -0,0 +1,127 @@
+//! Path input helpers for the jump-to-path prompt
+//!
+//! Expands `~`, resolves relative input against a base directory and
+//! completes the last path component against the filesystem.
+
+use std::path::{Path, PathBuf};
+
+/// Expand a leading `~` and resolve relative input against `base`
+pub fn resolve_input(input: &str, base: &Path) -> PathBuf {
+    let input = input.trim();
+    let expanded = if input == "~" {
+        dirs::home_dir().unwrap_or_default()
+    } else if let Some(rest) = input.strip_prefix("~/") {
+        dirs::home_dir().unwrap_or_default().join(rest)
+    } else {
+        PathBuf::from(input)
+    };
+
+    let path = if expanded.is_absolute() { expanded } else { base.join(expanded) };
+    normalize(&path)
+}
+
+/// Remove `.` and `..` components lexically (keeps symlinked paths as typed)
+fn normalize(path: &Path) -> PathBuf {
+    use std::path::Component;
+
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => {
+                result.pop();
+            },
+            other => result.push(other),
+        }
+    }
+    result
+}
+
+/// Completions of the last component of `input`, as full input strings
+/// Directories get a trailing `/` so completion can continue into them.
+pub fn complete(input: &str, base: &Path) -> Vec<String> {
+    // Split into the typed directory part and the partial name being completed
+    let (dir_part, partial) = match input.rfind('/') {
+        Some(idx) => input.split_at(idx + 1),
+        None if input == "~" => return vec!["~/".to_string()],
+        None => ("", input),
+    };
+    let dir = if dir_part.is_empty() { base.to_path_buf() } else { resolve_input(dir_part, base) };
+
+    let Ok(read_dir) = std::fs::read_dir(&dir) else { return Vec::new() };
+    let mut candidates: Vec<String> = read_dir
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            // Hidden entries only when explicitly asked for
+            if !name.starts_with(partial) || (name.starts_with('.') && !partial.starts_with('.')) {
+                return None;
+            }
+            let suffix = if entry.path().is_dir() { "/" } else { "" };
+            Some(format!("{}{}{}", dir_part, name, suffix))
+        })
+        .collect();
+    candidates.sort();
+    candidates
+}
+
+/// Longest common prefix of all candidates (char-boundary safe)
+pub fn longest_common_prefix(candidates: &[String]) -> String {
+    let Some(first) = candidates.first() else { return String::new() };
+    let mut prefix_len = first.len();
+    for candidate in &candidates[1..] {
+        prefix_len = first
+            .char_indices()
+            .zip(candidate.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map(|((idx, c), _)| idx + c.len_utf8())
+            .unwrap_or(0)
+            .min(prefix_len);
+    }
+    first[..prefix_len].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_resolve_input() {
+        let base = Path::new("/srv/app");
+        assert_eq!(resolve_input("logs/", base), PathBuf::from("/srv/app/logs"));
+        assert_eq!(resolve_input("../data", base), PathBuf::from("/srv/data"));
+        assert_eq!(resolve_input("/etc/", base), PathBuf::from("/etc"));
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(resolve_input("~/notes", base), home.join("notes"));
+            assert_eq!(resolve_input("~", base), home);
+        }
+    }
+
+    #[test]
+    fn test_complete_and_common_prefix() {
+        let temp = tempdir().unwrap();
+        let root = temp.path();
+        std::fs::create_dir(root.join("project-a")).unwrap();
+        std::fs::create_dir(root.join("project-b")).unwrap();
+        std::fs::write(root.join("profile.txt"), "").unwrap();
+        std::fs::create_dir(root.join(".private")).unwrap();
+
+        let input = format!("{}/pro", root.display());
+        let candidates = complete(&input, Path::new("/"));
+        assert_eq!(candidates, vec![
+            format!("{}/profile.txt", root.display()),
+            format!("{}/project-a/", root.display()),
+            format!("{}/project-b/", root.display()),
+        ]);
+        assert_eq!(longest_common_prefix(&candidates), format!("{}/pro", root.display()));
+        assert_eq!(longest_common_prefix(&candidates[1..]), format!("{}/project-", root.display()));
+
+        // Relative input and hidden entries
+        assert_eq!(complete("project-a", root), vec!["project-a/".to_string()]);
+        assert!(complete("", root).iter().all(|c| !c.starts_with('.')));
+        assert_eq!(complete(".pr", root), vec![".private/".to_string()]);
+        assert!(complete("missing/x", root).is_empty());
+    }
+}
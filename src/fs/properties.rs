@@ -0,0 +1,252 @@
+//! File properties (metadata) shown by the properties dialog
+//!
+//! Owner and group names are resolved with getpwuid_r/getgrgid_r on Unix;
+//! other platforms show the subset std::fs exposes (size, times, read-only).
+//! Permission bits can be edited on Unix only.
+
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Label/value rows describing `path`, in display order
+pub fn read_properties(path: &Path) -> std::io::Result<Vec<(&'static str, String)>> {
+    let meta = std::fs::symlink_metadata(path)?;
+    let file_type = meta.file_type();
+    let mut rows = vec![("Path", path.display().to_string())];
+
+    let kind = if file_type.is_symlink() {
+        "Symbolic link"
+    } else if file_type.is_dir() {
+        "Directory"
+    } else {
+        "File"
+    };
+    rows.push(("Type", kind.to_string()));
+    if file_type.is_symlink() {
+        let target = std::fs::read_link(path).map(|t| t.display().to_string()).unwrap_or_else(|e| e.to_string());
+        rows.push(("Target", target));
+    }
+
+    rows.push(("Size", format!("{} ({} bytes)", crate::ui::format_bytes(meta.len()), meta.len())));
+    if file_type.is_dir() {
+        let children = std::fs::read_dir(path)
+            .map(|entries| entries.count().to_string())
+            .unwrap_or_else(|e| format!("unreadable ({})", e));
+        rows.push(("Items", children));
+    }
+
+    unix_rows(&meta, &mut rows);
+    if !cfg!(unix) {
+        rows.push(("Read-only", if meta.permissions().readonly() { "yes" } else { "no" }.to_string()));
+    }
+
+    rows.push(("Created", format_time(meta.created())));
+    rows.push(("Modified", format_time(meta.modified())));
+    rows.push(("Accessed", format_time(meta.accessed())));
+    Ok(rows)
+}
+
+/// Permissions, owner, group, inode and link count
+#[cfg(unix)]
+fn unix_rows(meta: &std::fs::Metadata, rows: &mut Vec<(&'static str, String)>) {
+    use std::os::unix::fs::MetadataExt;
+
+    let mode = meta.mode();
+    rows.push(("Permissions", format!("{:04o} ({})", mode & 0o7777, symbolic_mode(mode))));
+    let owner = user_name(meta.uid()).map(|n| format!("{} ({})", n, meta.uid())).unwrap_or_else(|| meta.uid().to_string());
+    let group = group_name(meta.gid()).map(|n| format!("{} ({})", n, meta.gid())).unwrap_or_else(|| meta.gid().to_string());
+    rows.push(("Owner", owner));
+    rows.push(("Group", group));
+    rows.push(("Inode", meta.ino().to_string()));
+    rows.push(("Hard links", meta.nlink().to_string()));
+}
+
+#[cfg(not(unix))]
+fn unix_rows(_meta: &std::fs::Metadata, _rows: &mut Vec<(&'static str, String)>) {}
+
+/// `ls -l` style permission string, e.g. "drwxr-xr-x" (setuid/setgid/sticky included)
+pub fn symbolic_mode(mode: u32) -> String {
+    let kind = match mode & 0o170000 {
+        0o040000 => 'd',
+        0o120000 => 'l',
+        0o020000 => 'c',
+        0o060000 => 'b',
+        0o010000 => 'p',
+        0o140000 => 's',
+        _ => '-',
+    };
+    let mut symbolic = String::from(kind);
+    // (read, write, execute bit, special bit, special char if executable, special char if not)
+    let triads = [(0o400, 0o200, 0o100, 0o4000, 's', 'S'), (0o040, 0o020, 0o010, 0o2000, 's', 'S'), (0o004, 0o002, 0o001, 0o1000, 't', 'T')];
+    for (read, write, exec, special, with_exec, without_exec) in triads {
+        symbolic.push(if mode & read != 0 { 'r' } else { '-' });
+        symbolic.push(if mode & write != 0 { 'w' } else { '-' });
+        symbolic.push(match (mode & exec != 0, mode & special != 0) {
+            (true, true) => with_exec,
+            (false, true) => without_exec,
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    symbolic
+}
+
+/// Permission bits of `path` (including setuid/setgid/sticky)
+#[cfg(unix)]
+pub fn permission_mode(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::symlink_metadata(path).ok().map(|m| m.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+pub fn permission_mode(_path: &Path) -> Option<u32> {
+    None
+}
+
+/// Flip one of the nine rwx bits; `index` 0 is owner read, 8 is others execute
+pub fn toggle_permission_bit(mode: u32, index: usize) -> u32 {
+    mode ^ (0o400 >> index)
+}
+
+/// Parse a typed octal mode such as "755" or "4755" (at most 7777)
+pub fn parse_octal_mode(input: &str) -> Option<u32> {
+    if input.is_empty() || input.len() > 4 || !input.bytes().all(|b| (b'0'..=b'7').contains(&b)) {
+        return None;
+    }
+    u32::from_str_radix(input, 8).ok().filter(|mode| *mode <= 0o7777)
+}
+
+/// Apply permission bits to `path`
+/// Failures are reported in words ("not the owner") rather than as a raw errno.
+/// Symlinks are refused: chmod would follow the link and change its target.
+#[cfg(unix)]
+pub fn set_permission_mode(path: &Path, mode: u32) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    if path.is_symlink() {
+        return Err("it is a symlink; change the permissions of its target instead".to_string());
+    }
+    if mode > 0o7777 {
+        return Err(format!("{:o} is not a permission mode", mode));
+    }
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)).map_err(|e| match e.raw_os_error() {
+        Some(libc::EPERM) => "operation not permitted (only the owner or root can change permissions)".to_string(),
+        Some(libc::EROFS) => "the file system is read-only".to_string(),
+        _ => e.to_string(),
+    })
+}
+
+#[cfg(not(unix))]
+pub fn set_permission_mode(_path: &Path, _mode: u32) -> Result<(), String> {
+    Err("changing permissions is not supported on this platform".to_string())
+}
+
+/// Login name of `uid`, if it has an entry in the user database
+#[cfg(unix)]
+fn user_name(uid: u32) -> Option<String> {
+    with_lookup_buffer(|buf| unsafe {
+        let mut entry: libc::passwd = std::mem::zeroed();
+        let mut result = std::ptr::null_mut();
+        match libc::getpwuid_r(uid, &mut entry, buf.as_mut_ptr(), buf.len(), &mut result) {
+            0 if result.is_null() => Ok(None),
+            0 => Ok(Some(std::ffi::CStr::from_ptr(entry.pw_name).to_string_lossy().into_owned())),
+            code => Err(code),
+        }
+    })
+}
+
+/// Name of group `gid`, if it has an entry in the group database
+#[cfg(unix)]
+fn group_name(gid: u32) -> Option<String> {
+    with_lookup_buffer(|buf| unsafe {
+        let mut entry: libc::group = std::mem::zeroed();
+        let mut result = std::ptr::null_mut();
+        match libc::getgrgid_r(gid, &mut entry, buf.as_mut_ptr(), buf.len(), &mut result) {
+            0 if result.is_null() => Ok(None),
+            0 => Ok(Some(std::ffi::CStr::from_ptr(entry.gr_name).to_string_lossy().into_owned())),
+            code => Err(code),
+        }
+    })
+}
+
+/// Run a reentrant passwd/group lookup, retrying with a larger buffer while it reports ERANGE
+#[cfg(unix)]
+fn with_lookup_buffer(lookup: impl Fn(&mut [libc::c_char]) -> Result<Option<String>, libc::c_int>) -> Option<String> {
+    const MAX_BUFFER: usize = 1 << 20;
+    let mut size = 4096;
+    loop {
+        let mut buf = vec![0; size];
+        match lookup(&mut buf) {
+            Ok(name) => return name,
+            Err(libc::ERANGE) if size < MAX_BUFFER => size *= 2,
+            Err(_) => return None,
+        }
+    }
+}
+
+fn format_time(time: std::io::Result<SystemTime>) -> String {
+    match time {
+        Ok(time) => chrono::DateTime::<chrono::Local>::from(time).format("%Y-%m-%d %H:%M:%S").to_string(),
+        Err(_) => "not available".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_symbolic_mode() {
+        assert_eq!(symbolic_mode(0o100644), "-rw-r--r--");
+        assert_eq!(symbolic_mode(0o040755), "drwxr-xr-x");
+        assert_eq!(symbolic_mode(0o104755), "-rwsr-xr-x");
+        assert_eq!(symbolic_mode(0o041777), "drwxrwxrwt");
+        assert_eq!(symbolic_mode(0o102644), "-rw-r-Sr--");
+    }
+
+    #[test]
+    fn test_permission_editing() {
+        assert_eq!(toggle_permission_bit(0o644, 2), 0o744);
+        assert_eq!(toggle_permission_bit(0o755, 8), 0o754);
+        assert_eq!(parse_octal_mode("755"), Some(0o755));
+        assert_eq!(parse_octal_mode("4755"), Some(0o4755));
+        assert_eq!(parse_octal_mode("789"), None);
+        assert_eq!(parse_octal_mode("17555"), None);
+        assert_eq!(parse_octal_mode(""), None);
+        assert_eq!(parse_octal_mode("+755"), None);
+
+        #[cfg(unix)]
+        {
+            let temp = tempdir().unwrap();
+            let script = temp.path().join("run.sh");
+            std::fs::write(&script, "#!/bin/sh\n").unwrap();
+            set_permission_mode(&script, 0o750).unwrap();
+            assert_eq!(permission_mode(&script), Some(0o750));
+
+            // A link is refused and its target left alone
+            let link = temp.path().join("link");
+            std::os::unix::fs::symlink(&script, &link).unwrap();
+            assert!(set_permission_mode(&link, 0o777).unwrap_err().contains("symlink"));
+            assert_eq!(permission_mode(&script), Some(0o750));
+            assert!(set_permission_mode(&script, 0o17777).is_err());
+        }
+    }
+
+    #[test]
+    fn test_read_properties() {
+        let temp = tempdir().unwrap();
+        std::fs::write(temp.path().join("a.txt"), "hello").unwrap();
+        std::fs::create_dir(temp.path().join("sub")).unwrap();
+
+        let rows = read_properties(temp.path()).unwrap();
+        let value = |label: &str| rows.iter().find(|(l, _)| *l == label).map(|(_, v)| v.clone());
+        assert_eq!(value("Type").as_deref(), Some("Directory"));
+        assert_eq!(value("Items").as_deref(), Some("2"));
+
+        let rows = read_properties(&temp.path().join("a.txt")).unwrap();
+        let value = |label: &str| rows.iter().find(|(l, _)| *l == label).map(|(_, v)| v.clone());
+        assert_eq!(value("Size").as_deref(), Some("5 B (5 bytes)"));
+        assert!(value("Items").is_none());
+        #[cfg(unix)]
+        assert!(value("Permissions").unwrap().contains("-rw"));
+    }
+}
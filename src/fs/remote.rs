@@ -0,0 +1,434 @@
+//! SFTP remote panes (the `sftp` feature)
+//!
+//! `:sftp user@host[:port]` connects in the background and mounts the remote
+//! home directory in the active pane. Authentication tries the ssh agent, then
+//! the default keys in ~/.ssh. The host key must already be in
+//! ~/.ssh/known_hosts: there's no prompt to accept a new one, so connect once
+//! with `ssh` first. Remote panes only browse and view.
+//!
+//! Nothing here blocks the UI: every request to the host runs on a worker
+//! thread that owns the session. Drawing reads listings and metadata from a
+//! cache and queues a refresh for anything missing or older than
+//! `LISTING_TTL`; the results show up on a later frame. A request that fails
+//! at the session level drops the connection; the worker reconnects (at most
+//! every `RECONNECT_INTERVAL`), and until then the cached listings are shown
+//! with the host marked offline in the pane title.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Read};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+
+use ssh2::{CheckResult, ErrorCode, FileStat, KnownHostFileKind, Session, Sftp};
+
+use super::backend::{Backend, EntryMeta};
+
+/// How long a directory listing or stat is shown before it's read again
+const LISTING_TTL: Duration = Duration::from_secs(10);
+/// Shortest wait between attempts to re-establish a dropped session
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+/// Connect, handshake and per-request timeout
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// Private keys tried after the agent, in ~/.ssh
+const DEFAULT_KEYS: [&str; 3] = ["id_ed25519", "id_ecdsa", "id_rsa"];
+
+/// Where to connect: `user@host[:port]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SftpTarget {
+    pub user: String,
+    pub host: String,
+    pub port: u16,
+}
+
+impl SftpTarget {
+    /// Parse "user@host", "user@host:port" or just "host" (as the local user)
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        let (user, address) = match input.rsplit_once('@') {
+            Some((user, address)) => (user.to_string(), address),
+            None => (std::env::var("USER").or_else(|_| std::env::var("USERNAME")).ok()?, input),
+        };
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().ok()?),
+            None => (address, 22),
+        };
+        if user.is_empty() || host.is_empty() || host.contains(char::is_whitespace) {
+            return None;
+        }
+        Some(Self { user, host: host.to_string(), port })
+    }
+
+    /// "user@host", with ":port" when it isn't 22
+    pub fn label(&self) -> String {
+        if self.port == 22 {
+            format!("{}@{}", self.user, self.host)
+        } else {
+            format!("{}@{}:{}", self.user, self.host, self.port)
+        }
+    }
+}
+
+/// An authenticated session and its SFTP channel
+struct Connection {
+    _session: Session,
+    sftp: Sftp,
+}
+
+/// Work for the session thread
+enum Request {
+    List(PathBuf),
+    Stat(PathBuf),
+    Read { path: PathBuf, max_bytes: u64, reply: Sender<io::Result<Vec<u8>>> },
+}
+
+/// A listing as last read, or why it couldn't be
+struct Listing {
+    checked: Instant,
+    entries: Result<Vec<(PathBuf, EntryMeta)>, String>,
+}
+
+/// What the UI thread sees: results of finished requests
+#[derive(Default)]
+struct Cache {
+    listings: HashMap<PathBuf, Listing>,
+    entries: HashMap<PathBuf, (Instant, Option<EntryMeta>)>, // Every entry seen in a listing or stat; None = missing
+    queued: HashSet<PathBuf>, // Listings and stats requested but not answered yet
+    offline: bool,
+}
+
+/// A remote host browsed over SFTP
+pub struct RemoteFs {
+    target: SftpTarget,
+    pub home: PathBuf, // Directory the session starts in
+    cache: Arc<Mutex<Cache>>,
+    requests: Sender<Request>,
+}
+
+impl RemoteFs {
+    /// Connect and authenticate (blocks; see `ConnectJob`), then hand the
+    /// session to a worker thread
+    pub fn connect(target: SftpTarget) -> io::Result<Self> {
+        let connection = open(&target)?;
+        let home = connection.sftp.realpath(Path::new(".")).map_err(io::Error::from)?;
+        let cache = Arc::new(Mutex::new(Cache::default()));
+        let (requests, receiver) = mpsc::channel();
+        let worker = Worker { target: target.clone(), connection: Some(connection), last_attempt: Instant::now(), cache: Arc::clone(&cache) };
+        std::thread::spawn(move || worker.run(receiver));
+        Ok(Self { target, home, cache, requests })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Cache> {
+        lock(&self.cache)
+    }
+
+    /// Queue a listing or stat of `path` unless one is already on its way
+    fn queue(&self, cache: &mut Cache, request: Request, path: &Path) {
+        if cache.queued.insert(path.to_path_buf()) && self.requests.send(request).is_err() {
+            cache.queued.remove(path);
+        }
+    }
+}
+
+fn lock(cache: &Mutex<Cache>) -> std::sync::MutexGuard<'_, Cache> {
+    cache.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn still_loading(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::WouldBlock, format!("{} is still loading", path.display()))
+}
+
+impl Backend for RemoteFs {
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<(PathBuf, EntryMeta)>> {
+        let mut cache = self.lock();
+        let fresh = cache.listings.get(dir).is_some_and(|listing| listing.checked.elapsed() < LISTING_TTL);
+        if !fresh {
+            self.queue(&mut cache, Request::List(dir.to_path_buf()), dir);
+        }
+        // Until the refresh arrives the last listing is better than an empty column
+        match cache.listings.get(dir).map(|listing| &listing.entries) {
+            Some(Ok(entries)) => Ok(entries.clone()),
+            Some(Err(e)) => Err(io::Error::other(e.clone())),
+            None => Err(still_loading(dir)),
+        }
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<EntryMeta> {
+        let mut cache = self.lock();
+        match cache.entries.get(path) {
+            Some((_, Some(meta))) => return Ok(*meta),
+            Some((checked, None)) if checked.elapsed() < LISTING_TTL => {
+                return Err(io::Error::new(io::ErrorKind::NotFound, format!("{} doesn't exist", path.display())));
+            },
+            _ => {},
+        }
+        self.queue(&mut cache, Request::Stat(path.to_path_buf()), path);
+        Err(still_loading(path))
+    }
+
+    fn read_file(&self, path: &Path, max_bytes: u64) -> Receiver<io::Result<Vec<u8>>> {
+        let (reply, receiver) = mpsc::channel();
+        let request = Request::Read { path: path.to_path_buf(), max_bytes, reply };
+        if let Err(mpsc::SendError(Request::Read { reply, .. })) = self.requests.send(request) {
+            let _ = reply.send(Err(io::Error::new(io::ErrorKind::NotConnected, "the SFTP session has ended")));
+        }
+        receiver
+    }
+
+    fn remote_label(&self) -> Option<String> {
+        let offline = self.lock().offline;
+        Some(if offline { format!("{} (offline)", self.target.label()) } else { self.target.label() })
+    }
+
+    fn invalidate(&self) {
+        // Stale rather than gone, so the panes keep their entries until the refresh arrives
+        let mut cache = self.lock();
+        let expired = Instant::now().checked_sub(LISTING_TTL).unwrap_or_else(Instant::now);
+        for listing in cache.listings.values_mut() {
+            listing.checked = expired;
+        }
+        cache.entries.retain(|_, (_, meta)| meta.is_some());
+    }
+}
+
+/// The session thread: runs requests until the `RemoteFs` is dropped
+struct Worker {
+    target: SftpTarget,
+    connection: Option<Connection>,
+    last_attempt: Instant,
+    cache: Arc<Mutex<Cache>>,
+}
+
+impl Worker {
+    fn run(mut self, receiver: Receiver<Request>) {
+        for request in receiver {
+            match request {
+                Request::List(dir) => {
+                    let listing = self.request(|sftp| {
+                        Ok(sftp.readdir(&dir)?
+                            .into_iter()
+                            .map(|(path, stat)| {
+                                // readdir doesn't follow links; stat them to tell links to directories
+                                let is_symlink = stat.file_type().is_symlink();
+                                let target = if is_symlink { sftp.stat(&path).unwrap_or(stat) } else { stat };
+                                let meta = entry_meta(&target, is_symlink);
+                                (path, meta)
+                            })
+                            .collect::<Vec<_>>())
+                    });
+                    let now = Instant::now();
+                    let mut cache = self.lock();
+                    cache.queued.remove(&dir);
+                    match listing {
+                        Ok(entries) => {
+                            cache.entries.extend(entries.iter().map(|(path, meta)| (path.clone(), (now, Some(*meta)))));
+                            cache.listings.insert(dir, Listing { checked: now, entries: Ok(entries) });
+                        },
+                        // Offline: keep showing the last listing, and try again after the TTL
+                        Err(e) => match cache.listings.get_mut(&dir) {
+                            Some(listing) if listing.entries.is_ok() => listing.checked = now,
+                            _ => {
+                                cache.listings.insert(dir, Listing { checked: now, entries: Err(e.to_string()) });
+                            },
+                        },
+                    }
+                },
+                Request::Stat(path) => {
+                    let meta = self.request(|sftp| {
+                        let is_symlink = sftp.lstat(&path)?.file_type().is_symlink();
+                        Ok(entry_meta(&sftp.stat(&path)?, is_symlink))
+                    });
+                    let mut cache = self.lock();
+                    cache.queued.remove(&path);
+                    cache.entries.insert(path, (Instant::now(), meta.ok()));
+                },
+                Request::Read { path, max_bytes, reply } => {
+                    let read = self.request(|sftp| sftp.open(&path)).and_then(|file| {
+                        let mut bytes = Vec::new();
+                        file.take(max_bytes).read_to_end(&mut bytes)?;
+                        Ok(bytes)
+                    });
+                    let _ = reply.send(read);
+                },
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Cache> {
+        lock(&self.cache)
+    }
+
+    /// Run `request` on the session, re-establishing it if it dropped
+    /// A session that sat idle may have been closed by the server, so a
+    /// session-level failure on an old connection gets one fresh try.
+    fn request<T>(&mut self, request: impl Fn(&Sftp) -> Result<T, ssh2::Error>) -> io::Result<T> {
+        let (mut reconnected, mut retry_now) = (false, false);
+        loop {
+            if self.connection.is_none() {
+                if !retry_now && self.last_attempt.elapsed() < RECONNECT_INTERVAL {
+                    return Err(io::Error::new(io::ErrorKind::NotConnected, format!("{} is offline", self.target.label())));
+                }
+                self.last_attempt = Instant::now();
+                tracing::info!(host = %self.target.label(), "Reconnecting SFTP session");
+                let connection = open(&self.target);
+                self.lock().offline = connection.is_err();
+                self.connection = Some(connection?);
+                reconnected = true;
+            }
+            match request(&self.connection.as_ref().expect("connected above").sftp) {
+                Err(e) if matches!(e.code(), ErrorCode::Session(_)) => {
+                    tracing::warn!(host = %self.target.label(), error = %e, "SFTP session dropped");
+                    self.connection = None;
+                    self.lock().offline = true;
+                    if reconnected {
+                        return Err(e.into());
+                    }
+                    retry_now = true;
+                },
+                result => return result.map_err(io::Error::from),
+            }
+        }
+    }
+}
+
+fn entry_meta(stat: &FileStat, is_symlink: bool) -> EntryMeta {
+    EntryMeta {
+        is_dir: stat.is_dir(),
+        is_symlink,
+        len: stat.size.unwrap_or(0),
+        modified: stat.mtime.map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+    }
+}
+
+/// Open, verify and authenticate a session to `target`
+fn open(target: &SftpTarget) -> io::Result<Connection> {
+    let address = (target.host.as_str(), target.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("can't resolve {}", target.host)))?;
+    let tcp = TcpStream::connect_timeout(&address, TIMEOUT)?;
+
+    let mut session = Session::new()?;
+    session.set_tcp_stream(tcp);
+    session.set_timeout(TIMEOUT.as_millis() as u32);
+    session.handshake()?;
+    verify_host_key(&session, target)?;
+    authenticate(&session, &target.user)?;
+    let sftp = session.sftp()?;
+    Ok(Connection { _session: session, sftp })
+}
+
+/// Refuse hosts that aren't in ~/.ssh/known_hosts or whose key changed
+fn verify_host_key(session: &Session, target: &SftpTarget) -> io::Result<()> {
+    let denied = |message: String| io::Error::new(io::ErrorKind::PermissionDenied, message);
+    let (key, _) = session.host_key().ok_or_else(|| denied("the server sent no host key".to_string()))?;
+    let mut known_hosts = session.known_hosts()?;
+    let file = dirs::home_dir().unwrap_or_default().join(".ssh").join("known_hosts");
+    if known_hosts.read_file(&file, KnownHostFileKind::OpenSSH).is_err() {
+        return Err(denied(format!("can't read {}", file.display())));
+    }
+    match known_hosts.check_port(&target.host, target.port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::Mismatch => Err(denied(format!("the host key of {} has changed; refusing to connect", target.host))),
+        CheckResult::NotFound => Err(denied(format!("{} isn't in ~/.ssh/known_hosts (connect once with ssh to add it)", target.host))),
+        CheckResult::Failure => Err(denied(format!("couldn't check the host key of {}", target.host))),
+    }
+}
+
+/// Log in with the ssh agent, or a default key without a passphrase
+fn authenticate(session: &Session, user: &str) -> io::Result<()> {
+    if session.userauth_agent(user).is_ok() && session.authenticated() {
+        return Ok(());
+    }
+    let ssh_dir = dirs::home_dir().unwrap_or_default().join(".ssh");
+    for key in DEFAULT_KEYS.iter().map(|name| ssh_dir.join(name)).filter(|key| key.is_file()) {
+        if session.userauth_pubkey_file(user, None, &key, None).is_ok() && session.authenticated() {
+            return Ok(());
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        format!("no ssh-agent identity or key in ~/.ssh was accepted for {}", user),
+    ))
+}
+
+/// A connection being made in the background
+pub struct ConnectJob {
+    pub target: SftpTarget,
+    receiver: Receiver<io::Result<RemoteFs>>,
+}
+
+impl ConnectJob {
+    pub fn start(target: SftpTarget) -> Self {
+        let (tx, receiver) = mpsc::channel();
+        let worker_target = target.clone();
+        std::thread::spawn(move || {
+            let _ = tx.send(RemoteFs::connect(worker_target));
+        });
+        Self { target, receiver }
+    }
+
+    /// The connection or the reason it failed, once the attempt is over
+    pub fn poll(&self) -> Option<io::Result<RemoteFs>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(io::Error::other("the connection attempt stopped"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target() {
+        let target = SftpTarget::parse("alice@example.com:2222").unwrap();
+        assert_eq!((target.user.as_str(), target.host.as_str(), target.port), ("alice", "example.com", 2222));
+        assert_eq!(target.label(), "alice@example.com:2222");
+        assert_eq!(SftpTarget::parse(" bob@host ").unwrap().label(), "bob@host");
+        assert_eq!(SftpTarget::parse("bob@host:ssh"), None);
+        assert_eq!(SftpTarget::parse("@host"), None);
+        assert_eq!(SftpTarget::parse("bob@"), None);
+    }
+
+    #[test]
+    fn test_reads_come_from_the_cache_and_queue_refreshes() {
+        let (requests, queued) = mpsc::channel();
+        let remote = RemoteFs {
+            target: SftpTarget::parse("alice@example.com").unwrap(),
+            home: PathBuf::from("/home/alice"),
+            cache: Arc::default(),
+            requests,
+        };
+        let dir = Path::new("/home/alice");
+
+        // Nothing cached: the call returns at once and one listing is queued
+        assert_eq!(remote.read_dir(dir).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+        assert_eq!(remote.read_dir(dir).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+        assert!(matches!(queued.try_iter().collect::<Vec<_>>().as_slice(), [Request::List(d)] if d == dir));
+
+        // What the worker stores is served without another request
+        let file = dir.join("notes.txt");
+        let meta = EntryMeta { len: 5, ..EntryMeta::default() };
+        {
+            let mut cache = remote.lock();
+            cache.queued.clear();
+            cache.entries.insert(file.clone(), (Instant::now(), Some(meta)));
+            cache.entries.insert(dir.join("gone"), (Instant::now(), None));
+            cache.listings.insert(dir.to_path_buf(), Listing { checked: Instant::now(), entries: Ok(vec![(file.clone(), meta)]) });
+        }
+        assert_eq!(remote.read_dir(dir).unwrap(), vec![(file.clone(), meta)]);
+        assert_eq!(remote.metadata(&file).unwrap(), meta);
+        assert_eq!(remote.metadata(&dir.join("gone")).unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert!(queued.try_recv().is_err());
+
+        // Invalidating keeps the entries on screen while the refresh is queued
+        remote.invalidate();
+        assert_eq!(remote.read_dir(dir).unwrap().len(), 1);
+        assert!(matches!(queued.try_recv(), Ok(Request::List(_))));
+    }
+}
@@ -0,0 +1,149 @@
+//! Batch rename of marked entries
+//!
+//! New names come either from a regex find/replace over the file names or
+//! from a list of names edited in the built-in editor (one per line, like
+//! vidir). The whole plan is checked for collisions before anything is
+//! renamed, and applied in two phases through temporary names so swaps
+//! (a -> b, b -> a) work.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// (current path, new path) for every entry whose name changes
+pub type RenamePlan = Vec<(PathBuf, PathBuf)>;
+
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap_or_default().to_string_lossy().to_string()
+}
+
+/// Rename plan replacing every match of the regex `find` with `replace` ($1 for groups)
+pub fn pattern_plan(paths: &[PathBuf], find: &str, replace: &str) -> Result<RenamePlan, String> {
+    if find.is_empty() {
+        return Ok(Vec::new());
+    }
+    let regex = regex::Regex::new(find).map_err(|e| format!("invalid pattern: {}", e))?;
+    let names = paths.iter()
+        .map(|path| regex.replace_all(&file_name(path), replace).to_string())
+        .collect::<Vec<_>>();
+    plan_from_names(paths, &names)
+}
+
+/// Rename plan from the edited name list: line N is the new name of `paths[N]`
+pub fn edited_plan(paths: &[PathBuf], text: &str) -> Result<RenamePlan, String> {
+    let names: Vec<String> = text.trim_end_matches('\n').lines().map(|l| l.trim_end_matches('\r').to_string()).collect();
+    if names.len() != paths.len() {
+        return Err(format!("expected {} names but found {} lines (don't add or remove lines)", paths.len(), names.len()));
+    }
+    plan_from_names(paths, &names)
+}
+
+/// Text for the editor: one current name per line
+pub fn name_list(paths: &[PathBuf]) -> String {
+    paths.iter().map(|p| file_name(p) + "\n").collect()
+}
+
+fn plan_from_names(paths: &[PathBuf], names: &[String]) -> Result<RenamePlan, String> {
+    let mut plan = Vec::new();
+    for (path, name) in paths.iter().zip(names) {
+        if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+            return Err(format!("'{}' is not a valid name (for '{}')", name, file_name(path)));
+        }
+        if *name != file_name(path) {
+            plan.push((path.clone(), path.with_file_name(name)));
+        }
+    }
+    check_plan(&plan)?;
+    Ok(plan)
+}
+
+/// Reject plans where two entries get the same name or a new name is already taken
+pub fn check_plan(plan: &RenamePlan) -> Result<(), String> {
+    let mut targets: HashMap<&Path, &Path> = HashMap::new();
+    for (from, to) in plan {
+        if let Some(other) = targets.insert(to, from) {
+            return Err(format!("both '{}' and '{}' would be renamed to '{}'", file_name(other), file_name(from), file_name(to)));
+        }
+    }
+    for (from, to) in plan {
+        // Taken unless the entry holding the name is itself being renamed away
+        let freed = plan.iter().any(|(source, _)| source == to);
+        if to.symlink_metadata().is_ok() && !freed {
+            return Err(format!("'{}' already exists (renaming '{}')", file_name(to), file_name(from)));
+        }
+    }
+    Ok(())
+}
+
+/// Rename everything in `plan`, rolling back on failure; returns the number renamed
+pub fn apply_plan(plan: &RenamePlan) -> Result<usize, String> {
+    check_plan(plan)?;
+    let temp_path = |index: usize, from: &Path| {
+        from.with_file_name(format!(".senterm-rename-{}-{}", std::process::id(), index))
+    };
+
+    // Phase 1: move every source out of the way
+    for (index, (from, _)) in plan.iter().enumerate() {
+        if let Err(e) = std::fs::rename(from, temp_path(index, from)) {
+            for (undo, (from, _)) in plan.iter().enumerate().take(index) {
+                let _ = std::fs::rename(temp_path(undo, from), from);
+            }
+            return Err(format!("renaming '{}' failed: {}", file_name(from), e));
+        }
+    }
+    // Phase 2: move them to their new names
+    for (index, (from, to)) in plan.iter().enumerate() {
+        if let Err(e) = std::fs::rename(temp_path(index, from), to) {
+            for (undo, (from, to)) in plan.iter().enumerate() {
+                let moved = if undo < index { to.clone() } else { temp_path(undo, from) };
+                let _ = std::fs::rename(moved, from);
+            }
+            return Err(format!("renaming '{}' to '{}' failed: {}", file_name(from), file_name(to), e));
+        }
+    }
+    Ok(plan.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn touch(dir: &Path, names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(|name| {
+            let path = dir.join(name);
+            std::fs::write(&path, name).unwrap();
+            path
+        }).collect()
+    }
+
+    #[test]
+    fn test_pattern_plan() {
+        let temp = tempdir().unwrap();
+        let paths = touch(temp.path(), &["IMG_001.jpg", "IMG_002.jpg", "notes.txt"]);
+        let plan = pattern_plan(&paths, r"IMG_(\d+)", "photo-$1").unwrap();
+        assert_eq!(plan, vec![
+            (paths[0].clone(), temp.path().join("photo-001.jpg")),
+            (paths[1].clone(), temp.path().join("photo-002.jpg")),
+        ]);
+        assert!(pattern_plan(&paths, "(", "x").is_err());
+        // Every name collapsing to the same one is a collision
+        assert!(pattern_plan(&paths, r".*\.jpg", "same.jpg").unwrap_err().contains("both"));
+        // So is a name that's already taken by an entry that isn't renamed
+        assert!(pattern_plan(&paths, "IMG_001.jpg", "notes.txt").unwrap_err().contains("already exists"));
+    }
+
+    #[test]
+    fn test_edited_plan_swaps_names() {
+        let temp = tempdir().unwrap();
+        let paths = touch(temp.path(), &["a", "b", "c"]);
+        assert_eq!(name_list(&paths), "a\nb\nc\n");
+        assert!(edited_plan(&paths, "b\na\n").unwrap_err().contains("expected 3"));
+        assert!(edited_plan(&paths, "b\n\nc\n").is_err());
+
+        let plan = edited_plan(&paths, "b\na\nc\n").unwrap();
+        assert_eq!(apply_plan(&plan).unwrap(), 2);
+        assert_eq!(std::fs::read_to_string(temp.path().join("a")).unwrap(), "b");
+        assert_eq!(std::fs::read_to_string(temp.path().join("b")).unwrap(), "a");
+        assert_eq!(std::fs::read_dir(temp.path()).unwrap().count(), 3);
+    }
+}
@@ -0,0 +1,193 @@
+//! Fuzzy filename search
+//!
+//! A background walker descends from a root directory, scores every entry
+//! name against the query and streams the best matches back over a channel.
+//! Each keystroke cancels the previous walk and starts a new one.
+
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::job::BackgroundJob;
+
+/// Maximum number of results kept
+pub const MAX_RESULTS: usize = 50;
+
+/// A matching path and its fuzzy score
+pub type ScoredPath = (PathBuf, i64);
+
+/// Score `candidate` against `query` as a case-insensitive subsequence match
+/// Returns None if the query characters don't all appear in order.
+/// Consecutive matches and matches at word starts score higher.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    if query.is_empty() {
+        return None;
+    }
+
+    let candidate: Vec<char> = candidate.chars().collect();
+    let mut score = 0i64;
+    let mut pos = 0usize;
+    let mut prev_match: Option<usize> = None;
+
+    for q in query.chars().flat_map(char::to_lowercase) {
+        let found = (pos..candidate.len())
+            .find(|&i| candidate[i].to_lowercase().eq(std::iter::once(q)))?;
+
+        score += 10;
+        if prev_match.is_some_and(|prev| prev + 1 == found) {
+            score += 15; // contiguous bonus
+        }
+        let at_word_start = found == 0
+            || matches!(candidate[found - 1], '_' | '-' | '.' | ' ' | '/');
+        if at_word_start {
+            score += 10;
+        }
+        // Penalise gaps between matched characters
+        score -= (found - pos) as i64;
+
+        prev_match = Some(found);
+        pos = found + 1;
+    }
+
+    // Prefer shorter names when everything else is equal
+    score -= (candidate.len() / 8) as i64;
+    Some(score)
+}
+
+/// A fuzzy search running on a background thread; dropping it stops the walk
+pub struct SearchJob(BackgroundJob<Vec<ScoredPath>, Vec<ScoredPath>>);
+
+impl SearchJob {
+    /// Walk `root` up to `max_depth` levels, matching entry names against `query`
+    pub fn start(root: PathBuf, query: String, max_depth: usize, show_hidden: bool) -> Self {
+        Self(BackgroundJob::spawn(move |cancel, reporter| {
+            walk(&root, &query, max_depth, show_hidden, cancel, &mut |top| reporter.report(|| top.to_vec()))
+        }))
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.0.is_done()
+    }
+
+    /// Latest results since the last poll, if any
+    pub fn poll(&mut self) -> Option<Vec<ScoredPath>> {
+        let (latest, results) = self.0.poll();
+        results.or(latest)
+    }
+}
+
+/// Breadth-first walk keeping the best `MAX_RESULTS` matches (highest score first)
+fn walk(
+    root: &Path,
+    query: &str,
+    max_depth: usize,
+    show_hidden: bool,
+    cancel: &AtomicBool,
+    on_update: &mut dyn FnMut(&[ScoredPath]),
+) -> Vec<ScoredPath> {
+    let mut top: Vec<ScoredPath> = Vec::new();
+    let mut queue = std::collections::VecDeque::from([(root.to_path_buf(), 0usize)]);
+
+    while let Some((dir, depth)) = queue.pop_front() {
+        let Ok(read_dir) = std::fs::read_dir(&dir) else { continue };
+        let mut changed = false;
+
+        for entry in read_dir.flatten() {
+            if cancel.load(Ordering::Relaxed) {
+                return top;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !show_hidden && name.starts_with('.') {
+                continue;
+            }
+            let path = entry.path();
+
+            if let Some(score) = fuzzy_score(query, &name) {
+                let worst = top.last().map(|(_, s)| *s);
+                if top.len() < MAX_RESULTS || worst.is_some_and(|w| score > w) {
+                    top.push((path.clone(), score));
+                    // Stable sort keeps walk order (shallower first) for equal scores
+                    top.sort_by_key(|(_, s)| Reverse(*s));
+                    top.truncate(MAX_RESULTS);
+                    changed = true;
+                }
+            }
+
+            // Don't follow symlinked directories (avoids cycles)
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            if is_dir && depth < max_depth {
+                queue.push_back((path, depth + 1));
+            }
+        }
+
+        if changed {
+            on_update(&top);
+        }
+    }
+
+    top
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_fuzzy_score_subsequence() {
+        assert!(fuzzy_score("mdr", "main_driver.rs").is_some());
+        assert!(fuzzy_score("MAIN", "main.rs").is_some());
+        assert!(fuzzy_score("xyz", "main.rs").is_none());
+        // Order matters
+        assert!(fuzzy_score("nm", "main").is_none());
+        assert!(fuzzy_score("", "main").is_none());
+    }
+
+    #[test]
+    fn test_fuzzy_score_prefers_contiguous_and_word_starts() {
+        let contiguous = fuzzy_score("conf", "config.toml").unwrap();
+        let scattered = fuzzy_score("conf", "cargo_on_fire").unwrap();
+        assert!(contiguous > scattered);
+
+        let word_start = fuzzy_score("fm", "file_manager.rs").unwrap();
+        let mid_word = fuzzy_score("fm", "xfxm.rs").unwrap();
+        assert!(word_start > mid_word);
+    }
+
+    #[test]
+    fn test_search_job_finds_nested_matches() {
+        let temp = tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        std::fs::create_dir_all(root.join("src/fs")).unwrap();
+        std::fs::write(root.join("src/fs/watcher.rs"), "").unwrap();
+        std::fs::write(root.join("readme.md"), "").unwrap();
+        std::fs::write(root.join(".watch_hidden"), "").unwrap();
+
+        let mut job = SearchJob::start(root.clone(), "watch".to_string(), 5, false);
+        let mut results = Vec::new();
+        while !job.is_done() {
+            if let Some(r) = job.poll() {
+                results = r;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, root.join("src/fs/watcher.rs"));
+    }
+
+    #[test]
+    fn test_walk_respects_depth_and_cancel() {
+        let temp = tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        std::fs::create_dir_all(root.join("a/b")).unwrap();
+        std::fs::write(root.join("a/b/deep.txt"), "").unwrap();
+
+        let cancel = AtomicBool::new(false);
+        assert!(walk(&root, "deep", 1, true, &cancel, &mut |_| {}).is_empty());
+        assert_eq!(walk(&root, "deep", 2, true, &cancel, &mut |_| {}).len(), 1);
+
+        cancel.store(true, Ordering::Relaxed);
+        assert!(walk(&root, "deep", 2, true, &cancel, &mut |_| {}).is_empty());
+    }
+}
@@ -0,0 +1,137 @@
+//! Per-item results for file operations
+//!
+//! Batch operations (copy/delete/rename/chmod) record the outcome of every
+//! item so the UI can show a summary popup once the batch is finished.
+
+use std::path::{Path, PathBuf};
+
+/// Outcome of a single item in an operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItemOutcome {
+    Ok,
+    Skipped(String),
+    Failed(String),
+}
+
+/// Result for one item of an operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemResult {
+    pub path: PathBuf,
+    pub outcome: ItemOutcome,
+}
+
+/// Structured result of a (possibly batch) file operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationSummary {
+    /// Operation name shown to the user (e.g. "Delete", "Paste")
+    pub operation: String,
+    pub items: Vec<ItemResult>,
+    /// (source, new path) of every item copied or moved, so the operation can be undone
+    pub destinations: Vec<(PathBuf, PathBuf)>,
+}
+
+impl OperationSummary {
+    pub fn new(operation: &str) -> Self {
+        Self {
+            operation: operation.to_string(),
+            items: Vec::new(),
+            destinations: Vec::new(),
+        }
+    }
+
+    /// Record the result of an item operation
+    pub fn record<E: std::fmt::Display>(&mut self, path: &Path, result: Result<(), E>) {
+        let outcome = match result {
+            Ok(()) => ItemOutcome::Ok,
+            Err(e) => ItemOutcome::Failed(e.to_string()),
+        };
+        self.items.push(ItemResult { path: path.to_path_buf(), outcome });
+    }
+
+    /// Record an item that was intentionally skipped
+    pub fn skip(&mut self, path: &Path, reason: &str) {
+        self.items.push(ItemResult {
+            path: path.to_path_buf(),
+            outcome: ItemOutcome::Skipped(reason.to_string()),
+        });
+    }
+
+    pub fn succeeded(&self) -> usize {
+        self.items.iter().filter(|i| i.outcome == ItemOutcome::Ok).count()
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.items.iter().filter(|i| matches!(i.outcome, ItemOutcome::Skipped(_))).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.items.iter().filter(|i| matches!(i.outcome, ItemOutcome::Failed(_))).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Batches (more than one item) get a summary popup; single items only a status line
+    pub fn is_batch(&self) -> bool {
+        self.items.len() > 1
+    }
+
+    /// One-line status message for the footer
+    pub fn status_line(&self) -> String {
+        if let [item] = self.items.as_slice() {
+            let name = item.path.file_name()
+                .unwrap_or(item.path.as_os_str())
+                .to_string_lossy();
+            return match &item.outcome {
+                ItemOutcome::Ok => format!("{}: '{}' done", self.operation, name),
+                ItemOutcome::Skipped(reason) => format!("{}: '{}' skipped ({})", self.operation, name, reason),
+                ItemOutcome::Failed(reason) => format!("{} failed for '{}': {}", self.operation, name, reason),
+            };
+        }
+
+        format!(
+            "{}: {} ok, {} skipped, {} failed",
+            self.operation,
+            self.succeeded(),
+            self.skipped(),
+            self.failed()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_mixed_results() {
+        let mut summary = OperationSummary::new("Delete");
+        summary.record::<std::io::Error>(Path::new("/tmp/a.txt"), Ok(()));
+        summary.record(
+            Path::new("/tmp/b.txt"),
+            Err(std::io::Error::new(std::io::ErrorKind::PermissionDenied, "permission denied")),
+        );
+        summary.skip(Path::new("/tmp/c.txt"), "already exists");
+        summary.record::<std::io::Error>(Path::new("/tmp/d.txt"), Ok(()));
+
+        assert!(summary.is_batch());
+        assert_eq!(summary.succeeded(), 2);
+        assert_eq!(summary.failed(), 1);
+        assert_eq!(summary.skipped(), 1);
+        assert_eq!(summary.items[1].outcome, ItemOutcome::Failed("permission denied".to_string()));
+        assert_eq!(summary.status_line(), "Delete: 2 ok, 1 skipped, 1 failed");
+    }
+
+    #[test]
+    fn test_summary_single_item_status() {
+        let mut summary = OperationSummary::new("Paste");
+        summary.record::<std::io::Error>(Path::new("/tmp/a.txt"), Ok(()));
+        assert!(!summary.is_batch());
+        assert_eq!(summary.status_line(), "Paste: 'a.txt' done");
+
+        let mut failed = OperationSummary::new("Delete");
+        failed.record(Path::new("/tmp/b.txt"), Err("not found"));
+        assert_eq!(failed.status_line(), "Delete failed for 'b.txt': not found");
+    }
+}
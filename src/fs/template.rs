@@ -0,0 +1,100 @@
+//! File and folder templates (~/.config/senterm/templates)
+//!
+//! Every entry of the template directory is a template: a file is copied as
+//! boilerplate, a folder is copied recursively as project scaffolding.
+
+use std::path::{Path, PathBuf};
+
+/// A file or folder that can be copied into a directory under a new name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pub name: String,
+    pub path: PathBuf,
+    pub is_dir: bool,
+}
+
+/// Template directory, created on first use
+pub fn template_dir() -> Option<PathBuf> {
+    let dir = dirs::config_dir()?.join("senterm").join("templates");
+    std::fs::create_dir_all(&dir).ok()?;
+    Some(dir)
+}
+
+/// Templates in `dir`, folders first, then by name (dotfiles are skipped)
+pub fn list_templates(dir: &Path) -> Vec<Template> {
+    let Ok(read_dir) = std::fs::read_dir(dir) else { return Vec::new() };
+    let mut templates: Vec<Template> = read_dir
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                return None;
+            }
+            let path = entry.path();
+            Some(Template { name, is_dir: path.is_dir(), path })
+        })
+        .collect();
+    templates.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    templates
+}
+
+/// Copy `template` into `target_dir` as `name`; fails if `name` already exists there
+pub fn instantiate(template: &Template, target_dir: &Path, name: &str) -> std::io::Result<PathBuf> {
+    if name.is_empty() || name.contains('/') {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid name"));
+    }
+    let target = target_dir.join(name);
+    if target.symlink_metadata().is_ok() {
+        return Err(std::io::Error::new(std::io::ErrorKind::AlreadyExists, format!("'{}' already exists", name)));
+    }
+
+    if !template.is_dir {
+        std::fs::copy(&template.path, &target)?;
+        return Ok(target);
+    }
+    for entry in walkdir::WalkDir::new(&template.path) {
+        let entry = entry.map_err(std::io::Error::other)?;
+        let relative = entry.path().strip_prefix(&template.path).map_err(std::io::Error::other)?;
+        let dest = target.join(relative);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&dest)?;
+        } else {
+            std::fs::copy(entry.path(), &dest)?;
+        }
+    }
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_list_and_instantiate_templates() {
+        let temp = tempdir().unwrap();
+        let templates_dir = temp.path().join("templates");
+        std::fs::create_dir_all(templates_dir.join("rust-crate/src")).unwrap();
+        std::fs::write(templates_dir.join("rust-crate/Cargo.toml"), "[package]\n").unwrap();
+        std::fs::write(templates_dir.join("rust-crate/src/main.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(templates_dir.join("README.md"), "# Title\n").unwrap();
+        std::fs::write(templates_dir.join(".DS_Store"), "").unwrap();
+
+        let templates = list_templates(&templates_dir);
+        let names: Vec<&str> = templates.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["rust-crate", "README.md"]);
+
+        let project = temp.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        let created = instantiate(&templates[1], &project, "NOTES.md").unwrap();
+        assert_eq!(std::fs::read_to_string(created).unwrap(), "# Title\n");
+
+        let created = instantiate(&templates[0], &project, "app").unwrap();
+        assert_eq!(std::fs::read_to_string(created.join("src/main.rs")).unwrap(), "fn main() {}\n");
+        assert!(created.join("Cargo.toml").is_file());
+
+        // Existing names are never overwritten
+        assert!(instantiate(&templates[1], &project, "NOTES.md").is_err());
+        assert!(instantiate(&templates[1], &project, "").is_err());
+    }
+}
@@ -0,0 +1,362 @@
+//! Background paste with progress reporting
+//!
+//! Copies run on a worker thread and report progress through a channel so the
+//! TUI stays responsive. `App::on_tick` polls the job; Esc cancels it between
+//! chunks of a file, removing that file's partial copy and leaving anything
+//! already copied in place.
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::summary::OperationSummary;
+use super::ClipboardOperation;
+
+/// Minimum interval between progress updates sent to the UI
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Bytes copied between cancel checks
+const COPY_CHUNK_BYTES: usize = 1024 * 1024;
+
+/// Snapshot of a running transfer
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransferProgress {
+    pub copied_bytes: u64,
+    pub total_bytes: u64,
+    /// Name of the file currently being copied
+    pub current: String,
+}
+
+impl TransferProgress {
+    pub fn percent(&self) -> u16 {
+        if self.total_bytes == 0 {
+            return 0;
+        }
+        ((self.copied_bytes.min(self.total_bytes) * 100) / self.total_bytes) as u16
+    }
+}
+
+enum TransferEvent {
+    Progress(TransferProgress),
+    Finished(OperationSummary),
+}
+
+/// A paste running on a background thread
+pub struct PasteJob {
+    pub operation: ClipboardOperation,
+    pub progress: TransferProgress,
+    cancel: Arc<AtomicBool>,
+    receiver: Receiver<TransferEvent>,
+}
+
+impl PasteJob {
+    /// Spawn a worker that pastes `src_paths` into `dest_dir`
+    pub fn start(src_paths: Vec<PathBuf>, operation: ClipboardOperation, dest_dir: PathBuf) -> Self {
+        let (tx, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let worker_cancel = Arc::clone(&cancel);
+
+        std::thread::spawn(move || {
+            let mut last_sent = Instant::now();
+            let summary = run_paste(&src_paths, operation, &dest_dir, &worker_cancel, &mut |progress| {
+                if last_sent.elapsed() >= PROGRESS_INTERVAL {
+                    last_sent = Instant::now();
+                    let _ = tx.send(TransferEvent::Progress(progress));
+                }
+            });
+            let _ = tx.send(TransferEvent::Finished(summary));
+        });
+
+        Self {
+            operation,
+            progress: TransferProgress::default(),
+            cancel,
+            receiver,
+        }
+    }
+
+    /// Ask the worker to stop at the next chunk
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelling(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Drain pending events; returns the summary once the worker is done
+    pub fn poll(&mut self) -> Option<OperationSummary> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(TransferEvent::Progress(progress)) => self.progress = progress,
+                Ok(TransferEvent::Finished(summary)) => return Some(summary),
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => {
+                    // Worker died without reporting (panic)
+                    let mut summary = OperationSummary::new("Paste");
+                    summary.record(Path::new(&self.progress.current), Err("paste worker stopped unexpectedly"));
+                    return Some(summary);
+                }
+            }
+        }
+    }
+
+    /// Block until the worker is done
+    #[cfg(test)]
+    pub fn wait(mut self) -> OperationSummary {
+        loop {
+            if let Some(summary) = self.poll() {
+                return summary;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
+
+/// Paste every source into `dest_dir`, recording a per-item result
+fn run_paste(
+    src_paths: &[PathBuf],
+    operation: ClipboardOperation,
+    dest_dir: &Path,
+    cancel: &AtomicBool,
+    on_progress: &mut dyn FnMut(TransferProgress),
+) -> OperationSummary {
+    let mut summary = OperationSummary::new("Paste");
+
+    let total_bytes = match operation {
+        ClipboardOperation::Copy => src_paths.iter()
+            .map(|p| fs_extra::dir::get_size(p).unwrap_or(0))
+            .sum(),
+        // Moves are renames; there is nothing to measure
+        ClipboardOperation::Cut => 0,
+    };
+    let mut progress = TransferProgress { total_bytes, ..Default::default() };
+
+    for src_path in src_paths {
+        if cancel.load(Ordering::Relaxed) {
+            summary.skip(src_path, "cancelled");
+            continue;
+        }
+        let Some(dest_path) = unique_destination(src_path, dest_dir) else {
+            summary.skip(src_path, "no file name");
+            continue;
+        };
+
+        let result = match operation {
+            ClipboardOperation::Copy => copy_item(src_path, &dest_path, cancel, &mut progress, on_progress),
+            ClipboardOperation::Cut => fs::rename(src_path, &dest_path).map(|_| true),
+        };
+
+        match result {
+            Ok(true) => {
+                tracing::info!(?operation, from = ?src_path, to = ?dest_path, "Paste successful");
+                summary.record::<std::io::Error>(src_path, Ok(()));
+                summary.destinations.push((src_path.clone(), dest_path));
+            },
+            Ok(false) => summary.skip(src_path, "cancelled, files copied so far left in place"),
+            Err(e) => {
+                tracing::error!(?e, from = ?src_path, "Paste failed");
+                summary.record(src_path, Err(e));
+            }
+        }
+    }
+
+    summary
+}
+
+/// Destination path for `src_path` in `dest_dir`, adding a `_copy` suffix on collision
+fn unique_destination(src_path: &Path, dest_dir: &Path) -> Option<PathBuf> {
+    let file_name = src_path.file_name()?;
+    let dest_path = dest_dir.join(file_name);
+    if !dest_path.exists() {
+        return Some(dest_path);
+    }
+
+    let stem = src_path.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = src_path.extension().unwrap_or_default().to_string_lossy();
+    let new_name = if ext.is_empty() || src_path.is_dir() {
+        format!("{}_copy", src_path.file_name().unwrap_or_default().to_string_lossy())
+    } else {
+        format!("{}_copy.{}", stem, ext)
+    };
+    Some(dest_dir.join(new_name))
+}
+
+/// Copy a file or directory tree file-by-file
+/// Returns Ok(false) if cancelled before finishing
+fn copy_item(
+    src_path: &Path,
+    dest_path: &Path,
+    cancel: &AtomicBool,
+    progress: &mut TransferProgress,
+    on_progress: &mut dyn FnMut(TransferProgress),
+) -> Result<bool, std::io::Error> {
+    let to_io = |e: fs_extra::error::Error| std::io::Error::other(e.to_string());
+
+    if !src_path.is_dir() {
+        return copy_file(src_path, dest_path, cancel, progress, on_progress);
+    }
+
+    // Directories are listed depth-first with the root first
+    let content = fs_extra::dir::get_dir_content(src_path).map_err(to_io)?;
+    for dir in &content.directories {
+        let relative = Path::new(dir).strip_prefix(src_path).unwrap_or(Path::new(""));
+        fs::create_dir_all(dest_path.join(relative))?;
+    }
+
+    for file in &content.files {
+        if cancel.load(Ordering::Relaxed) {
+            return Ok(false);
+        }
+        let file = Path::new(file);
+        let relative = file.strip_prefix(src_path).unwrap_or(file);
+        if !copy_file(file, &dest_path.join(relative), cancel, progress, on_progress)? {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Copy one file a chunk at a time, checking `cancel` between chunks
+/// Returns Ok(false) if cancelled before the end; the partial copy is removed.
+fn copy_file(
+    src: &Path,
+    dest: &Path,
+    cancel: &AtomicBool,
+    progress: &mut TransferProgress,
+    on_progress: &mut dyn FnMut(TransferProgress),
+) -> Result<bool, std::io::Error> {
+    let base = progress.copied_bytes;
+    progress.current = src.file_name().unwrap_or_default().to_string_lossy().to_string();
+
+    let mut reader = fs::File::open(src)?;
+    let metadata = reader.metadata()?;
+    let mut writer = fs::OpenOptions::new().write(true).create_new(true).open(dest)?;
+    let mut buf = vec![0; COPY_CHUNK_BYTES];
+    let mut copied = 0u64;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        writer.write_all(&buf[..n])?;
+        copied += n as u64;
+        progress.copied_bytes = base + copied;
+        on_progress(progress.clone());
+        if cancel.load(Ordering::Relaxed) && copied < metadata.len() {
+            drop(writer);
+            let _ = fs::remove_file(dest);
+            return Ok(false);
+        }
+    }
+
+    fs::set_permissions(dest, metadata.permissions())?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_paste_job_copies_directory_tree() {
+        let temp = tempdir().unwrap();
+        let src = temp.path().join("src");
+        let dest = temp.path().join("dest");
+        fs::create_dir_all(src.join("nested/deeper")).unwrap();
+        fs::create_dir(&dest).unwrap();
+        fs::write(src.join("a.txt"), "hello").unwrap();
+        fs::write(src.join("nested/deeper/b.txt"), "world!").unwrap();
+
+        let job = PasteJob::start(vec![src.clone()], ClipboardOperation::Copy, dest.clone());
+        let summary = job.wait();
+
+        assert_eq!(summary.succeeded(), 1);
+        assert_eq!(fs::read_to_string(dest.join("src/a.txt")).unwrap(), "hello");
+        assert_eq!(fs::read_to_string(dest.join("src/nested/deeper/b.txt")).unwrap(), "world!");
+    }
+
+    #[test]
+    fn test_run_paste_reports_progress_and_collisions() {
+        let temp = tempdir().unwrap();
+        let file = temp.path().join("data.bin");
+        fs::write(&file, vec![0u8; 4096]).unwrap();
+
+        let cancel = AtomicBool::new(false);
+        let mut last = TransferProgress::default();
+        let summary = run_paste(
+            std::slice::from_ref(&file),
+            ClipboardOperation::Copy,
+            temp.path(),
+            &cancel,
+            &mut |p| last = p,
+        );
+
+        assert_eq!(summary.succeeded(), 1);
+        assert!(temp.path().join("data_copy.bin").exists());
+        assert_eq!(last.copied_bytes, 4096);
+        assert_eq!(last.total_bytes, 4096);
+        assert_eq!(last.percent(), 100);
+    }
+
+    #[test]
+    fn test_cancelled_paste_skips_remaining_items() {
+        let temp = tempdir().unwrap();
+        let dest = temp.path().join("dest");
+        fs::create_dir(&dest).unwrap();
+        let first = temp.path().join("first.txt");
+        let second = temp.path().join("second.txt");
+        fs::write(&first, "1").unwrap();
+        fs::write(&second, "2").unwrap();
+
+        let cancel = AtomicBool::new(false);
+        let summary = run_paste(
+            &[first.clone(), second.clone()],
+            ClipboardOperation::Copy,
+            &dest,
+            &cancel,
+            // Cancel as soon as the first file has been copied
+            &mut |_| cancel.store(true, Ordering::Relaxed),
+        );
+
+        assert_eq!(summary.succeeded(), 1);
+        assert_eq!(summary.skipped(), 1);
+        assert!(dest.join("first.txt").exists());
+        assert!(!dest.join("second.txt").exists());
+    }
+
+    #[test]
+    fn test_cancel_stops_a_large_file_between_chunks() {
+        let temp = tempdir().unwrap();
+        let dest = temp.path().join("dest");
+        fs::create_dir(&dest).unwrap();
+        let big = temp.path().join("big.bin");
+        fs::write(&big, vec![7u8; COPY_CHUNK_BYTES * 3]).unwrap();
+
+        let cancel = AtomicBool::new(false);
+        let mut updates = 0;
+        let summary = run_paste(
+            std::slice::from_ref(&big),
+            ClipboardOperation::Copy,
+            &dest,
+            &cancel,
+            // Cancel after the first chunk
+            &mut |_| {
+                updates += 1;
+                cancel.store(true, Ordering::Relaxed);
+            },
+        );
+
+        assert_eq!(updates, 1);
+        assert_eq!(summary.skipped(), 1);
+        assert!(!dest.join("big.bin").exists());
+    }
+}
@@ -0,0 +1,233 @@
+//! Trash (recycle bin) support
+//!
+//! Deleted items are moved into `~/.local/share/senterm/trash/<timestamp>/`
+//! together with a `manifest.json` recording their original paths, so the
+//! most recent deletion can be restored.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::summary::OperationSummary;
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// One trashed item
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashedItem {
+    /// Where the item lived before it was deleted
+    pub original: PathBuf,
+    /// File name inside the batch folder
+    pub stored_as: String,
+}
+
+/// Manifest of a single delete operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashManifest {
+    pub deleted_at: String,
+    pub items: Vec<TrashedItem>,
+}
+
+/// Trash directory (~/.local/share/senterm/trash)
+pub fn trash_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("senterm").join("trash"))
+}
+
+/// Move `paths` into a new timestamped folder under `trash_root`
+pub fn move_to_trash(trash_root: &Path, paths: &[PathBuf]) -> OperationSummary {
+    let mut summary = OperationSummary::new("Trash");
+    let now = chrono::Local::now();
+
+    // Timestamped names sort chronologically; add a suffix if two deletes share a millisecond
+    let base_name = now.format("%Y%m%d-%H%M%S%.3f").to_string();
+    let mut batch_dir = trash_root.join(&base_name);
+    let mut suffix = 1;
+    while batch_dir.exists() {
+        batch_dir = trash_root.join(format!("{}-{}", base_name, suffix));
+        suffix += 1;
+    }
+
+    if let Err(e) = fs::create_dir_all(&batch_dir) {
+        for path in paths {
+            summary.record(path, Err(format!("cannot create trash folder: {}", e)));
+        }
+        return summary;
+    }
+
+    let mut manifest = TrashManifest {
+        deleted_at: now.to_rfc3339(),
+        items: Vec::new(),
+    };
+
+    for (idx, path) in paths.iter().enumerate() {
+        let Some(file_name) = path.file_name() else {
+            summary.skip(path, "no file name");
+            continue;
+        };
+        // Prefix with the index so equal names from different folders don't clash
+        let stored_as = format!("{}_{}", idx, file_name.to_string_lossy());
+        let result = move_path(path, &batch_dir.join(&stored_as));
+        if result.is_ok() {
+            tracing::info!(path = ?path, batch = ?batch_dir, "Moved to trash");
+            manifest.items.push(TrashedItem { original: path.clone(), stored_as });
+        }
+        summary.record(path, result);
+    }
+
+    if manifest.items.is_empty() {
+        let _ = fs::remove_dir_all(&batch_dir);
+    } else if let Err(e) = write_manifest(&batch_dir, &manifest) {
+        tracing::error!(?e, batch = ?batch_dir, "Failed to write trash manifest");
+    }
+
+    summary
+}
+
+/// Restore the most recent deletion in `trash_root`
+/// Returns None if there is nothing to restore
+pub fn restore_latest(trash_root: &Path) -> Option<OperationSummary> {
+    let (batch_dir, mut manifest) = latest_batch(trash_root)?;
+    let mut summary = OperationSummary::new("Restore");
+    let mut remaining = Vec::new();
+
+    for item in manifest.items.drain(..) {
+        if item.original.exists() {
+            summary.skip(&item.original, "a file with that name already exists");
+            remaining.push(item);
+            continue;
+        }
+
+        let result = match item.original.parent() {
+            Some(parent) => fs::create_dir_all(parent)
+                .and_then(|_| move_path(&batch_dir.join(&item.stored_as), &item.original)),
+            None => move_path(&batch_dir.join(&item.stored_as), &item.original),
+        };
+        if result.is_err() {
+            remaining.push(item.clone());
+        }
+        summary.record(&item.original, result);
+    }
+
+    // Keep whatever couldn't be restored so a later undo can retry
+    if remaining.is_empty() {
+        let _ = fs::remove_dir_all(&batch_dir);
+    } else {
+        manifest.items = remaining;
+        if let Err(e) = write_manifest(&batch_dir, &manifest) {
+            tracing::error!(?e, batch = ?batch_dir, "Failed to update trash manifest");
+        }
+    }
+
+    Some(summary)
+}
+
+/// Newest batch folder that has a readable manifest
+fn latest_batch(trash_root: &Path) -> Option<(PathBuf, TrashManifest)> {
+    let mut batches: Vec<PathBuf> = fs::read_dir(trash_root).ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.join(MANIFEST_FILE).is_file())
+        .collect();
+    batches.sort();
+
+    batches.into_iter().rev().find_map(|batch| {
+        let content = fs::read_to_string(batch.join(MANIFEST_FILE)).ok()?;
+        match serde_json::from_str::<TrashManifest>(&content) {
+            Ok(manifest) => Some((batch, manifest)),
+            Err(e) => {
+                tracing::warn!(?e, batch = ?batch, "Ignoring unreadable trash manifest");
+                None
+            }
+        }
+    })
+}
+
+fn write_manifest(batch_dir: &Path, manifest: &TrashManifest) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(manifest).map_err(std::io::Error::other)?;
+    fs::write(batch_dir.join(MANIFEST_FILE), json)
+}
+
+/// Move a file or directory, falling back to copy + remove across filesystems
+pub(crate) fn move_path(from: &Path, to: &Path) -> std::io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    if from.is_dir() {
+        let mut options = fs_extra::dir::CopyOptions::new();
+        options.copy_inside = true;
+        fs_extra::dir::copy(from, to, &options).map_err(|e| std::io::Error::other(e.to_string()))?;
+        fs::remove_dir_all(from)
+    } else {
+        fs::copy(from, to)?;
+        fs::remove_file(from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_trash_and_restore_roundtrip() {
+        let temp = tempdir().unwrap();
+        let trash_root = temp.path().join("trash");
+        let work = temp.path().join("work");
+        fs::create_dir_all(work.join("folder")).unwrap();
+        fs::write(work.join("file.txt"), "content").unwrap();
+        fs::write(work.join("folder/inner.txt"), "inner").unwrap();
+
+        let summary = move_to_trash(&trash_root, &[work.join("file.txt"), work.join("folder")]);
+        assert_eq!(summary.succeeded(), 2);
+        assert!(!work.join("file.txt").exists());
+        assert!(!work.join("folder").exists());
+
+        let (_, manifest) = latest_batch(&trash_root).unwrap();
+        assert_eq!(manifest.items.len(), 2);
+        assert_eq!(manifest.items[0].original, work.join("file.txt"));
+
+        let restored = restore_latest(&trash_root).unwrap();
+        assert_eq!(restored.succeeded(), 2);
+        assert_eq!(fs::read_to_string(work.join("file.txt")).unwrap(), "content");
+        assert_eq!(fs::read_to_string(work.join("folder/inner.txt")).unwrap(), "inner");
+
+        // Batch folder is gone once everything is restored
+        assert!(restore_latest(&trash_root).is_none());
+    }
+
+    #[test]
+    fn test_restore_latest_picks_most_recent_batch() {
+        let temp = tempdir().unwrap();
+        let trash_root = temp.path().join("trash");
+        fs::write(temp.path().join("first.txt"), "1").unwrap();
+        fs::write(temp.path().join("second.txt"), "2").unwrap();
+
+        move_to_trash(&trash_root, &[temp.path().join("first.txt")]);
+        move_to_trash(&trash_root, &[temp.path().join("second.txt")]);
+
+        restore_latest(&trash_root).unwrap();
+        assert!(temp.path().join("second.txt").exists());
+        assert!(!temp.path().join("first.txt").exists());
+
+        restore_latest(&trash_root).unwrap();
+        assert!(temp.path().join("first.txt").exists());
+    }
+
+    #[test]
+    fn test_restore_skips_existing_original() {
+        let temp = tempdir().unwrap();
+        let trash_root = temp.path().join("trash");
+        let file = temp.path().join("file.txt");
+        fs::write(&file, "old").unwrap();
+
+        move_to_trash(&trash_root, std::slice::from_ref(&file));
+        fs::write(&file, "new").unwrap();
+
+        let summary = restore_latest(&trash_root).unwrap();
+        assert_eq!(summary.skipped(), 1);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "new");
+        // Still in the trash for a later retry
+        assert!(latest_batch(&trash_root).is_some());
+    }
+}
@@ -6,9 +6,16 @@
 #![allow(dead_code)]
 
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::mpsc::{self, Receiver, TryRecvError};
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Changes are applied once the directory has been quiet this long
+const QUIET_PERIOD: Duration = Duration::from_millis(250);
+/// ...or at the latest this long after the first pending change, so a long burst
+/// (e.g. a large extraction) still refreshes periodically
+const MAX_DELAY: Duration = Duration::from_secs(1);
 
 /// File change event
 #[derive(Debug, Clone)]
@@ -28,6 +35,14 @@ pub enum FileChangeKind {
     Other,
 }
 
+impl FileChange {
+    /// Directories whose listing this change affects: the parent of the changed
+    /// entry, and the entry itself in case it is a watched directory
+    pub fn affected_dirs(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        self.path.parent().map(|p| p.to_path_buf()).into_iter().chain(std::iter::once(self.path.clone()))
+    }
+}
+
 impl From<&EventKind> for FileChangeKind {
     fn from(kind: &EventKind) -> Self {
         match kind {
@@ -49,6 +64,10 @@ pub struct FileWatcher {
     rx: Receiver<Result<Event, notify::Error>>,
     /// Currently watched paths
     watched_paths: Vec<PathBuf>,
+    /// The set last passed to `sync_paths`
+    requested_paths: Vec<PathBuf>,
+    /// Requested paths that couldn't be watched, retried once the requested set changes
+    failed_paths: HashSet<PathBuf>,
 }
 
 impl FileWatcher {
@@ -69,6 +88,8 @@ impl FileWatcher {
             watcher,
             rx,
             watched_paths: Vec::new(),
+            requested_paths: Vec::new(),
+            failed_paths: HashSet::new(),
         })
     }
     
@@ -164,6 +185,73 @@ impl FileWatcher {
     pub fn is_watching(&self, path: &PathBuf) -> bool {
         self.watched_paths.contains(path)
     }
+    
+    /// Number of directories currently being watched
+    pub fn watch_count(&self) -> usize {
+        self.watched_paths.len()
+    }
+    
+    /// Watch exactly the given set of paths, unwatching any that are no longer needed
+    /// A path that fails is warned about once and not retried until the set changes.
+    pub fn sync_paths(&mut self, paths: &[PathBuf]) {
+        if self.requested_paths != paths {
+            self.requested_paths = paths.to_vec();
+            self.failed_paths.clear();
+        }
+        let stale: Vec<PathBuf> = self.watched_paths
+            .iter()
+            .filter(|p| !paths.contains(p))
+            .cloned()
+            .collect();
+        for path in stale {
+            if self.unwatch(&path).is_err() {
+                // Path may have been removed from disk; drop it from the list anyway
+                self.watched_paths.retain(|p| p != &path);
+            }
+        }
+        
+        for path in paths {
+            if self.failed_paths.contains(path) {
+                continue;
+            }
+            if let Err(e) = self.watch(path) {
+                tracing::warn!("Failed to watch {}: {}", path.display(), e);
+                self.failed_paths.insert(path.clone());
+            }
+        }
+    }
+}
+
+/// Collects changed directories and releases them in one batch per burst
+#[derive(Debug, Default)]
+pub struct ChangeDebouncer {
+    pending: HashSet<PathBuf>,
+    first_change: Option<Instant>,
+    last_change: Option<Instant>,
+}
+
+impl ChangeDebouncer {
+    pub fn add(&mut self, dirs: impl IntoIterator<Item = PathBuf>, now: Instant) {
+        let mut dirs = dirs.into_iter().peekable();
+        if dirs.peek().is_none() {
+            return;
+        }
+        self.pending.extend(dirs);
+        self.first_change.get_or_insert(now);
+        self.last_change = Some(now);
+    }
+
+    /// The pending directories, once the burst settled (or ran for too long)
+    pub fn take_ready(&mut self, now: Instant) -> Option<Vec<PathBuf>> {
+        let first = self.first_change?;
+        let last = self.last_change.unwrap_or(first);
+        if now.duration_since(last) < QUIET_PERIOD && now.duration_since(first) < MAX_DELAY {
+            return None;
+        }
+        self.first_change = None;
+        self.last_change = None;
+        Some(self.pending.drain().collect())
+    }
 }
 
 impl Default for FileWatcher {
@@ -198,5 +286,92 @@ mod tests {
         assert!(result.is_ok());
         assert!(watcher.is_watching(&dir.path().to_path_buf()));
     }
+    
+    #[test]
+    fn test_watch_count_accounting() {
+        let dir_a = tempdir().unwrap();
+        let dir_b = tempdir().unwrap();
+        let path_a = dir_a.path().to_path_buf();
+        let path_b = dir_b.path().to_path_buf();
+        let mut watcher = FileWatcher::new().unwrap();
+        assert_eq!(watcher.watch_count(), 0);
+        
+        watcher.watch(&path_a).unwrap();
+        watcher.watch(&path_b).unwrap();
+        assert_eq!(watcher.watch_count(), 2);
+        
+        // Watching the same path twice must not be counted twice
+        watcher.watch(&path_a).unwrap();
+        assert_eq!(watcher.watch_count(), 2);
+        
+        watcher.unwatch(&path_a).unwrap();
+        assert_eq!(watcher.watch_count(), 1);
+        assert!(!watcher.is_watching(&path_a));
+        
+        watcher.unwatch_all();
+        assert_eq!(watcher.watch_count(), 0);
+    }
+    
+    #[test]
+    fn test_sync_paths() {
+        let dir_a = tempdir().unwrap();
+        let dir_b = tempdir().unwrap();
+        let path_a = dir_a.path().to_path_buf();
+        let path_b = dir_b.path().to_path_buf();
+        let mut watcher = FileWatcher::new().unwrap();
+        
+        watcher.sync_paths(&[path_a.clone(), path_b.clone()]);
+        assert_eq!(watcher.watch_count(), 2);
+        
+        // Dropping a path from the set unwatches it
+        watcher.sync_paths(std::slice::from_ref(&path_b));
+        assert_eq!(watcher.watch_count(), 1);
+        assert!(watcher.is_watching(&path_b));
+        assert!(!watcher.is_watching(&path_a));
+        
+        watcher.sync_paths(&[]);
+        assert_eq!(watcher.watch_count(), 0);
+    }
+
+    #[test]
+    fn test_sync_paths_retries_failures_only_when_the_set_changes() {
+        let dir = tempdir().unwrap();
+        let missing = dir.path().join("later");
+        let mut watcher = FileWatcher::new().unwrap();
+
+        watcher.sync_paths(std::slice::from_ref(&missing));
+        assert!(watcher.failed_paths.contains(&missing));
+
+        // The same set again doesn't retry, even once the path can be watched
+        std::fs::create_dir(&missing).unwrap();
+        watcher.sync_paths(std::slice::from_ref(&missing));
+        assert!(!watcher.is_watching(&missing));
+
+        watcher.sync_paths(&[missing.clone(), dir.path().to_path_buf()]);
+        assert!(watcher.is_watching(&missing));
+        assert!(watcher.failed_paths.is_empty());
+    }
+
+    #[test]
+    fn test_debouncer_batches_bursts() {
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+        let mut debouncer = ChangeDebouncer::default();
+        assert_eq!(debouncer.take_ready(start), None);
+
+        debouncer.add([PathBuf::from("/a"), PathBuf::from("/b")], start);
+        debouncer.add([PathBuf::from("/a")], ms(100));
+        assert_eq!(debouncer.take_ready(ms(200)), None);
+        let mut dirs = debouncer.take_ready(ms(400)).unwrap();
+        dirs.sort();
+        assert_eq!(dirs, vec![PathBuf::from("/a"), PathBuf::from("/b")]);
+        assert_eq!(debouncer.take_ready(ms(500)), None);
+
+        // A steady stream of changes is still flushed after MAX_DELAY
+        for n in 0..12 {
+            debouncer.add([PathBuf::from("/c")], ms(1000 + n * 100));
+        }
+        assert_eq!(debouncer.take_ready(ms(2000)), Some(vec![PathBuf::from("/c")]));
+    }
 }
 
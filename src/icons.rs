@@ -0,0 +1,133 @@
+//! File icons for the pane list
+//!
+//! The default icons are plain Unicode so they render everywhere. With
+//! `use_nerd_icons` on, files get devicon glyphs from a Nerd Font by special
+//! file name first, then by extension.
+
+use std::path::Path;
+
+/// Glyphs for well-known file names (matched case-insensitively)
+const NERD_NAMES: &[(&str, char)] = &[
+    ("cargo.toml", '\u{e7a8}'),
+    ("cargo.lock", '\u{e7a8}'),
+    ("dockerfile", '\u{f308}'),
+    ("makefile", '\u{e779}'),
+    (".gitignore", '\u{f1d3}'),
+    (".gitattributes", '\u{f1d3}'),
+    (".gitmodules", '\u{f1d3}'),
+    ("license", '\u{f02d}'),
+    ("readme.md", '\u{f48a}'),
+];
+
+/// Glyphs by extension (lowercase)
+const NERD_EXTENSIONS: &[(&str, char)] = &[
+    ("rs", '\u{e7a8}'),
+    ("py", '\u{e73c}'),
+    ("js", '\u{e74e}'),
+    ("mjs", '\u{e74e}'),
+    ("ts", '\u{e628}'),
+    ("tsx", '\u{e7ba}'),
+    ("jsx", '\u{e7ba}'),
+    ("go", '\u{e626}'),
+    ("c", '\u{e61e}'),
+    ("h", '\u{e61e}'),
+    ("cpp", '\u{e61d}'),
+    ("cc", '\u{e61d}'),
+    ("hpp", '\u{e61d}'),
+    ("java", '\u{e738}'),
+    ("lua", '\u{e620}'),
+    ("rb", '\u{e739}'),
+    ("php", '\u{e73d}'),
+    ("html", '\u{e736}'),
+    ("css", '\u{e749}'),
+    ("md", '\u{e73e}'),
+    ("json", '\u{e60b}'),
+    ("toml", '\u{e615}'),
+    ("yaml", '\u{e615}'),
+    ("yml", '\u{e615}'),
+    ("sh", '\u{f489}'),
+    ("bash", '\u{f489}'),
+    ("zsh", '\u{f489}'),
+    ("txt", '\u{f15c}'),
+    ("pdf", '\u{f1c1}'),
+    ("lock", '\u{f023}'),
+    ("zip", '\u{f410}'),
+    ("tar", '\u{f410}'),
+    ("gz", '\u{f410}'),
+    ("xz", '\u{f410}'),
+    ("7z", '\u{f410}'),
+    ("png", '\u{f1c5}'),
+    ("jpg", '\u{f1c5}'),
+    ("jpeg", '\u{f1c5}'),
+    ("gif", '\u{f1c5}'),
+    ("svg", '\u{f1c5}'),
+    ("webp", '\u{f1c5}'),
+    ("mp3", '\u{f1c7}'),
+    ("wav", '\u{f1c7}'),
+    ("flac", '\u{f1c7}'),
+    ("mp4", '\u{f1c8}'),
+    ("mkv", '\u{f1c8}'),
+    ("mov", '\u{f1c8}'),
+];
+
+const NERD_DIRECTORY: char = '\u{f07b}';
+const NERD_SYMLINK: char = '\u{f0c1}';
+const NERD_EXECUTABLE: char = '\u{f489}';
+const NERD_FILE: char = '\u{f15b}';
+
+/// Kind of pane entry an icon is chosen for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Directory,
+    Symlink,
+    Executable,
+    File,
+}
+
+/// Icon shown before an entry name, followed by a space
+pub fn icon_for(path: &Path, kind: EntryKind, nerd: bool) -> String {
+    if !nerd {
+        let icon = match kind {
+            EntryKind::Directory => "■",
+            EntryKind::Symlink => "↗",
+            EntryKind::Executable | EntryKind::File => "·",
+        };
+        return format!("{} ", icon);
+    }
+    format!("{} ", nerd_glyph(path, kind))
+}
+
+fn nerd_glyph(path: &Path, kind: EntryKind) -> char {
+    match kind {
+        EntryKind::Directory => return NERD_DIRECTORY,
+        EntryKind::Symlink => return NERD_SYMLINK,
+        EntryKind::Executable | EntryKind::File => {},
+    }
+    let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    NERD_NAMES.iter()
+        .find(|(n, _)| *n == name)
+        .or_else(|| NERD_EXTENSIONS.iter().find(|(e, _)| *e == ext))
+        .map(|(_, glyph)| *glyph)
+        .unwrap_or(if kind == EntryKind::Executable { NERD_EXECUTABLE } else { NERD_FILE })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_icon_for() {
+        // Plain icons by default
+        assert_eq!(icon_for(Path::new("src"), EntryKind::Directory, false), "■ ");
+        assert_eq!(icon_for(Path::new("main.rs"), EntryKind::File, false), "· ");
+
+        // Special names win over the extension, then the extension, then the kind
+        assert_eq!(icon_for(Path::new("Cargo.toml"), EntryKind::File, true), "\u{e7a8} ");
+        assert_eq!(icon_for(Path::new("config.TOML"), EntryKind::File, true), "\u{e615} ");
+        assert_eq!(icon_for(Path::new("build.sh"), EntryKind::Executable, true), "\u{f489} ");
+        assert_eq!(icon_for(Path::new("a.out"), EntryKind::Executable, true), "\u{f489} ");
+        assert_eq!(icon_for(Path::new("notes.xyz"), EntryKind::File, true), "\u{f15b} ");
+        assert_eq!(icon_for(Path::new("docs.md"), EntryKind::Symlink, true), "\u{f0c1} ");
+    }
+}
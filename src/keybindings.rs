@@ -0,0 +1,361 @@
+//! Configurable global keybindings
+//!
+//! The `[keybindings]` config section maps action names to key specs such as
+//! `"ctrl+t"`, `"F5"`, `"shift+tab"` or `"q"`. An action listed in the config
+//! replaces its default keys; unlisted actions keep the defaults below.
+//! Unknown action names and unparseable keys are reported as warnings.
+
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Actions that can be rebound
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    ToggleHelp,
+    OpenSettings,
+    ToggleSettings,
+    ToggleConsole,
+    AddPane,
+    RemovePane,
+    ToggleShell,
+    ToggleProcessViewer,
+    Quit,
+    PrevMode,
+    NextMode,
+    FocusNext,
+    FocusPrev,
+    PaneLeft,
+    PaneRight,
+    CommandPalette,
+    GrepSearch,
+    GrowSplit,
+    ShrinkSplit,
+}
+
+impl Action {
+    pub const ALL: [Action; 19] = [
+        Action::ToggleHelp,
+        Action::OpenSettings,
+        Action::ToggleSettings,
+        Action::ToggleConsole,
+        Action::AddPane,
+        Action::RemovePane,
+        Action::ToggleShell,
+        Action::ToggleProcessViewer,
+        Action::Quit,
+        Action::PrevMode,
+        Action::NextMode,
+        Action::FocusNext,
+        Action::FocusPrev,
+        Action::PaneLeft,
+        Action::PaneRight,
+        Action::CommandPalette,
+        Action::GrepSearch,
+        Action::GrowSplit,
+        Action::ShrinkSplit,
+    ];
+
+    /// Name used in the config file
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::ToggleHelp => "toggle_help",
+            Action::OpenSettings => "open_settings",
+            Action::ToggleSettings => "toggle_settings",
+            Action::ToggleConsole => "toggle_console",
+            Action::AddPane => "add_pane",
+            Action::RemovePane => "remove_pane",
+            Action::ToggleShell => "toggle_shell",
+            Action::ToggleProcessViewer => "toggle_process_viewer",
+            Action::Quit => "quit",
+            Action::PrevMode => "prev_mode",
+            Action::NextMode => "next_mode",
+            Action::FocusNext => "focus_next",
+            Action::FocusPrev => "focus_prev",
+            Action::PaneLeft => "pane_left",
+            Action::PaneRight => "pane_right",
+            Action::CommandPalette => "command_palette",
+            Action::GrepSearch => "grep_search",
+            Action::GrowSplit => "grow_split",
+            Action::ShrinkSplit => "shrink_split",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    /// Built-in keys (the behaviour before keybindings were configurable)
+    fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Action::ToggleHelp => &["ctrl+alt+shift+k"],
+            Action::OpenSettings => &["ctrl+alt+shift+h", "super+alt+shift+h"],
+            Action::ToggleSettings => &["F8"],
+            Action::ToggleConsole => &["F5", "ctrl+t", "ctrl+shift+t"],
+            Action::AddPane => &["F3"],
+            Action::RemovePane => &["F4"],
+            Action::ToggleShell => &["F12", "`"],
+            Action::ToggleProcessViewer => &["F9"],
+            Action::Quit => &["q"],
+            Action::PrevMode => &["["],
+            Action::NextMode => &["]"],
+            Action::FocusNext => &["tab"],
+            Action::FocusPrev => &["shift+tab"],
+            Action::PaneLeft => &["ctrl+left"],
+            Action::PaneRight => &["ctrl+right"],
+            Action::CommandPalette => &["ctrl+p"],
+            // Many terminals send Ctrl+/ as Ctrl+7 (0x1F)
+            Action::GrepSearch => &["ctrl+/", "ctrl+7"],
+            Action::GrowSplit => &["ctrl+shift+right"],
+            Action::ShrinkSplit => &["ctrl+shift+left"],
+        }
+    }
+}
+
+/// A key with modifiers, normalised so that specs and terminal events compare equal
+pub type KeySpec = (KeyCode, KeyModifiers);
+
+/// Normalise shift handling, which terminals report inconsistently:
+/// plain characters carry shift in their case, chords with ctrl/alt/super
+/// use a lowercase letter plus an explicit SHIFT, and Shift+Tab is BackTab.
+fn normalize(code: KeyCode, modifiers: KeyModifiers) -> KeySpec {
+    let chord = KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SUPER;
+    match code {
+        KeyCode::Char(c) if modifiers.intersects(chord) => {
+            let mut modifiers = modifiers;
+            if c.is_uppercase() {
+                modifiers |= KeyModifiers::SHIFT;
+            }
+            (KeyCode::Char(c.to_ascii_lowercase()), modifiers)
+        },
+        KeyCode::Char(c) => {
+            let c = if modifiers.contains(KeyModifiers::SHIFT) { c.to_ascii_uppercase() } else { c };
+            (KeyCode::Char(c), modifiers - KeyModifiers::SHIFT)
+        },
+        KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => (KeyCode::BackTab, modifiers - KeyModifiers::SHIFT),
+        KeyCode::BackTab => (KeyCode::BackTab, modifiers - KeyModifiers::SHIFT),
+        _ => (code, modifiers),
+    }
+}
+
+/// Parse a key spec such as "ctrl+t", "F5", "shift+tab" or "q"
+pub fn parse_key(spec: &str) -> Option<KeySpec> {
+    let spec = spec.trim();
+    // "+" on its own (or as the last part, "ctrl++") is the plus key
+    let (mods_part, key_part) = match spec.strip_suffix("++") {
+        Some(mods) => (mods, "+"),
+        None if spec == "+" => ("", "+"),
+        None => match spec.rsplit_once('+') {
+            Some((mods, key)) => (mods, key),
+            None => ("", spec),
+        },
+    };
+
+    let mut modifiers = KeyModifiers::empty();
+    for part in mods_part.split('+').filter(|p| !p.is_empty()) {
+        modifiers |= match part.to_lowercase().as_str() {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "alt" | "option" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            "super" | "cmd" | "meta" => KeyModifiers::SUPER,
+            _ => return None,
+        };
+    }
+
+    let lower = key_part.to_lowercase();
+    let code = match lower.as_str() {
+        "esc" | "escape" => KeyCode::Esc,
+        "enter" | "return" => KeyCode::Enter,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "space" => KeyCode::Char(' '),
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "backtick" => KeyCode::Char('`'),
+        _ => {
+            let mut chars = key_part.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                (Some('f' | 'F'), Some(_)) => KeyCode::F(lower[1..].parse().ok().filter(|n| (1..=24).contains(n))?),
+                _ => return None,
+            }
+        }
+    };
+
+    // Letter case in a chord doesn't imply shift ("Ctrl+T" is ctrl+t)
+    let chord = KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SUPER;
+    let code = match code {
+        KeyCode::Char(c) if modifiers.intersects(chord) => KeyCode::Char(c.to_ascii_lowercase()),
+        other => other,
+    };
+
+    Some(normalize(code, modifiers))
+}
+
+/// Human-readable key, e.g. "Ctrl+P", "F5" or "Shift+Tab"
+pub fn describe_key((code, modifiers): KeySpec) -> String {
+    // Shift+Tab is stored as BackTab
+    let (code, modifiers) = match code {
+        KeyCode::BackTab => (KeyCode::Tab, modifiers | KeyModifiers::SHIFT),
+        _ => (code, modifiers),
+    };
+    let mut parts: Vec<String> = [
+        (KeyModifiers::CONTROL, "Ctrl"),
+        (KeyModifiers::ALT, "Alt"),
+        (KeyModifiers::SUPER, "Super"),
+        (KeyModifiers::SHIFT, "Shift"),
+    ]
+    .into_iter()
+    .filter(|(m, _)| modifiers.contains(*m))
+    .map(|(_, name)| name.to_string())
+    .collect();
+
+    parts.push(match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) if !parts.is_empty() => c.to_ascii_uppercase().to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        KeyCode::PageUp => "PgUp".to_string(),
+        KeyCode::PageDown => "PgDn".to_string(),
+        other => format!("{:?}", other),
+    });
+    parts.join("+")
+}
+
+/// Lookup table from keys to actions
+pub struct KeyBindings {
+    keys: HashMap<KeySpec, Action>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self::from_config(&HashMap::new()).0
+    }
+}
+
+impl KeyBindings {
+    /// Build from the config section; returns the bindings and any warnings
+    pub fn from_config(config: &HashMap<String, Vec<String>>) -> (Self, Vec<String>) {
+        let mut warnings = Vec::new();
+        let mut keys = HashMap::new();
+
+        for action in Action::ALL {
+            if !config.contains_key(action.name()) {
+                for spec in action.default_keys() {
+                    if let Some(key) = parse_key(spec) {
+                        keys.insert(key, action);
+                    }
+                }
+            }
+        }
+
+        // Sorted so conflicts resolve the same way on every run
+        let mut entries: Vec<_> = config.iter().collect();
+        entries.sort();
+        for (name, specs) in entries {
+            let Some(action) = Action::from_name(name) else {
+                warnings.push(format!("Unknown keybinding action '{}'", name));
+                continue;
+            };
+            for spec in specs {
+                match parse_key(spec) {
+                    Some(key) => {
+                        keys.insert(key, action);
+                    },
+                    None => warnings.push(format!("Invalid key '{}' for '{}'", spec, name)),
+                }
+            }
+        }
+
+        (Self { keys }, warnings)
+    }
+
+    pub fn lookup(&self, key: &KeyEvent) -> Option<Action> {
+        self.keys.get(&normalize(key.code, key.modifiers)).copied()
+    }
+
+    /// Keys bound to `action`, described and sorted (for display)
+    pub fn keys_for(&self, action: Action) -> Vec<String> {
+        let mut keys: Vec<String> = self.keys.iter()
+            .filter(|(_, a)| **a == action)
+            .map(|(key, _)| describe_key(*key))
+            .collect();
+        keys.sort();
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_parse_key_specs() {
+        assert_eq!(parse_key("q"), Some((KeyCode::Char('q'), KeyModifiers::empty())));
+        assert_eq!(parse_key("F5"), Some((KeyCode::F(5), KeyModifiers::empty())));
+        assert_eq!(parse_key("Ctrl+T"), Some((KeyCode::Char('t'), KeyModifiers::CONTROL)));
+        assert_eq!(parse_key("shift+k"), Some((KeyCode::Char('K'), KeyModifiers::empty())));
+        assert_eq!(parse_key("shift+tab"), Some((KeyCode::BackTab, KeyModifiers::empty())));
+        assert_eq!(parse_key("ctrl++"), Some((KeyCode::Char('+'), KeyModifiers::CONTROL)));
+        assert_eq!(parse_key("hyper+x"), None);
+        assert_eq!(parse_key("F99"), None);
+        assert_eq!(parse_key("nope"), None);
+    }
+
+    #[test]
+    fn test_describe_key() {
+        assert_eq!(describe_key(parse_key("ctrl+p").unwrap()), "Ctrl+P");
+        assert_eq!(describe_key(parse_key("F5").unwrap()), "F5");
+        assert_eq!(describe_key(parse_key("shift+tab").unwrap()), "Shift+Tab");
+        assert_eq!(describe_key(parse_key(".").unwrap()), ".");
+        assert_eq!(describe_key(parse_key("B").unwrap()), "B");
+        assert_eq!(KeyBindings::default().keys_for(Action::ToggleShell), vec!["F12", "`"]);
+    }
+
+    #[test]
+    fn test_defaults_match_terminal_events() {
+        let bindings = KeyBindings::default();
+        let ctrl_alt = KeyModifiers::CONTROL | KeyModifiers::ALT;
+
+        assert_eq!(bindings.lookup(&event(KeyCode::Char('q'), KeyModifiers::empty())), Some(Action::Quit));
+        assert_eq!(bindings.lookup(&event(KeyCode::Char('t'), KeyModifiers::CONTROL)), Some(Action::ToggleConsole));
+        // Terminals report Ctrl+Alt+Shift+K either as 'K' or as 'k' with SHIFT
+        assert_eq!(bindings.lookup(&event(KeyCode::Char('K'), ctrl_alt)), Some(Action::ToggleHelp));
+        assert_eq!(bindings.lookup(&event(KeyCode::Char('k'), ctrl_alt | KeyModifiers::SHIFT)), Some(Action::ToggleHelp));
+        assert_eq!(bindings.lookup(&event(KeyCode::BackTab, KeyModifiers::SHIFT)), Some(Action::FocusPrev));
+        assert_eq!(bindings.lookup(&event(KeyCode::Char('Q'), KeyModifiers::SHIFT)), None);
+    }
+
+    #[test]
+    fn test_config_overrides_and_warnings() {
+        let config = HashMap::from([
+            ("quit".to_string(), vec!["ctrl+q".to_string()]),
+            ("add_pane".to_string(), vec!["bogus+key".to_string()]),
+            ("launch_rockets".to_string(), vec!["r".to_string()]),
+        ]);
+        let (bindings, warnings) = KeyBindings::from_config(&config);
+
+        assert_eq!(bindings.lookup(&event(KeyCode::Char('q'), KeyModifiers::CONTROL)), Some(Action::Quit));
+        // The configured keys replace the default 'q'
+        assert_eq!(bindings.lookup(&event(KeyCode::Char('q'), KeyModifiers::empty())), None);
+        // Other actions keep their defaults
+        assert_eq!(bindings.lookup(&event(KeyCode::F(5), KeyModifiers::empty())), Some(Action::ToggleConsole));
+
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings.iter().any(|w| w.contains("launch_rockets")));
+        assert!(warnings.iter().any(|w| w.contains("bogus+key")));
+    }
+}
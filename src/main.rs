@@ -18,10 +18,17 @@ mod logging;
 mod navigation;
 mod events;
 mod process;
+mod palette;
 mod plugin;
+mod tasks;
+mod keybindings;
+mod osc;
+mod copy_mode;
+mod icons;
 
 use app::App;
 use crate::app::AppMode;
+use crate::keybindings::Action;
 use ui::ui;
 
 #[tokio::main]
@@ -39,7 +46,9 @@ async fn main() -> Result<()> {
 
     // Create app
     let mut app = App::new();
+    app.plugins.dispatch_hook(&plugin::HookEvent::AppStarted);
     let res = run_app(&mut terminal, &mut app).await;
+    app.plugins.dispatch_hook(&plugin::HookEvent::AppExiting);
 
     // Restore terminal
     disable_raw_mode()?;
@@ -50,7 +59,8 @@ async fn main() -> Result<()> {
     )?;
     terminal.show_cursor()?;
 
-    // Save config before exiting
+    // Save config (including the open panes) before exiting
+    app.save_session();
     if let Err(e) = app.config.save() {
         eprintln!("Failed to save config: {}", e);
     }
@@ -62,6 +72,38 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Draw, move or remove the viewer's pixel image (Kitty/iTerm2)
+/// It lives outside ratatui's buffer, so it is written after the frame and
+/// re-emitted only when its placement changes (scroll, resize, leaving the viewer).
+fn sync_inline_image<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<()> {
+    use std::io::Write;
+
+    let wanted = app.inline_image_placement();
+    if wanted == app.inline_image_shown {
+        return Ok(());
+    }
+
+    let mut stdout = io::stdout();
+    if let Some((protocol, _)) = app.inline_image_shown.take() {
+        // Kitty keeps images on their own layer; iTerm2 paints them into the cells
+        if protocol == crate::viewer::image::GraphicsProtocol::Kitty {
+            write!(stdout, "{}", crate::viewer::image::KITTY_DELETE_ALL)?;
+            stdout.flush()?;
+        }
+        terminal.clear()?;
+        terminal.draw(|f| ui(f, app))?;
+    }
+    if let Some((_, placement)) = &wanted {
+        if let Some(sequence) = app.inline_image_sequence(placement) {
+            crossterm::queue!(stdout, crossterm::cursor::MoveTo(placement.x, placement.y))?;
+            write!(stdout, "{}", sequence)?;
+        }
+    }
+    stdout.flush()?;
+    app.inline_image_shown = wanted;
+    Ok(())
+}
+
 #[tracing::instrument(skip(terminal, app))]
 async fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<()> {
     tracing::info!("Starting main event loop");
@@ -69,17 +111,22 @@ async fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app:
 
     loop {
         terminal.draw(|f| ui(f, app))?;
+        sync_inline_image(terminal, app)?;
         
         // Update console PTY size based on actual terminal area
-        if app.show_console && app.console.is_running {
+        if app.show_console {
             let size = terminal.size()?;
-            // Console panel is 40% width, full height minus header(1) and footer(1) and borders(2) and help line(1)
-            let console_cols = ((size.width as f32 * 0.40) as u16).saturating_sub(4); // -4 for borders and margins
-            let console_rows = size.height.saturating_sub(5); // -1 header -1 footer -2 borders -1 help line
+            // Console panel width comes from the split ratio, full height minus header(1) and footer(1) and borders(2) and help line(1)
+            let console_percent = app.config.split_ratios.console_percent() as u32;
+            let console_cols = ((size.width as u32 * console_percent / 100) as u16).saturating_sub(4); // -4 for borders and margins
+            let tab_strip = u16::from(app.consoles.len() > 1);
+            let console_rows = size.height.saturating_sub(5 + tab_strip); // -1 header -1 footer -2 borders -1 help line (-1 tab strip)
             
-            // Only resize if size actually changed
-            if app.console.size != (console_cols, console_rows) {
-                app.console.resize(console_cols, console_rows);
+            // All tabs share the panel; only resize if size actually changed
+            for console in app.consoles.iter_mut().filter(|c| c.is_running) {
+                if console.size != (console_cols, console_rows) {
+                    console.resize(console_cols, console_rows);
+                }
             }
         }
         
@@ -96,12 +143,14 @@ async fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app:
             }
         }
         
-        // Update process viewer visible height based on terminal size
-        if app.show_process_viewer {
-            let size = terminal.size()?;
-            // Calculate visible height: 85% of screen height - header(2) - details(6) - footer(1) - borders(4)
-            let visible_height = ((size.height as f32 * 0.85) as usize).saturating_sub(13);
-            app.process_viewer.set_visible_height(visible_height.max(5));
+        // Process list scrolling goes by the list height drawn last frame
+        if app.show_process_viewer && app.process_list_height.get() > 0 {
+            app.process_viewer.set_visible_height(app.process_list_height.get());
+        }
+
+        // Editor page moves go by the text area drawn last frame
+        if let Some(editor) = &mut app.text_editor {
+            editor.set_visible_height(app.viewer_area.get().height as usize);
         }
 
         let poll_timeout = Duration::from_millis(100);
@@ -114,13 +163,9 @@ async fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app:
                 app.status_message = Some("Shell exited".to_string());
             }
         }
-        if app.show_console && app.console.is_running {
-            if !app.console.check_running() {
-                // Console process exited, close console mode
-                app.show_console = false;
-                app.console_focus = false;
-                app.status_message = Some("Console exited".to_string());
-            }
+        if app.close_exited_consoles() {
+            // Last console tab exited, console mode closed
+            app.status_message = Some("Console exited".to_string());
         }
 
         if crossterm::event::poll(poll_timeout)?{
@@ -134,55 +179,38 @@ async fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app:
                     }
                 },
                 Event::Key(key) => {
-                    // Global Hotkeys
-                    // Ctrl + Shift + Alt + K
-                    // Note: Shift + k usually produces 'K', so we check for 'K' with Control and Alt modifiers.
-                    // We also check for 'k' with all three modifiers to be safe.
-                    let is_help_hotkey = (key.code == KeyCode::Char('K') && key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL | crossterm::event::KeyModifiers::ALT)) ||
-                                         (key.code == KeyCode::Char('k') && key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL | crossterm::event::KeyModifiers::ALT | crossterm::event::KeyModifiers::SHIFT));
-
-                    if is_help_hotkey {
+                    // Global Hotkeys (configurable, see keybindings.rs)
+                    let action = app.keybindings.lookup(&key);
+
+                    if action == Some(Action::ToggleHelp) {
                         app.toggle_help();
                         continue; // Skip other processing
                     }
 
-                    // Settings hotkey: Ctrl/Cmd + Shift + Alt + H
-                    let is_settings_hotkey = (key.code == KeyCode::Char('H') && key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL | crossterm::event::KeyModifiers::ALT)) ||
-                                             (key.code == KeyCode::Char('h') && key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL | crossterm::event::KeyModifiers::ALT | crossterm::event::KeyModifiers::SHIFT)) ||
-                                             (key.code == KeyCode::Char('H') && key.modifiers.contains(crossterm::event::KeyModifiers::SUPER | crossterm::event::KeyModifiers::ALT)) ||
-                                             (key.code == KeyCode::Char('h') && key.modifiers.contains(crossterm::event::KeyModifiers::SUPER | crossterm::event::KeyModifiers::ALT | crossterm::event::KeyModifiers::SHIFT));
-
-                    if is_settings_hotkey {
+                    if action == Some(Action::OpenSettings) {
                         app.mode = AppMode::Settings;
                         continue;
                     }
 
-                    // Console panel toggle hotkey: F5 or Ctrl+T
-                    let is_console_toggle = key.code == KeyCode::F(5) ||
-                        ((key.code == KeyCode::Char('t') || key.code == KeyCode::Char('T')) && 
-                         key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL));
-                    if is_console_toggle {
+                    if action == Some(Action::ToggleConsole) {
                         app.toggle_console();
                         continue;
                     }
                     
                     // Pane management - handle BEFORE console to always work
-                    // F3: Add pane, F4: Remove pane (Shift+F3 doesn't work well in some terminals)
                     if let AppMode::FileManager = app.mode {
-                        if key.code == KeyCode::F(3) {
+                        if action == Some(Action::AddPane) {
                             app.add_pane();
                             continue;
                         }
-                        if key.code == KeyCode::F(4) {
+                        if action == Some(Action::RemovePane) {
                             app.remove_pane();
                             continue;
                         }
-                    }
-                    
-                    // F5: Toggle console (Shell) panel - global hotkey
-                    if key.code == KeyCode::F(5) {
-                        app.toggle_console();
-                        continue;
+                        if matches!(action, Some(Action::GrowSplit | Action::ShrinkSplit)) {
+                            app.resize_focused_split(action == Some(Action::GrowSplit));
+                            continue;
+                        }
                     }
                     
                     // Handle console input when console is open and focused
@@ -191,9 +219,11 @@ async fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app:
                         continue;
                     }
                     
-                    // Shell toggle hotkey: F12 or ` (backtick)
-                    let is_shell_hotkey = key.code == KeyCode::F(12) || 
-                                          (key.code == KeyCode::Char('`') && !app.show_shell);
+                    // Shell toggle hotkey (F12 or ` by default)
+                    // Character keys only open the shell; once open they are typed into it
+                    let is_char_key = matches!(key.code, KeyCode::Char(_)) && !key.modifiers.intersects(
+                        crossterm::event::KeyModifiers::CONTROL | crossterm::event::KeyModifiers::ALT);
+                    let is_shell_hotkey = action == Some(Action::ToggleShell) && !(is_char_key && app.show_shell);
                     
                     if is_shell_hotkey {
                         app.toggle_shell();
@@ -206,14 +236,26 @@ async fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app:
                         continue;
                     }
 
-                    // Process viewer toggle hotkey: F9
-                    if key.code == KeyCode::F(9) && !app.show_process_viewer {
+                    // Command palette hotkey (Ctrl+P by default)
+                    if action == Some(Action::CommandPalette) && app.mode == AppMode::FileManager {
+                        app.open_command_palette();
+                        continue;
+                    }
+
+                    // Content search hotkey (Ctrl+/ by default)
+                    if action == Some(Action::GrepSearch) && app.mode == AppMode::FileManager {
+                        app.open_grep();
+                        continue;
+                    }
+
+                    // Process viewer toggle hotkey (F9 by default)
+                    if action == Some(Action::ToggleProcessViewer) && !app.show_process_viewer {
                         app.toggle_process_viewer();
                         continue;
                     }
                     
-                    // Settings toggle hotkey: F8
-                    if key.code == KeyCode::F(8) {
+                    // Settings toggle hotkey (F8 by default)
+                    if action == Some(Action::ToggleSettings) {
                         if let AppMode::Settings = app.mode {
                             app.mode = AppMode::FileManager;
                         } else {
@@ -228,6 +270,28 @@ async fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app:
                         continue;
                     }
 
+                    // Esc cancels a running background paste, extraction or download
+                    if key.code == KeyCode::Esc && (app.cancel_paste() || app.cancel_extract() || app.cancel_url_fetch()) {
+                        continue;
+                    }
+
+                    // Operation summary popup: scroll or dismiss
+                    if let Some(summary) = &app.operation_summary {
+                        match key.code {
+                            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                                app.operation_summary = None;
+                            },
+                            KeyCode::Down | KeyCode::Char('j') if app.summary_scroll + 1 < summary.items.len() => {
+                                app.summary_scroll += 1;
+                            },
+                            KeyCode::Up | KeyCode::Char('k') => {
+                                app.summary_scroll = app.summary_scroll.saturating_sub(1);
+                            },
+                            _ => {}
+                        }
+                        continue; // Modal blocks other input
+                    }
+
                     if app.show_help {
                         if key.code == KeyCode::Esc || key.code == KeyCode::Char('q') {
                             app.toggle_help();
@@ -235,34 +299,139 @@ async fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app:
                         continue; // Modal blocks other input
                     }
 
-                    // Handle bookmark selection
-                    if app.show_bookmarks {
+                    // Handle the command palette: typing filters, Enter runs
+                    if let Some(palette) = &mut app.command_palette {
+                        let match_count = palette.matches().len();
                         match key.code {
-                            KeyCode::Char('1'..='9') => {
-                                if let KeyCode::Char(c) = key.code {
-                                    let idx = c.to_digit(10).unwrap() as usize - 1;
-                                    if idx < app.config.bookmarks.len() {
-                                        app.active_fs_mut().current_dir = app.config.bookmarks[idx].clone();
-                                        app.show_bookmarks = false;
-                                        app.status_message = Some(format!("Jumped to bookmark {}", idx + 1));
-                                    }
+                            KeyCode::Char(c) => {
+                                palette.filter.push(c);
+                                palette.selected = 0;
+                            },
+                            KeyCode::Backspace => {
+                                palette.filter.pop();
+                                palette.selected = 0;
+                            },
+                            KeyCode::Up => palette.selected = palette.selected.saturating_sub(1),
+                            KeyCode::Down if palette.selected + 1 < match_count => palette.selected += 1,
+                            KeyCode::Enter => {
+                                let command = palette.selected_entry().map(|e| e.command.clone());
+                                app.command_palette = None;
+                                if let Some(command) = command {
+                                    app.run_palette_command(command);
+                                }
+                            },
+                            KeyCode::Esc => {
+                                app.command_palette = None;
+                            },
+                            _ => {}
+                        }
+                        continue; // Modal blocks other input
+                    }
+
+                    // Handle the bookmark list: typing filters, Enter jumps
+                    if let Some(list) = &mut app.bookmark_list {
+                        let match_count = list.matches(&app.config.bookmarks).len();
+                        match key.code {
+                            KeyCode::Char(c) => {
+                                list.filter.push(c);
+                                list.selected = 0;
+                            },
+                            KeyCode::Backspace => {
+                                list.filter.pop();
+                                list.selected = 0;
+                            },
+                            KeyCode::Up => list.selected = list.selected.saturating_sub(1),
+                            KeyCode::Down if list.selected + 1 < match_count => list.selected += 1,
+                            KeyCode::Enter => {
+                                if let Some(idx) = list.selected_bookmark(&app.config.bookmarks) {
+                                    let bookmark = app.config.bookmarks[idx].clone();
+                                    app.active_fs_mut().jump_to_dir(bookmark.path);
+                                    app.bookmark_list = None;
+                                    app.status_message = Some(format!("Jumped to bookmark '{}'", bookmark.name));
+                                }
+                            },
+                            KeyCode::Delete => {
+                                if let Some(idx) = list.selected_bookmark(&app.config.bookmarks) {
+                                    list.selected = list.selected.min(match_count.saturating_sub(2));
+                                    let removed = app.config.bookmarks.remove(idx);
+                                    let _ = app.config.save();
+                                    app.status_message = Some(format!("Deleted bookmark '{}'", removed.name));
                                 }
                             },
-                            KeyCode::Char('d') => {
-                                // Delete bookmark mode - press number to delete
-                                if let KeyCode::Char(c) = key.code {
-                                    if c.is_ascii_digit() {
-                                        let idx = c.to_digit(10).unwrap() as usize - 1;
-                                        if idx < app.config.bookmarks.len() {
-                                            app.config.bookmarks.remove(idx);
-                                            let _ = app.config.save();
-                                            app.status_message = Some(format!("Deleted bookmark {}", idx + 1));
-                                        }
+                            KeyCode::Esc => {
+                                app.bookmark_list = None;
+                            },
+                            _ => {}
+                        }
+                        continue; // Modal blocks other input
+                    }
+
+                    // Handle the jump list: typing filters and re-ranks, Enter jumps
+                    if let Some(list) = &mut app.jump_list {
+                        let now = crate::app::unix_now();
+                        let match_count = list.matches(&app.config.dir_history, now).len();
+                        match key.code {
+                            KeyCode::Char(c) => {
+                                list.filter.push(c);
+                                list.selected = 0;
+                            },
+                            KeyCode::Backspace => {
+                                list.filter.pop();
+                                list.selected = 0;
+                            },
+                            KeyCode::Up => list.selected = list.selected.saturating_sub(1),
+                            KeyCode::Down if list.selected + 1 < match_count => list.selected += 1,
+                            KeyCode::Enter => {
+                                if let Some(idx) = list.selected_entry(&app.config.dir_history, now) {
+                                    let dir = app.config.dir_history[idx].path.clone();
+                                    app.jump_list = None;
+                                    if dir.is_dir() {
+                                        app.status_message = Some(format!("Jumped to {}", dir.display()));
+                                        app.active_fs_mut().jump_to_dir(dir);
+                                    } else {
+                                        app.config.dir_history.remove(idx);
+                                        app.status_message = Some(format!("{} no longer exists", dir.display()));
                                     }
                                 }
                             },
-                            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('B') => {
-                                app.show_bookmarks = false;
+                            KeyCode::Delete => {
+                                if let Some(idx) = list.selected_entry(&app.config.dir_history, now) {
+                                    list.selected = list.selected.min(match_count.saturating_sub(2));
+                                    app.config.dir_history.remove(idx);
+                                }
+                            },
+                            KeyCode::Esc => {
+                                app.jump_list = None;
+                            },
+                            _ => {}
+                        }
+                        continue; // Modal blocks other input
+                    }
+
+                    // Handle the disk usage overlay: Enter drills into a directory, Backspace goes up
+                    if let Some(view) = &mut app.disk_usage {
+                        let entries = app.du_cache.get(&view.dir).cloned().unwrap_or_default();
+                        match key.code {
+                            KeyCode::Up | KeyCode::Char('k') => view.selected = view.selected.saturating_sub(1),
+                            KeyCode::Down | KeyCode::Char('j') if view.selected + 1 < entries.len() => view.selected += 1,
+                            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
+                                if let Some(entry) = entries.get(view.selected).filter(|e| e.is_dir) {
+                                    app.show_disk_usage(entry.path.clone(), None);
+                                }
+                            },
+                            KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => {
+                                let dir = view.dir.clone();
+                                if let Some(parent) = dir.parent() {
+                                    app.show_disk_usage(parent.to_path_buf(), Some(&dir));
+                                }
+                            },
+                            KeyCode::Char('r') => app.rescan_disk_usage(),
+                            KeyCode::Esc | KeyCode::Char('q') => {
+                                // Dropping the view cancels a running scan
+                                if view.job.is_some() {
+                                    app.status_message = Some("Disk usage scan cancelled".to_string());
+                                }
+                                app.disk_usage = None;
                             },
                             _ => {}
                         }
@@ -274,11 +443,31 @@ async fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app:
                         continue;
                     }
 
-                match key.code {
-                    KeyCode::Char('q') => {
+                    // Type-ahead find captures letters (including hotkeys) until it times out
+                    if app.mode == AppMode::FileManager && crate::events::handle_type_ahead_keys(app, key.code) {
+                        continue;
+                    }
+
+                    // The viewer's search prompt captures typing (and Esc clears matches)
+                    if crate::events::handle_viewer_search_keys(app, key.code) {
+                        continue;
+                    }
+
+                    // ...and so does its ':' line jump prompt
+                    if crate::events::handle_viewer_command_keys(app, key.code) {
+                        continue;
+                    }
+
+                    // The theme editor takes every key while it's open (Esc leaves it)
+                    if app.mode == AppMode::Settings && crate::events::handle_theme_editor_keys(app, key.code) {
+                        continue;
+                    }
+
+                match (action, key.code) {
+                    (Some(Action::Quit), _) => {
                         if let AppMode::Viewer = app.mode {
                             // In Viewer mode, delegate to viewer handler if editing
-                            if app.viewer_editing {
+                            if app.viewer_editing || app.hex_editor.is_some() {
                                 crate::events::handle_viewer_keys(app, key.code, key.modifiers);
                             } else {
                                 // Close viewer if not editing
@@ -291,12 +480,12 @@ async fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app:
                             app.should_quit = true;
                         }
                     },
-                    KeyCode::Esc => {
+                    (_, KeyCode::Esc) => {
                         // Esc behavior depends on mode
                         match app.mode {
                             AppMode::Viewer => {
-                                // If editing, delegate to vim handler; otherwise close viewer
-                                if app.viewer_editing {
+                                // If editing, delegate to vim/hex handler; otherwise close viewer
+                                if app.viewer_editing || app.hex_editor.is_some() {
                                     crate::events::handle_viewer_keys(app, KeyCode::Esc, crossterm::event::KeyModifiers::empty());
                                 } else {
                                     // Close viewer and return to file manager
@@ -316,10 +505,10 @@ async fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app:
                             }
                         }
                     },
-                    KeyCode::Char('[') => app.toggle_mode(false),
-                    KeyCode::Char(']') => app.toggle_mode(true),
-                    // F3/Shift+F3 handled earlier (before console handler)
-                    KeyCode::Tab => {
+                    (Some(Action::PrevMode), _) => app.toggle_mode(false),
+                    (Some(Action::NextMode), _) => app.toggle_mode(true),
+                    // Pane add/remove handled earlier (before console handler)
+                    (Some(Action::FocusNext), _) => {
                         if let AppMode::FileManager = app.mode {
                             // Cycle through panes and console (if open)
                             app.cycle_focus_forward();
@@ -327,7 +516,7 @@ async fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app:
                             app.toggle_mode(true);
                         }
                     },
-                    KeyCode::BackTab => {
+                    (Some(Action::FocusPrev), _) => {
                         if let AppMode::FileManager = app.mode {
                             // Cycle backward through panes and console (if open)
                             app.cycle_focus_backward();
@@ -335,12 +524,12 @@ async fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app:
                             app.toggle_mode(false);
                         }
                     },
-                    KeyCode::Left if key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) => {
+                    (Some(Action::PaneLeft), _) => {
                         if let AppMode::FileManager = app.mode {
                             app.switch_pane_left();
                         }
                     },
-                    KeyCode::Right if key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) => {
+                    (Some(Action::PaneRight), _) => {
                         if let AppMode::FileManager = app.mode {
                             app.switch_pane_right();
                         }
@@ -416,6 +605,39 @@ async fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app:
                 }
             }
         }
+
+        // Run a terminal program chosen with 'o' (open with)
+        if let Some(open) = app.pending_open.take() {
+            disable_raw_mode()?;
+            execute!(
+                io::stdout(),
+                LeaveAlternateScreen,
+                DisableMouseCapture
+            )?;
+
+            let result = open.run_foreground();
+
+            enable_raw_mode()?;
+            execute!(
+                io::stdout(),
+                EnterAlternateScreen,
+                EnableMouseCapture
+            )?;
+            terminal.clear()?;
+
+            match result {
+                Ok(status) if status.success() => {
+                    app.status_message = Some(format!("Returned from {}", open.program));
+                }
+                Ok(status) => {
+                    app.status_message = Some(format!("{} exited with {}", open.program, status));
+                }
+                Err(e) => {
+                    app.status_message = Some(format!("Failed to run {}: {}", open.program, e));
+                }
+            }
+            app.refresh_both_panes();
+        }
     }
 }
 
@@ -504,9 +726,15 @@ fn handle_mouse_event(mouse: crossterm::event::MouseEvent, app: &mut App, width:
 
     // Get the directory for the clicked column
     if let Some(clicked_dir) = crate::navigation::get_active_directory(app.active_fs()) {
-        let entries = crate::fs::FileSystem::get_entries_for_dir(&clicked_dir);
+        let entries = app.active_fs().entries_for_dir(&clicked_dir);
         if (item_row as usize) < entries.len() {
             app.active_fs_mut().set_selection(clicked_dir, item_row as usize);
+
+            // Double-click on the same item enters the directory / opens the file
+            let window = Duration::from_millis(app.config.double_click_ms);
+            if app.click_tracker.register(Instant::now(), mouse.column, mouse.row, window) {
+                crate::events::handle_file_manager_keys(app, KeyCode::Enter);
+            }
         }
     }
 }
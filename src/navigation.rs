@@ -110,7 +110,7 @@ pub fn get_active_directory(fs: &FileSystem) -> Option<PathBuf> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
     use crate::config::SortOption;
 
     /// Create a test FileSystem with specified path
@@ -128,6 +128,14 @@ mod tests {
             navigation_path,
             column_selections: HashMap::new(),
             sort_option: SortOption::Name,
+            sort_reverse: false,
+            dirs_first: true,
+            previous_dir: None,
+            marked: HashSet::new(),
+            show_hidden: false,
+            view_mode: crate::fs::ViewMode::Compact,
+            entered_dirs: Vec::new(),
+            backend: std::sync::Arc::new(crate::fs::backend::LocalFs),
         }
     }
 
@@ -0,0 +1,132 @@
+//! OSC 7 working-directory reports from the embedded shells
+//!
+//! Shells configured for it (fish, zsh/bash prompt hooks, vte.sh) print
+//! `ESC ] 7 ; file://host/path BEL` at each prompt. vt100 drops OSC 7, so the
+//! raw PTY output is scanned before it reaches the parser.
+
+use std::path::PathBuf;
+
+const OSC7_START: &[u8] = b"\x1b]7;";
+
+/// Longest unterminated sequence kept between reads
+const MAX_PENDING: usize = 4096;
+
+/// Finds OSC 7 sequences in PTY output, including ones split across reads
+#[derive(Debug, Default)]
+pub struct Osc7Scanner {
+    pending: Vec<u8>,
+}
+
+impl Osc7Scanner {
+    /// Scan a chunk of output; returns the last directory reported in it
+    pub fn feed(&mut self, data: &[u8]) -> Option<PathBuf> {
+        let buf: std::borrow::Cow<[u8]> = if self.pending.is_empty() {
+            data.into()
+        } else {
+            let mut joined = std::mem::take(&mut self.pending);
+            joined.extend_from_slice(data);
+            joined.into()
+        };
+
+        let mut found = None;
+        let mut pos = 0;
+        while let Some(offset) = find(&buf[pos..], OSC7_START) {
+            let start = pos + offset + OSC7_START.len();
+            match find_terminator(&buf[start..]) {
+                Some((len, term_len)) => {
+                    found = parse_osc7(&buf[start..start + len]).or(found);
+                    pos = start + len + term_len;
+                },
+                None => {
+                    // Unterminated: wait for the rest
+                    if buf.len() - pos - offset <= MAX_PENDING {
+                        self.pending = buf[pos + offset..].to_vec();
+                    }
+                    return found;
+                },
+            }
+        }
+
+        // Keep a trailing partial "ESC ] 7 ;"
+        let tail = &buf[pos..];
+        for keep in (1..OSC7_START.len()).rev() {
+            if tail.len() >= keep && tail.ends_with(&OSC7_START[..keep]) {
+                self.pending = tail[tail.len() - keep..].to_vec();
+                break;
+            }
+        }
+        found
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Length of the payload and of its terminator (BEL or ESC \)
+fn find_terminator(data: &[u8]) -> Option<(usize, usize)> {
+    data.iter().enumerate().find_map(|(i, &b)| match b {
+        0x07 => Some((i, 1)),
+        0x1b if data.get(i + 1) == Some(&b'\\') => Some((i, 2)),
+        _ => None,
+    })
+}
+
+/// Directory of a `file://host/path` payload (host ignored, path percent-decoded)
+fn parse_osc7(payload: &[u8]) -> Option<PathBuf> {
+    let text = std::str::from_utf8(payload).ok()?;
+    let rest = text.strip_prefix("file://").or_else(|| text.strip_prefix("kitty-shell-cwd://"))?;
+    let path = &rest[rest.find('/')?..];
+    Some(PathBuf::from(percent_decode(path)))
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            },
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            },
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc7_bel_st_and_percent_decoding() {
+        let mut scanner = Osc7Scanner::default();
+        assert_eq!(
+            scanner.feed(b"prompt\x1b]7;file://host/home/me/My%20Docs\x07$ "),
+            Some(PathBuf::from("/home/me/My Docs"))
+        );
+        // ST terminator; the last report in a chunk wins
+        assert_eq!(
+            scanner.feed(b"\x1b]7;file:///tmp\x1b\\ls\r\n\x1b]7;file://h/var\x07"),
+            Some(PathBuf::from("/var"))
+        );
+        assert_eq!(scanner.feed(b"no reports here"), None);
+    }
+
+    #[test]
+    fn test_osc7_split_across_reads() {
+        let mut scanner = Osc7Scanner::default();
+        assert_eq!(scanner.feed(b"output\x1b]"), None);
+        assert_eq!(scanner.feed(b"7;file://host/srv/da"), None);
+        assert_eq!(scanner.feed(b"ta\x07"), Some(PathBuf::from("/srv/data")));
+    }
+}
@@ -0,0 +1,150 @@
+//! Command palette (Ctrl+P by default)
+//!
+//! One filterable list of built-in file manager commands, global actions and
+//! the commands registered by plugins.
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::keybindings::{describe_key, Action};
+
+/// What running a palette entry does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteCommand {
+    Key(KeyCode), // Same as pressing the key in the file manager
+    Action(Action), // A global (rebindable) action
+    Plugin { plugin: String, function: String },
+}
+
+/// A row of the palette
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteEntry {
+    pub name: String,
+    pub description: String,
+    pub keybinding: Option<String>,
+    pub command: PaletteCommand,
+}
+
+/// File manager commands listed in the palette: (name, description, key)
+const FILE_MANAGER_COMMANDS: &[(&str, &str, KeyCode)] = &[
+    ("New file", "Create a file in the current directory", KeyCode::F(8)),
+    ("New folder", "Create a folder in the current directory", KeyCode::F(7)),
+    ("New from template", "Copy a template from ~/.config/senterm/templates", KeyCode::Char('N')),
+    ("Rename", "Rename the selected entry", KeyCode::F(2)),
+    ("Delete", "Delete the selection (to the trash if enabled)", KeyCode::Delete),
+    ("Undo", "Reverse the last rename, move, paste, create or trash deletion", KeyCode::Char('u')),
+    ("Copy", "Copy the selection to the clipboard", KeyCode::Char('c')),
+    ("Cut", "Cut the selection to the clipboard", KeyCode::Char('x')),
+    ("Paste", "Paste the clipboard into the current directory", KeyCode::Char('p')),
+    ("Copy path", "Copy the absolute path to the system clipboard", KeyCode::Char('y')),
+    ("Copy file name", "Copy the file name to the system clipboard", KeyCode::Char('Y')),
+    ("Toggle hidden files", "Show or hide dotfiles", KeyCode::Char('.')),
+    ("Toggle detailed view", "Show size and modified time columns", KeyCode::Char('i')),
+    ("Cycle sort order", "Sort by name, size or modified time", KeyCode::Char('s')),
+    ("Go to path", "Jump to a typed path", KeyCode::Char('g')),
+    ("Search files", "Fuzzy search below the current directory", KeyCode::Char('/')),
+    ("Open with", "Open the selection with an external program", KeyCode::Char('o')),
+    ("Extract archive", "Extract the selected archive into a folder", KeyCode::Char('e')),
+    ("Diff files", "Compare the marked file with the selection", KeyCode::Char('=')),
+    ("Run task", "Pick a npm/make/cargo/just task to run", KeyCode::Char('t')),
+    ("Add bookmark", "Bookmark the current directory", KeyCode::Char('b')),
+    ("Bookmark list", "Open the bookmark list", KeyCode::Char('B')),
+    ("Jump list", "Jump to a frequently used directory", KeyCode::Char('J')),
+    ("Disk usage", "Show recursive sizes below the selected directory", KeyCode::Char('U')),
+    ("Properties", "Show size, permissions, owner and times of the selection", KeyCode::Char('I')),
+    ("Console cd here", "cd the console shell to the current directory", KeyCode::Char('z')),
+];
+
+/// Global actions listed in the palette: (name, description, action)
+const ACTION_COMMANDS: &[(&str, &str, Action)] = &[
+    ("Toggle console", "Show or hide the console panel", Action::ToggleConsole),
+    ("Toggle shell", "Open the shell popup", Action::ToggleShell),
+    ("Add pane", "Split the file manager into another pane", Action::AddPane),
+    ("Remove pane", "Close the active pane", Action::RemovePane),
+    ("Grow split", "Widen the focused pane or panel", Action::GrowSplit),
+    ("Shrink split", "Narrow the focused pane or panel", Action::ShrinkSplit),
+    ("Grep contents", "Search inside the files below the current directory", Action::GrepSearch),
+    ("Process viewer", "Open the process viewer", Action::ToggleProcessViewer),
+    ("Settings", "Open the settings screen", Action::ToggleSettings),
+    ("Help", "Show the key reference", Action::ToggleHelp),
+    ("Quit", "Exit senterm", Action::Quit),
+];
+
+/// Built-in palette entries; `keys_for` describes the keys bound to an action
+pub fn builtin_entries(keys_for: impl Fn(Action) -> Vec<String>) -> Vec<PaletteEntry> {
+    let file_manager = FILE_MANAGER_COMMANDS.iter().map(|&(name, description, key)| PaletteEntry {
+        name: name.to_string(),
+        description: description.to_string(),
+        keybinding: Some(describe_key((key, KeyModifiers::empty()))),
+        command: PaletteCommand::Key(key),
+    });
+    let actions = ACTION_COMMANDS.iter().map(|&(name, description, action)| PaletteEntry {
+        name: name.to_string(),
+        description: description.to_string(),
+        keybinding: keys_for(action).into_iter().next(),
+        command: PaletteCommand::Action(action),
+    });
+    file_manager.chain(actions).collect()
+}
+
+/// Palette popup state: the entries, the typed filter and the highlighted match
+#[derive(Debug)]
+pub struct CommandPalette {
+    pub entries: Vec<PaletteEntry>,
+    pub filter: String,
+    pub selected: usize, // Index into `matches`
+}
+
+impl CommandPalette {
+    pub fn new(entries: Vec<PaletteEntry>) -> Self {
+        Self { entries, filter: String::new(), selected: 0 }
+    }
+
+    /// Indices of the entries whose name or description contains the filter (case-insensitive)
+    pub fn matches(&self) -> Vec<usize> {
+        let filter = self.filter.to_lowercase();
+        self.entries.iter()
+            .enumerate()
+            .filter(|(_, e)| {
+                e.name.to_lowercase().contains(&filter) || e.description.to_lowercase().contains(&filter)
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    pub fn selected_entry(&self) -> Option<&PaletteEntry> {
+        self.matches().get(self.selected).map(|&i| &self.entries[i])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_filters_builtin_and_plugin_entries() {
+        let mut entries = builtin_entries(|action| match action {
+            Action::ToggleConsole => vec!["F5".to_string()],
+            _ => Vec::new(),
+        });
+        entries.push(PaletteEntry {
+            name: "format-json".to_string(),
+            description: "Pretty-print a JSON file".to_string(),
+            keybinding: None,
+            command: PaletteCommand::Plugin { plugin: "json".to_string(), function: "format".to_string() },
+        });
+        let mut palette = CommandPalette::new(entries);
+        assert_eq!(palette.matches().len(), palette.entries.len());
+
+        // Matches names and descriptions, ignoring case
+        palette.filter = "CONSOLE".to_string();
+        let names: Vec<&str> = palette.matches().iter().map(|&i| palette.entries[i].name.as_str()).collect();
+        assert_eq!(names, vec!["Console cd here", "Toggle console"]);
+        palette.selected = 1;
+        assert_eq!(palette.selected_entry().unwrap().keybinding.as_deref(), Some("F5"));
+
+        palette.filter = "json".to_string();
+        palette.selected = 0;
+        assert!(matches!(palette.selected_entry().unwrap().command, PaletteCommand::Plugin { .. }));
+        assert_eq!(palette.entries[0].keybinding.as_deref(), Some("F8"));
+    }
+}
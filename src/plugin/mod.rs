@@ -17,13 +17,77 @@
 //! │   └── init.lua       # Main plugin script
 //! └── simple_script.lua  # Single-file plugin
 //! ```
+//!
+//! # Hooks
+//! Each plugin runs its entry script in its own Lua VM. Manifest plugins list
+//! `[[hooks]]` (event + function); single-file plugins define global functions
+//! named `on_<event>` (e.g. `on_directory_entered`). Hook functions receive an
+//! event table such as `{ event = "file_selected", path = "/tmp/a.txt" }`.
+//! A plugin whose Lua code raises an error is disabled.
+//! With `plugin_hot_reload` enabled, edited plugins are reloaded in place.
+//!
+//! # Sandbox
+//! Plugins are untrusted unless their manifest sets `trusted = true` or the
+//! user promotes them (Settings > Plugins). Untrusted plugins get an `io`
+//! table that only opens files for reading under the browsed directory, an
+//! `os` table limited to time functions (also what `require("io")` and
+//! `require("os")` return), no `dofile`/`loadfile`/`package.loadlib`, `load`
+//! for source text only, and `require` limited to Lua files in the plugin's
+//! own directory.
+//!
+//! Any call into a plugin, trusted or not, is stopped with an error once it
+//! runs longer than [`CALL_TIME_LIMIT`], so a runaway loop disables the plugin
+//! instead of hanging the UI.
+//!
+//! # Viewer handlers
+//! A `viewer` handler is called with the file path and returns the text to
+//! show, optionally followed by `"markdown"` to render it as markdown.
+//! Returning nil (or raising an error) falls back to the built-in viewer.
+//!
+//! # Lua API
+//! Every VM has a global `senterm` table. Calls are queued and applied by the
+//! app on its next tick, so they take effect after the hook returns:
+//! - `senterm.notify(msg)`: show a popup message; returns false when dropped
+//!   because the plugin sent more than [`NOTIFY_LIMIT`] within [`NOTIFY_WINDOW`]
+//! - `senterm.set_status(msg)`: replace the status bar text
+//! - `senterm.current_path()`: the directory browsed in the active pane
 
 #![allow(dead_code)]
 
+use crate::fs::FileWatcher;
 use anyhow::Result;
+use mlua::Lua;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Most notifications a plugin may send per [`NOTIFY_WINDOW`]
+pub const NOTIFY_LIMIT: usize = 3;
+pub const NOTIFY_WINDOW: Duration = Duration::from_secs(5);
+/// Longest a single hook, command or script run may take
+pub const CALL_TIME_LIMIT: Duration = Duration::from_millis(500);
+/// VM instructions between checks of `CALL_TIME_LIMIT`
+const HOOK_INSTRUCTIONS: u32 = 10_000;
+
+/// When the running call into a VM started (Lua app data read by the time hook)
+struct CallStart(Instant);
+
+/// A UI change requested through the `senterm` Lua table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginEffect {
+    Notify { plugin: String, message: String },
+    SetStatus { plugin: String, message: String },
+}
+
+/// App state shared with the Lua VMs, which can't borrow App directly
+#[derive(Debug, Default)]
+struct HostState {
+    effects: Vec<PluginEffect>,
+    current_path: PathBuf,
+}
 
 /// Plugin manifest
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,6 +117,9 @@ pub struct PluginManifest {
     /// Plugin hooks
     #[serde(default)]
     pub hooks: Vec<PluginHook>,
+    /// Full `io`/`os` access; untrusted plugins run sandboxed
+    #[serde(default)]
+    pub trusted: bool,
 }
 
 fn default_entry() -> String {
@@ -100,6 +167,7 @@ pub struct PluginHook {
 }
 
 /// Available hook events
+#[derive(Debug, Clone)]
 pub enum HookEvent {
     /// Called when a file is selected
     FileSelected { path: PathBuf },
@@ -123,6 +191,28 @@ impl HookEvent {
             HookEvent::AppExiting => "app_exiting",
         }
     }
+
+    /// Names of all events, for discovering `on_<event>` functions
+    pub const NAMES: [&'static str; 5] = ["file_selected", "directory_entered", "file_opened", "app_started", "app_exiting"];
+
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            HookEvent::FileSelected { path }
+            | HookEvent::DirectoryEntered { path }
+            | HookEvent::FileOpened { path } => Some(path),
+            HookEvent::AppStarted | HookEvent::AppExiting => None,
+        }
+    }
+
+    /// Table passed to Lua hook functions
+    fn to_lua_table<'lua>(&self, lua: &'lua Lua) -> mlua::Result<mlua::Table<'lua>> {
+        let table = lua.create_table()?;
+        table.set("event", self.name())?;
+        if let Some(path) = self.path() {
+            table.set("path", path.to_string_lossy().into_owned())?;
+        }
+        Ok(table)
+    }
 }
 
 /// Loaded plugin information
@@ -132,6 +222,10 @@ pub struct LoadedPlugin {
     pub manifest: PluginManifest,
     /// Plugin directory
     pub path: PathBuf,
+    /// Plugin directory or single .lua file it was loaded from
+    pub source: PathBuf,
+    /// Runs with the full Lua standard library instead of the sandbox
+    pub trusted: bool,
     /// Whether plugin is enabled
     pub enabled: bool,
 }
@@ -146,6 +240,16 @@ pub struct PluginManager {
     commands: HashMap<String, (String, String)>, // (plugin_name, function)
     /// File handlers
     file_handlers: HashMap<String, (String, String)>, // extension -> (plugin_name, function)
+    /// Lua VM of each loaded plugin
+    runtimes: HashMap<String, Lua>,
+    /// Queue and context behind the `senterm` table
+    host: Rc<RefCell<HostState>>,
+    /// Watches plugin_dir when hot reloading is enabled
+    watcher: Option<FileWatcher>,
+    /// Plugins the user promoted to trusted
+    trusted: HashSet<String>,
+    /// Plugins the user disabled
+    disabled: HashSet<String>,
 }
 
 impl PluginManager {
@@ -158,12 +262,22 @@ impl PluginManager {
         
         std::fs::create_dir_all(&plugin_dir)?;
         
-        Ok(Self {
+        Ok(Self::with_dir(plugin_dir))
+    }
+
+    /// Plugin manager reading from `plugin_dir` (nothing loaded yet)
+    pub fn with_dir(plugin_dir: PathBuf) -> Self {
+        Self {
             plugins: HashMap::new(),
             plugin_dir,
             commands: HashMap::new(),
             file_handlers: HashMap::new(),
-        })
+            runtimes: HashMap::new(),
+            host: Rc::default(),
+            watcher: None,
+            trusted: HashSet::new(),
+            disabled: HashSet::new(),
+        }
     }
     
     /// Load all plugins from the plugin directory
@@ -195,6 +309,15 @@ impl PluginManager {
     
     /// Load a plugin from a directory
     fn load_plugin(&mut self, path: &Path) -> Result<()> {
+        let (plugin, lua) = self.read_plugin(path)?;
+        tracing::info!("Loaded plugin: {}", plugin.manifest.name);
+        self.register(plugin, lua);
+        Ok(())
+    }
+
+    /// Parse a directory plugin's manifest and run its entry script
+    /// A plugin disabled by the user is listed without running its script (no VM)
+    fn read_plugin(&self, path: &Path) -> Result<(LoadedPlugin, Option<Lua>)> {
         let manifest_path = path.join("plugin.toml");
         let manifest_content = std::fs::read_to_string(&manifest_path)?;
         let manifest: PluginManifest = toml::from_str(&manifest_content)?;
@@ -218,47 +341,56 @@ impl PluginManager {
                 entry_path
             ));
         }
-        
-        // Register commands
-        for cmd in &manifest.commands {
-            self.commands.insert(
-                cmd.name.clone(),
-                (manifest.name.clone(), cmd.function.clone()),
-            );
-        }
-        
-        // Register file handlers
-        for handler in &manifest.handlers {
-            for ext in &handler.extensions {
-                self.file_handlers.insert(
-                    ext.clone(),
-                    (manifest.name.clone(), handler.function.clone()),
-                );
-            }
-        }
+        let trusted = manifest.trusted || self.trusted.contains(&manifest.name);
+        let enabled = !self.disabled.contains(&manifest.name);
+        let lua = if enabled {
+            Some(run_script(&entry_path, &manifest.name, &self.host, (!trusted).then_some(path))?)
+        } else {
+            None
+        };
         
         let plugin = LoadedPlugin {
-            manifest: manifest.clone(),
+            manifest,
             path: path.to_path_buf(),
-            enabled: true,
+            source: path.to_path_buf(),
+            trusted,
+            enabled,
         };
-        
-        self.plugins.insert(manifest.name.clone(), plugin);
-        tracing::info!("Loaded plugin: {}", manifest.name);
-        
-        Ok(())
+        Ok((plugin, lua))
     }
     
     /// Load a simple single-file plugin
     fn load_simple_plugin(&mut self, path: &Path) -> Result<()> {
+        let (plugin, lua) = self.read_simple_plugin(path)?;
+        tracing::info!("Loaded simple plugin: {}", plugin.manifest.name);
+        self.register(plugin, lua);
+        Ok(())
+    }
+
+    /// Run a single-file plugin and describe it with a minimal manifest
+    fn read_simple_plugin(&self, path: &Path) -> Result<(LoadedPlugin, Option<Lua>)> {
         let name = path.file_stem()
             .and_then(|s| s.to_str())
             .ok_or_else(|| anyhow::anyhow!("Invalid plugin filename"))?
             .to_string();
+        let trusted = self.trusted.contains(&name);
+        let enabled = !self.disabled.contains(&name);
+        let root = path.parent().unwrap_or(Path::new("."));
+        let lua = if enabled {
+            Some(run_script(path, &name, &self.host, (!trusted).then_some(root))?)
+        } else {
+            None
+        };
+
+        // Hooks are the `on_<event>` functions the script defined
+        let hooks = HookEvent::NAMES.iter()
+            .map(|event| PluginHook { event: event.to_string(), function: format!("on_{}", event) })
+            .filter(|hook| lua.as_ref().is_some_and(|lua| lua.globals().get::<_, mlua::Function>(hook.function.as_str()).is_ok()))
+            .collect();
         
         // Create a minimal manifest for single-file plugins
         let manifest = PluginManifest {
-            name: name.clone(),
+            name,
             version: "1.0.0".to_string(),
             description: format!("Single-file plugin: {}", path.display()),
             author: String::new(),
@@ -266,24 +398,177 @@ impl PluginManager {
             entry: path.file_name().unwrap().to_string_lossy().to_string(),
             commands: Vec::new(),
             handlers: Vec::new(),
-            hooks: Vec::new(),
+            hooks,
+            trusted: false,
         };
         
         let plugin = LoadedPlugin {
             manifest,
-            path: path.parent().unwrap_or(Path::new(".")).to_path_buf(),
-            enabled: true,
+            path: root.to_path_buf(),
+            source: path.to_path_buf(),
+            trusted,
+            enabled,
+        };
+        Ok((plugin, lua))
+    }
+
+    /// Add a loaded plugin and, when it has a VM, its commands and file handlers
+    fn register(&mut self, plugin: LoadedPlugin, lua: Option<Lua>) {
+        let name = plugin.manifest.name.clone();
+        let Some(lua) = lua else {
+            self.plugins.insert(name, plugin);
+            return;
         };
         
-        self.plugins.insert(name.clone(), plugin);
-        tracing::info!("Loaded simple plugin: {}", name);
+        // Register commands
+        for cmd in &plugin.manifest.commands {
+            self.commands.insert(
+                cmd.name.clone(),
+                (name.clone(), cmd.function.clone()),
+            );
+        }
         
+        // Register file handlers
+        for handler in &plugin.manifest.handlers {
+            for ext in &handler.extensions {
+                self.file_handlers.insert(
+                    ext.trim_start_matches('.').to_lowercase(),
+                    (name.clone(), handler.function.clone()),
+                );
+            }
+        }
+        
+        self.plugins.insert(name.clone(), plugin);
+        self.runtimes.insert(name, lua);
+    }
+
+    /// Remove a plugin along with everything it registered
+    fn unregister(&mut self, name: &str) {
+        self.plugins.remove(name);
+        self.release(name);
+    }
+
+    /// Drop a plugin's VM, commands and file handlers (it stays listed)
+    fn release(&mut self, name: &str) {
+        self.runtimes.remove(name);
+        self.commands.retain(|_, (plugin, _)| plugin != name);
+        self.file_handlers.retain(|_, (plugin, _)| plugin != name);
+    }
+
+    /// Plugins the user has disabled (listed, but not run, by the next load)
+    pub fn set_disabled_plugins(&mut self, names: &[String]) {
+        self.disabled = names.iter().cloned().collect();
+    }
+
+    /// Enable or disable `name` for this session and later loads; returns the new state
+    /// Enabling reloads the plugin from disk.
+    pub fn toggle_enabled(&mut self, name: &str) -> std::result::Result<bool, String> {
+        let plugin = self.get(name).ok_or_else(|| format!("Plugin not found: {}", name))?;
+        let source = plugin.source.clone();
+        if plugin.enabled {
+            self.disabled.insert(name.to_string());
+            self.set_enabled(name, false).map_err(|e| e.to_string())?;
+            self.release(name);
+            Ok(false)
+        } else {
+            self.disabled.remove(name);
+            self.reload_path(&source)?;
+            Ok(true)
+        }
+    }
+
+    /// Watch the plugin directory so edited plugins are reloaded by `poll_reloads`
+    pub fn enable_hot_reload(&mut self) -> Result<()> {
+        let mut watcher = FileWatcher::new()?;
+        watcher.watch_recursive(&self.plugin_dir)?;
+        self.watcher = Some(watcher);
         Ok(())
     }
+
+    /// Reload the plugins whose files changed since the last call
+    /// Returns one message per plugin: Ok("Reloaded …") or Err(reason the old version was kept).
+    pub fn poll_reloads(&mut self) -> Vec<std::result::Result<String, String>> {
+        let Some(watcher) = &self.watcher else { return Vec::new() };
+        let mut sources: Vec<PathBuf> = watcher.poll_changes()
+            .into_iter()
+            .filter_map(|change| self.plugin_source(&change.path))
+            .collect();
+        sources.sort();
+        sources.dedup();
+
+        sources.iter().map(|source| self.reload_path(source)).collect()
+    }
+
+    /// Plugin directory or single-file plugin that `changed` belongs to
+    fn plugin_source(&self, changed: &Path) -> Option<PathBuf> {
+        let relative = changed.strip_prefix(&self.plugin_dir).ok()?;
+        let first = relative.components().next()?;
+        let source = self.plugin_dir.join(first);
+        let in_subdir = relative.components().count() > 1;
+        (in_subdir || source.is_dir() || source.extension().is_some_and(|e| e == "lua")).then_some(source)
+    }
+
+    /// Re-read the plugin at `source`, replacing the loaded version only if it loads cleanly
+    pub fn reload_path(&mut self, source: &Path) -> std::result::Result<String, String> {
+        let previous = self.plugins.values()
+            .find(|p| p.source == source)
+            .map(|p| p.manifest.name.clone());
+
+        if !source.exists() {
+            return match previous {
+                Some(name) => {
+                    self.unregister(&name);
+                    Ok(format!("Unloaded plugin '{}'", name))
+                }
+                None => Err(format!("{} is not a plugin", source.display())),
+            };
+        }
+
+        let loaded = if source.is_dir() {
+            self.read_plugin(source)
+        } else {
+            self.read_simple_plugin(source)
+        };
+        match loaded {
+            Ok((plugin, lua)) => {
+                if let Some(name) = &previous {
+                    self.unregister(name);
+                }
+                let name = plugin.manifest.name.clone();
+                self.register(plugin, lua);
+                tracing::info!(plugin = %name, "Reloaded plugin");
+                Ok(format!("Reloaded plugin '{}'", name))
+            }
+            Err(e) => {
+                tracing::warn!(source = ?source, "Plugin reload failed: {}", e);
+                match previous {
+                    Some(name) => Err(format!("Reloading '{}' failed (previous version kept): {}", name, e)),
+                    None => Err(format!("Loading {} failed: {}", source.display(), e)),
+                }
+            }
+        }
+    }
     
-    /// Get list of loaded plugins
+    /// Get list of loaded plugins, sorted by name
     pub fn list(&self) -> Vec<&LoadedPlugin> {
-        self.plugins.values().collect()
+        let mut plugins: Vec<&LoadedPlugin> = self.plugins.values().collect();
+        plugins.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
+        plugins
+    }
+
+    /// Plugins the user has promoted to trusted (applied by the next load)
+    pub fn set_trusted_plugins(&mut self, names: &[String]) {
+        self.trusted = names.iter().cloned().collect();
+    }
+
+    /// Trust `name` and reload it with the full API
+    pub fn promote(&mut self, name: &str) -> std::result::Result<String, String> {
+        let source = self.get(name)
+            .map(|p| p.source.clone())
+            .ok_or_else(|| format!("Plugin not found: {}", name))?;
+        self.trusted.insert(name.to_string());
+        self.reload_path(&source)
+            .map(|_| format!("Plugin '{}' is now trusted", name))
     }
     
     /// Get a plugin by name
@@ -305,6 +590,35 @@ impl PluginManager {
         &self.commands
     }
     
+    /// Commands of enabled plugins as (plugin name, command), sorted by command name
+    pub fn command_list(&self) -> Vec<(&str, &PluginCommand)> {
+        let mut commands: Vec<(&str, &PluginCommand)> = self.plugins.values()
+            .filter(|p| p.enabled)
+            .flat_map(|p| p.manifest.commands.iter().map(|c| (p.manifest.name.as_str(), c)))
+            .collect();
+        commands.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+        commands
+    }
+
+    /// Call a plugin command's Lua function; a failing plugin is disabled
+    pub fn run_command(&mut self, plugin: &str, function: &str) -> Result<()> {
+        if !self.plugins.get(plugin).is_some_and(|p| p.enabled) {
+            return Err(anyhow::anyhow!("Plugin {} is not enabled", plugin));
+        }
+        let lua = self.runtimes.get(plugin)
+            .ok_or_else(|| anyhow::anyhow!("Plugin {} has no Lua runtime", plugin))?;
+        start_call(lua);
+        let result = lua.globals()
+            .get::<_, mlua::Function>(function)
+            .and_then(|f| f.call::<_, ()>(()));
+        if let Err(e) = result {
+            tracing::error!(plugin = %plugin, function = %function, "Plugin command failed: {}", e);
+            let _ = self.set_enabled(plugin, false);
+            return Err(anyhow::anyhow!("{}: {}", plugin, e));
+        }
+        Ok(())
+    }
+
     /// Check if a file extension has a handler
     pub fn has_handler(&self, extension: &str) -> bool {
         self.file_handlers.contains_key(extension)
@@ -319,16 +633,128 @@ impl PluginManager {
     pub fn plugin_dir(&self) -> &Path {
         &self.plugin_dir
     }
+
+    /// Enabled plugin and function registered as a "viewer" handler for `path`'s extension
+    fn viewer_handler(&self, path: &Path) -> Option<(&str, &str)> {
+        let ext = path.extension()?.to_string_lossy().to_lowercase();
+        let (plugin_name, function) = self.file_handlers.get(&ext)?;
+        let plugin = self.plugins.get(plugin_name).filter(|p| p.enabled)?;
+        plugin.manifest.handlers.iter()
+            .any(|h| h.function == *function && h.handler_type == "viewer")
+            .then_some((plugin_name.as_str(), function.as_str()))
+    }
+
+    pub fn has_viewer_handler(&self, path: &Path) -> bool {
+        self.viewer_handler(path).is_some()
+    }
+
+    /// Viewer content produced by a plugin's viewer handler for `path`
+    /// None when no plugin handles it, or the handler errored or returned nil.
+    pub fn view_file(&mut self, path: &Path) -> Option<crate::viewer::ViewerContent> {
+        let (plugin_name, function) = self.viewer_handler(path)?;
+        let plugin_name = plugin_name.to_string();
+        let lua = self.runtimes.get(&plugin_name)?;
+
+        start_call(lua);
+        let result = lua.globals()
+            .get::<_, mlua::Function>(function)
+            .and_then(|f| f.call::<_, (Option<String>, Option<String>)>(path.to_string_lossy().into_owned()));
+        match result {
+            Ok((Some(text), format)) if format.as_deref() == Some("markdown") => {
+                Some(crate::viewer::ViewerContent::Markdown(text))
+            }
+            Ok((Some(text), _)) => Some(crate::viewer::ViewerContent::PlainText(text)),
+            Ok((None, _)) => None,
+            Err(e) => {
+                tracing::error!(plugin = %plugin_name, path = ?path, "Plugin viewer handler failed: {}", e);
+                tracing::warn!(plugin = %plugin_name, "Disabling plugin after a Lua error");
+                let _ = self.set_enabled(&plugin_name, false);
+                None
+            }
+        }
+    }
+
+    /// Directory returned by `senterm.current_path()`
+    pub fn set_current_path(&mut self, path: &Path) {
+        let mut host = self.host.borrow_mut();
+        if host.current_path != path {
+            host.current_path = path.to_path_buf();
+        }
+    }
+
+    /// Take the UI changes plugins queued since the last call
+    pub fn drain_effects(&mut self) -> Vec<PluginEffect> {
+        std::mem::take(&mut self.host.borrow_mut().effects)
+    }
+
+    /// Whether any enabled plugin handles `event` (lets callers skip building the event)
+    pub fn has_hook(&self, event: &str) -> bool {
+        self.plugins.values()
+            .filter(|p| p.enabled)
+            .any(|p| p.manifest.hooks.iter().any(|h| h.event == event))
+    }
+
+    /// Call every enabled plugin's function registered for `event`
+    /// A plugin whose hook fails is logged and disabled.
+    pub fn dispatch_hook(&mut self, event: &HookEvent) {
+        let mut failed = Vec::new();
+        for (name, plugin) in self.plugins.iter().filter(|(_, p)| p.enabled) {
+            let Some(lua) = self.runtimes.get(name) else { continue };
+            for hook in plugin.manifest.hooks.iter().filter(|h| h.event == event.name()) {
+                start_call(lua);
+                let result = lua.globals()
+                    .get::<_, mlua::Function>(hook.function.as_str())
+                    .and_then(|function| function.call::<_, ()>(event.to_lua_table(lua)?));
+                if let Err(e) = result {
+                    tracing::error!(plugin = %name, hook = %hook.function, "Plugin hook failed: {}", e);
+                    failed.push(name.clone());
+                    break;
+                }
+            }
+        }
+
+        for name in failed {
+            tracing::warn!(plugin = %name, "Disabling plugin after a Lua error");
+            let _ = self.set_enabled(&name, false);
+        }
+    }
+}
+
+/// Create a Lua VM with the `senterm` table and run the script at `path` in it
+/// `sandbox_root` (the plugin's directory) sandboxes an untrusted plugin.
+fn run_script(path: &Path, plugin: &str, host: &Rc<RefCell<HostState>>, sandbox_root: Option<&Path>) -> Result<Lua> {
+    let source = std::fs::read_to_string(path)?;
+    let lua = Lua::new();
+    install_api(&lua, plugin, host)
+        .map_err(|e| anyhow::anyhow!("Failed to set up Lua API for {}: {}", plugin, e))?;
+    if let Some(root) = sandbox_root {
+        install_sandbox(&lua, host, root)
+            .map_err(|e| anyhow::anyhow!("Failed to sandbox {}: {}", plugin, e))?;
+    }
+    lua.set_hook(mlua::HookTriggers::new().every_nth_instruction(HOOK_INSTRUCTIONS), |lua, _| {
+        match lua.app_data_ref::<CallStart>() {
+            Some(start) if start.0.elapsed() > CALL_TIME_LIMIT => Err(mlua::Error::RuntimeError(
+                format!("plugin call ran longer than {} ms", CALL_TIME_LIMIT.as_millis()),
+            )),
+            _ => Ok(()),
+        }
+    });
+    start_call(&lua);
+    lua.load(source.as_str())
+        .set_name(path.to_string_lossy())
+        .exec()
+        .map_err(|e| anyhow::anyhow!("Lua error in {:?}: {}", path, e))?;
+    Ok(lua)
+}
+
+/// Restart the time limit for a call into `lua`
+fn start_call(lua: &Lua) {
+    lua.set_app_data(CallStart(Instant::now()));
 }
 
 impl Default for PluginManager {
     fn default() -> Self {
-        Self::new().unwrap_or_else(|_| Self {
-            plugins: HashMap::new(),
-            plugin_dir: PathBuf::new(),
-            commands: HashMap::new(),
-            file_handlers: HashMap::new(),
-        })
+        Self::new().unwrap_or_else(|_| Self::with_dir(PathBuf::new()))
     }
 }
 
@@ -356,6 +782,139 @@ fn version_compatible(current: &str, required: &str) -> bool {
     current_parts.len() >= required_parts.len()
 }
 
+/// Register the `senterm` table for `plugin`
+fn install_api(lua: &Lua, plugin: &str, host: &Rc<RefCell<HostState>>) -> mlua::Result<()> {
+    let api = lua.create_table()?;
+
+    let (name, queue) = (plugin.to_string(), Rc::clone(host));
+    let sent: RefCell<Vec<Instant>> = RefCell::default();
+    api.set("notify", lua.create_function(move |_, message: String| {
+        let now = Instant::now();
+        let mut sent = sent.borrow_mut();
+        sent.retain(|t| now.duration_since(*t) < NOTIFY_WINDOW);
+        if sent.len() >= NOTIFY_LIMIT {
+            tracing::warn!(plugin = %name, "Dropping plugin notification (rate limited)");
+            return Ok(false);
+        }
+        sent.push(now);
+        queue.borrow_mut().effects.push(PluginEffect::Notify { plugin: name.clone(), message });
+        Ok(true)
+    })?)?;
+
+    let (name, queue) = (plugin.to_string(), Rc::clone(host));
+    api.set("set_status", lua.create_function(move |_, message: String| {
+        queue.borrow_mut().effects.push(PluginEffect::SetStatus { plugin: name.clone(), message });
+        Ok(())
+    })?)?;
+
+    let context = Rc::clone(host);
+    api.set("current_path", lua.create_function(move |_, ()| {
+        Ok(context.borrow().current_path.to_string_lossy().into_owned())
+    })?)?;
+
+    lua.globals().set("senterm", api)
+}
+
+/// `path` resolved against `dir`, if it exists under `dir`
+fn readable_path(path: &str, dir: &Path) -> std::result::Result<PathBuf, String> {
+    let denied = || format!("read access to {} denied (outside {})", path, dir.display());
+    let resolved = dir.join(path).canonicalize().map_err(|_| denied())?;
+    let root = dir.canonicalize().map_err(|_| denied())?;
+    if resolved.starts_with(&root) {
+        Ok(resolved)
+    } else {
+        Err(denied())
+    }
+}
+
+/// Replace `io`, `os` and the file-loading globals with restricted versions
+fn install_sandbox(lua: &Lua, host: &Rc<RefCell<HostState>>, root: &Path) -> mlua::Result<()> {
+    let globals = lua.globals();
+    let io: mlua::Table = globals.get("io")?;
+    let os: mlua::Table = globals.get("os")?;
+
+    // Reads only, and only below the browsed directory
+    let sandboxed_io = lua.create_table()?;
+    let open = lua.create_registry_value(io.get::<_, mlua::Function>("open")?)?;
+    let context = Rc::clone(host);
+    sandboxed_io.set("open", lua.create_function(move |lua, (path, mode): (String, Option<String>)| {
+        let mode = mode.unwrap_or_else(|| "r".to_string());
+        if !matches!(mode.as_str(), "r" | "rb") {
+            return Err(mlua::Error::RuntimeError(format!("sandbox: write access to {} denied", path)));
+        }
+        let path = readable_path(&path, &context.borrow().current_path)
+            .map_err(|e| mlua::Error::RuntimeError(format!("sandbox: {}", e)))?;
+        lua.registry_value::<mlua::Function>(&open)?
+            .call::<_, mlua::MultiValue>((path.to_string_lossy().into_owned(), mode))
+    })?)?;
+    let lines = lua.create_registry_value(io.get::<_, mlua::Function>("lines")?)?;
+    let context = Rc::clone(host);
+    sandboxed_io.set("lines", lua.create_function(move |lua, path: String| {
+        let path = readable_path(&path, &context.borrow().current_path)
+            .map_err(|e| mlua::Error::RuntimeError(format!("sandbox: {}", e)))?;
+        lua.registry_value::<mlua::Function>(&lines)?
+            .call::<_, mlua::MultiValue>(path.to_string_lossy().into_owned())
+    })?)?;
+    globals.set("io", sandboxed_io.clone())?;
+
+    // Clock and date functions only: no processes, file changes or exit
+    let sandboxed_os = lua.create_table()?;
+    for name in ["clock", "date", "difftime", "time"] {
+        sandboxed_os.set(name, os.get::<_, mlua::Value>(name)?)?;
+    }
+    for name in ["execute", "exit", "getenv", "remove", "rename", "setlocale", "tmpname"] {
+        sandboxed_os.set(name, lua.create_function(move |_, _: mlua::MultiValue| -> mlua::Result<()> {
+            Err(mlua::Error::RuntimeError(format!("sandbox: os.{} is not available to untrusted plugins", name)))
+        })?)?;
+    }
+    globals.set("os", sandboxed_os.clone())?;
+
+    // `require("io")` and friends return what was already loaded, so swap those too
+    let package: mlua::Table = globals.get("package")?;
+    let loaded: mlua::Table = package.get("loaded")?;
+    loaded.set("io", sandboxed_io)?;
+    loaded.set("os", sandboxed_os)?;
+
+    globals.set("dofile", mlua::Value::Nil)?;
+    globals.set("loadfile", mlua::Value::Nil)?;
+    // Binary chunks can corrupt the VM; `load` only takes source text
+    let load = lua.create_registry_value(globals.get::<_, mlua::Function>("load")?)?;
+    globals.set("load", lua.create_function(move |lua, args: mlua::MultiValue| {
+        // (chunk, name, mode[, env]): an `env` that was passed stays, even nil
+        let mut args: Vec<mlua::Value> = args.into_iter().collect();
+        if args.len() < 3 {
+            args.resize(3, mlua::Value::Nil);
+        }
+        args[2] = mlua::Value::String(lua.create_string("t")?);
+        lua.registry_value::<mlua::Function>(&load)?
+            .call::<_, mlua::MultiValue>(mlua::MultiValue::from_vec(args))
+    })?)?;
+
+    // `require` only finds Lua source in the plugin's directory: no C modules or preloads
+    package.set("loadlib", mlua::Value::Nil)?;
+    package.set("preload", lua.create_table()?)?;
+    let root = root.to_path_buf();
+    package.set("path", format!("{0}/?.lua;{0}/?/init.lua", root.to_string_lossy()))?;
+    package.set("cpath", "")?;
+    let searcher = lua.create_function(move |lua, name: String| {
+        let relative = name.replace('.', "/");
+        let found = [format!("{}.lua", relative), format!("{}/init.lua", relative)]
+            .into_iter()
+            .find_map(|candidate| readable_path(&candidate, &root).ok());
+        let Some(path) = found else {
+            return Ok(mlua::Value::String(lua.create_string(format!("\n\tno file '{}.lua' in the plugin directory", relative))?));
+        };
+        let source = std::fs::read_to_string(&path).map_err(mlua::Error::external)?;
+        let chunk = lua.load(source.as_str())
+            .set_name(path.to_string_lossy())
+            .set_mode(mlua::ChunkMode::Text)
+            .into_function()?;
+        Ok(mlua::Value::Function(chunk))
+    })?;
+    package.set("searchers", lua.create_sequence_from([searcher])?)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -368,5 +927,221 @@ mod tests {
         assert!(!version_compatible("0.9.0", "1.0.0"));
         assert!(!version_compatible("1.0.0", "1.0.1"));
     }
-}
 
+    #[test]
+    fn test_dispatch_hook_runs_lua_and_disables_on_error() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("counter.lua"), r#"
+            entered = {}
+            function on_directory_entered(e) table.insert(entered, e.path) end
+            function on_app_started(e) error("boom") end
+        "#).unwrap();
+
+        let mut manager = PluginManager::with_dir(temp.path().to_path_buf());
+        manager.load_all().unwrap();
+        assert_eq!(manager.get("counter").unwrap().manifest.hooks.len(), 2);
+
+        manager.dispatch_hook(&HookEvent::DirectoryEntered { path: PathBuf::from("/tmp") });
+        let entered: Vec<String> = manager.runtimes["counter"].load("return entered").eval().unwrap();
+        assert_eq!(entered, vec!["/tmp".to_string()]);
+
+        // A failing hook disables the plugin instead of propagating
+        manager.dispatch_hook(&HookEvent::AppStarted);
+        assert!(!manager.get("counter").unwrap().enabled);
+        manager.dispatch_hook(&HookEvent::DirectoryEntered { path: PathBuf::from("/var") });
+        let entered: Vec<String> = manager.runtimes["counter"].load("return entered").eval().unwrap();
+        assert_eq!(entered.len(), 1);
+    }
+
+    #[test]
+    fn test_lua_api_queues_effects_and_rate_limits() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("chatty.lua"), r#"
+            function on_app_started(e)
+                for i = 1, 5 do senterm.notify("hello " .. i) end
+                senterm.set_status("in " .. senterm.current_path())
+            end
+        "#).unwrap();
+
+        let mut manager = PluginManager::with_dir(temp.path().to_path_buf());
+        manager.load_all().unwrap();
+        manager.set_current_path(Path::new("/srv"));
+        manager.dispatch_hook(&HookEvent::AppStarted);
+
+        let effects = manager.drain_effects();
+        let notify = |message: &str| PluginEffect::Notify { plugin: "chatty".to_string(), message: message.to_string() };
+        assert_eq!(effects, vec![
+            notify("hello 1"),
+            notify("hello 2"),
+            notify("hello 3"),
+            PluginEffect::SetStatus { plugin: "chatty".to_string(), message: "in /srv".to_string() },
+        ]);
+        assert!(manager.drain_effects().is_empty());
+    }
+
+    #[test]
+    fn test_viewer_handler_overrides_and_falls_back() {
+        let temp = tempfile::tempdir().unwrap();
+        let plugin_dir = temp.path().join("plist");
+        std::fs::create_dir(&plugin_dir).unwrap();
+        std::fs::write(plugin_dir.join("plugin.toml"), r#"
+            name = "plist"
+            version = "1.0.0"
+            [[handlers]]
+            extensions = ["plist", "md"]
+            function = "view"
+        "#).unwrap();
+        std::fs::write(plugin_dir.join("init.lua"), r##"
+            function view(path)
+                if path:match("%.md$") then return "# " .. path, "markdown" end
+                if path:match("empty") then return nil end
+                if path:match("bad") then error("cannot parse") end
+                return "decoded " .. path
+            end
+        "##).unwrap();
+
+        let mut manager = PluginManager::with_dir(temp.path().to_path_buf());
+        manager.load_all().unwrap();
+        assert!(manager.has_viewer_handler(Path::new("a.PLIST")));
+        assert!(!manager.has_viewer_handler(Path::new("a.txt")));
+
+        assert!(matches!(manager.view_file(Path::new("a.plist")),
+            Some(crate::viewer::ViewerContent::PlainText(t)) if t == "decoded a.plist"));
+        assert!(matches!(manager.view_file(Path::new("a.md")),
+            Some(crate::viewer::ViewerContent::Markdown(t)) if t == "# a.md"));
+        assert!(manager.view_file(Path::new("empty.plist")).is_none());
+
+        // An error falls back to the built-in viewer and disables the plugin
+        assert!(manager.view_file(Path::new("bad.plist")).is_none());
+        assert!(!manager.has_viewer_handler(Path::new("a.plist")));
+    }
+
+    #[test]
+    fn test_reload_keeps_previous_version_on_error() {
+        let temp = tempfile::tempdir().unwrap();
+        let script = temp.path().join("greet.lua");
+        std::fs::write(&script, "function on_app_started(e) senterm.set_status('v1') end").unwrap();
+
+        let mut manager = PluginManager::with_dir(temp.path().to_path_buf());
+        manager.load_all().unwrap();
+        let status = |manager: &mut PluginManager| {
+            manager.dispatch_hook(&HookEvent::AppStarted);
+            manager.drain_effects()
+        };
+        assert_eq!(status(&mut manager), vec![PluginEffect::SetStatus { plugin: "greet".to_string(), message: "v1".to_string() }]);
+
+        // A syntax error keeps v1 running
+        std::fs::write(&script, "function on_app_started(e").unwrap();
+        assert!(manager.reload_path(&script).unwrap_err().contains("previous version kept"));
+        assert_eq!(status(&mut manager).len(), 1);
+
+        std::fs::write(&script, "function on_app_started(e) senterm.set_status('v2') end").unwrap();
+        assert_eq!(manager.reload_path(&script), Ok("Reloaded plugin 'greet'".to_string()));
+        assert_eq!(status(&mut manager), vec![PluginEffect::SetStatus { plugin: "greet".to_string(), message: "v2".to_string() }]);
+
+        std::fs::remove_file(&script).unwrap();
+        assert_eq!(manager.reload_path(&script), Ok("Unloaded plugin 'greet'".to_string()));
+        assert!(manager.get("greet").is_none());
+        assert_eq!(manager.plugin_source(&temp.path().join("dir/init.lua")), Some(temp.path().join("dir")));
+        assert_eq!(manager.plugin_source(&temp.path().join("notes.txt")), None);
+    }
+
+    #[test]
+    fn test_untrusted_plugins_are_sandboxed() {
+        let temp = tempfile::tempdir().unwrap();
+        let browsed = temp.path().join("browsed");
+        std::fs::create_dir(&browsed).unwrap();
+        std::fs::write(browsed.join("notes.txt"), "inside").unwrap();
+        std::fs::write(temp.path().join("secret.txt"), "outside").unwrap();
+        let plugins = temp.path().join("plugins");
+        std::fs::create_dir(&plugins).unwrap();
+        std::fs::write(plugins.join("probe.lua"), r#"
+            function try(f, ...) local ok, err = pcall(f, ...) return ok and "ok" or tostring(err) end
+            function read(path) return io.open(path):read("a") end
+        "#).unwrap();
+
+        let mut manager = PluginManager::with_dir(plugins.clone());
+        manager.load_all().unwrap();
+        manager.set_current_path(&browsed);
+        assert!(!manager.get("probe").unwrap().trusted);
+
+        let eval = |manager: &PluginManager, code: &str| -> String {
+            start_call(&manager.runtimes["probe"]);
+            manager.runtimes["probe"].load(code).eval().unwrap()
+        };
+        assert_eq!(eval(&manager, "return read('notes.txt')"), "inside");
+        assert!(eval(&manager, "return try(read, '../secret.txt')").contains("read access"));
+        assert!(eval(&manager, "return try(io.open, 'new.txt', 'w')").contains("write access"));
+        assert!(eval(&manager, "return try(os.execute, 'true')").contains("os.execute"));
+        assert_eq!(eval(&manager, "return tostring(dofile)"), "nil");
+        assert_eq!(eval(&manager, "return type(os.time())"), "number");
+
+        // The real libraries can't be reached another way
+        assert!(eval(&manager, "return try(require('os').execute, 'true')").contains("os.execute"));
+        assert!(eval(&manager, "return try(package.loaded.os.execute, 'true')").contains("os.execute"));
+        assert!(eval(&manager, "return try(package.loaded.io.open, 'new.txt', 'w')").contains("write access"));
+        assert_eq!(eval(&manager, "return tostring(package.loadlib)"), "nil");
+        assert!(eval(&manager, "return try(require, 'socket')").contains("no file"));
+        assert!(eval(&manager, "package.preload.x = function() return io end return try(require, 'x')")
+            .contains("no file"));
+        assert!(eval(&manager, "return select(2, load(string.dump(function() end)))").contains("binary"));
+        assert_eq!(eval(&manager, "return load('return 1 + 1')() .. ''"), "2");
+        std::fs::write(plugins.join("helper.lua"), "return { answer = 42 }").unwrap();
+        assert_eq!(eval(&manager, "return require('helper').answer .. ''"), "42");
+
+        // Promoting reloads the plugin with the full standard library
+        assert_eq!(manager.promote("probe"), Ok("Plugin 'probe' is now trusted".to_string()));
+        assert!(manager.get("probe").unwrap().trusted);
+        let secret = temp.path().join("secret.txt").to_string_lossy().into_owned();
+        assert_eq!(eval(&manager, &format!("return read('{}')", secret)), "outside");
+    }
+
+    #[test]
+    fn test_runaway_plugin_is_stopped_and_disabled() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("spin.lua"), "function spin() while true do end end").unwrap();
+        let mut manager = PluginManager::with_dir(temp.path().to_path_buf());
+        manager.load_all().unwrap();
+
+        let started = Instant::now();
+        let error = manager.run_command("spin", "spin").unwrap_err().to_string();
+        assert!(error.contains("ran longer than"), "{}", error);
+        assert!(started.elapsed() < CALL_TIME_LIMIT * 4);
+        assert!(!manager.get("spin").unwrap().enabled);
+    }
+
+    #[test]
+    fn test_disabled_plugins_register_nothing() {
+        let temp = tempfile::tempdir().unwrap();
+        let plugin_dir = temp.path().join("fmt");
+        std::fs::create_dir(&plugin_dir).unwrap();
+        std::fs::write(plugin_dir.join("plugin.toml"), r#"
+            name = "fmt"
+            version = "0.2.0"
+            [[commands]]
+            name = "format"
+            function = "format"
+            [[handlers]]
+            extensions = ["fmt"]
+            function = "view"
+        "#).unwrap();
+        std::fs::write(plugin_dir.join("init.lua"), "function format() end function view(p) return p end").unwrap();
+
+        let mut manager = PluginManager::with_dir(temp.path().to_path_buf());
+        manager.set_disabled_plugins(&["fmt".to_string()]);
+        manager.load_all().unwrap();
+        // Listed for the settings tab, but nothing registered or run
+        assert!(!manager.get("fmt").unwrap().enabled);
+        assert!(manager.get_commands().is_empty());
+        assert!(!manager.has_viewer_handler(Path::new("a.fmt")));
+        assert!(manager.runtimes.is_empty());
+
+        assert_eq!(manager.toggle_enabled("fmt"), Ok(true));
+        assert_eq!(manager.command_list().len(), 1);
+        assert!(manager.has_viewer_handler(Path::new("a.fmt")));
+
+        assert_eq!(manager.toggle_enabled("fmt"), Ok(false));
+        assert!(manager.get_commands().is_empty());
+        assert!(manager.run_command("fmt", "format").is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! Export the visible process list to CSV or JSON
+
+use std::path::{Path, PathBuf};
+
+use super::ProcessInfo;
+
+/// Output format for process exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn toggle(&self) -> Self {
+        match self {
+            ExportFormat::Csv => ExportFormat::Json,
+            ExportFormat::Json => ExportFormat::Csv,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+const CSV_HEADER: &str = "pid,parent,name,cpu,mem_bytes,mem_percent,status,user,cmd";
+
+/// Quote a CSV field if it contains a separator, quote or newline
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn to_csv(processes: &[&ProcessInfo]) -> String {
+    let mut output = String::from(CSV_HEADER);
+    output.push('\n');
+    for process in processes {
+        let fields = [
+            process.pid.to_string(),
+            process.parent_pid.map(|p| p.to_string()).unwrap_or_default(),
+            csv_field(&process.name),
+            format!("{:.1}", process.cpu_usage),
+            process.memory_bytes.to_string(),
+            format!("{:.2}", process.memory_percent),
+            csv_field(&process.status),
+            csv_field(process.user.as_deref().unwrap_or("")),
+            csv_field(&process.cmd.join(" ")),
+        ];
+        output.push_str(&fields.join(","));
+        output.push('\n');
+    }
+    output
+}
+
+pub fn to_json(processes: &[&ProcessInfo]) -> String {
+    let values: Vec<serde_json::Value> = processes.iter()
+        .map(|process| serde_json::json!({
+            "pid": process.pid,
+            "parent": process.parent_pid,
+            "name": process.name,
+            "cpu": process.cpu_usage,
+            "mem_bytes": process.memory_bytes,
+            "mem_percent": process.memory_percent,
+            "status": process.status,
+            "user": process.user,
+            "cmd": process.cmd,
+        }))
+        .collect();
+    serde_json::to_string_pretty(&values).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Write `processes` to `dir/senterm-processes-<timestamp>.<ext>`
+pub fn export_processes(processes: &[&ProcessInfo], format: ExportFormat, dir: &Path) -> std::io::Result<PathBuf> {
+    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let path = dir.join(format!("senterm-processes-{}.{}", timestamp, format.extension()));
+    let content = match format {
+        ExportFormat::Csv => to_csv(processes),
+        ExportFormat::Json => to_json(processes),
+    };
+    std::fs::write(&path, content)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    fn sample() -> ProcessInfo {
+        ProcessInfo {
+            pid: 42,
+            parent_pid: Some(1),
+            name: "web, server".to_string(),
+            cmd: vec!["/usr/bin/web".to_string(), "--name=\"x\"".to_string()],
+            cpu_usage: 12.5,
+            memory_bytes: 2048,
+            memory_percent: 0.5,
+            status: "Running".to_string(),
+            user: None,
+            start_time: 0,
+            children: Vec::new(),
+            is_expanded: true,
+            cpu_history: VecDeque::new(),
+            mem_history: VecDeque::new(),
+        }
+    }
+
+    #[test]
+    fn test_csv_quotes_fields() {
+        let process = sample();
+        let csv = to_csv(&[&process]);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(CSV_HEADER));
+        assert_eq!(
+            lines.next(),
+            Some("42,1,\"web, server\",12.5,2048,0.50,Running,,\"/usr/bin/web --name=\"\"x\"\"\"")
+        );
+    }
+
+    #[test]
+    fn test_json_keeps_cmd_as_array() {
+        let process = sample();
+        let value: serde_json::Value = serde_json::from_str(&to_json(&[&process])).unwrap();
+        assert_eq!(value[0]["pid"], 42);
+        assert_eq!(value[0]["cmd"][1], "--name=\"x\"");
+        assert!(value[0]["user"].is_null());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = export_processes(&[&process], ExportFormat::Json, dir.path()).unwrap();
+        assert_eq!(path.extension().unwrap(), "json");
+        assert!(path.file_name().unwrap().to_string_lossy().starts_with("senterm-processes-"));
+    }
+}
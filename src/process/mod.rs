@@ -1,3 +1,7 @@
+pub mod export;
+pub mod priority;
+pub mod resources;
+
 use sysinfo::{Pid, Process, ProcessStatus, System, ProcessRefreshKind};
 use std::collections::{HashMap, VecDeque};
 use std::time::Instant;
@@ -34,6 +38,36 @@ impl ProcessFilter {
     }
 }
 
+/// Steps cycled by the CPU threshold key ('c'), in percent
+pub const CPU_THRESHOLDS: [f32; 6] = [0.0, 1.0, 5.0, 10.0, 25.0, 50.0];
+/// Steps cycled by the memory threshold key ('m'), in percent
+pub const MEM_THRESHOLDS: [f32; 5] = [0.0, 0.5, 1.0, 5.0, 10.0];
+
+/// Next step after `current`, wrapping back to the first (off)
+fn next_threshold(steps: &[f32], current: f32) -> f32 {
+    steps.iter().copied().find(|&step| step > current).unwrap_or(steps[0])
+}
+
+/// Add the ancestors of `matching` that are `eligible`, so the tree stays
+/// connected when a child passes a filter its parent doesn't
+fn with_ancestors(
+    matching: Vec<u32>,
+    parent_of: impl Fn(u32) -> Option<u32>,
+    eligible: impl Fn(u32) -> bool,
+) -> Vec<u32> {
+    let mut result: std::collections::HashSet<u32> = matching.iter().copied().collect();
+    for pid in matching {
+        let mut current = pid;
+        while let Some(parent) = parent_of(current) {
+            if !eligible(parent) || !result.insert(parent) {
+                break;
+            }
+            current = parent;
+        }
+    }
+    result.into_iter().collect()
+}
+
 /// Process sort field
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProcessSort {
@@ -147,6 +181,24 @@ impl ProcessInfo {
     }
 }
 
+/// System-wide load shown above the process list
+#[derive(Debug, Clone, Default)]
+pub struct SystemSummary {
+    pub cpu_percent: f32,
+    pub core_usage: Vec<f32>,
+    pub used_memory: u64,
+    pub total_memory: u64,
+}
+
+impl SystemSummary {
+    pub fn memory_percent(&self) -> f32 {
+        if self.total_memory == 0 {
+            return 0.0;
+        }
+        (self.used_memory as f64 / self.total_memory as f64 * 100.0) as f32
+    }
+}
+
 /// Process tree viewer state
 pub struct ProcessViewer {
     pub sys: System,
@@ -163,6 +215,23 @@ pub struct ProcessViewer {
     pub show_details: bool,
     pub last_refresh: Instant,
     pub current_user_id: Option<String>,
+    /// Hide processes below these usages (0 = off); ancestors of busy processes stay visible
+    pub min_cpu: f32,
+    pub min_mem_percent: f32,
+    /// Total CPU, per-core and memory usage from the last refresh
+    pub summary: SystemSummary,
+    /// PID awaiting confirmation of a force kill (SIGKILL)
+    pub confirm_kill: Option<u32>,
+    /// Show open files / ports in the details panel ('o')
+    pub show_resources: bool,
+    /// Resources of the selected process, fetched lazily and kept until the selection changes
+    pub resources_cache: Option<(u32, Option<resources::ProcessResources>)>,
+    /// Format used by 'E' (toggled with 'J')
+    pub export_format: export::ExportFormat,
+    /// Process chosen with 'n' and the nice value being typed for it
+    pub renice_input: Option<(u32, String)>,
+    /// Result of the last action, shown in the footer
+    pub message: Option<String>,
 }
 
 impl ProcessViewer {
@@ -193,6 +262,15 @@ impl ProcessViewer {
             show_details: true,
             last_refresh: Instant::now(),
             current_user_id,
+            min_cpu: 0.0,
+            min_mem_percent: 0.0,
+            summary: SystemSummary::default(),
+            show_resources: false,
+            resources_cache: None,
+            export_format: export::ExportFormat::default(),
+            confirm_kill: None,
+            renice_input: None,
+            message: None,
         };
         
         viewer.refresh();
@@ -203,6 +281,13 @@ impl ProcessViewer {
     pub fn refresh(&mut self) {
         // Refresh CPU info first for accurate readings
         self.sys.refresh_cpu_all();
+        self.sys.refresh_memory();
+        self.summary = SystemSummary {
+            cpu_percent: self.sys.global_cpu_usage(),
+            core_usage: self.sys.cpus().iter().map(|cpu| cpu.cpu_usage()).collect(),
+            used_memory: self.sys.used_memory(),
+            total_memory: self.sys.total_memory(),
+        };
         
         self.sys.refresh_processes_specifics(
             sysinfo::ProcessesToUpdate::All,
@@ -251,11 +336,21 @@ impl ProcessViewer {
     
     /// Rebuild the tree display order based on filter and sort
     fn rebuild_tree_order(&mut self) {
-        let mut filtered: Vec<u32> = self.processes.keys()
+        let busy: Vec<u32> = self.processes.keys()
             .filter(|pid| self.filter_process(**pid))
             .filter(|pid| self.search_filter(**pid))
+            .filter(|pid| self.threshold_filter(**pid))
             .copied()
             .collect();
+        let mut filtered = if self.min_cpu > 0.0 || self.min_mem_percent > 0.0 {
+            with_ancestors(
+                busy,
+                |pid| self.processes.get(&pid).and_then(|p| p.parent_pid),
+                |pid| self.processes.contains_key(&pid) && self.filter_process(pid) && self.search_filter(pid),
+            )
+        } else {
+            busy
+        };
         
         // Sort
         filtered.sort_by(|a, b| {
@@ -370,6 +465,26 @@ impl ProcessViewer {
         }
     }
     
+    fn threshold_filter(&self, pid: u32) -> bool {
+        self.processes.get(&pid).is_some_and(|info| {
+            info.cpu_usage >= self.min_cpu && info.memory_percent >= self.min_mem_percent
+        })
+    }
+
+    /// Cycle the minimum CPU% threshold
+    pub fn cycle_cpu_threshold(&mut self) {
+        self.min_cpu = next_threshold(&CPU_THRESHOLDS, self.min_cpu);
+        self.rebuild_tree_order();
+        self.selected_index = 0;
+    }
+
+    /// Cycle the minimum memory% threshold
+    pub fn cycle_mem_threshold(&mut self) {
+        self.min_mem_percent = next_threshold(&MEM_THRESHOLDS, self.min_mem_percent);
+        self.rebuild_tree_order();
+        self.selected_index = 0;
+    }
+    
     fn search_filter(&self, pid: u32) -> bool {
         if self.search_query.is_empty() {
             return true;
@@ -475,6 +590,27 @@ impl ProcessViewer {
         }
     }
     
+    /// Collapse or expand every node, keeping the cursor on the selected
+    /// process or, if that got hidden, its nearest visible ancestor
+    pub fn set_all_expanded(&mut self, expanded: bool) {
+        let selected = self.tree_order.get(self.selected_index).copied();
+        for info in self.processes.values_mut() {
+            info.is_expanded = expanded;
+        }
+        self.rebuild_tree_order();
+
+        let mut current = selected;
+        while let Some(pid) = current {
+            if let Some(idx) = self.tree_order.iter().position(|p| *p == pid) {
+                self.selected_index = idx;
+                break;
+            }
+            current = self.processes.get(&pid).and_then(|p| p.parent_pid);
+        }
+        self.selected_index = self.selected_index.min(self.tree_order.len().saturating_sub(1));
+        self.ensure_visible();
+    }
+    
     /// Move to parent process
     pub fn move_to_parent(&mut self) {
         if let Some(pid) = self.tree_order.get(self.selected_index).copied() {
@@ -489,27 +625,90 @@ impl ProcessViewer {
         }
     }
     
-    /// Kill selected process
-    pub fn kill_selected(&mut self, force: bool) -> Result<(), String> {
-        if let Some(pid) = self.tree_order.get(self.selected_index).copied() {
-            let pid_sysinfo = Pid::from_u32(pid);
-            
-            if let Some(process) = self.sys.process(pid_sysinfo) {
-                if force {
-                    process.kill();
-                    Ok(())
-                } else {
-                    // SIGTERM
-                    process.kill();
-                    Ok(())
-                }
-            } else {
-                Err(format!("Process {} not found", pid))
-            }
+    /// Kill selected process: SIGTERM, or SIGKILL when `force` is set
+    /// Returns a description of what was sent on success.
+    pub fn kill_selected(&mut self, force: bool) -> Result<String, String> {
+        match self.tree_order.get(self.selected_index).copied() {
+            Some(pid) => self.kill_pid(pid, force),
+            None => Err("No process selected".to_string()),
+        }
+    }
+
+    /// Send SIGTERM (or SIGKILL when `force` is set) to `pid`
+    pub fn kill_pid(&mut self, pid: u32, force: bool) -> Result<String, String> {
+        let Some(process) = self.sys.process(Pid::from_u32(pid)) else {
+            return Err(format!("Process {} not found", pid));
+        };
+        let name = process.name().to_string_lossy().to_string();
+
+        let (signal, label) = if force {
+            (sysinfo::Signal::Kill, "SIGKILL")
+        } else {
+            (sysinfo::Signal::Term, "SIGTERM")
+        };
+        // Platforms without the signal (Windows has no SIGTERM) fall back to kill()
+        let (sent, label) = match process.kill_with(signal) {
+            Some(sent) => (sent, label),
+            None => (process.kill(), "kill"),
+        };
+
+        if sent {
+            Ok(format!("Sent {} to {} ({})", label, name, pid))
         } else {
-            Err("No process selected".to_string())
+            let reason = std::io::Error::last_os_error();
+            Err(format!("{} to {} ({}) failed: {}", label, name, pid, reason))
         }
     }
+
+    /// Open the nice value prompt for the selected process, pre-filled with the current value
+    pub fn start_renice(&mut self) {
+        if let Some(process) = self.selected_process() {
+            let current = priority::get_nice(process.pid).map(|n| n.to_string()).unwrap_or_default();
+            self.renice_input = Some((process.pid, current));
+        }
+    }
+
+    /// Apply a typed nice value to the process `pid` (the one the prompt was opened for,
+    /// even if the list has moved since)
+    pub fn renice(&mut self, pid: u32, input: &str) -> Result<String, String> {
+        let value = priority::parse_nice(input)?;
+        let name = self.processes.get(&pid).map(|process| process.name.clone()).unwrap_or_default();
+        priority::set_nice(pid, value)
+            .map(|_| format!("Set nice value of {} ({}) to {}", name, pid, value))
+            .map_err(|e| format!("Renice {} ({}) failed: {}", name, pid, e))
+    }
+
+    /// Fetch open files / ports for the selected process if not cached yet
+    pub fn update_resources(&mut self) {
+        if !self.show_resources {
+            return;
+        }
+        let Some(pid) = self.tree_order.get(self.selected_index).copied() else { return };
+        if self.resources_cache.as_ref().is_some_and(|(cached, _)| *cached == pid) {
+            return;
+        }
+        self.resources_cache = Some((pid, resources::fetch(pid)));
+    }
+
+    /// Cached resources for the selected process
+    pub fn selected_resources(&self) -> Option<&Option<resources::ProcessResources>> {
+        let pid = self.tree_order.get(self.selected_index)?;
+        self.resources_cache.as_ref().filter(|(cached, _)| cached == pid).map(|(_, resources)| resources)
+    }
+
+    /// Write the filtered, sorted list to the home directory; returns the file path
+    pub fn export(&self) -> Result<std::path::PathBuf, String> {
+        let dir = dirs::home_dir().ok_or("home directory not found")?;
+        let processes: Vec<&ProcessInfo> = self.tree_order.iter()
+            .filter_map(|pid| self.processes.get(pid))
+            .collect();
+        export::export_processes(&processes, self.export_format, &dir).map_err(|e| e.to_string())
+    }
+
+    /// Ask for confirmation before force-killing the selected process
+    pub fn request_force_kill(&mut self) {
+        self.confirm_kill = self.tree_order.get(self.selected_index).copied();
+    }
     
     /// Cycle filter
     pub fn cycle_filter(&mut self) {
@@ -564,3 +763,25 @@ impl std::fmt::Debug for ProcessViewer {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_threshold_wraps() {
+        assert_eq!(next_threshold(&CPU_THRESHOLDS, 0.0), 1.0);
+        assert_eq!(next_threshold(&CPU_THRESHOLDS, 25.0), 50.0);
+        assert_eq!(next_threshold(&CPU_THRESHOLDS, 50.0), 0.0);
+    }
+
+    #[test]
+    fn test_with_ancestors_keeps_tree_connected() {
+        // 1 -> 10 -> 100 (busy), 1 -> 20 (idle), 30 is filtered out by name
+        let parents = HashMap::from([(10, 1), (100, 10), (20, 1), (200, 30)]);
+        let parent_of = |pid: u32| parents.get(&pid).copied();
+
+        let mut pids = with_ancestors(vec![100, 200], parent_of, |pid| pid != 30);
+        pids.sort();
+        assert_eq!(pids, vec![1, 10, 100, 200]);
+    }
+}
@@ -0,0 +1,95 @@
+//! Process priority (nice value) lookup and adjustment
+//!
+//! Unix only; other platforms report the operation as unsupported.
+
+/// Valid nice values, from highest to lowest priority
+pub const NICE_RANGE: std::ops::RangeInclusive<i32> = -20..=19;
+
+/// Parse a typed nice value, checking it is in `NICE_RANGE`
+pub fn parse_nice(input: &str) -> Result<i32, String> {
+    let value: i32 = input.trim().parse()
+        .map_err(|_| format!("'{}' is not a number", input.trim()))?;
+    if NICE_RANGE.contains(&value) {
+        Ok(value)
+    } else {
+        Err(format!("nice value must be between {} and {}", NICE_RANGE.start(), NICE_RANGE.end()))
+    }
+}
+
+#[cfg(unix)]
+fn clear_errno() {
+    // getpriority() may legitimately return -1, so errno is the only error signal
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    unsafe { *libc::__errno_location() = 0 };
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+    unsafe { *libc::__error() = 0 };
+}
+
+/// Current nice value of `pid`, or None if it can't be read
+#[cfg(unix)]
+pub fn get_nice(pid: u32) -> Option<i32> {
+    clear_errno();
+    let value = unsafe { libc::getpriority(libc::PRIO_PROCESS, pid as libc::id_t) };
+    if value == -1 && std::io::Error::last_os_error().raw_os_error().unwrap_or(0) != 0 {
+        return None;
+    }
+    Some(value)
+}
+
+#[cfg(not(unix))]
+pub fn get_nice(_pid: u32) -> Option<i32> {
+    None
+}
+
+/// Set the nice value of `pid`
+/// Lowering the value (raising priority) usually needs root; that failure is
+/// reported as "operation not permitted" rather than a raw errno.
+#[cfg(unix)]
+pub fn set_nice(pid: u32, value: i32) -> Result<(), String> {
+    if !NICE_RANGE.contains(&value) {
+        return Err(format!("nice value must be between {} and {}", NICE_RANGE.start(), NICE_RANGE.end()));
+    }
+    let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, value) };
+    if result == 0 {
+        return Ok(());
+    }
+
+    let error = std::io::Error::last_os_error();
+    match error.raw_os_error() {
+        Some(libc::EPERM) | Some(libc::EACCES) => {
+            Err("operation not permitted (lowering the nice value requires root)".to_string())
+        },
+        Some(libc::ESRCH) => Err(format!("process {} not found", pid)),
+        _ => Err(error.to_string()),
+    }
+}
+
+#[cfg(not(unix))]
+pub fn set_nice(_pid: u32, _value: i32) -> Result<(), String> {
+    Err("changing priority is not supported on this platform".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nice() {
+        assert_eq!(parse_nice(" -5 "), Ok(-5));
+        assert_eq!(parse_nice("19"), Ok(19));
+        assert!(parse_nice("20").is_err());
+        assert!(parse_nice("-21").is_err());
+        assert!(parse_nice("abc").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_get_and_set_own_nice() {
+        let pid = std::process::id();
+        let current = get_nice(pid).unwrap();
+        // Re-applying the current value is always permitted
+        assert_eq!(set_nice(pid, current), Ok(()));
+        assert_eq!(get_nice(pid), Some(current));
+        assert!(set_nice(pid, 42).is_err());
+    }
+}
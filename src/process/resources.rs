@@ -0,0 +1,137 @@
+//! Open file descriptors and network ports of a single process
+//!
+//! Linux only: reads `/proc/<pid>/fd` and matches socket inodes against
+//! `/proc/<pid>/net/{tcp,tcp6,udp,udp6}`. Other platforms report nothing.
+
+/// Open files and sockets of one process
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessResources {
+    /// Number of open file descriptors (None if permission was denied)
+    pub open_fds: Option<usize>,
+    /// Ports owned by the process, e.g. "TCP 127.0.0.1:8080 LISTEN"
+    pub ports: Vec<String>,
+}
+
+/// Read the resources of `pid`; None where this isn't supported
+#[cfg(target_os = "linux")]
+pub fn fetch(pid: u32) -> Option<ProcessResources> {
+    use std::collections::HashSet;
+
+    let proc_dir = std::path::PathBuf::from(format!("/proc/{}", pid));
+    let fds: Option<Vec<std::path::PathBuf>> = std::fs::read_dir(proc_dir.join("fd"))
+        .ok()
+        .map(|entries| entries.flatten().map(|e| e.path()).collect());
+
+    // Sockets show up as fd links to "socket:[<inode>]"
+    let socket_inodes: HashSet<u64> = fds.iter()
+        .flatten()
+        .filter_map(|fd| std::fs::read_link(fd).ok())
+        .filter_map(|target| {
+            let target = target.to_string_lossy().to_string();
+            target.strip_prefix("socket:[")?.strip_suffix(']')?.parse().ok()
+        })
+        .collect();
+
+    let mut ports = Vec::new();
+    if !socket_inodes.is_empty() {
+        for (file, protocol) in [("tcp", "TCP"), ("tcp6", "TCP6"), ("udp", "UDP"), ("udp6", "UDP6")] {
+            let Ok(table) = std::fs::read_to_string(proc_dir.join("net").join(file)) else { continue };
+            ports.extend(parse_socket_table(&table, protocol, &socket_inodes));
+        }
+    }
+
+    Some(ProcessResources { open_fds: fds.map(|f| f.len()), ports })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn fetch(_pid: u32) -> Option<ProcessResources> {
+    None
+}
+
+/// Entries of a /proc/net/{tcp,udp}[6] table whose inode is in `inodes`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_socket_table(table: &str, protocol: &str, inodes: &std::collections::HashSet<u64>) -> Vec<String> {
+    table.lines()
+        .skip(1) // column header
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // sl local_address rem_address st tx:rx tr:when retrnsmt uid timeout inode
+            let inode: u64 = fields.get(9)?.parse().ok()?;
+            if !inodes.contains(&inode) {
+                return None;
+            }
+            let local = parse_address(fields.get(1)?)?;
+            let state = if protocol.starts_with("TCP") { tcp_state(fields.get(3)?) } else { "" };
+            Some(format!("{} {} {}", protocol, local, state).trim_end().to_string())
+        })
+        .collect()
+}
+
+/// Decode "0100007F:1F90" (IPv4) or the 32-hex-digit IPv6 form into "ip:port"
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_address(hex: &str) -> Option<String> {
+    let (addr, port) = hex.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    // The kernel prints each 32-bit word in host (little-endian) order
+    let words: Vec<u32> = (0..addr.len() / 8)
+        .map(|i| u32::from_str_radix(&addr[i * 8..i * 8 + 8], 16))
+        .collect::<Result<_, _>>()
+        .ok()?;
+    match words.as_slice() {
+        [v4] => Some(format!("{}:{}", std::net::Ipv4Addr::from(v4.to_le_bytes()), port)),
+        [a, b, c, d] => {
+            let mut bytes = [0u8; 16];
+            for (chunk, word) in bytes.chunks_mut(4).zip([a, b, c, d]) {
+                chunk.copy_from_slice(&word.to_le_bytes());
+            }
+            Some(format!("[{}]:{}", std::net::Ipv6Addr::from(bytes), port))
+        },
+        _ => None,
+    }
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn tcp_state(hex: &str) -> &'static str {
+    match hex {
+        "01" => "ESTABLISHED",
+        "02" => "SYN_SENT",
+        "03" => "SYN_RECV",
+        "04" => "FIN_WAIT1",
+        "05" => "FIN_WAIT2",
+        "06" => "TIME_WAIT",
+        "07" => "CLOSE",
+        "08" => "CLOSE_WAIT",
+        "09" => "LAST_ACK",
+        "0A" => "LISTEN",
+        "0B" => "CLOSING",
+        _ => "UNKNOWN",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_parse_socket_table() {
+        let table = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n\
+            \x20  0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 5555 1\n\
+            \x20  1: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 7777 1\n";
+        let inodes = HashSet::from([5555]);
+        assert_eq!(parse_socket_table(table, "TCP", &inodes), vec!["TCP 127.0.0.1:8080 LISTEN".to_string()]);
+
+        assert_eq!(
+            parse_address("00000000000000000000000001000000:0050"),
+            Some("[::1]:80".to_string())
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_fetch_own_process() {
+        let resources = fetch(std::process::id()).unwrap();
+        // stdin/stdout/stderr at least
+        assert!(resources.open_fds.unwrap() >= 3);
+    }
+}
@@ -1,7 +1,11 @@
-use sysinfo::{CpuRefreshKind, MemoryRefreshKind, RefreshKind, System};
+use std::path::{Path, PathBuf};
+
+use sysinfo::{CpuRefreshKind, DiskRefreshKind, Disks, MemoryRefreshKind, RefreshKind, System};
 
 pub struct SystemManager {
     pub sys: System,
+    disks: Disks,
+    disks_listed_for: Option<PathBuf>, // Directory the mount list was last read for
 }
 
 impl SystemManager {
@@ -10,7 +14,24 @@ impl SystemManager {
             sys: System::new_with_specifics(
                 RefreshKind::nothing().with_cpu(CpuRefreshKind::everything()).with_memory(MemoryRefreshKind::everything()),
             ),
+            disks: Disks::new(),
+            disks_listed_for: None,
+        }
+    }
+
+    /// Free and total bytes of the filesystem holding `path` (the disk with the longest matching mount point)
+    /// The mount list is read again only when `path` changes; otherwise just that disk is queried.
+    pub fn disk_space(&mut self, path: &Path) -> Option<(u64, u64)> {
+        if self.disks_listed_for.as_deref() != Some(path) {
+            self.disks.refresh_specifics(true, DiskRefreshKind::nothing());
+            self.disks_listed_for = Some(path.to_path_buf());
         }
+        let disk = self.disks.list_mut()
+            .iter_mut()
+            .filter(|disk| path.starts_with(disk.mount_point()))
+            .max_by_key(|disk| disk.mount_point().as_os_str().len())?;
+        disk.refresh_specifics(DiskRefreshKind::nothing().with_storage());
+        Some((disk.available_space(), disk.total_space()))
     }
 
     pub fn refresh(&mut self) {
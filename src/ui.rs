@@ -30,6 +30,77 @@ fn truncate_path(path: &str, max_len: usize) -> String {
     }
 }
 
+// Helper function to format a byte count for display
+pub fn format_bytes(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else if bytes < 1024 * 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{:.2} GB", bytes as f64 / (1024.0 * 1024.0 * 1024.0))
+    }
+}
+
+// Helper function to format how long ago something happened ("3d ago")
+fn format_relative_time(elapsed: std::time::Duration) -> String {
+    let secs = elapsed.as_secs();
+    match secs {
+        0..=59 => "now".to_string(),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86_399 => format!("{}h ago", secs / 3600),
+        86_400..=2_591_999 => format!("{}d ago", secs / 86_400),
+        2_592_000..=31_535_999 => format!("{}mo ago", secs / 2_592_000),
+        _ => format!("{}y ago", secs / 31_536_000),
+    }
+}
+
+// " → target" for symlinks, empty otherwise
+fn symlink_suffix(path: &std::path::Path) -> String {
+    crate::fs::symlink_target(path)
+        .map(|target| format!(" → {}", target.display()))
+        .unwrap_or_default()
+}
+
+/// Minimum column width for the detailed view; narrower columns fall back to names only
+const DETAIL_MIN_WIDTH: usize = 24;
+
+// Size (or item count for directories) and modified time for the detailed view
+fn entry_details(path: &std::path::Path, is_dir: bool) -> String {
+    let size = if is_dir {
+        match std::fs::read_dir(path) {
+            Ok(entries) => format!("{} items", entries.count()),
+            Err(_) => "-".to_string(),
+        }
+    } else {
+        path.metadata().map(|m| format_bytes(m.len())).unwrap_or_else(|_| "-".to_string())
+    };
+    let modified = path.metadata()
+        .and_then(|m| m.modified())
+        .ok()
+        .map(|time| format_relative_time(time.elapsed().unwrap_or_default()))
+        .unwrap_or_default();
+
+    format!("{:>9} {:>7}", size, modified)
+}
+
+/// Size and age of a remote entry, from its listing (directories show no item count)
+fn listed_entry_details(meta: &crate::fs::backend::EntryMeta) -> String {
+    let size = if meta.is_dir { "-".to_string() } else { format_bytes(meta.len) };
+    let modified = meta.modified
+        .map(|time| format_relative_time(time.elapsed().unwrap_or_default()))
+        .unwrap_or_default();
+
+    format!("{:>9} {:>7}", size, modified)
+}
+
+// Name on the left and details right-aligned to `width` columns
+fn detailed_entry_line(name: &str, details: &str, width: usize) -> String {
+    let name_width = width.saturating_sub(details.chars().count() + 1);
+    format!("{:<name_width$} {}", truncate_str(name, name_width), details)
+}
+
 pub fn ui(f: &mut Frame, app: &App) {
     // Render background
     let theme = &app.config.theme;
@@ -53,14 +124,31 @@ pub fn ui(f: &mut Frame, app: &App) {
         draw_help_popup(f, app);
     }
 
-    if app.show_bookmarks {
-        draw_bookmarks_popup(f, app);
+    if let Some(list) = &app.bookmark_list {
+        draw_bookmarks_popup(f, app, list);
+    }
+
+    if let Some(list) = &app.jump_list {
+        draw_jump_list_popup(f, app, list);
+    }
+
+    if let Some(palette) = &app.command_palette {
+        draw_command_palette_popup(f, app, palette);
+    }
+
+    if let Some(view) = &app.disk_usage {
+        draw_disk_usage_popup(f, app, view);
     }
 
     // Render dialog popups
     if !matches!(app.dialog, crate::app::DialogMode::None) {
         draw_dialog_popup(f, app);
     }
+
+    // Render batch operation summary
+    if app.operation_summary.is_some() {
+        draw_summary_popup(f, app);
+    }
     
     // Render shell popup (above dialogs)
     if app.show_shell {
@@ -95,6 +183,7 @@ fn draw_help_popup(f: &mut Frame, app: &App) {
         ListItem::new("  [ / ]              : Switch Mode (Prev/Next)"),
         ListItem::new("  Q                  : Quit"),
         ListItem::new("  Ctrl+Shift+Alt+K   : Toggle Help"),
+        ListItem::new("  Ctrl+P             : Command Palette"),
         ListItem::new(""),
         ListItem::new(" FILE MANAGER"),
         ListItem::new(" ─────────────────────────────────────────────────────"),
@@ -102,11 +191,30 @@ fn draw_help_popup(f: &mut Frame, app: &App) {
         ListItem::new("  Arrow Keys         : Navigate"),
         ListItem::new("  ENTER              : Open Directory"),
         ListItem::new("  BACKSPACE          : Go to Parent Directory"),
-        ListItem::new("  /                  : Search Files"),
+        ListItem::new("  -                  : Toggle Previous Directory"),
+        ListItem::new("  /                  : Fuzzy Search Files"),
+        ListItem::new("  Ctrl+/             : Grep File Contents"),
         ListItem::new("  s                  : Cycle Sort (Name/Size/Date)"),
+        ListItem::new("  t                  : Run Project Task (npm/make/cargo/just)"),
+        ListItem::new("  N (Shift+n)        : New File/Folder From Template"),
         ListItem::new("  b                  : Bookmark Current Directory"),
-        ListItem::new("  B (Shift+b)        : Show Bookmarks"),
-        ListItem::new("  c/x/p              : Copy/Cut/Paste"),
+        ListItem::new("  B (Shift+b)        : Bookmark List (type to filter)"),
+        ListItem::new("  J (Shift+j)        : Jump List (frecent directories)"),
+        ListItem::new("  U (Shift+u)        : Disk Usage of Selected Directory"),
+        ListItem::new("  I (Shift+i)        : File Properties (permissions, owner, times)"),
+        ListItem::new("  .                  : Show/Hide Dotfiles"),
+        ListItem::new("  i                  : Toggle Detailed View (size/date)"),
+        ListItem::new("  o                  : Open With External Program"),
+        ListItem::new("  e                  : Extract Archive"),
+        ListItem::new("  =                  : Diff Marked File with Selection"),
+        ListItem::new("  g                  : Go To Path (Tab completes)"),
+        ListItem::new("  f                  : Type-Ahead Find"),
+        ListItem::new("  SPACE              : Mark/Unmark Entry"),
+        ListItem::new("  DEL / D            : Delete (trash if enabled) / Delete Permanently"),
+        ListItem::new("  u                  : Undo Last File Operation"),
+        ListItem::new("  c/x/p              : Copy/Cut/Paste (marked or selected)"),
+        ListItem::new("  y/Y                : Copy Path / File Name to Clipboard"),
+        ListItem::new("  z/Z                : Console cd Here / Go to Console Dir"),
         ListItem::new(""),
         ListItem::new(" SETTINGS"),
         ListItem::new(" ─────────────────────────────────────────────────────"),
@@ -120,53 +228,251 @@ fn draw_help_popup(f: &mut Frame, app: &App) {
     f.render_widget(list, area);
 }
 
-fn draw_bookmarks_popup(f: &mut Frame, app: &App) {
+fn draw_bookmarks_popup(f: &mut Frame, app: &App, list: &crate::app::BookmarkList) {
     let theme = &app.config.theme;
     let area = centered_rect(70, 60, f.area());
 
     f.render_widget(ratatui::widgets::Clear, area);
 
-    let bookmark_items: Vec<ListItem> = if app.config.bookmarks.is_empty() {
-        vec![
-            ListItem::new(""),
-            ListItem::new("  No bookmarks yet!"),
-            ListItem::new(""),
-            ListItem::new("  Press 'b' in File Manager to bookmark"),
-            ListItem::new("  the current directory."),
-            ListItem::new(""),
-        ]
+    let mut items = vec![
+        ListItem::new(format!(" Filter: {}█", list.filter)),
+        ListItem::new(" ─────────────────────────────────────────────────────"),
+    ];
+
+    let matches = list.matches(&app.config.bookmarks);
+    if app.config.bookmarks.is_empty() {
+        items.push(ListItem::new(""));
+        items.push(ListItem::new("  No bookmarks yet!"));
+        items.push(ListItem::new(""));
+        items.push(ListItem::new("  Press 'b' in File Manager to bookmark"));
+        items.push(ListItem::new("  the current directory."));
+    } else if matches.is_empty() {
+        items.push(ListItem::new("  No matching bookmarks"));
     } else {
-        let mut items = vec![
-            ListItem::new(" BOOKMARKED DIRECTORIES"),
-            ListItem::new(" ─────────────────────────────────────────────────────"),
-            ListItem::new(""),
-        ];
+        // Keep the highlighted row inside the window (borders, filter and hint rows excluded)
+        let max_visible = (area.height as usize).saturating_sub(7).max(1);
+        let start = list.selected.saturating_sub(max_visible - 1);
+        for (row, &idx) in matches.iter().enumerate().skip(start).take(max_visible) {
+            let bookmark = &app.config.bookmarks[idx];
+            let line = format!(
+                "  {:<20} {}",
+                truncate_str(&bookmark.name, 20),
+                truncate_path(&bookmark.path.display().to_string(), 50)
+            );
+            let style = if row == list.selected {
+                Style::default().fg(theme.selection_fg).bg(theme.selection_bg).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            items.push(ListItem::new(line).style(style));
+        }
+    }
 
-        for (idx, bookmark) in app.config.bookmarks.iter().enumerate().take(9) {
-            let path_str = bookmark.display().to_string();
-            items.push(ListItem::new(format!(
-                "  [{}] {}",
-                idx + 1,
-                truncate_path(&path_str, 50)
-            )));
+    // Hints pinned to the bottom row
+    let inner_height = area.height.saturating_sub(2) as usize;
+    while items.len() + 1 < inner_height {
+        items.push(ListItem::new(""));
+    }
+    items.push(ListItem::new("  Type: Filter  |  ↑/↓: Select  |  ENTER: Jump  |  DEL: Delete  |  ESC: Close")
+        .style(Style::default().fg(theme.border)));
+
+    let block = Block::default()
+        .title(format!(" BOOKMARKS ({}/{}) ", matches.len(), app.config.bookmarks.len()))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Thick)
+        .border_style(Style::default().fg(theme.accent_color))
+        .style(Style::default().bg(theme.bg).fg(theme.fg));
+
+    let list = List::new(items).block(block);
+    f.render_widget(list, area);
+}
+
+fn draw_jump_list_popup(f: &mut Frame, app: &App, list: &crate::app::JumpList) {
+    let theme = &app.config.theme;
+    let area = centered_rect(70, 60, f.area());
+
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let mut items = vec![
+        ListItem::new(format!(" Filter: {}█", list.filter)),
+        ListItem::new(" ─────────────────────────────────────────────────────"),
+    ];
+
+    let history = &app.config.dir_history;
+    let matches = list.matches(history, crate::app::unix_now());
+    if history.is_empty() {
+        items.push(ListItem::new(""));
+        items.push(ListItem::new("  No directory history yet!"));
+        items.push(ListItem::new(""));
+        items.push(ListItem::new("  Directories you enter are listed here,"));
+        items.push(ListItem::new("  most frequently and recently used first."));
+    } else if matches.is_empty() {
+        items.push(ListItem::new("  No matching directories"));
+    } else {
+        // Keep the highlighted row inside the window (borders, filter and hint rows excluded)
+        let max_visible = (area.height as usize).saturating_sub(7).max(1);
+        let start = list.selected.saturating_sub(max_visible - 1);
+        for (row, &idx) in matches.iter().enumerate().skip(start).take(max_visible) {
+            let visit = &history[idx];
+            let line = format!(
+                "  {:>5}  {}",
+                visit.visits,
+                truncate_path(&visit.path.display().to_string(), 60)
+            );
+            let style = if row == list.selected {
+                Style::default().fg(theme.selection_fg).bg(theme.selection_bg).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            items.push(ListItem::new(line).style(style));
         }
+    }
 
+    // Hints pinned to the bottom row
+    let inner_height = area.height.saturating_sub(2) as usize;
+    while items.len() + 1 < inner_height {
         items.push(ListItem::new(""));
-        items.push(ListItem::new(" ─────────────────────────────────────────────────────"));
-        items.push(ListItem::new("  1-9: Jump to bookmark  |  d+NUM: Delete bookmark"));
-        items.push(ListItem::new("  ESC/Q/B: Close"));
+    }
+    items.push(ListItem::new("  Type: Filter  |  ↑/↓: Select  |  ENTER: Jump  |  DEL: Forget  |  ESC: Close")
+        .style(Style::default().fg(theme.border)));
+
+    let block = Block::default()
+        .title(format!(" JUMP LIST ({}/{}) ", matches.len(), history.len()))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Thick)
+        .border_style(Style::default().fg(theme.accent_color))
+        .style(Style::default().bg(theme.bg).fg(theme.fg));
+
+    let list = List::new(items).block(block);
+    f.render_widget(list, area);
+}
+
+fn draw_disk_usage_popup(f: &mut Frame, app: &App, view: &crate::fs::du::DiskUsageView) {
+    const BAR_WIDTH: usize = 20;
+    let theme = &app.config.theme;
+    let area = centered_rect(80, 70, f.area());
 
-        items
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let entries = app.du_cache.get(&view.dir).map(Vec::as_slice).unwrap_or_default();
+    let total: u64 = entries.iter().map(|e| e.size).sum();
+    let mut items = vec![
+        ListItem::new(format!(" {}", truncate_path(&view.dir.display().to_string(), 70))),
+        ListItem::new(" ─────────────────────────────────────────────────────"),
+    ];
+
+    if let Some(job) = &view.job {
+        items.push(ListItem::new(""));
+        items.push(ListItem::new(format!("  Scanning... {} files", job.files_scanned())));
+    } else if entries.is_empty() {
+        items.push(ListItem::new("  Empty directory"));
+    } else {
+        // Bars are relative to the largest child, percentages to the whole directory
+        let largest = entries[0].size.max(1);
+        let max_visible = (area.height as usize).saturating_sub(7).max(1);
+        let start = view.selected.saturating_sub(max_visible - 1);
+        for (row, entry) in entries.iter().enumerate().skip(start).take(max_visible) {
+            let filled = (entry.size as u128 * BAR_WIDTH as u128 / largest as u128) as usize;
+            let percent = if total > 0 { entry.size as f64 * 100.0 / total as f64 } else { 0.0 };
+            let name = entry.path.file_name().unwrap_or_default().to_string_lossy();
+            let line = format!(
+                "  {:>10}  [{}{}] {:>5.1}%  {}{}",
+                format_bytes(entry.size),
+                "█".repeat(filled),
+                " ".repeat(BAR_WIDTH - filled),
+                percent,
+                name,
+                if entry.is_dir { "/" } else { "" }
+            );
+            let style = if row == view.selected {
+                Style::default().fg(theme.selection_fg).bg(theme.selection_bg).add_modifier(Modifier::BOLD)
+            } else if entry.is_dir {
+                Style::default().fg(theme.directory_fg)
+            } else {
+                Style::default()
+            };
+            items.push(ListItem::new(line).style(style));
+        }
+    }
+
+    // Hints pinned to the bottom row
+    let inner_height = area.height.saturating_sub(2) as usize;
+    while items.len() + 1 < inner_height {
+        items.push(ListItem::new(""));
+    }
+    items.push(ListItem::new("  ↑/↓: Select  |  ENTER: Open dir  |  BACKSPACE: Parent  |  r: Rescan  |  ESC: Close")
+        .style(Style::default().fg(theme.border)));
+
+    let title = if view.job.is_some() {
+        " DISK USAGE (scanning) ".to_string()
+    } else {
+        format!(" DISK USAGE ({} in {} items) ", format_bytes(total), entries.len())
     };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Thick)
+        .border_style(Style::default().fg(theme.accent_color))
+        .style(Style::default().bg(theme.bg).fg(theme.fg));
+
+    let list = List::new(items).block(block);
+    f.render_widget(list, area);
+}
+
+fn draw_command_palette_popup(f: &mut Frame, app: &App, palette: &crate::palette::CommandPalette) {
+    let theme = &app.config.theme;
+    let area = centered_rect(70, 60, f.area());
+
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let mut items = vec![
+        ListItem::new(format!(" > {}█", palette.filter)),
+        ListItem::new(" ─────────────────────────────────────────────────────"),
+    ];
+
+    let matches = palette.matches();
+    if matches.is_empty() {
+        items.push(ListItem::new("  No matching commands"));
+    } else {
+        // Keep the highlighted row inside the window (borders, filter and hint rows excluded)
+        let max_visible = (area.height as usize).saturating_sub(7).max(1);
+        let start = palette.selected.saturating_sub(max_visible - 1);
+        // Key column right-aligned against the border
+        let width = area.width.saturating_sub(4) as usize;
+        for (row, &idx) in matches.iter().enumerate().skip(start).take(max_visible) {
+            let entry = &palette.entries[idx];
+            let key = entry.keybinding.as_deref().unwrap_or("");
+            let text = format!("  {:<24} {}", truncate_str(&entry.name, 24), entry.description);
+            let text = truncate_str(&text, width.saturating_sub(key.chars().count() + 1));
+            let padding = width.saturating_sub(text.chars().count() + key.chars().count());
+            let line = format!("{}{}{}", text, " ".repeat(padding), key);
+            let style = if row == palette.selected {
+                Style::default().fg(theme.selection_fg).bg(theme.selection_bg).add_modifier(Modifier::BOLD)
+            } else if matches!(entry.command, crate::palette::PaletteCommand::Plugin { .. }) {
+                Style::default().fg(theme.accent_color)
+            } else {
+                Style::default()
+            };
+            items.push(ListItem::new(line).style(style));
+        }
+    }
+
+    // Hints pinned to the bottom row
+    let inner_height = area.height.saturating_sub(2) as usize;
+    while items.len() + 1 < inner_height {
+        items.push(ListItem::new(""));
+    }
+    items.push(ListItem::new("  Type: Filter  |  ↑/↓: Select  |  ENTER: Run  |  ESC: Close")
+        .style(Style::default().fg(theme.border)));
 
     let block = Block::default()
-        .title(" BOOKMARKS ")
+        .title(format!(" COMMAND PALETTE ({}/{}) ", matches.len(), palette.entries.len()))
         .borders(Borders::ALL)
         .border_type(BorderType::Thick)
         .border_style(Style::default().fg(theme.accent_color))
         .style(Style::default().bg(theme.bg).fg(theme.fg));
 
-    let list = List::new(bookmark_items).block(block);
+    let list = List::new(items).block(block);
     f.render_widget(list, area);
 }
 
@@ -223,6 +529,9 @@ fn draw_file_manager(f: &mut Frame, app: &App) {
     // Build panel indicators
     let mut panel_indicators = String::new();
     if app.show_console { panel_indicators.push_str(" │ [Console]"); }
+    if app.file_watcher.is_some() {
+        panel_indicators.push_str(&format!(" │ [Watch:{}]", app.watch_count()));
+    }
     
     let header_text = format!("{} │ {}{}{}", title_prefix, pane_indicator, truncate_path(&path_str, 50), panel_indicators);
     let header = Paragraph::new(header_text)
@@ -230,7 +539,7 @@ fn draw_file_manager(f: &mut Frame, app: &App) {
     f.render_widget(header, chunks[0]);
 
     // Determine layout based on which panels are shown
-    // - Console open: 2-way split (Tree 60% | Console 40%)
+    // - Console open: 2-way split (Tree | Console, 60/40 unless resized)
     // - No panels: Full tree
     let is_any_panel_focused = app.console_focus;
     
@@ -239,8 +548,8 @@ fn draw_file_manager(f: &mut Frame, app: &App) {
         let main_split = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([
-                Constraint::Percentage(60),  // File manager area
-                Constraint::Percentage(40),  // Console panel
+                Constraint::Percentage(100 - app.config.split_ratios.console_percent()),  // File manager area
+                Constraint::Percentage(app.config.split_ratios.console_percent()),        // Console panel
             ])
             .split(chunks[1]);
         
@@ -253,35 +562,36 @@ fn draw_file_manager(f: &mut Frame, app: &App) {
     };
 
     // Content area - split or single pane (file manager)
+    // The active pane gets the preview column (show_preview), whatever has the focus
+    let draw_pane = |f: &mut Frame, fs: &crate::fs::FileSystem, area: ratatui::layout::Rect, pane: Pane| {
+        let preview = (app.active_pane == pane && app.config.show_preview).then_some(app.file_preview.as_ref());
+        let is_active = app.active_pane == pane && !is_any_panel_focused;
+        if let Some(preview_area) = draw_single_pane(f, fs, app.git_overlay.as_ref(), area, is_active, &app.config, preview) {
+            app.preview_area.set(preview_area);
+        }
+    };
     match app.pane_count {
         1 => {
-            draw_single_pane(f, &app.fs_left, content_area, !is_any_panel_focused, theme);
+            draw_pane(f, &app.fs_left, content_area, Pane::Left);
         },
         2 => {
             let panes = Layout::default()
                 .direction(Direction::Horizontal)
-                .constraints([
-                    Constraint::Percentage(50),
-                    Constraint::Percentage(50),
-                ])
+                .constraints(app.config.split_ratios.pane_widths(2).into_iter().map(Constraint::Percentage))
                 .split(content_area);
 
-            draw_single_pane(f, &app.fs_left, panes[0], app.active_pane == Pane::Left && !is_any_panel_focused, theme);
-            draw_single_pane(f, &app.fs_center, panes[1], app.active_pane == Pane::Center && !is_any_panel_focused, theme);
+            draw_pane(f, &app.fs_left, panes[0], Pane::Left);
+            draw_pane(f, &app.fs_center, panes[1], Pane::Center);
         },
         _ => {
             let panes = Layout::default()
                 .direction(Direction::Horizontal)
-                .constraints([
-                    Constraint::Percentage(33),
-                    Constraint::Percentage(34),
-                    Constraint::Percentage(33),
-                ])
+                .constraints(app.config.split_ratios.pane_widths(3).into_iter().map(Constraint::Percentage))
                 .split(content_area);
 
-            draw_single_pane(f, &app.fs_left, panes[0], app.active_pane == Pane::Left && !is_any_panel_focused, theme);
-            draw_single_pane(f, &app.fs_center, panes[1], app.active_pane == Pane::Center && !is_any_panel_focused, theme);
-            draw_single_pane(f, &app.fs_right, panes[2], app.active_pane == Pane::Right && !is_any_panel_focused, theme);
+            draw_pane(f, &app.fs_left, panes[0], Pane::Left);
+            draw_pane(f, &app.fs_center, panes[1], Pane::Center);
+            draw_pane(f, &app.fs_right, panes[2], Pane::Right);
         }
     }
 
@@ -297,17 +607,88 @@ fn draw_file_manager(f: &mut Frame, app: &App) {
         hints.push("Q:Quit");
         format!(" {}", hints.join(" │ "))
     };
+    // Background paste/extract progress replaces the regular status text
+    let background = app.paste_job.as_ref()
+        .map(|(_, job)| (&job.progress, if job.is_cancelling() { "Cancelling" } else { "Pasting" }))
+        .or_else(|| app.extract_job.as_ref()
+            .map(|(_, job)| (&job.progress, if job.is_cancelling() { "Cancelling" } else { "Extracting" })))
+        .or_else(|| app.url_fetch.as_ref()
+            .map(|job| (&job.progress, if job.is_cancelling() { "Cancelling" } else { "Downloading" })));
+    let status_text = if let Some((progress, action)) = background {
+        let width = 20usize;
+        let filled = (progress.percent() as usize * width) / 100;
+        format!(
+            " {} [{}{}] {:>3}% ({} / {}) {} │ Esc:Cancel",
+            action,
+            "█".repeat(filled),
+            "░".repeat(width - filled),
+            progress.percent(),
+            format_bytes(progress.copied_bytes),
+            format_bytes(progress.total_bytes),
+            progress.current
+        )
+    } else {
+        status_text
+    };
+    let marked_count = app.active_fs().marked.len();
+    let status_text = if marked_count > 0 {
+        format!(" {} selected │{}", marked_count, status_text)
+    } else {
+        status_text
+    };
 
     let status_block = Block::default().style(Style::default().bg(theme.footer_bg));
     f.render_widget(status_block, chunks[2]);
+
+    // Clock and disk space take the right end; the status text gets the rest
+    let info_text = footer_info_text(&app.footer_info);
+    let status_area = if info_text.is_empty() {
+        chunks[2]
+    } else {
+        let footer_split = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(info_text.chars().count() as u16)])
+            .split(chunks[2]);
+        let info = Paragraph::new(info_text)
+            .style(Style::default().fg(theme.footer_fg).bg(theme.footer_bg));
+        f.render_widget(info, footer_split[1]);
+        footer_split[0]
+    };
     
     let status = Paragraph::new(status_text)
         .style(Style::default().fg(theme.footer_fg).bg(theme.footer_bg));
-    f.render_widget(status, chunks[2]);
+    f.render_widget(status, status_area);
+}
+
+/// " 120.50 GB free / 500.00 GB │ 14:05 ", or empty if both segments are off
+fn footer_info_text(info: &crate::app::FooterInfo) -> String {
+    let mut segments = Vec::new();
+    if let Some((free, total)) = info.disk {
+        segments.push(format!("{} free / {}", format_bytes(free), format_bytes(total)));
+    }
+    if let Some(clock) = &info.clock {
+        segments.push(clock.clone());
+    }
+    if segments.is_empty() {
+        String::new()
+    } else {
+        format!(" │ {} ", segments.join(" │ "))
+    }
 }
 
 /// Draw a single file manager pane
-fn draw_single_pane(f: &mut Frame, fs: &crate::fs::FileSystem, area: ratatui::layout::Rect, is_active: bool, theme: &crate::config::Theme) {
+/// `preview` is Some for the pane with the preview column, holding the preview
+/// once loaded; the column's text area is returned when a file is selected.
+fn draw_single_pane(
+    f: &mut Frame,
+    fs: &crate::fs::FileSystem,
+    git: Option<&crate::fs::git::GitOverlay>,
+    area: ratatui::layout::Rect,
+    is_active: bool,
+    config: &crate::config::Config,
+    preview: Option<Option<&crate::viewer::preview::FilePreview>>,
+) -> Option<ratatui::layout::Rect> {
+    let theme = &config.theme;
     // Draw pane border first
     let pane_border_style = if is_active {
         Style::default().fg(theme.accent_color).add_modifier(Modifier::BOLD)
@@ -315,10 +696,15 @@ fn draw_single_pane(f: &mut Frame, fs: &crate::fs::FileSystem, area: ratatui::la
         Style::default().fg(Color::DarkGray)
     };
     
-    let pane_block = Block::default()
+    let mut pane_block = Block::default()
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(pane_border_style);
+    // Remote panes are titled with their host
+    let remote = fs.backend.remote_label();
+    if let Some(host) = &remote {
+        pane_block = pane_block.title(format!(" sftp://{} ", host));
+    }
     f.render_widget(pane_block, area);
     
     // Inner area for content
@@ -327,10 +713,11 @@ fn draw_single_pane(f: &mut Frame, fs: &crate::fs::FileSystem, area: ratatui::la
     // Build columns using navigation module
     let nav_columns = crate::navigation::calculate_visible_columns(fs, 5);
     let visible_path = &nav_columns.visible_path;
-    let total_columns = nav_columns.total_columns;
+    let preview_file = preview.filter(|_| remote.is_none()).and(fs.selected_entry()).filter(|path| path.is_file());
+    let total_columns = nav_columns.total_columns + usize::from(preview_file.is_some());
     
     if total_columns == 0 {
-        return;
+        return None;
     }
     
     // Create equal-width columns
@@ -348,7 +735,12 @@ fn draw_single_pane(f: &mut Frame, fs: &crate::fs::FileSystem, area: ratatui::la
     // Render each level in the visible path
     for (level, dir_path) in visible_path.iter().enumerate() {
         let is_active_column = col_idx == fs.active_column_index;
-        let entries = crate::fs::FileSystem::get_entries_for_dir_sorted(dir_path, fs.sort_option);
+        let entries = fs.entries_for_dir(dir_path);
+        // Git gutter is only shown for directories inside a repository
+        let show_git = remote.is_none() && git.is_some_and(|g| g.is_tracked_dir(dir_path));
+        // Width inside the column's left border (and git gutter)
+        let column_width = columns[col_idx].width.saturating_sub(if show_git { 3 } else { 1 }) as usize;
+        let show_details = fs.view_mode == crate::fs::ViewMode::Detailed && column_width >= DETAIL_MIN_WIDTH;
         
         let items: Vec<ListItem> = entries
             .iter()
@@ -366,8 +758,10 @@ fn draw_single_pane(f: &mut Frame, fs: &crate::fs::FileSystem, area: ratatui::la
                     path.file_name().unwrap_or_default().to_string_lossy().to_string()
                 };
 
-                let is_dir = path.is_dir();
-                let is_symlink = path.is_symlink();
+                // Remote entries come with their metadata; local ones are checked on disk
+                let remote_meta = remote.is_some().then(|| fs.entry_meta(path).unwrap_or_default());
+                let is_dir = remote_meta.map_or_else(|| path.is_dir(), |meta| meta.is_dir);
+                let is_symlink = remote_meta.map_or_else(|| path.is_symlink(), |meta| meta.is_symlink);
                 
                 let is_executable = if let Some(ext) = path.extension() {
                     let ext_str = ext.to_string_lossy().to_lowercase();
@@ -376,24 +770,49 @@ fn draw_single_pane(f: &mut Frame, fs: &crate::fs::FileSystem, area: ratatui::la
                     false
                 };
 
-                let icon = if is_dir { "■ " } else if is_symlink { "↗ " } else { "· " };
-                let display_text = format!("{} {}", icon, file_name);
+                let is_broken_link = remote_meta.is_none() && is_symlink && !path.exists();
+
+                let kind = if is_dir {
+                    crate::icons::EntryKind::Directory
+                } else if is_symlink {
+                    crate::icons::EntryKind::Symlink
+                } else if is_executable {
+                    crate::icons::EntryKind::Executable
+                } else {
+                    crate::icons::EntryKind::File
+                };
+                let icon = crate::icons::icon_for(path, kind, config.use_nerd_icons);
+                let mut name_text = format!("{} {}", icon, file_name);
+                if let Some(target) = remote_meta.is_none().then(|| crate::fs::symlink_target(path)).flatten() {
+                    name_text.push_str(&format!(" → {}", truncate_str(&target.to_string_lossy(), 30)));
+                }
+                let display_text = if show_details && !is_parent_entry {
+                    let details = match &remote_meta {
+                        Some(meta) => listed_entry_details(meta),
+                        None => entry_details(path, is_dir),
+                    };
+                    detailed_entry_line(&name_text, &details, column_width)
+                } else {
+                    name_text
+                };
 
                 let mut style = Style::default().bg(theme.bg);
 
-                if is_dir {
+                if is_broken_link {
+                    style = style.fg(Color::Red);
+                } else if is_dir {
                     style = style.fg(theme.directory_fg);
                 } else if is_symlink {
                     style = style.fg(theme.symlink_fg);
                 } else if is_executable {
                     style = style.fg(theme.executable_fg);
                 } else {
-                    style = style.fg(theme.file_fg);
+                    style = style.fg(config.ext_color(path).unwrap_or(theme.file_fg));
                 }
 
                 // Clipboard highlighting
-                if let Some((clip_path, op)) = &fs.clipboard {
-                    if clip_path == path {
+                if let Some((clip_paths, op)) = &fs.clipboard {
+                    if clip_paths.contains(path) {
                         match op {
                             crate::fs::ClipboardOperation::Copy => {
                                 style = style.fg(Color::Yellow);
@@ -405,6 +824,11 @@ fn draw_single_pane(f: &mut Frame, fs: &crate::fs::FileSystem, area: ratatui::la
                     }
                 }
 
+                // Marked entries (multi-selection)
+                if fs.marked.contains(path) {
+                    style = style.bg(Color::DarkGray).add_modifier(Modifier::BOLD);
+                }
+
                 // Selection highlighting
                 let current_selection = fs.get_selection(dir_path);
                 if idx == current_selection {
@@ -427,7 +851,15 @@ fn draw_single_pane(f: &mut Frame, fs: &crate::fs::FileSystem, area: ratatui::la
                     style = style.add_modifier(Modifier::DIM);
                 }
 
-                ListItem::new(display_text).style(style)
+                if show_git {
+                    let (marker, color) = git_marker(git.and_then(|g| g.state(path)));
+                    ListItem::new(ratatui::text::Line::from(vec![
+                        ratatui::text::Span::styled(marker, Style::default().fg(color)),
+                        ratatui::text::Span::raw(display_text),
+                    ])).style(style)
+                } else {
+                    ListItem::new(display_text).style(style)
+                }
             })
             .collect();
 
@@ -440,15 +872,53 @@ fn draw_single_pane(f: &mut Frame, fs: &crate::fs::FileSystem, area: ratatui::la
         };
 
         let dir_name = dir_path.file_name().unwrap_or_default().to_str().unwrap_or("...");
+        // The focused column shows the pane's sort, e.g. "src name↓"
+        let title = if is_active_column {
+            format!("{} {}", dir_name, fs.sort_label(dir_path))
+        } else {
+            dir_name.to_string()
+        };
         
         let list = List::new(items)
             .block(Block::default()
                 .borders(Borders::LEFT)
                 .border_style(border_style)
-                .title(dir_name));
+                .title(title));
         f.render_widget(list, columns[col_idx]);
         col_idx += 1;
     }
+
+    let path = preview_file?;
+    let block = Block::default()
+        .borders(Borders::LEFT)
+        .border_style(Style::default().fg(theme.border))
+        .title(path.file_name().unwrap_or_default().to_string_lossy().to_string());
+    let text_area = block.inner(columns[col_idx]);
+    f.render_widget(block, columns[col_idx]);
+    // A preview of the previous selection isn't shown while the new one loads
+    if let Some(preview) = preview.flatten().filter(|p| p.path == path) {
+        let style = if preview.is_content {
+            Style::default().fg(theme.fg)
+        } else {
+            Style::default().fg(theme.footer_fg)
+        };
+        let lines: Vec<ratatui::text::Line> = preview.lines.iter()
+            .map(|line| ratatui::text::Line::styled(crate::viewer::expand_tabs(line, 0, config.tab_width), style))
+            .collect();
+        f.render_widget(Paragraph::new(lines), text_area);
+    }
+    Some(text_area)
+}
+
+/// Gutter marker and color for an entry's git state
+fn git_marker(state: Option<crate::fs::git::GitState>) -> (&'static str, Color) {
+    use crate::fs::git::GitState;
+    match state {
+        Some(GitState::Modified) => ("● ", Color::Yellow),
+        Some(GitState::Staged) => ("+ ", Color::Cyan),
+        Some(GitState::Untracked) => ("? ", Color::Green),
+        None => ("  ", Color::Reset),
+    }
 }
 
 /// Draw the console panel on the right side (PTY passthrough)
@@ -466,8 +936,8 @@ fn draw_console_shell(f: &mut Frame, app: &App, area: ratatui::layout::Rect, the
     };
     
     // Title with working directory
-    let title = if app.console.is_running {
-        format!(" SHELL - {} ", truncate_path(&app.console.working_dir.display().to_string(), 30))
+    let title = if app.console().is_running {
+        format!(" SHELL - {} ", truncate_path(&app.console().display_dir().display().to_string(), 30))
     } else {
         " SHELL - Not Running ".to_string()
     };
@@ -485,72 +955,35 @@ fn draw_console_shell(f: &mut Frame, app: &App, area: ratatui::layout::Rect, the
     // Inner area
     let inner_area = area.inner(ratatui::layout::Margin { vertical: 1, horizontal: 1 });
     
-    // Split into terminal area and help line
+    // Split into tab strip (with several tabs), terminal area and help line
+    let tab_strip = u16::from(app.consoles.len() > 1);
     let layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
+            Constraint::Length(tab_strip), // Tab strip
             Constraint::Min(3),      // Terminal area
             Constraint::Length(1),   // Help line
         ])
         .split(inner_area);
     
-    let terminal_area = layout[0];
+    if tab_strip > 0 {
+        draw_console_tabs(f, app, layout[0], theme);
+    }
+    let terminal_area = layout[1];
     
-    // Render terminal content from vt100 parser
-    if let Ok(parser) = app.console.parser.try_lock() {
+    // Render terminal content from vt100 parser (the frozen copy in copy-mode)
+    let mut scrolled_back = 0;
+    let copy_mode = app.console().copy_mode.as_ref();
+    if let Some(copy) = copy_mode {
+        draw_vt100_rows(f, &copy.screen, terminal_area, theme, Some(copy));
+        f.set_cursor_position((terminal_area.x + copy.cursor.1, terminal_area.y + copy.cursor.0));
+    } else if let Ok(parser) = app.console().parser.try_lock() {
         let screen = parser.screen();
+        scrolled_back = screen.scrollback();
+        draw_vt100_rows(f, screen, terminal_area, theme, None);
         
-        // Render each row
-        for row in 0..terminal_area.height {
-            let row_idx = row as usize;
-            if row_idx >= screen.size().0 as usize {
-                break;
-            }
-            
-            let mut spans: Vec<ratatui::text::Span> = Vec::new();
-            let mut current_text = String::new();
-            let mut current_style = Style::default();
-            
-            for col in 0..terminal_area.width {
-                let col_idx = col as usize;
-                if col_idx >= screen.size().1 as usize {
-                    break;
-                }
-                
-                let cell = screen.cell(row_idx as u16, col_idx as u16);
-                
-                if let Some(cell) = cell {
-                    // Convert vt100 colors to ratatui colors with theme support
-                    let style = convert_vt100_style(&cell, theme);
-                    
-                    if style != current_style && !current_text.is_empty() {
-                        spans.push(ratatui::text::Span::styled(current_text.clone(), current_style));
-                        current_text.clear();
-                    }
-                    
-                    current_style = style;
-                    current_text.push(cell.contents().chars().next().unwrap_or(' '));
-                } else {
-                    current_text.push(' ');
-                }
-            }
-            
-            if !current_text.is_empty() {
-                spans.push(ratatui::text::Span::styled(current_text, current_style));
-            }
-            
-            let line = ratatui::text::Line::from(spans);
-            let para = Paragraph::new(line);
-            f.render_widget(para, ratatui::layout::Rect {
-                x: terminal_area.x,
-                y: terminal_area.y + row,
-                width: terminal_area.width,
-                height: 1,
-            });
-        }
-        
-        // Set cursor position from vt100 screen if console has focus
-        if app.console_focus && app.console.is_running {
+        // Set cursor position from vt100 screen if console has focus (not while scrolled back)
+        if app.console_focus && app.console().is_running && scrolled_back == 0 {
             let (cursor_row, cursor_col) = screen.cursor_position();
             let cursor_x = terminal_area.x + cursor_col;
             let cursor_y = terminal_area.y + cursor_row;
@@ -563,14 +996,37 @@ fn draw_console_shell(f: &mut Frame, app: &App, area: ratatui::layout::Rect, the
     }
     
     // Help line
-    let help_text = if app.console_focus {
-        " Esc:Unfocus | F5:Close "
+    let help_para = if copy_mode.is_some() {
+        copy_mode_indicator(theme)
+    } else if scrolled_back > 0 {
+        scrollback_indicator(scrolled_back, theme)
     } else {
-        " Tab:Focus | F5:Close "
+        let help_text = if app.console_focus {
+            " Esc:Unfocus | F6:New Tab | Ctrl+PgUp/PgDn:Tabs | Shift+PgUp:Scrollback | F7:Copy | F5:Close "
+        } else {
+            " Tab:Focus | F5:Close "
+        };
+        Paragraph::new(help_text).style(Style::default().fg(theme.footer_fg).bg(theme.footer_bg))
     };
-    let help_para = Paragraph::new(help_text)
-        .style(Style::default().fg(theme.footer_fg).bg(theme.footer_bg));
-    f.render_widget(help_para, layout[1]);
+    f.render_widget(help_para, layout[2]);
+}
+
+/// Tab strip of the console panel: " 1:src  2:docs " with the shown tab highlighted
+fn draw_console_tabs(f: &mut Frame, app: &App, area: ratatui::layout::Rect, theme: &crate::config::Theme) {
+    let spans: Vec<ratatui::text::Span> = app.consoles.iter()
+        .enumerate()
+        .map(|(i, console)| {
+            let dir = console.display_dir();
+            let name = dir.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| dir.display().to_string());
+            let style = if i == app.active_console {
+                Style::default().fg(theme.selection_fg).bg(theme.selection_bg).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.border)
+            };
+            ratatui::text::Span::styled(format!(" {}:{} ", i + 1, truncate_path(&name, 16)), style)
+        })
+        .collect();
+    f.render_widget(Paragraph::new(ratatui::text::Line::from(spans)), area);
 }
 
 fn draw_viewer_popup(f: &mut Frame, app: &App) {
@@ -607,20 +1063,64 @@ fn draw_viewer_popup(f: &mut Frame, app: &App) {
     // Header Content (Filename, etc.)
     let header_text = if let Some(editor) = &app.text_editor {
         if let Some(path) = &editor.file_path {
-             format!(" FILE: {} {}", path.display(), if editor.modified { "[+]" } else { "" })
+             let format = match (editor.line_ending, editor.trailing_newline) {
+                 (crate::viewer::editor::LineEnding::CrLf, true) => " [dos]",
+                 (crate::viewer::editor::LineEnding::CrLf, false) => " [dos][noeol]",
+                 (_, false) if editor.lines.iter().any(|l| !l.is_empty()) => " [noeol]",
+                 _ => "",
+             };
+             format!(" FILE: {}{}{} {}", path.display(), symlink_suffix(path), format, if editor.modified { "[+]" } else { "" })
         } else {
             " NEW FILE ".to_string()
         }
     } else if let Some(content) = &app.viewer_content {
         match content {
-            crate::viewer::ViewerContent::Image(path) => format!(" IMAGE: {}", path.display()),
+            crate::viewer::ViewerContent::Image(path) => format!(" IMAGE: {}{}", path.display(), symlink_suffix(path)),
+            crate::viewer::ViewerContent::Diff(diff) => format!(
+                " DIFF: {} ↔ {}  (+{} -{})",
+                diff.old_path.display(), diff.new_path.display(), diff.added, diff.removed
+            ),
+            crate::viewer::ViewerContent::LargeFile(file) => format!(
+                " FILE: {}{}  ({}, read in windows)", file.path.display(), symlink_suffix(&file.path), format_bytes(file.len)
+            ),
+            crate::viewer::ViewerContent::Table(_) => match &app.viewer_path {
+                Some(path) => format!(" TABLE: {}{}", path.display(), symlink_suffix(path)),
+                None => " TABLE ".to_string(),
+            },
             _ => " FILE VIEWER ".to_string(),
         }
     } else {
          " FILE VIEWER ".to_string()
     };
 
-    let header = Paragraph::new(header_text)
+    let mut header_spans = vec![ratatui::text::Span::raw(header_text)];
+    if let Some(follow) = &app.viewer_follow {
+        let label = if follow.paused { "  [follow paused — F to resume]" } else { "  [following]" };
+        header_spans.push(ratatui::text::Span::styled(label, Style::default().fg(Color::Green)));
+    }
+    let encoding = app.text_editor.as_ref().map_or(app.viewer_encoding, |editor| editor.encoding);
+    if let Some(encoding) = encoding {
+        header_spans.push(ratatui::text::Span::styled(
+            format!("  [{}]", encoding.label()),
+            Style::default().fg(theme.accent_color),
+        ));
+    }
+    if let Some(crate::viewer::ViewerContent::LargeFile(file)) = &app.viewer_content {
+        if !file.is_indexed() {
+            header_spans.push(ratatui::text::Span::styled(
+                format!("  [indexing lines {}%]", file.indexed_percent()),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+    }
+    if app.viewer_changed_on_disk {
+        let hint = if app.viewer_editing { ":e to reload" } else { "r to reload" };
+        header_spans.push(ratatui::text::Span::styled(
+            format!("  [changed on disk — {}]", hint),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    let header = Paragraph::new(ratatui::text::Line::from(header_spans))
         .style(Style::default().fg(theme.accent_color).add_modifier(Modifier::BOLD));
     f.render_widget(header, layout[0]);
 
@@ -632,15 +1132,26 @@ fn draw_viewer_popup(f: &mut Frame, app: &App) {
     let content_area = layout[2];
 
     // Check if in edit mode
-    if app.viewer_editing {
+    if let Some(editor) = &app.hex_editor {
+        app.viewer_area.set(content_area);
+        draw_hex_editor(f, editor, content_area, theme);
+        let status_text = format!(
+            " HEX EDIT | Offset 0x{:08X} ({}) of {} | {} modified | 0-9 a-f:Overwrite | Ctrl+S:Save | Esc:Leave ",
+            editor.cursor, editor.cursor, editor.data.len(), editor.dirty_count()
+        );
+        let footer = Paragraph::new(status_text)
+            .style(Style::default().fg(theme.fg).bg(theme.selection_bg));
+        f.render_widget(footer, layout[3]);
+    } else if app.viewer_editing {
         if let Some(editor) = &app.text_editor {
+            app.viewer_area.set(content_area);
             draw_editor_content(f, editor, content_area, theme);
             
             // Status line in Footer
             let status_text = if editor.mode == crate::viewer::VimMode::Command {
                 format!(":{}", editor.command_buffer)
             } else {
-                format!(" {} | Line {}/{}, Col {} | {}", 
+                format!(" {} | Line {}/{}, Col {} | {}{}", 
                     editor.status_message,
                     editor.cursor_row + 1,
                     editor.lines.len(),
@@ -653,8 +1164,10 @@ fn draw_viewer_popup(f: &mut Frame, app: &App) {
                             crate::viewer::VimMode::Command => "COMMAND",
                             crate::viewer::VimMode::Visual => "VISUAL",
                             crate::viewer::VimMode::VisualLine => "V-LINE",
+                            crate::viewer::VimMode::VisualBlock => "V-BLOCK",
                         },
-                    }
+                    },
+                    editor.recording_macro.map(|reg| format!(" recording @{}", reg)).unwrap_or_default()
                 )
             };
             let footer = Paragraph::new(status_text)
@@ -666,15 +1179,86 @@ fn draw_viewer_popup(f: &mut Frame, app: &App) {
         
         // Footer for ReadOnly
         let wrap_indicator = if app.viewer_wrap_mode { "[W]" } else { "" };
-        let footer_text = format!(" g/G:Top/Bottom | j/k:↑↓ | d/u:Half | w:Wrap{} | i:Edit | ESC:Close ", wrap_indicator);
-        let footer = Paragraph::new(footer_text)
-            .style(Style::default().fg(theme.footer_fg).bg(theme.footer_bg))
-            .alignment(ratatui::layout::Alignment::Right);
+        let footer = match &app.viewer_search {
+            _ if app.viewer_command.is_some() => {
+                Paragraph::new(format!(" :{}█", app.viewer_command.as_deref().unwrap_or_default()))
+            },
+            Some(crate::viewer::search::ViewerSearch { input: Some(input), .. }) => {
+                Paragraph::new(format!(" /{}█", input))
+            },
+            _ if app.viewer_hex_search.as_ref().is_some_and(|s| s.input.is_some()) => {
+                let input = app.viewer_hex_search.as_ref().and_then(|s| s.input.as_deref()).unwrap_or_default();
+                Paragraph::new(format!(" /{}█  (hex bytes like DE AD BE EF, or text; quote text that looks like hex)", input))
+            },
+            search => {
+                let search_status = search.as_ref()
+                    .map(|s| format!("/{} {} | n/N:Next/Prev | ", s.pattern, s.position()))
+                    .or_else(|| app.viewer_hex_search.as_ref().map(|s| format!(
+                        "/{} {} at 0x{:X} | n/N:Next/Prev | ", s.query, s.position(), s.current_match().unwrap_or(0)
+                    )))
+                    .unwrap_or_default();
+                let markdown_toggle = match &app.viewer_content {
+                    Some(crate::viewer::ViewerContent::Markdown(_)) if app.viewer_markdown_raw => "m:Rendered | ",
+                    Some(crate::viewer::ViewerContent::Markdown(_)) => "m:Raw | ",
+                    Some(crate::viewer::ViewerContent::Table(_)) => "←/→:Columns | ",
+                    _ => "",
+                };
+                Paragraph::new(format!(
+                    " {}g/G:Top/Bottom | j/k:↑↓ | d/u:Half | :N:Line | /:Search | w:Wrap{} | {}r:Reload | i:Edit | ESC:Close ",
+                    search_status, wrap_indicator, markdown_toggle
+                ))
+                .alignment(ratatui::layout::Alignment::Right)
+            },
+        };
+        let footer = footer.style(Style::default().fg(theme.footer_fg).bg(theme.footer_bg));
         f.render_widget(footer, layout[3]);
     }
 }
 
 fn draw_viewer_readonly_content(f: &mut Frame, app: &App, area: ratatui::layout::Rect, theme: &crate::config::Theme) {
+    app.viewer_area.set(area);
+
+    if let Some(crate::viewer::ViewerContent::Diff(diff)) = &app.viewer_content {
+        draw_diff(f, app, area, theme, diff);
+        return;
+    }
+
+    if let Some(crate::viewer::ViewerContent::Table(table)) = &app.viewer_content {
+        draw_table(f, app, area, theme, table);
+        return;
+    }
+
+    if let Some(crate::viewer::ViewerContent::HexView(data, truncated)) = &app.viewer_content {
+        // The goto target or current match is reversed, other matches underlined
+        let highlighted = |offset: usize| app.viewer_hex_highlight
+            .is_some_and(|(start, len)| (start..start + len).contains(&offset));
+        let style_of = |offset: usize| {
+            let style = if highlighted(offset) {
+                Style::default().fg(theme.accent_color).add_modifier(Modifier::REVERSED)
+            } else if app.viewer_hex_search.as_ref().is_some_and(|s| s.is_in_match(offset)) {
+                Style::default().fg(theme.accent_color).add_modifier(Modifier::UNDERLINED)
+            } else {
+                Style::default().fg(theme.fg)
+            };
+            [style; 3]
+        };
+        let header = if *truncated { format!("{}  (truncated)", crate::viewer::hex::HEX_HEADER) } else { crate::viewer::hex::HEX_HEADER.to_string() };
+        draw_hex_rows(f, area, data, app.viewer_scroll, &header, theme, &style_of);
+        return;
+    }
+
+    if let Some(crate::viewer::ViewerContent::LargeFile(file)) = &app.viewer_content {
+        draw_large_file(f, app, area, theme, file);
+        return;
+    }
+
+    if let Some(crate::viewer::ViewerContent::Markdown(text)) = &app.viewer_content {
+        if !app.viewer_markdown_raw {
+            draw_rendered_markdown(f, app, area, theme, text);
+            return;
+        }
+    }
+
     // Check if content is syntax highlighted code
     if let Some(crate::viewer::ViewerContent::HighlightedCode { highlighted, .. }) = &app.viewer_content {
         draw_highlighted_code(f, app, area, theme, highlighted);
@@ -713,12 +1297,23 @@ fn draw_viewer_readonly_content(f: &mut Frame, app: &App, area: ratatui::layout:
             info.push_str("\n  IMAGE PREVIEW\n");
             info.push_str("  ──────────────────────────────\n");
             info.push_str(&format!("  {}\n\n", preview.metadata()));
-            info.push_str(&preview.content);
+            match &preview.inline {
+                // Blank rows for the pixel image drawn after the frame
+                Some(inline) => {
+                    let (_, rows) = inline.fit(area, crate::viewer::image::cell_pixel_size());
+                    info.push_str(&"\n".repeat(rows as usize));
+                },
+                None => info.push_str(&preview.content),
+            }
             info
         },
-        Some(crate::viewer::ViewerContent::HexView(data, truncated)) => {
-            crate::viewer::format_hex_view(data, *truncated)
+        // Drawn row by row above
+        Some(crate::viewer::ViewerContent::HexView(..)) | Some(crate::viewer::ViewerContent::LargeFile(_)) => String::new(),
+        Some(crate::viewer::ViewerContent::ArchiveListing(entries, truncated)) => {
+            crate::viewer::format_archive_listing(entries, *truncated)
         },
+        Some(crate::viewer::ViewerContent::Diff(diff)) => diff.text.clone(),
+        Some(crate::viewer::ViewerContent::Table(table)) => table.to_text(),
         Some(crate::viewer::ViewerContent::Error(e)) => format!("Error: {}", e),
         None => "No content loaded".to_string(),
     };
@@ -732,28 +1327,32 @@ fn draw_viewer_readonly_content(f: &mut Frame, app: &App, area: ratatui::layout:
         let wrapped_lines = wrap_lines_with_numbers(&content_text, content_width);
         let total_lines = wrapped_lines.len();
         
-        let visible = wrapped_lines
-            .iter()
-            .skip(app.viewer_scroll)
-            .take(visible_height);
-
         let mut styled_lines = Vec::new();
-        for (line_num_opt, line_content) in visible {
-            let line_prefix = match line_num_opt {
-                Some(n) => format!("{:>4} │ ", n),
-                None => "     │ ".to_string(), // Continuation line
-            };
-            
-            styled_lines.push(ListItem::new(ratatui::text::Line::from(vec![
-                ratatui::text::Span::styled(line_prefix, Style::default().fg(theme.border)),
-                ratatui::text::Span::raw(line_content.clone()),
-            ])));
+        // Track the source line and column of each row for search highlights
+        let (mut line_idx, mut col) = (0, 0);
+        for (row, (line_num_opt, line_content)) in wrapped_lines.iter().enumerate().take(app.viewer_scroll + visible_height) {
+            if let Some(n) = line_num_opt {
+                line_idx = n - 1;
+                col = 0;
+            }
+            if row >= app.viewer_scroll {
+                let line_prefix = match line_num_opt {
+                    Some(n) => format!("{:>4} │ ", n),
+                    None => "     │ ".to_string(), // Continuation line
+                };
+
+                let mut spans = vec![ratatui::text::Span::styled(line_prefix, Style::default().fg(theme.border))];
+                spans.extend(expand_tab_spans(search_highlighted_spans(app, line_idx, col, vec![(line_content.clone(), Style::default())]), app.config.tab_width));
+                styled_lines.push(ListItem::new(ratatui::text::Line::from(spans)));
+            }
+            col += line_content.chars().count();
         }
 
         let list = List::new(styled_lines);
         f.render_widget(list, area);
         
-        draw_scrollbar(f, area, app.viewer_scroll, total_lines, visible_height, theme);
+        let line_rows = first_rows_of_lines(wrapped_lines.iter().map(|(n, _)| *n));
+        draw_viewer_scrollbar(f, app, area, total_lines, visible_height, Some(&line_rows));
     } else {
         // No wrap: original behavior
         let lines: Vec<&str> = content_text.lines().collect();
@@ -769,17 +1368,140 @@ fn draw_viewer_readonly_content(f: &mut Frame, app: &App, area: ratatui::layout:
             let line_num = app.viewer_scroll + i + 1;
             let line_prefix = format!("{:>4} │ ", line_num);
             
-            styled_lines.push(ListItem::new(ratatui::text::Line::from(vec![
-                ratatui::text::Span::styled(line_prefix, Style::default().fg(theme.border)),
-                ratatui::text::Span::raw(*line),
-            ])));
+            let mut spans = vec![ratatui::text::Span::styled(line_prefix, Style::default().fg(theme.border))];
+            spans.extend(expand_tab_spans(search_highlighted_spans(app, line_num - 1, 0, vec![(line.to_string(), Style::default())]), app.config.tab_width));
+            styled_lines.push(ListItem::new(ratatui::text::Line::from(spans)));
         }
 
         let list = List::new(styled_lines);
         f.render_widget(list, area);
         
-        draw_scrollbar(f, area, app.viewer_scroll, total_lines, visible_height, theme);
+        draw_viewer_scrollbar(f, app, area, total_lines, visible_height, None);
+    }
+}
+
+/// Render the visible lines of a file too large to load, read from disk on each draw
+fn draw_large_file(f: &mut Frame, app: &App, area: ratatui::layout::Rect, theme: &crate::config::Theme, file: &crate::viewer::large::LargeFile) {
+    let visible_height = area.height as usize;
+    let total_lines = file.line_count();
+    let number_width = total_lines.to_string().len().max(4);
+
+    let items: Vec<ListItem> = match file.read_lines(app.viewer_scroll, visible_height) {
+        Ok(lines) => lines.into_iter().enumerate().map(|(i, line)| {
+            let mut spans = vec![ratatui::text::Span::styled(
+                format!("{:>width$} │ ", app.viewer_scroll + i + 1, width = number_width),
+                Style::default().fg(theme.border),
+            )];
+            spans.extend(expand_tab_spans(vec![ratatui::text::Span::styled(line, Style::default().fg(theme.fg))], app.config.tab_width));
+            ListItem::new(ratatui::text::Line::from(spans))
+        }).collect(),
+        Err(e) => vec![ListItem::new(format!("Error: failed to read file: {}", e))],
+    };
+    f.render_widget(List::new(items), area);
+    draw_scrollbar(f, area, app.viewer_scroll, total_lines, visible_height, theme);
+}
+
+/// Render markdown formatted (headings, lists, tables, code blocks); 'm' shows the source
+fn draw_rendered_markdown(f: &mut Frame, app: &App, area: ratatui::layout::Rect, theme: &crate::config::Theme, text: &str) {
+    let visible_height = area.height as usize;
+    // Leave the last column for the scrollbar
+    let lines = render_markdown_to_lines(text, area.width.saturating_sub(1) as usize, 1, &app.config.syntax_theme);
+    let total_lines = lines.len();
+
+    let items: Vec<ListItem> = lines.into_iter()
+        .skip(app.viewer_scroll)
+        .take(visible_height)
+        .map(ListItem::new)
+        .collect();
+    f.render_widget(List::new(items), area);
+    draw_scrollbar(f, area, app.viewer_scroll, total_lines, visible_height, theme);
+}
+
+/// Render a unified diff: added lines green, removed red, hunk headers in the accent color
+fn draw_diff(f: &mut Frame, app: &App, area: ratatui::layout::Rect, theme: &crate::config::Theme, diff: &crate::viewer::DiffView) {
+    use crate::viewer::diff::DiffLineKind;
+
+    let visible_height = area.height as usize;
+    let content_width = area.width.saturating_sub(7) as usize; // "1234 │ " and scrollbar
+    let line_style = |kind: DiffLineKind| match kind {
+        DiffLineKind::Added => Style::default().fg(Color::Green),
+        DiffLineKind::Removed => Style::default().fg(Color::Red),
+        DiffLineKind::Hunk => Style::default().fg(theme.accent_color).add_modifier(Modifier::BOLD),
+        DiffLineKind::Context => Style::default().fg(theme.fg),
+    };
+    let gutter = |line: &crate::viewer::diff::DiffLine| match line.line_number {
+        Some(n) => format!("{:>4} │ ", n),
+        None => "     │ ".to_string(),
+    };
+
+    // Rows as (diff line index, column the row starts at, text); long lines wrap in wrap mode
+    let rows: Vec<(usize, usize, String)> = if app.viewer_wrap_mode && content_width > 10 {
+        wrap_lines_with_numbers(&diff.text, content_width)
+            .into_iter()
+            .scan((0, 0), |(index, col), (line_num, text)| {
+                if let Some(n) = line_num {
+                    *index = n - 1;
+                    *col = 0;
+                }
+                let row = (*index, *col, text);
+                *col += row.2.chars().count();
+                Some(row)
+            })
+            .collect()
+    } else {
+        diff.lines.iter().enumerate().map(|(i, l)| (i, 0, l.text.clone())).collect()
+    };
+    let total_lines = rows.len();
+
+    let mut styled_lines = Vec::new();
+    for (index, col, text) in rows.into_iter().skip(app.viewer_scroll).take(visible_height) {
+        let Some(line) = diff.lines.get(index) else { continue };
+        let prefix = if col == 0 { gutter(line) } else { "     │ ".to_string() };
+        let mut spans = vec![ratatui::text::Span::styled(prefix, Style::default().fg(theme.border))];
+        spans.extend(search_highlighted_spans(app, index, col, vec![(text, line_style(line.kind))]));
+        styled_lines.push(ListItem::new(ratatui::text::Line::from(spans)));
+    }
+
+    f.render_widget(List::new(styled_lines), area);
+    draw_scrollbar(f, area, app.viewer_scroll, total_lines, visible_height, theme);
+}
+
+/// Render a CSV/TSV table: summary and header row stay put, rows scroll below them
+/// and Left/Right shift the first visible column.
+fn draw_table(f: &mut Frame, app: &App, area: ratatui::layout::Rect, theme: &crate::config::Theme, table: &crate::viewer::TableView) {
+    let first_col = app.viewer_hscroll.min(table.widths.len().saturating_sub(1));
+    let gutter_style = Style::default().fg(theme.border);
+    let row_line = |gutter: String, text: String, style: Style| {
+        ratatui::text::Line::from(vec![
+            ratatui::text::Span::styled(gutter, gutter_style),
+            ratatui::text::Span::styled(text, style),
+        ])
+    };
+
+    let mut summary = format!(" {}", table.summary());
+    if first_col > 0 {
+        summary.push_str(&format!(" · from column {}", first_col + 1));
+    }
+    let mut lines = vec![
+        ratatui::text::Line::styled(summary, Style::default().fg(theme.accent_color)),
+        row_line("     │ ".to_string(), table.format_row(&table.headers, first_col), Style::default().fg(theme.fg).add_modifier(Modifier::BOLD)),
+        row_line("─────┼─".to_string(), table.separator(first_col), gutter_style),
+    ];
+    let header_height = lines.len();
+
+    let body_height = (area.height as usize).saturating_sub(header_height);
+    for (i, row) in table.rows.iter().enumerate().skip(app.viewer_scroll).take(body_height) {
+        lines.push(row_line(format!("{:>4} │ ", i + 1), table.format_row(row, first_col), Style::default().fg(theme.fg)));
     }
+
+    f.render_widget(Paragraph::new(lines), area);
+
+    let body_area = ratatui::layout::Rect {
+        y: area.y + header_height as u16,
+        height: body_height as u16,
+        ..area
+    };
+    draw_scrollbar(f, body_area, app.viewer_scroll, table.rows.len(), body_height, theme);
 }
 
 /// Wrap lines and return (line_number_option, wrapped_text) pairs
@@ -907,6 +1629,77 @@ enum MarkdownBlock {
     ListItem { ordered: bool, number: Option<usize>, text: String },
     Quote(String),
     HorizontalRule,
+    Table { headers: Vec<String>, aligns: Vec<TableAlign>, rows: Vec<Vec<String>> },
+}
+
+/// Column alignment of a markdown table (from `:--`, `:-:`, `--:` markers)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TableAlign {
+    Left,
+    Center,
+    Right,
+}
+
+/// Split a table row into trimmed cells (`| a | b |` -> ["a", "b"])
+fn split_table_row(line: &str) -> Vec<String> {
+    let trimmed = line.trim();
+    let trimmed = trimmed.strip_prefix('|').unwrap_or(trimmed);
+    let trimmed = trimmed.strip_suffix('|').unwrap_or(trimmed);
+
+    let mut cells = Vec::new();
+    let mut current = String::new();
+    let mut chars = trimmed.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            // Escaped pipe stays inside the cell
+            '\\' if chars.peek() == Some(&'|') => {
+                current.push('|');
+                chars.next();
+            }
+            '|' => cells.push(std::mem::take(&mut current).trim().to_string()),
+            _ => current.push(ch),
+        }
+    }
+    cells.push(current.trim().to_string());
+    cells
+}
+
+/// Parse a table separator row (`|---|:--:|`) into column alignments
+fn parse_table_separator(line: &str) -> Option<Vec<TableAlign>> {
+    if !line.contains('|') {
+        return None;
+    }
+
+    split_table_row(line)
+        .iter()
+        .map(|cell| {
+            let left = cell.starts_with(':');
+            let right = cell.ends_with(':');
+            let dashes = cell.trim_matches(':');
+            if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+                return None;
+            }
+            Some(match (left, right) {
+                (true, true) => TableAlign::Center,
+                (false, true) => TableAlign::Right,
+                _ => TableAlign::Left,
+            })
+        })
+        .collect()
+}
+
+/// Pad a cell to the column width according to its alignment
+fn align_table_cell(text: &str, width: usize, align: TableAlign) -> String {
+    let text = truncate_str(text, width);
+    let padding = width.saturating_sub(text.chars().count());
+    match align {
+        TableAlign::Left => format!("{}{}", text, " ".repeat(padding)),
+        TableAlign::Right => format!("{}{}", " ".repeat(padding), text),
+        TableAlign::Center => {
+            let left = padding / 2;
+            format!("{}{}{}", " ".repeat(left), text, " ".repeat(padding - left))
+        }
+    }
 }
 
 /// Parse markdown text into blocks
@@ -946,6 +1739,25 @@ fn parse_markdown(text: &str) -> Vec<MarkdownBlock> {
             continue;
         }
         
+        // Tables (header row followed by a separator row with matching columns)
+        if line.contains('|') {
+            let headers = split_table_row(line);
+            let aligns = lines.peek().and_then(|next| parse_table_separator(next));
+            if let Some(aligns) = aligns.filter(|a| a.len() == headers.len()) {
+                lines.next(); // consume separator row
+                let mut rows = Vec::new();
+                while let Some(next) = lines.peek() {
+                    if next.trim().is_empty() || !next.contains('|') {
+                        break;
+                    }
+                    rows.push(split_table_row(next));
+                    lines.next();
+                }
+                blocks.push(MarkdownBlock::Table { headers, aligns, rows });
+                continue;
+            }
+        }
+        
         // Headings
         if line.starts_with('#') {
             let level = line.chars().take_while(|&c| c == '#').count() as u8;
@@ -1114,12 +1926,12 @@ fn parse_inline_markdown(text: &str) -> Vec<ratatui::text::Span<'static>> {
     spans
 }
 
-/// Render markdown blocks to ratatui Lines
-#[allow(dead_code)]
-fn render_markdown_to_lines(
+/// Render markdown blocks to ratatui Lines, coloring code blocks with `syntax_theme`
+pub(crate) fn render_markdown_to_lines(
     text: &str,
     max_width: usize,
     indent: usize,
+    syntax_theme: &str,
 ) -> Vec<ratatui::text::Line<'static>> {
     use crate::viewer::{highlight_code, HighlightedLine};
     
@@ -1167,7 +1979,7 @@ fn render_markdown_to_lines(
                 
                 // Syntax highlighted code
                 let ext = lang.as_deref().unwrap_or("txt");
-                let highlighted: Vec<HighlightedLine> = highlight_code(&code, ext);
+                let highlighted: Vec<HighlightedLine> = highlight_code(&code, ext, syntax_theme);
                 
                 for hl_line in highlighted {
                     let mut spans = vec![
@@ -1242,6 +2054,58 @@ fn render_markdown_to_lines(
                 ]));
             }
             
+            MarkdownBlock::Table { headers, aligns, rows } => {
+                let col_count = headers.len();
+                let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count().max(3)).collect();
+                for row in &rows {
+                    for (i, cell) in row.iter().enumerate().take(col_count) {
+                        widths[i] = widths[i].max(cell.chars().count());
+                    }
+                }
+
+                // Shrink columns evenly if the table doesn't fit ("│ " + " │ " separators + " │")
+                let border_width = col_count * 3 + 1;
+                let available = content_width.saturating_sub(border_width);
+                if widths.iter().sum::<usize>() > available && col_count > 0 {
+                    let max_col = (available / col_count).max(3);
+                    for w in widths.iter_mut() {
+                        *w = (*w).min(max_col);
+                    }
+                }
+
+                let border_style = Style::default().fg(Color::DarkGray);
+                let render_row = |cells: &[String], style: Style| {
+                    let mut spans = vec![ratatui::text::Span::styled(format!("{}│ ", indent_str), border_style)];
+                    for (i, width) in widths.iter().enumerate() {
+                        let cell = cells.get(i).map(|c| c.as_str()).unwrap_or("");
+                        spans.push(ratatui::text::Span::styled(align_table_cell(cell, *width, aligns[i]), style));
+                        spans.push(ratatui::text::Span::styled(
+                            if i + 1 == col_count { " │" } else { " │ " },
+                            border_style,
+                        ));
+                    }
+                    ratatui::text::Line::from(spans)
+                };
+
+                // Horizontal border with the given corner/junction characters
+                let segments: Vec<String> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+                let border = |left: &str, mid: &str, right: &str| ratatui::text::Line::from(ratatui::text::Span::styled(
+                    format!("{}{}{}{}", indent_str, left, segments.join(mid), right),
+                    border_style,
+                ));
+
+                lines.push(border("┌", "┬", "┐"));
+                lines.push(render_row(
+                    &headers,
+                    Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+                ));
+                lines.push(border("├", "┼", "┤"));
+                for row in &rows {
+                    lines.push(render_row(row, Style::default().fg(Color::White)));
+                }
+                lines.push(border("└", "┴", "┘"));
+            }
+            
             MarkdownBlock::Paragraph(text) => {
                 let wrapped = wrap_text(&text, content_width);
                 for wrapped_line in wrapped {
@@ -1261,6 +2125,37 @@ fn render_markdown_to_lines(
 
 /// Draw scrollbar for viewer
 fn draw_scrollbar(f: &mut Frame, area: ratatui::layout::Rect, scroll: usize, total_lines: usize, visible_height: usize, theme: &crate::config::Theme) {
+    draw_marked_scrollbar(f, area, scroll, total_lines, visible_height, theme, &Default::default());
+}
+
+/// Viewer scrollbar with the search matches ticked along the track
+/// `line_rows` holds the first display row of each source line in wrap mode.
+fn draw_viewer_scrollbar(f: &mut Frame, app: &App, area: ratatui::layout::Rect, total_rows: usize, visible_height: usize, line_rows: Option<&[usize]>) {
+    let marks = app.viewer_search.as_ref()
+        .map(|search| search.scrollbar_marks(total_rows, area.height as usize, |line| match line_rows {
+            Some(rows) => rows.get(line).copied().unwrap_or(total_rows),
+            None => line,
+        }))
+        .unwrap_or_default();
+    draw_marked_scrollbar(f, area, app.viewer_scroll, total_rows, visible_height, &app.config.theme, &marks);
+}
+
+/// First display row of each source line, from the line numbers of wrapped rows
+fn first_rows_of_lines(line_numbers: impl Iterator<Item = Option<usize>>) -> Vec<usize> {
+    line_numbers.enumerate()
+        .filter_map(|(row, n)| n.map(|_| row))
+        .collect()
+}
+
+fn draw_marked_scrollbar(
+    f: &mut Frame,
+    area: ratatui::layout::Rect,
+    scroll: usize,
+    total_lines: usize,
+    visible_height: usize,
+    theme: &crate::config::Theme,
+    marks: &crate::viewer::search::ScrollbarMarks,
+) {
     if total_lines > visible_height {
         let scrollbar_area = ratatui::layout::Rect {
             x: area.x + area.width.saturating_sub(1),
@@ -1273,8 +2168,22 @@ fn draw_scrollbar(f: &mut Frame, area: ratatui::layout::Rect, scroll: usize, tot
         let thumb_y = (progress * (area.height.saturating_sub(1) as f64)) as u16;
         
         for i in 0..area.height {
-            let ch = if i == thumb_y { "█" } else { "│" };
-            let style = if i == thumb_y { Style::default().fg(theme.accent_color) } else { Style::default().fg(theme.border) };
+            let cell = i as usize;
+            let matches = marks.counts.get(cell).copied().unwrap_or(0);
+            let (ch, style) = if i == thumb_y {
+                ("█", Style::default().fg(theme.accent_color))
+            } else if matches > 0 {
+                // Denser cells get a heavier tick; the current match's cell is highlighted
+                let ch = if marks.is_dense(cell) { "■" } else { "▪" };
+                let style = if marks.current == Some(cell) {
+                    Style::default().fg(theme.accent_color).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::Yellow)
+                };
+                (ch, style)
+            } else {
+                ("│", Style::default().fg(theme.border))
+            };
             f.render_widget(Paragraph::new(ch).style(style), 
                 ratatui::layout::Rect { x: scrollbar_area.x, y: scrollbar_area.y + i, width: 1, height: 1 });
         }
@@ -1287,7 +2196,7 @@ fn draw_highlighted_code(
     app: &App, 
     area: ratatui::layout::Rect, 
     theme: &crate::config::Theme,
-    highlighted: &[crate::viewer::HighlightedLine]
+    highlighted: &crate::viewer::LazyHighlight
 ) {
     use ratatui::text::{Line, Span};
 
@@ -1299,45 +2208,57 @@ fn draw_highlighted_code(
         // Wrap mode for highlighted code
         let wrapped = wrap_highlighted_lines(highlighted, content_width);
         let total_lines = wrapped.len();
-        
-        let visible = wrapped
-            .iter()
-            .skip(app.viewer_scroll)
-            .take(visible_height);
 
+        // Report the source lines on screen so on_tick can highlight around them
+        let first_line = wrapped.iter().take(app.viewer_scroll + 1).rev()
+            .find_map(|(n, _)| *n)
+            .unwrap_or(1);
+        let last_line = wrapped.iter().skip(app.viewer_scroll).take(visible_height)
+            .filter_map(|(n, _)| *n)
+            .max()
+            .unwrap_or(first_line);
+        app.viewer_drawn_lines.set((first_line - 1, last_line));
+        
         let mut styled_lines = Vec::new();
-        for (line_num_opt, segments) in visible {
-            let line_prefix = match line_num_opt {
-                Some(n) => format!("{:>4} │ ", n),
-                None => "     │ ".to_string(),
-            };
-            
-            let mut spans = vec![
-                Span::styled(line_prefix, Style::default().fg(theme.border)),
-            ];
-            
-            for (text, color) in segments {
-                spans.push(Span::styled(text.clone(), Style::default().fg(*color)));
+        // Track the source line and column of each row for search highlights
+        let (mut line_idx, mut col) = (0, 0);
+        for (row, (line_num_opt, segments)) in wrapped.iter().enumerate().take(app.viewer_scroll + visible_height) {
+            if let Some(n) = line_num_opt {
+                line_idx = n - 1;
+                col = 0;
             }
-            
-            styled_lines.push(ListItem::new(Line::from(spans)));
+            if row >= app.viewer_scroll {
+                let line_prefix = match line_num_opt {
+                    Some(n) => format!("{:>4} │ ", n),
+                    None => "     │ ".to_string(),
+                };
+
+                let mut spans = vec![
+                    Span::styled(line_prefix, Style::default().fg(theme.border)),
+                ];
+                let pieces = segments.iter()
+                    .map(|(text, color)| (text.clone(), Style::default().fg(*color)))
+                    .collect();
+                spans.extend(expand_tab_spans(search_highlighted_spans(app, line_idx, col, pieces), app.config.tab_width));
+
+                styled_lines.push(ListItem::new(Line::from(spans)));
+            }
+            col += segments.iter().map(|(text, _)| text.chars().count()).sum::<usize>();
         }
 
         let list = List::new(styled_lines);
         f.render_widget(list, area);
         
-        draw_scrollbar(f, area, app.viewer_scroll, total_lines, visible_height, theme);
+        let line_rows = first_rows_of_lines(wrapped.iter().map(|(n, _)| *n));
+        draw_viewer_scrollbar(f, app, area, total_lines, visible_height, Some(&line_rows));
     } else {
         // No wrap mode
         let total_lines = highlighted.len();
-        
-        let visible_lines = highlighted
-            .iter()
-            .skip(app.viewer_scroll)
-            .take(visible_height);
+        let end = (app.viewer_scroll + visible_height).min(total_lines);
+        app.viewer_drawn_lines.set((app.viewer_scroll, end));
 
         let mut styled_lines = Vec::new();
-        for (i, line) in visible_lines.enumerate() {
+        for (i, line) in (app.viewer_scroll..end).map(|n| highlighted.line_or_plain(n)).enumerate() {
             let line_num = app.viewer_scroll + i + 1;
             let line_prefix = format!("{:>4} │ ", line_num);
             
@@ -1345,12 +2266,10 @@ fn draw_highlighted_code(
                 Span::styled(line_prefix, Style::default().fg(theme.border)),
             ];
             
-            for segment in &line.segments {
-                spans.push(Span::styled(
-                    segment.text.clone(),
-                    Style::default().fg(segment.fg),
-                ));
-            }
+            let pieces = line.segments.iter()
+                .map(|segment| (segment.text.clone(), Style::default().fg(segment.fg)))
+                .collect();
+            spans.extend(expand_tab_spans(search_highlighted_spans(app, line_num - 1, 0, pieces), app.config.tab_width));
             
             styled_lines.push(ListItem::new(Line::from(spans)));
         }
@@ -1358,19 +2277,76 @@ fn draw_highlighted_code(
         let list = List::new(styled_lines);
         f.render_widget(list, area);
         
-        draw_scrollbar(f, area, app.viewer_scroll, total_lines, visible_height, theme);
+        draw_viewer_scrollbar(f, app, area, total_lines, visible_height, None);
+    }
+}
+
+/// Expand the tabs in the spans of one row; terminal cells can't show '\t'
+fn expand_tab_spans(spans: Vec<ratatui::text::Span<'static>>, tab_width: usize) -> Vec<ratatui::text::Span<'static>> {
+    let mut col = 0;
+    spans.into_iter()
+        .map(|span| {
+            if !span.content.contains('\t') {
+                col += span.content.chars().count();
+                return span;
+            }
+            let text = crate::viewer::expand_tabs(&span.content, col, tab_width);
+            col += text.chars().count();
+            ratatui::text::Span::styled(text, span.style)
+        })
+        .collect()
+}
+
+/// Style `pieces` of a row starting at column `col` of source line `line`,
+/// showing viewer search matches in reverse video (the current one also bold)
+fn search_highlighted_spans(
+    app: &App,
+    line: usize,
+    col: usize,
+    pieces: Vec<(String, Style)>,
+) -> Vec<ratatui::text::Span<'static>> {
+    let ranges = app.viewer_search.as_ref()
+        .map(|search| search.ranges_on_line(line))
+        .unwrap_or_default();
+    if ranges.is_empty() {
+        return pieces.into_iter().map(|(text, style)| ratatui::text::Span::styled(text, style)).collect();
     }
+
+    let mut spans = Vec::new();
+    let mut pos = col;
+    for (text, style) in pieces {
+        let mut current = String::new();
+        let mut current_style = None;
+        for ch in text.chars() {
+            let char_style = match ranges.iter().find(|(start, end, _)| pos >= *start && pos < *end) {
+                Some((_, _, true)) => style.add_modifier(Modifier::REVERSED | Modifier::BOLD),
+                Some((_, _, false)) => style.add_modifier(Modifier::REVERSED),
+                None => style,
+            };
+            if current_style.is_some_and(|s| s != char_style) {
+                spans.push(ratatui::text::Span::styled(std::mem::take(&mut current), current_style.unwrap_or(style)));
+            }
+            current.push(ch);
+            current_style = Some(char_style);
+            pos += 1;
+        }
+        if !current.is_empty() {
+            spans.push(ratatui::text::Span::styled(current, current_style.unwrap_or(style)));
+        }
+    }
+    spans
 }
 
 /// Wrap highlighted lines for wrap mode
 /// Returns (line_number_option, segments) where segments are (text, color) pairs
 fn wrap_highlighted_lines(
-    highlighted: &[crate::viewer::HighlightedLine], 
+    highlighted: &crate::viewer::LazyHighlight, 
     max_width: usize
 ) -> Vec<(Option<usize>, Vec<(String, Color)>)> {
     let mut result = Vec::new();
     
-    for (line_idx, line) in highlighted.iter().enumerate() {
+    for line_idx in 0..highlighted.len() {
+        let line = highlighted.line_or_plain(line_idx);
         let line_num = line_idx + 1;
         
         // Flatten all segments into one string with color info
@@ -1428,6 +2404,77 @@ fn wrap_highlighted_lines(
     result
 }
 
+/// Hex editor rows: the cursor byte is reversed (its pending nibble underlined)
+/// and changed bytes are yellow
+fn draw_hex_editor(f: &mut Frame, editor: &crate::viewer::hex::HexEditor, area: ratatui::layout::Rect, theme: &crate::config::Theme) {
+    let style_of = |offset: usize| {
+        let style = if editor.is_byte_dirty(offset) {
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.fg)
+        };
+        if offset != editor.cursor {
+            return [style; 3];
+        }
+        let active = Style::default().add_modifier(Modifier::REVERSED);
+        let other = style.add_modifier(Modifier::UNDERLINED);
+        let (high, low) = if editor.low_nibble { (other, active) } else { (active, other) };
+        [high, low, style.add_modifier(Modifier::REVERSED)]
+    };
+    draw_hex_rows(f, area, &editor.data, editor.scroll, crate::viewer::hex::HEX_HEADER, theme, &style_of);
+}
+
+/// Column header and the rows of `data` from row `scroll` on; only visible rows are formatted
+fn draw_hex_rows(
+    f: &mut Frame,
+    area: ratatui::layout::Rect,
+    data: &[u8],
+    scroll: usize,
+    header: &str,
+    theme: &crate::config::Theme,
+    style_of: &dyn Fn(usize) -> [Style; 3],
+) {
+    let visible_rows = (area.height as usize).saturating_sub(1);
+    let total_rows = crate::viewer::hex::row_count(data.len());
+    let mut lines = vec![ratatui::text::Line::styled(header.to_string(), Style::default().fg(theme.border))];
+    lines.extend((scroll..(scroll + visible_rows).min(total_rows)).map(|row| hex_row_line(data, row, theme.border, style_of)));
+    f.render_widget(Paragraph::new(lines), area);
+
+    let rows_area = ratatui::layout::Rect { y: area.y + 1, height: area.height.saturating_sub(1), ..area };
+    draw_scrollbar(f, rows_area, scroll, total_rows, visible_rows, theme);
+}
+
+/// One hex dump row: offset, 16 bytes in hex (two groups of 8) and the ASCII column
+/// `style_of` gives the style of a byte's high digit, low digit and ASCII character.
+fn hex_row_line(data: &[u8], row: usize, label: Color, style_of: &dyn Fn(usize) -> [Style; 3]) -> ratatui::text::Line<'static> {
+    use crate::viewer::hex::{ascii_char, BYTES_PER_ROW};
+    use ratatui::text::Span;
+
+    let start = row * BYTES_PER_ROW;
+    let chunk = &data[start.min(data.len())..(start + BYTES_PER_ROW).min(data.len())];
+    let mut spans = vec![Span::styled(format!(" {:08X}  ", start), Style::default().fg(label))];
+    for i in 0..BYTES_PER_ROW {
+        if i == 8 {
+            spans.push(Span::raw(" "));
+        }
+        match chunk.get(i) {
+            Some(&byte) => {
+                let [high, low, _] = style_of(start + i);
+                let digits = format!("{:02X}", byte);
+                spans.push(Span::styled(digits[..1].to_string(), high));
+                spans.push(Span::styled(digits[1..].to_string(), low));
+                spans.push(Span::raw(" "));
+            },
+            None => spans.push(Span::raw("   ")),
+        }
+    }
+    spans.push(Span::raw(" "));
+    for (i, &byte) in chunk.iter().enumerate() {
+        spans.push(Span::styled(ascii_char(byte).to_string(), style_of(start + i)[2]));
+    }
+    ratatui::text::Line::from(spans)
+}
+
 fn draw_editor_content(f: &mut Frame, editor: &crate::viewer::TextEditor, area: ratatui::layout::Rect, theme: &crate::config::Theme) {
     // Calculate visible range
     let content_height = area.height as usize;
@@ -1439,19 +2486,29 @@ fn draw_editor_content(f: &mut Frame, editor: &crate::viewer::TextEditor, area:
 
     let mut display_lines = Vec::new();
     for (idx, line) in editor.lines.iter().enumerate().skip(scroll_offset).take(content_height) {
-        let line_num = idx + 1;
-        let line_prefix = format!("{:>4} │ ", line_num);
+        let line_prefix = format!("{:>4} │ ", editor.gutter_number(idx));
         
-        let line_content = if idx == editor.cursor_row {
+        let line_content = if let Some((start, end)) = editor.visual_columns(idx) {
+            // Visual selection in reverse video
+            let chars: Vec<char> = line.chars().collect();
+            let piece = |from: usize, to: usize| chars[from..to].iter().collect::<String>();
+            let mut spans = vec![ratatui::text::Span::styled(line_prefix, Style::default().fg(theme.accent_color))];
+            spans.extend(expand_tab_spans(vec![
+                ratatui::text::Span::raw(piece(0, start)),
+                ratatui::text::Span::styled(piece(start, end), Style::default().add_modifier(Modifier::REVERSED)),
+                ratatui::text::Span::raw(piece(end, chars.len())),
+            ], editor.tab_width));
+            ratatui::text::Line::from(spans)
+        } else if idx == editor.cursor_row {
              // Simple highlight for current line
              ratatui::text::Line::from(vec![
                 ratatui::text::Span::styled(line_prefix, Style::default().fg(theme.accent_color).add_modifier(Modifier::BOLD)),
-                ratatui::text::Span::styled(line, Style::default().bg(theme.selection_bg)),
+                ratatui::text::Span::styled(crate::viewer::expand_tabs(line, 0, editor.tab_width), Style::default().bg(theme.selection_bg)),
              ])
         } else {
              ratatui::text::Line::from(vec![
                 ratatui::text::Span::styled(line_prefix, Style::default().fg(theme.border)),
-                ratatui::text::Span::raw(line),
+                ratatui::text::Span::raw(crate::viewer::expand_tabs(line, 0, editor.tab_width)),
              ])
         };
         
@@ -1464,7 +2521,9 @@ fn draw_editor_content(f: &mut Frame, editor: &crate::viewer::TextEditor, area:
     // Render cursor
     if matches!(editor.mode, crate::viewer::VimMode::Insert | crate::viewer::VimMode::Normal) {
         let line_prefix_len = 7; // "1234 | " is 7 chars
-        let cursor_x = area.x + line_prefix_len + editor.cursor_col as u16;
+        // Screen column of the cursor with the tabs before it expanded
+        let before: String = editor.get_current_line().chars().take(editor.cursor_col).collect();
+        let cursor_x = area.x + line_prefix_len + crate::viewer::expand_tabs(&before, 0, editor.tab_width).chars().count() as u16;
         let cursor_y = area.y + (editor.cursor_row - scroll_offset) as u16;
         
         if cursor_y < area.y + area.height {
@@ -1539,7 +2598,12 @@ fn draw_dialog_popup(f: &mut Frame, app: &App) {
     use crate::app::DialogMode;
 
     let theme = &app.config.theme;
-    let area = centered_rect(60, 30, f.area());
+    let area = match app.dialog {
+        DialogMode::Properties { .. } => centered_rect(60, 60, f.area()),
+        DialogMode::Chmod { .. } => centered_rect(60, 40, f.area()),
+        DialogMode::BatchRename { .. } => centered_rect(70, 50, f.area()),
+        _ => centered_rect(60, 30, f.area()),
+    };
 
     f.render_widget(ratatui::widgets::Clear, area);
 
@@ -1553,68 +2617,315 @@ fn draw_dialog_popup(f: &mut Frame, app: &App) {
                          truncate_path(new_name, 40))
             )
         },
-        DialogMode::Delete { path_name } => {
+        DialogMode::BatchRename { paths, find, replace, editing_replace } => {
+            let cursor = |active: bool| if active { "_" } else { "" };
+            // Preview as many renames as fit, or why the pattern can't be applied
+            let max_preview = (area.height as usize).saturating_sub(10).max(1);
+            let preview = match crate::fs::rename::pattern_plan(paths, find, replace) {
+                Ok(plan) if plan.is_empty() => "  (no names change)".to_string(),
+                Ok(plan) => {
+                    let mut lines: Vec<String> = plan.iter()
+                        .take(max_preview)
+                        .map(|(from, to)| format!(
+                            "  {} → {}",
+                            truncate_str(&from.file_name().unwrap_or_default().to_string_lossy(), 30),
+                            truncate_str(&to.file_name().unwrap_or_default().to_string_lossy(), 30)
+                        ))
+                        .collect();
+                    if plan.len() > max_preview {
+                        lines.push(format!("  ... and {} more", plan.len() - max_preview));
+                    }
+                    lines.join("\n")
+                },
+                Err(e) => format!("  ✗ {}", truncate_str(&e, 60)),
+            };
+            (
+                " BATCH RENAME ",
+                format!("\n  {} items\n  Find (regex): {}{}\n  Replace:      {}{}\n\n{}\n\n  TAB: Switch field  |  ENTER: Rename all  |  F2: Edit names in editor  |  ESC: Cancel",
+                         paths.len(), find, cursor(!*editing_replace), replace, cursor(*editing_replace), preview)
+            )
+        },
+        DialogMode::Delete { path_name, permanent } => {
+            let action = if *permanent { "Permanently delete" } else { "Move to trash" };
+            (
+                " DELETE CONFIRMATION ",
+                format!("\n  {}: {}\n\n  Y: Confirm  |  N/ESC: Cancel",
+                         action, truncate_path(path_name, 40))
+            )
+        },
+        DialogMode::NewFile { name } => {
+            (
+                " NEW FILE ",
+                format!("\n  Name: {}\n\n  ENTER: Create  |  ESC: Cancel",
+                         truncate_path(name, 40))
+            )
+        },
+        DialogMode::AddBookmark { path, name } => {
+            (
+                " ADD BOOKMARK ",
+                format!("\n  Directory: {}\n  Label:     {}_\n\n  ENTER: Save (empty = directory name)  |  ESC: Cancel",
+                         truncate_path(&path.display().to_string(), 40),
+                         name)
+            )
+        },
+        DialogMode::NewFolder { name } => {
+            (
+                " NEW FOLDER ",
+                format!("\n  Name: {}\n\n  ENTER: Create  |  ESC: Cancel",
+                         truncate_path(name, 40))
+            )
+        },
+        DialogMode::Search { query, results, selected } => {
+            let searching = app.search_job.is_some();
+            let results_text = if results.is_empty() {
+                if query.is_empty() {
+                    "  (Type to search...)".to_string()
+                } else if searching {
+                    "  Searching...".to_string()
+                } else {
+                    "  No results found".to_string()
+                }
+            } else {
+                // Show paths relative to the search root, keeping the selection visible
+                let root = &app.active_fs().current_dir;
+                let max_visible = (area.height as usize).saturating_sub(9).max(1);
+                let start = selected.saturating_sub(max_visible - 1);
+                results
+                    .iter()
+                    .enumerate()
+                    .skip(start)
+                    .take(max_visible)
+                    .map(|(i, (path, _))| {
+                        let marker = if i == *selected { "►" } else { " " };
+                        let relative = path.strip_prefix(root).unwrap_or(path);
+                        format!("  {} {}", marker, truncate_path(&relative.to_string_lossy(), 50))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+
+            (
+                " SEARCH ",
+                format!("\n  Query: {}\n\n  Results ({} found{}):\n{}\n\n  ↑/↓: Select  |  ENTER: Jump  |  ESC: Cancel",
+                         truncate_path(query, 40),
+                         results.len(),
+                         if searching { ", searching" } else { "" },
+                         results_text)
+            )
+        },
+        DialogMode::Grep { query, hits, selected } => {
+            let searching = app.grep_job.is_some();
+            let hits_text = if hits.is_empty() {
+                if query.is_empty() {
+                    "  (Type to search file contents...)".to_string()
+                } else if searching {
+                    "  Searching...".to_string()
+                } else {
+                    "  No matches found".to_string()
+                }
+            } else {
+                // "path:line  text", paths relative to the search root
+                let root = &app.active_fs().current_dir;
+                let max_visible = (area.height as usize).saturating_sub(9).max(1);
+                let start = selected.saturating_sub(max_visible - 1);
+                hits
+                    .iter()
+                    .enumerate()
+                    .skip(start)
+                    .take(max_visible)
+                    .map(|(i, hit)| {
+                        let marker = if i == *selected { "►" } else { " " };
+                        let relative = hit.path.strip_prefix(root).unwrap_or(&hit.path);
+                        let location = format!("{}:{}", truncate_path(&relative.to_string_lossy(), 30), hit.line);
+                        format!("  {} {}  {}", marker, location, truncate_str(&hit.text, 50))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+            let capped = if hits.len() >= crate::fs::grep::MAX_HITS { ", limit reached" } else { "" };
+
+            (
+                " GREP ",
+                format!("\n  Pattern: {}\n\n  Matches ({} found{}{}):\n{}\n\n  ↑/↓: Select  |  ENTER: Open at line  |  ESC: Cancel",
+                         truncate_path(query, 40),
+                         hits.len(),
+                         capped,
+                         if searching { ", searching" } else { "" },
+                         hits_text)
+            )
+        },
+        DialogMode::Command { input } => {
+            (
+                " COMMAND MODE ",
+                format!("\n  :{}\n\n  ENTER: Execute  |  ESC: Cancel",
+                         truncate_path(input, 40))
+            )
+        },
+        DialogMode::SftpConnect { target } => {
+            (
+                " SFTP CONNECT ",
+                format!("\n  Host: {}_\n\n  user@host[:port] (keys from ssh-agent or ~/.ssh)\n  ENTER: Connect  |  ESC: Cancel",
+                         truncate_path(target, 40))
+            )
+        },
+        DialogMode::QuitConfirm => {
+            (
+                " QUIT CONFIRMATION ",
+                "\n  Are you sure you want to quit?\n\n  Y: Quit  |  N/ESC: Cancel".to_string()
+            )
+        },
+        DialogMode::TrustPlugin { name } => {
+            (
+                " TRUST PLUGIN ",
+                format!("\n  Give '{}' full file and process access?\n\n  Y: Trust  |  N/ESC: Cancel",
+                         truncate_str(name, 30))
+            )
+        },
+        DialogMode::Properties { rows, .. } => {
+            let lines = rows.iter()
+                .map(|(label, value)| format!("  {:<12} {}", label, truncate_path(value, 60)))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let hint = if cfg!(unix) { "  p: Edit permissions  |  ESC/ENTER: Close" } else { "  ESC/ENTER: Close" };
+            (
+                " PROPERTIES ",
+                format!("\n{}\n\n{}", lines, hint)
+            )
+        },
+        DialogMode::Chmod { path, mode, cursor, octal, confirm, .. } => {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let symbolic = crate::fs::properties::symbolic_mode(*mode);
+            if *confirm {
+                (
+                    " CHANGE PERMISSIONS ",
+                    format!("\n  Set '{}' to {} ({})?\n\n  Y: Apply  |  N/ESC: Back",
+                             truncate_path(&name, 30), crate::app::format_octal_mode(*mode), &symbolic[1..])
+                )
+            } else {
+                // [r][w][x] per class; the bit under the cursor is shown as <r>
+                let classes = ["Owner ", "Group ", "Others"].iter()
+                    .enumerate()
+                    .map(|(class, label)| {
+                        let boxes: String = (0..3)
+                            .map(|bit| {
+                                let index = class * 3 + bit;
+                                let ch = symbolic.chars().nth(index + 1).unwrap_or('-');
+                                if index == *cursor { format!("<{}>", ch) } else { format!("[{}]", ch) }
+                            })
+                            .collect();
+                        format!("{} {}", label, boxes)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("   ");
+                (
+                    " CHANGE PERMISSIONS ",
+                    format!("\n  File:  {}\n  Octal: {}_\n\n  {}\n\n  ←/→: Move  |  SPACE: Toggle  |  0-7: Type octal  |  ENTER: Apply  |  ESC: Cancel",
+                             truncate_path(&name, 40), octal, classes)
+                )
+            }
+        },
+        DialogMode::TextStats { name, stats } => {
             (
-                " DELETE CONFIRMATION ",
-                format!("\n  Delete: {}\n\n  Y: Confirm  |  N/ESC: Cancel",
-                         truncate_path(path_name, 40))
+                " TEXT STATS ",
+                format!(
+                    "\n  File        {}\n  Lines       {}\n  Words       {}\n  Characters  {}\n  Bytes       {}\n\n  ESC/ENTER: Close",
+                    truncate_path(name, 40), stats.lines, stats.words, stats.chars, stats.bytes
+                )
             )
         },
-        DialogMode::NewFile { name } => {
+        DialogMode::ReloadFile => {
             (
-                " NEW FILE ",
-                format!("\n  Name: {}\n\n  ENTER: Create  |  ESC: Cancel",
-                         truncate_path(name, 40))
+                " RELOAD FILE ",
+                "\n  Discard unsaved changes and reload from disk?\n\n  Y: Reload  |  N/ESC: Cancel".to_string()
             )
         },
-        DialogMode::NewFolder { name } => {
+        DialogMode::OpenWith { path, command } => {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
             (
-                " NEW FOLDER ",
-                format!("\n  Name: {}\n\n  ENTER: Create  |  ESC: Cancel",
-                         truncate_path(name, 40))
+                " OPEN WITH ",
+                format!("\n  File:    {}\n  Command: {}_\n\n  ENTER: Run  |  ESC: Cancel",
+                         truncate_path(&name, 40),
+                         command)
             )
         },
-        DialogMode::Search { query, results } => {
-            let results_text = if results.is_empty() {
-                if query.is_empty() {
-                    "  (Type to search...)".to_string()
-                } else {
-                    "  No results found".to_string()
-                }
-            } else {
-                results
+        DialogMode::GoTo { input, candidates, cycle, error } => {
+            let status = if let Some(error) = error {
+                format!("  ✗ {}", error)
+            } else if candidates.len() > 1 {
+                // Show candidate names around the current one
+                let names: Vec<String> = candidates
                     .iter()
-                    .take(5)
-                    .map(|(path, _)| {
-                        let name = path.file_name()
-                            .unwrap_or_default()
-                            .to_string_lossy()
-                            .to_string();
-                        format!("  • {}", truncate_path(&name, 45))
+                    .enumerate()
+                    .map(|(i, c)| {
+                        let name = c.trim_end_matches('/').rsplit('/').next().unwrap_or(c);
+                        if Some(i) == *cycle { format!("[{}]", name) } else { name.to_string() }
                     })
-                    .collect::<Vec<_>>()
-                    .join("\n")
+                    .collect();
+                format!("  {}", truncate_str(&names.join("  "), 54))
+            } else {
+                String::new()
             };
+            (
+                " GO TO PATH ",
+                format!("\n  Path: {}_\n{}\n\n  TAB: Complete  |  ENTER: Go  |  ESC: Cancel",
+                         input, status)
+            )
+        },
+        DialogMode::TemplatePicker { templates, selected } => {
+            let max_visible = (area.height as usize).saturating_sub(6).max(1);
+            let start = selected.saturating_sub(max_visible - 1);
+            let template_lines = templates
+                .iter()
+                .enumerate()
+                .skip(start)
+                .take(max_visible)
+                .map(|(i, template)| {
+                    let marker = if i == *selected { "►" } else { " " };
+                    let kind = if template.is_dir { "folder" } else { "file" };
+                    format!("  {} [{:<6}] {}", marker, kind, truncate_str(&template.name, 40))
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
 
             (
-                " SEARCH ",
-                format!("\n  Query: {}\n\n  Results ({} found):\n{}\n\n  ENTER: Jump  |  ESC: Cancel",
-                         truncate_path(query, 40),
-                         results.len(),
-                         results_text)
+                " NEW FROM TEMPLATE ",
+                format!("\n{}\n\n  ↑/↓: Select  |  ENTER: Choose  |  ESC: Cancel", template_lines)
             )
         },
-        DialogMode::Command { input } => {
+        DialogMode::TemplateName { template, name } => {
             (
-                " COMMAND MODE ",
-                format!("\n  :{}\n\n  ENTER: Execute  |  ESC: Cancel",
-                         truncate_path(input, 40))
+                " NEW FROM TEMPLATE ",
+                format!("\n  Template: {}\n  Name:     {}_\n\n  ENTER: Create  |  ESC: Cancel",
+                         truncate_path(&template.name, 40),
+                         name)
             )
         },
-        DialogMode::QuitConfirm => {
+        DialogMode::TaskPicker { tasks, selected } => {
+            // The exact command sent to the console, wrapped to the popup width
+            let width = (area.width as usize).saturating_sub(6).max(1);
+            let command: Vec<char> = tasks.get(*selected).map(|t| t.command.chars().collect()).unwrap_or_default();
+            let command_lines: Vec<String> = command.chunks(width)
+                .map(|chunk| format!("  {}", chunk.iter().collect::<String>()))
+                .collect();
+
+            // Keep the selection visible within a fixed-size window
+            let max_visible = (area.height as usize).saturating_sub(8 + command_lines.len()).max(1);
+            let start = selected.saturating_sub(max_visible - 1);
+            let task_lines = tasks
+                .iter()
+                .enumerate()
+                .skip(start)
+                .take(max_visible)
+                .map(|(i, task)| {
+                    let marker = if i == *selected { "►" } else { " " };
+                    format!("  {} [{:<5}] {}", marker, task.source.as_str(), truncate_str(&task.name, 40))
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+
             (
-                " QUIT CONFIRMATION ",
-                "\n  Are you sure you want to quit?\n\n  Y: Quit  |  N/ESC: Cancel".to_string()
+                " RUN TASK ",
+                format!("\n{}\n\n  Runs:\n{}\n\n  ↑/↓: Select  |  ENTER: Run in console  |  ESC: Cancel", task_lines, command_lines.join("\n"))
             )
         },
     };
@@ -1673,6 +2984,11 @@ fn draw_settings(f: &mut Frame, app: &App) {
             " [2] Interface ",
             if app.settings_tab == SettingsTab::Interface { tab_style_active } else { tab_style_inactive }
         ),
+        Span::styled(" │ ", Style::default().fg(theme.border)),
+        Span::styled(
+            " [3] Plugins ",
+            if app.settings_tab == SettingsTab::Plugins { tab_style_active } else { tab_style_inactive }
+        ),
     ]);
     let tabs_para = Paragraph::new(tabs);
     f.render_widget(tabs_para, layout[0]);
@@ -1685,12 +3001,22 @@ fn draw_settings(f: &mut Frame, app: &App) {
         SettingsTab::Interface => {
             draw_settings_interface_tab(f, app, layout[1]);
         }
+        SettingsTab::Plugins => {
+            draw_settings_plugins_tab(f, app, layout[1]);
+        }
     }
 
     // Footer
     let footer_text = match app.settings_tab {
-        SettingsTab::Theme => " ↑/↓: Select  |  Enter: Apply  |  1-2: Tab  |  ESC: Close",
-        SettingsTab::Interface => " ↑/↓: Change Value  |  1-2: Tab  |  ESC: Close",
+        SettingsTab::Theme if app.theme_editor.as_ref().is_some_and(|e| e.input.is_some()) => {
+            " Type #RRGGBB or a color name  |  Enter: Set  |  ESC: Cancel"
+        },
+        SettingsTab::Theme if app.theme_editor.is_some() => {
+            " ↑/↓: Role  |  +/-: Lighter/Darker  |  Enter/#: Type Color  |  w: Save as Custom Theme  |  ESC: Discard"
+        },
+        SettingsTab::Theme => " ↑/↓: Select  |  Enter: Apply  |  c: Edit Colors  |  e: Export  |  r: Reload  |  s/S: Syntax Theme  |  3-0: Quick Select  |  1/2/←/→: Tab  |  ESC: Close",
+        SettingsTab::Interface => " ↑/↓: Change Value  |  w: Watcher  |  p: Sort per Dir  |  e: Ext Colors  |  n: Nerd Icons  |  c/d: Clock/Disk  |  1-3/←/→: Tab  |  ESC: Close",
+        SettingsTab::Plugins => " ↑/↓: Select  |  Enter: Enable/Disable  |  t: Trust  |  1-3/←/→: Tab  |  ESC: Close",
     };
     let footer = Paragraph::new(footer_text)
         .style(Style::default().fg(theme.footer_fg));
@@ -1698,7 +3024,6 @@ fn draw_settings(f: &mut Frame, app: &App) {
 }
 
 fn draw_settings_theme_tab(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
-    use crate::config::Theme;
     use ratatui::text::{Line, Span};
     
     let theme = &app.config.theme;
@@ -1708,11 +3033,12 @@ fn draw_settings_theme_tab(f: &mut Frame, app: &App, area: ratatui::layout::Rect
         .constraints([
             Constraint::Min(8),     // Theme list
             Constraint::Length(5),  // Preview
+            Constraint::Length(6),  // Syntax theme preview
         ])
         .split(area);
 
     // Theme list
-    let all_themes = Theme::all_themes();
+    let all_themes = &app.themes;
     let items: Vec<ListItem> = all_themes
         .iter()
         .enumerate()
@@ -1746,10 +3072,13 @@ fn draw_settings_theme_tab(f: &mut Frame, app: &App, area: ratatui::layout::Rect
 
     let theme_list = List::new(items)
         .block(Block::default().borders(Borders::NONE));
-    f.render_widget(theme_list, inner_layout[0]);
+    match &app.theme_editor {
+        Some(editor) => draw_theme_editor(f, app, editor, inner_layout[0]),
+        None => f.render_widget(theme_list, inner_layout[0]),
+    }
 
-    // Preview section
-    let selected_theme = &all_themes[app.settings_theme_index];
+    // Preview section (the edited theme while editing)
+    let selected_theme = if app.theme_editor.is_some() { theme } else { &all_themes[app.settings_theme_index] };
     let preview_text = vec![
         Line::from(vec![
             Span::styled(" Preview: ", Style::default().fg(theme.fg)),
@@ -1771,12 +3100,62 @@ fn draw_settings_theme_tab(f: &mut Frame, app: &App, area: ratatui::layout::Rect
             .borders(Borders::ALL)
             .border_style(Style::default().fg(selected_theme.border)));
     f.render_widget(preview, inner_layout[1]);
+
+    // Syntax theme preview (highlighted with the active syntax theme)
+    const SYNTAX_SAMPLE: &str = "fn main() {\n    let name = \"senterm\"; // greet\n    println!(\"Hello, {}!\", name);\n}";
+    let syntax_lines: Vec<Line> = crate::viewer::highlight_code(SYNTAX_SAMPLE, "rs", &app.config.syntax_theme)
+        .into_iter()
+        .map(|line| Line::from(
+            std::iter::once(Span::raw(" "))
+                .chain(line.segments.into_iter().map(|seg| Span::styled(seg.text, Style::default().fg(seg.fg))))
+                .collect::<Vec<_>>()
+        ))
+        .collect();
+    let syntax_preview = Paragraph::new(syntax_lines)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border))
+            .title(format!(" Syntax: {} (s/S to change) ", app.config.syntax_theme)));
+    f.render_widget(syntax_preview, inner_layout[2]);
+}
+
+/// Color roles of the theme being edited, with their current values
+fn draw_theme_editor(f: &mut Frame, app: &App, editor: &crate::app::ThemeEditor, area: ratatui::layout::Rect) {
+    use ratatui::text::{Line, Span};
+
+    let theme = &app.config.theme;
+    let visible = area.height.saturating_sub(1).max(1) as usize;
+    let first = editor.role.saturating_sub(visible - 1);
+    let mut lines = vec![Line::from(Span::styled(
+        format!(" Editing: {}", theme.name),
+        Style::default().fg(theme.accent_color).add_modifier(Modifier::BOLD),
+    ))];
+    for (i, role) in crate::config::Theme::COLOR_ROLES.iter().enumerate().skip(first).take(visible) {
+        let color = theme.color(role).unwrap_or_default();
+        let is_selected = i == editor.role;
+        let value = match &editor.input {
+            Some(input) if is_selected => format!("{}█", input),
+            _ => color.to_string(),
+        };
+        let style = if is_selected {
+            Style::default().fg(theme.selection_fg).bg(theme.selection_bg).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.fg)
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!(" {} {:<15}", if is_selected { "►" } else { " " }, role), style),
+            Span::styled("████", Style::default().fg(color)),
+            Span::styled(format!(" {}", value), style),
+        ]));
+    }
+    f.render_widget(Paragraph::new(lines), area);
 }
 
 fn draw_settings_interface_tab(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     use ratatui::text::{Line, Span};
     
     let theme = &app.config.theme;
+    let ext_colors_on = app.config.ext_colors_enabled && !app.config.ext_colors.is_empty();
     
     let content_text = vec![
         Line::from(""),
@@ -1797,6 +3176,89 @@ fn draw_settings_interface_tab(f: &mut Frame, app: &App, area: ratatui::layout::
             Span::styled(" Default: 3, Maximum: 10", Style::default().fg(theme.footer_fg)),
         ]),
         Line::from(""),
+        Line::from(vec![
+            Span::styled(" File Watcher: ", Style::default().fg(theme.fg).add_modifier(Modifier::BOLD)),
+            Span::styled(
+                if app.config.enable_watcher { "ON" } else { "OFF" },
+                Style::default().fg(if app.config.enable_watcher { Color::Green } else { Color::Red }),
+            ),
+            Span::styled(format!(" ({} dirs watched)", app.watch_count()), Style::default().fg(theme.footer_fg)),
+        ]),
+        Line::from(vec![
+            Span::styled(" w to toggle; turn off on network mounts where change events are unreliable", Style::default().fg(theme.fg)),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(" Remember Sort per Directory: ", Style::default().fg(theme.fg).add_modifier(Modifier::BOLD)),
+            Span::styled(
+                if app.config.persist_dir_sort { "ON" } else { "OFF" },
+                Style::default().fg(if app.config.persist_dir_sort { Color::Green } else { Color::Red }),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled(" p to toggle; when on, 's' saves the order to the directory's .senterm-sort", Style::default().fg(theme.fg)),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(" Directories First: ", Style::default().fg(theme.fg).add_modifier(Modifier::BOLD)),
+            Span::styled(
+                if app.config.dirs_first { "ON" } else { "OFF" },
+                Style::default().fg(if app.config.dirs_first { Color::Green } else { Color::Red }),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled(" f to toggle; off sorts files and directories together by the sort key", Style::default().fg(theme.fg)),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(" Extension Colors: ", Style::default().fg(theme.fg).add_modifier(Modifier::BOLD)),
+            Span::styled(
+                if ext_colors_on { format!("{} extensions", app.config.ext_colors.len()) } else { "OFF".to_string() },
+                Style::default().fg(if ext_colors_on { Color::Green } else { Color::Red }),
+            ),
+        ]),
+        Line::from(ext_color_samples(&app.config)),
+        Line::from(vec![
+            Span::styled(" e to toggle (the default palette when none is set); edit [ext_colors] in config.toml", Style::default().fg(theme.fg)),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(" Nerd Font Icons: ", Style::default().fg(theme.fg).add_modifier(Modifier::BOLD)),
+            Span::styled(
+                if app.config.use_nerd_icons { "ON" } else { "OFF" },
+                Style::default().fg(if app.config.use_nerd_icons { Color::Green } else { Color::Red }),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled(" n to toggle; needs a Nerd Font in the terminal, otherwise the glyphs show as boxes", Style::default().fg(theme.fg)),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(" Footer Clock: ", Style::default().fg(theme.fg).add_modifier(Modifier::BOLD)),
+            Span::styled(
+                if app.config.show_clock { "ON" } else { "OFF" },
+                Style::default().fg(if app.config.show_clock { Color::Green } else { Color::Red }),
+            ),
+            Span::styled("   Footer Disk Space: ", Style::default().fg(theme.fg).add_modifier(Modifier::BOLD)),
+            Span::styled(
+                if app.config.show_disk_space { "ON" } else { "OFF" },
+                Style::default().fg(if app.config.show_disk_space { Color::Green } else { Color::Red }),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled(" c / d to toggle; disk space is for the filesystem holding the active directory", Style::default().fg(theme.fg)),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(" Text Preview Limit: ", Style::default().fg(theme.fg).add_modifier(Modifier::BOLD)),
+            Span::styled(crate::viewer::format_limit(app.config.preview_limits().text_bytes), Style::default().fg(theme.directory_fg)),
+            Span::styled("   Hex View Limit: ", Style::default().fg(theme.fg).add_modifier(Modifier::BOLD)),
+            Span::styled(crate::viewer::format_limit(app.config.preview_limits().binary_bytes), Style::default().fg(theme.directory_fg)),
+        ]),
+        Line::from(vec![
+            Span::styled(" t / T and b / B to double / halve; larger text opens windowed, larger binaries aren't shown", Style::default().fg(theme.fg)),
+        ]),
+        Line::from(""),
     ];
     
     let para = Paragraph::new(content_text)
@@ -1804,6 +3266,108 @@ fn draw_settings_interface_tab(f: &mut Frame, app: &App, area: ratatui::layout::
     f.render_widget(para, area);
 }
 
+/// Configured extensions, each drawn in its color
+fn ext_color_samples(config: &crate::config::Config) -> Vec<ratatui::text::Span<'static>> {
+    let mut exts: Vec<_> = config.ext_colors.iter().collect();
+    exts.sort_by(|a, b| a.0.cmp(b.0));
+    let mut spans = vec![ratatui::text::Span::raw(" ")];
+    spans.extend(exts.into_iter().map(|(ext, color)| {
+        ratatui::text::Span::styled(format!(" .{}", ext), Style::default().fg(*color))
+    }));
+    spans
+}
+
+fn draw_settings_plugins_tab(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    use ratatui::text::{Line, Span};
+
+    let theme = &app.config.theme;
+    let plugins = app.plugins.list();
+    let label = Style::default().fg(theme.fg).add_modifier(Modifier::BOLD);
+    let dim = Style::default().fg(theme.footer_fg);
+
+    let mut content_text = vec![
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(" Plugins", Style::default().fg(theme.accent_color).add_modifier(Modifier::BOLD)),
+        ]),
+        Line::from(vec![
+            Span::styled(" Directory: ", label),
+            Span::styled(app.plugins.plugin_dir().display().to_string(), Style::default().fg(theme.directory_fg)),
+        ]),
+        Line::from(""),
+    ];
+
+    if plugins.is_empty() {
+        content_text.push(Line::from(Span::styled(" No plugins found. Add a .lua file or a plugin folder above.", dim)));
+    }
+    for (i, plugin) in plugins.iter().enumerate() {
+        let selected = i == app.settings_plugin_index;
+        let name_style = if selected {
+            Style::default().fg(theme.selection_fg).bg(theme.selection_bg).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.fg)
+        };
+        let (state, state_color) = if plugin.enabled { ("ON ", Color::Green) } else { ("OFF", Color::Red) };
+        let (trust, trust_color) = if plugin.trusted { ("trusted", Color::Yellow) } else { ("sandboxed", Color::Green) };
+        content_text.push(Line::from(vec![
+            Span::styled(if selected { " ▶ " } else { "   " }, Style::default().fg(theme.accent_color)),
+            Span::styled(format!("[{}] ", state), Style::default().fg(state_color)),
+            Span::styled(format!("{:<24}", truncate_str(&plugin.manifest.name, 24)), name_style),
+            Span::styled(format!(" v{:<10}", plugin.manifest.version), dim),
+            Span::styled(format!(" {}", trust), Style::default().fg(trust_color)),
+        ]));
+    }
+
+    // Details of the highlighted plugin
+    if let Some(plugin) = plugins.get(app.settings_plugin_index) {
+        let manifest = &plugin.manifest;
+        content_text.push(Line::from(""));
+        content_text.push(Line::from(Span::styled(format!(" {}", manifest.name), Style::default().fg(theme.accent_color).add_modifier(Modifier::BOLD))));
+        if !manifest.description.is_empty() {
+            content_text.push(Line::from(Span::styled(format!("   {}", manifest.description), Style::default().fg(theme.fg))));
+        }
+        if !manifest.author.is_empty() {
+            content_text.push(Line::from(vec![Span::styled("   Author: ", label), Span::styled(manifest.author.clone(), dim)]));
+        }
+        content_text.push(Line::from(Span::styled("   Commands:", label)));
+        if manifest.commands.is_empty() {
+            content_text.push(Line::from(Span::styled("     (none)", dim)));
+        }
+        for command in &manifest.commands {
+            let key = command.keybinding.as_ref().map(|k| format!(" [{}]", k)).unwrap_or_default();
+            content_text.push(Line::from(vec![
+                Span::styled(format!("     {}{}", command.name, key), Style::default().fg(theme.fg)),
+                Span::styled(format!("  {}", command.description), dim),
+            ]));
+        }
+        content_text.push(Line::from(Span::styled("   Handlers:", label)));
+        if manifest.handlers.is_empty() && manifest.hooks.is_empty() {
+            content_text.push(Line::from(Span::styled("     (none)", dim)));
+        }
+        for handler in &manifest.handlers {
+            content_text.push(Line::from(vec![
+                Span::styled(format!("     .{}", handler.extensions.join(" .")), Style::default().fg(theme.fg)),
+                Span::styled(format!("  {} → {}()", handler.handler_type, handler.function), dim),
+            ]));
+        }
+        for hook in &manifest.hooks {
+            content_text.push(Line::from(vec![
+                Span::styled(format!("     on {}", hook.event), Style::default().fg(theme.fg)),
+                Span::styled(format!("  → {}()", hook.function), dim),
+            ]));
+        }
+    }
+
+    content_text.push(Line::from(""));
+    content_text.push(Line::from(Span::styled(
+        " Sandboxed plugins can only read files under the browsed directory and can't run programs.",
+        dim,
+    )));
+
+    let para = Paragraph::new(content_text)
+        .style(Style::default().bg(theme.bg));
+    f.render_widget(para, area);
+}
 
 fn draw_temp_message_popup(f: &mut Frame, app: &App) {
     if let Some((message, _)) = &app.temp_message {
@@ -1833,6 +3397,55 @@ fn draw_temp_message_popup(f: &mut Frame, app: &App) {
     }
 }
 
+fn draw_summary_popup(f: &mut Frame, app: &App) {
+    use crate::fs::ItemOutcome;
+
+    let Some(summary) = &app.operation_summary else { return };
+    let theme = &app.config.theme;
+    let area = centered_rect(60, 60, f.area());
+
+    f.render_widget(ratatui::widgets::Clear, area);
+
+    let lines: Vec<ratatui::text::Line> = summary.items.iter()
+        .skip(app.summary_scroll)
+        .map(|item| {
+            let name = item.path.file_name()
+                .unwrap_or(item.path.as_os_str())
+                .to_string_lossy()
+                .to_string();
+            let (marker, color, reason) = match &item.outcome {
+                ItemOutcome::Ok => ("✓", Color::Green, String::new()),
+                ItemOutcome::Skipped(reason) => ("–", Color::Yellow, format!(" — {}", reason)),
+                ItemOutcome::Failed(reason) => ("✗", Color::Red, format!(" — {}", reason)),
+            };
+            ratatui::text::Line::from(vec![
+                ratatui::text::Span::styled(format!(" {} ", marker), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+                ratatui::text::Span::styled(name, Style::default().fg(theme.fg)),
+                ratatui::text::Span::styled(reason, Style::default().fg(color)),
+            ])
+        })
+        .collect();
+
+    let title = format!(
+        " {} — {} ok, {} skipped, {} failed ",
+        summary.operation.to_uppercase(),
+        summary.succeeded(),
+        summary.skipped(),
+        summary.failed()
+    );
+
+    let para = Paragraph::new(lines)
+        .style(Style::default().bg(theme.bg))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(theme.accent_color))
+            .title(title)
+            .title_bottom(" j/k: Scroll | Esc: Close "));
+
+    f.render_widget(para, area);
+}
+
 fn draw_shell_popup(f: &mut Frame, app: &App) {
     let theme = &app.config.theme;
     
@@ -1844,7 +3457,7 @@ fn draw_shell_popup(f: &mut Frame, app: &App) {
     
     // Main block
     let title = if app.shell.is_running {
-        format!(" SHELL - {} ", truncate_path(&app.shell.working_dir.display().to_string(), 50))
+        format!(" SHELL - {} ", truncate_path(&app.shell.display_dir().display().to_string(), 50))
     } else {
         " SHELL - Not Running ".to_string()
     };
@@ -1873,77 +3486,91 @@ fn draw_shell_popup(f: &mut Frame, app: &App) {
     
     let terminal_area = layout[0];
     
-    // Render terminal content from vt100 parser
-    if let Ok(parser) = app.shell.parser.try_lock() {
+    // Render terminal content from vt100 parser (the frozen copy in copy-mode)
+    let mut scrolled_back = 0;
+    let copy_mode = app.shell.copy_mode.as_ref();
+    if let Some(copy) = copy_mode {
+        draw_vt100_rows(f, &copy.screen, terminal_area, theme, Some(copy));
+        f.set_cursor_position((terminal_area.x + copy.cursor.1, terminal_area.y + copy.cursor.0));
+    } else if let Ok(parser) = app.shell.parser.try_lock() {
         let screen = parser.screen();
+        scrolled_back = screen.scrollback();
+        draw_vt100_rows(f, screen, terminal_area, theme, None);
         
-        // Render each row
-        for row in 0..terminal_area.height {
-            let row_idx = row as usize;
-            if row_idx >= screen.size().0 as usize {
-                break;
-            }
-            
-            let mut spans: Vec<ratatui::text::Span> = Vec::new();
-            let mut current_text = String::new();
-            let mut current_style = Style::default();
-            
-            for col in 0..terminal_area.width {
-                let col_idx = col as usize;
-                if col_idx >= screen.size().1 as usize {
-                    break;
-                }
-                
-                let cell = screen.cell(row_idx as u16, col_idx as u16);
-                
-                if let Some(cell) = cell {
-                    // Convert vt100 colors to ratatui colors with theme support
-                    let style = convert_vt100_style(&cell, theme);
-                    
-                    if style != current_style && !current_text.is_empty() {
-                        spans.push(ratatui::text::Span::styled(current_text.clone(), current_style));
-                        current_text.clear();
-                    }
-                    
-                    current_style = style;
-                    current_text.push(cell.contents().chars().next().unwrap_or(' '));
-                } else {
-                    current_text.push(' ');
-                }
-            }
-            
-            if !current_text.is_empty() {
-                spans.push(ratatui::text::Span::styled(current_text, current_style));
-            }
-            
-            let line = ratatui::text::Line::from(spans);
-            let para = Paragraph::new(line);
-            f.render_widget(para, ratatui::layout::Rect {
-                x: terminal_area.x,
-                y: terminal_area.y + row,
-                width: terminal_area.width,
-                height: 1,
-            });
-        }
-        
-        // Set cursor position from vt100 screen
+        // Set cursor position from vt100 screen (not while scrolled back)
         let (cursor_row, cursor_col) = screen.cursor_position();
         let cursor_x = terminal_area.x + cursor_col;
         let cursor_y = terminal_area.y + cursor_row;
         
-        if cursor_x < terminal_area.x + terminal_area.width && 
+        if scrolled_back == 0 &&
+           cursor_x < terminal_area.x + terminal_area.width && 
            cursor_y < terminal_area.y + terminal_area.height {
             f.set_cursor_position((cursor_x, cursor_y));
         }
     }
     
     // Help line
-    let help_text = " Full PTY Shell | F12/`:Close | All keys forwarded to shell ";
-    let help_para = Paragraph::new(help_text)
-        .style(Style::default().fg(theme.footer_fg).bg(theme.footer_bg));
+    let help_para = if copy_mode.is_some() {
+        copy_mode_indicator(theme)
+    } else if scrolled_back > 0 {
+        scrollback_indicator(scrolled_back, theme)
+    } else {
+        Paragraph::new(" Full PTY Shell | F12/`:Close | Shift+PgUp/PgDn:Scrollback | F7:Copy | Other keys forwarded to shell ")
+            .style(Style::default().fg(theme.footer_fg).bg(theme.footer_bg))
+    };
     f.render_widget(help_para, layout[1]);
 }
 
+/// Draw a vt100 screen cell by cell; copy-mode selections are shown reversed
+fn draw_vt100_rows(f: &mut Frame, screen: &vt100::Screen, area: ratatui::layout::Rect, theme: &crate::config::Theme, copy: Option<&crate::copy_mode::CopyMode>) {
+    let (screen_rows, screen_cols) = screen.size();
+    for row in 0..area.height.min(screen_rows) {
+        let mut spans: Vec<ratatui::text::Span> = Vec::new();
+        let mut current_text = String::new();
+        let mut current_style = Style::default();
+
+        for col in 0..area.width.min(screen_cols) {
+            let (text, mut style) = match screen.cell(row, col) {
+                // Convert vt100 colors to ratatui colors with theme support
+                Some(cell) => (cell.contents().chars().next().unwrap_or(' '), convert_vt100_style(cell, theme)),
+                None => (' ', Style::default()),
+            };
+            if copy.is_some_and(|c| c.is_selected(row, col)) {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+
+            if style != current_style && !current_text.is_empty() {
+                spans.push(ratatui::text::Span::styled(std::mem::take(&mut current_text), current_style));
+            }
+            current_style = style;
+            current_text.push(text);
+        }
+
+        if !current_text.is_empty() {
+            spans.push(ratatui::text::Span::styled(current_text, current_style));
+        }
+
+        f.render_widget(Paragraph::new(ratatui::text::Line::from(spans)), ratatui::layout::Rect {
+            x: area.x,
+            y: area.y + row,
+            width: area.width,
+            height: 1,
+        });
+    }
+}
+
+/// Help line shown while a shell is in copy-mode
+fn copy_mode_indicator(theme: &crate::config::Theme) -> Paragraph<'static> {
+    Paragraph::new(" COPY MODE | ←↑↓→/hjkl:Move | 0/$:Line Start/End | v/Space:Select | y/Enter:Copy | Esc:Cancel ")
+        .style(Style::default().fg(theme.bg).bg(theme.accent_color).add_modifier(Modifier::BOLD))
+}
+
+/// Help line shown instead of the usual one while a shell is scrolled back
+fn scrollback_indicator(rows: usize, theme: &crate::config::Theme) -> Paragraph<'static> {
+    Paragraph::new(format!(" ↑ SCROLLBACK: {} lines up | Shift+PgUp/PgDn/↑/↓:Scroll | Any other key:Live ", rows))
+        .style(Style::default().fg(theme.bg).bg(theme.accent_color).add_modifier(Modifier::BOLD))
+}
+
 /// Convert vt100 cell style to ratatui Style with theme support
 fn convert_vt100_style(cell: &vt100::Cell, theme: &crate::config::Theme) -> Style {
     let mut style = Style::default();
@@ -2027,27 +3654,105 @@ fn draw_process_viewer_popup(f: &mut Frame, app: &App) {
     let layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
+            Constraint::Length(2),   // System summary (CPU, cores, memory)
             Constraint::Length(2),   // Header (filter, sort, search)
             Constraint::Min(10),     // Process list
-            Constraint::Length(6),   // Details panel
+            // Details panel (taller with open files / ports)
+            Constraint::Length(if app.process_viewer.show_resources { 6 + PROCESS_RESOURCE_LINES } else { 6 }),
             Constraint::Length(1),   // Footer (help)
         ])
         .split(inner_area);
     
+    // System summary
+    draw_process_summary(f, app, layout[0]);
+    
     // Header
-    draw_process_header(f, app, layout[0]);
+    draw_process_header(f, app, layout[1]);
     
     // Process list
-    draw_process_list(f, app, layout[1]);
+    draw_process_list(f, app, layout[2]);
     
     // Details panel
-    draw_process_details(f, app, layout[2]);
+    draw_process_details(f, app, layout[3]);
     
-    // Footer
-    let footer_text = " ↑↓:Navigate  t:Toggle Tree  k:Kill  K:Force Kill  p:Parent  f:Filter  s:Sort  /:Search  F9/ESC:Close ";
-    let footer = Paragraph::new(footer_text)
-        .style(Style::default().fg(theme.footer_fg).bg(theme.footer_bg));
-    f.render_widget(footer, layout[3]);
+    // Footer: force kill confirmation, else last result and key help
+    let viewer = &app.process_viewer;
+    let help = format!(
+        " ↑↓:Navigate  t:Toggle Tree  z/Z:Collapse/Expand All  Del:Terminate  K:Force Kill  n:Nice  o:Files/Ports  p:Parent  f:Filter  c/m:Min CPU/MEM  s:Sort  /:Search  E:Export({})  J:CSV/JSON  F9/ESC:Close ",
+        viewer.export_format.extension().to_uppercase()
+    );
+    let footer = if let Some(process) = viewer.confirm_kill.and_then(|pid| viewer.processes.get(&pid)) {
+        Paragraph::new(format!(" Force kill {} ({}) with SIGKILL? (y/N) ", process.name, process.pid))
+            .style(Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD))
+    } else if let Some((pid, input)) = &viewer.renice_input {
+        let name = viewer.processes.get(pid).map(|process| process.name.as_str()).unwrap_or_default();
+        Paragraph::new(format!(" Nice value of {} ({}) (-20..19): {}█  Enter:Apply  Esc:Cancel", name, pid, input))
+            .style(Style::default().fg(theme.footer_fg).bg(theme.footer_bg).add_modifier(Modifier::BOLD))
+    } else {
+        let text = match &viewer.message {
+            Some(message) => format!(" {} │{}", message, help),
+            None => help,
+        };
+        Paragraph::new(text).style(Style::default().fg(theme.footer_fg).bg(theme.footer_bg))
+    };
+    f.render_widget(footer, layout[4]);
+}
+
+/// Color for a load percentage (green / yellow / red)
+fn load_color(percent: f32) -> Color {
+    if percent > 80.0 { Color::Red } else if percent > 50.0 { Color::Yellow } else { Color::Green }
+}
+
+/// Horizontal usage bar of `width` cells
+fn usage_bar(percent: f32, width: usize) -> String {
+    let filled = ((percent.clamp(0.0, 100.0) / 100.0) * width as f32).round() as usize;
+    "█".repeat(filled) + &"░".repeat(width - filled)
+}
+
+/// Single-cell vertical bar for one core's load
+fn core_bar_char(percent: f32) -> char {
+    const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let index = ((percent.clamp(0.0, 100.0) / 100.0) * (LEVELS.len() - 1) as f32).round() as usize;
+    LEVELS[index]
+}
+
+fn draw_process_summary(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let summary = &app.process_viewer.summary;
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Length(1)])
+        .split(area);
+
+    // Total CPU and memory
+    let memory_percent = summary.memory_percent();
+    let totals = ratatui::text::Line::from(vec![
+        ratatui::text::Span::styled(" CPU ", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+        ratatui::text::Span::styled(
+            format!("[{}] {:>5.1}%", usage_bar(summary.cpu_percent, 20), summary.cpu_percent),
+            Style::default().fg(load_color(summary.cpu_percent)),
+        ),
+        ratatui::text::Span::styled("   MEM ", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+        ratatui::text::Span::styled(
+            format!(
+                "[{}] {} / {}",
+                usage_bar(memory_percent, 20),
+                format_bytes(summary.used_memory),
+                format_bytes(summary.total_memory)
+            ),
+            Style::default().fg(load_color(memory_percent)),
+        ),
+    ]);
+    f.render_widget(Paragraph::new(totals), rows[0]);
+
+    // One mini-bar per core
+    let mut cores = vec![ratatui::text::Span::styled(
+        format!(" {} cores ", summary.core_usage.len()),
+        Style::default().fg(Color::DarkGray),
+    )];
+    cores.extend(summary.core_usage.iter().map(|&usage| {
+        ratatui::text::Span::styled(core_bar_char(usage).to_string(), Style::default().fg(load_color(usage)))
+    }));
+    f.render_widget(Paragraph::new(ratatui::text::Line::from(cores)), rows[1]);
 }
 
 fn draw_process_header(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
@@ -2058,6 +3763,7 @@ fn draw_process_header(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
         .constraints([
             Constraint::Length(25),  // Filter
             Constraint::Length(20),  // Sort
+            Constraint::Length(24),  // CPU/MEM thresholds
             Constraint::Min(20),     // Search
             Constraint::Length(15),  // Process count
         ])
@@ -2076,6 +3782,19 @@ fn draw_process_header(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
         .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
     f.render_widget(sort, header_layout[1]);
     
+    // Thresholds
+    let threshold_text = if viewer.min_cpu > 0.0 || viewer.min_mem_percent > 0.0 {
+        format!(" Min: CPU≥{}% MEM≥{}% ", viewer.min_cpu, viewer.min_mem_percent)
+    } else {
+        " Min: off ".to_string()
+    };
+    let threshold_style = if viewer.min_cpu > 0.0 || viewer.min_mem_percent > 0.0 {
+        Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::DarkGray)
+    };
+    f.render_widget(Paragraph::new(threshold_text).style(threshold_style), header_layout[2]);
+    
     // Search
     let search_text = if viewer.search_mode {
         format!(" Search: {}_ ", viewer.search_query)
@@ -2090,14 +3809,14 @@ fn draw_process_header(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
         Style::default().fg(Color::DarkGray)
     };
     let search = Paragraph::new(search_text).style(search_style);
-    f.render_widget(search, header_layout[2]);
+    f.render_widget(search, header_layout[3]);
     
     // Process count
     let count_text = format!(" {} procs ", viewer.tree_order.len());
     let count = Paragraph::new(count_text)
         .style(Style::default().fg(Color::White))
         .alignment(ratatui::layout::Alignment::Right);
-    f.render_widget(count, header_layout[3]);
+    f.render_widget(count, header_layout[4]);
 }
 
 fn draw_process_list(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
@@ -2123,8 +3842,9 @@ fn draw_process_list(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
         height: 1,
     });
     
-    // Calculate visible height
+    // Calculate visible height (kept for the key handlers' scrolling)
     let visible_height = list_area.height as usize;
+    app.process_list_height.set(visible_height);
     
     // Get visible processes
     let visible = viewer.visible_processes(visible_height);
@@ -2192,6 +3912,9 @@ fn draw_process_list(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     }
 }
 
+/// Lines reserved for the open files / ports section
+const PROCESS_RESOURCE_LINES: u16 = 4;
+
 fn draw_process_details(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     let viewer = &app.process_viewer;
     
@@ -2213,16 +3936,18 @@ fn draw_process_details(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
                 Constraint::Length(1),  // CPU bar
                 Constraint::Length(1),  // Memory bar
                 Constraint::Length(1),  // CPU history sparkline
+                Constraint::Min(0),     // Open files / ports (when expanded)
             ])
             .split(inner);
         
         // Name and PID
         let info_text = format!(
-            " {} (PID: {}) | Parent: {} | User: {} | Started: {}s ago",
+            " {} (PID: {}) | Parent: {} | User: {} | Nice: {} | Started: {}s ago",
             process.name,
             process.pid,
             process.parent_pid.map(|p| p.to_string()).unwrap_or_else(|| "-".to_string()),
             process.user.as_deref().unwrap_or("-"),
+            crate::process::priority::get_nice(process.pid).map(|n| n.to_string()).unwrap_or_else(|| "-".to_string()),
             std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .map(|d| d.as_secs().saturating_sub(process.start_time))
@@ -2307,9 +4032,150 @@ fn draw_process_details(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
             let history = Paragraph::new(history_line);
             f.render_widget(history, detail_layout[4]);
         }
+
+        if viewer.show_resources {
+            let lines: Vec<ratatui::text::Line> = match viewer.selected_resources() {
+                None => vec![ratatui::text::Line::from(" Loading open files / ports...")],
+                Some(None) => vec![ratatui::text::Line::from(" Open files / ports: unavailable on this platform")],
+                Some(Some(resources)) => {
+                    let fds = resources.open_fds.map(|n| n.to_string()).unwrap_or_else(|| "permission denied".to_string());
+                    let mut lines = vec![ratatui::text::Line::from(format!(
+                        " Open files: {}  |  Ports: {}",
+                        fds,
+                        resources.ports.len()
+                    ))];
+                    let shown = PROCESS_RESOURCE_LINES as usize - 1;
+                    for (i, port) in resources.ports.iter().enumerate().take(shown) {
+                        let text = if i + 1 == shown && resources.ports.len() > shown {
+                            format!("   ... and {} more", resources.ports.len() - i)
+                        } else {
+                            format!("   {}", port)
+                        };
+                        lines.push(ratatui::text::Line::from(text));
+                    }
+                    lines
+                },
+            };
+            f.render_widget(Paragraph::new(lines).style(Style::default().fg(Color::Gray)), detail_layout[5]);
+        }
     } else {
         let no_selection = Paragraph::new(" No process selected")
             .style(Style::default().fg(Color::DarkGray));
         f.render_widget(no_selection, inner);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_bars() {
+        assert_eq!(usage_bar(50.0, 4), "██░░");
+        assert_eq!(usage_bar(150.0, 3), "███");
+        assert_eq!(core_bar_char(0.0), '▁');
+        assert_eq!(core_bar_char(100.0), '█');
+        assert_eq!(load_color(90.0), Color::Red);
+    }
+
+    #[test]
+    fn test_hex_row_line() {
+        let text = |data: &[u8], row| -> String {
+            hex_row_line(data, row, Color::Reset, &|_| [Style::default(); 3]).spans.iter().map(|s| s.content.as_ref()).collect()
+        };
+        assert_eq!(text(b"Hello", 0), format!(" 00000000  48 65 6C 6C 6F {} Hello", " ".repeat(3 * 11 + 1)));
+        // Non-printable bytes show as '.' and the second row starts at offset 0x10
+        let data: Vec<u8> = (0..20).collect();
+        assert!(text(&data, 1).starts_with(" 00000010  10 11 12 13 "));
+        assert!(text(&data, 1).ends_with(" ...."));
+        assert!(text(&data, 0).contains("07  08"));
+    }
+
+    #[test]
+    fn test_parse_markdown_simple_table() {
+        let text = "| Name | Size |\n|------|------|\n| a.txt | 10 |\n| b.txt | 20 |\n\nAfter";
+        let blocks = parse_markdown(text);
+
+        match &blocks[0] {
+            MarkdownBlock::Table { headers, aligns, rows } => {
+                assert_eq!(headers, &vec!["Name".to_string(), "Size".to_string()]);
+                assert_eq!(aligns, &vec![TableAlign::Left, TableAlign::Left]);
+                assert_eq!(rows.len(), 2);
+                assert_eq!(rows[1], vec!["b.txt".to_string(), "20".to_string()]);
+            }
+            other => panic!("Expected table, got {:?}", other),
+        }
+        assert!(matches!(&blocks[1], MarkdownBlock::Paragraph(p) if p == "After"));
+    }
+
+    #[test]
+    fn test_parse_markdown_table_alignment() {
+        let text = "| L | C | R |\n|:--|:-:|--:|\n| 1 | 2 | 3 |";
+        let blocks = parse_markdown(text);
+
+        match &blocks[0] {
+            MarkdownBlock::Table { aligns, .. } => {
+                assert_eq!(aligns, &vec![TableAlign::Left, TableAlign::Center, TableAlign::Right]);
+            }
+            other => panic!("Expected table, got {:?}", other),
+        }
+
+        assert_eq!(align_table_cell("ab", 6, TableAlign::Left), "ab    ");
+        assert_eq!(align_table_cell("ab", 6, TableAlign::Right), "    ab");
+        assert_eq!(align_table_cell("ab", 6, TableAlign::Center), "  ab  ");
+    }
+
+    #[test]
+    fn test_render_markdown_table_box() {
+        let text = "| Name | N |\n|:-:|--:|\n| a | 1 |\n| longer | |\n| x |";
+        let rendered: Vec<String> = render_markdown_to_lines(text, 80, 0, crate::viewer::highlight::DEFAULT_SYNTAX_THEME)
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert_eq!(rendered, vec![
+            "┌────────┬─────┐",
+            "│  Name  │   N │",
+            "├────────┼─────┤",
+            "│   a    │   1 │",
+            "│ longer │     │",
+            // Ragged row padded with empty cells
+            "│   x    │     │",
+            "└────────┴─────┘",
+        ]);
+    }
+
+    #[test]
+    fn test_parse_markdown_malformed_table_is_paragraph() {
+        // No separator row
+        let blocks = parse_markdown("| a | b |\n| c | d |");
+        assert!(blocks.iter().all(|b| matches!(b, MarkdownBlock::Paragraph(_))));
+
+        // Separator column count doesn't match the header
+        let blocks = parse_markdown("| a | b |\n|---|");
+        assert!(matches!(&blocks[0], MarkdownBlock::Paragraph(_)));
+    }
+
+    #[test]
+    fn test_format_relative_time() {
+        use std::time::Duration;
+        assert_eq!(format_relative_time(Duration::from_secs(5)), "now");
+        assert_eq!(format_relative_time(Duration::from_secs(5 * 60)), "5m ago");
+        assert_eq!(format_relative_time(Duration::from_secs(3 * 3600)), "3h ago");
+        assert_eq!(format_relative_time(Duration::from_secs(3 * 86_400)), "3d ago");
+        assert_eq!(format_relative_time(Duration::from_secs(60 * 86_400)), "2mo ago");
+        assert_eq!(format_relative_time(Duration::from_secs(800 * 86_400)), "2y ago");
+    }
+
+    #[test]
+    fn test_detailed_entry_line_right_aligns_details() {
+        let line = detailed_entry_line("· notes.txt", "1.0 KB  3d ago", 30);
+        assert_eq!(line.chars().count(), 30);
+        assert!(line.starts_with("· notes.txt "));
+        assert!(line.ends_with("1.0 KB  3d ago"));
+
+        // Long names are truncated to keep the details visible
+        let line = detailed_entry_line("· a_really_long_file_name.txt", "1.0 KB  3d ago", 24);
+        assert_eq!(line.chars().count(), 24);
+        assert!(line.ends_with("1.0 KB  3d ago"));
+    }
+}
@@ -0,0 +1,225 @@
+//! Archive listing for zip and tar files
+//!
+//! Lists entries (name, size, compressed size) without extracting anything.
+//! Listing stops after `MAX_LISTED_ENTRIES` so a hostile archive with
+//! millions of entries can't stall the viewer.
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use super::ViewerContent;
+use crate::ui::format_bytes;
+
+/// Maximum number of entries listed from a single archive
+pub const MAX_LISTED_ENTRIES: usize = 5000;
+
+/// Supported archive formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+/// One entry in an archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// Path inside the archive ('/' separated)
+    pub name: String,
+    pub size: u64,
+    /// Compressed size (zip only; tar compresses the stream as a whole)
+    pub compressed_size: Option<u64>,
+    pub is_dir: bool,
+}
+
+/// Detect the archive format from the file name
+pub fn archive_kind(path: &Path) -> Option<ArchiveKind> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if name.ends_with(".zip") {
+        Some(ArchiveKind::Zip)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(ArchiveKind::TarGz)
+    } else if name.ends_with(".tar") {
+        Some(ArchiveKind::Tar)
+    } else {
+        None
+    }
+}
+
+/// Load an archive listing for the viewer
+pub fn load_archive(path: &Path) -> ViewerContent {
+    match list_archive(path, MAX_LISTED_ENTRIES) {
+        Ok((entries, truncated)) => ViewerContent::ArchiveListing(entries, truncated),
+        Err(e) => ViewerContent::Error(format!("Failed to read archive: {}", e)),
+    }
+}
+
+/// List up to `limit` entries sorted by name; the flag is true if more entries were skipped
+pub fn list_archive(path: &Path, limit: usize) -> Result<(Vec<ArchiveEntry>, bool), String> {
+    let (mut entries, truncated) = match archive_kind(path) {
+        Some(ArchiveKind::Zip) => list_zip(path, limit),
+        Some(ArchiveKind::Tar) => {
+            let file = File::open(path).map_err(|e| e.to_string())?;
+            list_tar(BufReader::new(file), limit)
+        },
+        Some(ArchiveKind::TarGz) => {
+            let file = File::open(path).map_err(|e| e.to_string())?;
+            list_tar(flate2::read::GzDecoder::new(BufReader::new(file)), limit)
+        },
+        None => Err("not a supported archive".to_string()),
+    }?;
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok((entries, truncated))
+}
+
+fn list_zip(path: &Path, limit: usize) -> Result<(Vec<ArchiveEntry>, bool), String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+
+    let mut entries = Vec::new();
+    for index in 0..archive.len().min(limit) {
+        // Raw access reads the header only; nothing is decompressed
+        let entry = archive.by_index_raw(index).map_err(|e| e.to_string())?;
+        entries.push(ArchiveEntry {
+            name: entry.name().to_string(),
+            size: entry.size(),
+            compressed_size: Some(entry.compressed_size()),
+            is_dir: entry.is_dir(),
+        });
+    }
+    Ok((entries, archive.len() > limit))
+}
+
+fn list_tar<R: Read>(reader: R, limit: usize) -> Result<(Vec<ArchiveEntry>, bool), String> {
+    let mut archive = tar::Archive::new(reader);
+    let mut entries = Vec::new();
+
+    for entry in archive.entries().map_err(|e| e.to_string())? {
+        if entries.len() == limit {
+            return Ok((entries, true));
+        }
+        let entry = entry.map_err(|e| e.to_string())?;
+        let header = entry.header();
+        entries.push(ArchiveEntry {
+            name: entry.path().map_err(|e| e.to_string())?.to_string_lossy().to_string(),
+            size: header.size().unwrap_or(0),
+            compressed_size: None,
+            is_dir: header.entry_type().is_dir(),
+        });
+    }
+    Ok((entries, false))
+}
+
+/// Render the (name-sorted) listing as an indented tree with a summary header
+pub fn format_archive_listing(entries: &[ArchiveEntry], truncated: bool) -> String {
+    let files = entries.iter().filter(|e| !e.is_dir).count();
+    let dirs = entries.len() - files;
+    let total_size: u64 = entries.iter().map(|e| e.size).sum();
+    let compressed: Option<u64> = entries.iter().map(|e| e.compressed_size).sum();
+
+    let mut output = String::new();
+    output.push_str("\n  ARCHIVE CONTENTS\n");
+    output.push_str("  ──────────────────────────────\n");
+    output.push_str(&format!(
+        "  {} files, {} folders  |  {} uncompressed{}\n",
+        files,
+        dirs,
+        format_bytes(total_size),
+        compressed.map(|c| format!(", {} compressed", format_bytes(c))).unwrap_or_default()
+    ));
+    if truncated {
+        output.push_str(&format!("  NOTE: Only the first {} entries are listed\n", entries.len()));
+    }
+    output.push('\n');
+    output.push_str(&format!("  {:>10}  {:>10}  Name\n", "Size", "Packed"));
+
+    for entry in entries {
+        let trimmed = entry.name.trim_end_matches('/');
+        let depth = trimmed.matches('/').count();
+        let base = trimmed.rsplit('/').next().unwrap_or(trimmed);
+        let (size, packed) = if entry.is_dir {
+            (String::new(), String::new())
+        } else {
+            (
+                format_bytes(entry.size),
+                entry.compressed_size.map(format_bytes).unwrap_or_else(|| "-".to_string()),
+            )
+        };
+        let name = if entry.is_dir { format!("■ {}/", base) } else { format!("· {}", base) };
+        output.push_str(&format!("  {:>10}  {:>10}  {}{}\n", size, packed, "  ".repeat(depth), name));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    fn write_zip(path: &Path, files: &[(&str, &[u8])]) {
+        let mut writer = zip::ZipWriter::new(File::create(path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        writer.add_directory("docs/", options).unwrap();
+        for (name, data) in files {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn test_archive_kind() {
+        assert_eq!(archive_kind(Path::new("a.ZIP")), Some(ArchiveKind::Zip));
+        assert_eq!(archive_kind(Path::new("a.tar.gz")), Some(ArchiveKind::TarGz));
+        assert_eq!(archive_kind(Path::new("a.tgz")), Some(ArchiveKind::TarGz));
+        assert_eq!(archive_kind(Path::new("a.tar")), Some(ArchiveKind::Tar));
+        assert_eq!(archive_kind(Path::new("a.gz")), None);
+    }
+
+    #[test]
+    fn test_list_zip_and_cap() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("test.zip");
+        write_zip(&path, &[("docs/readme.md", b"hello"), ("main.rs", &[b'x'; 1000])]);
+
+        let (entries, truncated) = list_archive(&path, MAX_LISTED_ENTRIES).unwrap();
+        assert!(!truncated);
+        assert_eq!(entries.len(), 3);
+        assert!(entries[0].is_dir);
+        assert_eq!(entries[2].name, "main.rs");
+        assert_eq!(entries[2].size, 1000);
+        assert!(entries[2].compressed_size.is_some());
+
+        let (entries, truncated) = list_archive(&path, 2).unwrap();
+        assert!(truncated);
+        assert_eq!(entries.len(), 2);
+
+        let text = format_archive_listing(&entries, truncated);
+        assert!(text.contains("1 files, 1 folders"));
+        assert!(text.contains("Only the first 2 entries"));
+        assert!(text.contains("    · readme.md"));
+    }
+
+    #[test]
+    fn test_list_tar_gz() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("test.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(File::create(&path).unwrap(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(4);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, "src/lib.rs", &b"abcd"[..]).unwrap();
+        builder.append_data(&mut header, "Cargo.toml", &b"[ws]"[..]).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        // Entries come back sorted by name, whatever the order in the archive
+        let (entries, truncated) = list_archive(&path, MAX_LISTED_ENTRIES).unwrap();
+        assert!(!truncated);
+        let file = |name: &str| ArchiveEntry { name: name.to_string(), size: 4, compressed_size: None, is_dir: false };
+        assert_eq!(entries, vec![file("Cargo.toml"), file("src/lib.rs")]);
+    }
+}
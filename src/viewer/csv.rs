@@ -0,0 +1,214 @@
+//! CSV/TSV preview as an aligned table
+//!
+//! The delimiter (comma, tab or semicolon) is detected from the first lines;
+//! only the first `csv_preview_rows` data rows are kept for display.
+
+use std::path::Path;
+
+use super::ViewerContent;
+
+/// Data rows shown when none is configured
+pub const DEFAULT_MAX_ROWS: usize = 1000;
+
+/// Widest a column gets before its cells are cut with '…'
+const MAX_COLUMN_WIDTH: usize = 40;
+
+/// Lines looked at when detecting the delimiter
+const DETECT_SAMPLE_LINES: usize = 20;
+
+const DELIMITERS: [u8; 3] = [b',', b'\t', b';'];
+
+/// A parsed delimited file
+#[derive(Debug, Clone)]
+pub struct TableView {
+    pub headers: Vec<String>,
+    /// Data rows, at most the configured limit; ragged rows are padded to the header width
+    pub rows: Vec<Vec<String>>,
+    /// Data rows in the whole file
+    pub total_rows: usize,
+    /// Display width of each column
+    pub widths: Vec<usize>,
+    pub delimiter: u8,
+    /// File content, for editing
+    pub raw: String,
+}
+
+impl TableView {
+    pub fn is_truncated(&self) -> bool {
+        self.rows.len() < self.total_rows
+    }
+
+    /// Summary shown above the table, e.g. "showing 1000 of 52310 rows"
+    pub fn summary(&self) -> String {
+        let delimiter = match self.delimiter {
+            b'\t' => "tab",
+            b';' => "semicolon",
+            _ => "comma",
+        };
+        let rows = if self.is_truncated() {
+            format!("showing {} of {} rows", self.rows.len(), self.total_rows)
+        } else {
+            format!("{} rows", self.total_rows)
+        };
+        format!("{} · {} columns · {}-separated", rows, self.headers.len(), delimiter)
+    }
+
+    /// Cells of `row` from column `first_col` on, padded to their widths and joined with " │ "
+    pub fn format_row(&self, row: &[String], first_col: usize) -> String {
+        self.widths.iter()
+            .enumerate()
+            .skip(first_col)
+            .map(|(i, width)| pad_cell(row.get(i).map(String::as_str).unwrap_or(""), *width))
+            .collect::<Vec<_>>()
+            .join(" │ ")
+    }
+
+    /// Border under the header, matching `format_row`
+    pub fn separator(&self, first_col: usize) -> String {
+        self.widths.iter()
+            .skip(first_col)
+            .map(|w| "─".repeat(*w))
+            .collect::<Vec<_>>()
+            .join("─┼─")
+    }
+
+    /// Whole table as aligned text (for copying)
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![self.summary(), self.format_row(&self.headers, 0), self.separator(0)];
+        lines.extend(self.rows.iter().map(|row| self.format_row(row, 0)));
+        lines.join("\n")
+    }
+}
+
+/// Cut or pad `text` to exactly `width` characters
+fn pad_cell(text: &str, width: usize) -> String {
+    let text = text.replace(['\n', '\r'], " ");
+    let len = text.chars().count();
+    if len > width {
+        let cut: String = text.chars().take(width.saturating_sub(1)).collect();
+        format!("{}…", cut)
+    } else {
+        format!("{}{}", text, " ".repeat(width - len))
+    }
+}
+
+/// Whether `path` is previewed as a table
+pub fn is_delimited_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| matches!(e.to_lowercase().as_str(), "csv" | "tsv" | "tab"))
+}
+
+/// Pick the delimiter that splits the first lines into the most, consistently sized, fields
+pub fn detect_delimiter(content: &str) -> u8 {
+    let sample: String = content.lines()
+        .take(DETECT_SAMPLE_LINES)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    DELIMITERS.iter()
+        .copied()
+        .filter_map(|delimiter| {
+            let mut reader = ::csv::ReaderBuilder::new()
+                .delimiter(delimiter)
+                .has_headers(false)
+                .flexible(true)
+                .from_reader(sample.as_bytes());
+            let counts: Vec<usize> = reader.records().map_while(Result::ok).map(|r| r.len()).collect();
+            let first = *counts.first()?;
+            // Consistent field counts score higher than ragged splits
+            let consistent = counts.iter().all(|&c| c == first);
+            (first > 1).then_some((delimiter, consistent, first))
+        })
+        .max_by_key(|(_, consistent, fields)| (*consistent, *fields))
+        .map(|(delimiter, _, _)| delimiter)
+        .unwrap_or(b',')
+}
+
+/// Parse delimited `content`, keeping at most `max_rows` data rows
+pub fn parse_table(content: &str, delimiter: u8, max_rows: usize) -> Result<TableView, String> {
+    let mut reader = ::csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+
+    let mut records = reader.records();
+    let headers: Vec<String> = match records.next() {
+        Some(record) => record.map_err(|e| e.to_string())?.iter().map(str::to_string).collect(),
+        None => Vec::new(),
+    };
+
+    let mut rows = Vec::new();
+    let mut total_rows = 0;
+    for record in records {
+        let record = record.map_err(|e| e.to_string())?;
+        total_rows += 1;
+        if rows.len() < max_rows {
+            rows.push(record.iter().map(str::to_string).collect::<Vec<_>>());
+        }
+    }
+
+    // Ragged rows: pad every row (and the header) to the widest row
+    let columns = rows.iter().map(Vec::len).chain([headers.len()]).max().unwrap_or(0);
+    let pad = |mut cells: Vec<String>| {
+        cells.resize(columns, String::new());
+        cells
+    };
+    let headers = pad(headers);
+    let rows: Vec<Vec<String>> = rows.into_iter().map(pad).collect();
+
+    let widths = (0..columns)
+        .map(|i| {
+            std::iter::once(&headers)
+                .chain(rows.iter())
+                .map(|row| row[i].chars().count())
+                .max()
+                .unwrap_or(0)
+                .clamp(1, MAX_COLUMN_WIDTH)
+        })
+        .collect();
+
+    Ok(TableView { headers, rows, total_rows, widths, delimiter, raw: content.to_string() })
+}
+
+/// Load a CSV/TSV file as a table of at most `max_rows` data rows
+pub fn load_table(path: &Path, max_rows: usize) -> ViewerContent {
+    let content = match std::fs::read(path) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(e) => return ViewerContent::Error(format!("Failed to read file: {}", e)),
+    };
+    match parse_table(&content, detect_delimiter(&content), max_rows) {
+        Ok(table) => ViewerContent::Table(table),
+        // Not parseable as a table: show the text instead
+        Err(_) => ViewerContent::PlainText(content),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_delimiter() {
+        assert_eq!(detect_delimiter("a,b,c\n1,2,3\n"), b',');
+        assert_eq!(detect_delimiter("a\tb\n1\t2\n"), b'\t');
+        assert_eq!(detect_delimiter("name;price\n\"x, y\";1,5\n"), b';');
+    }
+
+    #[test]
+    fn test_parse_table_quotes_ragged_and_cap() {
+        let content = "name,note,n\n\"Smith, J\",\"said \"\"hi\"\"\",1\nshort\na,b,c,extra\nlast,,\n";
+        let table = parse_table(content, b',', 3).unwrap();
+        assert_eq!(table.headers, vec!["name", "note", "n", ""]);
+        assert_eq!(table.rows[0][..3], ["Smith, J", "said \"hi\"", "1"]);
+        // Ragged row padded with empty cells
+        assert_eq!(table.rows[1], vec!["short", "", "", ""]);
+        assert_eq!(table.total_rows, 4);
+        assert!(table.is_truncated());
+        assert!(table.summary().starts_with("showing 3 of 4 rows"));
+
+        assert_eq!(table.format_row(&table.headers, 1), "note      │ n │      ");
+        assert_eq!(table.separator(2), "──┼──────");
+    }
+}
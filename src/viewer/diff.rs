@@ -0,0 +1,144 @@
+//! Unified line diff of two files for the viewer
+
+use std::path::{Path, PathBuf};
+
+use similar::{ChangeTag, TextDiff};
+
+/// Unchanged lines shown around each change
+const CONTEXT_LINES: usize = 3;
+
+/// Bytes checked for NUL when deciding whether a file is binary
+const BINARY_CHECK_BYTES: usize = 8192;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLineKind {
+    Context,
+    Added,
+    Removed,
+    /// "@@ -a,b +c,d @@" header starting a hunk
+    Hunk,
+}
+
+/// One displayed diff line; `text` includes the ' ', '+' or '-' prefix
+#[derive(Debug, Clone)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    /// Line number in the new file (old file for removed lines)
+    pub line_number: Option<usize>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DiffView {
+    pub old_path: PathBuf,
+    pub new_path: PathBuf,
+    pub lines: Vec<DiffLine>,
+    /// All lines joined with newlines (for copying, searching and wrapping)
+    pub text: String,
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// Hunks of changed lines between `old` and `new`, with context
+pub fn diff_texts(old: &str, new: &str) -> Vec<DiffLine> {
+    let diff = TextDiff::from_lines(old, new);
+    let mut lines = Vec::new();
+
+    for group in diff.grouped_ops(CONTEXT_LINES) {
+        let (Some(first), Some(last)) = (group.first(), group.last()) else { continue };
+        let old_range = first.old_range().start..last.old_range().end;
+        let new_range = first.new_range().start..last.new_range().end;
+        lines.push(DiffLine {
+            kind: DiffLineKind::Hunk,
+            line_number: None,
+            text: format!(
+                "@@ -{},{} +{},{} @@",
+                old_range.start + 1, old_range.len(), new_range.start + 1, new_range.len()
+            ),
+        });
+
+        for op in &group {
+            for change in diff.iter_changes(op) {
+                let (kind, prefix, index) = match change.tag() {
+                    ChangeTag::Equal => (DiffLineKind::Context, ' ', change.new_index()),
+                    ChangeTag::Insert => (DiffLineKind::Added, '+', change.new_index()),
+                    ChangeTag::Delete => (DiffLineKind::Removed, '-', change.old_index()),
+                };
+                let value = change.value().trim_end_matches(['\n', '\r']);
+                lines.push(DiffLine {
+                    kind,
+                    line_number: index.map(|i| i + 1),
+                    text: format!("{}{}", prefix, value),
+                });
+            }
+        }
+    }
+    lines
+}
+
+fn read_text(path: &Path) -> Result<String, String> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if bytes.iter().take(BINARY_CHECK_BYTES).any(|&b| b == 0) {
+        return Err(format!("{} is a binary file", path.display()));
+    }
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Diff two text files
+pub fn diff_files(old_path: &Path, new_path: &Path) -> Result<DiffView, String> {
+    let old = read_text(old_path)?;
+    let new = read_text(new_path)?;
+
+    let mut lines = diff_texts(&old, &new);
+    if lines.is_empty() {
+        lines.push(DiffLine { kind: DiffLineKind::Hunk, line_number: None, text: "Files are identical".to_string() });
+    }
+    let added = lines.iter().filter(|l| l.kind == DiffLineKind::Added).count();
+    let removed = lines.iter().filter(|l| l.kind == DiffLineKind::Removed).count();
+    let text = lines.iter().map(|l| l.text.as_str()).collect::<Vec<_>>().join("\n");
+
+    Ok(DiffView {
+        old_path: old_path.to_path_buf(),
+        new_path: new_path.to_path_buf(),
+        lines,
+        text,
+        added,
+        removed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_texts_hunks() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\n";
+        let new = "a\nb\nc\nd\nE\nf\ng\nh\ni\nj\n";
+        let lines = diff_texts(old, new);
+        let rendered: Vec<&str> = lines.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(rendered, vec![
+            "@@ -2,8 +2,9 @@", " b", " c", " d", "-e", "+E", " f", " g", " h", " i", "+j",
+        ]);
+        assert_eq!(lines[4].kind, DiffLineKind::Removed);
+        assert_eq!(lines[4].line_number, Some(5));
+        assert_eq!(lines[10].line_number, Some(10));
+    }
+
+    #[test]
+    fn test_diff_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b, bin) = (dir.path().join("a.txt"), dir.path().join("b.txt"), dir.path().join("c.bin"));
+        std::fs::write(&a, "one\ntwo\n").unwrap();
+        std::fs::write(&b, "one\nthree\n").unwrap();
+        std::fs::write(&bin, [0u8, 1, 2]).unwrap();
+
+        let view = diff_files(&a, &b).unwrap();
+        assert_eq!((view.added, view.removed), (1, 1));
+        assert!(view.text.contains("-two\n+three"));
+
+        assert_eq!(diff_files(&a, &a).unwrap().lines[0].text, "Files are identical");
+        assert!(diff_files(&a, &bin).is_err());
+    }
+}
@@ -1,4 +1,7 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crossterm::event::KeyCode;
 
 /// Editor style (Vim or Nano)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -14,6 +17,7 @@ pub enum VimMode {
     Command,
     Visual,
     VisualLine,
+    VisualBlock,
 }
 
 /// Pending operator for operator-pending mode (e.g., d, y, c followed by motion)
@@ -25,6 +29,175 @@ pub enum PendingOperator {
     Change,   // c
     Indent,   // >
     Outdent,  // <
+    GPrefix,  // g (gg, gc)
+    Record,   // q (register to record into)
+    Play,     // @ (register to play)
+    Register, // " (register for the next yank/delete/paste)
+}
+
+/// Macros calling other macros (or themselves) stop at this nesting depth
+pub const MAX_MACRO_DEPTH: usize = 20;
+
+/// Maximum number of keys one top-level macro playback may feed back
+pub const MAX_MACRO_KEYS: usize = 10_000;
+
+/// How an insert session was started; replaying it with '.' starts the same way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertEntry {
+    Insert,          // i
+    Append,          // a
+    LineStart,       // I
+    LineEnd,         // A
+    OpenBelow,       // o
+    OpenAbove,       // O
+    SubstituteChar,  // s
+    SubstituteLine,  // S, cc
+    ChangeToEnd,     // C, c$
+}
+
+/// Last change made in Normal mode, repeated by '.'
+/// Repeatable: x, X, dd, dw, D/d$, r, >>, <<, J, ~ and the text typed in an
+/// i/a/I/A/o/O/s/S/C/cc session. A count given to '.' replaces the original count.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepeatableChange {
+    DeleteChar(usize),
+    DeleteCharBefore(usize),
+    DeleteLines(usize),
+    DeleteWord(usize),
+    DeleteToEnd,
+    ReplaceChar(char),
+    Indent(usize),
+    Outdent(usize),
+    JoinLines(usize),
+    ToggleCase(usize),
+    Insert { entry: InsertEntry, text: String },
+}
+
+impl RepeatableChange {
+    fn with_count(self, count: usize) -> Self {
+        match self {
+            Self::DeleteChar(_) => Self::DeleteChar(count),
+            Self::DeleteCharBefore(_) => Self::DeleteCharBefore(count),
+            Self::DeleteLines(_) => Self::DeleteLines(count),
+            Self::DeleteWord(_) => Self::DeleteWord(count),
+            Self::Indent(_) => Self::Indent(count),
+            Self::Outdent(_) => Self::Outdent(count),
+            Self::JoinLines(_) => Self::JoinLines(count),
+            Self::ToggleCase(_) => Self::ToggleCase(count),
+            other => other,
+        }
+    }
+}
+
+/// Line ending written when the file is saved (`:set ff=unix|dos`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,   // unix
+    CrLf, // dos
+}
+
+impl LineEnding {
+    /// Ending of the first line break in `content` (LF if there is none)
+    pub fn detect(content: &str) -> Self {
+        match content.find('\n') {
+            Some(i) if content[..i].ends_with('\r') => LineEnding::CrLf,
+            _ => LineEnding::Lf,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+
+    /// Vim 'fileformat' name
+    pub fn name(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "unix",
+            LineEnding::CrLf => "dos",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "unix" => Some(LineEnding::Lf),
+            "dos" => Some(LineEnding::CrLf),
+            _ => None,
+        }
+    }
+}
+
+/// A parsed `:s/pat/rep/flags` (current line) or `:%s/pat/rep/flags` (whole buffer)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Substitute {
+    pub pattern: String,
+    pub replacement: String,
+    pub whole_buffer: bool,
+    pub all: bool, // 'g' flag: every match on a line, not just the first
+}
+
+/// Parse a substitute command; `\/` stands for a literal '/'
+/// Other backslash sequences are kept so regex escapes still work.
+pub fn parse_substitute(command: &str) -> Option<Substitute> {
+    let (whole_buffer, rest) = match command.strip_prefix('%') {
+        Some(rest) => (true, rest),
+        None => (false, command),
+    };
+    let rest = rest.strip_prefix("s/")?;
+
+    let mut parts = vec![String::new()];
+    let mut chars = rest.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('/') => parts.last_mut()?.push('/'),
+                Some(next) => {
+                    let part = parts.last_mut()?;
+                    part.push('\\');
+                    part.push(next);
+                },
+                None => parts.last_mut()?.push('\\'),
+            },
+            '/' if parts.len() < 3 => parts.push(String::new()),
+            c => parts.last_mut()?.push(c),
+        }
+    }
+    if parts.len() < 2 {
+        return None;
+    }
+    let flags = parts.get(2).cloned().unwrap_or_default();
+    if !flags.chars().all(|f| f == 'g') {
+        return None;
+    }
+    Some(Substitute {
+        pattern: parts[0].clone(),
+        replacement: parts[1].clone(),
+        whole_buffer,
+        all: flags.contains('g'),
+    })
+}
+
+/// Insert started with I/A (or c) on a visual block, copied to the other rows on Esc
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockInsert {
+    top: usize,
+    bottom: usize,
+    col: usize,
+    len_before: usize, // Characters on the top row before typing
+    line_count: usize, // Typing a newline cancels the block insert
+}
+
+/// Question asked on nano's bottom line; the typed answer is kept in `command_buffer`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NanoPrompt {
+    Search,                 // ^W: text to find
+    GoToLine,               // ^_ / M-G: line[,column]
+    ReplaceSearch,          // ^\: text to replace
+    ReplaceWith { pattern: String },
+    // y/n/a asked at each match; `origin` is where the replace started
+    ReplaceConfirm { pattern: String, replacement: String, count: usize, origin: (usize, usize), wrapped: bool },
 }
 
 #[derive(Debug, Clone)]
@@ -40,6 +213,16 @@ pub struct TextEditor {
     pub clipboard_is_line: bool, // True if clipboard contains whole lines
     pub status_message: String,
     pub modified: bool,
+    // File format, detected on load and kept on save
+    pub line_ending: LineEnding,
+    pub trailing_newline: bool, // Whether the last line ends with a line break
+    pub auto_indent: bool, // New lines copy the indentation (one level deeper after an opening bracket)
+    pub tab_width: usize,  // Columns per tab stop and spaces per indent level
+    pub expand_tab: bool,  // Indent with spaces rather than '\t'
+    pub relative_numbers: bool, // Gutter shows distance from the cursor line (which keeps its own number)
+    pub auto_pair: bool,   // Typing an opening bracket or quote also inserts its closing one
+    pub encoding: Option<super::TextEncoding>, // Encoding of a file that isn't plain UTF-8, kept on save
+    visible_height: usize, // Text rows on screen, set by the UI; page moves go by this
     // Operator-pending mode
     pub pending_op: PendingOperator,
     // Undo/Redo
@@ -53,10 +236,24 @@ pub struct TextEditor {
     // Visual mode
     pub visual_start_row: usize,
     pub visual_start_col: usize,
+    pub block_insert: Option<BlockInsert>,
     // Count prefix (e.g., 5j to move down 5 lines)
     pub count_buffer: String,
+    // Dot repeat
+    pub last_change: Option<RepeatableChange>,
+    insert_record: Option<(InsertEntry, String)>, // Text typed in the current insert session
+    // Named registers a-z ("ay, "ap): (lines, is_line) like the unnamed clipboard
+    pub registers: HashMap<char, (Vec<String>, bool)>,
+    pub pending_register: Option<(char, (Vec<String>, bool))>, // Selected register and the saved unnamed clipboard
+    // Macros (q/@)
+    pub macros: HashMap<char, Vec<KeyCode>>, // Registers a-z
+    pub recording_macro: Option<char>,
+    pub macro_buffer: Vec<KeyCode>,
+    pub last_macro: Option<char>,
+    pub macro_depth: usize,       // Nesting of macros being played
+    pub macro_keys_played: usize, // Keys fed back by the current top-level playback
     // Nano specific
-    pub nano_search_mode: bool, // True when Ctrl+W search is active
+    pub nano_prompt: Option<NanoPrompt>,
 }
 
 // Helper functions for UTF-8 safe string operations
@@ -85,16 +282,69 @@ fn remove_char_at(s: &mut String, char_idx: usize) -> Option<char> {
     }
 }
 
+fn insert_str_at(s: &mut String, char_idx: usize, text: &str) {
+    let byte_idx = char_to_byte_index(s, char_idx);
+    s.insert_str(byte_idx, text);
+}
+
+/// Pad `s` with spaces to at least `width` characters
+fn pad_to(s: &mut String, width: usize) {
+    let len = char_count(s);
+    if len < width {
+        s.extend(std::iter::repeat_n(' ', width - len));
+    }
+}
+
 fn split_off_at_char(s: &mut String, char_idx: usize) -> String {
     let byte_idx = char_to_byte_index(s, char_idx);
     s.split_off(byte_idx)
 }
 
+/// Default columns per tab stop (and spaces per indent level)
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+
+/// Leading spaces and tabs of `line`
+fn leading_whitespace(line: &str) -> &str {
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
 /// Check if a character is a word character (alphanumeric or underscore)
 fn is_word_char(c: char) -> bool {
     c.is_alphanumeric() || c == '_'
 }
 
+/// Closing character auto-paired with `c`
+fn pair_closer(c: char) -> Option<char> {
+    match c {
+        '(' => Some(')'),
+        '[' => Some(']'),
+        '{' => Some('}'),
+        '"' | '\'' => Some(c),
+        _ => None,
+    }
+}
+
+/// Line comment prefix for a file, by extension (or file name for extensionless files)
+pub fn comment_prefix_for(path: &Path) -> Option<&'static str> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if matches!(name.as_str(), "makefile" | "dockerfile" | "justfile" | "cmakelists.txt") {
+        return Some("#");
+    }
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    let prefix = match ext.as_str() {
+        "rs" | "c" | "h" | "cpp" | "hpp" | "cc" | "cxx" | "js" | "jsx" | "ts" | "tsx" | "mjs"
+        | "java" | "kt" | "kts" | "go" | "swift" | "scala" | "cs" | "dart" | "zig" | "php" => "//",
+        "py" | "sh" | "bash" | "zsh" | "fish" | "rb" | "pl" | "toml" | "yaml" | "yml" | "r"
+        | "conf" | "cmake" | "mk" | "nix" | "ps1" | "tf" | "ex" | "exs" | "jl" => "#",
+        "lua" | "sql" | "hs" | "elm" | "ada" => "--",
+        "lisp" | "el" | "clj" | "scm" | "asm" | "ini" => ";",
+        "tex" | "erl" | "m" => "%",
+        "vim" => "\"",
+        _ => return None,
+    };
+    Some(prefix)
+}
+
 /// Public helper for char count
 pub fn char_count_pub(s: &str) -> usize {
     s.chars().count()
@@ -102,16 +352,9 @@ pub fn char_count_pub(s: &str) -> usize {
 
 impl TextEditor {
     pub fn new(content: String, file_path: Option<PathBuf>) -> Self {
-        let lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
-        let lines = if lines.is_empty() {
-            vec![String::new()]
-        } else {
-            lines
-        };
-
-        TextEditor {
+        let mut editor = TextEditor {
             file_path,
-            lines,
+            lines: Vec::new(),
             cursor_row: 0,
             cursor_col: 0,
             mode: VimMode::Normal,
@@ -121,6 +364,15 @@ impl TextEditor {
             clipboard_is_line: false,
             status_message: "-- NORMAL --".to_string(),
             modified: false,
+            line_ending: LineEnding::Lf,
+            trailing_newline: false,
+            auto_indent: true,
+            tab_width: DEFAULT_TAB_WIDTH,
+            expand_tab: true,
+            relative_numbers: false,
+            auto_pair: false,
+            encoding: None,
+            visible_height: 30,
             pending_op: PendingOperator::None,
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
@@ -130,9 +382,77 @@ impl TextEditor {
             last_search_col: 0,
             visual_start_row: 0,
             visual_start_col: 0,
+            block_insert: None,
             count_buffer: String::new(),
-            nano_search_mode: false,
+            nano_prompt: None,
+            last_change: None,
+            insert_record: None,
+            registers: HashMap::new(),
+            pending_register: None,
+            macros: HashMap::new(),
+            recording_macro: None,
+            macro_buffer: Vec::new(),
+            last_macro: None,
+            macro_depth: 0,
+            macro_keys_played: 0,
+        };
+        editor.load_content(&content);
+        editor
+    }
+
+    /// Replace the buffer with `content`, detecting its line ending and final newline
+    pub fn load_content(&mut self, content: &str) {
+        self.lines = content.lines().map(|s| s.to_string()).collect();
+        if self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+        self.line_ending = LineEnding::detect(content);
+        self.trailing_newline = content.ends_with('\n');
+        self.cursor_row = 0;
+        self.cursor_col = 0;
+        self.modified = false;
+    }
+
+    /// Apply a `:set` option; returns the status message
+    /// Supports `ff=unix|dos` (`fileformat`), `ff?`, `eol`/`noeol`, `ai`/`noai` (`autoindent`) and `rnu`/`nornu`.
+    pub fn set_option(&mut self, option: &str) -> Result<String, String> {
+        match option {
+            "ai" | "autoindent" | "noai" | "noautoindent" => {
+                self.auto_indent = !option.starts_with("no");
+                return Ok(format!("Auto-indent {}", if self.auto_indent { "on" } else { "off" }));
+            },
+            "et" | "expandtab" | "noet" | "noexpandtab" => {
+                self.expand_tab = !option.starts_with("no");
+                return Ok(format!("Indenting with {}", if self.expand_tab { "spaces" } else { "tabs" }));
+            },
+            "rnu" | "relativenumber" | "nornu" | "norelativenumber" => {
+                self.relative_numbers = !option.starts_with("no");
+                return Ok(format!("Relative line numbers {}", if self.relative_numbers { "on" } else { "off" }));
+            },
+            "ff?" | "fileformat?" => return Ok(format!("fileformat={}", self.line_ending.name())),
+            "eol" | "noeol" => {
+                self.trailing_newline = option == "eol";
+                self.modified = true;
+                return Ok(format!("{} newline at end of file", if self.trailing_newline { "Adding" } else { "No" }));
+            },
+            _ => {}
+        }
+        if let Some(value) = option.strip_prefix("ts=").or_else(|| option.strip_prefix("tabstop=")) {
+            let width = value.parse::<usize>().ok().filter(|w| (1..=16).contains(w))
+                .ok_or_else(|| format!("Invalid tabstop: {} (use 1-16)", value))?;
+            self.tab_width = width;
+            return Ok(format!("tabstop={}", width));
         }
+        let value = option.strip_prefix("ff=")
+            .or_else(|| option.strip_prefix("fileformat="))
+            .ok_or_else(|| format!("Unknown option: {}", option))?;
+        let ending = LineEnding::from_name(value)
+            .ok_or_else(|| format!("Invalid fileformat: {} (use unix or dos)", value))?;
+        if ending != self.line_ending {
+            self.line_ending = ending;
+            self.modified = true;
+        }
+        Ok(format!("fileformat={}", ending.name()))
     }
     
     /// Toggle between Vim and Nano editor styles
@@ -151,7 +471,7 @@ impl TextEditor {
         }
         self.pending_op = PendingOperator::None;
         self.count_buffer.clear();
-        self.nano_search_mode = false;
+        self.nano_prompt = None;
     }
     
     /// Get the count from count_buffer, default to 1
@@ -163,7 +483,11 @@ impl TextEditor {
     
     /// Save current state for undo
     pub fn save_undo(&mut self) {
-        self.undo_stack.push((self.lines.clone(), self.cursor_row, self.cursor_col));
+        self.push_undo((self.lines.clone(), self.cursor_row, self.cursor_col));
+    }
+
+    fn push_undo(&mut self, state: (Vec<String>, usize, usize)) {
+        self.undo_stack.push(state);
         self.redo_stack.clear(); // Clear redo on new change
         // Limit undo stack size
         if self.undo_stack.len() > 100 {
@@ -199,8 +523,22 @@ impl TextEditor {
         }
     }
 
+    /// Buffer text as saved: the file's line ending, plus a final newline if it had one
     pub fn get_content(&self) -> String {
-        self.lines.join("\n")
+        let ending = self.line_ending.as_str();
+        let mut content = self.lines.join(ending);
+        if self.trailing_newline {
+            content.push_str(ending);
+        }
+        content
+    }
+
+    /// The content as bytes in the file's encoding; Err if it has characters the encoding lacks
+    pub fn encoded_content(&self) -> Result<Vec<u8>, String> {
+        match self.encoding {
+            Some(encoding) => crate::viewer::encoding::encode(&self.get_content(), encoding),
+            None => Ok(self.get_content().into_bytes()),
+        }
     }
 
     pub fn get_current_line(&self) -> &str {
@@ -268,12 +606,10 @@ impl TextEditor {
         self.cursor_col = 0;
     }
     
-    /// Move to line number (1-indexed)
+    /// Move to line number (1-indexed, clamped to the buffer; 0 is the first line)
     pub fn move_to_line(&mut self, line_num: usize) {
-        if line_num > 0 {
-            self.cursor_row = (line_num - 1).min(self.lines.len().saturating_sub(1));
-            self.cursor_col = 0;
-        }
+        self.cursor_row = line_num.saturating_sub(1).min(self.lines.len().saturating_sub(1));
+        self.cursor_col = 0;
     }
     
     /// Move to first non-blank character (^)
@@ -435,31 +771,43 @@ impl TextEditor {
         self.cursor_col = col.min(len.saturating_sub(1));
     }
     
+    /// Number shown in the gutter for line `row` (0-based)
+    pub fn gutter_number(&self, row: usize) -> usize {
+        if self.relative_numbers && row != self.cursor_row {
+            row.abs_diff(self.cursor_row)
+        } else {
+            row + 1
+        }
+    }
+
+    /// Set the number of text rows on screen, which page moves go by
+    pub fn set_visible_height(&mut self, height: usize) {
+        self.visible_height = height.max(1);
+    }
+
     /// Move half page down (Ctrl+d)
     pub fn move_half_page_down(&mut self) {
-        let half_page = 15;
+        let half_page = (self.visible_height / 2).max(1);
         self.cursor_row = (self.cursor_row + half_page).min(self.lines.len().saturating_sub(1));
         self.clamp_cursor_col();
     }
     
     /// Move half page up (Ctrl+u)
     pub fn move_half_page_up(&mut self) {
-        let half_page = 15;
+        let half_page = (self.visible_height / 2).max(1);
         self.cursor_row = self.cursor_row.saturating_sub(half_page);
         self.clamp_cursor_col();
     }
     
     /// Move full page down (Ctrl+f)
     pub fn move_page_down(&mut self) {
-        let page = 30;
-        self.cursor_row = (self.cursor_row + page).min(self.lines.len().saturating_sub(1));
+        self.cursor_row = (self.cursor_row + self.visible_height).min(self.lines.len().saturating_sub(1));
         self.clamp_cursor_col();
     }
     
     /// Move full page up (Ctrl+b)
     pub fn move_page_up(&mut self) {
-        let page = 30;
-        self.cursor_row = self.cursor_row.saturating_sub(page);
+        self.cursor_row = self.cursor_row.saturating_sub(self.visible_height);
         self.clamp_cursor_col();
     }
     
@@ -821,23 +1169,41 @@ impl TextEditor {
         }
     }
     
+    /// One indent level: `tab_width` spaces, or a tab when expandtab is off
+    pub fn indent_unit(&self) -> String {
+        if self.expand_tab {
+            " ".repeat(self.tab_width)
+        } else {
+            "\t".to_string()
+        }
+    }
+
+    /// Remove one indent level (a tab or up to `tab_width` spaces) from the start of `row`
+    /// Returns the number of characters removed.
+    pub fn remove_indent_level(&mut self, row: usize) -> usize {
+        let line = &mut self.lines[row];
+        if line.starts_with('\t') {
+            line.remove(0);
+            return 1;
+        }
+        let removed = line.chars().take(self.tab_width).take_while(|&c| c == ' ').count();
+        line.drain(..removed);
+        removed
+    }
+
     /// Indent current line (>>)
     pub fn indent_line(&mut self) {
         self.save_undo();
-        self.lines[self.cursor_row].insert_str(0, "    ");
-        self.cursor_col += 4;
+        let unit = self.indent_unit();
+        self.lines[self.cursor_row].insert_str(0, &unit);
+        self.cursor_col += unit.len();
         self.modified = true;
     }
     
     /// Outdent current line (<<)
     pub fn outdent_line(&mut self) {
         self.save_undo();
-        let line = &mut self.lines[self.cursor_row];
-        let mut removed = 0;
-        while removed < 4 && line.starts_with(' ') {
-            line.remove(0);
-            removed += 1;
-        }
+        let removed = self.remove_indent_level(self.cursor_row);
         if removed > 0 {
             self.cursor_col = self.cursor_col.saturating_sub(removed);
             self.modified = true;
@@ -1003,6 +1369,144 @@ impl TextEditor {
         self.status_message = format!("Pattern not found: {}", self.search_pattern);
     }
     
+    /// Position of the match under the cursor among all matches of the search pattern, as (N, M)
+    pub fn match_position(&self) -> Option<(usize, usize)> {
+        if self.search_pattern.is_empty() {
+            return None;
+        }
+        let mut total = 0;
+        let mut current = None;
+        for (row, line) in self.lines.iter().enumerate() {
+            for (byte_pos, _) in line.match_indices(&self.search_pattern) {
+                total += 1;
+                if row == self.cursor_row && line[..byte_pos].chars().count() == self.cursor_col {
+                    current = Some(total);
+                }
+            }
+        }
+        current.map(|n| (n, total))
+    }
+
+    /// Nano "Where Is": next match with wraparound, reported as "Match N of M"
+    pub fn nano_search_next(&mut self) {
+        self.search_next();
+        if self.status_message.starts_with("Pattern not found") {
+            self.status_message = format!("\"{}\" not found", self.search_pattern);
+        } else if let Some((n, total)) = self.match_position() {
+            let wrapped = if self.status_message.ends_with("(wrapped)") { " [Search Wrapped]" } else { "" };
+            self.status_message = format!("Match {} of {}{}", n, total, wrapped);
+        }
+    }
+
+    /// Nano "Go To Line": `line` or `line,column` (1-based)
+    pub fn nano_go_to_line(&mut self, input: &str) {
+        let mut parts = input.trim().splitn(2, ',');
+        let line = parts.next().and_then(|l| l.trim().parse::<usize>().ok());
+        let col = parts.next().map(|c| c.trim().parse::<usize>().ok());
+        match (line, col) {
+            (Some(line), None | Some(Some(_))) => {
+                self.move_to_line(line);
+                let col = col.flatten().unwrap_or(1).saturating_sub(1);
+                self.cursor_col = col.min(char_count(self.get_current_line()));
+                self.status_message = format!("Line {} of {}", self.cursor_row + 1, self.lines.len());
+            },
+            _ => self.status_message = "Invalid line or column number".to_string(),
+        }
+    }
+
+    /// First match of `pattern` at or after (row, col), without wrapping
+    fn find_from(&self, pattern: &str, row: usize, col: usize) -> Option<(usize, usize)> {
+        for r in row..self.lines.len() {
+            let line = &self.lines[r];
+            let start = if r == row { char_to_byte_index(line, col.min(char_count(line))) } else { 0 };
+            if let Some(byte_pos) = line[start..].find(pattern) {
+                return Some((r, line[..start + byte_pos].chars().count()));
+            }
+        }
+        None
+    }
+
+    /// Start nano's interactive replace at the cursor
+    pub fn nano_start_replace(&mut self, pattern: String, replacement: String) {
+        if pattern.is_empty() {
+            self.nano_prompt = None;
+            self.status_message = "No search pattern".to_string();
+            return;
+        }
+        self.search_pattern = pattern.clone();
+        let origin = (self.cursor_row, self.cursor_col);
+        self.nano_prompt = Some(NanoPrompt::ReplaceConfirm { pattern, replacement, count: 0, origin, wrapped: false });
+        self.nano_replace_advance(origin.0, origin.1);
+    }
+
+    /// Move to the next match from (row, col), wrapping once and stopping back at the origin
+    fn nano_replace_advance(&mut self, row: usize, col: usize) {
+        let Some(NanoPrompt::ReplaceConfirm { pattern, origin, wrapped, .. }) = self.nano_prompt.clone() else { return };
+        let mut found = self.find_from(&pattern, row, col);
+        let mut now_wrapped = wrapped;
+        if found.is_none() && !wrapped {
+            now_wrapped = true;
+            found = self.find_from(&pattern, 0, 0);
+        }
+        match found {
+            Some(pos) if !(now_wrapped && pos >= origin) => {
+                if let Some(NanoPrompt::ReplaceConfirm { wrapped, .. }) = self.nano_prompt.as_mut() {
+                    *wrapped = now_wrapped;
+                }
+                (self.cursor_row, self.cursor_col) = pos;
+                self.status_message = "Replace this instance? (y)es (n)o (a)ll, ^C to cancel".to_string();
+            },
+            _ => self.nano_finish_replace(),
+        }
+    }
+
+    /// Answer the replace prompt: 'y' replaces this match, 'n' skips it, 'a' replaces the rest
+    pub fn nano_answer_replace(&mut self, answer: char) {
+        let Some(NanoPrompt::ReplaceConfirm { pattern, replacement, .. }) = self.nano_prompt.clone() else { return };
+        match answer.to_ascii_lowercase() {
+            'y' => {
+                self.nano_replace_current(&pattern, &replacement);
+                let col = self.cursor_col + char_count(&replacement);
+                self.nano_replace_advance(self.cursor_row, col);
+            },
+            'n' => self.nano_replace_advance(self.cursor_row, self.cursor_col + 1),
+            'a' => {
+                while matches!(self.nano_prompt, Some(NanoPrompt::ReplaceConfirm { .. })) {
+                    self.nano_answer_replace('y');
+                }
+            },
+            _ => {}
+        }
+    }
+
+    /// Replace the match under the cursor; the whole replace is a single undo step
+    fn nano_replace_current(&mut self, pattern: &str, replacement: &str) {
+        let Some(NanoPrompt::ReplaceConfirm { count, origin, wrapped, .. }) = self.nano_prompt.clone() else { return };
+        if count == 0 {
+            self.save_undo();
+        }
+        let line = &mut self.lines[self.cursor_row];
+        let start = char_to_byte_index(line, self.cursor_col);
+        line.replace_range(start..start + pattern.len(), replacement);
+        // Keep the stop position in place when replacing before it on the origin row
+        let mut origin = origin;
+        if wrapped && self.cursor_row == origin.0 && self.cursor_col < origin.1 {
+            origin.1 = (origin.1 + char_count(replacement)).saturating_sub(char_count(pattern));
+        }
+        if let Some(NanoPrompt::ReplaceConfirm { count: c, origin: o, .. }) = self.nano_prompt.as_mut() {
+            *c = count + 1;
+            *o = origin;
+        }
+        self.modified = true;
+    }
+
+    /// End the replace prompt and report how many matches were replaced
+    pub fn nano_finish_replace(&mut self) {
+        if let Some(NanoPrompt::ReplaceConfirm { count, .. }) = self.nano_prompt.take() {
+            self.status_message = format!("Replaced {} occurrence{}", count, if count == 1 { "" } else { "s" });
+        }
+    }
+
     /// Search word under cursor (*)
     pub fn search_word_under_cursor(&mut self) {
         let line = self.get_current_line();
@@ -1032,31 +1536,118 @@ impl TextEditor {
     }
 
     // Insert mode commands
+    /// Insert `c` at the cursor
+    /// With auto-pairing an opening bracket or quote brings its closing one,
+    /// and a closing one already next to the cursor is stepped over.
     pub fn insert_char(&mut self, c: char) {
         let cursor_col = self.cursor_col;
+        if self.auto_pair {
+            let line = self.get_current_line();
+            let prev = cursor_col.checked_sub(1).and_then(|i| line.chars().nth(i));
+            let next = line.chars().nth(cursor_col);
+            if next == Some(c) && matches!(c, ')' | ']' | '}' | '"' | '\'') {
+                self.cursor_col += 1;
+                return;
+            }
+            // A quote right after a word is an apostrophe or closes a string
+            let after_word = matches!(c, '"' | '\'') && prev.is_some_and(char::is_alphanumeric);
+            if let Some(closer) = pair_closer(c).filter(|_| !after_word) {
+                let line = self.get_current_line_mut();
+                insert_char_at(line, cursor_col, closer);
+                insert_char_at(line, cursor_col, c);
+                self.cursor_col += 1;
+                self.modified = true;
+                return;
+            }
+        }
         let line = self.get_current_line_mut();
         insert_char_at(line, cursor_col, c);
         self.cursor_col += 1;
         self.modified = true;
     }
 
+    /// Split the line at the cursor
+    /// With auto-indent the new line keeps the current indentation, one level
+    /// deeper after an opening bracket; between a bracket pair the closing
+    /// bracket moves to a line of its own.
     pub fn insert_newline(&mut self) {
         let cursor_col = self.cursor_col;
+        let row = self.cursor_row;
         let line = self.get_current_line_mut();
-        let rest = split_off_at_char(line, cursor_col);
-        self.lines.insert(self.cursor_row + 1, rest);
+        let mut rest = split_off_at_char(line, cursor_col);
+        let mut indent = String::new();
+
+        if self.auto_indent {
+            let before = &self.lines[row];
+            let base = leading_whitespace(before).to_string();
+            let opener = before.trim_end().chars().last().filter(|c| matches!(c, '{' | '(' | '['));
+            rest = rest.trim_start().to_string();
+            indent = base.clone();
+            if let Some(opener) = opener {
+                indent.push_str(&self.indent_unit());
+                let closer = match opener { '{' => '}', '(' => ')', _ => ']' };
+                if rest.starts_with(closer) {
+                    self.lines.insert(row + 1, format!("{}{}", base, rest));
+                    rest = String::new();
+                }
+            }
+        }
+
+        self.cursor_col = char_count(&indent);
+        self.lines.insert(row + 1, indent + &rest);
         self.cursor_row += 1;
-        self.cursor_col = 0;
         self.modified = true;
     }
 
-    pub fn backspace(&mut self) {
+    /// Open a new line below (o) or above (O) the cursor, indented like the current line
+    pub fn open_line(&mut self, below: bool) {
+        if below {
+            self.cursor_col = char_count(self.get_current_line());
+            self.insert_newline();
+            return;
+        }
+        let indent = if self.auto_indent { leading_whitespace(self.get_current_line()).to_string() } else { String::new() };
+        self.cursor_col = char_count(&indent);
+        self.lines.insert(self.cursor_row, indent);
+        self.modified = true;
+    }
+
+    /// Delete before the cursor; in leading spaces this removes back to the previous indent stop
+    /// With auto-pairing, an empty pair around the cursor is deleted whole.
+    /// Returns the text removed before the cursor ("\n" for a line join; an auto-paired closer isn't included).
+    pub fn backspace(&mut self) -> String {
         if self.cursor_col > 0 {
             let cursor_col = self.cursor_col;
-            let line = self.get_current_line_mut();
-            remove_char_at(line, cursor_col - 1);
+            if self.auto_pair {
+                let line = self.get_current_line();
+                let next = line.chars().nth(cursor_col);
+                if next.is_some() && line.chars().nth(cursor_col - 1).and_then(pair_closer) == next {
+                    let line = self.get_current_line_mut();
+                    remove_char_at(line, cursor_col);
+                    let opener = remove_char_at(line, cursor_col - 1);
+                    self.cursor_col -= 1;
+                    self.modified = true;
+                    return opener.into_iter().collect();
+                }
+            }
+            let in_indent = self.get_current_line().chars().take(cursor_col).all(|c| c == ' ');
+            let count = if in_indent { (cursor_col - 1) % self.tab_width + 1 } else { 1 };
+            let mut removed: Vec<char> = (0..count).filter_map(|_| self.remove_char_before()).collect();
+            removed.reverse();
+            removed.into_iter().collect()
+        } else {
+            self.remove_char_before().map(String::from).unwrap_or_default()
+        }
+    }
+
+    /// Delete the one character before the cursor, joining with the previous line at column 0
+    fn remove_char_before(&mut self) -> Option<char> {
+        if self.cursor_col > 0 {
+            let cursor_col = self.cursor_col;
+            let removed = remove_char_at(self.get_current_line_mut(), cursor_col - 1);
             self.cursor_col -= 1;
             self.modified = true;
+            removed
         } else if self.cursor_row > 0 {
             // Join with previous line
             let current_line = self.lines.remove(self.cursor_row);
@@ -1064,6 +1655,9 @@ impl TextEditor {
             self.cursor_col = char_count(&self.lines[self.cursor_row]);
             self.lines[self.cursor_row].push_str(&current_line);
             self.modified = true;
+            Some('\n')
+        } else {
+            None
         }
     }
 
@@ -1075,6 +1669,216 @@ impl TextEditor {
         self.status_message = "-- INSERT --".to_string();
     }
 
+    /// Use register `reg` for the next yank, delete or paste ('"' is the unnamed clipboard)
+    /// The register is swapped in as the clipboard until `finish_register`.
+    pub fn select_register(&mut self, reg: char) -> bool {
+        self.finish_register();
+        if reg == '"' {
+            return true;
+        }
+        if !reg.is_ascii_alphabetic() {
+            self.status_message = format!("Invalid register: {}", reg);
+            return false;
+        }
+        let contents = self.registers.get(&reg.to_ascii_lowercase()).cloned().unwrap_or_default();
+        let unnamed = (
+            std::mem::replace(&mut self.clipboard, contents.0),
+            std::mem::replace(&mut self.clipboard_is_line, contents.1),
+        );
+        self.pending_register = Some((reg, unnamed));
+        self.status_message = format!("\"{}", reg);
+        true
+    }
+
+    /// Store a yank/delete made with the selected register, or restore the unnamed clipboard after a paste
+    /// Text yanked into a register also becomes the unnamed clipboard; an uppercase register appends.
+    pub fn finish_register(&mut self) {
+        let Some((reg, unnamed)) = self.pending_register.take() else { return };
+        let lower = reg.to_ascii_lowercase();
+        let previous = self.registers.get(&lower).cloned().unwrap_or_default();
+        let current = (self.clipboard.clone(), self.clipboard_is_line);
+        if current == previous {
+            (self.clipboard, self.clipboard_is_line) = unnamed;
+            return;
+        }
+        let stored = if reg.is_ascii_uppercase() && !previous.0.is_empty() {
+            let (mut lines, is_line) = previous;
+            if is_line || current.1 {
+                lines.extend(current.0);
+            } else if let (Some(last), Some((first, rest))) = (lines.last_mut(), current.0.split_first()) {
+                last.push_str(first);
+                lines.extend(rest.iter().cloned());
+            }
+            (lines, is_line || current.1)
+        } else {
+            current
+        };
+        self.registers.insert(lower, stored);
+    }
+
+    /// Start recording keys into register `reg` (an uppercase register appends to its lowercase one)
+    pub fn start_macro_recording(&mut self, reg: char) -> bool {
+        if !reg.is_ascii_alphabetic() {
+            self.status_message = format!("Invalid register: {}", reg);
+            return false;
+        }
+        let lower = reg.to_ascii_lowercase();
+        self.macro_buffer = if reg.is_ascii_uppercase() {
+            self.macros.get(&lower).cloned().unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        self.recording_macro = Some(lower);
+        self.status_message = format!("recording @{}", lower);
+        true
+    }
+
+    /// Stop recording and store the keys in the register
+    pub fn stop_macro_recording(&mut self) {
+        if let Some(reg) = self.recording_macro.take() {
+            let keys = std::mem::take(&mut self.macro_buffer);
+            self.status_message = format!("Recorded @{} ({} keys)", reg, keys.len());
+            self.macros.insert(reg, keys);
+        }
+    }
+
+    /// Keys stored in the register to play; '@' means the last played register
+    pub fn macro_keys(&mut self, reg: char) -> Option<Vec<KeyCode>> {
+        let reg = if reg == '@' { self.last_macro? } else { reg.to_ascii_lowercase() };
+        match self.macros.get(&reg) {
+            Some(keys) => {
+                self.last_macro = Some(reg);
+                Some(keys.clone())
+            },
+            None => {
+                self.status_message = format!("Register @{} is empty", reg);
+                None
+            },
+        }
+    }
+
+    /// Apply a repeatable change and remember it for '.'
+    pub fn perform_change(&mut self, change: RepeatableChange) {
+        self.apply_change(&change);
+        self.last_change = Some(change);
+    }
+
+    fn apply_change(&mut self, change: &RepeatableChange) {
+        match *change {
+            RepeatableChange::DeleteChar(n) => (0..n).for_each(|_| self.delete_char()),
+            RepeatableChange::DeleteCharBefore(n) => (0..n).for_each(|_| self.delete_char_before()),
+            RepeatableChange::DeleteLines(n) => self.delete_lines(n),
+            RepeatableChange::DeleteWord(n) => (0..n).for_each(|_| self.delete_word()),
+            RepeatableChange::DeleteToEnd => self.delete_to_end(),
+            RepeatableChange::ReplaceChar(c) => self.replace_char(c),
+            RepeatableChange::Indent(n) => (0..n).for_each(|_| self.indent_line()),
+            RepeatableChange::Outdent(n) => (0..n).for_each(|_| self.outdent_line()),
+            RepeatableChange::JoinLines(n) => (0..n).for_each(|_| self.join_lines()),
+            RepeatableChange::ToggleCase(n) => (0..n).for_each(|_| self.toggle_case()),
+            RepeatableChange::Insert { entry, ref text } => {
+                self.start_insert(entry);
+                for c in text.chars() {
+                    match c {
+                        '\n' => self.insert_newline(),
+                        '\x08' => {
+                            self.remove_char_before();
+                        },
+                        _ => self.insert_char(c),
+                    }
+                }
+            },
+        }
+    }
+
+    /// Repeat the last change at the cursor as a single undo step ('.')
+    pub fn repeat_last_change(&mut self, count: Option<usize>) {
+        let Some(mut change) = self.last_change.clone() else {
+            self.status_message = "No previous change to repeat".to_string();
+            return;
+        };
+        if let Some(count) = count {
+            change = change.with_count(count);
+        }
+        let before = (self.lines.clone(), self.cursor_row, self.cursor_col);
+        let undo_stack = std::mem::take(&mut self.undo_stack);
+        self.apply_change(&change);
+        self.undo_stack = undo_stack;
+        if self.lines != before.0 {
+            self.push_undo(before);
+        }
+        if self.mode == VimMode::Insert {
+            self.enter_normal_mode();
+        }
+        self.last_change = Some(change);
+    }
+
+    /// Enter Insert mode the way `entry` does and start recording the typed text for '.'
+    pub fn begin_insert(&mut self, entry: InsertEntry) {
+        self.start_insert(entry);
+        self.insert_record = Some((entry, String::new()));
+    }
+
+    fn start_insert(&mut self, entry: InsertEntry) {
+        match entry {
+            InsertEntry::Insert => self.save_undo(),
+            InsertEntry::Append => {
+                self.save_undo();
+                self.move_cursor_right();
+            },
+            InsertEntry::LineStart => {
+                self.save_undo();
+                self.move_to_first_nonblank();
+            },
+            InsertEntry::LineEnd => {
+                self.save_undo();
+                self.cursor_col = char_count(self.get_current_line()); // Past the last char
+            },
+            InsertEntry::OpenBelow | InsertEntry::OpenAbove => {
+                self.save_undo();
+                self.open_line(entry == InsertEntry::OpenBelow);
+            },
+            InsertEntry::SubstituteChar => self.substitute_char(),
+            InsertEntry::SubstituteLine => self.substitute_line(),
+            InsertEntry::ChangeToEnd => self.change_to_end(),
+        }
+        self.enter_insert_mode();
+    }
+
+    /// Record text typed in Insert mode ('\n' for Enter)
+    pub fn record_insert(&mut self, c: char) {
+        if let Some((_, text)) = self.insert_record.as_mut() {
+            text.push(c);
+        }
+    }
+
+    /// Record what a Backspace in Insert mode removed: text typed in this session is
+    /// dropped from the recording, and anything older is replayed as one '\x08' per character
+    pub fn record_backspace(&mut self, removed: &str) {
+        if let Some((_, text)) = self.insert_record.as_mut() {
+            for c in removed.chars().rev() {
+                if text.ends_with(c) {
+                    text.pop();
+                } else {
+                    text.push('\x08');
+                }
+            }
+        }
+    }
+
+    /// Moving the cursor in Insert mode starts a new recording, as in Vim
+    pub fn restart_insert_record(&mut self) {
+        if self.insert_record.is_some() {
+            self.insert_record = Some((InsertEntry::Insert, String::new()));
+        }
+    }
+
+    /// Leaving Insert mode makes the recorded session the change '.' repeats
+    pub fn finish_insert_record(&mut self) {
+        if let Some((entry, text)) = self.insert_record.take() {
+            self.last_change = Some(RepeatableChange::Insert { entry, text });
+        }
+    }
+
     pub fn enter_normal_mode(&mut self) {
         self.mode = VimMode::Normal;
         self.pending_op = PendingOperator::None;
@@ -1110,8 +1914,26 @@ impl TextEditor {
         self.status_message = "-- VISUAL LINE --".to_string();
     }
     
+    pub fn enter_visual_block_mode(&mut self) {
+        self.mode = VimMode::VisualBlock;
+        self.visual_start_row = self.cursor_row;
+        self.visual_start_col = self.cursor_col;
+        self.pending_op = PendingOperator::None;
+        self.count_buffer.clear();
+        self.status_message = "-- VISUAL BLOCK --".to_string();
+    }
+
     /// Get visual selection range (start_row, start_col, end_row, end_col)
+    /// In block mode this is the rectangle's top-left and bottom-right corners.
     pub fn get_visual_selection(&self) -> (usize, usize, usize, usize) {
+        if self.mode == VimMode::VisualBlock {
+            return (
+                self.cursor_row.min(self.visual_start_row),
+                self.cursor_col.min(self.visual_start_col),
+                self.cursor_row.max(self.visual_start_row),
+                self.cursor_col.max(self.visual_start_col),
+            );
+        }
         let (sr, sc, er, ec) = if self.cursor_row < self.visual_start_row 
             || (self.cursor_row == self.visual_start_row && self.cursor_col < self.visual_start_col) {
             (self.cursor_row, self.cursor_col, self.visual_start_row, self.visual_start_col)
@@ -1126,12 +1948,44 @@ impl TextEditor {
         }
     }
     
+    /// Selected columns `start..end` on `row`, if the row is part of the visual selection
+    pub fn visual_columns(&self, row: usize) -> Option<(usize, usize)> {
+        let (sr, sc, er, ec) = self.get_visual_selection();
+        if !matches!(self.mode, VimMode::Visual | VimMode::VisualLine | VimMode::VisualBlock) || row < sr || row > er {
+            return None;
+        }
+        let len = char_count(self.lines.get(row)?);
+        let (start, end) = match self.mode {
+            VimMode::VisualLine => (0, len),
+            VimMode::VisualBlock => (sc, ec + 1),
+            _ => (if row == sr { sc } else { 0 }, if row == er { ec + 1 } else { len }),
+        };
+        Some((start.min(len), end.min(len)))
+    }
+
+    /// Block columns `sc..=ec` of each row in `sr..=er`; rows too short to reach the block give ""
+    fn block_pieces(&self, sr: usize, sc: usize, er: usize, ec: usize) -> Vec<String> {
+        self.lines[sr..=er].iter()
+            .map(|line| line.chars().skip(sc).take(ec + 1 - sc).collect())
+            .collect()
+    }
+
     /// Delete visual selection
     pub fn delete_visual_selection(&mut self) {
         self.save_undo();
         let (sr, sc, er, ec) = self.get_visual_selection();
         
-        if self.mode == VimMode::VisualLine {
+        if self.mode == VimMode::VisualBlock {
+            // Remove the block's columns from every row (short rows lose only what they have)
+            self.clipboard = self.block_pieces(sr, sc, er, ec);
+            self.clipboard_is_line = false;
+            for line in &mut self.lines[sr..=er] {
+                let chars: Vec<char> = line.chars().collect();
+                if sc < chars.len() {
+                    *line = chars[..sc].iter().chain(&chars[(ec + 1).min(chars.len())..]).collect();
+                }
+            }
+        } else if self.mode == VimMode::VisualLine {
             self.clipboard = self.lines.drain(sr..=er).collect();
             self.clipboard_is_line = true;
             if self.lines.is_empty() {
@@ -1190,7 +2044,11 @@ impl TextEditor {
     pub fn yank_visual_selection(&mut self) {
         let (sr, sc, er, ec) = self.get_visual_selection();
         
-        if self.mode == VimMode::VisualLine {
+        if self.mode == VimMode::VisualBlock {
+            self.clipboard = self.block_pieces(sr, sc, er, ec);
+            self.clipboard_is_line = false;
+            self.status_message = format!("Block of {} lines yanked", self.clipboard.len());
+        } else if self.mode == VimMode::VisualLine {
             self.clipboard = self.lines[sr..=er].to_vec();
             self.clipboard_is_line = true;
             self.status_message = format!("{} lines yanked", self.clipboard.len());
@@ -1278,13 +2136,15 @@ impl TextEditor {
     
     /// Get visual selection as text (for copying)
     pub fn get_visual_selection_text(&self) -> String {
-        if self.mode != VimMode::Visual && self.mode != VimMode::VisualLine {
+        if !matches!(self.mode, VimMode::Visual | VimMode::VisualLine | VimMode::VisualBlock) {
             return String::new();
         }
         
         let (sr, sc, er, ec) = self.get_visual_selection();
         
-        if self.mode == VimMode::VisualLine {
+        if self.mode == VimMode::VisualBlock {
+            self.block_pieces(sr, sc, er, ec).join("\n")
+        } else if self.mode == VimMode::VisualLine {
             self.lines[sr..=er].join("\n")
         } else if sr == er {
             let line = &self.lines[sr];
@@ -1307,5 +2167,553 @@ impl TextEditor {
             result.join("\n")
         }
     }
+
+    /// Start typing at the visual block's left edge (I) or right edge (A)
+    /// The text typed on the top row is copied to the other rows on Esc.
+    pub fn block_insert(&mut self, append: bool) {
+        let (sr, sc, er, ec) = self.get_visual_selection();
+        self.save_undo();
+        self.start_block_insert(sr, er, if append { ec + 1 } else { sc });
+    }
+
+    /// Replace the visual block with typed text (c)
+    pub fn change_block(&mut self) {
+        let (sr, sc, er, _) = self.get_visual_selection();
+        self.delete_visual_selection();
+        self.start_block_insert(sr, er, sc);
+    }
+
+    fn start_block_insert(&mut self, top: usize, bottom: usize, col: usize) {
+        pad_to(&mut self.lines[top], col);
+        self.block_insert = Some(BlockInsert {
+            top,
+            bottom,
+            col,
+            len_before: char_count(&self.lines[top]),
+            line_count: self.lines.len(),
+        });
+        self.cursor_row = top;
+        self.cursor_col = col;
+        self.enter_insert_mode();
+        self.status_message = "-- INSERT (block) --".to_string();
+    }
+
+    /// Copy the text typed during a block insert to the block's other rows
+    /// Rows shorter than the insert column are padded with spaces, so the text
+    /// always lands in the same column.
+    pub fn finish_block_insert(&mut self) {
+        let Some(block) = self.block_insert.take() else { return };
+        if self.lines.len() != block.line_count {
+            return;
+        }
+        let top: Vec<char> = self.lines[block.top].chars().collect();
+        let added = top.len().saturating_sub(block.len_before);
+        if added == 0 || block.col + added > top.len() {
+            return;
+        }
+        let text: String = top[block.col..block.col + added].iter().collect();
+        for line in &mut self.lines[block.top + 1..=block.bottom] {
+            pad_to(line, block.col);
+            insert_str_at(line, block.col, &text);
+        }
+        self.modified = true;
+    }
+
+    /// Toggle line comments on rows `start..=end` (gc)
+    /// Comments are added after each line's indentation, or stripped if the
+    /// first line is already commented
+    pub fn toggle_comment_lines(&mut self, start: usize, end: usize) {
+        let Some(prefix) = self.file_path.as_deref().and_then(comment_prefix_for) else {
+            self.status_message = "No comment syntax for this file type".to_string();
+            return;
+        };
+        let end = end.min(self.lines.len().saturating_sub(1));
+        if start > end {
+            return;
+        }
+
+        self.save_undo();
+        let uncomment = self.lines[start].trim_start().starts_with(prefix);
+
+        for line in &mut self.lines[start..=end] {
+            let indent_len = line.len() - line.trim_start().len();
+            let body = &line[indent_len..];
+            if uncomment {
+                if let Some(rest) = body.strip_prefix(prefix) {
+                    let rest = rest.strip_prefix(' ').unwrap_or(rest);
+                    *line = format!("{}{}", &line[..indent_len], rest);
+                }
+            } else if !body.is_empty() {
+                *line = format!("{}{} {}", &line[..indent_len], prefix, body);
+            }
+        }
+
+        self.cursor_col = self.cursor_col.min(char_count(&self.lines[self.cursor_row]).saturating_sub(1));
+        self.modified = true;
+        let count = end - start + 1;
+        self.status_message = format!(
+            "{} {} line{}",
+            if uncomment { "Uncommented" } else { "Commented" },
+            count,
+            if count == 1 { "" } else { "s" }
+        );
+    }
+
+    /// Run a substitute command as one undo step; returns the number of replacements
+    /// An empty pattern reuses the last search. With `regex`, the pattern is a regular
+    /// expression and `$1`/`${name}` in the replacement refer to its groups.
+    pub fn substitute(&mut self, command: &Substitute, regex: bool) -> Result<usize, String> {
+        let pattern = if command.pattern.is_empty() { self.search_pattern.clone() } else { command.pattern.clone() };
+        if pattern.is_empty() {
+            return Err("No previous search pattern".to_string());
+        }
+        let limit = if command.all { 0 } else { 1 }; // 0 = every match
+        let matcher = if regex {
+            Some(regex::Regex::new(&pattern).map_err(|e| format!("Invalid pattern: {}", e))?)
+        } else {
+            None
+        };
+
+        let rows = if command.whole_buffer { 0..self.lines.len() } else { self.cursor_row..self.cursor_row + 1 };
+        let mut replaced = Vec::new();
+        let mut count = 0;
+        for row in rows {
+            let line = &self.lines[row];
+            let (new_line, n) = match &matcher {
+                Some(re) => {
+                    let n = re.find_iter(line).count();
+                    (re.replacen(line, limit, command.replacement.as_str()).into_owned(), n)
+                },
+                None => {
+                    let n = line.matches(pattern.as_str()).count();
+                    let new_line = if command.all {
+                        line.replace(pattern.as_str(), &command.replacement)
+                    } else {
+                        line.replacen(pattern.as_str(), &command.replacement, 1)
+                    };
+                    (new_line, n)
+                },
+            };
+            if n > 0 {
+                count += if command.all { n } else { 1 };
+                replaced.push((row, new_line));
+            }
+        }
+
+        if count == 0 {
+            return Err(format!("Pattern not found: {}", pattern));
+        }
+        self.save_undo();
+        for (row, line) in replaced {
+            self.lines[row] = line;
+        }
+        self.modified = true;
+        self.clamp_cursor_col();
+        Ok(count)
+    }
+
+    /// Toggle comments on the visual selection and return to normal mode
+    pub fn toggle_comment_visual(&mut self) {
+        let (sr, _, er, _) = self.get_visual_selection();
+        self.enter_normal_mode();
+        self.cursor_row = sr;
+        self.toggle_comment_lines(sr, er);
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_comment_rust_line() {
+        let mut editor = TextEditor::new(
+            "fn main() {\n    let x = 1;\n}".to_string(),
+            Some(PathBuf::from("main.rs")),
+        );
+        editor.cursor_row = 1;
+
+        editor.toggle_comment_lines(1, 1);
+        assert_eq!(editor.lines[1], "    // let x = 1;");
+        assert!(editor.modified);
+        assert_eq!(editor.undo_stack.len(), 1);
+
+        editor.toggle_comment_lines(1, 1);
+        assert_eq!(editor.lines[1], "    let x = 1;");
+
+        editor.undo();
+        assert_eq!(editor.lines[1], "    // let x = 1;");
+    }
+
+    #[test]
+    fn test_toggle_comment_python_selection() {
+        let mut editor = TextEditor::new(
+            "def f():\n    a = 1\n\n    return a".to_string(),
+            Some(PathBuf::from("script.py")),
+        );
+        editor.enter_visual_line_mode();
+        editor.cursor_row = 3;
+
+        editor.toggle_comment_visual();
+        assert_eq!(editor.mode, VimMode::Normal);
+        assert_eq!(editor.lines, vec!["# def f():", "    # a = 1", "", "    # return a"]);
+
+        // First line commented -> whole selection is uncommented
+        editor.toggle_comment_lines(0, 3);
+        assert_eq!(editor.lines, vec!["def f():", "    a = 1", "", "    return a"]);
+    }
+
+    #[test]
+    fn test_line_endings_round_trip() {
+        let mut editor = TextEditor::new("a\r\nb\r\n".to_string(), None);
+        assert_eq!(editor.lines, vec!["a", "b"]);
+        assert_eq!(editor.line_ending, LineEnding::CrLf);
+        assert_eq!(editor.get_content(), "a\r\nb\r\n");
+
+        // No final newline is kept as is
+        editor.load_content("a\nb");
+        assert_eq!(editor.line_ending, LineEnding::Lf);
+        assert_eq!(editor.get_content(), "a\nb");
+
+        assert_eq!(editor.set_option("ff=dos"), Ok("fileformat=dos".to_string()));
+        assert!(editor.modified);
+        editor.set_option("eol").unwrap();
+        assert_eq!(editor.get_content(), "a\r\nb\r\n");
+        assert!(editor.set_option("ff=mac").is_err());
+        assert!(editor.set_option("tw=80").is_err());
+        assert!(editor.set_option("ts=0").is_err());
+    }
+
+    #[test]
+    fn test_parse_substitute() {
+        let sub = parse_substitute("s/a\\/b/c/g").unwrap();
+        assert_eq!((sub.pattern.as_str(), sub.replacement.as_str()), ("a/b", "c"));
+        assert!(sub.all && !sub.whole_buffer);
+
+        // Empty replacement, no trailing slash, regex escapes kept
+        let sub = parse_substitute("%s/\\d+/").unwrap();
+        assert_eq!((sub.pattern.as_str(), sub.replacement.as_str()), ("\\d+", ""));
+        assert!(sub.whole_buffer && !sub.all);
+
+        assert!(parse_substitute("s/only").is_none());
+        assert!(parse_substitute("s/a/b/x").is_none());
+        assert!(parse_substitute("set ff=dos").is_none());
+    }
+
+    #[test]
+    fn test_substitute_counts_and_undoes_in_one_step() {
+        let mut editor = TextEditor::new("foo foo\nbar\nfoo".to_string(), None);
+        let all = parse_substitute("%s/foo/x/g").unwrap();
+        assert_eq!(editor.substitute(&all, false), Ok(3));
+        assert_eq!(editor.lines, vec!["x x", "bar", "x"]);
+        assert_eq!(editor.undo_stack.len(), 1);
+        editor.undo();
+        assert_eq!(editor.lines, vec!["foo foo", "bar", "foo"]);
+
+        // First match on the current line only
+        let first = parse_substitute("s/foo/y/").unwrap();
+        assert_eq!(editor.substitute(&first, false), Ok(1));
+        assert_eq!(editor.lines[0], "y foo");
+
+        // Regex with groups; a miss leaves no undo entry
+        let re = parse_substitute("%s/(\\w)oo/$1$1/g").unwrap();
+        assert_eq!(editor.substitute(&re, true), Ok(2));
+        assert_eq!(editor.lines, vec!["y ff", "bar", "ff"]);
+        let undo_len = editor.undo_stack.len();
+        assert!(editor.substitute(&parse_substitute("s/zzz/q/").unwrap(), false).is_err());
+        assert_eq!(editor.undo_stack.len(), undo_len);
+        assert!(editor.substitute(&parse_substitute("s/(/q/").unwrap(), true).is_err());
+    }
+
+    #[test]
+    fn test_visual_block_delete_and_insert() {
+        let mut editor = TextEditor::new("abcd\nab\nabcdef".to_string(), None);
+        editor.cursor_col = 1;
+        editor.enter_visual_block_mode();
+        editor.cursor_row = 2;
+        editor.cursor_col = 2;
+        assert_eq!(editor.get_visual_selection(), (0, 1, 2, 2));
+        assert_eq!(editor.get_visual_selection_text(), "bc\nb\nbc");
+
+        // Short rows lose only the part inside the block
+        editor.delete_visual_selection();
+        assert_eq!(editor.lines, vec!["ad", "a", "adef"]);
+        assert_eq!(editor.clipboard, vec!["bc", "b", "bc"]);
+
+        // A: append after the block's right edge, padding short rows
+        editor.cursor_row = 0;
+        editor.cursor_col = 1;
+        editor.enter_visual_block_mode();
+        editor.cursor_row = 2;
+        editor.block_insert(true);
+        editor.insert_char('|');
+        editor.finish_block_insert();
+        editor.enter_normal_mode();
+        assert_eq!(editor.lines, vec!["ad|", "a |", "ad|ef"]);
+
+        // One undo step for the whole insert
+        editor.undo();
+        assert_eq!(editor.lines, vec!["ad", "a", "adef"]);
+    }
+
+    #[test]
+    fn test_repeat_replays_backspace_over_existing_text() {
+        let mut editor = TextEditor::new("        a\n        b".to_string(), None);
+        editor.cursor_col = 8;
+        editor.begin_insert(InsertEntry::Insert);
+        // Backspace at an indent stop removes four spaces that predate the insert
+        let removed = editor.backspace();
+        editor.record_backspace(&removed);
+        editor.insert_char('x');
+        editor.record_insert('x');
+        editor.finish_insert_record();
+        editor.enter_normal_mode();
+        assert_eq!(editor.lines[0], "    xa");
+
+        editor.cursor_row = 1;
+        editor.cursor_col = 8;
+        editor.repeat_last_change(None);
+        assert_eq!(editor.lines[1], "    xb");
+    }
+
+    #[test]
+    fn test_auto_indent_and_indent_backspace() {
+        let mut editor = TextEditor::new("    fn f() {}".to_string(), None);
+        editor.mode = VimMode::Insert;
+        editor.cursor_col = 12; // Between the braces
+        editor.insert_newline();
+        assert_eq!(editor.lines, vec!["    fn f() {", "        ", "    }"]);
+        assert_eq!((editor.cursor_row, editor.cursor_col), (1, 8));
+
+        // Backspace in leading spaces removes a whole indent level
+        editor.backspace();
+        assert_eq!(editor.lines[1], "    ");
+        editor.insert_char('x');
+        editor.backspace();
+        assert_eq!(editor.lines[1], "    ");
+
+        // 'o' keeps the indentation; without auto-indent lines start at column 0
+        editor.cursor_row = 2;
+        editor.open_line(true);
+        assert_eq!(editor.lines[3], "    ");
+        editor.set_option("noai").unwrap();
+        editor.insert_newline();
+        assert_eq!(editor.lines[4], "");
+    }
+
+    #[test]
+    fn test_auto_pair() {
+        let mut editor = TextEditor::new(String::new(), None);
+        editor.auto_pair = true;
+        for c in "f(x".chars() {
+            editor.insert_char(c);
+        }
+        assert_eq!((editor.lines[0].as_str(), editor.cursor_col), ("f(x)", 3));
+        // The closing bracket is stepped over, not doubled
+        editor.insert_char(')');
+        assert_eq!((editor.lines[0].as_str(), editor.cursor_col), ("f(x)", 4));
+
+        // No pair for an apostrophe after a word
+        for c in " don't".chars() {
+            editor.insert_char(c);
+        }
+        assert_eq!(editor.lines[0], "f(x) don't");
+
+        // Backspace in an empty pair removes both
+        editor.insert_char('[');
+        assert_eq!(editor.lines[0], "f(x) don't[]");
+        editor.backspace();
+        assert_eq!((editor.lines[0].as_str(), editor.cursor_col), ("f(x) don't", 10));
+    }
+
+    #[test]
+    fn test_relative_gutter_numbers() {
+        let mut editor = TextEditor::new("a\nb\nc\nd".to_string(), None);
+        editor.cursor_row = 2;
+        assert_eq!(editor.gutter_number(0), 1);
+        editor.set_option("rnu").unwrap();
+        // Distance elsewhere, the absolute number on the cursor line
+        assert_eq!((0..4).map(|row| editor.gutter_number(row)).collect::<Vec<_>>(), vec![2, 1, 3, 1]);
+    }
+
+    #[test]
+    fn test_nano_search_go_to_line_and_replace() {
+        let mut editor = TextEditor::new("foo bar\nbaz foo\nfoo".to_string(), None);
+        editor.search_pattern = "foo".to_string();
+        editor.nano_search_next();
+        assert_eq!((editor.cursor_row, editor.cursor_col), (1, 4));
+        assert_eq!(editor.status_message, "Match 2 of 3");
+        editor.nano_search_next();
+        editor.nano_search_next();
+        assert_eq!(editor.status_message, "Match 1 of 3 [Search Wrapped]");
+
+        editor.nano_go_to_line("3,2");
+        assert_eq!((editor.cursor_row, editor.cursor_col), (2, 1));
+        editor.nano_go_to_line("x");
+        assert_eq!(editor.status_message, "Invalid line or column number");
+
+        // Starts at the cursor, wraps around and stops where it began
+        editor.cursor_row = 1;
+        editor.cursor_col = 0;
+        editor.nano_start_replace("foo".to_string(), "qux".to_string());
+        assert_eq!((editor.cursor_row, editor.cursor_col), (1, 4));
+        editor.nano_answer_replace('n');
+        assert_eq!((editor.cursor_row, editor.cursor_col), (2, 0));
+        editor.nano_answer_replace('y');
+        assert_eq!((editor.cursor_row, editor.cursor_col), (0, 0));
+        editor.nano_answer_replace('a');
+        assert_eq!(editor.lines, vec!["qux bar", "baz foo", "qux"]);
+        assert_eq!(editor.nano_prompt, None);
+        assert_eq!(editor.status_message, "Replaced 2 occurrences");
+
+        // One undo step for the whole replace
+        editor.undo();
+        assert_eq!(editor.lines, vec!["foo bar", "baz foo", "foo"]);
+    }
+
+    #[test]
+    fn test_dot_repeat() {
+        let mut editor = TextEditor::new("abcdef\none\ntwo\nthree".to_string(), None);
+        editor.perform_change(RepeatableChange::DeleteChar(2));
+        assert_eq!(editor.lines[0], "cdef");
+        editor.repeat_last_change(None);
+        assert_eq!(editor.lines[0], "ef");
+        // A count replaces the original one; the repeat is a single undo step
+        editor.repeat_last_change(Some(1));
+        assert_eq!(editor.lines[0], "f");
+        editor.undo();
+        assert_eq!(editor.lines[0], "ef");
+
+        editor.cursor_row = 1;
+        editor.perform_change(RepeatableChange::DeleteLines(1));
+        editor.repeat_last_change(None);
+        assert_eq!(editor.lines, vec!["ef", "three"]);
+
+        // The text typed in an insert session is replayed with the same entry
+        editor.begin_insert(InsertEntry::LineEnd);
+        for c in "!?".chars() {
+            editor.insert_char(c);
+            editor.record_insert(c);
+        }
+        let removed = editor.backspace();
+        editor.record_backspace(&removed);
+        editor.finish_insert_record();
+        editor.enter_normal_mode();
+        assert_eq!(editor.lines[1], "three!");
+        editor.cursor_row = 0;
+        editor.cursor_col = 0;
+        editor.repeat_last_change(None);
+        assert_eq!(editor.lines[0], "ef!");
+        assert_eq!(editor.mode, VimMode::Normal);
+        editor.undo();
+        assert_eq!(editor.lines[0], "ef");
+
+        editor.perform_change(RepeatableChange::ReplaceChar('x'));
+        editor.cursor_col = 1;
+        editor.repeat_last_change(None);
+        assert_eq!(editor.lines[0], "xx");
+    }
+
+    #[test]
+    fn test_macro_registers() {
+        let mut editor = TextEditor::new(String::new(), None);
+        assert!(!editor.start_macro_recording('1'));
+        assert!(editor.start_macro_recording('a'));
+        editor.macro_buffer.extend([KeyCode::Char('x'), KeyCode::Char('j')]);
+        editor.stop_macro_recording();
+        assert_eq!(editor.recording_macro, None);
+
+        // An uppercase register appends
+        editor.start_macro_recording('A');
+        editor.macro_buffer.push(KeyCode::Esc);
+        editor.stop_macro_recording();
+        assert_eq!(editor.macros[&'a'], vec![KeyCode::Char('x'), KeyCode::Char('j'), KeyCode::Esc]);
+
+        // '@@' replays the last played register
+        assert_eq!(editor.macro_keys('@'), None);
+        assert_eq!(editor.macro_keys('a').map(|k| k.len()), Some(3));
+        assert_eq!(editor.macro_keys('@').map(|k| k.len()), Some(3));
+        assert_eq!(editor.macro_keys('b'), None);
+        assert_eq!(editor.status_message, "Register @b is empty");
+    }
+
+    #[test]
+    fn test_named_registers() {
+        let mut editor = TextEditor::new("one\ntwo\nthree".to_string(), None);
+        editor.select_register('a');
+        editor.yank_line();
+        editor.finish_register();
+        assert_eq!(editor.registers[&'a'], (vec!["one".to_string()], true));
+        assert_eq!(editor.clipboard, vec!["one"]);
+
+        // Yanking without a register leaves it alone; "ap pastes it without touching the unnamed clipboard
+        editor.cursor_row = 1;
+        editor.yank_line();
+        editor.select_register('a');
+        editor.paste_after();
+        editor.finish_register();
+        assert_eq!(editor.lines, vec!["one", "two", "one", "three"]);
+        assert_eq!(editor.clipboard, vec!["two"]);
+
+        // An uppercase register appends
+        editor.cursor_row = 3;
+        editor.select_register('A');
+        editor.yank_line();
+        editor.finish_register();
+        assert_eq!(editor.registers[&'a'].0, vec!["one", "three"]);
+        assert!(!editor.select_register('%'));
+    }
+
+    #[test]
+    fn test_tab_indentation() {
+        let mut editor = TextEditor::new("\tif x {\n\t\tbody\n".to_string(), None);
+        editor.set_option("noet").unwrap();
+        editor.indent_line();
+        assert_eq!(editor.lines[0], "\t\tif x {");
+        editor.cursor_row = 1;
+        editor.outdent_line();
+        assert_eq!(editor.lines[1], "\tbody");
+
+        // Auto-indent copies tabs and adds a tab after an opening bracket
+        editor.cursor_row = 0;
+        editor.cursor_col = char_count(&editor.lines[0]);
+        editor.insert_newline();
+        assert_eq!(editor.lines[1], "\t\t\t");
+
+        // Tabs survive a load/save round trip
+        let mut editor = TextEditor::new("a\tb\n\tc\n".to_string(), None);
+        assert_eq!(editor.get_content(), "a\tb\n\tc\n");
+
+        // Spaces: ts sets the width of an indent level
+        editor.set_option("ts=2").unwrap();
+        editor.indent_line();
+        assert_eq!(editor.lines[0], "  a\tb");
+        editor.outdent_line();
+        editor.outdent_line();
+        assert_eq!(editor.lines[0], "a\tb");
+    }
+
+    #[test]
+    fn test_toggle_comment_unknown_type() {
+        let mut editor = TextEditor::new("data".to_string(), Some(PathBuf::from("file.xyz")));
+        editor.toggle_comment_lines(0, 0);
+        assert_eq!(editor.lines[0], "data");
+        assert!(editor.undo_stack.is_empty());
+        assert_eq!(comment_prefix_for(Path::new("query.sql")), Some("--"));
+        assert_eq!(comment_prefix_for(Path::new("Makefile")), Some("#"));
+    }
+
+    #[test]
+    fn test_page_moves_follow_visible_height() {
+        let text: String = (0..100).map(|i| format!("{}\n", i)).collect();
+        let mut editor = TextEditor::new(text, None);
+        editor.set_visible_height(11);
+        editor.move_half_page_down();
+        assert_eq!(editor.cursor_row, 5);
+        editor.move_page_down();
+        assert_eq!(editor.cursor_row, 16);
+        editor.move_page_up();
+        editor.move_half_page_up();
+        assert_eq!(editor.cursor_row, 0);
+    }
+}
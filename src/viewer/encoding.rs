@@ -0,0 +1,135 @@
+//! Text encodings other than UTF-8
+//!
+//! A byte order mark decides UTF-8 or UTF-16 and is written back on save.
+//! Otherwise text that isn't valid UTF-8 goes to chardetng for a guess among
+//! the legacy encodings. The viewer's `:enc NAME` overrides the guess.
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+/// How a text file was encoded, so an edit is saved the same way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextEncoding {
+    pub encoding: &'static Encoding,
+    pub bom: bool, // The file started with a byte order mark
+}
+
+impl TextEncoding {
+    /// UTF-8 without a byte order mark, the default that needs no mention
+    pub fn is_plain_utf8(&self) -> bool {
+        self.encoding == UTF_8 && !self.bom
+    }
+
+    /// Name for the viewer header
+    pub fn label(&self) -> String {
+        if self.encoding == UTF_8 && self.bom {
+            "UTF-8 BOM".to_string()
+        } else {
+            self.encoding.name().to_string()
+        }
+    }
+}
+
+/// Text of `bytes` and how it was encoded; None for binary data
+pub fn decode(bytes: &[u8]) -> Option<(String, TextEncoding)> {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        let (text, had_errors) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+        return (!had_errors).then(|| (text.into_owned(), TextEncoding { encoding, bom: true }));
+    }
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return Some((text.to_string(), TextEncoding { encoding: UTF_8, bom: false }));
+    }
+    let encoding = detect(bytes);
+    let (text, _) = encoding.decode_without_bom_handling(bytes);
+    if super::is_likely_binary(&text) {
+        return None;
+    }
+    Some((text.into_owned(), TextEncoding { encoding, bom: false }))
+}
+
+/// Decode `bytes` as `encoding`, replacing invalid sequences (a leading byte order mark is dropped)
+pub fn decode_as(bytes: &[u8], encoding: &'static Encoding) -> (String, TextEncoding) {
+    let bom = Encoding::for_bom(bytes).is_some_and(|(found, _)| found == encoding);
+    (encoding.decode_with_bom_removal(bytes).0.into_owned(), TextEncoding { encoding, bom })
+}
+
+/// Best guess for bytes that aren't UTF-8
+fn detect(bytes: &[u8]) -> &'static Encoding {
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(bytes, true);
+    detector.guess(None, false)
+}
+
+/// `text` in `target`'s encoding, for saving; Err if it has characters the encoding can't represent
+pub fn encode(text: &str, target: TextEncoding) -> Result<Vec<u8>, String> {
+    let encoding = target.encoding;
+    // encoding_rs only decodes UTF-16; write it with the byte order mark it was read with
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        let mut bytes = if encoding == UTF_16LE { vec![0xFF, 0xFE] } else { vec![0xFE, 0xFF] };
+        for unit in text.encode_utf16() {
+            bytes.extend(if encoding == UTF_16LE { unit.to_le_bytes() } else { unit.to_be_bytes() });
+        }
+        return Ok(bytes);
+    }
+    let (bytes, _, had_errors) = encoding.encode(text);
+    if had_errors {
+        return Err(format!("the text has characters {} can't represent", encoding.name()));
+    }
+    if encoding == UTF_8 && target.bom {
+        return Ok([&[0xEF, 0xBB, 0xBF], bytes.as_ref()].concat());
+    }
+    Ok(bytes.into_owned())
+}
+
+/// The encoding label of a viewer `:enc NAME` (or `:encoding NAME`) command
+pub fn parse_command(input: &str) -> Option<&str> {
+    let mut words = input.split_whitespace();
+    match (words.next(), words.next(), words.next()) {
+        (Some("enc" | "encoding"), Some(label), None) => Some(label),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use encoding_rs::{EUC_KR, GBK, SHIFT_JIS, WINDOWS_1252};
+
+    fn detected(text: &str, encoding: &'static Encoding) -> &'static Encoding {
+        let (bytes, _, _) = encoding.encode(text);
+        let (decoded, detected) = decode(&bytes).unwrap();
+        assert_eq!(decoded, text);
+        detected.encoding
+    }
+
+    #[test]
+    fn test_detect_legacy_encodings() {
+        assert_eq!(detected("Café crème, naïve résumé\n", WINDOWS_1252), WINDOWS_1252);
+        assert_eq!(detected("안녕하세요. 파일 관리자입니다.\n", EUC_KR), EUC_KR);
+        assert_eq!(detected("これは日本語のテキストです。\n", SHIFT_JIS), SHIFT_JIS);
+        assert_eq!(detected("这是一个简体中文的文本文件。\n", GBK), GBK);
+        assert_eq!(detected("plain ascii\n", UTF_8), UTF_8);
+
+        let utf16: Vec<u8> = [0xFF, 0xFE].into_iter().chain("hi 한".encode_utf16().flat_map(u16::to_le_bytes)).collect();
+        assert_eq!(decode(&utf16), Some(("hi 한".to_string(), TextEncoding { encoding: UTF_16LE, bom: true })));
+        assert_eq!(decode(&[0x00, 0x01, 0xFF, 0x00, 0x02]), None);
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let euc_kr = TextEncoding { encoding: EUC_KR, bom: false };
+        let bytes = encode("파일", euc_kr).unwrap();
+        assert_eq!(decode_as(&bytes, EUC_KR), ("파일".to_string(), euc_kr));
+        assert!(encode("파일", TextEncoding { encoding: WINDOWS_1252, bom: false }).is_err());
+        assert_eq!(encode("a", TextEncoding { encoding: UTF_16BE, bom: true }).unwrap(), vec![0xFE, 0xFF, 0x00, b'a']);
+
+        // A UTF-8 byte order mark is kept through a round trip
+        let (text, found) = decode(b"\xEF\xBB\xBFname,size\n").unwrap();
+        assert_eq!((text.as_str(), found.label().as_str()), ("name,size\n", "UTF-8 BOM"));
+        assert_eq!(encode(&text, found).unwrap(), b"\xEF\xBB\xBFname,size\n");
+
+        assert_eq!(parse_command("enc shift_jis"), Some("shift_jis"));
+        assert_eq!(parse_command("encoding auto"), Some("auto"));
+        assert_eq!(parse_command("12"), None);
+    }
+}
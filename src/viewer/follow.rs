@@ -0,0 +1,116 @@
+//! Follow mode for the viewer ("tail -f")
+//!
+//! The followed file is stat'ed on every tick. Bytes appended since the last
+//! read are decoded and appended to the viewer content, so a growing log is
+//! never read again in full. A file that shrank (truncated or rotated) is
+//! reloaded instead.
+
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use super::ViewerContent;
+
+/// Follow state of the viewed file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FollowState {
+    pub offset: u64,  // Bytes of the file already shown
+    pub paused: bool, // Scrolled up by hand; new lines are appended but not scrolled to
+}
+
+/// What happened to the file since the last read
+#[derive(Debug, PartialEq, Eq)]
+pub enum Growth {
+    Unchanged,
+    Appended(String),
+    Truncated,
+}
+
+/// Whether `content` can be followed (plain and highlighted text)
+pub fn can_follow(content: &ViewerContent) -> bool {
+    matches!(content, ViewerContent::PlainText(_) | ViewerContent::HighlightedCode { .. })
+}
+
+/// Read what was appended to `path` after `offset`, advancing `offset` past it
+/// A multi-byte character cut off at the end of the file is left for the next read.
+pub fn read_appended(path: &Path, offset: &mut u64) -> std::io::Result<Growth> {
+    let len = std::fs::metadata(path)?.len();
+    if len < *offset {
+        return Ok(Growth::Truncated);
+    }
+    if len == *offset {
+        return Ok(Growth::Unchanged);
+    }
+
+    let mut file = std::fs::File::open(path)?;
+    file.seek(SeekFrom::Start(*offset))?;
+    let mut bytes = Vec::new();
+    file.take(len - *offset).read_to_end(&mut bytes)?;
+
+    let text = match std::str::from_utf8(&bytes) {
+        Ok(text) => text.to_string(),
+        Err(e) if e.error_len().is_none() => {
+            bytes.truncate(e.valid_up_to());
+            String::from_utf8_lossy(&bytes).into_owned()
+        },
+        Err(_) => String::from_utf8_lossy(&bytes).into_owned(),
+    };
+    *offset += bytes.len() as u64;
+    Ok(if text.is_empty() { Growth::Unchanged } else { Growth::Appended(text) })
+}
+
+/// Append text read from the file to the viewer content; false if it can't be followed
+pub fn append(content: &mut ViewerContent, text: &str) -> bool {
+    match content {
+        ViewerContent::PlainText(raw) => raw.push_str(text),
+        ViewerContent::HighlightedCode { raw, highlighted } => {
+            let continues_last_line = !raw.is_empty() && !raw.ends_with('\n');
+            raw.push_str(text);
+            highlighted.append(text, continues_last_line);
+        },
+        _ => return false,
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_read_appended() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("app.log");
+        std::fs::write(&path, "first\n").unwrap();
+        let mut offset = 6;
+        assert_eq!(read_appended(&path, &mut offset).unwrap(), Growth::Unchanged);
+
+        // A split multi-byte character waits for its remaining bytes
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"second \xed\x95").unwrap();
+        assert_eq!(read_appended(&path, &mut offset).unwrap(), Growth::Appended("second ".to_string()));
+        file.write_all(b"\x9c\n").unwrap();
+        assert_eq!(read_appended(&path, &mut offset).unwrap(), Growth::Appended("한\n".to_string()));
+        assert_eq!(offset, std::fs::metadata(&path).unwrap().len());
+
+        std::fs::write(&path, "rotated\n").unwrap();
+        assert_eq!(read_appended(&path, &mut offset).unwrap(), Growth::Truncated);
+    }
+
+    #[test]
+    fn test_append_continues_partial_line() {
+        let mut content = ViewerContent::HighlightedCode {
+            raw: "a = 1\nb =".to_string(),
+            highlighted: super::super::LazyHighlight::new("a = 1\nb =", "py", super::super::highlight::DEFAULT_SYNTAX_THEME),
+        };
+        assert!(append(&mut content, " 2\nc = 3\n"));
+        let ViewerContent::HighlightedCode { raw, highlighted } = &content else { unreachable!() };
+        assert_eq!(raw, "a = 1\nb = 2\nc = 3\n");
+        assert_eq!(highlighted.len(), 3);
+        let text: String = highlighted.line_or_plain(1).segments.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(text, "b = 2");
+
+        assert!(!append(&mut ViewerContent::Markdown(String::new()), "x"));
+    }
+}
@@ -0,0 +1,344 @@
+//! Hex view rows and the hex editor for binary files
+//!
+//! The hex view is drawn row by row from the loaded bytes, so only the rows on
+//! screen are ever formatted. In the editor, bytes are overwritten in place, one hex digit (nibble) at a time; the file
+//! never grows or shrinks. Overwritten bytes remember their original value
+//! until saved, so they can be highlighted and typing the old value back
+//! clears them. Saving writes only the changed bytes.
+
+use std::collections::BTreeMap;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+
+/// Bytes shown per row
+pub const BYTES_PER_ROW: usize = 16;
+
+/// Column header above the rows
+pub const HEX_HEADER: &str = " Offset    00 01 02 03 04 05 06 07  08 09 0A 0B 0C 0D 0E 0F  ASCII";
+
+/// Rows needed to show `len` bytes
+pub fn row_count(len: usize) -> usize {
+    len.div_ceil(BYTES_PER_ROW)
+}
+
+/// Character shown in the ASCII column: printable ASCII as is, anything else '.'
+pub fn ascii_char(byte: u8) -> char {
+    if (0x20..=0x7E).contains(&byte) { byte as char } else { '.' }
+}
+
+/// Parse a goto-offset: decimal ("4096") or 0x-prefixed hex ("0x1000")
+pub fn parse_offset(input: &str) -> Option<usize> {
+    let input = input.trim();
+    match input.strip_prefix("0x").or_else(|| input.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => input.parse().ok(),
+    }
+}
+
+/// Bytes to search for: hex pairs ("DE AD BE EF", "deadbeef") or ASCII text
+/// Quotes force ASCII, for text that happens to be valid hex ("\"cafe\"").
+pub fn parse_pattern(query: &str) -> Option<Vec<u8>> {
+    if query.is_empty() {
+        return None;
+    }
+    if let Some(text) = query.strip_prefix('"').and_then(|q| q.strip_suffix('"')).filter(|t| !t.is_empty()) {
+        return Some(text.as_bytes().to_vec());
+    }
+    let digits: String = query.chars().filter(|c| !c.is_whitespace()).collect();
+    let is_hex = !digits.is_empty() && digits.len().is_multiple_of(2) && digits.chars().all(|c| c.is_ascii_hexdigit());
+    if is_hex {
+        return (0..digits.len()).step_by(2).map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok()).collect();
+    }
+    Some(query.as_bytes().to_vec())
+}
+
+/// Offsets of every occurrence of `pattern` in `data` (overlapping ones included)
+pub fn find_all(data: &[u8], pattern: &[u8]) -> Vec<usize> {
+    if pattern.is_empty() {
+        return Vec::new();
+    }
+    data.windows(pattern.len())
+        .enumerate()
+        .filter(|(_, window)| *window == pattern)
+        .map(|(offset, _)| offset)
+        .collect()
+}
+
+/// '/' search in the hex view
+#[derive(Debug, Clone, Default)]
+pub struct HexSearch {
+    pub input: Option<String>, // Text being typed at the prompt
+    pub query: String,          // Last submitted query, as typed
+    pub pattern: Vec<u8>,
+    pub matches: Vec<usize>,    // Offsets of all matches
+    pub current: usize,
+}
+
+impl HexSearch {
+    /// Open the prompt, keeping the previous search until a new one is submitted
+    pub fn prompt(previous: Option<HexSearch>) -> Self {
+        let mut search = previous.unwrap_or_default();
+        search.input = Some(String::new());
+        search
+    }
+
+    /// Search `data` for `query`, selecting the first match at or after `from`
+    pub fn submit(&mut self, query: String, data: &[u8], from: usize) {
+        self.pattern = parse_pattern(&query).unwrap_or_default();
+        self.matches = find_all(data, &self.pattern);
+        self.current = self.matches.iter().position(|&offset| offset >= from).unwrap_or(0);
+        self.query = query;
+        self.input = None;
+    }
+
+    pub fn current_match(&self) -> Option<usize> {
+        self.matches.get(self.current).copied()
+    }
+
+    /// Whether the byte at `offset` is part of any match
+    pub fn is_in_match(&self, offset: usize) -> bool {
+        let before = self.matches.partition_point(|&start| start <= offset);
+        before > 0 && offset < self.matches[before - 1] + self.pattern.len()
+    }
+
+    /// Select the next (or previous) match, wrapping around
+    pub fn step(&mut self, forward: bool) {
+        let count = self.matches.len();
+        if count == 0 {
+            return;
+        }
+        self.current = if forward { (self.current + 1) % count } else { (self.current + count - 1) % count };
+    }
+
+    /// Position shown in the footer, e.g. "3/17"
+    pub fn position(&self) -> String {
+        if self.matches.is_empty() {
+            "0/0".to_string()
+        } else {
+            format!("{}/{}", self.current + 1, self.matches.len())
+        }
+    }
+}
+
+/// Editing state of a binary file
+pub struct HexEditor {
+    pub path: PathBuf,
+    pub data: Vec<u8>,
+    original: BTreeMap<usize, u8>, // Original value of every byte changed since the last save
+    pub cursor: usize,              // Offset of the byte under the cursor
+    pub low_nibble: bool,           // The next digit replaces the low nibble (the high one was typed)
+    pub scroll: usize,              // First row shown
+    pub confirm_discard: bool,      // Esc was pressed once with unsaved changes
+}
+
+impl HexEditor {
+    /// Load `path` for editing, up to `max_bytes` (the hex view's limit)
+    pub fn open(path: &Path, max_bytes: u64) -> Result<Self, String> {
+        let size = std::fs::metadata(path).map_err(|e| e.to_string())?.len();
+        if size > max_bytes {
+            return Err(format!("file is larger than the {} hex editing limit (max_binary_preview_bytes)", super::format_limit(max_bytes)));
+        }
+        let data = std::fs::read(path).map_err(|e| e.to_string())?;
+        if data.is_empty() {
+            return Err("the file is empty".to_string());
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            data,
+            original: BTreeMap::new(),
+            cursor: 0,
+            low_nibble: false,
+            scroll: 0,
+            confirm_discard: false,
+        })
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        !self.original.is_empty()
+    }
+
+    /// Number of bytes changed since the last save
+    pub fn dirty_count(&self) -> usize {
+        self.original.len()
+    }
+
+    pub fn is_byte_dirty(&self, offset: usize) -> bool {
+        self.original.contains_key(&offset)
+    }
+
+    /// Put the cursor on `offset` (clamped to the file)
+    pub fn move_to(&mut self, offset: usize) {
+        self.cursor = offset.min(self.data.len().saturating_sub(1));
+        self.low_nibble = false;
+    }
+
+    /// Move the cursor by `delta` bytes (negative is backwards)
+    pub fn move_by(&mut self, delta: isize) {
+        self.move_to(self.cursor.saturating_add_signed(delta));
+    }
+
+    /// Overwrite the nibble under the cursor with hex digit `c`; false if `c` isn't one
+    /// After the low nibble the cursor moves on to the next byte. Typing also
+    /// withdraws a pending discard confirmation.
+    pub fn type_digit(&mut self, c: char) -> bool {
+        let Some(digit) = c.to_digit(16) else { return false };
+        let digit = digit as u8;
+        self.confirm_discard = false;
+        let old = self.data[self.cursor];
+        let new = if self.low_nibble { (old & 0xF0) | digit } else { (digit << 4) | (old & 0x0F) };
+
+        let original = *self.original.entry(self.cursor).or_insert(old);
+        if new == original {
+            self.original.remove(&self.cursor);
+        }
+        self.data[self.cursor] = new;
+
+        if self.low_nibble && self.cursor + 1 < self.data.len() {
+            self.move_by(1);
+        } else {
+            self.low_nibble = !self.low_nibble;
+        }
+        true
+    }
+
+    /// Scroll so the cursor's row is within `rows` visible rows
+    pub fn keep_cursor_visible(&mut self, rows: usize) {
+        let row = self.cursor / BYTES_PER_ROW;
+        let rows = rows.max(1);
+        if row < self.scroll {
+            self.scroll = row;
+        } else if row >= self.scroll + rows {
+            self.scroll = row + 1 - rows;
+        }
+    }
+
+    /// Write the changed bytes back in place; returns how many were written
+    /// Refuses if the file changed size on disk since it was opened.
+    pub fn save(&mut self) -> std::io::Result<usize> {
+        let mut file = std::fs::OpenOptions::new().write(true).open(&self.path)?;
+        if file.metadata()?.len() != self.data.len() as u64 {
+            return Err(std::io::Error::other("the file changed size on disk since it was opened"));
+        }
+        // Write contiguous runs of changed bytes
+        let offsets: Vec<usize> = self.original.keys().copied().collect();
+        for run in offsets.chunk_by(|a, b| b - a == 1) {
+            let (start, end) = (run[0], run[run.len() - 1] + 1);
+            file.seek(SeekFrom::Start(start as u64))?;
+            file.write_all(&self.data[start..end])?;
+        }
+        file.flush()?;
+        let written = self.original.len();
+        self.original.clear();
+        self.confirm_discard = false;
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_offset_and_rows() {
+        assert_eq!(parse_offset("4096"), Some(4096));
+        assert_eq!(parse_offset(" 0x1F "), Some(0x1F));
+        assert_eq!(parse_offset("0XfF"), Some(0xFF));
+        assert_eq!(parse_offset("0x"), None);
+        assert_eq!(parse_offset("12ab"), None);
+        assert_eq!(row_count(0), 0);
+        assert_eq!(row_count(16), 1);
+        assert_eq!(row_count(17), 2);
+        assert_eq!(ascii_char(b'A'), 'A');
+        assert_eq!(ascii_char(0x1F), '.');
+        assert_eq!(ascii_char(0x7F), '.');
+    }
+
+    #[test]
+    fn test_hex_search() {
+        assert_eq!(parse_pattern("DE AD be ef"), Some(vec![0xDE, 0xAD, 0xBE, 0xEF]));
+        assert_eq!(parse_pattern("PNG"), Some(b"PNG".to_vec()));
+        assert_eq!(parse_pattern("abc"), Some(b"abc".to_vec())); // Odd number of digits
+        assert_eq!(parse_pattern("\"cafe\""), Some(b"cafe".to_vec()));
+        assert_eq!(parse_pattern(""), None);
+
+        let data = b"\x89PNG..aaa..PNG";
+        assert_eq!(find_all(data, b"aa"), vec![6, 7]);
+
+        let mut search = HexSearch::prompt(None);
+        search.submit("\"PNG\"".to_string(), data, 2);
+        assert_eq!(search.matches, vec![1, 11]);
+        assert_eq!(search.current_match(), Some(11));
+        assert!(search.is_in_match(3) && !search.is_in_match(4));
+        search.step(true);
+        assert_eq!((search.current_match(), search.position()), (Some(1), "1/2".to_string()));
+        search.step(false);
+        assert_eq!(search.current_match(), Some(11));
+        search.submit("00 11".to_string(), data, 0);
+        assert_eq!((search.current_match(), search.position()), (None, "0/0".to_string()));
+    }
+
+    #[test]
+    fn test_edit_and_save_in_place() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("data.bin");
+        std::fs::write(&path, [0x00, 0x11, 0x22, 0x33]).unwrap();
+
+        let mut editor = HexEditor::open(&path, 1024).unwrap();
+        editor.move_to(1);
+        assert!(editor.type_digit('a'));
+        assert!(editor.low_nibble);
+        assert!(editor.type_digit('B'));
+        assert!(!editor.type_digit('g'));
+        assert_eq!(editor.data[1], 0xAB);
+        assert_eq!(editor.cursor, 2);
+
+        // Typing the original value back clears the change
+        editor.type_digit('2');
+        editor.type_digit('2');
+        assert_eq!(editor.dirty_count(), 1);
+        assert!(editor.is_byte_dirty(1) && !editor.is_byte_dirty(2));
+
+        // The last byte keeps the cursor and wraps to its high nibble
+        editor.move_to(100);
+        assert_eq!(editor.cursor, 3);
+        editor.type_digit('f');
+        editor.type_digit('f');
+        assert_eq!((editor.cursor, editor.low_nibble), (3, false));
+
+        assert_eq!(editor.save().unwrap(), 2);
+        assert!(!editor.is_dirty());
+        assert_eq!(std::fs::read(&path).unwrap(), vec![0x00, 0xAB, 0x22, 0xFF]);
+    }
+
+    #[test]
+    fn test_open_limits_and_size_check() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("big.bin");
+        std::fs::File::create(&path).unwrap().set_len(1025).unwrap();
+        assert!(HexEditor::open(&path, 1024).is_err());
+        std::fs::write(&path, []).unwrap();
+        assert!(HexEditor::open(&path, 1024).is_err());
+
+        std::fs::write(&path, [1, 2]).unwrap();
+        let mut editor = HexEditor::open(&path, 1024).unwrap();
+        editor.type_digit('0');
+        std::fs::write(&path, [1, 2, 3]).unwrap();
+        assert!(editor.save().is_err());
+    }
+
+    #[test]
+    fn test_keep_cursor_visible() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("data.bin");
+        std::fs::write(&path, vec![0u8; BYTES_PER_ROW * 10]).unwrap();
+        let mut editor = HexEditor::open(&path, 1024).unwrap();
+        editor.move_to(BYTES_PER_ROW * 6);
+        editor.keep_cursor_visible(4);
+        assert_eq!(editor.scroll, 3);
+        editor.move_to(BYTES_PER_ROW);
+        editor.keep_cursor_visible(4);
+        assert_eq!(editor.scroll, 1);
+    }
+}
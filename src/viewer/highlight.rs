@@ -1,8 +1,11 @@
 #![allow(dead_code)]
 
-use syntect::highlighting::ThemeSet;
-use syntect::parsing::SyntaxSet;
-use syntect::easy::HighlightLines;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use syntect::highlighting::{HighlightIterator, HighlightState, Highlighter, Theme, ThemeSet};
+use syntect::parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet};
 use ratatui::style::Color;
 
 /// A single styled segment of text
@@ -129,70 +132,207 @@ impl SyntaxHighlighter {
             || self.get_syntax_name(extension).is_some()
     }
 
-    /// Highlight code content
-    pub fn highlight(&self, content: &str, extension: &str) -> Vec<HighlightedLine> {
-        // Try by extension first (most reliable)
-        let syntax = if let Some(s) = self.syntax_set.find_syntax_by_extension(extension) {
-            s
-        } else if let Some(syntax_name) = self.get_syntax_name(extension) {
-            // Try by syntax name as fallback
-            match self.syntax_set.find_syntax_by_name(syntax_name) {
-                Some(s) => s,
-                None => return self.plain_text_lines(content),
-            }
-        } else {
-            return self.plain_text_lines(content);
-        };
+    /// Find the syntax for an extension, by extension first and then by name
+    fn find_syntax(&self, extension: &str) -> Option<&SyntaxReference> {
+        self.syntax_set.find_syntax_by_extension(extension).or_else(|| {
+            self.get_syntax_name(extension)
+                .and_then(|name| self.syntax_set.find_syntax_by_name(name))
+        })
+    }
 
-        // Use base16-ocean.dark theme (terminal-friendly)
-        let theme = &self.theme_set.themes["base16-ocean.dark"];
-        let mut highlighter = HighlightLines::new(syntax, theme);
-
-        let mut result = Vec::new();
-
-        for line in content.lines() {
-            let ranges = match highlighter.highlight_line(line, &self.syntax_set) {
-                Ok(r) => r,
-                Err(_) => {
-                    // On error, return plain text for this line
-                    result.push(HighlightedLine {
-                        segments: vec![StyledSegment {
-                            text: line.to_string(),
-                            fg: Color::White,
-                            bg: Color::Reset,
-                        }],
-                    });
-                    continue;
-                }
-            };
-
-            let segments: Vec<StyledSegment> = ranges
-                .iter()
-                .map(|(style, text)| StyledSegment {
-                    text: text.to_string(),
-                    fg: syntect_to_ratatui_color(style.foreground),
-                    bg: Color::Reset, // Use terminal background
-                })
-                .collect();
-
-            result.push(HighlightedLine { segments });
+    /// Syntax theme `name`, falling back to base16-ocean.dark (terminal-friendly)
+    fn theme(&self, name: &str) -> &Theme {
+        self.theme_set.themes.get(name)
+            .unwrap_or(&self.theme_set.themes[DEFAULT_SYNTAX_THEME])
+    }
+
+    /// Parser state for the first line of a file
+    fn start_state(&self, syntax: &SyntaxReference, highlighter: &Highlighter) -> LineState {
+        LineState {
+            parse: ParseState::new(syntax),
+            highlight: HighlightState::new(highlighter, ScopeStack::new()),
         }
+    }
+
+    /// Highlight one line, advancing `state` to the next line
+    fn highlight_line(&self, line: &str, state: &mut LineState, highlighter: &Highlighter) -> HighlightedLine {
+        let ops = match state.parse.parse_line(line, &self.syntax_set) {
+            Ok(ops) => ops,
+            // On error, return plain text for this line
+            Err(_) => return plain_line(line),
+        };
 
-        result
+        let segments = HighlightIterator::new(&mut state.highlight, &ops, line, highlighter)
+            .map(|(style, text)| StyledSegment {
+                text: text.to_string(),
+                fg: syntect_to_ratatui_color(style.foreground),
+                bg: Color::Reset, // Use terminal background
+            })
+            .collect();
+
+        HighlightedLine { segments }
+    }
+
+    /// Highlight code content with the syntax theme `theme`
+    pub fn highlight(&self, content: &str, extension: &str, theme: &str) -> Vec<HighlightedLine> {
+        let Some(syntax) = self.find_syntax(extension) else {
+            return self.plain_text_lines(content);
+        };
+
+        let highlighter = Highlighter::new(self.theme(theme));
+        let mut state = self.start_state(syntax, &highlighter);
+        content.lines()
+            .map(|line| self.highlight_line(line, &mut state, &highlighter))
+            .collect()
     }
 
     /// Convert plain text to unhighlighted lines
     fn plain_text_lines(&self, content: &str) -> Vec<HighlightedLine> {
-        content
-            .lines()
-            .map(|line| HighlightedLine {
-                segments: vec![StyledSegment {
-                    text: line.to_string(),
-                    fg: Color::White,
-                    bg: Color::Reset,
-                }],
-            })
-            .collect()
+        content.lines().map(plain_line).collect()
+    }
+}
+
+/// An unstyled line
+fn plain_line(line: &str) -> HighlightedLine {
+    HighlightedLine {
+        segments: vec![StyledSegment {
+            text: line.to_string(),
+            fg: Color::White,
+            bg: Color::Reset,
+        }],
+    }
+}
+
+/// Parser and highlighter state carried from one line to the next
+#[derive(Clone, Debug)]
+struct LineState {
+    parse: ParseState,
+    highlight: HighlightState,
+}
+
+/// Lines highlighted at a time
+pub const HIGHLIGHT_CHUNK_LINES: usize = 200;
+
+/// Lines highlighted above and below the visible window
+pub const HIGHLIGHT_BUFFER_LINES: usize = 200;
+
+/// Syntax highlighting computed on demand, one chunk of lines at a time
+///
+/// The cache is keyed by the first line of each `HIGHLIGHT_CHUNK_LINES` range.
+/// Each chunk continues from the parser state at the end of the one above, so
+/// multi-line strings and comments keep their colors across chunk boundaries.
+/// Jumping ahead parses the skipped chunks only to record their end states,
+/// without keeping their styled lines. Lines outside the cache have no
+/// highlighting yet and should be drawn plain.
+#[derive(Clone, Debug)]
+pub struct LazyHighlight {
+    extension: String,
+    theme: String,
+    lines: Vec<String>,
+    chunks: BTreeMap<usize, Vec<HighlightedLine>>,
+    end_states: BTreeMap<usize, LineState>, // Parser state after each chunk parsed so far
+}
+
+impl LazyHighlight {
+    /// Prepare `content` for highlighting with the syntax theme `theme`, highlighting only the first chunk
+    pub fn new(content: &str, extension: &str, theme: &str) -> Self {
+        let mut lazy = LazyHighlight {
+            extension: extension.to_string(),
+            theme: theme.to_string(),
+            lines: content.lines().map(str::to_string).collect(),
+            chunks: BTreeMap::new(),
+            end_states: BTreeMap::new(),
+        };
+        lazy.ensure_range(0..HIGHLIGHT_CHUNK_LINES);
+        lazy
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Highlight every chunk overlapping `range` that isn't cached yet
+    pub fn ensure_range(&mut self, range: Range<usize>) {
+        let end = range.end.min(self.lines.len());
+        let first_chunk = range.start - range.start % HIGHLIGHT_CHUNK_LINES;
+        for chunk_start in (first_chunk..end).step_by(HIGHLIGHT_CHUNK_LINES) {
+            if !self.chunks.contains_key(&chunk_start) {
+                let chunk = self.highlight_chunk(chunk_start);
+                self.chunks.insert(chunk_start, chunk);
+            }
+        }
+    }
+
+    fn highlight_chunk(&mut self, chunk_start: usize) -> Vec<HighlightedLine> {
+        let chunk_end = (chunk_start + HIGHLIGHT_CHUNK_LINES).min(self.lines.len());
+
+        let syntax_highlighter = get_highlighter();
+        let Some(syntax) = syntax_highlighter.find_syntax(&self.extension) else {
+            return self.lines[chunk_start..chunk_end].iter().map(|l| plain_line(l)).collect();
+        };
+
+        let highlighter = Highlighter::new(syntax_highlighter.theme(&self.theme));
+        let mut state = self.state_at(chunk_start, syntax, &highlighter);
+        let lines = self.lines[chunk_start..chunk_end].iter()
+            .map(|line| syntax_highlighter.highlight_line(line, &mut state, &highlighter))
+            .collect();
+        self.end_states.insert(chunk_start, state);
+        lines
+    }
+
+    /// Parser state at the start of the chunk at `chunk_start`, parsing the chunks
+    /// above it back to the last recorded state
+    fn state_at(&mut self, chunk_start: usize, syntax: &SyntaxReference, highlighter: &Highlighter) -> LineState {
+        let syntax_highlighter = get_highlighter();
+        let (mut next, mut state) = match self.end_states.range(..chunk_start).next_back() {
+            Some((&start, state)) => (start + HIGHLIGHT_CHUNK_LINES, state.clone()),
+            None => (0, syntax_highlighter.start_state(syntax, highlighter)),
+        };
+        while next < chunk_start {
+            for line in &self.lines[next..next + HIGHLIGHT_CHUNK_LINES] {
+                syntax_highlighter.highlight_line(line, &mut state, highlighter);
+            }
+            self.end_states.insert(next, state.clone());
+            next += HIGHLIGHT_CHUNK_LINES;
+        }
+        state
+    }
+
+    /// Add text appended to the file (follow mode)
+    /// `continues_last_line` is set when the old content didn't end with a newline,
+    /// so the first appended line completes the last one. Chunks from the first
+    /// changed line on are highlighted again when next drawn.
+    pub fn append(&mut self, text: &str, continues_last_line: bool) {
+        let mut new_lines = text.lines();
+        let first_changed = match self.lines.last_mut() {
+            Some(last) if continues_last_line => {
+                last.push_str(new_lines.next().unwrap_or(""));
+                self.lines.len() - 1
+            },
+            _ => self.lines.len(),
+        };
+        self.lines.extend(new_lines.map(str::to_string));
+        let first_chunk = first_changed - first_changed % HIGHLIGHT_CHUNK_LINES;
+        self.chunks.split_off(&first_chunk);
+        self.end_states.split_off(&first_chunk);
+    }
+
+    /// Highlighted line at `index`, if its chunk has been highlighted
+    pub fn line(&self, index: usize) -> Option<&HighlightedLine> {
+        let chunk_start = index - index % HIGHLIGHT_CHUNK_LINES;
+        self.chunks.get(&chunk_start)?.get(index - chunk_start)
+    }
+
+    /// Highlighted line at `index`, or the unstyled text if not highlighted yet
+    pub fn line_or_plain(&self, index: usize) -> Cow<'_, HighlightedLine> {
+        match self.line(index) {
+            Some(line) => Cow::Borrowed(line),
+            None => Cow::Owned(plain_line(self.lines.get(index).map(String::as_str).unwrap_or(""))),
+        }
     }
 }
 
@@ -212,14 +352,29 @@ use std::sync::OnceLock;
 
 static HIGHLIGHTER: OnceLock<SyntaxHighlighter> = OnceLock::new();
 
+/// Syntax theme used when none is configured
+pub const DEFAULT_SYNTAX_THEME: &str = "base16-ocean.dark";
+
+/// Names of the bundled syntax themes, sorted
+pub fn syntax_theme_names() -> Vec<String> {
+    let mut names: Vec<String> = get_highlighter().theme_set.themes.keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// Whether `name` is a bundled syntax theme
+pub fn is_syntax_theme(name: &str) -> bool {
+    get_highlighter().theme_set.themes.contains_key(name)
+}
+
 /// Get or create the global highlighter instance
 pub fn get_highlighter() -> &'static SyntaxHighlighter {
     HIGHLIGHTER.get_or_init(SyntaxHighlighter::new)
 }
 
 /// Highlight code with the global highlighter
-pub fn highlight_code(content: &str, extension: &str) -> Vec<HighlightedLine> {
-    get_highlighter().highlight(content, extension)
+pub fn highlight_code(content: &str, extension: &str, theme: &str) -> Vec<HighlightedLine> {
+    get_highlighter().highlight(content, extension, theme)
 }
 
 /// Check if extension is supported for highlighting
@@ -236,7 +391,7 @@ mod tests {
         let code = r#"def hello():
     print("Hello, World!")
 "#;
-        let result = highlight_code(code, "py");
+        let result = highlight_code(code, "py", DEFAULT_SYNTAX_THEME);
         assert!(!result.is_empty());
     }
 
@@ -246,21 +401,68 @@ mod tests {
     println!("Hello, World!");
 }
 "#;
-        let result = highlight_code(code, "rs");
+        let result = highlight_code(code, "rs", DEFAULT_SYNTAX_THEME);
         assert!(!result.is_empty());
     }
 
     #[test]
     fn test_json_highlighting() {
         let code = r#"{"key": "value", "number": 42}"#;
-        let result = highlight_code(code, "json");
+        let result = highlight_code(code, "json", DEFAULT_SYNTAX_THEME);
         assert!(!result.is_empty());
     }
 
+    #[test]
+    fn test_syntax_theme_names_and_unknown_theme() {
+        let names = syntax_theme_names();
+        assert!(names.iter().any(|n| n == DEFAULT_SYNTAX_THEME));
+        assert!(names.iter().any(|n| n == "InspiredGitHub"));
+        assert!(is_syntax_theme("InspiredGitHub"));
+        assert!(!is_syntax_theme("no-such-theme"));
+    }
+
+    #[test]
+    fn test_lazy_highlight_matches_full_highlight() {
+        let code: String = (0..HIGHLIGHT_CHUNK_LINES * 3)
+            .map(|i| format!("let x{} = \"{}\"; // line\n", i, i))
+            .collect();
+        let mut lazy = LazyHighlight::new(&code, "rs", DEFAULT_SYNTAX_THEME);
+        assert_eq!(lazy.len(), HIGHLIGHT_CHUNK_LINES * 3);
+        assert!(lazy.line(0).is_some());
+        // Only the first chunk is highlighted up front
+        assert!(lazy.line(HIGHLIGHT_CHUNK_LINES).is_none());
+        assert_eq!(lazy.line_or_plain(HIGHLIGHT_CHUNK_LINES).segments[0].fg, Color::White);
+
+        lazy.ensure_range(HIGHLIGHT_CHUNK_LINES..HIGHLIGHT_CHUNK_LINES * 3);
+        let full = highlight_code(&code, "rs", DEFAULT_SYNTAX_THEME);
+        for (i, expected) in full.iter().enumerate() {
+            let line = lazy.line(i).unwrap();
+            let colors = |l: &HighlightedLine| l.segments.iter().map(|s| (s.text.clone(), s.fg)).collect::<Vec<_>>();
+            assert_eq!(colors(line), colors(expected));
+        }
+    }
+
+    #[test]
+    fn test_lazy_highlight_carries_state_past_skipped_chunks() {
+        // A block comment opened in the first chunk and closed in the third
+        let code = format!("/*\n{}*/\nfn main() {{}}\n", "inside\n".repeat(HIGHLIGHT_CHUNK_LINES * 2));
+        let full = highlight_code(&code, "rs", DEFAULT_SYNTAX_THEME);
+        let mut lazy = LazyHighlight::new(&code, "rs", DEFAULT_SYNTAX_THEME);
+        let last = lazy.len() - 1;
+        // Jump straight past the second chunk
+        lazy.ensure_range(last..last + 1);
+        assert!(lazy.line(HIGHLIGHT_CHUNK_LINES).is_none());
+
+        let colors = |l: &HighlightedLine| l.segments.iter().map(|s| (s.text.clone(), s.fg)).collect::<Vec<_>>();
+        for (i, expected) in full.iter().enumerate().skip(HIGHLIGHT_CHUNK_LINES * 2) {
+            assert_eq!(colors(lazy.line(i).unwrap()), colors(expected), "line {}", i);
+        }
+    }
+
     #[test]
     fn test_unsupported_extension() {
         let code = "some text content";
-        let result = highlight_code(code, "xyz");
+        let result = highlight_code(code, "xyz", DEFAULT_SYNTAX_THEME);
         assert!(!result.is_empty());
         // Should return plain white text
         assert_eq!(result[0].segments[0].fg, Color::White);
@@ -274,7 +476,7 @@ if [ -f "file.txt" ]; then
     cat file.txt
 fi
 "#;
-        let result = highlight_code(code, "sh");
+        let result = highlight_code(code, "sh", DEFAULT_SYNTAX_THEME);
         assert!(!result.is_empty());
         // Should have syntax highlighting (not plain white)
         assert!(is_highlight_supported("sh"));
@@ -290,7 +492,7 @@ if exist "file.txt" (
     type file.txt
 )
 "#;
-        let result = highlight_code(code, "bat");
+        let result = highlight_code(code, "bat", DEFAULT_SYNTAX_THEME);
         assert!(!result.is_empty());
         // Should have syntax highlighting
         assert!(is_highlight_supported("bat"));
@@ -3,9 +3,12 @@
 //! Supports multiple rendering methods:
 //! - ASCII art (universal fallback)
 //! - Unicode block characters (better quality, most modern terminals)
-//! - Sixel (xterm, mlterm, WezTerm)
-//! - Kitty Graphics Protocol (Kitty, WezTerm)
-//! - iTerm2 Inline Images (iTerm2)
+//! - Sixel (xterm, mlterm)
+//! - Kitty Graphics Protocol (Kitty, Ghostty)
+//! - iTerm2 Inline Images (iTerm2, WezTerm)
+//!
+//! Pixel images (Kitty/iTerm2) can't live in ratatui's cell buffer: the viewer
+//! leaves their area blank and the main loop writes the escape sequence on top.
 
 use std::path::Path;
 use std::io::Cursor;
@@ -41,6 +44,8 @@ pub struct ImagePreview {
     pub format: String,
     /// File size in bytes
     pub file_size: u64,
+    /// Pixel image drawn over the viewer (Kitty/iTerm2); `content` is unused then
+    pub inline: Option<InlineImage>,
 }
 
 impl ImagePreview {
@@ -53,6 +58,7 @@ impl ImagePreview {
             method: ImageRenderMethod::Ascii,
             format: "Error".to_string(),
             file_size: 0,
+            inline: None,
         }
     }
     
@@ -136,6 +142,7 @@ pub fn load_image_ascii(path: &Path, max_width: u32, max_height: u32) -> ImagePr
         method: ImageRenderMethod::Ascii,
         format,
         file_size,
+        inline: None,
     }
 }
 
@@ -199,6 +206,7 @@ pub fn load_image_unicode(path: &Path, max_width: u32, max_height: u32) -> Image
         method: ImageRenderMethod::UnicodeBlocks,
         format,
         file_size,
+        inline: None,
     }
 }
 
@@ -355,137 +363,209 @@ pub fn load_image_sixel(path: &Path, max_width: u32, max_height: u32) -> ImagePr
         method: ImageRenderMethod::Sixel,
         format,
         file_size,
+        inline: None,
     }
 }
 
 // ============================================================================
-// Kitty Graphics Protocol Implementation
+// Pixel Graphics (Kitty Graphics Protocol / iTerm2 Inline Images)
 // ============================================================================
 
-/// Encode image using Kitty Graphics Protocol
-/// Sends PNG data as base64 in chunks
-pub fn load_image_kitty(path: &Path, max_width: u32, max_height: u32) -> ImagePreview {
-    let file_size = std::fs::metadata(path)
-        .map(|m| m.len())
-        .unwrap_or(0);
-    
-    let img = match image::open(path) {
-        Ok(img) => img,
-        Err(e) => return ImagePreview::error(&format!("Failed to load image: {}", e)),
-    };
-    
-    let (orig_width, orig_height) = img.dimensions();
-    let format = path.extension()
-        .and_then(|e| e.to_str())
-        .map(|s| s.to_uppercase())
-        .unwrap_or_else(|| "Unknown".to_string());
-    
-    // Resize to fit terminal
-    let resized = img.resize(max_width, max_height, image::imageops::FilterType::Lanczos3);
-    
-    // Encode as PNG
-    let mut png_data = Vec::new();
-    if let Err(e) = resized.write_to(&mut Cursor::new(&mut png_data), ImageFormat::Png) {
-        return ImagePreview::error(&format!("Failed to encode PNG: {}", e));
+/// Terminal protocols that draw real pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphicsProtocol {
+    /// Kitty graphics protocol (Kitty, Ghostty)
+    Kitty,
+    /// iTerm2 inline images (iTerm2, WezTerm)
+    ITerm2,
+}
+
+/// Deletes every visible Kitty image placement
+pub const KITTY_DELETE_ALL: &str = "\x1b_Ga=d,d=A,q=2\x1b\\";
+
+/// Rows of the viewer's image header ("IMAGE PREVIEW", separator, metadata)
+pub const IMAGE_HEADER_LINES: usize = 5;
+
+/// Columns taken by the viewer's line-number gutter plus the two-space indent
+const IMAGE_LEFT_MARGIN: u16 = 9;
+
+/// Largest side kept in memory for pixel rendering
+const INLINE_MAX_PIXELS: u32 = 2048;
+
+/// Cell size used when the terminal doesn't report its pixel size
+const DEFAULT_CELL_PIXELS: (u16, u16) = (8, 16);
+
+/// Where a pixel image goes on screen, in cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InlinePlacement {
+    pub x: u16,
+    pub y: u16,
+    pub cols: u16,
+    /// Rows visible on screen
+    pub rows: u16,
+    /// Rows scrolled off the top
+    pub skip_rows: u16,
+    /// Rows of the whole image
+    pub total_rows: u16,
+}
+
+/// An image drawn with a pixel graphics protocol instead of characters
+#[derive(Clone)]
+pub struct InlineImage {
+    pub protocol: GraphicsProtocol,
+    image: DynamicImage,
+}
+
+impl InlineImage {
+    pub fn new(image: DynamicImage, protocol: GraphicsProtocol) -> Self {
+        let image = if image.width() > INLINE_MAX_PIXELS || image.height() > INLINE_MAX_PIXELS {
+            image.resize(INLINE_MAX_PIXELS, INLINE_MAX_PIXELS, image::imageops::FilterType::Triangle)
+        } else {
+            image
+        };
+        Self { protocol, image }
     }
-    
-    // Base64 encode
-    let b64_data = BASE64.encode(&png_data);
-    
-    // Kitty graphics protocol format:
-    // ESC _ G <control data> ; <payload> ESC \
-    // For PNG: a=T (transmit), f=100 (PNG format), t=d (direct transmission)
-    
-    let mut content = String::new();
-    
-    // Send in chunks of 4096 bytes (Kitty recommendation)
-    let chunk_size = 4096;
-    let chunks: Vec<&str> = b64_data.as_bytes()
-        .chunks(chunk_size)
-        .map(|c| std::str::from_utf8(c).unwrap_or(""))
-        .collect();
-    
-    for (i, chunk) in chunks.iter().enumerate() {
-        let is_last = i == chunks.len() - 1;
-        let more = if is_last { 0 } else { 1 };
-        
-        if i == 0 {
-            // First chunk: include all control parameters
-            content.push_str(&format!(
-                "\x1b_Ga=T,f=100,m={};{}\x1b\\",
-                more, chunk
-            ));
+
+    /// Cells (cols, rows) the image takes when fitted below the header of `area`
+    /// Keeps the aspect ratio and never scales past the image's own size.
+    pub fn fit(&self, area: ratatui::layout::Rect, cell_px: (u16, u16)) -> (u16, u16) {
+        let max_cols = area.width.saturating_sub(IMAGE_LEFT_MARGIN + 1) as f64;
+        let max_rows = (area.height as usize).saturating_sub(IMAGE_HEADER_LINES) as f64;
+        let (cell_w, cell_h) = (cell_px.0.max(1) as f64, cell_px.1.max(1) as f64);
+        let (img_cols, img_rows) = (self.image.width() as f64 / cell_w, self.image.height() as f64 / cell_h);
+
+        let scale = (max_cols / img_cols).min(max_rows / img_rows).min(1.0);
+        let cols = (img_cols * scale).round() as u16;
+        let rows = (img_rows * scale).round() as u16;
+        if cols == 0 || rows == 0 {
+            (0, 0)
         } else {
-            // Subsequent chunks: only m parameter
-            content.push_str(&format!(
-                "\x1b_Gm={};{}\x1b\\",
-                more, chunk
-            ));
+            (cols, rows)
         }
     }
-    
-    ImagePreview {
-        content,
-        width: orig_width,
-        height: orig_height,
-        method: ImageRenderMethod::Kitty,
-        format,
-        file_size,
+
+    /// Placement of the image in the viewer content `area` scrolled by `scroll` lines
+    pub fn placement(&self, area: ratatui::layout::Rect, scroll: usize, cell_px: (u16, u16)) -> Option<InlinePlacement> {
+        let (cols, total_rows) = self.fit(area, cell_px);
+        let header_visible = IMAGE_HEADER_LINES.saturating_sub(scroll) as u16;
+        let skip_rows = scroll.saturating_sub(IMAGE_HEADER_LINES).min(total_rows as usize) as u16;
+        let rows = total_rows - skip_rows;
+        if cols == 0 || rows == 0 {
+            return None;
+        }
+        Some(InlinePlacement {
+            x: area.x + IMAGE_LEFT_MARGIN,
+            y: area.y + header_visible,
+            cols,
+            rows,
+            skip_rows,
+            total_rows,
+        })
+    }
+
+    /// Escape sequence drawing the visible part of the image at the cursor
+    pub fn escape_sequence(&self, placement: &InlinePlacement) -> Option<String> {
+        let (width, height) = self.image.dimensions();
+        let top = (height as u64 * placement.skip_rows as u64 / placement.total_rows.max(1) as u64) as u32;
+        let visible = self.image.crop_imm(0, top, width, height.saturating_sub(top).max(1));
+
+        let mut png_data = Vec::new();
+        visible.write_to(&mut Cursor::new(&mut png_data), ImageFormat::Png).ok()?;
+        let b64_data = BASE64.encode(&png_data);
+
+        Some(match self.protocol {
+            GraphicsProtocol::Kitty => kitty_sequence(&b64_data, placement.cols, placement.rows),
+            // ESC ] 1337 ; File = [arguments] : base64_data BEL
+            GraphicsProtocol::ITerm2 => format!(
+                "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=0:{}\x07",
+                png_data.len(),
+                placement.cols,
+                placement.rows,
+                b64_data
+            ),
+        })
     }
 }
 
-// ============================================================================
-// iTerm2 Inline Images Implementation
-// ============================================================================
+/// Kitty graphics protocol: ESC _ G <control data> ; <payload> ESC \
+/// PNG (f=100) sent in 4096-byte chunks, scaled to `cols` x `rows` cells,
+/// without moving the cursor (C=1) or replying (q=2)
+fn kitty_sequence(b64_data: &str, cols: u16, rows: u16) -> String {
+    let chunks: Vec<&[u8]> = b64_data.as_bytes().chunks(4096).collect();
+    let mut content = String::with_capacity(b64_data.len() + chunks.len() * 16);
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = if i + 1 < chunks.len() { 1 } else { 0 };
+        let chunk = std::str::from_utf8(chunk).unwrap_or("");
+        if i == 0 {
+            content.push_str(&format!("\x1b_Ga=T,f=100,q=2,C=1,c={},r={},m={};{}\x1b\\", cols, rows, more, chunk));
+        } else {
+            content.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+        }
+    }
+    content
+}
 
-/// Encode image using iTerm2 Inline Images Protocol
-pub fn load_image_iterm2(path: &Path, max_width: u32, max_height: u32) -> ImagePreview {
+/// Load an image for pixel rendering with `protocol`
+pub fn load_image_inline(path: &Path, protocol: GraphicsProtocol) -> ImagePreview {
     let file_size = std::fs::metadata(path)
         .map(|m| m.len())
         .unwrap_or(0);
-    
+
     let img = match image::open(path) {
         Ok(img) => img,
         Err(e) => return ImagePreview::error(&format!("Failed to load image: {}", e)),
     };
-    
+
     let (orig_width, orig_height) = img.dimensions();
     let format = path.extension()
         .and_then(|e| e.to_str())
         .map(|s| s.to_uppercase())
         .unwrap_or_else(|| "Unknown".to_string());
-    
-    // Resize to fit terminal
-    let resized = img.resize(max_width, max_height, image::imageops::FilterType::Lanczos3);
-    
-    // Encode as PNG
-    let mut png_data = Vec::new();
-    if let Err(e) = resized.write_to(&mut Cursor::new(&mut png_data), ImageFormat::Png) {
-        return ImagePreview::error(&format!("Failed to encode PNG: {}", e));
-    }
-    
-    // Base64 encode
-    let b64_data = BASE64.encode(&png_data);
-    
-    // iTerm2 inline image format:
-    // ESC ] 1337 ; File = [arguments] : base64_data BEL
-    // Arguments: inline=1 (display inline), size=N (file size), 
-    //            width=N, height=N (in cells or px/%)
-    
-    let content = format!(
-        "\x1b]1337;File=inline=1;size={}:{}\x07",
-        png_data.len(),
-        b64_data
-    );
-    
+
     ImagePreview {
-        content,
+        content: String::new(),
         width: orig_width,
         height: orig_height,
-        method: ImageRenderMethod::ITerm2,
+        method: match protocol {
+            GraphicsProtocol::Kitty => ImageRenderMethod::Kitty,
+            GraphicsProtocol::ITerm2 => ImageRenderMethod::ITerm2,
+        },
         format,
         file_size,
+        inline: Some(InlineImage::new(img, protocol)),
+    }
+}
+
+/// Terminal cell size in pixels, as reported by the terminal (or a common default)
+pub fn cell_pixel_size() -> (u16, u16) {
+    match crossterm::terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => {
+            (size.width / size.columns, size.height / size.rows)
+        },
+        _ => DEFAULT_CELL_PIXELS,
+    }
+}
+
+/// Detect a pixel graphics protocol from the environment
+pub fn detect_graphics_protocol() -> Option<GraphicsProtocol> {
+    graphics_protocol_from_env(|name| std::env::var(name).ok())
+}
+
+fn graphics_protocol_from_env(var: impl Fn(&str) -> Option<String>) -> Option<GraphicsProtocol> {
+    // Multiplexers don't pass the escape sequences through
+    if var("TMUX").is_some() || var("STY").is_some() {
+        return None;
+    }
+
+    let term = var("TERM").unwrap_or_default();
+    let term_program = var("TERM_PROGRAM").unwrap_or_default();
+    if var("KITTY_WINDOW_ID").is_some() || term.contains("kitty") || term.contains("ghostty") || term_program == "ghostty" {
+        return Some(GraphicsProtocol::Kitty);
     }
+    if term_program == "iTerm.app" || term_program == "WezTerm" || var("LC_TERMINAL").as_deref() == Some("iTerm2") {
+        return Some(GraphicsProtocol::ITerm2);
+    }
+    None
 }
 
 /// Check if terminal supports true color
@@ -510,22 +590,16 @@ pub fn supports_true_color() -> bool {
 
 /// Detect best image rendering method for current terminal
 pub fn detect_render_method() -> ImageRenderMethod {
-    // Check for Kitty terminal
-    if std::env::var("KITTY_WINDOW_ID").is_ok() {
-        return ImageRenderMethod::Kitty;
-    }
-    
-    // Check TERM_PROGRAM for known terminals
-    if let Ok(term_program) = std::env::var("TERM_PROGRAM") {
-        match term_program.as_str() {
-            // iTerm2 uses its own inline images protocol
-            "iTerm.app" => return ImageRenderMethod::ITerm2,
-            // WezTerm supports both Sixel and Kitty, prefer Sixel
-            "WezTerm" => return ImageRenderMethod::Sixel,
-            // mlterm supports Sixel
-            "mlterm" => return ImageRenderMethod::Sixel,
-            _ => {}
-        }
+    // Real pixels where the terminal supports them
+    match detect_graphics_protocol() {
+        Some(GraphicsProtocol::Kitty) => return ImageRenderMethod::Kitty,
+        Some(GraphicsProtocol::ITerm2) => return ImageRenderMethod::ITerm2,
+        None => {}
+    }
+    
+    // mlterm supports Sixel
+    if std::env::var("TERM_PROGRAM").is_ok_and(|p| p == "mlterm") {
+        return ImageRenderMethod::Sixel;
     }
     
     // Check TERM for xterm with Sixel support
@@ -553,8 +627,8 @@ pub fn load_image_auto(path: &Path, max_width: u32, max_height: u32) -> ImagePre
         ImageRenderMethod::Ascii => load_image_ascii(path, max_width, max_height),
         ImageRenderMethod::UnicodeBlocks => load_image_unicode(path, max_width, max_height),
         ImageRenderMethod::Sixel => load_image_sixel(path, max_width, max_height),
-        ImageRenderMethod::Kitty => load_image_kitty(path, max_width, max_height),
-        ImageRenderMethod::ITerm2 => load_image_iterm2(path, max_width, max_height),
+        ImageRenderMethod::Kitty => load_image_inline(path, GraphicsProtocol::Kitty),
+        ImageRenderMethod::ITerm2 => load_image_inline(path, GraphicsProtocol::ITerm2),
     }
 }
 
@@ -568,6 +642,37 @@ mod tests {
         assert_eq!(gray_to_ascii(255), '@');
     }
     
+    #[test]
+    fn test_graphics_protocol_from_env() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string())
+        };
+        assert_eq!(graphics_protocol_from_env(env(&[("TERM", "xterm-kitty")])), Some(GraphicsProtocol::Kitty));
+        assert_eq!(graphics_protocol_from_env(env(&[("TERM_PROGRAM", "iTerm.app")])), Some(GraphicsProtocol::ITerm2));
+        assert_eq!(graphics_protocol_from_env(env(&[("TERM", "xterm-256color")])), None);
+        // Not through tmux
+        assert_eq!(graphics_protocol_from_env(env(&[("TERM", "xterm-kitty"), ("TMUX", "/tmp/tmux")])), None);
+    }
+
+    #[test]
+    fn test_inline_image_placement() {
+        let image = InlineImage::new(DynamicImage::new_rgb8(800, 400), GraphicsProtocol::Kitty);
+        let area = ratatui::layout::Rect { x: 1, y: 3, width: 110, height: 45 };
+        // 800x400 px at 8x16 px cells: 100x25 cells, fits without scaling
+        assert_eq!(image.fit(area, (8, 16)), (100, 25));
+
+        let top = image.placement(area, 0, (8, 16)).unwrap();
+        assert_eq!((top.x, top.y, top.rows, top.skip_rows), (10, 8, 25, 0));
+
+        // Scrolled past the header and 5 image rows
+        let scrolled = image.placement(area, IMAGE_HEADER_LINES + 5, (8, 16)).unwrap();
+        assert_eq!((scrolled.y, scrolled.rows, scrolled.skip_rows), (3, 20, 5));
+        let sequence = image.escape_sequence(&scrolled).unwrap();
+        assert!(sequence.starts_with("\x1b_Ga=T,f=100,q=2,C=1,c=100,r=20,"));
+
+        assert!(image.placement(area, IMAGE_HEADER_LINES + 25, (8, 16)).is_none());
+    }
+
     #[test]
     fn test_supports_true_color() {
         // Should not panic
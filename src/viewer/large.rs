@@ -0,0 +1,307 @@
+//! Windowed viewing of text files too large to load
+//!
+//! The file is indexed on a worker thread by scanning for line breaks, keeping
+//! only a sparse set of line start offsets. Drawing seeks to the nearest
+//! recorded line and reads just the visible lines, each capped at
+//! `MAX_LINE_BYTES`, so memory use doesn't grow with the file.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+
+/// A line start is recorded every this many lines...
+const CHECKPOINT_LINES: usize = 1024;
+/// ...and after every line that ends this far past the last record (long lines)
+const CHECKPOINT_BYTES: u64 = 1024 * 1024;
+
+/// Bytes of a line shown; longer lines are cut off with an ellipsis
+pub const MAX_LINE_BYTES: usize = 4096;
+
+/// Bytes indexed between progress updates
+pub const INDEX_STEP_BYTES: u64 = 32 * 1024 * 1024;
+
+/// A text file read a window at a time
+#[derive(Debug, Clone)]
+pub struct LargeFile {
+    pub path: PathBuf,
+    pub len: u64,                   // Size when opened
+    checkpoints: Vec<(usize, u64)>, // (line, byte offset of its start), ascending
+    newlines: usize,                // Line breaks found so far
+    scanned: u64,                   // Bytes indexed so far
+    ends_with_newline: bool,        // The last indexed byte is a line break
+}
+
+/// Whether the start of `path` looks like UTF-8 text
+pub fn looks_like_text(path: &Path) -> bool {
+    let mut sample = Vec::new();
+    if File::open(path).and_then(|f| f.take(8192).read_to_end(&mut sample)).is_err() {
+        return false;
+    }
+    let text = match std::str::from_utf8(&sample) {
+        Ok(text) => text,
+        // A multi-byte character cut off by the sample size is fine
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&sample[..e.valid_up_to()]).unwrap_or_default(),
+        Err(_) => return false,
+    };
+    !super::is_likely_binary(text)
+}
+
+impl LargeFile {
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let len = std::fs::metadata(path)?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            len,
+            checkpoints: vec![(0, 0)],
+            newlines: 0,
+            scanned: 0,
+            ends_with_newline: false,
+        })
+    }
+
+    pub fn is_indexed(&self) -> bool {
+        self.scanned >= self.len
+    }
+
+    /// Percentage of the file indexed
+    pub fn indexed_percent(&self) -> u64 {
+        (self.scanned * 100).checked_div(self.len).unwrap_or(100)
+    }
+
+    /// Lines found so far (all of them once indexed), counting a last line without a line break
+    pub fn line_count(&self) -> usize {
+        self.newlines + usize::from(self.scanned > 0 && !self.ends_with_newline)
+    }
+
+    /// Index up to `budget` more bytes
+    pub fn index_more(&mut self, budget: u64) -> std::io::Result<()> {
+        let target = self.len.min(self.scanned.saturating_add(budget));
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.scanned))?;
+        let mut reader = file.take(target - self.scanned);
+        let mut buf = vec![0; 256 * 1024];
+        loop {
+            let n = reader.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            for (i, &byte) in buf[..n].iter().enumerate() {
+                if byte != b'\n' {
+                    continue;
+                }
+                self.newlines += 1;
+                let start = self.scanned + i as u64 + 1;
+                let last = self.checkpoints.last().map_or(0, |&(_, offset)| offset);
+                if self.newlines.is_multiple_of(CHECKPOINT_LINES) || start - last >= CHECKPOINT_BYTES {
+                    self.checkpoints.push((self.newlines, start));
+                }
+            }
+            self.ends_with_newline = buf[n - 1] == b'\n';
+            self.scanned += n as u64;
+        }
+        // The file shrank since it was opened: what's there is all of it
+        if self.scanned < target {
+            self.len = self.scanned;
+        }
+        Ok(())
+    }
+
+    /// Up to `count` lines from line `start` (0-based), without line breaks
+    pub fn read_lines(&self, start: usize, count: usize) -> std::io::Result<Vec<String>> {
+        // checkpoints[0] is line 0, so there's always one at or before `start`
+        let index = self.checkpoints.partition_point(|&(line, _)| line <= start) - 1;
+        let (mut line, offset) = self.checkpoints[index];
+        let mut reader = BufReader::with_capacity(64 * 1024, File::open(&self.path)?);
+        reader.seek(SeekFrom::Start(offset))?;
+        while line < start {
+            if !skip_line(&mut reader)? {
+                return Ok(Vec::new());
+            }
+            line += 1;
+        }
+
+        let mut lines = Vec::new();
+        for line in start..start + count {
+            // A recorded start of the next line saves scanning the rest of a long one
+            let next = self.checkpoints
+                .binary_search_by_key(&(line + 1), |&(line, _)| line)
+                .ok()
+                .map(|i| self.checkpoints[i].1);
+            match read_capped_line(&mut reader, next.is_none())? {
+                Some(text) => lines.push(text),
+                None => break,
+            }
+            if let Some(offset) = next {
+                reader.seek(SeekFrom::Start(offset))?;
+            }
+        }
+        Ok(lines)
+    }
+}
+
+/// Indexes a `LargeFile` on a worker thread, sending a copy after each step
+/// Dropping the job stops the worker at its next step.
+pub struct IndexJob {
+    receiver: Receiver<std::io::Result<LargeFile>>,
+}
+
+impl IndexJob {
+    pub fn start(mut file: LargeFile) -> Self {
+        let (tx, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            while !file.is_indexed() {
+                let step = file.index_more(INDEX_STEP_BYTES).map(|_| file.clone());
+                let failed = step.is_err();
+                if tx.send(step).is_err() || failed {
+                    return;
+                }
+            }
+        });
+        Self { receiver }
+    }
+
+    /// The latest progress sent since the last poll
+    pub fn poll(&self) -> Option<std::io::Result<LargeFile>> {
+        self.receiver.try_iter().last()
+    }
+}
+
+/// Move past the next line break; false at the end of the file
+fn skip_line(reader: &mut impl BufRead) -> std::io::Result<bool> {
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(false);
+        }
+        match buf.iter().position(|&b| b == b'\n') {
+            Some(i) => {
+                reader.consume(i + 1);
+                return Ok(true);
+            },
+            None => {
+                let n = buf.len();
+                reader.consume(n);
+            },
+        }
+    }
+}
+
+/// Read a line, keeping its first `MAX_LINE_BYTES`; None at the end of the file
+/// Unless `scan_rest`, reading stops at the cut and the caller seeks to the next line.
+fn read_capped_line(reader: &mut impl BufRead, scan_rest: bool) -> std::io::Result<Option<String>> {
+    let mut bytes = Vec::new();
+    let mut cut = false;
+    let mut read_any = false;
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        read_any = true;
+        let (end, found) = match buf.iter().position(|&b| b == b'\n') {
+            Some(i) => (i, true),
+            None => (buf.len(), false),
+        };
+        let room = MAX_LINE_BYTES - bytes.len();
+        bytes.extend_from_slice(&buf[..end.min(room)]);
+        cut |= end > room;
+        reader.consume(end + usize::from(found));
+        if found || (cut && !scan_rest) {
+            break;
+        }
+    }
+    if !read_any {
+        return Ok(None);
+    }
+
+    if bytes.last() == Some(&b'\r') {
+        bytes.pop();
+    }
+    if cut {
+        // Don't show a character split by the cut as garbage
+        if let Err(e) = std::str::from_utf8(&bytes) {
+            if e.error_len().is_none() {
+                bytes.truncate(e.valid_up_to());
+            }
+        }
+    }
+    let mut text = String::from_utf8_lossy(&bytes).into_owned();
+    if cut {
+        text.push('…');
+    }
+    Ok(Some(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_index_and_read_window() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("big.log");
+        let text: String = (0..5000).map(|i| format!("line {}\n", i)).collect::<String>() + "no newline";
+        std::fs::write(&path, &text).unwrap();
+
+        let mut file = LargeFile::open(&path).unwrap();
+        file.index_more(1000).unwrap();
+        assert!(!file.is_indexed());
+        file.index_more(u64::MAX).unwrap();
+        assert_eq!(file.line_count(), 5001);
+        assert_eq!(file.indexed_percent(), 100);
+
+        assert_eq!(file.read_lines(0, 2).unwrap(), vec!["line 0", "line 1"]);
+        // Across a checkpoint, and past the end
+        assert_eq!(file.read_lines(1023, 2).unwrap(), vec!["line 1023", "line 1024"]);
+        assert_eq!(file.read_lines(4999, 5).unwrap(), vec!["line 4999", "no newline"]);
+        assert!(file.read_lines(6000, 5).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_index_job_finishes_the_index() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("big.log");
+        std::fs::write(&path, "line\n".repeat(3000)).unwrap();
+
+        let job = IndexJob::start(LargeFile::open(&path).unwrap());
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        let file = loop {
+            match job.poll() {
+                Some(Ok(file)) if file.is_indexed() => break file,
+                _ if std::time::Instant::now() > deadline => panic!("indexing didn't finish"),
+                _ => std::thread::sleep(std::time::Duration::from_millis(10)),
+            }
+        };
+        assert_eq!(file.line_count(), 3000);
+        assert_eq!(file.read_lines(2999, 2).unwrap(), vec!["line"]);
+    }
+
+    #[test]
+    fn test_long_lines_are_cut() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("long.txt");
+        // The cut falls inside a two-byte character
+        let long = "a".to_string() + &"é".repeat(CHECKPOINT_BYTES as usize);
+        std::fs::write(&path, format!("{}\r\nshort\n{}\nlast\n", long, long)).unwrap();
+
+        let mut file = LargeFile::open(&path).unwrap();
+        file.index_more(u64::MAX).unwrap();
+        assert_eq!(file.line_count(), 4);
+        let lines = file.read_lines(0, 4).unwrap();
+        assert_eq!(lines[0], "a".to_string() + &"é".repeat(MAX_LINE_BYTES / 2 - 1) + "…");
+        assert_eq!(&lines[1..], ["short".to_string(), lines[0].clone(), "last".to_string()]);
+        assert_eq!(file.read_lines(3, 1).unwrap(), vec!["last"]);
+    }
+
+    #[test]
+    fn test_looks_like_text() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("data");
+        std::fs::write(&path, "plain text\n").unwrap();
+        assert!(looks_like_text(&path));
+        std::fs::write(&path, [0u8, 1, 2, 0, 0, 3]).unwrap();
+        assert!(!looks_like_text(&path));
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -5,76 +6,60 @@ pub mod text;
 pub mod editor;
 pub mod highlight;
 pub mod image;
+pub mod archive;
+pub mod search;
+pub mod diff;
+pub mod csv;
+pub mod follow;
+pub mod hex;
+pub mod large;
+pub mod encoding;
+pub mod stats;
+pub mod preview;
+pub mod url;
 
 pub use editor::{TextEditor, VimMode, EditorStyle};
-pub use highlight::{HighlightedLine, highlight_code, is_highlight_supported};
+pub use highlight::{HighlightedLine, LazyHighlight, highlight_code, is_highlight_supported};
 pub use image::{ImagePreview, load_image_auto};
+pub use archive::{ArchiveEntry, archive_kind, format_archive_listing};
+pub use diff::DiffView;
+pub use self::csv::TableView;
+pub use self::encoding::TextEncoding;
+
+/// Default largest text file loaded whole (max_text_preview_bytes); larger ones open windowed
+pub const DEFAULT_MAX_TEXT_PREVIEW: u64 = 1024 * 1024;
+
+/// Default largest file shown (and editable) in the hex view (max_binary_preview_bytes)
+pub const DEFAULT_MAX_BINARY_PREVIEW: u64 = 5 * 1024 * 1024;
+
+/// Size limits for previews
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreviewLimits {
+    pub text_bytes: u64,   // Larger text files are read a window at a time, larger binary ones shown in hex
+    pub binary_bytes: u64, // Larger files aren't previewed at all
+    pub csv_rows: usize,   // Data rows kept from a CSV/TSV file
+}
 
-/// Format binary data as hex view
-pub fn format_hex_view(data: &[u8], truncated: bool) -> String {
-    // Estimate capacity to avoid reallocations
-    // Each 16 bytes line is ~85 chars. Ratio ~5.3. Using 6x to be safe.
-    let estimated_capacity = data.len() * 6 + 1024;
-    let mut output = String::with_capacity(estimated_capacity);
-    
-    // Header
-    output.push_str("╔══════════════════════════════════════════════════════════════════════════════════╗\n");
-    output.push_str("║                              HEX VIEWER                                          ║\n");
-    output.push_str("╠══════════════════════════════════════════════════════════════════════════════════╣\n");
-    
-    if truncated {
-        output.push_str("║ NOTE: File is larger than 100MB. Showing first 1MB only.                        ║\n");
-        output.push_str("╠══════════════════════════════════════════════════════════════════════════════════╣\n");
-    }
-    
-    output.push_str("║ Offset    00 01 02 03 04 05 06 07  08 09 0A 0B 0C 0D 0E 0F  ASCII                ║\n");
-    output.push_str("╠══════════════════════════════════════════════════════════════════════════════════╣\n");
-    
-    // Process data in 16-byte chunks
-    for (offset, chunk) in data.chunks(16).enumerate() {
-        let address = offset * 16;
-        output.push_str(&format!("║ {:08X}  ", address));
-        
-        // Hex values (first 8 bytes)
-        for i in 0..8 {
-            if i < chunk.len() {
-                output.push_str(&format!("{:02X} ", chunk[i]));
-            } else {
-                output.push_str("   ");
-            }
-        }
-        output.push(' ');
-        
-        // Hex values (next 8 bytes)
-        for i in 8..16 {
-            if i < chunk.len() {
-                output.push_str(&format!("{:02X} ", chunk[i]));
-            } else {
-                output.push_str("   ");
-            }
+impl Default for PreviewLimits {
+    fn default() -> Self {
+        Self {
+            text_bytes: DEFAULT_MAX_TEXT_PREVIEW,
+            binary_bytes: DEFAULT_MAX_BINARY_PREVIEW,
+            csv_rows: self::csv::DEFAULT_MAX_ROWS,
         }
-        output.push(' ');
-        
-        // ASCII representation
-        for &byte in chunk {
-            let ch = if byte >= 0x20 && byte <= 0x7E {
-                byte as char
-            } else {
-                '.'
-            };
-            output.push(ch);
-        }
-        
-        // Padding for incomplete lines
-        for _ in chunk.len()..16 {
-            output.push(' ');
-        }
-        
-        output.push_str(" ║\n");
     }
-    
-    output.push_str("╚══════════════════════════════════════════════════════════════════════════════════╝\n");
-    output
+}
+
+/// A size limit for messages and settings ("512 KB", "5 MB")
+pub fn format_limit(bytes: u64) -> String {
+    const MB: u64 = 1024 * 1024;
+    if bytes >= MB && bytes.is_multiple_of(MB) {
+        format!("{} MB", bytes / MB)
+    } else if bytes >= MB {
+        format!("{:.1} MB", bytes as f64 / MB as f64)
+    } else {
+        format!("{} KB", bytes / 1024)
+    }
 }
 
 /// Format JSON with pretty-printing
@@ -96,16 +81,24 @@ pub fn format_json(content: &str) -> String {
 #[derive(Clone)]
 pub enum ViewerContent {
     PlainText(String),
-    HighlightedCode { raw: String, highlighted: Vec<HighlightedLine> },
+    HighlightedCode { raw: String, highlighted: LazyHighlight },
     Markdown(String),
     Image(PathBuf), // Store path to image file (legacy, for metadata display)
     ImagePreviewContent(ImagePreview), // Rendered image preview
     HexView(Vec<u8>, bool), // Binary data and whether it was truncated
+    LargeFile(large::LargeFile), // Text too large to load, read a window at a time
+    ArchiveListing(Vec<ArchiveEntry>, bool), // Archive entries and whether the list was capped
+    Diff(DiffView), // Unified diff of two files
+    Table(TableView), // CSV/TSV rows
     Error(String),
 }
 
 /// Check if a file type is supported for preview
 pub fn is_supported_file_type(path: &Path) -> bool {
+    if archive_kind(path).is_some() || self::csv::is_delimited_file(path) {
+        return true;
+    }
+
     let extension = path.extension()
         .and_then(|e| e.to_str())
         .map(|s| s.to_lowercase());
@@ -158,7 +151,7 @@ pub fn is_supported_file_type(path: &Path) -> bool {
         Some("docx") | Some("xlsx") | Some("xls") => true,
         _ => {
             // Try to read as text to see if it's a text file
-            fs::read_to_string(path).is_ok()
+            read_text(path, None).is_ok()
         }
     }
 }
@@ -188,12 +181,111 @@ fn get_extension_for_special_file(path: &Path) -> Option<&'static str> {
     }
 }
 
+/// Load a file using built-in extension detection only
+#[allow(dead_code)]
 pub fn load_file(path: &Path) -> ViewerContent {
+    load_file_with_overrides(path, &HashMap::new(), highlight::DEFAULT_SYNTAX_THEME, PreviewLimits::default(), None)
+}
+
+/// Read a text file as `encoding`, or as UTF-8 falling back to a detected legacy encoding
+pub fn read_text(path: &Path, encoding: Option<&'static encoding_rs::Encoding>) -> std::io::Result<String> {
+    read_text_encoded(path, encoding).map(|(text, _)| text)
+}
+
+/// `read_text`, also telling how the file was encoded
+pub fn read_text_encoded(path: &Path, encoding: Option<&'static encoding_rs::Encoding>) -> std::io::Result<(String, TextEncoding)> {
+    let bytes = fs::read(path)?;
+    match encoding {
+        Some(encoding) => Ok(self::encoding::decode_as(&bytes, encoding)),
+        None => self::encoding::decode(&bytes)
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "not text in a known encoding")),
+    }
+}
+
+/// Look up a user syntax override (extension -> highlighter language) for a path
+fn syntax_override_for(path: &Path, overrides: &HashMap<String, String>) -> Option<String> {
+    if overrides.is_empty() {
+        return None;
+    }
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    let language = overrides
+        .iter()
+        .find(|(ext, _)| ext.trim_start_matches('.').to_lowercase() == extension)
+        .map(|(_, lang)| lang.to_lowercase())?;
+
+    if is_highlight_supported(&language) {
+        Some(language)
+    } else {
+        // Unknown target language: fall back to built-in detection
+        tracing::warn!("Unknown syntax override '{}' for .{} files", language, extension);
+        None
+    }
+}
+
+/// Load a file, consulting user syntax overrides before built-in extension matching
+/// Code is colored with `syntax_theme`.
+/// Text above `limits.text_bytes` opens windowed; other files of unknown type go to the hex view.
+/// Text is decoded as `encoding` if given, otherwise as UTF-8 or a detected legacy encoding.
+pub fn load_file_with_overrides(
+    path: &Path,
+    overrides: &HashMap<String, String>,
+    syntax_theme: &str,
+    limits: PreviewLimits,
+    encoding: Option<&'static encoding_rs::Encoding>,
+) -> ViewerContent {
+    load_file_with_encoding(path, overrides, syntax_theme, limits, encoding).0
+}
+
+/// `load_file_with_overrides`, also telling how the text was encoded (None when no text was read)
+pub fn load_file_with_encoding(
+    path: &Path,
+    overrides: &HashMap<String, String>,
+    syntax_theme: &str,
+    limits: PreviewLimits,
+    encoding: Option<&'static encoding_rs::Encoding>,
+) -> (ViewerContent, Option<TextEncoding>) {
+    let found = std::cell::Cell::new(None);
+    let read = |path: &Path| {
+        read_text_encoded(path, encoding).map(|(text, text_encoding)| {
+            found.set(Some(text_encoding));
+            text
+        })
+    };
+    let content = load_content(path, overrides, syntax_theme, limits, &read);
+    (content, found.get())
+}
+
+fn load_content(
+    path: &Path,
+    overrides: &HashMap<String, String>,
+    syntax_theme: &str,
+    limits: PreviewLimits,
+    read_text: &dyn Fn(&Path) -> std::io::Result<String>,
+) -> ViewerContent {
+    if let Some(language) = syntax_override_for(path, overrides) {
+        return match read_text(path) {
+            Ok(content) => {
+                let highlighted = LazyHighlight::new(&content, &language, syntax_theme);
+                ViewerContent::HighlightedCode { raw: content, highlighted }
+            },
+            Err(e) => ViewerContent::Error(format!("Failed to read file: {}", e)),
+        };
+    }
+
+    // Archives are matched on the full name (.tar.gz has two extensions)
+    if archive_kind(path).is_some() {
+        return archive::load_archive(path);
+    }
+
+    if self::csv::is_delimited_file(path) {
+        return self::csv::load_table(path, limits.csv_rows);
+    }
+
     // Check for special filenames first (Makefile, Dockerfile, etc.)
     if let Some(ext) = get_extension_for_special_file(path) {
-        match fs::read_to_string(path) {
+        match read_text(path) {
             Ok(content) => {
-                let highlighted = highlight_code(&content, ext);
+                let highlighted = LazyHighlight::new(&content, ext, syntax_theme);
                 return ViewerContent::HighlightedCode { raw: content, highlighted };
             },
             Err(e) => return ViewerContent::Error(format!("Failed to read file: {}", e)),
@@ -225,18 +317,18 @@ pub fn load_file(path: &Path) -> ViewerContent {
             }
         },
         Some("md") | Some("markdown") => {
-            match fs::read_to_string(path) {
+            match read_text(path) {
                 Ok(content) => ViewerContent::Markdown(content),
                 Err(e) => ViewerContent::Error(format!("Failed to read file: {}", e)),
             }
         },
         // JSON files - auto format and syntax highlight
         Some("json") => {
-            match fs::read_to_string(path) {
+            match read_text(path) {
                 Ok(content) => {
                     // Try to parse and pretty-print JSON
                     let formatted = format_json(&content);
-                    let highlighted = highlight_code(&formatted, "json");
+                    let highlighted = LazyHighlight::new(&formatted, "json", syntax_theme);
                     ViewerContent::HighlightedCode { raw: formatted, highlighted }
                 },
                 Err(e) => ViewerContent::Error(format!("Failed to read file: {}", e)),
@@ -244,9 +336,9 @@ pub fn load_file(path: &Path) -> ViewerContent {
         },
         // Code files with syntax highlighting support
         Some(ext) if is_highlight_supported(ext) => {
-            match fs::read_to_string(path) {
+            match read_text(path) {
                 Ok(content) => {
-                    let highlighted = highlight_code(&content, ext);
+                    let highlighted = LazyHighlight::new(&content, ext, syntax_theme);
                     ViewerContent::HighlightedCode { raw: content, highlighted }
                 },
                 Err(e) => ViewerContent::Error(format!("Failed to read file: {}", e)),
@@ -254,17 +346,17 @@ pub fn load_file(path: &Path) -> ViewerContent {
         },
         // Plain text files without highlighting
         Some("txt") | Some("log") => {
-            match fs::read_to_string(path) {
+            match read_text(path) {
                 Ok(content) => ViewerContent::PlainText(content),
                 Err(e) => ViewerContent::Error(format!("Failed to read file: {}", e)),
             }
         },
         // Config files - try to apply INI highlighting
         Some("conf") | Some("cfg") | Some("ini") => {
-            match fs::read_to_string(path) {
+            match read_text(path) {
                 Ok(content) => {
                     if is_highlight_supported("ini") {
-                        let highlighted = highlight_code(&content, "ini");
+                        let highlighted = LazyHighlight::new(&content, "ini", syntax_theme);
                         ViewerContent::HighlightedCode { raw: content, highlighted }
                     } else {
                         ViewerContent::PlainText(content)
@@ -290,31 +382,33 @@ pub fn load_file(path: &Path) -> ViewerContent {
             };
             
             let file_size = metadata.len();
-            const MAX_PLAIN_TEXT_SIZE: u64 = 1024 * 1024; // 1MB
-            const MAX_BINARY_SIZE: u64 = 5 * 1024 * 1024; // 5MB for binary/hex view
             
-            // For files larger than 1MB, try binary view directly
-            if file_size > MAX_PLAIN_TEXT_SIZE {
-                if file_size > MAX_BINARY_SIZE {
+            // Text above the text limit is read a window at a time; binary goes to the hex view
+            if file_size > limits.text_bytes {
+                if let Some(content) = load_large_text(path) {
+                    return content;
+                }
+                if file_size > limits.binary_bytes {
                     return ViewerContent::Error(format!(
-                        "File is too large to preview ({:.2} MB).\n\nMaximum size for hex view: 5MB\nUse an external viewer for larger files.",
-                        file_size as f64 / (1024.0 * 1024.0)
+                        "File is too large to preview ({:.2} MB).\n\nMaximum size for hex view: {} (max_binary_preview_bytes)\nUse an external viewer for larger files.",
+                        file_size as f64 / (1024.0 * 1024.0),
+                        format_limit(limits.binary_bytes)
                     ));
                 }
                 // Load as binary hex view
-                return load_binary_file(path);
+                return load_binary_file(path, limits.binary_bytes);
             }
             
-            // Try to read as text (1MB or less)
-            match fs::read_to_string(path) {
+            // Try to read as text (within the text limit)
+            match read_text(path) {
                 Ok(content) => {
                     // Check if content looks like binary (contains null bytes or too many non-printable chars)
                     if is_likely_binary(&content) {
                         // Load as hex view instead of error
-                        load_binary_file(path)
+                        load_binary_file(path, limits.binary_bytes)
                     } else if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
                         if is_highlight_supported(extension) {
-                            let highlighted = highlight_code(&content, extension);
+                            let highlighted = LazyHighlight::new(&content, extension, syntax_theme);
                             ViewerContent::HighlightedCode { raw: content, highlighted }
                         } else {
                             ViewerContent::PlainText(content)
@@ -325,21 +419,57 @@ pub fn load_file(path: &Path) -> ViewerContent {
                 },
                 Err(_) => {
                     // Failed to read as text - try as binary hex view
-                    load_binary_file(path)
+                    load_binary_file(path, limits.binary_bytes)
                 }
             }
         }
     }
 }
 
+/// Target of the viewer's ':' command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineJump {
+    Line(usize), // 0-based
+    End,
+}
+
+/// Parse ":N" (line N, 1-based) or ":$" (last line); anything else is ignored
+pub fn parse_line_jump(input: &str) -> Option<LineJump> {
+    let input = input.trim();
+    if input == "$" {
+        return Some(LineJump::End);
+    }
+    input.parse::<usize>().ok().map(|n| LineJump::Line(n.saturating_sub(1)))
+}
+
+/// Replace tabs with spaces up to the next multiple of `tab_width`
+/// `start` is the display column `text` begins at, so the pieces of a split line stay aligned.
+pub fn expand_tabs(text: &str, start: usize, tab_width: usize) -> String {
+    let tab_width = tab_width.max(1);
+    let mut col = start;
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == '\t' {
+            let spaces = tab_width - col % tab_width;
+            out.extend(std::iter::repeat_n(' ', spaces));
+            col += spaces;
+        } else {
+            out.push(c);
+            col += 1;
+        }
+    }
+    out
+}
+
 /// Check if content is likely binary (contains null bytes or too many control characters)
-fn is_likely_binary(content: &str) -> bool {
+pub(crate) fn is_likely_binary(content: &str) -> bool {
     if content.is_empty() {
         return false;
     }
     
     // Check first 8KB for binary indicators
-    let sample = &content[..content.len().min(8192)];
+    let end = (0..=content.len().min(8192)).rev().find(|&i| content.is_char_boundary(i)).unwrap_or(0);
+    let sample = &content[..end];
     let mut control_chars = 0;
     
     for ch in sample.chars() {
@@ -358,12 +488,21 @@ fn is_likely_binary(content: &str) -> bool {
     ratio > 0.1
 }
 
-fn load_binary_file(path: &Path) -> ViewerContent {
+/// Open `path` windowed if it's text
+fn load_large_text(path: &Path) -> Option<ViewerContent> {
+    if !large::looks_like_text(path) {
+        return None;
+    }
+    Some(match large::LargeFile::open(path) {
+        Ok(file) => ViewerContent::LargeFile(file),
+        Err(e) => ViewerContent::Error(format!("Failed to open file: {}", e)),
+    })
+}
+
+fn load_binary_file(path: &Path, max_bytes: u64) -> ViewerContent {
     use std::fs::File;
     use std::io::Read;
 
-    const MAX_BINARY_VIEW_SIZE: u64 = 5 * 1024 * 1024; // 5MB max for hex view
-
     let metadata = match fs::metadata(path) {
         Ok(m) => m,
         Err(e) => return ViewerContent::Error(format!("Failed to get metadata: {}", e)),
@@ -371,11 +510,12 @@ fn load_binary_file(path: &Path) -> ViewerContent {
 
     let file_size = metadata.len();
     
-    // Check 5MB limit for binary view
-    if file_size > MAX_BINARY_VIEW_SIZE {
+    // Check the configured limit for binary view
+    if file_size > max_bytes {
         return ViewerContent::Error(format!(
-            "Binary file is too large to preview ({:.2} MB).\n\nMaximum size for hex view: 5MB\nUse an external hex editor for larger files.",
-            file_size as f64 / (1024.0 * 1024.0)
+            "Binary file is too large to preview ({:.2} MB).\n\nMaximum size for hex view: {} (max_binary_preview_bytes)\nUse an external hex editor for larger files.",
+            file_size as f64 / (1024.0 * 1024.0),
+            format_limit(max_bytes)
         ));
     }
 
@@ -387,7 +527,7 @@ fn load_binary_file(path: &Path) -> ViewerContent {
     let mut handle = file.take(file_size);
     let mut buffer = Vec::new();
     match handle.read_to_end(&mut buffer) {
-        Ok(_) => ViewerContent::HexView(buffer, false), // Not truncated since we enforce 5MB limit
+        Ok(_) => ViewerContent::HexView(buffer, false), // Not truncated since we enforce the size limit
         Err(e) => ViewerContent::Error(format!("Failed to read binary file: {}", e)),
     }
 }
@@ -399,38 +539,22 @@ mod tests {
     use std::io::Write;
 
     #[test]
-    fn test_format_hex_view_empty() {
-        let data: &[u8] = &[];
-        let output = format_hex_view(data, false);
-        
-        assert!(output.contains("HEX VIEWER"));
-        assert!(output.contains("Offset"));
+    fn test_expand_tabs() {
+        assert_eq!(expand_tabs("\tx", 0, 4), "    x");
+        assert_eq!(expand_tabs("ab\tc", 0, 4), "ab  c");
+        assert_eq!(expand_tabs("\tc", 3, 4), " c");
+        assert_eq!(expand_tabs("no tabs", 0, 8), "no tabs");
     }
 
     #[test]
-    fn test_format_hex_view_small_data() {
-        let data: &[u8] = &[0x48, 0x65, 0x6c, 0x6c, 0x6f]; // "Hello"
-        let output = format_hex_view(data, false);
-        
-        assert!(output.contains("48 65 6C 6C 6F") || output.contains("48 65 6c 6c 6f"));
-        assert!(output.contains("Hello"));
-    }
-
-    #[test]
-    fn test_format_hex_view_truncated_flag() {
-        let data: &[u8] = &[0x00, 0x01, 0x02];
-        let output = format_hex_view(data, true);
-        
-        assert!(output.contains("NOTE: File is larger than 100MB"));
-    }
-
-    #[test]
-    fn test_format_hex_view_non_printable() {
-        let data: &[u8] = &[0x00, 0x01, 0x02, 0x1F]; // Non-printable bytes
-        let output = format_hex_view(data, false);
-        
-        // Non-printable should show as '.'
-        assert!(output.contains("...."));
+    fn test_parse_line_jump() {
+        assert_eq!(parse_line_jump("123"), Some(LineJump::Line(122)));
+        assert_eq!(parse_line_jump(" 1 "), Some(LineJump::Line(0)));
+        assert_eq!(parse_line_jump("0"), Some(LineJump::Line(0)));
+        assert_eq!(parse_line_jump("$"), Some(LineJump::End));
+        assert_eq!(parse_line_jump(""), None);
+        assert_eq!(parse_line_jump("12a"), None);
+        assert_eq!(parse_line_jump("-3"), None);
     }
 
     #[test]
@@ -611,11 +735,74 @@ mod tests {
             ViewerContent::HighlightedCode { raw, highlighted } => {
                 assert!(raw.contains("fn main"));
                 assert!(!highlighted.is_empty());
+                assert!(highlighted.line(0).is_some());
             },
             _ => panic!("Expected HighlightedCode content for Rust"),
         }
     }
 
+    #[test]
+    fn test_load_file_syntax_override() {
+        let temp = tempdir().unwrap();
+        let conf_file = temp.path().join("app.conf");
+        let text = "[server]\nport = 8080\nname = \"demo\"\n";
+        fs::write(&conf_file, text).unwrap();
+
+        let mut overrides = HashMap::new();
+        overrides.insert("conf".to_string(), "toml".to_string());
+
+        let content = load_file_with_overrides(&conf_file, &overrides, highlight::DEFAULT_SYNTAX_THEME, PreviewLimits::default(), None);
+        match content {
+            ViewerContent::HighlightedCode { raw, highlighted } => {
+                assert_eq!(raw, text);
+                // Highlighted exactly as TOML would be
+                let expected = highlight_code(text, "toml", highlight::DEFAULT_SYNTAX_THEME);
+                let colors = |lines: &[HighlightedLine]| -> Vec<Vec<(String, ratatui::style::Color)>> {
+                    lines.iter()
+                        .map(|l| l.segments.iter().map(|s| (s.text.clone(), s.fg)).collect())
+                        .collect()
+                };
+                let lazy: Vec<HighlightedLine> = (0..highlighted.len())
+                    .map(|i| highlighted.line(i).unwrap().clone())
+                    .collect();
+                assert_eq!(colors(&lazy), colors(&expected));
+            },
+            _ => panic!("Expected HighlightedCode content for overridden .conf"),
+        }
+    }
+
+    #[test]
+    fn test_load_file_syntax_override_plain_text() {
+        let temp = tempdir().unwrap();
+        let txt_file = temp.path().join("deploy.txt");
+        fs::write(&txt_file, "key: value\nlist:\n  - a\n").unwrap();
+
+        let mut overrides = HashMap::new();
+        overrides.insert(".TXT".to_string(), "YAML".to_string());
+
+        assert!(matches!(
+            load_file_with_overrides(&txt_file, &overrides, highlight::DEFAULT_SYNTAX_THEME, PreviewLimits::default(), None),
+            ViewerContent::HighlightedCode { .. }
+        ));
+    }
+
+    #[test]
+    fn test_load_file_syntax_override_unknown_language() {
+        let temp = tempdir().unwrap();
+        let txt_file = temp.path().join("notes.txt");
+        fs::write(&txt_file, "hello").unwrap();
+
+        let mut overrides = HashMap::new();
+        overrides.insert("txt".to_string(), "no-such-language".to_string());
+
+        // Falls back to the built-in handling for .txt
+        let content = load_file_with_overrides(&txt_file, &overrides, highlight::DEFAULT_SYNTAX_THEME, PreviewLimits::default(), None);
+        assert_eq!(
+            std::mem::discriminant(&content),
+            std::mem::discriminant(&load_file(&txt_file))
+        );
+    }
+
     #[test]
     fn test_load_file_nonexistent() {
         let path = Path::new("/nonexistent/file/path.txt");
@@ -629,6 +816,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_load_file_preview_limits() {
+        let temp_dir = tempdir().unwrap();
+        let file = temp_dir.path().join("data.unknown");
+        std::fs::write(&file, "plain words\n".repeat(200)).unwrap();
+        let limits = |text_bytes, binary_bytes| PreviewLimits { text_bytes, binary_bytes, ..PreviewLimits::default() };
+
+        assert!(matches!(load_file_with_overrides(&file, &HashMap::new(), highlight::DEFAULT_SYNTAX_THEME, limits(4096, 8192), None), ViewerContent::PlainText(_)));
+        // Above the text limit text opens windowed
+        assert!(matches!(load_file_with_overrides(&file, &HashMap::new(), highlight::DEFAULT_SYNTAX_THEME, limits(1024, 8192), None), ViewerContent::LargeFile(_)));
+        // ...unless a loader of its own reads it whole, keeping the highlighting
+        let code = temp_dir.path().join("big.rs");
+        std::fs::write(&code, "fn main() {}\n".repeat(200)).unwrap();
+        assert!(matches!(load_file_with_overrides(&code, &HashMap::new(), highlight::DEFAULT_SYNTAX_THEME, limits(1024, 8192), None), ViewerContent::HighlightedCode { .. }));
+
+        // Binary data goes to the hex view, and isn't loaded above both limits
+        std::fs::write(&file, [0u8, 1, 2, 3].repeat(600)).unwrap();
+        assert!(matches!(load_file_with_overrides(&file, &HashMap::new(), highlight::DEFAULT_SYNTAX_THEME, limits(1024, 8192), None), ViewerContent::HexView(..)));
+        match load_file_with_overrides(&file, &HashMap::new(), highlight::DEFAULT_SYNTAX_THEME, limits(1024, 2048), None) {
+            ViewerContent::Error(msg) => assert!(msg.contains("2 KB (max_binary_preview_bytes)")),
+            _ => panic!("Expected Error content above both limits"),
+        }
+        assert_eq!(format_limit(5 * 1024 * 1024), "5 MB");
+        assert_eq!(format_limit(1536 * 1024), "1.5 MB");
+    }
+
     #[test]
     fn test_viewer_content_clone() {
         let content = ViewerContent::PlainText("test".to_string());
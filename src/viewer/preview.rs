@@ -0,0 +1,113 @@
+//! Preview column of the file manager
+//!
+//! With `show_preview` on, the file selected in the active pane is shown in a
+//! column right of its listing: the first lines of text, an ASCII thumbnail of
+//! an image, or what kind of file it is. The viewer's loaders are reused with
+//! a low text limit, so larger files are read a window at a time and a preview
+//! never reads more of a text file than the lines it shows.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::{PreviewLimits, ViewerContent};
+
+/// Largest text file a preview loads whole; larger ones are read windowed
+const PREVIEW_TEXT_BYTES: u64 = 256 * 1024;
+
+/// Preview of one file, for a column of a given size
+#[derive(Debug, Clone)]
+pub struct FilePreview {
+    pub path: PathBuf,
+    pub modified: Option<SystemTime>, // Modification time when loaded; a newer one reloads
+    pub size: (u16, u16),             // Column width and height it was loaded for
+    pub lines: Vec<String>,
+    pub is_content: bool,             // Lines of the file itself rather than a description
+}
+
+impl FilePreview {
+    /// Whether this still shows `path` as it is now, at `size`
+    pub fn is_current(&self, path: &Path, size: (u16, u16)) -> bool {
+        self.path == path && self.size == size && self.modified == crate::fs::history::modified_time(path)
+    }
+}
+
+/// Preview `path` for a column of `width` x `height` cells
+pub fn load(path: &Path, overrides: &HashMap<String, String>, width: u16, height: u16) -> FilePreview {
+    let rows = height as usize;
+    let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase());
+    let content_lines = |text: &str| -> Vec<String> { text.lines().take(rows).map(str::to_string).collect() };
+
+    let lines = if matches!(extension.as_deref(), Some("jpg" | "jpeg" | "png" | "gif")) {
+        // Always ASCII: escape-sequence images can't be drawn inside a column
+        let image = super::image::load_image_ascii(path, width.max(1) as u32, height.saturating_sub(2).max(1) as u32);
+        let mut lines = vec![image.metadata(), String::new()];
+        lines.extend(content_lines(&image.content));
+        Some(lines)
+    } else {
+        // No hex view: binary files get the description instead
+        // Only the raw text is shown, so the syntax theme doesn't matter
+        let limits = PreviewLimits { text_bytes: PREVIEW_TEXT_BYTES, binary_bytes: 0, csv_rows: rows.max(1) };
+        match super::load_file_with_overrides(path, overrides, super::highlight::DEFAULT_SYNTAX_THEME, limits, None) {
+            ViewerContent::PlainText(text) | ViewerContent::Markdown(text) => Some(content_lines(&text)),
+            ViewerContent::HighlightedCode { raw, .. } => Some(content_lines(&raw)),
+            ViewerContent::LargeFile(file) => file.read_lines(0, rows).ok(),
+            ViewerContent::Table(table) => Some(content_lines(&table.to_text())),
+            ViewerContent::ArchiveListing(entries, truncated) => {
+                Some(content_lines(&super::format_archive_listing(&entries, truncated)))
+            },
+            _ => None,
+        }
+    };
+
+    FilePreview {
+        path: path.to_path_buf(),
+        modified: crate::fs::history::modified_time(path),
+        size: (width, height),
+        is_content: lines.is_some(),
+        lines: lines.unwrap_or_else(|| describe(path)),
+    }
+}
+
+/// Name, size and modification time of a file without a text preview
+fn describe(path: &Path) -> Vec<String> {
+    let mut lines = vec![
+        path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        String::new(),
+    ];
+    if let Ok(metadata) = std::fs::metadata(path) {
+        lines.push(format!("{} bytes", metadata.len()));
+        if let Ok(modified) = metadata.modified() {
+            let modified: chrono::DateTime<chrono::Local> = modified.into();
+            lines.push(format!("Modified {}", modified.format("%Y-%m-%d %H:%M")));
+        }
+    }
+    lines.push(String::new());
+    lines.push("No preview for this file type".to_string());
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_preview_text_and_binary() {
+        let temp = tempdir().unwrap();
+        let text = temp.path().join("notes.txt");
+        std::fs::write(&text, "one\ntwo\nthree\nfour\n").unwrap();
+        let preview = load(&text, &HashMap::new(), 20, 3);
+        assert!(preview.is_content);
+        assert_eq!(preview.lines, vec!["one", "two", "three"]);
+        assert!(preview.is_current(&text, (20, 3)));
+        assert!(!preview.is_current(&text, (20, 4)));
+
+        let binary = temp.path().join("blob.bin");
+        std::fs::write(&binary, [0u8, 1, 2, 0, 0, 3]).unwrap();
+        let preview = load(&binary, &HashMap::new(), 20, 10);
+        assert!(!preview.is_content);
+        assert_eq!(preview.lines[0], "blob.bin");
+        assert_eq!(preview.lines[2], "6 bytes");
+    }
+}
@@ -0,0 +1,201 @@
+//! Search within the read-only viewer
+//!
+//! Matches are found line by line and addressed by character columns, so the
+//! same positions work for plain text, highlighted code and wrapped rows.
+
+/// A match on one line: `start..end` are character columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchMatch {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// '/' search state of the read-only viewer
+#[derive(Debug, Clone, Default)]
+pub struct ViewerSearch {
+    /// Pattern being typed at the prompt (None once submitted)
+    pub input: Option<String>,
+    pub pattern: String,
+    pub matches: Vec<SearchMatch>,
+    pub current: usize,
+}
+
+impl ViewerSearch {
+    /// Start typing a new pattern, keeping the previous matches visible
+    pub fn prompt(previous: Option<ViewerSearch>) -> Self {
+        let mut search = previous.unwrap_or_default();
+        search.input = Some(String::new());
+        search
+    }
+
+    /// Run `pattern` against `text`, selecting the first match at or below `from_line`
+    pub fn submit(&mut self, pattern: String, text: &str, from_line: usize) {
+        self.matches = find_matches(text, &pattern);
+        self.current = self.matches.iter().position(|m| m.line >= from_line).unwrap_or(0);
+        self.pattern = pattern;
+        self.input = None;
+    }
+
+    pub fn current_match(&self) -> Option<&SearchMatch> {
+        self.matches.get(self.current)
+    }
+
+    /// Move to the next (or previous) match, wrapping around
+    pub fn step(&mut self, forward: bool) {
+        let count = self.matches.len();
+        if count == 0 {
+            return;
+        }
+        self.current = if forward { (self.current + 1) % count } else { (self.current + count - 1) % count };
+    }
+
+    /// Matches on `line` as (start, end, is_current)
+    pub fn ranges_on_line(&self, line: usize) -> Vec<(usize, usize, bool)> {
+        let first = self.matches.partition_point(|m| m.line < line);
+        self.matches[first..].iter()
+            .enumerate()
+            .take_while(|(_, m)| m.line == line)
+            .map(|(i, m)| (m.start, m.end, first + i == self.current))
+            .collect()
+    }
+
+    /// Match markers for a scrollbar track of `height` cells over `total_rows` display rows
+    /// `row_of_line` maps a source line to its first display row (wrapped lines push later lines down).
+    pub fn scrollbar_marks(&self, total_rows: usize, height: usize, row_of_line: impl Fn(usize) -> usize) -> ScrollbarMarks {
+        let mut marks = ScrollbarMarks { counts: vec![0; height], current: None };
+        if height == 0 || total_rows == 0 {
+            return marks;
+        }
+        for (i, m) in self.matches.iter().enumerate() {
+            let cell = (row_of_line(m.line) * height / total_rows).min(height - 1);
+            marks.counts[cell] += 1;
+            if i == self.current {
+                marks.current = Some(cell);
+            }
+        }
+        marks
+    }
+
+    /// Position shown in the footer, e.g. "3/17"
+    pub fn position(&self) -> String {
+        if self.matches.is_empty() {
+            "0/0".to_string()
+        } else {
+            format!("{}/{}", self.current + 1, self.matches.len())
+        }
+    }
+}
+
+/// Matches per scrollbar cell, and the cell holding the current match
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScrollbarMarks {
+    pub counts: Vec<usize>,
+    pub current: Option<usize>,
+}
+
+impl ScrollbarMarks {
+    /// Cells with at least half as many matches as the busiest cell are drawn as dense
+    pub fn is_dense(&self, cell: usize) -> bool {
+        let max = self.counts.iter().copied().max().unwrap_or(0);
+        max > 1 && self.counts[cell] * 2 >= max
+    }
+}
+
+/// Non-overlapping matches of `pattern` in `text`
+/// Case-insensitive unless the pattern contains an uppercase letter (smart case).
+pub fn find_matches(text: &str, pattern: &str) -> Vec<SearchMatch> {
+    if pattern.is_empty() {
+        return Vec::new();
+    }
+    let ignore_case = !pattern.chars().any(char::is_uppercase);
+    // Fold per character so columns stay aligned with the original line
+    let fold = |c: char| if ignore_case { c.to_lowercase().next().unwrap_or(c) } else { c };
+    let needle: Vec<char> = pattern.chars().map(fold).collect();
+
+    let mut matches = Vec::new();
+    for (line_idx, line) in text.lines().enumerate() {
+        let hay: Vec<char> = line.chars().map(fold).collect();
+        let mut col = 0;
+        while col + needle.len() <= hay.len() {
+            if hay[col..col + needle.len()] == needle[..] {
+                matches.push(SearchMatch { line: line_idx, start: col, end: col + needle.len() });
+                col += needle.len();
+            } else {
+                col += 1;
+            }
+        }
+    }
+    matches
+}
+
+/// Rows a line of `chars` characters takes when wrapped at `width`
+fn rows_for(chars: usize, width: usize) -> usize {
+    chars.div_ceil(width).max(1)
+}
+
+/// Total rows of `text` wrapped at `width` characters
+pub fn wrapped_row_count(text: &str, width: usize) -> usize {
+    text.lines().map(|l| rows_for(l.chars().count(), width)).sum()
+}
+
+/// Wrapped row on which `m` starts when `text` is wrapped at `width` characters
+pub fn wrapped_row_of(text: &str, m: &SearchMatch, width: usize) -> usize {
+    let above: usize = text.lines()
+        .take(m.line)
+        .map(|l| rows_for(l.chars().count(), width))
+        .sum();
+    above + m.start / width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrollbar_marks() {
+        let text: String = (0..100).map(|i| if i == 10 || i == 95 || i == 96 { "hit\n" } else { "-\n" }).collect();
+        let mut search = ViewerSearch::default();
+        search.submit("hit".to_string(), &text, 50);
+
+        let marks = search.scrollbar_marks(100, 10, |line| line);
+        assert_eq!(marks.counts.iter().sum::<usize>(), 3);
+        assert_eq!((marks.counts[1], marks.counts[9]), (1, 2));
+        assert_eq!(marks.current, Some(9));
+        assert!(marks.is_dense(9));
+        assert!(marks.is_dense(1)); // Half of the busiest cell
+        // Rows added above by wrapping move the markers down
+        assert_eq!(search.scrollbar_marks(200, 10, |line| line + 100).counts[5], 1);
+    }
+
+    #[test]
+    fn test_find_matches_smart_case() {
+        let text = "Foo bar foo\nnothing\nfoofoo";
+        let matches = find_matches(text, "foo");
+        assert_eq!(matches.len(), 4);
+        assert_eq!(matches[0], SearchMatch { line: 0, start: 0, end: 3 });
+        assert_eq!(matches[3], SearchMatch { line: 2, start: 3, end: 6 });
+        // An uppercase letter makes the search case-sensitive
+        assert_eq!(find_matches(text, "Foo").len(), 1);
+        assert!(find_matches(text, "").is_empty());
+    }
+
+    #[test]
+    fn test_search_navigation_and_wrapped_rows() {
+        let text = "aaaaaaaaaa x\nshort\nx";
+        let mut search = ViewerSearch::prompt(None);
+        search.submit("x".to_string(), text, 1);
+        // Starts at the first match at or below line 1
+        assert_eq!(search.position(), "2/2");
+        search.step(true);
+        assert_eq!(search.position(), "1/2");
+        search.step(false);
+        assert_eq!(search.current_match().map(|m| m.line), Some(2));
+        assert_eq!(search.ranges_on_line(0), vec![(11, 12, false)]);
+
+        // Width 5: line 0 takes 3 rows, line 1 one row
+        assert_eq!(wrapped_row_count(text, 5), 5);
+        assert_eq!(wrapped_row_of(text, &search.matches[0], 5), 2);
+        assert_eq!(wrapped_row_of(text, &search.matches[1], 5), 4);
+    }
+}
@@ -0,0 +1,35 @@
+//! Line, word, character and byte counts of viewed text
+
+/// Counts shown by the viewer's 'I' dialog
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TextStats {
+    pub lines: usize,
+    pub words: usize, // Runs of non-whitespace, like `wc -w`
+    pub chars: usize,
+    pub bytes: usize, // UTF-8 length of the text
+}
+
+impl TextStats {
+    pub fn of(text: &str) -> Self {
+        Self {
+            lines: text.lines().count(),
+            words: text.split_whitespace().count(),
+            chars: text.chars().count(),
+            bytes: text.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_stats() {
+        assert_eq!(TextStats::of(""), TextStats::default());
+        assert_eq!(
+            TextStats::of("Hello, world!\n\n  두 단어\tand tabs\nno newline"),
+            TextStats { lines: 4, words: 8, chars: 41, bytes: 47 }
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! Viewing http(s) URLs
+//!
+//! `:open URL` (in command mode or the viewer's ':' prompt) downloads the
+//! resource on a worker thread into a fresh temporary directory, which the
+//! viewer then opens like any local file; the directory is removed when the
+//! download is dropped. Redirects are followed. The copy is named after
+//! the last path segment of the final URL; when that name doesn't say what the
+//! content is, the response's content type adds an extension so the right
+//! view is picked. Downloads stop at `url_max_bytes`; Esc cancels one.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::fs::transfer::TransferProgress;
+
+/// Minimum interval between progress updates sent to the UI
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(50);
+/// Longest wait for the server to answer or send more of the body
+const TIMEOUT: Duration = Duration::from_secs(30);
+/// Extensions of pages generated on the server, which say nothing about what they return
+const SCRIPT_EXTENSIONS: [&str; 5] = ["php", "asp", "aspx", "jsp", "cgi"];
+
+/// A downloaded resource
+#[derive(Debug)]
+pub struct Fetched {
+    pub path: PathBuf,          // Temporary copy to view
+    pub truncated: bool,        // Stopped at the size limit
+    pub dir: tempfile::TempDir, // Holds the copy; deleted with it when dropped
+}
+
+enum FetchEvent {
+    Progress(TransferProgress),
+    Finished(Result<Fetched, String>),
+}
+
+/// A download running on a background thread
+pub struct UrlFetch {
+    pub url: String,
+    pub progress: TransferProgress,
+    cancel: Arc<AtomicBool>,
+    receiver: Receiver<FetchEvent>,
+}
+
+impl UrlFetch {
+    /// Download up to `max_bytes` of `url` into a new temporary directory
+    pub fn start(url: String, max_bytes: u64) -> Self {
+        let (tx, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let worker_cancel = Arc::clone(&cancel);
+        let worker_url = url.clone();
+
+        std::thread::spawn(move || {
+            let mut last_sent = Instant::now();
+            let result = download(&worker_url, max_bytes, &worker_cancel, &mut |progress| {
+                if last_sent.elapsed() >= PROGRESS_INTERVAL {
+                    last_sent = Instant::now();
+                    let _ = tx.send(FetchEvent::Progress(progress));
+                }
+            });
+            let _ = tx.send(FetchEvent::Finished(result));
+        });
+
+        Self { url, progress: TransferProgress::default(), cancel, receiver }
+    }
+
+    /// Ask the worker to stop; it reports the download as cancelled
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelling(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Drain pending events; returns the result once the worker is done
+    pub fn poll(&mut self) -> Option<Result<Fetched, String>> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(FetchEvent::Progress(progress)) => self.progress = progress,
+                Ok(FetchEvent::Finished(result)) => return Some(result),
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => return Some(Err("download worker stopped unexpectedly".to_string())),
+            }
+        }
+    }
+}
+
+/// The URL of a `:open URL` command, or a URL typed on its own
+pub fn parse_command(input: &str) -> Option<&str> {
+    let input = input.trim();
+    let url = match input.split_once(char::is_whitespace) {
+        Some(("open", url)) => url.trim(),
+        _ => input,
+    };
+    url.contains("://").then_some(url)
+}
+
+/// Why `url` can't be opened, if it can't
+pub fn check_scheme(url: &str) -> Result<(), String> {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(()),
+        Ok(parsed) => Err(format!("{}:// URLs can't be opened, only http(s)", parsed.scheme())),
+        Err(e) => Err(format!("Not a URL: {} ({})", url, e)),
+    }
+}
+
+fn download(
+    url: &str,
+    max_bytes: u64,
+    cancel: &AtomicBool,
+    on_progress: &mut dyn FnMut(TransferProgress),
+) -> Result<Fetched, String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(TIMEOUT)
+        .user_agent(concat!("senterm/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| e.to_string())?;
+    // Redirects are followed; the name comes from where they ended
+    let mut response = client.get(url).send().map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    let content_type = response.headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let name = file_name(response.url(), content_type.as_deref());
+
+    let mut progress = TransferProgress {
+        total_bytes: response.content_length().unwrap_or(0).min(max_bytes),
+        current: name.clone(),
+        ..Default::default()
+    };
+    let mut bytes = Vec::new();
+    let mut buf = vec![0; 64 * 1024];
+    // One byte past the limit tells whether there was more
+    while (bytes.len() as u64) <= max_bytes {
+        if cancel.load(Ordering::Relaxed) {
+            return Err("Download cancelled".to_string());
+        }
+        let n = response.read(&mut buf).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        bytes.extend_from_slice(&buf[..n]);
+        progress.copied_bytes = bytes.len() as u64;
+        on_progress(progress.clone());
+    }
+    let truncated = bytes.len() as u64 > max_bytes;
+    bytes.truncate(max_bytes as usize);
+
+    // A directory of its own keeps repeated downloads of the same name apart
+    let dir = tempfile::Builder::new().prefix("senterm-url-").tempdir().map_err(|e| e.to_string())?;
+    let path = dir.path().join(name);
+    std::fs::write(&path, &bytes).map_err(|e| e.to_string())?;
+    Ok(Fetched { path, truncated, dir })
+}
+
+/// Name for the downloaded copy: the last path segment of `url`, with an
+/// extension from the content type when the name doesn't tell what it is
+pub fn file_name(url: &reqwest::Url, content_type: Option<&str>) -> String {
+    let segment = url.path_segments()
+        .and_then(|mut segments| segments.rfind(|s| !s.is_empty()))
+        .unwrap_or("index");
+    let name: String = segment.chars()
+        .map(|c| if c.is_alphanumeric() || "._-".contains(c) { c } else { '_' })
+        .collect();
+
+    let extension = Path::new(&name).extension().map(|e| e.to_string_lossy().to_lowercase());
+    let uninformative = extension.as_deref().is_none_or(|e| SCRIPT_EXTENSIONS.contains(&e));
+    match content_type.and_then(extension_for) {
+        Some(hint) if uninformative => format!("{}.{}", name, hint),
+        _ => name,
+    }
+}
+
+/// Viewer extension for a content type ("application/json; charset=utf-8" -> "json")
+fn extension_for(content_type: &str) -> Option<&'static str> {
+    let mime = content_type.split(';').next()?.trim().to_lowercase();
+    Some(match mime.as_str() {
+        "application/json" => "json",
+        m if m.ends_with("+json") => "json",
+        "text/markdown" | "text/x-markdown" => "md",
+        "text/html" => "html",
+        "text/csv" => "csv",
+        "text/tab-separated-values" => "tsv",
+        "application/xml" | "text/xml" => "xml",
+        m if m.ends_with("+xml") => "xml",
+        "application/yaml" | "application/x-yaml" | "text/yaml" => "yaml",
+        "application/toml" => "toml",
+        "application/javascript" | "text/javascript" => "js",
+        "text/css" => "css",
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        m if m.starts_with("text/") => "txt",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_name_from_url_and_content_type() {
+        let url = |s: &str| reqwest::Url::parse(s).unwrap();
+        assert_eq!(file_name(&url("https://example.com/docs/README.md"), Some("text/plain")), "README.md");
+        assert_eq!(file_name(&url("https://api.example.com/v1/users?page=2"), Some("application/json; charset=utf-8")), "users.json");
+        assert_eq!(file_name(&url("https://example.com/feed.php"), Some("application/rss+xml")), "feed.php.xml");
+        assert_eq!(file_name(&url("https://example.com/"), Some("text/html")), "index.html");
+        assert_eq!(file_name(&url("https://example.com/a%20b"), None), "a_20b");
+
+        assert_eq!(parse_command("open https://example.com/x.json"), Some("https://example.com/x.json"));
+        assert_eq!(parse_command("http://example.com"), Some("http://example.com"));
+        assert_eq!(parse_command("42"), None);
+        assert!(check_scheme("https://example.com").is_ok());
+        assert!(check_scheme("ftp://example.com/file").is_err());
+    }
+}